@@ -3,8 +3,8 @@ use super::accessibility::{
     handle_modal_tab_navigation,
 };
 use super::components::{
-    frame_checkbox_row_with_focus, frame_text_button, frame_text_button_with_focus,
-    frame_vertical_scrollbar,
+    frame_checkbox_row_with_focus, frame_choice_button, frame_text_button,
+    frame_text_button_with_focus, frame_vertical_scrollbar,
 };
 use super::input::{FrameTextInputSpec, frame_text_input};
 use super::primitives::{
@@ -16,17 +16,18 @@ use super::settings_panel::{settings_hint_text, settings_section};
 use super::{
     ActiveView, ClickEvent, Context, ExternalPaths, FILE_LIST_ACTION_ICON_SIZE, FRAME_APP_VERSION,
     FluentBuilder, FocusHandle, FrameAppState, FrameRoot, FrameTextInputKind, InteractiveElement,
-    IntoElement, LEFT_COLUMN_SPAN, PANEL_HEADER_HEIGHT, ParentElement, RIGHT_COLUMN_SPAN,
-    SETTINGS_CONTROL_HEIGHT, SURFACE_MOTION_DURATION, ScrollHandle, StatefulInteractiveElement,
-    Styled, TITLEBAR_ACTION_ICON_SIZE, TITLEBAR_DIVIDER_HEIGHT, TITLEBAR_HEIGHT,
-    TITLEBAR_ICON_SIZE, TITLEBAR_LINUX_WINDOW_BUTTON_SIZE, TITLEBAR_LINUX_WINDOW_CONTROLS_GAP,
+    IntoElement, LEFT_COLUMN_SPAN, NotificationMode, OverwritePolicy, PANEL_HEADER_HEIGHT,
+    ParentElement, RIGHT_COLUMN_SPAN, SETTINGS_CONTROL_HEIGHT, SURFACE_MOTION_DURATION,
+    ScrollHandle, StatefulInteractiveElement, Styled, TITLEBAR_ACTION_ICON_SIZE,
+    TITLEBAR_DIVIDER_HEIGHT, TITLEBAR_HEIGHT, TITLEBAR_ICON_SIZE,
+    TITLEBAR_LINUX_WINDOW_BUTTON_SIZE, TITLEBAR_LINUX_WINDOW_CONTROLS_GAP,
     TITLEBAR_LINUX_WINDOW_CONTROLS_PADDING_X, TITLEBAR_LOGO_SIZE,
     TITLEBAR_MACOS_NATIVE_TRAFFIC_LIGHT_PLACEHOLDER_WIDTH, TITLEBAR_NAV_BUTTON_HEIGHT,
     TITLEBAR_PLATFORM_DIVIDER_HEIGHT, TITLEBAR_SEGMENT_HEIGHT, TITLEBAR_TOP_PADDING,
     TITLEBAR_TRAFFIC_LIGHT_SIZE, TITLEBAR_WINDOWS_WINDOW_BUTTON_WIDTH,
     TITLEBAR_WINDOWS_WINDOW_ICON_SIZE, TITLEBAR_WINDOWS_WINDOW_MAX_ICON_SIZE,
     UPDATE_INSTALL_WAIT_MESSAGE, UpdateInfo, UpdateStatus, WORKSPACE_COLUMNS, WORKSPACE_GAP,
-    Window, WindowControlArea, assets, div, ease_in_out, format_total_size, mix_color,
+    WatchFolder, Window, WindowControlArea, assets, div, ease_in_out, format_total_size, mix_color,
     motion_is_hidden, motion_target, px, relative, set_motion_target, settings_sheet_right_inset,
     svg, theme,
 };
@@ -254,9 +255,9 @@ pub(super) fn titlebar_add_source_button(
         window,
         cx,
     )
-    .on_click(cx.listener(|_root, _: &ClickEvent, window, cx| {
+    .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
         cx.stop_propagation();
-        FrameRoot::prompt_add_source(window, cx);
+        root.prompt_add_source(window, cx);
     }))
 }
 
@@ -299,11 +300,27 @@ pub(super) struct AppSettingsSheetProps<'a> {
     pub(super) error: Option<&'a str>,
     pub(super) default_output_directory: Option<&'a str>,
     pub(super) output_directory_error: Option<&'a str>,
+    pub(super) overwrite_policy: OverwritePolicy,
+    pub(super) overwrite_policy_error: Option<&'a str>,
+    pub(super) scratch_directory: Option<&'a str>,
+    pub(super) scratch_directory_error: Option<&'a str>,
+    pub(super) watch_folders: &'a [WatchFolder],
+    pub(super) watch_folders_error: Option<&'a str>,
+    pub(super) has_recent_directories: bool,
+    pub(super) recent_directories_error: Option<&'a str>,
+    pub(super) ffmpeg_version: Option<&'a str>,
+    pub(super) ffmpeg_path: Option<&'a str>,
+    pub(super) media_tools_error: Option<&'a str>,
+    pub(super) media_tools_validating: bool,
+    pub(super) capabilities_refreshing: bool,
+    pub(super) notification_mode: NotificationMode,
+    pub(super) notification_mode_error: Option<&'a str>,
     pub(super) auto_update_check: bool,
     pub(super) update_status: &'a UpdateStatus,
     pub(super) update_install_ready: bool,
     pub(super) value_focus: &'a FocusHandle,
     pub(super) output_directory_focus: &'a FocusHandle,
+    pub(super) scratch_directory_focus: &'a FocusHandle,
     pub(super) auto_update_focus: &'a FocusHandle,
     pub(super) check_now_focus: &'a FocusHandle,
     pub(super) download_focus: &'a FocusHandle,
@@ -458,6 +475,45 @@ pub(super) fn app_settings_sheet(
                                     window,
                                     cx,
                                 ))
+                                .child(app_settings_overwrite_policy_section(
+                                    props.overwrite_policy,
+                                    props.overwrite_policy_error,
+                                    window,
+                                    cx,
+                                ))
+                                .child(app_settings_scratch_directory_section(
+                                    props.scratch_directory,
+                                    props.scratch_directory_error,
+                                    props.scratch_directory_focus,
+                                    window,
+                                    cx,
+                                ))
+                                .child(app_settings_watch_folders_section(
+                                    props.watch_folders,
+                                    props.watch_folders_error,
+                                    window,
+                                    cx,
+                                ))
+                                .child(app_settings_recent_directories_section(
+                                    props.has_recent_directories,
+                                    props.recent_directories_error,
+                                    window,
+                                    cx,
+                                ))
+                                .child(app_settings_ffmpeg_section(
+                                    props.ffmpeg_path,
+                                    props.media_tools_error,
+                                    props.media_tools_validating,
+                                    props.capabilities_refreshing,
+                                    window,
+                                    cx,
+                                ))
+                                .child(app_settings_notifications_section(
+                                    props.notification_mode,
+                                    props.notification_mode_error,
+                                    window,
+                                    cx,
+                                ))
                                 .child(
                                     settings_section("Max concurrency")
                                         .child(app_settings_concurrency_control(
@@ -497,12 +553,16 @@ pub(super) fn app_settings_sheet(
                                     cx,
                                 )),
                         )
-                        .child(app_settings_version_label()),
+                        .child(app_settings_version_label(props.ffmpeg_version)),
                 ),
         )
 }
 
-fn app_settings_version_label() -> gpui::Div {
+fn app_settings_version_label(ffmpeg_version: Option<&str>) -> gpui::Div {
+    let label = ffmpeg_version.map_or_else(
+        || format!("Frame v{FRAME_APP_VERSION}"),
+        |ffmpeg_version| format!("Frame v{FRAME_APP_VERSION} · FFmpeg {ffmpeg_version}"),
+    );
     div()
         .w_full()
         .flex()
@@ -510,7 +570,7 @@ fn app_settings_version_label() -> gpui::Div {
         .pt_4()
         .text_size(px(11.0))
         .text_color(color(theme::FRAME_GRAY_400))
-        .child(theme::ui_text_owned(format!("Frame v{FRAME_APP_VERSION}")))
+        .child(theme::ui_text_owned(label))
 }
 
 fn app_settings_output_directory_section(
@@ -542,9 +602,9 @@ fn app_settings_output_directory_section(
                 cx,
             )
             .w_full()
-            .on_click(cx.listener(|_root, _: &ClickEvent, window, cx| {
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
                 cx.stop_propagation();
-                FrameRoot::prompt_default_output_folder(window, cx);
+                root.prompt_default_output_folder(window, cx);
             })),
         )
         .child(
@@ -569,6 +629,430 @@ fn app_settings_output_directory_section(
     section
 }
 
+fn app_settings_scratch_directory_section(
+    scratch_directory: Option<&str>,
+    error: Option<&str>,
+    focus: &FocusHandle,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let selected_path = scratch_directory
+        .unwrap_or("System temporary folder")
+        .to_string();
+    let button_label = if scratch_directory.is_some() {
+        "Change scratch folder"
+    } else {
+        "Choose scratch folder"
+    };
+
+    let mut section = settings_section("Scratch folder")
+        .child(
+            frame_text_button_with_focus(
+                "app-settings-scratch-directory",
+                button_label,
+                ButtonVariant::Secondary,
+                false,
+                true,
+                focus,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
+                cx.stop_propagation();
+                root.prompt_scratch_folder(window, cx);
+            })),
+        )
+        .child(
+            div()
+                .id("app-settings-scratch-directory-path")
+                .overflow_hidden()
+                .text_color(color(theme::FRAME_GRAY_600))
+                .child(selected_path),
+        )
+        .child(settings_hint_text(
+            "Large intermediate files are written here instead of the system temporary folder.",
+        ));
+
+    if scratch_directory.is_some() {
+        section = section.child(
+            frame_text_button(
+                "app-settings-scratch-directory-reset",
+                "Use system temporary folder",
+                ButtonVariant::Secondary,
+                false,
+                true,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                root.settings_ui.scratch_directory_error = root
+                    .set_scratch_directory(None)
+                    .err()
+                    .map(|error| format!("Failed to save settings: {error}"));
+                cx.notify();
+            })),
+        );
+    }
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-scratch-directory-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+}
+
+fn app_settings_watch_folders_section(
+    watch_folders: &[WatchFolder],
+    error: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut section = settings_section("Watch folders");
+    for (index, folder) in watch_folders.iter().enumerate() {
+        let path = folder.path.clone();
+        section = section.child(
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(
+                    div()
+                        .id(format!("app-settings-watch-folder-{index}"))
+                        .flex_1()
+                        .min_w_0()
+                        .truncate()
+                        .text_color(color(theme::FRAME_GRAY_600))
+                        .child(folder.path.to_string_lossy().into_owned()),
+                )
+                .child(
+                    frame_text_button(
+                        format!("app-settings-watch-folder-remove-{index}"),
+                        "Remove",
+                        ButtonVariant::Secondary,
+                        false,
+                        true,
+                        window,
+                        cx,
+                    )
+                    .on_click(cx.listener(
+                        move |root, _: &ClickEvent, _window, cx| {
+                            cx.stop_propagation();
+                            root.settings_ui.watch_folders_error = root
+                                .remove_watch_folder(&path)
+                                .err()
+                                .map(|error| format!("Failed to save settings: {error}"));
+                            cx.notify();
+                        },
+                    )),
+                ),
+        );
+    }
+
+    section = section
+        .child(
+            frame_text_button(
+                "app-settings-watch-folder-add",
+                "Add watch folder",
+                ButtonVariant::Secondary,
+                false,
+                true,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
+                cx.stop_propagation();
+                root.prompt_add_watch_folder(window, cx);
+            })),
+        )
+        .child(settings_hint_text(
+            "New media in these folders is converted with the selected file's settings and saved to the default output folder.",
+        ));
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-watch-folders-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+}
+
+fn app_settings_recent_directories_section(
+    has_recent_directories: bool,
+    error: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut section = settings_section("Recent folders")
+        .child(
+            frame_text_button(
+                "app-settings-recent-directories-forget",
+                "Forget recent folders",
+                ButtonVariant::Secondary,
+                false,
+                has_recent_directories,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                root.settings_ui.recent_directories_error = root
+                    .forget_dialog_directories()
+                    .err()
+                    .map(|error| format!("Failed to save settings: {error}"));
+                cx.notify();
+            })),
+        )
+        .child(settings_hint_text(
+            "File dialogs reopen in the folder last used for each kind of file.",
+        ));
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-recent-directories-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+}
+
+fn app_settings_ffmpeg_section(
+    ffmpeg_path: Option<&str>,
+    error: Option<&str>,
+    validating: bool,
+    refreshing: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let selected_path = ffmpeg_path.unwrap_or("Bundled FFmpeg").to_string();
+    let choose_label = if validating {
+        "Checking FFmpeg..."
+    } else if ffmpeg_path.is_some() {
+        "Change FFmpeg"
+    } else {
+        "Use another FFmpeg"
+    };
+
+    let mut section = settings_section("FFmpeg")
+        .child(
+            frame_text_button(
+                "app-settings-ffmpeg-path",
+                choose_label,
+                ButtonVariant::Secondary,
+                false,
+                !validating,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
+                cx.stop_propagation();
+                root.prompt_ffmpeg_binary(window, cx);
+            })),
+        )
+        .child(
+            div()
+                .id("app-settings-ffmpeg-path-label")
+                .overflow_hidden()
+                .text_color(color(theme::FRAME_GRAY_600))
+                .child(selected_path),
+        )
+        .child(settings_hint_text(
+            "FFprobe is expected in the same folder. System builds may offer encoders the bundled one lacks.",
+        ));
+
+    if ffmpeg_path.is_some() {
+        section = section.child(
+            frame_text_button(
+                "app-settings-ffmpeg-path-reset",
+                "Use bundled FFmpeg",
+                ButtonVariant::Secondary,
+                false,
+                true,
+                window,
+                cx,
+            )
+            .w_full()
+            .on_click(cx.listener(|root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                root.settings_ui.media_tools_error = root
+                    .switch_media_tools(None, cx)
+                    .err()
+                    .map(|error| format!("Failed to save settings: {error}"));
+                cx.notify();
+            })),
+        );
+    }
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-ffmpeg-path-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+        .child(app_settings_ffmpeg_refresh_button(refreshing, window, cx))
+        .child(settings_hint_text(
+            "Run again after installing GPU drivers or replacing FFmpeg.",
+        ))
+}
+
+fn app_settings_ffmpeg_refresh_button(
+    refreshing: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Stateful<gpui::Div> {
+    frame_text_button(
+        "app-settings-ffmpeg-refresh",
+        if refreshing {
+            "Detecting..."
+        } else {
+            "Detect capabilities again"
+        },
+        ButtonVariant::Secondary,
+        false,
+        !refreshing,
+        window,
+        cx,
+    )
+    .w_full()
+    .on_click(cx.listener(|root, _: &ClickEvent, _window, cx| {
+        cx.stop_propagation();
+        root.refresh_runtime_capabilities(cx);
+        cx.notify();
+    }))
+}
+
+/// Overwrite policies in the order they are offered, with their labels.
+const OVERWRITE_POLICY_CHOICES: [(OverwritePolicy, &str); 3] = [
+    (OverwritePolicy::Rename, "Keep both"),
+    (OverwritePolicy::Overwrite, "Overwrite"),
+    (OverwritePolicy::Fail, "Ask"),
+];
+
+fn app_settings_overwrite_policy_section(
+    selected_policy: OverwritePolicy,
+    error: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut grid = div().grid().grid_cols(3).gap_2();
+    for (policy, label) in OVERWRITE_POLICY_CHOICES {
+        grid = grid.child(
+            frame_choice_button(
+                format!("app-settings-overwrite-policy-{policy:?}"),
+                label,
+                policy == selected_policy,
+                true,
+                window,
+                cx,
+            )
+            .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                root.settings_ui.overwrite_policy_error = root
+                    .set_overwrite_policy(policy)
+                    .err()
+                    .map(|error| format!("Failed to save settings: {error}"));
+                cx.notify();
+            })),
+        );
+    }
+
+    let mut section = settings_section("Existing outputs")
+        .child(grid)
+        .child(settings_hint_text(
+            "Keep both adds a number to the new file's name. Ask stops the conversion and asks what to do.",
+        ));
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-overwrite-policy-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+}
+
+fn app_settings_notifications_section(
+    selected_mode: NotificationMode,
+    error: Option<&str>,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut grid = div().grid().grid_cols(2).gap_2();
+    for mode in NotificationMode::ALL {
+        grid = grid.child(
+            frame_choice_button(
+                format!("app-settings-notification-mode-{mode:?}"),
+                mode.label(),
+                mode == selected_mode,
+                true,
+                window,
+                cx,
+            )
+            .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                root.settings_ui.notification_mode_error = root
+                    .set_notification_mode(mode)
+                    .err()
+                    .map(|error| format!("Failed to save settings: {error}"));
+                cx.notify();
+            })),
+        );
+    }
+
+    let mut section = settings_section("Notifications")
+        .child(grid)
+        .child(settings_hint_text(
+            "Cancelled conversions are never announced.",
+        ));
+
+    if let Some(error) = error {
+        section = section.child(
+            div()
+                .id("app-settings-notification-mode-error")
+                .role(gpui::Role::Alert)
+                .aria_label(error.to_string())
+                .text_color(color(theme::FRAME_RED))
+                .child(error.to_string()),
+        );
+    }
+
+    section
+}
+
 #[derive(Clone, Copy)]
 struct AppSettingsUpdateFocuses<'a> {
     auto_update: &'a FocusHandle,
@@ -1586,10 +2070,10 @@ pub(super) fn drag_drop_overlay(
         .backdrop_blur(px(4.0 * progress))
         .opacity(progress)
         .occlude()
-        .on_drop(cx.listener(|root, paths: &ExternalPaths, _window, cx| {
+        .on_drop(cx.listener(|root, paths: &ExternalPaths, window, cx| {
             cx.stop_propagation();
             root.close_drag_drop_overlay();
-            FrameRoot::import_source_paths(paths.paths().to_vec(), cx);
+            FrameRoot::import_dropped_paths(paths.paths().to_vec(), window, cx);
             cx.notify();
         }))
         .child(
@@ -1689,9 +2173,11 @@ pub(super) fn windows_window_controls(
                 cx,
             )
             .window_control_area(WindowControlArea::Close)
-            .on_click(cx.listener(|_, _: &ClickEvent, window, cx| {
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
                 cx.stop_propagation();
-                window.remove_window();
+                if root.window_should_close(window, cx) {
+                    window.remove_window();
+                }
             })),
         )
 }
@@ -1764,9 +2250,11 @@ pub(super) fn linux_window_controls(window: &mut Window, cx: &mut Context<FrameR
                 cx,
             )
             .window_control_area(WindowControlArea::Close)
-            .on_click(cx.listener(|_, _: &ClickEvent, window, cx| {
+            .on_click(cx.listener(|root, _: &ClickEvent, window, cx| {
                 cx.stop_propagation();
-                window.remove_window();
+                if root.window_should_close(window, cx) {
+                    window.remove_window();
+                }
             })),
         )
 }
@@ -16,11 +16,12 @@ impl FrameRoot {
         };
         self.normalize_selected_actionable_conversion_configs();
 
+        let settings = self.app_settings();
         let mut tasks = self
             .file_queue
             .queue_selected_pending_conversions()
             .iter()
-            .map(|file| conversion_task_from_file(file, &output_directory))
+            .map(|file| self.conversion_task_for_file(file, &output_directory, &settings))
             .collect::<Vec<_>>();
         disambiguate_output_paths(&mut tasks);
 
@@ -43,6 +44,31 @@ impl FrameRoot {
 
         tasks
     }
+    /// Task converting `file` with the output settings in `settings`, a
+    /// snapshot from [`Self::app_settings`].
+    pub(super) fn conversion_task_for_file(
+        &self,
+        file: &FileItem,
+        output_directory: &str,
+        settings: &AppSettings,
+    ) -> frame_core::types::ConversionTask {
+        let mut task = conversion_task_with_settings(file, output_directory, settings);
+        task.preprobed = self.source_metadata.preprobed_for(&file.id).cloned();
+        self.apply_hardware_capabilities(&mut task);
+        task
+    }
+    /// Decodes in software when the runtime lacks the hwaccel a task asks
+    /// for, instead of letting `FFmpeg` fail mid-task.
+    pub(super) fn apply_hardware_capabilities(&self, task: &mut frame_core::types::ConversionTask) {
+        if let Some(capabilities) = &self.hardware_capabilities
+            && downgrade_unsupported_hw_decode(&mut task.config, capabilities)
+        {
+            eprintln!(
+                "Hardware decoding is unavailable for {}; decoding {} in software",
+                task.config.video_codec, task.file_path
+            );
+        }
+    }
     pub(super) fn start_selected_conversions(&mut self, cx: &mut Context<Self>) {
         if self.is_processing || self.update_installation_in_progress() {
             return;
@@ -55,6 +81,7 @@ impl FrameRoot {
         }
 
         self.active_conversion_task_ids = tasks.iter().map(|task| task.id.clone()).collect();
+        self.conversion_batch_started_at = Some(Instant::now());
         self.is_processing = true;
         self.spawn_conversion_batch(tasks, cx);
         cx.notify();
@@ -64,12 +91,15 @@ impl FrameRoot {
         tasks: Vec<frame_core::types::ConversionTask>,
         cx: &Context<Self>,
     ) {
-        let (tx, rx) = mpsc::channel();
+        // Bounded so a task flooding stderr cannot outgrow what the UI
+        // drains; lifecycle events wait for room instead of being dropped.
+        let (sender, receiver) = conversion_event_queue(EVENT_QUEUE_CAPACITY);
         let controller = self.conversion_processes.clone();
+        let task_ids = tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
 
         cx.background_spawn(async move {
             let result = run_conversion_batch_with_control(tasks, &controller, |event| {
-                let _ = tx.send(event);
+                sender.send(event);
             });
             if let Err(error) = result {
                 eprintln!("Conversion batch failed: {error}");
@@ -79,31 +109,36 @@ impl FrameRoot {
 
         cx.spawn(async move |this, cx| {
             loop {
-                let mut is_disconnected = false;
-                loop {
-                    match rx.try_recv() {
-                        Ok(event) => {
-                            if this
-                                .update(cx, |root, cx| {
-                                    root.apply_conversion_event(event);
-                                    cx.notify();
-                                })
-                                .is_err()
-                            {
-                                return;
+                // Read before draining, so nothing sent after the drain is
+                // missed once the sender is gone.
+                let is_disconnected = receiver.is_disconnected();
+                let events = receiver.drain();
+                let stats = receiver.stats();
+                if !events.is_empty()
+                    && this
+                        .update(cx, |root, cx| {
+                            for event in events {
+                                root.apply_conversion_event(event);
                             }
-                        }
-                        Err(TryRecvError::Empty) => break,
-                        Err(TryRecvError::Disconnected) => {
-                            is_disconnected = true;
-                            break;
-                        }
-                    }
+                            root.conversion_events.record_event_queue_stats(stats);
+                            cx.notify();
+                        })
+                        .is_err()
+                {
+                    return;
                 }
 
                 if is_disconnected {
                     this.update(cx, |root, cx| {
+                        root.conversion_events.record_event_queue_stats(stats);
+                        if let Some(notice) = root.conversion_events.event_queue_notice() {
+                            eprintln!("{notice}");
+                        }
+                        if stats.dropped_log_lines > 0 {
+                            root.restore_task_logs(&task_ids);
+                        }
                         root.refresh_processing_state_from_queue();
+                        root.start_pending_conversion_tasks(cx);
                         cx.notify();
                     })
                     .ok();
@@ -117,6 +152,58 @@ impl FrameRoot {
         })
         .detach();
     }
+    /// Shows the logs the runner kept for the finished tasks `ids`, in place
+    /// of streamed logs that lost lines to a full event queue.
+    pub(super) fn restore_task_logs(&mut self, ids: &[String]) {
+        for id in ids {
+            if let Ok(Some(lines)) = self.conversion_processes.task_log(id) {
+                self.conversion_events
+                    .restore_log(&self.file_queue, id, lines);
+            }
+        }
+    }
+    /// Starts the tasks held back while a batch ran, as one batch, so they
+    /// share its concurrency limit instead of adding to it.
+    pub(super) fn start_pending_conversion_tasks(&mut self, cx: &Context<Self>) {
+        let Some(mut tasks) = self.take_startable_conversion_tasks() else {
+            return;
+        };
+
+        disambiguate_output_paths(&mut tasks);
+        self.spawn_conversion_batch(tasks, cx);
+    }
+
+    /// Pending tasks, marked as the running batch, when no other batch is
+    /// running.
+    pub(super) fn take_startable_conversion_tasks(
+        &mut self,
+    ) -> Option<Vec<frame_core::types::ConversionTask>> {
+        if self.pending_conversion_tasks.is_empty()
+            || self.conversion_batch_running()
+            || self.update_installation_in_progress()
+        {
+            return None;
+        }
+
+        let tasks = std::mem::take(&mut self.pending_conversion_tasks);
+        self.active_conversion_task_ids = tasks.iter().map(|task| task.id.clone()).collect();
+        self.conversion_batch_started_at = Some(Instant::now());
+        self.is_processing = true;
+        Some(tasks)
+    }
+
+    /// Drops a held back task that has not started yet, returning whether
+    /// `id` was one.
+    pub(super) fn cancel_pending_conversion_task(&mut self, id: &str) -> bool {
+        let count = self.pending_conversion_tasks.len();
+        self.pending_conversion_tasks.retain(|task| task.id != id);
+        if self.pending_conversion_tasks.len() == count {
+            return false;
+        }
+
+        self.apply_conversion_event(ConversionEvent::cancelled(id));
+        true
+    }
     pub(super) fn pause_conversion_task(&mut self, id: &str) -> bool {
         if !self
             .file_queue
@@ -152,6 +239,9 @@ impl FrameRoot {
         }
     }
     pub(super) fn cancel_conversion_task(&mut self, id: &str) -> bool {
+        if self.cancel_pending_conversion_task(id) {
+            return true;
+        }
         if !self
             .file_queue
             .file_by_id(id)
@@ -168,6 +258,19 @@ impl FrameRoot {
             }
         }
     }
+    /// Stops all work before Frame closes: held back tasks are dropped, and
+    /// the tasks of the running batch and every analysis are cancelled.
+    pub(super) fn cancel_all_conversions(&mut self) {
+        self.pending_conversion_tasks.clear();
+        for id in &self.active_conversion_task_ids {
+            if let Err(error) = self.conversion_processes.cancel_task(id) {
+                eprintln!("Failed to cancel {id}: {error}");
+            }
+        }
+        if let Err(error) = self.conversion_processes.cancel_active_tasks() {
+            eprintln!("Failed to cancel running processes: {error}");
+        }
+    }
     pub(super) fn prepare_file_for_reconversion(&mut self, id: &str) -> bool {
         if self.update_installation_in_progress() {
             return false;
@@ -213,29 +316,131 @@ impl FrameRoot {
         );
     }
     pub(super) fn apply_conversion_event(&mut self, event: ConversionEvent) {
+        self.track_conversion_output(&event);
+        self.record_output_conflict(&event);
+        let task_notification = self.task_notification_for_event(&event);
         self.conversion_events
             .apply_conversion_event(&mut self.file_queue, event);
+        if let Some(notification) = task_notification {
+            self.notifier.notify_task_finished(notification);
+        }
         self.refresh_processing_state_from_queue();
     }
 
+    /// Notification for a task that `event` completes or fails, when the
+    /// notification mode announces it. Tasks the user is cancelling are
+    /// never announced.
+    fn task_notification_for_event(&mut self, event: &ConversionEvent) -> Option<TaskNotification> {
+        let (id, outcome) = match event {
+            ConversionEvent::Started(payload) => {
+                self.conversion_task_started_at
+                    .insert(payload.id.clone(), Instant::now());
+                return None;
+            }
+            ConversionEvent::Completed(payload) => (
+                &payload.id,
+                TaskNotificationOutcome::Completed {
+                    output_path: PathBuf::from(&payload.output_path),
+                    output_size_bytes: payload.summary.output_size_bytes,
+                },
+            ),
+            ConversionEvent::Error(payload) => (
+                &payload.id,
+                TaskNotificationOutcome::Failed {
+                    reason: payload.code.summary().to_string(),
+                },
+            ),
+            ConversionEvent::Cancelled(payload) => {
+                self.conversion_task_started_at.remove(&payload.id);
+                return None;
+            }
+            ConversionEvent::Progress(_)
+            | ConversionEvent::Log(_)
+            | ConversionEvent::LogBatch(_)
+            | ConversionEvent::Warning(_) => return None,
+        };
+        let started_at = self.conversion_task_started_at.remove(id);
+        let failed = matches!(outcome, TaskNotificationOutcome::Failed { .. });
+        let file = self.file_queue.file_by_id(id)?;
+        if !self.notification_mode.notifies_task(failed) || file.status == FileStatus::Cancelling {
+            return None;
+        }
+
+        Some(TaskNotification {
+            file_name: file.name.clone(),
+            elapsed: started_at.map(|started_at| started_at.elapsed()),
+            outcome,
+        })
+    }
+
+    /// Chooses which finished conversions are announced and saves it.
+    pub(super) fn set_notification_mode(
+        &mut self,
+        mode: NotificationMode,
+    ) -> Result<(), crate::app_persistence::AppPersistenceError> {
+        let previous = std::mem::replace(&mut self.notification_mode, mode);
+        if let Err(error) = self.persist_app_settings() {
+            self.notification_mode = previous;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Chooses what happens when an output already exists and saves it.
+    pub(super) fn set_overwrite_policy(
+        &mut self,
+        policy: OverwritePolicy,
+    ) -> Result<(), crate::app_persistence::AppPersistenceError> {
+        let previous = std::mem::replace(&mut self.overwrite_policy, policy);
+        if let Err(error) = self.persist_app_settings() {
+            self.overwrite_policy = previous;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
     fn refresh_processing_state_from_queue(&mut self) {
         let was_processing = self.is_processing;
-        self.is_processing = !all_conversions_settled(&self.file_queue);
+        self.is_processing = self.conversion_batch_running();
 
         if was_processing && !self.is_processing {
             self.notify_active_conversion_batch_finished();
         }
     }
 
+    /// Whether a file is converting or waiting in the running batch. Tasks
+    /// held back for the next batch do not count.
+    pub(super) fn conversion_batch_running(&self) -> bool {
+        self.file_queue.files().iter().any(|file| {
+            !file.status.is_settled()
+                && !self
+                    .pending_conversion_tasks
+                    .iter()
+                    .any(|task| task.id == file.id)
+        })
+    }
+
     fn notify_active_conversion_batch_finished(&mut self) {
         let summary = conversion_finished_notification_for_task_ids(
             &self.file_queue,
             &self.active_conversion_task_ids,
         );
         self.active_conversion_task_ids.clear();
+        let elapsed = self
+            .conversion_batch_started_at
+            .take()
+            .map(|started_at| started_at.elapsed());
+        self.conversion_task_started_at.clear();
 
-        if let Some(summary) = summary {
-            self.notifier.notify_conversion_finished(summary);
+        if let Some(summary) = summary
+            && self.notification_mode.notifies_queue()
+        {
+            self.notifier.notify_conversion_finished(match elapsed {
+                Some(elapsed) => summary.with_elapsed(elapsed),
+                None => summary,
+            });
         }
     }
 
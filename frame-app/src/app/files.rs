@@ -117,32 +117,59 @@ impl FrameRoot {
         true
     }
 
-    pub(super) fn prompt_add_source(window: &Window, cx: &Context<Self>) {
-        let dialog = source_file_dialog(window);
+    pub(super) fn prompt_add_source(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = source_file_dialog(
+            window,
+            NativeDialogFilterSet::AnyMedia,
+            self.recent_dialog_directory(DialogPurpose::OpenInput)
+                .as_deref(),
+        );
         cx.spawn(async move |this, cx| {
             let paths = pick_source_files(dialog).await;
+            drop(claim);
             let Some(paths) = paths else {
                 return;
             };
-            if paths.is_empty() {
+            let Some(first) = paths.first() else {
                 return;
-            }
+            };
+            let first = first.clone();
 
-            this.update(cx, |_root, cx| Self::import_source_paths(paths, cx))
-                .ok();
+            this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::OpenInput, &first);
+                Self::import_picked_source_paths(paths, cx);
+            })
+            .ok();
         })
         .detach();
     }
 
-    pub(super) fn prompt_add_source_folder(window: &Window, cx: &Context<Self>) {
-        let dialog = source_folder_dialog(window);
+    pub(super) fn prompt_add_source_folder(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = source_folder_dialog(
+            window,
+            self.recent_dialog_directory(DialogPurpose::OpenInput)
+                .as_deref(),
+        );
         cx.spawn(async move |this, cx| {
-            let Some(path) = pick_source_folder(dialog).await else {
+            let paths = pick_source_folders(dialog).await;
+            drop(claim);
+            let Some(paths) = paths else {
                 return;
             };
+            let Some(first) = paths.first() else {
+                return;
+            };
+            let first = first.clone();
 
-            this.update(cx, |_root, cx| {
-                Self::import_source_paths(vec![path], cx);
+            this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::OpenInput, &first);
+                Self::import_source_paths(paths, cx);
             })
             .ok();
         })
@@ -150,17 +177,126 @@ impl FrameRoot {
     }
 
     pub(super) fn import_source_paths(paths: Vec<PathBuf>, cx: &Context<Self>) {
+        Self::import_sources(paths, false, cx);
+    }
+
+    /// Imports dropped paths. A drop holding a folder is scanned and probed
+    /// first, then queued in one batch once its summary is confirmed.
+    pub(super) fn import_dropped_paths(
+        paths: Vec<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !paths.iter().any(|path| path.is_dir()) {
+            Self::import_source_paths(paths, cx);
+            return;
+        }
+
+        cx.spawn_in(window, async move |this, cx| {
+            let summary = cx
+                .background_spawn(async move { scan_dropped_paths(paths, true) })
+                .await;
+            if let Some(skipped) = summary.report.summary() {
+                eprintln!("Dropped folder scan: {skipped}");
+            }
+            let Ok(Some(receiver)) = this.update_in(cx, |root, window, cx| {
+                if root.update_installation_in_progress() {
+                    return None;
+                }
+                let detail = summary.detail();
+                let detail = (!detail.is_empty()).then_some(detail.as_str());
+                let buttons = if summary.sources.is_empty() {
+                    vec![PromptButton::ok("OK")]
+                } else {
+                    vec![
+                        PromptButton::ok("Add Files"),
+                        PromptButton::cancel("Cancel"),
+                    ]
+                };
+                Some(window.prompt(PromptLevel::Info, &summary.title(), detail, &buttons, cx))
+            }) else {
+                return;
+            };
+            let confirmed = matches!(receiver.await, Ok(0));
+            if !confirmed || summary.sources.is_empty() {
+                return;
+            }
+            this.update(cx, |root, cx| root.add_dropped_sources(summary.sources, cx))
+                .ok();
+        })
+        .detach();
+    }
+
+    /// Queues the files of a confirmed drop scan, reusing their probes.
+    fn add_dropped_sources(&mut self, sources: Vec<DroppedSource>, cx: &mut Context<Self>) {
+        if self.update_installation_in_progress() {
+            return;
+        }
+        let mut probes = Vec::with_capacity(sources.len());
+        let files = sources
+            .into_iter()
+            .map(|source| {
+                let id = self.next_file_id();
+                let mut file = FileItem::from_path(
+                    id.clone(),
+                    source.path.to_string_lossy(),
+                    source.preprobed.fingerprint.size_bytes,
+                );
+                file.relative_subpath = source.relative_subpath;
+                probes.push((id, source.preprobed));
+                file
+            })
+            .collect::<Vec<_>>();
+        if self.file_queue.add_files(files) == 0 {
+            return;
+        }
+        for (file_id, source) in probes {
+            self.apply_probed_source(file_id, source, true);
+        }
+        cx.notify();
+    }
+
+    /// Imports files picked in the source dialog, keeping files with an
+    /// unlisted extension chosen through its "All Files" filter so the probe
+    /// decides whether they are media.
+    pub(super) fn import_picked_source_paths(paths: Vec<PathBuf>, cx: &Context<Self>) {
+        Self::import_sources(paths, true, cx);
+    }
+
+    fn import_sources(paths: Vec<PathBuf>, picked: bool, cx: &Context<Self>) {
         if paths.is_empty() {
             return;
         }
 
         cx.spawn(async move |this, cx| {
-            let paths = cx
-                .background_spawn(async move { discover_supported_source_paths(paths) })
+            let scan = cx
+                .background_spawn(async move {
+                    if picked {
+                        discover_picked_sources(paths)
+                    } else {
+                        discover_supported_sources(paths)
+                    }
+                })
                 .await;
+            if let Some(summary) = scan.report.summary() {
+                eprintln!("Source import: {summary}");
+            }
+            let mut relative_subpaths = HashMap::new();
+            let paths = scan
+                .sources
+                .into_iter()
+                .map(|source| {
+                    if let Some(subpath) = source.relative_subpath {
+                        relative_subpaths.insert(source.path.clone(), subpath);
+                    }
+                    source.path
+                })
+                .collect::<Vec<_>>();
             let Ok(imports) = this.update(cx, |root, _cx| {
                 if root.update_installation_in_progress() {
                     Vec::new()
+                } else if picked {
+                    root.allocate_file_ids(paths)
                 } else {
                     root.allocate_file_imports(paths)
                 }
@@ -175,7 +311,11 @@ impl FrameRoot {
                 .background_spawn(async move {
                     imports
                         .into_iter()
-                        .map(|(id, path)| FileItem::from_os_path(id, &path))
+                        .map(|(id, path)| {
+                            let mut file = FileItem::from_os_path(id, &path);
+                            file.relative_subpath = relative_subpaths.remove(&path);
+                            file
+                        })
                         .collect::<Vec<_>>()
                 })
                 .await;
@@ -200,7 +340,10 @@ impl FrameRoot {
         .detach();
     }
     pub(super) fn allocate_file_imports(&mut self, paths: Vec<PathBuf>) -> Vec<(String, PathBuf)> {
-        filter_supported_source_paths(paths)
+        self.allocate_file_ids(filter_supported_source_paths(paths))
+    }
+    pub(super) fn allocate_file_ids(&mut self, paths: Vec<PathBuf>) -> Vec<(String, PathBuf)> {
+        paths
             .into_iter()
             .map(|path| {
                 let id = self.next_file_id();
@@ -317,12 +317,18 @@ impl FrameRoot {
                         codec: "subrip".to_string(),
                         language: Some("eng".to_string()),
                         label: Some("Dialogue".to_string()),
+                        is_default: false,
+                        is_forced: false,
+                        is_bitmap: false,
                     },
                     crate::settings::SubtitleTrack {
                         index: 3,
                         codec: "ass".to_string(),
                         language: Some("jpn".to_string()),
                         label: Some("Signs".to_string()),
+                        is_default: false,
+                        is_forced: false,
+                        is_bitmap: false,
                     },
                 ],
                 ..SourceMetadata::default()
@@ -387,6 +393,9 @@ impl FrameRoot {
                         label: Some("Main mix".to_string()),
                         bitrate_kbps: Some(1536.0),
                         sample_rate: Some("48000".to_string()),
+                        channel_layout: Some("stereo".to_string()),
+                        is_default: true,
+                        is_forced: false,
                     },
                     crate::settings::AudioTrack {
                         index: 1,
@@ -396,6 +405,9 @@ impl FrameRoot {
                         label: Some("Reference".to_string()),
                         bitrate_kbps: Some(192.0),
                         sample_rate: Some("48000".to_string()),
+                        channel_layout: None,
+                        is_default: false,
+                        is_forced: false,
                     },
                 ],
                 tags: Some(SourceTags {
@@ -0,0 +1,94 @@
+use super::*;
+
+/// How often files handed over by other launches are picked up.
+const FORWARDED_FILE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+impl FrameRoot {
+    /// Brings the window to the front and adds the files other launches and
+    /// the platform's open-file events hand over.
+    pub(super) fn start_forwarded_file_polling(
+        &self,
+        files: ForwardedFiles,
+        window: &Window,
+        cx: &Context<Self>,
+    ) {
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                if let Some(paths) = files.try_take()
+                    && this
+                        .update_in(cx, |root, window, cx| {
+                            window.activate_window();
+                            root.open_forwarded_paths(paths, cx);
+                        })
+                        .is_err()
+                {
+                    return;
+                }
+                cx.background_executor()
+                    .timer(FORWARDED_FILE_POLL_INTERVAL)
+                    .await;
+            }
+        })
+        .detach();
+    }
+
+    /// Adds forwarded files and folders. Files that are missing or not media
+    /// are reported in a notification, since the window may not have been in
+    /// front when they were opened; so are files whose probe fails later.
+    pub(super) fn open_forwarded_paths(&mut self, paths: Vec<PathBuf>, cx: &Context<Self>) {
+        if self.update_installation_in_progress() {
+            return;
+        }
+        let mut accepted = Vec::new();
+        let mut missing = Vec::new();
+        let mut unsupported = Vec::new();
+        for path in paths {
+            if path.is_dir() {
+                accepted.push(path);
+            } else if !path.is_file() {
+                missing.push(path);
+            } else if is_supported_source_path(&path) {
+                self.forwarded_source_paths.insert(path.clone());
+                accepted.push(path);
+            } else {
+                unsupported.push(path);
+            }
+        }
+
+        for (paths, reason) in [
+            (missing, "the file does not exist"),
+            (unsupported, "not a supported media file"),
+        ] {
+            if !paths.is_empty() {
+                self.notifier
+                    .notify_files_not_opened(FilesNotOpenedSummary {
+                        file_names: paths.iter().map(|path| forwarded_file_name(path)).collect(),
+                        reason: reason.to_string(),
+                    });
+            }
+        }
+        Self::import_source_paths(accepted, cx);
+    }
+
+    /// Reports a forwarded file whose probe finished with `error`; files
+    /// added from inside the window show the error in the file list only.
+    pub(super) fn finish_forwarded_source_probe(&mut self, path: &str, error: Option<String>) {
+        if !self.forwarded_source_paths.remove(Path::new(path)) {
+            return;
+        }
+        if let Some(reason) = error {
+            self.notifier
+                .notify_files_not_opened(FilesNotOpenedSummary {
+                    file_names: vec![forwarded_file_name(Path::new(path))],
+                    reason,
+                });
+        }
+    }
+}
+
+fn forwarded_file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
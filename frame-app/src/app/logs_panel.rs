@@ -16,6 +16,7 @@ use super::{
     div, px, theme, uniform_list,
 };
 use crate::numeric::usize_to_f32;
+use std::path::PathBuf;
 
 pub(super) fn logs_view(
     queue: &FileQueue,
@@ -31,6 +32,7 @@ pub(super) fn logs_view(
     let selected_line_count = selected_id.map_or(0, |id| conversion_events.logs_for(id).len());
     let selected_logs_copied =
         selected_id.is_some_and(|id| copied_log_file_id.is_some_and(|copied| copied == id));
+    let selected_log_file_path = selected_id.and_then(|id| conversion_events.log_file_path(id));
 
     div()
         .size_full()
@@ -43,9 +45,13 @@ pub(super) fn logs_view(
             selected_id,
             selected_line_count,
             selected_logs_copied,
+            selected_log_file_path,
             window,
             cx,
         ))
+        .when_some(conversion_events.event_queue_notice(), |this, notice| {
+            this.child(logs_event_queue_notice(notice))
+        })
         .child(logs_body(
             conversion_events,
             selected_id,
@@ -62,6 +68,7 @@ pub(super) fn logs_tab_strip(
     selected_id: Option<&str>,
     selected_line_count: usize,
     selected_logs_copied: bool,
+    selected_log_file_path: Option<&str>,
     window: &mut Window,
     cx: &mut Context<FrameRoot>,
 ) -> gpui::Div {
@@ -109,6 +116,9 @@ pub(super) fn logs_tab_strip(
         .gap_2()
         .px_4()
         .child(tabs)
+        .when_some(selected_log_file_path, |this, log_file_path| {
+            this.child(logs_open_file_button(log_file_path, window, cx))
+        })
         .when_some(selected_id, |this, selected_id| {
             this.child(logs_copy_button(
                 selected_id,
@@ -469,6 +479,31 @@ pub(super) fn logs_copy_button(
     }))
 }
 
+pub(super) fn logs_open_file_button(
+    log_file_path: &str,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> impl IntoElement {
+    let log_file_path = PathBuf::from(log_file_path);
+    frame_icon_button(
+        "logs-open-file",
+        assets::ICON_FILE_UP,
+        "Open log file",
+        FrameIconButtonVariant::Ghost,
+        true,
+        FrameIconButtonSize {
+            button: FRAME_ICON_BUTTON_SM_SIZE,
+            icon: FRAME_ICON_SM_SIZE,
+        },
+        window,
+        cx,
+    )
+    .on_click(cx.listener(move |_root, _: &ClickEvent, _window, cx| {
+        cx.stop_propagation();
+        cx.open_with_system(&log_file_path);
+    }))
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) enum LogLineTone {
     Default,
@@ -520,6 +555,17 @@ pub(super) const fn log_line_hover_tone_color(tone: LogLineTone) -> theme::RgbaT
     }
 }
 
+/// Line under the tabs saying the latest batch's output outran the panel.
+pub(super) fn logs_event_queue_notice(notice: String) -> gpui::Div {
+    div()
+        .w_full()
+        .px_4()
+        .py_1()
+        .text_size(px(theme::TEXT_LABEL_SIZE))
+        .text_color(color(theme::FRAME_AMBER))
+        .child(theme::ui_text_owned(notice))
+}
+
 pub(super) fn logs_empty_state(message: &'static str) -> gpui::Div {
     div()
         .size_full()
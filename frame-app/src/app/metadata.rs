@@ -40,26 +40,25 @@ impl FrameRoot {
         cx.notify();
 
         cx.spawn(async move |this, cx| {
+            let probed_path = file_path.clone();
             let result = cx
-                .background_spawn(async move { probe_source_metadata(&file_path) })
+                .background_spawn(async move { probe_source(&file_path) })
                 .await;
 
             this.update(cx, |root, cx| {
                 match result {
-                    Ok(metadata) => {
-                        root.source_metadata.mark_ready(file_id.clone(), metadata);
-                        if root.file_queue.selected_file_id() == Some(file_id.as_str()) {
-                            let selected_metadata = root.selected_source_metadata();
-                            if normalize_selected_config && !root.update_installation_in_progress()
-                            {
-                                root.normalize_selected_config(selected_metadata.as_ref());
-                            }
-                            root.resolve_selected_settings_tab(selected_metadata.as_ref());
-                        }
+                    Ok(source) => {
+                        root.finish_forwarded_source_probe(&probed_path, None);
+                        root.apply_probed_source(
+                            file_id.clone(),
+                            source,
+                            normalize_selected_config,
+                        );
                     }
                     Err(error) => {
-                        root.source_metadata
-                            .mark_error(file_id.clone(), error.to_string());
+                        let message = error.to_string();
+                        root.finish_forwarded_source_probe(&probed_path, Some(message.clone()));
+                        root.source_metadata.mark_error(file_id.clone(), message);
                     }
                 }
                 cx.notify();
@@ -68,4 +67,23 @@ impl FrameRoot {
         })
         .detach();
     }
+
+    /// Stores a finished probe and, when its file is selected, fits the
+    /// settings and visible tab to the source.
+    pub(super) fn apply_probed_source(
+        &mut self,
+        file_id: String,
+        source: PreprobedSource,
+        normalize_selected_config: bool,
+    ) {
+        let selected = self.file_queue.selected_file_id() == Some(file_id.as_str());
+        self.source_metadata.mark_probed(file_id, source);
+        if selected {
+            let selected_metadata = self.selected_source_metadata();
+            if normalize_selected_config && !self.update_installation_in_progress() {
+                self.normalize_selected_config(selected_metadata.as_ref());
+            }
+            self.resolve_selected_settings_tab(selected_metadata.as_ref());
+        }
+    }
 }
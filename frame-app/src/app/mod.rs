@@ -5,11 +5,13 @@ mod conversion;
 mod file_list_panel;
 mod files;
 mod fixtures;
+mod forwarded_files;
 mod input;
 mod logs_panel;
 mod logs_state;
 mod metadata;
 mod motion;
+mod output_conflicts;
 mod preview_actions;
 mod preview_panel;
 mod primitives;
@@ -22,6 +24,8 @@ mod state;
 mod tests;
 mod update_actions;
 mod update_session;
+mod watch_folder_actions;
+mod window_status;
 mod workspace;
 pub use runtime::{frame_window_options, init_app, open_frame_window};
 
@@ -36,6 +40,7 @@ use motion::{
     mix_color, mix_scalar, motion_is_hidden, motion_target, retarget_hover_motion, selected_motion,
     set_motion_target, settings_sheet_right_inset, subtitle_popover_slide_offset,
 };
+use output_conflicts::OutputConflict;
 use preview_panel::{
     FlipAxis, PreviewCanvasRenderState, PreviewCropRenderState, PreviewMediaRenderState,
     PreviewOverlayRenderState, PreviewPanelProps, PreviewTimecodeInputFocuses, crop_aspect_id,
@@ -46,6 +51,7 @@ use preview_panel::{
     timeline_keyboard_time_for_key, timeline_slider_percent_from_bounds,
 };
 use primitives::color;
+use window_status::WindowCloseState;
 use workspace::{welcome_view, workspace_view};
 
 #[cfg(target_os = "linux")]
@@ -69,17 +75,23 @@ use crate::{
     WINDOW_MIN_HEIGHT, WINDOW_MIN_WIDTH, WORKSPACE_COLUMNS, WORKSPACE_GAP,
     active_view_from_env_value,
     app_info::{FRAME_APP_ID, FRAME_APP_VERSION},
-    app_persistence::{AppPersistence, AppSettings},
+    app_persistence::{AppPersistence, AppSettings, RuntimeAppSettings},
     assets::{self},
-    capabilities::{detect_available_encoders, detect_available_filters},
+    capabilities::{
+        detect_available_capabilities, detect_available_filters, detect_ffmpeg_info,
+        detect_hardware_capabilities, detect_hw_devices, detect_verified_encoders,
+        refresh_capabilities, validate_media_tools,
+    },
     conversion_events::{ActiveLogFile, ConversionEventState, LogLine, all_conversions_settled},
     conversion_runner::{
-        ConversionProcessController, conversion_task_from_file, disambiguate_output_paths,
-        run_conversion_batch_with_control,
+        CANCEL_EXIT_TIMEOUT, ConversionProcessController, conversion_task_with_settings,
+        disambiguate_output_paths, remove_sample_previews, run_conversion_batch_with_control,
     },
+    drop_scan::{DroppedSource, scan_dropped_paths},
     file_filters::{
-        AUDIO_FILE_EXTENSIONS, IMAGE_FILE_EXTENSIONS, discover_supported_source_paths,
-        filter_supported_source_paths, is_supported_overlay_image_path, is_supported_subtitle_path,
+        AUDIO_FILE_EXTENSIONS, IMAGE_FILE_EXTENSIONS, discover_picked_sources,
+        discover_supported_sources, filter_supported_source_paths, is_supported_overlay_image_path,
+        is_supported_source_path, is_supported_subtitle_path,
     },
     file_queue::{
         BatchSelectionState, FileItem, FileQueue, FileStateTone, FileStatus, RowActionAvailability,
@@ -87,11 +99,18 @@ use crate::{
     },
     format_total_size,
     native_dialogs::{
-        output_folder_dialog, overlay_image_dialog, pick_output_folder, pick_overlay_image_file,
-        pick_source_files, pick_source_folder, pick_subtitle_file, source_file_dialog,
-        source_folder_dialog, subtitle_file_dialog,
+        DialogPurpose, NativeDialogFilterSet, claim_native_dialog, dialog_start_directory,
+        export_presets_dialog, ffmpeg_binary_dialog, import_presets_dialog, output_folder_dialog,
+        overlay_image_dialog, pick_ffmpeg_binary, pick_output_folder, pick_overlay_image_file,
+        pick_preset_export_path, pick_preset_file, pick_save_output_path, pick_source_files,
+        pick_source_folders, pick_subtitle_file, remembered_dialog_directory, save_output_dialog,
+        save_path_output_target, scratch_folder_dialog, source_file_dialog, source_folder_dialog,
+        subtitle_file_dialog, watch_folder_dialog,
+    },
+    notifications::{
+        AppNotifier, FilesNotOpenedSummary, NotificationMode, TaskNotification,
+        TaskNotificationOutcome, conversion_finished_notification_for_task_ids,
     },
-    notifications::{AppNotifier, conversion_finished_notification_for_task_ids},
     preview::{
         ASPECT_OPTIONS, CropRect, DragHandle, MAX_OVERLAY_WIDTH, MIN_OVERLAY_WIDTH, MediaSnapshot,
         MetadataStatus as PreviewMetadataStatus, OverlayDragHandle, OverlayDragPoint,
@@ -106,6 +125,8 @@ use crate::{
         MIN_PREVIEW_DIMENSION, PreviewCommand, PreviewRenderPresentation, PreviewSession,
         PreviewSessionConfig, PreviewSourceKind as EnginePreviewSourceKind, PreviewTransform,
     },
+    queue_status::{queue_status_for_task_ids, queue_window_title},
+    runtime_binaries::{MediaToolPaths, set_configured_media_tools},
     settings::{
         ConversionConfig, CropSettings, DEFAULT_SUBTITLE_FONT_COLOR,
         DEFAULT_SUBTITLE_OUTLINE_COLOR, MetadataField, OverlaySettings, PresetDefinition,
@@ -113,10 +134,11 @@ use crate::{
         SourceInfoSection, SourceKind, SourceMetadata, SourceTags, SubtitleFontOption,
         SubtitleFontSizeOption, apply_audio_bitrate, apply_audio_bitrate_mode,
         apply_audio_channels, apply_audio_codec, apply_audio_normalize, apply_audio_quality,
-        apply_audio_volume, apply_crf, apply_custom_height, apply_custom_width, apply_fps,
-        apply_gif_colors, apply_gif_dither, apply_gif_loop, apply_hw_decode,
-        apply_image_jpeg_huffman, apply_image_jpeg_quality, apply_image_png_compression,
-        apply_image_png_prediction, apply_image_tiff_compression, apply_image_webp_compression,
+        apply_audio_volume, apply_container_defaults, apply_crf, apply_custom_height,
+        apply_custom_width, apply_fps, apply_gif_colors, apply_gif_dither, apply_gif_loop,
+        apply_hw_decode, apply_hw_device, apply_image_avif_crf, apply_image_jpeg_huffman,
+        apply_image_jpeg_quality, apply_image_png_compression, apply_image_png_prediction,
+        apply_image_sequence_output, apply_image_tiff_compression, apply_image_webp_compression,
         apply_image_webp_lossless, apply_image_webp_preset, apply_image_webp_quality,
         apply_metadata_field, apply_metadata_mode, apply_nvenc_spatial_aq, apply_nvenc_temporal_aq,
         apply_output_container, apply_pixel_format, apply_preset, apply_processing_mode,
@@ -125,11 +147,11 @@ use crate::{
         apply_subtitle_outline_color, apply_subtitle_position, apply_trim_times,
         apply_video_bitrate, apply_video_bitrate_mode, apply_video_codec, apply_video_preset,
         apply_videotoolbox_allow_sw, audio_channel_options, audio_codec_options,
-        audio_codec_supports_vbr, audio_quality_range, audio_track_options, create_custom_preset,
-        default_presets, fps_options, gif_color_options, gif_dither_options,
+        audio_codec_supports_vbr, audio_quality_range, audio_track_options, copy_source_settings,
+        create_custom_preset, fps_options, gif_color_options, gif_dither_options,
         image_jpeg_huffman_options, image_png_prediction_options, image_tiff_compression_options,
         image_webp_preset_options, is_gif_container, is_hardware_video_codec, is_nvenc_video_codec,
-        is_videotoolbox_video_codec, metadata_field_options, metadata_field_value,
+        is_videotoolbox_video_codec, merged_presets, metadata_field_options, metadata_field_value,
         metadata_mode_options, normalize_output_config, normalized_hex_color,
         output_container_options, output_processing_mode_options, preset_options,
         resolution_options, resolve_active_settings_tab, sanitize_output_name,
@@ -139,18 +161,27 @@ use crate::{
         toggle_subtitle_track_selection, video_codec_options, video_pixel_format_options,
         video_preset_options, visible_settings_tabs,
     },
-    source_metadata::{
-        MetadataStatus, SourceMetadataEntry, SourceMetadataStore, probe_source_metadata,
-    },
+    single_instance::ForwardedFiles,
+    source_metadata::{MetadataStatus, SourceMetadataEntry, SourceMetadataStore, probe_source},
     theme,
     update_runtime::{
         build_update_client, unix_timestamp, update_check_is_due, updates_disabled_explanation,
     },
     visual_fixture_from_env_value,
+    watch_folders::{
+        WATCH_FOLDER_POLL_INTERVAL, WatchFolder, WatchFolderCandidate, WatchFolderScanner,
+        missing_processed_files, record_watch_folder_output,
+    },
 };
-use frame_core::capabilities::{AvailableEncoders, AvailableFilters};
+use frame_core::capabilities::{
+    AvailableEncoders, AvailableFilters, FfmpegCapabilities, FfmpegInfo, HardwareCapabilities,
+    downgrade_unsupported_hw_decode,
+};
+use frame_core::event_queue::{EVENT_QUEUE_CAPACITY, conversion_event_queue};
 use frame_core::events::ConversionEvent;
-use frame_core::types::DEFAULT_MAX_CONCURRENCY;
+use frame_core::hw_devices::{HwDevice, hw_device_kind};
+use frame_core::task_log_file::{TaskLogRetention, prune_task_logs};
+use frame_core::types::{DEFAULT_MAX_CONCURRENCY, OverwritePolicy, PostAction, PreprobedSource};
 use frame_updater::{DownloadProgress, UpdateChannel, UpdateCheck, UpdateInfo, UpdatePackage};
 use gpui::{
     App, Bounds, BoxShadow, ClickEvent, ClipboardItem, Context, DispatchPhase, DragMoveEvent,
@@ -167,13 +198,14 @@ use gpui::{
     uniform_list,
 };
 use std::{
+    collections::{HashMap, HashSet},
     ops::Range,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         Arc,
         mpsc::{self, TryRecvError},
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 actions!(
@@ -263,12 +295,51 @@ pub struct FrameRoot {
     drag_drop_ui: DragDropUiState,
     max_concurrency: usize,
     default_output_directory: Option<std::path::PathBuf>,
+    overwrite_policy: OverwritePolicy,
+    output_name_template: Option<String>,
+    preserve_file_times: bool,
+    post_action: PostAction,
+    scratch_directory: Option<std::path::PathBuf>,
+    /// `FFmpeg` and `FFprobe` used instead of the bundled ones.
+    media_tools: Option<MediaToolPaths>,
+    notification_mode: NotificationMode,
+    /// Settings applied when a file switches to the keyed container.
+    container_defaults: std::collections::BTreeMap<String, ConversionConfig>,
+    watch_folders: Vec<WatchFolder>,
+    recent_dialog_directories: std::collections::BTreeMap<DialogPurpose, std::path::PathBuf>,
     text_input_ui: FrameTextInputUiState,
     source_metadata: SourceMetadataStore,
     conversion_processes: ConversionProcessController,
     available_encoders: AvailableEncoders,
     available_filters: AvailableFilters,
+    /// `None` until detection finishes, so hardware decoding is not refused
+    /// before the runtime was asked.
+    hardware_capabilities: Option<HardwareCapabilities>,
+    /// Filters, muxers and protocols; `None` until detection finishes.
+    ffmpeg_capabilities: Option<FfmpegCapabilities>,
+    /// Version of the runtime; `None` until detection finishes.
+    ffmpeg_info: Option<FfmpegInfo>,
+    /// GPUs hardware encoders can bind to.
+    hw_devices: Vec<HwDevice>,
     active_conversion_task_ids: Vec<String>,
+    /// When the running batch started, for its time-left estimate.
+    conversion_batch_started_at: Option<Instant>,
+    /// When each running task started converting, for its notification.
+    conversion_task_started_at: HashMap<String, Instant>,
+    /// Watch folder tasks and retries held back until the running batch
+    /// finishes, so they start as one batch under the concurrency limit.
+    pending_conversion_tasks: Vec<frame_core::types::ConversionTask>,
+    /// Output each running task writes, kept out of watch folder scans.
+    conversion_output_paths: HashMap<String, PathBuf>,
+    /// Tasks stopped by an existing output, asked about one at a time.
+    output_conflicts: Vec<OutputConflict>,
+    output_conflict_prompt_open: bool,
+    /// Title last given to the window.
+    window_title: Option<String>,
+    window_close_state: WindowCloseState,
+    /// Files opened from outside the window whose probe has not finished;
+    /// a failed probe is reported in a notification.
+    forwarded_source_paths: HashSet<PathBuf>,
     notifier: AppNotifier,
     subtitle_font_families: Vec<String>,
     presets: Vec<PresetDefinition>,
@@ -296,6 +367,16 @@ struct SettingsUiState {
     max_concurrency_draft: String,
     max_concurrency_error: Option<String>,
     output_directory_error: Option<String>,
+    overwrite_policy_error: Option<String>,
+    scratch_directory_error: Option<String>,
+    media_tools_error: Option<String>,
+    /// A chosen `FFmpeg` is being checked before it is used.
+    media_tools_validating: bool,
+    notification_mode_error: Option<String>,
+    watch_folders_error: Option<String>,
+    recent_directories_error: Option<String>,
+    /// `FFmpeg` detection is running again after a refresh.
+    capabilities_refreshing: bool,
     preset_name_draft: String,
     preset_notice: Option<PresetNotice>,
     next_custom_preset_sequence: u64,
@@ -369,6 +450,14 @@ impl Default for SettingsUiState {
             max_concurrency_draft: DEFAULT_MAX_CONCURRENCY.to_string(),
             max_concurrency_error: None,
             output_directory_error: None,
+            overwrite_policy_error: None,
+            scratch_directory_error: None,
+            media_tools_error: None,
+            media_tools_validating: false,
+            notification_mode_error: None,
+            watch_folders_error: None,
+            recent_directories_error: None,
+            capabilities_refreshing: false,
             preset_name_draft: String::new(),
             preset_notice: None,
             next_custom_preset_sequence: 0,
@@ -737,6 +826,8 @@ struct SettingsRenderState<'a> {
     metadata_error: Option<&'a str>,
     settings_disabled: bool,
     output_name: &'a str,
+    /// Folder chosen with Save As for the selected file.
+    output_directory: Option<&'a str>,
     output_name_focus: Option<&'a FocusHandle>,
     audio_bitrate_focus: Option<&'a FocusHandle>,
     video_width_focus: Option<&'a FocusHandle>,
@@ -761,6 +852,15 @@ struct SettingsRenderState<'a> {
     subtitle_fonts: &'a [String],
     available_encoders: &'a AvailableEncoders,
     available_filters: &'a AvailableFilters,
+    hardware_capabilities: Option<&'a HardwareCapabilities>,
+    ffmpeg_capabilities: Option<&'a FfmpegCapabilities>,
+    hw_devices: &'a [HwDevice],
+}
+
+#[derive(Clone, Copy)]
+struct SettingsVideoHardware<'a> {
+    capabilities: Option<&'a HardwareCapabilities>,
+    devices: &'a [HwDevice],
 }
 
 #[derive(Clone, Copy)]
@@ -0,0 +1,137 @@
+use super::*;
+
+/// Code of the error reported when a task's output already exists and the
+/// overwrite policy is [`OverwritePolicy::Fail`].
+const OUTPUT_EXISTS_CODE: &str = "output.exists";
+
+/// A task that stopped because its output already exists.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct OutputConflict {
+    pub(super) task_id: String,
+    pub(super) output_path: String,
+}
+
+impl FrameRoot {
+    /// Remembers a task that failed because its output already exists, so
+    /// the user is asked whether to overwrite it or keep both.
+    pub(super) fn record_output_conflict(&mut self, event: &ConversionEvent) {
+        let ConversionEvent::Error(payload) = event else {
+            return;
+        };
+        let Some(detail) = payload
+            .detail
+            .as_ref()
+            .filter(|detail| detail.code == OUTPUT_EXISTS_CODE)
+        else {
+            return;
+        };
+        self.output_conflicts.push(OutputConflict {
+            task_id: payload.id.clone(),
+            output_path: detail.context.path.clone().unwrap_or_default(),
+        });
+    }
+
+    /// Asks about the next output conflict while no other one is shown.
+    pub(super) fn prompt_next_output_conflict(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.output_conflict_prompt_open {
+            return;
+        }
+        let Some((conflict, file_name)) = self.take_next_output_conflict() else {
+            return;
+        };
+
+        self.output_conflict_prompt_open = true;
+        let detail = format!(
+            "{} already exists, so {file_name} was not converted.",
+            conflict.output_path
+        );
+        let receiver = window.prompt(
+            PromptLevel::Warning,
+            "Output already exists",
+            Some(&detail),
+            &[
+                PromptButton::ok("Overwrite"),
+                PromptButton::new("Keep Both"),
+                PromptButton::cancel("Skip"),
+            ],
+            cx,
+        );
+
+        cx.spawn(async move |this, cx| {
+            let policy = match receiver.await {
+                Ok(0) => Some(OverwritePolicy::Overwrite),
+                Ok(1) => Some(OverwritePolicy::Rename),
+                _ => None,
+            };
+            this.update(cx, |root, cx| {
+                root.output_conflict_prompt_open = false;
+                if let Some(policy) = policy {
+                    root.retry_conversion_with_policy(&conflict.task_id, policy, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// The oldest conflict whose file is still in the queue and failed,
+    /// with the file's name; conflicts of removed or retried files are
+    /// dropped.
+    pub(super) fn take_next_output_conflict(&mut self) -> Option<(OutputConflict, String)> {
+        while !self.output_conflicts.is_empty() {
+            let conflict = self.output_conflicts.remove(0);
+            if let Some(file) = self
+                .file_queue
+                .file_by_id(&conflict.task_id)
+                .filter(|file| file.status == FileStatus::Error)
+            {
+                let name = file.name.clone();
+                return Some((conflict, name));
+            }
+        }
+        None
+    }
+
+    /// Converts the failed file `id` again with `policy` instead of the
+    /// app's overwrite policy, once no batch is running.
+    pub(super) fn retry_conversion_with_policy(
+        &mut self,
+        id: &str,
+        policy: OverwritePolicy,
+        cx: &Context<Self>,
+    ) {
+        if self.update_installation_in_progress() {
+            return;
+        }
+        let Some(output_directory) = self
+            .default_output_directory
+            .as_ref()
+            .map(|path| path.to_string_lossy().into_owned())
+        else {
+            return;
+        };
+        let Some(file) = self
+            .file_queue
+            .files_mut()
+            .iter_mut()
+            .find(|file| file.id == id && file.status == FileStatus::Error)
+        else {
+            return;
+        };
+        file.status = FileStatus::Queued;
+        file.progress_percent = 0;
+        file.conversion_error = None;
+        let file = file.clone();
+
+        let mut task =
+            self.conversion_task_for_file(&file, &output_directory, &self.app_settings());
+        task.config.overwrite_policy = policy;
+        self.pending_conversion_tasks.push(task);
+        self.start_pending_conversion_tasks(cx);
+    }
+}
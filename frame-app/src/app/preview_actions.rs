@@ -895,9 +895,18 @@ impl FrameRoot {
             return;
         }
 
-        let dialog = overlay_image_dialog(window);
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = overlay_image_dialog(
+            window,
+            self.recent_dialog_directory(DialogPurpose::OverlayImage)
+                .as_deref(),
+        );
         cx.spawn(async move |this, cx| {
-            let Some(path) = pick_overlay_image_file(dialog).await else {
+            let path = pick_overlay_image_file(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
                 return;
             };
             if !is_supported_overlay_image_path(&path) {
@@ -909,9 +918,10 @@ impl FrameRoot {
                     async move { load_preview_overlay_image_dimensions(path) }
                 })
                 .await;
-            let path = path.to_string_lossy().to_string();
 
             this.update(cx, move |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::OverlayImage, &path);
+                let path = path.to_string_lossy().to_string();
                 if !root.selected_preview_overlay_controls_enabled() {
                     return;
                 }
@@ -23,6 +23,8 @@ impl Render for FrameRoot {
         }
 
         self.reconcile_text_input_focus(window, cx);
+        self.sync_window_title(window);
+        self.prompt_next_output_conflict(window, cx);
 
         let state = self.app_state();
         let source_metadata_entry = self.selected_source_metadata_entry();
@@ -38,6 +40,8 @@ impl Render for FrameRoot {
             selected_file.map_or_else(ConversionConfig::default, |file| file.config.clone());
         let selected_output_name =
             selected_file.map_or_else(String::new, |file| file.output_name.clone());
+        let selected_output_directory =
+            selected_file.and_then(|file| file.output_directory.clone());
         let preview_runtime_request = self.selected_preview_runtime_request(&source_metadata_entry);
         self.sync_preview_crop_for_selection(
             selected_file_id.as_deref(),
@@ -392,6 +396,7 @@ impl Render for FrameRoot {
                     metadata_error: source_metadata_entry.error.as_deref(),
                     settings_disabled: self.file_queue.selected_file_locked(),
                     output_name: &selected_output_name,
+                    output_directory: selected_output_directory.as_deref(),
                     output_name_focus: Some(&output_name_focus),
                     audio_bitrate_focus: Some(&audio_bitrate_focus),
                     video_width_focus: Some(&video_width_focus),
@@ -454,6 +459,9 @@ impl Render for FrameRoot {
                     subtitle_fonts: &self.subtitle_font_families,
                     available_encoders: &self.available_encoders,
                     available_filters: &self.available_filters,
+                    hardware_capabilities: self.hardware_capabilities.as_ref(),
+                    ffmpeg_capabilities: self.ffmpeg_capabilities.as_ref(),
+                    hw_devices: &self.hw_devices,
                 };
                 content.child(workspace_view(
                     &self.file_queue,
@@ -537,10 +545,10 @@ impl Render for FrameRoot {
                     }
                 }),
             )
-            .on_drop(cx.listener(|root, paths: &ExternalPaths, _window, cx| {
+            .on_drop(cx.listener(|root, paths: &ExternalPaths, window, cx| {
                 cx.stop_propagation();
                 root.close_drag_drop_overlay();
-                Self::import_source_paths(paths.paths().to_vec(), cx);
+                Self::import_dropped_paths(paths.paths().to_vec(), window, cx);
                 cx.notify();
             }))
             .on_drag_move(cx.listener(
@@ -562,6 +570,11 @@ impl Render for FrameRoot {
                 true,
                 cx,
             );
+            let scratch_directory_focus = self.ensure_focus(
+                FrameFocusKey::Control("app-settings-scratch-directory".to_string()),
+                true,
+                cx,
+            );
             let auto_update_focus = self.ensure_focus(
                 FrameFocusKey::Control("app-settings-auto-update-check".to_string()),
                 true,
@@ -624,11 +637,33 @@ impl Render for FrameRoot {
                         .as_deref()
                         .and_then(std::path::Path::to_str),
                     output_directory_error: self.settings_ui.output_directory_error.as_deref(),
+                    overwrite_policy: self.overwrite_policy,
+                    overwrite_policy_error: self.settings_ui.overwrite_policy_error.as_deref(),
+                    scratch_directory: self
+                        .scratch_directory
+                        .as_deref()
+                        .and_then(std::path::Path::to_str),
+                    scratch_directory_error: self.settings_ui.scratch_directory_error.as_deref(),
+                    watch_folders: &self.watch_folders,
+                    watch_folders_error: self.settings_ui.watch_folders_error.as_deref(),
+                    has_recent_directories: !self.recent_dialog_directories.is_empty(),
+                    recent_directories_error: self.settings_ui.recent_directories_error.as_deref(),
+                    ffmpeg_version: self.ffmpeg_info.as_ref().map(|info| info.version.as_str()),
+                    ffmpeg_path: self
+                        .media_tools
+                        .as_ref()
+                        .and_then(|tools| tools.ffmpeg.to_str()),
+                    media_tools_error: self.settings_ui.media_tools_error.as_deref(),
+                    media_tools_validating: self.settings_ui.media_tools_validating,
+                    capabilities_refreshing: self.settings_ui.capabilities_refreshing,
+                    notification_mode: self.notification_mode,
+                    notification_mode_error: self.settings_ui.notification_mode_error.as_deref(),
                     auto_update_check: self.auto_update_check,
                     update_status: &self.update_ui.status,
                     update_install_ready,
                     value_focus: &value_focus,
                     output_directory_focus: &output_directory_focus,
+                    scratch_directory_focus: &scratch_directory_focus,
                     auto_update_focus: &auto_update_focus,
                     check_now_focus: &check_now_focus,
                     download_focus: &download_focus,
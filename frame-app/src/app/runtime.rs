@@ -3,7 +3,12 @@ use super::*;
 pub fn init_app(cx: &mut App, name: impl Into<SharedString>) {
     cx.activate(true);
     cx.set_text_rendering_mode(TextRenderingMode::PlatformDefault);
-    cx.on_action(|_: &Quit, cx| cx.quit());
+    cx.on_action(quit_frame);
+    cx.on_app_quit(|_| {
+        remove_sample_previews();
+        async {}
+    })
+    .detach();
     cx.bind_keys([
         KeyBinding::new("cmd-q", Quit, None),
         KeyBinding::new(
@@ -52,21 +57,54 @@ pub fn init_app(cx: &mut App, name: impl Into<SharedString>) {
     .detach();
 }
 
-/// Opens Frame's main application window.
+/// Quits by closing each window, so running conversions are confirmed and
+/// stopped as they are when the window itself is closed. The last window to
+/// close quits Frame.
+fn quit_frame(_: &Quit, cx: &mut App) {
+    let windows = cx.windows();
+    if windows.is_empty() {
+        cx.quit();
+        return;
+    }
+
+    for handle in windows {
+        let should_close = handle.downcast::<FrameRoot>().map_or(Ok(true), |root| {
+            root.update(cx, |root, window, cx| root.window_should_close(window, cx))
+        });
+        if should_close.unwrap_or(true) {
+            handle
+                .update(cx, |_, window, _| window.remove_window())
+                .ok();
+        }
+    }
+}
+
+/// Opens Frame's main application window, which adds the files sent
+/// through `forwarded_files`.
 ///
 /// # Panics
 ///
 /// Panics when GPUI cannot create the main window.
-pub fn open_frame_window(cx: &mut App) {
+pub fn open_frame_window(cx: &mut App, forwarded_files: ForwardedFiles) {
     let bounds = Bounds::centered(None, size(px(WINDOW_MIN_WIDTH), px(WINDOW_MIN_HEIGHT)), cx);
-    cx.open_window(frame_window_options(bounds), |_, cx| {
-        cx.new(|cx| {
+    cx.open_window(frame_window_options(bounds), |window, cx| {
+        let root = cx.new(|cx| {
             let mut root = FrameRoot::new_with_platform_persistence();
             root.restore_pending_update_session(cx);
             root.load_runtime_capabilities(cx);
+            root.prune_conversion_logs(cx);
+            root.start_watch_folder_polling(cx);
             root.startup_update_check(cx);
+            root.start_forwarded_file_polling(forwarded_files, window, cx);
             root
-        })
+        });
+        let weak_root = root.downgrade();
+        window.on_window_should_close(cx, move |window, cx| {
+            weak_root
+                .update(cx, |root, cx| root.window_should_close(window, cx))
+                .unwrap_or(true)
+        });
+        root
     })
     .expect("failed to open Frame GPUI window");
 }
@@ -1,9 +1,22 @@
+use std::path::{Path, PathBuf};
+
 use super::{
-    Context, FrameRoot, FrameTextInputKind, PresetDefinition, PresetNotice, PresetNoticeTone,
-    PromptButton, PromptLevel, Window, apply_preset, apply_subtitle_burn_path,
-    create_custom_preset, is_supported_subtitle_path, output_folder_dialog, pick_output_folder,
-    pick_subtitle_file, subtitle_file_dialog,
+    Context, ConversionConfig, DialogPurpose, FrameRoot, FrameTextInputKind, MediaToolPaths,
+    PresetDefinition, PresetNotice, PresetNoticeTone, PromptButton, PromptLevel, Window,
+    apply_container_defaults, apply_output_container, apply_preset, apply_subtitle_burn_path,
+    claim_native_dialog, create_custom_preset, dialog_start_directory, export_presets_dialog,
+    ffmpeg_binary_dialog, import_presets_dialog, is_supported_subtitle_path,
+    normalize_output_config, output_folder_dialog, pick_ffmpeg_binary, pick_output_folder,
+    pick_preset_export_path, pick_preset_file, pick_save_output_path, pick_subtitle_file,
+    refresh_capabilities, remembered_dialog_directory, save_output_dialog, save_path_output_target,
+    scratch_folder_dialog, set_configured_media_tools, subtitle_file_dialog, validate_media_tools,
+};
+use crate::{
+    app_persistence::AppPersistenceError,
+    preset_transfer::{export_presets, import_presets},
+    runtime_binaries::app_install_directory,
 };
+use frame_core::scratch::validate_scratch_directory;
 
 impl FrameRoot {
     pub(super) fn open_app_settings(&mut self) {
@@ -12,12 +25,24 @@ impl FrameRoot {
         self.settings_ui.max_concurrency_draft = self.max_concurrency.to_string();
         self.settings_ui.max_concurrency_error = None;
         self.settings_ui.output_directory_error = None;
+        self.settings_ui.overwrite_policy_error = None;
+        self.settings_ui.scratch_directory_error = None;
+        self.settings_ui.media_tools_error = None;
+        self.settings_ui.notification_mode_error = None;
+        self.settings_ui.watch_folders_error = None;
+        self.settings_ui.recent_directories_error = None;
     }
 
     pub(super) fn close_app_settings(&mut self) {
         self.settings_ui.is_open = false;
         self.settings_ui.max_concurrency_error = None;
         self.settings_ui.output_directory_error = None;
+        self.settings_ui.overwrite_policy_error = None;
+        self.settings_ui.scratch_directory_error = None;
+        self.settings_ui.media_tools_error = None;
+        self.settings_ui.notification_mode_error = None;
+        self.settings_ui.watch_folders_error = None;
+        self.settings_ui.recent_directories_error = None;
         self.text_input_ui
             .focuses
             .clear(FrameTextInputKind::MaxConcurrency);
@@ -67,10 +92,15 @@ impl FrameRoot {
         (value > 0).then_some(value)
     }
 
-    pub(super) fn prompt_default_output_folder(window: &Window, cx: &Context<Self>) {
-        let dialog = output_folder_dialog(window);
+    pub(super) fn prompt_default_output_folder(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = output_folder_dialog(window, self.default_output_directory.as_deref());
         cx.spawn(async move |this, cx| {
-            let Some(path) = pick_output_folder(dialog).await else {
+            let path = pick_output_folder(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
                 return;
             };
 
@@ -102,22 +132,264 @@ impl FrameRoot {
         Ok(())
     }
 
+    pub(super) fn prompt_scratch_folder(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = scratch_folder_dialog(window, self.scratch_directory.as_deref());
+        cx.spawn(async move |this, cx| {
+            let path = pick_output_folder(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+
+            this.update(cx, |root, cx| {
+                root.settings_ui.scratch_directory_error = root
+                    .set_scratch_directory(Some(path))
+                    .err()
+                    .map(|error| format!("Failed to set scratch folder: {error}"));
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Sets the directory large intermediates are written to, or restores
+    /// the OS temporary directory when `path` is `None`.
+    pub(super) fn set_scratch_directory(
+        &mut self,
+        path: Option<std::path::PathBuf>,
+    ) -> Result<(), AppPersistenceError> {
+        if self.update_installation_in_progress() {
+            return Err(AppPersistenceError::InstallationInProgress);
+        }
+        let path = path
+            .map(|path| {
+                validate_scratch_directory(&path, app_install_directory().as_deref()).map_err(
+                    |error| AppPersistenceError::InvalidScratchDirectory(error.to_string()),
+                )
+            })
+            .transpose()?;
+        let previous = std::mem::replace(&mut self.scratch_directory, path);
+        if let Err(error) = self.persist_app_settings() {
+            self.scratch_directory = previous;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Asks for an `FFmpeg` binary, with `FFprobe` expected beside it, and
+    /// switches to the pair once both pass [`validate_media_tools`].
+    pub(super) fn prompt_ffmpeg_binary(&self, window: &Window, cx: &Context<Self>) {
+        if self.settings_ui.media_tools_validating {
+            return;
+        }
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let current = self
+            .media_tools
+            .as_ref()
+            .map(|tools| tools.ffmpeg.as_path());
+        let dialog = ffmpeg_binary_dialog(window, current);
+        cx.spawn(async move |this, cx| {
+            let path = pick_ffmpeg_binary(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+            let tools = MediaToolPaths::beside_ffmpeg(path);
+            let started = this.update(cx, |root, cx| {
+                root.settings_ui.media_tools_validating = true;
+                root.settings_ui.media_tools_error = None;
+                cx.notify();
+            });
+            if started.is_err() {
+                return;
+            }
+
+            let validation = cx
+                .background_spawn({
+                    let tools = tools.clone();
+                    async move { validate_media_tools(&tools) }
+                })
+                .await;
+            this.update(cx, |root, cx| {
+                root.settings_ui.media_tools_validating = false;
+                root.settings_ui.media_tools_error = match validation {
+                    Ok(_) => root
+                        .switch_media_tools(Some(tools), cx)
+                        .err()
+                        .map(|error| format!("Failed to save settings: {error}")),
+                    Err(error) => Some(format!("Cannot use this FFmpeg: {error}")),
+                };
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Runs the tools in `tools` from now on, or the bundled ones when
+    /// `None`, and detects their capabilities again. Returning to the
+    /// bundled tools is not validated, so it works however broken the
+    /// chosen ones have become.
+    pub(super) fn switch_media_tools(
+        &mut self,
+        tools: Option<MediaToolPaths>,
+        cx: &mut Context<Self>,
+    ) -> Result<(), AppPersistenceError> {
+        self.set_media_tools(tools)?;
+        set_configured_media_tools(self.media_tools.clone());
+        refresh_capabilities();
+        self.settings_ui.capabilities_refreshing = true;
+        self.load_runtime_capabilities(cx);
+        Ok(())
+    }
+
+    /// Saves the `FFmpeg` and `FFprobe` used instead of the bundled ones,
+    /// restoring the previous choice when saving fails.
+    pub(super) fn set_media_tools(
+        &mut self,
+        tools: Option<MediaToolPaths>,
+    ) -> Result<(), AppPersistenceError> {
+        if self.update_installation_in_progress() {
+            return Err(AppPersistenceError::InstallationInProgress);
+        }
+        let previous = std::mem::replace(&mut self.media_tools, tools);
+        if let Err(error) = self.persist_app_settings() {
+            self.media_tools = previous;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Directory the file dialog for `purpose` opens in.
+    pub(super) fn recent_dialog_directory(&self, purpose: DialogPurpose) -> Option<PathBuf> {
+        dialog_start_directory(
+            self.recent_dialog_directories
+                .get(&purpose)
+                .map(PathBuf::as_path),
+        )
+    }
+
+    /// Remembers the folder of `selected` for the next dialog opened for
+    /// `purpose`.
+    pub(super) fn remember_dialog_directory(&mut self, purpose: DialogPurpose, selected: &Path) {
+        if self.update_installation_in_progress() {
+            return;
+        }
+        let Some(directory) = remembered_dialog_directory(selected) else {
+            return;
+        };
+        if self.recent_dialog_directories.get(&purpose) == Some(&directory) {
+            return;
+        }
+        self.recent_dialog_directories.insert(purpose, directory);
+        if let Err(error) = self.persist_app_settings() {
+            eprintln!("Failed to save recent folders: {error}");
+        }
+    }
+
+    /// Clears every remembered dialog folder so dialogs open in the home
+    /// directory again.
+    pub(super) fn forget_dialog_directories(&mut self) -> Result<(), AppPersistenceError> {
+        if self.update_installation_in_progress() {
+            return Err(AppPersistenceError::InstallationInProgress);
+        }
+        let previous = std::mem::take(&mut self.recent_dialog_directories);
+        if let Err(error) = self.persist_app_settings() {
+            self.recent_dialog_directories = previous;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn prompt_save_output_as(&self, window: &Window, cx: &Context<Self>) {
+        if self.file_queue.selected_file_locked() {
+            return;
+        }
+        let Some(file) = self.file_queue.selected_file() else {
+            return;
+        };
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let initial_directory = dialog_start_directory(
+            file.output_directory
+                .as_deref()
+                .map(Path::new)
+                .or_else(|| {
+                    self.recent_dialog_directories
+                        .get(&DialogPurpose::SaveOutput)
+                        .map(PathBuf::as_path)
+                })
+                .or(self.default_output_directory.as_deref()),
+        );
+        let dialog = save_output_dialog(
+            window,
+            &file.output_name,
+            &file.config.container,
+            initial_directory.as_deref(),
+        );
+        cx.spawn(async move |this, cx| {
+            let path = pick_save_output_path(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+            let Some((directory, file_name)) = save_path_output_target(&path) else {
+                return;
+            };
+
+            this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::SaveOutput, &path);
+                if root.file_queue.selected_file_locked() {
+                    return;
+                }
+                if root.file_queue.set_selected_output_target(
+                    directory.to_string_lossy().into_owned(),
+                    &file_name,
+                ) {
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     pub(super) fn prompt_subtitle_burn_file(&self, window: &Window, cx: &Context<Self>) {
         if self.file_queue.selected_file_locked() {
             return;
         }
 
-        let dialog = subtitle_file_dialog(window);
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = subtitle_file_dialog(
+            window,
+            self.recent_dialog_directory(DialogPurpose::SubtitleFile)
+                .as_deref(),
+        );
         cx.spawn(async move |this, cx| {
-            let Some(path) = pick_subtitle_file(dialog).await else {
+            let path = pick_subtitle_file(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
                 return;
             };
             if !is_supported_subtitle_path(&path) {
                 return;
             }
-            let path = path.to_string_lossy().to_string();
 
             this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::SubtitleFile, &path);
+                let path = path.to_string_lossy().to_string();
                 if root
                     .update_selected_config(|config| apply_subtitle_burn_path(config, Some(path)))
                 {
@@ -203,6 +475,127 @@ impl FrameRoot {
         true
     }
 
+    pub(super) fn prompt_import_presets(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = import_presets_dialog(
+            window,
+            self.recent_dialog_directory(DialogPurpose::PresetFile)
+                .as_deref(),
+        );
+        cx.spawn(async move |this, cx| {
+            let path = pick_preset_file(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+
+            this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::PresetFile, &path);
+                root.import_presets_from(&path);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    pub(super) fn prompt_export_presets(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = export_presets_dialog(
+            window,
+            self.recent_dialog_directory(DialogPurpose::PresetFile)
+                .as_deref(),
+        );
+        cx.spawn(async move |this, cx| {
+            let path = pick_preset_export_path(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+
+            this.update(cx, |root, cx| {
+                root.remember_dialog_directory(DialogPurpose::PresetFile, &path);
+                root.export_presets_to(&path);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Adds the valid presets of the file at `path` to the library, noting
+    /// which were skipped and why.
+    pub(super) fn import_presets_from(&mut self, path: &Path) -> bool {
+        if self.update_installation_in_progress() {
+            return false;
+        }
+        let import = match import_presets(path, &self.presets) {
+            Ok(import) => import,
+            Err(error) => {
+                self.settings_ui.preset_notice = Some(PresetNotice {
+                    text: format!("Presets not imported: {error}"),
+                    tone: PresetNoticeTone::Error,
+                });
+                return false;
+            }
+        };
+
+        let previous_len = self.presets.len();
+        let previous_sequence = self.settings_ui.next_custom_preset_sequence;
+        for mut preset in import.presets.iter().cloned() {
+            let (id, next_sequence) = self.next_custom_preset_identity();
+            preset.id = id;
+            self.presets.push(preset);
+            self.settings_ui.next_custom_preset_sequence = next_sequence;
+        }
+        if let Err(error) = self.persist_app_settings() {
+            self.presets.truncate(previous_len);
+            self.settings_ui.next_custom_preset_sequence = previous_sequence;
+            self.settings_ui.preset_notice = Some(PresetNotice {
+                text: format!("Presets not imported: {error}"),
+                tone: PresetNoticeTone::Error,
+            });
+            return false;
+        }
+
+        self.settings_ui.preset_notice = Some(PresetNotice {
+            text: import.summary(),
+            tone: if import.skipped.is_empty() {
+                PresetNoticeTone::Success
+            } else {
+                PresetNoticeTone::Error
+            },
+        });
+        !import.presets.is_empty()
+    }
+
+    /// Writes the custom presets to `path`.
+    pub(super) fn export_presets_to(&mut self, path: &Path) -> bool {
+        let (text, tone, exported) = match export_presets(path, &self.presets) {
+            Ok(1) => (
+                "Exported 1 preset".to_string(),
+                PresetNoticeTone::Success,
+                true,
+            ),
+            Ok(count) => (
+                format!("Exported {count} presets"),
+                PresetNoticeTone::Success,
+                true,
+            ),
+            Err(error) => (
+                format!("Presets not exported: {error}"),
+                PresetNoticeTone::Error,
+                false,
+            ),
+        };
+        self.settings_ui.preset_notice = Some(PresetNotice { text, tone });
+        exported
+    }
+
     pub(super) fn apply_preset_to_selected(&mut self, preset_id: &str) -> bool {
         if self.update_installation_in_progress() || self.file_queue.selected_file_locked() {
             return false;
@@ -306,6 +699,76 @@ impl FrameRoot {
         changed
     }
 
+    /// Defaults saved for `container`, if any.
+    pub(super) fn container_defaults(&self, container: &str) -> Option<&ConversionConfig> {
+        self.container_defaults
+            .get(&container.trim().to_ascii_lowercase())
+    }
+
+    /// Saves `config`, retargeted at `container`, as the settings files get
+    /// when they switch to `container`.
+    pub(super) fn set_container_defaults(
+        &mut self,
+        container: &str,
+        config: &ConversionConfig,
+    ) -> Result<(), AppPersistenceError> {
+        let container = container.trim().to_ascii_lowercase();
+        let mut defaults = config.clone();
+        apply_output_container(&mut defaults, &container);
+        normalize_output_config(&mut defaults, None);
+        let previous = self.container_defaults.insert(container.clone(), defaults);
+        if let Err(error) = self.persist_app_settings() {
+            match previous {
+                Some(previous) => self.container_defaults.insert(container, previous),
+                None => self.container_defaults.remove(&container),
+            };
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn save_selected_config_as_container_defaults(&mut self) -> bool {
+        if self.update_installation_in_progress() {
+            return false;
+        }
+        let Some(config) = self.selected_config().cloned() else {
+            return false;
+        };
+        let container = config.container.to_ascii_uppercase();
+        let (text, tone, saved) = match self.set_container_defaults(&config.container, &config) {
+            Ok(()) => (
+                format!("Saved {container} defaults"),
+                PresetNoticeTone::Success,
+                true,
+            ),
+            Err(error) => (
+                format!("Defaults not saved: {error}"),
+                PresetNoticeTone::Error,
+                false,
+            ),
+        };
+        self.settings_ui.preset_notice = Some(PresetNotice { text, tone });
+        saved
+    }
+
+    /// Switches the selected file to `container`, starting from the
+    /// defaults saved for it when there are any.
+    pub(super) fn apply_container_to_selected(&mut self, container: &str) -> bool {
+        let metadata = self.selected_source_metadata();
+        let defaults = self.container_defaults(container).cloned();
+        self.update_selected_config(|config| {
+            let switched = !config.container.eq_ignore_ascii_case(container);
+            let changed = apply_output_container(config, container);
+            match defaults.as_ref().filter(|_| switched) {
+                Some(defaults) => {
+                    apply_container_defaults(config, defaults, metadata.as_ref()) | changed
+                }
+                None => changed | normalize_output_config(config, metadata.as_ref()),
+            }
+        })
+    }
+
     fn next_custom_preset_identity(&self) -> (String, u64) {
         let mut sequence = self.settings_ui.next_custom_preset_sequence;
 
@@ -1,16 +1,18 @@
 use super::{
     ClickEvent, Context, ConversionConfig, DragMoveEvent, FocusHandle, FrameRoot, ParentElement,
-    Render, StatefulInteractiveElement, Styled, Window, apply_image_jpeg_huffman,
-    apply_image_jpeg_quality, apply_image_png_compression, apply_image_png_prediction,
-    apply_image_tiff_compression, apply_image_webp_compression, apply_image_webp_lossless,
-    apply_image_webp_preset, apply_image_webp_quality, apply_pixel_format, color, div,
-    frame_choice_button, frame_list_item_with_caption, frame_slider, frame_slider_handle,
-    image_jpeg_huffman_options, image_png_prediction_options, image_tiff_compression_options,
-    image_webp_preset_options, px, range_fraction, range_value_for_key, range_value_from_fraction,
-    settings_field_label, settings_hint_text, settings_section, settings_value_badge,
-    settings_video_resolution_section, settings_video_scaling_section, theme,
-    timeline_slider_percent_from_bounds, video_pixel_format_options,
+    Render, StatefulInteractiveElement, Styled, Window, apply_image_avif_crf,
+    apply_image_jpeg_huffman, apply_image_jpeg_quality, apply_image_png_compression,
+    apply_image_png_prediction, apply_image_sequence_output, apply_image_tiff_compression,
+    apply_image_webp_compression, apply_image_webp_lossless, apply_image_webp_preset,
+    apply_image_webp_quality, apply_pixel_format, color, div, frame_choice_button,
+    frame_list_item_with_caption, frame_slider, frame_slider_handle, image_jpeg_huffman_options,
+    image_png_prediction_options, image_tiff_compression_options, image_webp_preset_options, px,
+    range_fraction, range_value_for_key, range_value_from_fraction, settings_field_label,
+    settings_hint_text, settings_section, settings_value_badge, settings_video_resolution_section,
+    settings_video_scaling_section, theme, timeline_slider_percent_from_bounds,
+    video_pixel_format_options,
 };
+use frame_core::image_sequence::supports_sequence_output;
 use gpui::{AppContext, InteractiveElement, prelude::FluentBuilder};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -19,6 +21,7 @@ enum SettingsImageRangeTarget {
     WebpQuality,
     WebpCompression,
     PngCompression,
+    AvifCrf,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -74,6 +77,52 @@ pub(in crate::app) fn settings_images_tab(
             window,
             cx,
         ))
+        .when(supports_sequence_output(&config.container), |tab| {
+            tab.child(settings_images_output_section(
+                config,
+                settings_disabled,
+                window,
+                cx,
+            ))
+        })
+}
+
+fn settings_images_output_section(
+    config: &ConversionConfig,
+    disabled: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut grid = div().grid().grid_cols(2).gap_2();
+    for (sequence, label) in [(false, "Single frame"), (true, "Every frame")] {
+        grid = grid.child(
+            frame_choice_button(
+                format!("image-output-mode-{sequence}"),
+                label,
+                config.image_sequence_output == sequence,
+                !disabled,
+                window,
+                cx,
+            )
+            .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                if disabled {
+                    return;
+                }
+                if root
+                    .update_selected_config(|config| apply_image_sequence_output(config, sequence))
+                {
+                    cx.notify();
+                }
+            })),
+        );
+    }
+
+    settings_section("Output")
+        .child(grid)
+        .child(settings_hint_text(
+            "Every frame is written as a numbered file in a folder named after the output.",
+        ))
 }
 
 fn settings_images_pixel_format_section(
@@ -122,6 +171,7 @@ fn settings_images_encoding_section(
         "webp" => settings_image_webp_section(config, settings_disabled, window, cx),
         "png" => settings_image_png_section(config, settings_disabled, window, cx),
         "tiff" => settings_image_tiff_section(config, settings_disabled, window, cx),
+        "avif" => settings_image_avif_section(config, settings_disabled, cx),
         "bmp" => settings_section("BMP encoding")
             .child(settings_hint_text("BMP output is uncompressed.")),
         _ => settings_section("Image encoding").child(settings_hint_text(
@@ -259,6 +309,25 @@ fn settings_image_tiff_section(
     ))
 }
 
+fn settings_image_avif_section(
+    config: &ConversionConfig,
+    settings_disabled: bool,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    settings_section("AVIF encoding").child(settings_image_range_field(
+        "CRF",
+        config.image_avif_crf.to_string(),
+        config.image_avif_crf,
+        0,
+        63,
+        "Best quality",
+        "Smallest",
+        SettingsImageRangeTarget::AvifCrf,
+        settings_disabled,
+        cx,
+    ))
+}
+
 fn settings_image_webp_mode_grid(
     config: &ConversionConfig,
     disabled: bool,
@@ -426,6 +495,7 @@ fn apply_settings_image_range_value(
         SettingsImageRangeTarget::WebpQuality => apply_image_webp_quality(config, value),
         SettingsImageRangeTarget::WebpCompression => apply_image_webp_compression(config, value),
         SettingsImageRangeTarget::PngCompression => apply_image_png_compression(config, value),
+        SettingsImageRangeTarget::AvifCrf => apply_image_avif_crf(config, value),
     }
 }
 
@@ -455,6 +525,7 @@ const fn settings_image_range_slider_id(target: SettingsImageRangeTarget) -> &'s
         SettingsImageRangeTarget::WebpQuality => "settings-image-webp-quality-slider",
         SettingsImageRangeTarget::WebpCompression => "settings-image-webp-compression-slider",
         SettingsImageRangeTarget::PngCompression => "settings-image-png-compression-slider",
+        SettingsImageRangeTarget::AvifCrf => "settings-image-avif-crf-slider",
     }
 }
 
@@ -464,6 +535,7 @@ const fn settings_image_range_slider_label(target: SettingsImageRangeTarget) ->
         SettingsImageRangeTarget::WebpQuality => "WebP quality",
         SettingsImageRangeTarget::WebpCompression => "WebP compression",
         SettingsImageRangeTarget::PngCompression => "PNG compression",
+        SettingsImageRangeTarget::AvifCrf => "AVIF CRF",
     }
 }
 
@@ -473,6 +545,7 @@ const fn settings_image_range_handle_id(target: SettingsImageRangeTarget) -> &'s
         SettingsImageRangeTarget::WebpQuality => "settings-image-webp-quality-handle",
         SettingsImageRangeTarget::WebpCompression => "settings-image-webp-compression-handle",
         SettingsImageRangeTarget::PngCompression => "settings-image-png-compression-handle",
+        SettingsImageRangeTarget::AvifCrf => "settings-image-avif-crf-handle",
     }
 }
 
@@ -1,20 +1,29 @@
 use super::{
-    ClickEvent, Context, ConversionConfig, FocusHandle, FrameRoot, FrameTextInputKind,
-    FrameTextInputSpec, ParentElement, SourceMetadata, StatefulInteractiveElement, Styled, Window,
-    apply_output_container, apply_processing_mode, div, frame_choice_button, frame_text_input,
-    normalize_output_config, output_container_options, output_processing_mode_options,
+    ButtonVariant, ClickEvent, Context, ConversionConfig, FocusHandle, FrameRoot,
+    FrameTextInputKind, FrameTextInputSpec, ParentElement, SourceMetadata,
+    StatefulInteractiveElement, Styled, Window, apply_processing_mode, div, frame_choice_button,
+    frame_text_button, frame_text_input, output_container_options, output_processing_mode_options,
     settings_hint_text, settings_section,
 };
 
+#[expect(
+    clippy::too_many_arguments,
+    reason = "The output tab renders the selected file's name and Save As folder alongside its config."
+)]
 pub(in crate::app) fn settings_output_tab(
     config: &ConversionConfig,
     metadata: Option<&SourceMetadata>,
     settings_disabled: bool,
     output_name: &str,
+    output_directory: Option<&str>,
     output_name_focus: Option<&FocusHandle>,
     window: &mut Window,
     cx: &mut Context<FrameRoot>,
 ) -> gpui::Div {
+    let output_hint = output_directory.map_or_else(
+        || "Output is saved to the default folder selected in Settings.".to_string(),
+        |directory| format!("Output is saved to {directory}."),
+    );
     div()
         .flex()
         .flex_col()
@@ -39,9 +48,13 @@ pub(in crate::app) fn settings_output_tab(
                     window,
                     cx,
                 ))
-                .child(settings_hint_text(
-                    "Output is saved to the default folder selected in Settings.",
-                )),
+                .child(settings_output_location_buttons(
+                    settings_disabled,
+                    output_directory.is_some(),
+                    window,
+                    cx,
+                ))
+                .child(settings_hint_text(&output_hint)),
         )
         .child(
             settings_section("Output container").child(settings_container_grid(
@@ -119,12 +132,8 @@ pub(in crate::app) fn settings_container_grid(
                     return;
                 }
 
-                let metadata = root.selected_source_metadata();
-                let changed = root.update_selected_config(|config| {
-                    apply_output_container(config, &container)
-                        | normalize_output_config(config, metadata.as_ref())
-                });
-                if changed {
+                if root.apply_container_to_selected(&container) {
+                    let metadata = root.selected_source_metadata();
                     root.resolve_selected_settings_tab(metadata.as_ref());
                     cx.notify();
                 }
@@ -134,6 +143,51 @@ pub(in crate::app) fn settings_container_grid(
     grid
 }
 
+fn settings_output_location_buttons(
+    disabled: bool,
+    has_output_directory: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let mut row = div().flex().gap_2().child(
+        frame_text_button(
+            "settings-output-save-as",
+            "Save as…",
+            ButtonVariant::Secondary,
+            false,
+            !disabled,
+            window,
+            cx,
+        )
+        .on_click(cx.listener(move |root, _: &ClickEvent, window, cx| {
+            cx.stop_propagation();
+            if !disabled {
+                root.prompt_save_output_as(window, cx);
+            }
+        })),
+    );
+    if has_output_directory {
+        row = row.child(
+            frame_text_button(
+                "settings-output-default-folder",
+                "Use default folder",
+                ButtonVariant::Secondary,
+                false,
+                !disabled,
+                window,
+                cx,
+            )
+            .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                if !disabled && root.file_queue.clear_selected_output_directory() {
+                    cx.notify();
+                }
+            })),
+        );
+    }
+    row
+}
+
 pub(in crate::app) fn settings_output_name_field(
     output_name: &str,
     disabled: bool,
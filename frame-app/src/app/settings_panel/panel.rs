@@ -3,8 +3,8 @@ use super::{
     ButtonVariant, ClickEvent, Context, FluentBuilder, FrameRoot, FrameSurface, InteractiveElement,
     IntoElement, PANEL_HEADER_HEIGHT, ParentElement, SETTINGS_PANEL_PADDING,
     SETTINGS_TAB_BUTTON_SIZE, SETTINGS_TAB_ICON_SIZE, SettingsPresetsTabState, SettingsRenderState,
-    SettingsSubtitlesTabState, SettingsTab, SettingsVideoInputFocuses, SourceKind,
-    StatefulInteractiveElement, Styled, Window, apply_button_motion, button_colors,
+    SettingsSubtitlesTabState, SettingsTab, SettingsVideoHardware, SettingsVideoInputFocuses,
+    SourceKind, StatefulInteractiveElement, Styled, Window, apply_button_motion, button_colors,
     button_highlight_shadows, button_motion, color, div, frame_tooltip, icon_svg, mix_color,
     panel_bottom_separator, px, resolve_active_settings_tab, settings_audio_filters_tab,
     settings_audio_tab, settings_images_tab, settings_metadata_tab, settings_output_tab,
@@ -199,6 +199,7 @@ pub(in crate::app) fn settings_tab_content(
             settings.metadata,
             settings.settings_disabled,
             settings.output_name,
+            settings.output_directory,
             settings.output_name_focus,
             window,
             cx,
@@ -207,6 +208,10 @@ pub(in crate::app) fn settings_tab_content(
             settings.config,
             settings.settings_disabled,
             settings.available_encoders,
+            SettingsVideoHardware {
+                capabilities: settings.hardware_capabilities,
+                devices: settings.hw_devices,
+            },
             SettingsVideoInputFocuses {
                 width: settings.video_width_focus,
                 height: settings.video_height_focus,
@@ -253,6 +258,9 @@ pub(in crate::app) fn settings_tab_content(
                 config: settings.config,
                 metadata: settings.metadata,
                 settings_disabled: settings.settings_disabled,
+                burn_in_supported: settings
+                    .ffmpeg_capabilities
+                    .is_none_or(|capabilities| capabilities.flags.subtitle_burn_in),
                 subtitle_fonts: settings.subtitle_fonts,
                 focuses: settings.subtitle_focuses,
                 color_focuses: settings.subtitle_color_focuses,
@@ -24,6 +24,7 @@ pub(in crate::app) fn settings_presets_tab(
     window: &mut Window,
     cx: &mut Context<FrameRoot>,
 ) -> gpui::Div {
+    let has_custom_presets = state.presets.iter().any(|preset| !preset.built_in);
     let mut list = div().grid().grid_cols(1);
     for option in preset_options(state.config, state.presets, state.metadata) {
         list = list.child(settings_preset_row(
@@ -47,6 +48,18 @@ pub(in crate::app) fn settings_presets_tab(
             cx,
         ))
         .child(list)
+        .child(settings_presets_transfer_row(
+            state.settings_disabled,
+            has_custom_presets,
+            window,
+            cx,
+        ))
+        .child(settings_container_defaults_button(
+            &state.config.container,
+            state.settings_disabled,
+            window,
+            cx,
+        ))
 }
 
 fn settings_presets_header(notice: Option<&PresetNotice>) -> gpui::Div {
@@ -126,6 +139,81 @@ fn settings_save_preset_button(
     }))
 }
 
+fn settings_presets_transfer_row(
+    settings_disabled: bool,
+    has_custom_presets: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Div {
+    let import_enabled = !settings_disabled;
+    let export_enabled = !settings_disabled && has_custom_presets;
+    div()
+        .flex()
+        .gap_2()
+        .child(
+            frame_text_button(
+                "settings-import-presets",
+                "Import",
+                ButtonVariant::Secondary,
+                false,
+                import_enabled,
+                window,
+                cx,
+            )
+            .flex_1()
+            .on_click(cx.listener(move |root, _: &ClickEvent, window, cx| {
+                cx.stop_propagation();
+                if import_enabled {
+                    root.prompt_import_presets(window, cx);
+                }
+            })),
+        )
+        .child(
+            frame_text_button(
+                "settings-export-presets",
+                "Export",
+                ButtonVariant::Secondary,
+                false,
+                export_enabled,
+                window,
+                cx,
+            )
+            .flex_1()
+            .on_click(cx.listener(move |root, _: &ClickEvent, window, cx| {
+                cx.stop_propagation();
+                if export_enabled {
+                    root.prompt_export_presets(window, cx);
+                }
+            })),
+        )
+}
+
+/// Saves the selected file's settings as the ones files get when they
+/// switch to its container.
+fn settings_container_defaults_button(
+    container: &str,
+    settings_disabled: bool,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
+) -> gpui::Stateful<gpui::Div> {
+    let enabled = !settings_disabled;
+    frame_text_button(
+        "settings-save-container-defaults",
+        format!("Save as {} default", container.to_ascii_uppercase()),
+        ButtonVariant::Secondary,
+        false,
+        enabled,
+        window,
+        cx,
+    )
+    .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+        cx.stop_propagation();
+        if enabled && root.save_selected_config_as_container_defaults() {
+            cx.notify();
+        }
+    }))
+}
+
 fn settings_preset_row(
     option: PresetOption,
     settings_disabled: bool,
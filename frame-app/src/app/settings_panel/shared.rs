@@ -27,7 +27,7 @@ pub(in crate::app) fn settings_value_badge(value: String) -> gpui::Div {
         .child(value)
 }
 
-pub(in crate::app) fn settings_hint_text(text: &'static str) -> gpui::Div {
+pub(in crate::app) fn settings_hint_text(text: &str) -> gpui::Div {
     div()
         .text_size(px(theme::TEXT_LABEL_SIZE))
         .text_color(color(theme::FRAME_GRAY_600))
@@ -89,6 +89,8 @@ pub(in crate::app) struct SettingsSubtitlesTabState<'a> {
     pub(in crate::app) config: &'a ConversionConfig,
     pub(in crate::app) metadata: Option<&'a SourceMetadata>,
     pub(in crate::app) settings_disabled: bool,
+    /// `FFmpeg` has the libass `subtitles` filter, or was not checked yet.
+    pub(in crate::app) burn_in_supported: bool,
     pub(in crate::app) subtitle_fonts: &'a [String],
     pub(in crate::app) focuses: SettingsSubtitleFocuses<'a>,
     pub(in crate::app) color_focuses: SettingsSubtitleColorInputFocuses<'a>,
@@ -173,12 +175,15 @@ pub(in crate::app) fn settings_subtitles_tab(
             .child(settings_subtitle_burn_button(
                 config,
                 burn_in_disabled,
+                state.burn_in_supported,
                 state.focuses.burn_file,
                 window,
                 cx,
             ))
             .child(settings_hint_text(if copy_mode {
                 "Burn-in subtitles are disabled in stream copy mode."
+            } else if !state.burn_in_supported {
+                "Burn-in subtitles need an FFmpeg build with libass."
             } else {
                 "Burning in subtitles will force video re-encoding."
             })),
@@ -227,6 +232,7 @@ pub(in crate::app) fn settings_subtitles_tab(
 fn settings_subtitle_burn_button(
     config: &ConversionConfig,
     disabled: bool,
+    supported: bool,
     burn_file_focus: Option<&FocusHandle>,
     window: &mut Window,
     cx: &mut Context<FrameRoot>,
@@ -243,7 +249,7 @@ fn settings_subtitle_burn_button(
                 .min_w_0()
                 .child(settings_subtitle_load_button(
                     config,
-                    disabled,
+                    disabled || !supported,
                     burn_file_focus,
                     window,
                     cx,
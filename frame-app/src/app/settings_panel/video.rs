@@ -26,6 +26,7 @@ pub(in crate::app) fn settings_video_tab(
     config: &ConversionConfig,
     settings_disabled: bool,
     available_encoders: &AvailableEncoders,
+    hardware: SettingsVideoHardware<'_>,
     focuses: SettingsVideoInputFocuses<'_>,
     window: &mut Window,
     cx: &mut Context<FrameRoot>,
@@ -120,7 +121,13 @@ pub(in crate::app) fn settings_video_tab(
             ))
         })
         .when(is_hardware_video_codec(&config.video_codec), |this| {
-            this.child(settings_video_hw_section(config, settings_disabled, cx))
+            this.child(settings_video_hw_section(
+                config,
+                settings_disabled,
+                hardware,
+                window,
+                cx,
+            ))
         })
 }
 
@@ -846,13 +853,24 @@ fn settings_video_videotoolbox_section(
 
 fn settings_video_hw_section(
     config: &ConversionConfig,
-    disabled: bool,
-    cx: &Context<FrameRoot>,
+    settings_disabled: bool,
+    hardware: SettingsVideoHardware<'_>,
+    window: &mut Window,
+    cx: &mut Context<FrameRoot>,
 ) -> gpui::Div {
-    settings_section("Hardware acceleration").child(settings_video_checkbox_row(
+    let supported = hardware
+        .capabilities
+        .is_none_or(|capabilities| capabilities.supports_hw_decode(&config.video_codec));
+    let disabled = settings_disabled || (!supported && !config.hw_decode);
+    let hint = if supported {
+        "Use GPU for decoding input video (faster)"
+    } else {
+        "Unavailable: this FFmpeg build lacks the matching hardware decoder"
+    };
+    let section = settings_section("Hardware acceleration").child(settings_video_checkbox_row(
         "video-hw-decode",
         "Hardware decoding",
-        "Use GPU for decoding input video (faster)",
+        hint,
         config.hw_decode,
         disabled,
         cx,
@@ -864,7 +882,62 @@ fn settings_video_hw_section(
                 cx.notify();
             }
         },
-    ))
+    ));
+
+    let kind = hw_device_kind(&config.video_codec);
+    let devices = hardware
+        .devices
+        .iter()
+        .filter(|device| Some(device.kind) == kind)
+        .collect::<Vec<_>>();
+    // A single device needs no choice.
+    if devices.len() < 2 {
+        return section;
+    }
+
+    let mut grid = div().grid().grid_cols(2).gap_2().child(
+        frame_choice_button(
+            "video-hw-device-auto",
+            "Automatic",
+            config.hw_device.is_none(),
+            !settings_disabled,
+            window,
+            cx,
+        )
+        .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+            cx.stop_propagation();
+            if settings_disabled {
+                return;
+            }
+            if root.update_selected_config(|config| apply_hw_device(config, None)) {
+                cx.notify();
+            }
+        })),
+    );
+    for device in devices {
+        let value = device.config_value();
+        grid = grid.child(
+            frame_choice_button(
+                format!("video-hw-device-{}", device.index),
+                device.name.clone(),
+                config.hw_device.as_deref() == Some(value.as_str()),
+                !settings_disabled,
+                window,
+                cx,
+            )
+            .on_click(cx.listener(move |root, _: &ClickEvent, _window, cx| {
+                cx.stop_propagation();
+                if settings_disabled {
+                    return;
+                }
+                if root.update_selected_config(|config| apply_hw_device(config, Some(&value))) {
+                    cx.notify();
+                }
+            })),
+        );
+    }
+
+    section.child(settings_field_label("GPU")).child(grid)
 }
 
 fn settings_video_checkbox_row(
@@ -9,17 +9,26 @@ impl FrameRoot {
     #[must_use]
     pub fn new_with_platform_persistence() -> Self {
         let notifier = AppNotifier::system();
-        match AppPersistence::platform() {
+        let root = match AppPersistence::platform() {
             Ok(persistence) => Self::new_with_persistence_and_notifier(persistence, notifier),
             Err(_) => Self::new_inner(None, AppSettings::default(), notifier),
-        }
+        };
+        set_configured_media_tools(root.media_tools.clone());
+        root
     }
 
     pub fn load_runtime_capabilities(&mut self, cx: &mut Context<Self>) {
         cx.spawn(async move |this, cx| {
             let detected = cx
                 .background_spawn(async {
-                    (detect_available_encoders(), detect_available_filters())
+                    (
+                        detect_verified_encoders(),
+                        detect_available_filters(),
+                        detect_hardware_capabilities(),
+                        detect_available_capabilities(),
+                        detect_ffmpeg_info(),
+                        detect_hw_devices(),
+                    )
                 })
                 .await;
 
@@ -34,6 +43,24 @@ impl FrameRoot {
                     Ok(filters) => root.available_filters = filters,
                     Err(error) => eprintln!("Failed to detect FFmpeg filter capabilities: {error}"),
                 }
+                match detected.2 {
+                    Ok(hardware) => root.hardware_capabilities = Some(hardware),
+                    Err(error) => {
+                        eprintln!(
+                            "Failed to detect FFmpeg hardware decoding capabilities: {error}"
+                        );
+                    }
+                }
+                match detected.3 {
+                    Ok(capabilities) => root.ffmpeg_capabilities = Some(capabilities),
+                    Err(error) => eprintln!("Failed to detect FFmpeg build capabilities: {error}"),
+                }
+                match detected.4 {
+                    Ok(info) => root.ffmpeg_info = Some(info),
+                    Err(error) => eprintln!("Failed to detect the FFmpeg version: {error}"),
+                }
+                root.hw_devices = detected.5;
+                root.settings_ui.capabilities_refreshing = false;
                 cx.notify();
             })
             .ok();
@@ -41,6 +68,32 @@ impl FrameRoot {
         .detach();
     }
 
+    /// Drops the cached `FFmpeg` detections and runs them again, e.g. after
+    /// GPU drivers were installed mid-session.
+    pub fn refresh_runtime_capabilities(&mut self, cx: &mut Context<Self>) {
+        if self.settings_ui.capabilities_refreshing {
+            return;
+        }
+        refresh_capabilities();
+        self.settings_ui.capabilities_refreshing = true;
+        self.load_runtime_capabilities(cx);
+    }
+
+    /// Removes conversion logs past [`TaskLogRetention`] in the background.
+    pub fn prune_conversion_logs(&self, cx: &Context<Self>) {
+        let Ok(Some(directory)) = self.conversion_processes.task_log_directory() else {
+            return;
+        };
+        cx.background_spawn(async move {
+            if let Err(error) =
+                prune_task_logs(&directory, TaskLogRetention::default(), SystemTime::now())
+            {
+                eprintln!("Failed to prune conversion logs: {error}");
+            }
+        })
+        .detach();
+    }
+
     #[cfg(test)]
     pub(crate) fn new_with_notifier(notifier: AppNotifier) -> Self {
         Self::new_inner(None, AppSettings::default(), notifier)
@@ -65,6 +118,12 @@ impl FrameRoot {
         notifier: AppNotifier,
     ) -> Self {
         let conversion_processes = ConversionProcessController::default();
+        if let Some(directory) = persistence
+            .as_ref()
+            .and_then(AppPersistence::conversion_log_directory)
+        {
+            let _ = conversion_processes.set_task_log_directory(Some(directory.to_path_buf()));
+        }
         let max_concurrency = if conversion_processes
             .update_max_concurrency(persisted_settings.max_concurrency)
             .is_ok()
@@ -73,6 +132,7 @@ impl FrameRoot {
         } else {
             DEFAULT_MAX_CONCURRENCY
         };
+        let media_tools = persisted_settings.media_tools();
         let presets = merged_presets(persisted_settings.custom_presets);
         let settings_ui = SettingsUiState {
             max_concurrency_draft: max_concurrency.to_string(),
@@ -99,12 +159,35 @@ impl FrameRoot {
             drag_drop_ui: DragDropUiState::default(),
             max_concurrency,
             default_output_directory: persisted_settings.default_output_directory,
+            overwrite_policy: persisted_settings.overwrite_policy,
+            output_name_template: persisted_settings.output_name_template,
+            preserve_file_times: persisted_settings.preserve_file_times,
+            post_action: persisted_settings.post_action,
+            scratch_directory: persisted_settings.scratch_directory,
+            media_tools,
+            notification_mode: persisted_settings.notification_mode,
+            container_defaults: persisted_settings.container_defaults,
+            watch_folders: persisted_settings.watch_folders,
+            recent_dialog_directories: persisted_settings.recent_dialog_directories,
             text_input_ui: FrameTextInputUiState::default(),
             source_metadata: SourceMetadataStore::default(),
             conversion_processes,
             available_encoders: AvailableEncoders::default(),
             available_filters: AvailableFilters::default(),
+            hardware_capabilities: None,
+            ffmpeg_capabilities: None,
+            ffmpeg_info: None,
+            hw_devices: Vec::new(),
             active_conversion_task_ids: Vec::new(),
+            conversion_batch_started_at: None,
+            conversion_task_started_at: HashMap::new(),
+            pending_conversion_tasks: Vec::new(),
+            conversion_output_paths: HashMap::new(),
+            output_conflicts: Vec::new(),
+            output_conflict_prompt_open: false,
+            window_title: None,
+            window_close_state: WindowCloseState::default(),
+            forwarded_source_paths: HashSet::new(),
             notifier,
             subtitle_font_families: frame_core::fonts::list_system_font_families(),
             presets,
@@ -157,15 +240,30 @@ impl FrameRoot {
             return Ok(());
         };
 
-        persistence.save(&AppSettings::from_runtime(
-            self.max_concurrency,
-            self.default_output_directory.clone(),
-            &self.presets,
-            self.auto_update_check,
-            self.update_channel,
-            self.skipped_update_version.clone(),
-            self.last_update_check_at,
-        ))
+        persistence.save(&self.app_settings())
+    }
+
+    /// Settings of the app as they would be saved now.
+    pub(super) fn app_settings(&self) -> AppSettings {
+        AppSettings::from_runtime(&RuntimeAppSettings {
+            max_concurrency: self.max_concurrency,
+            default_output_directory: self.default_output_directory.as_deref(),
+            presets: &self.presets,
+            auto_update_check: self.auto_update_check,
+            update_channel: self.update_channel,
+            skipped_update_version: self.skipped_update_version.as_deref(),
+            last_update_check_at: self.last_update_check_at,
+            overwrite_policy: self.overwrite_policy,
+            output_name_template: self.output_name_template.as_deref(),
+            preserve_file_times: self.preserve_file_times,
+            post_action: self.post_action,
+            scratch_directory: self.scratch_directory.as_deref(),
+            media_tools: self.media_tools.as_ref(),
+            notification_mode: self.notification_mode,
+            container_defaults: &self.container_defaults,
+            watch_folders: &self.watch_folders,
+            recent_dialog_directories: &self.recent_dialog_directories,
+        })
     }
 }
 
@@ -175,18 +273,6 @@ impl Default for FrameRoot {
     }
 }
 
-fn merged_presets(custom_presets: Vec<PresetDefinition>) -> Vec<PresetDefinition> {
-    let mut presets = default_presets();
-
-    for preset in custom_presets {
-        if !presets.iter().any(|existing| existing.id == preset.id) {
-            presets.push(preset);
-        }
-    }
-
-    presets
-}
-
 fn next_custom_preset_sequence(presets: &[PresetDefinition]) -> u64 {
     presets
         .iter()
@@ -23,6 +23,7 @@ use super::primitives::{ButtonVariant, button_colors, frame_highlight_px};
 use super::settings_panel::{hex_to_subtitle_hsv, subtitle_hsv_to_hex};
 use super::*;
 use crate::app_persistence::{AppPersistence, AppSettings};
+use crate::conversion_runner::conversion_task_from_file;
 use crate::notifications::{AppNotifier, ConversionNotificationSummary};
 use crate::preview_engine::{
     PreviewCrop as EnginePreviewCrop, PreviewFrame, render_image_from_frame,
@@ -317,6 +318,27 @@ mod frame_root_conversion {
         assert_eq!(tasks[0].output_directory, "/tmp/frame-output");
     }
 
+    #[test]
+    fn queue_selected_conversion_tasks_applies_template_to_unedited_output_names() {
+        let mut root = root_with_output_directory();
+        root.output_name_template = Some("{name}_{height}p".to_string());
+        root.file_queue
+            .add_file(FileItem::from_path("first", "/tmp/one.mp4", 1));
+        let mut renamed = FileItem::from_path("second", "/tmp/two.mp4", 1);
+        renamed.output_name = "two-final".to_string();
+        root.file_queue.add_file(renamed);
+
+        let tasks = root.queue_selected_conversion_tasks();
+
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.output_template.as_deref())
+                .collect::<Vec<_>>(),
+            [Some("{name}_{height}p"), None]
+        );
+    }
+
     #[test]
     fn queue_selected_conversion_tasks_assigns_and_exposes_unique_output_names() {
         let mut root = FrameRoot::new();
@@ -434,10 +456,79 @@ mod frame_root_conversion {
             [ConversionNotificationSummary {
                 completed_count: 1,
                 error_count: 1,
+                elapsed: None,
             }]
         );
     }
 
+    #[test]
+    fn per_task_notifications_skip_cancelled_tasks_and_the_queue_summary() {
+        let queue_notifications = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let task_notifications = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let received_queue = queue_notifications.clone();
+        let received_tasks = task_notifications.clone();
+        let notifier = AppNotifier::from_conversion_finished_handler(move |summary| {
+            received_queue
+                .lock()
+                .expect("notifications should be writable")
+                .push(summary);
+        })
+        .with_task_finished_handler(move |notification| {
+            received_tasks
+                .lock()
+                .expect("notifications should be writable")
+                .push(notification);
+        });
+        let mut root = FrameRoot::new_with_notifier(notifier);
+        root.notification_mode = NotificationMode::PerTask;
+        root.default_output_directory = Some(PathBuf::from("/tmp/frame-output"));
+        for (id, path) in [
+            ("first", "/tmp/one.mp4"),
+            ("second", "/tmp/two.mp4"),
+            ("third", "/tmp/three.mp4"),
+        ] {
+            root.file_queue.add_file(FileItem::from_path(id, path, 1));
+        }
+        root.queue_selected_conversion_tasks();
+        root.active_conversion_task_ids = vec![
+            "first".to_string(),
+            "second".to_string(),
+            "third".to_string(),
+        ];
+        root.is_processing = true;
+
+        root.apply_conversion_event(ConversionEvent::started("first", "/tmp/out/one.mp4"));
+        root.apply_conversion_event(ConversionEvent::completed("first", "/tmp/out/one.mp4"));
+        root.file_queue.mark_file_cancelling("second");
+        root.apply_conversion_event(ConversionEvent::error("second", "ffmpeg was stopped"));
+        root.apply_conversion_event(ConversionEvent::error("third", "ffmpeg failed"));
+
+        let task_notifications = task_notifications
+            .lock()
+            .expect("notifications should be readable");
+        assert_eq!(
+            task_notifications
+                .iter()
+                .map(|notification| (notification.file_name.as_str(), notification.title()))
+                .collect::<Vec<_>>(),
+            [
+                ("one.mp4", "Conversion Finished"),
+                ("three.mp4", "Conversion Failed")
+            ]
+        );
+        assert!(task_notifications[0].elapsed.is_some());
+        assert_eq!(
+            task_notifications[0].output_path(),
+            Some(Path::new("/tmp/out/one.mp4"))
+        );
+        assert!(
+            queue_notifications
+                .lock()
+                .expect("notifications should be readable")
+                .is_empty()
+        );
+    }
+
     #[test]
     fn apply_conversion_event_does_not_notify_when_active_batch_has_no_results() {
         let notifications = std::sync::Arc::new(Mutex::new(Vec::new()));
@@ -505,6 +596,169 @@ mod frame_root_conversion {
         );
     }
 
+    fn pending_watch_folder_file(root: &mut FrameRoot, id: &str, path: &str) {
+        let mut file = FileItem::from_path(id, path, 1);
+        file.status = FileStatus::Queued;
+        root.pending_conversion_tasks
+            .push(conversion_task_from_file(&file, "/tmp/frame-output"));
+        root.file_queue.add_file(file);
+    }
+
+    #[test]
+    fn watch_folder_tasks_wait_for_the_running_batch() {
+        let mut root = FrameRoot::new();
+        root.file_queue
+            .add_file(FileItem::from_path("manual", "/tmp/manual.mp4", 1));
+        root.file_queue
+            .update_status("manual", FileStatus::Converting, 10);
+        root.active_conversion_task_ids = vec!["manual".to_string()];
+        root.is_processing = true;
+        pending_watch_folder_file(&mut root, "watched", "/tmp/watch/render.mov");
+
+        assert!(root.take_startable_conversion_tasks().is_none());
+
+        root.apply_conversion_event(ConversionEvent::completed("manual", "/tmp/out/manual.mp4"));
+        assert!(!root.is_processing);
+        let tasks = root
+            .take_startable_conversion_tasks()
+            .expect("watch folder tasks should start once the batch is done");
+
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.id.as_str())
+                .collect::<Vec<_>>(),
+            ["watched"]
+        );
+        assert_eq!(root.active_conversion_task_ids, ["watched"]);
+        assert!(root.is_processing);
+        assert!(root.pending_conversion_tasks.is_empty());
+    }
+
+    #[test]
+    fn existing_outputs_are_asked_about_once_per_failed_file() {
+        let mut root = FrameRoot::new();
+        for id in ["first", "second", "removed"] {
+            root.file_queue
+                .add_file(FileItem::from_path(id, format!("/tmp/{id}.mov"), 1));
+            root.file_queue.update_status(id, FileStatus::Converting, 0);
+        }
+        for id in ["first", "removed"] {
+            root.apply_conversion_event(ConversionEvent::failed(
+                id,
+                &frame_core::error::ConversionError::OutputExists(format!("/out/{id}.mp4")),
+                Vec::new(),
+                None,
+            ));
+        }
+        root.apply_conversion_event(ConversionEvent::error("second", "Encoder failed"));
+        root.file_queue.remove_file("removed");
+
+        let (conflict, file_name) = root
+            .take_next_output_conflict()
+            .expect("the existing output should be asked about");
+
+        assert_eq!(conflict.task_id, "first");
+        assert_eq!(conflict.output_path, "/out/first.mp4");
+        assert_eq!(file_name, "first.mov");
+        assert!(root.take_next_output_conflict().is_none());
+    }
+
+    #[test]
+    fn cancelling_a_waiting_watch_folder_task_drops_it() {
+        let mut root = FrameRoot::new();
+        pending_watch_folder_file(&mut root, "watched", "/tmp/watch/render.mov");
+
+        assert!(root.cancel_conversion_task("watched"));
+
+        assert!(root.pending_conversion_tasks.is_empty());
+        assert_eq!(
+            root.file_queue
+                .file_by_id("watched")
+                .map(|file| file.status),
+            Some(FileStatus::Idle)
+        );
+    }
+
+    #[test]
+    fn cancel_all_conversions_drops_held_back_tasks_and_cancels_the_batch() {
+        let mut root = FrameRoot::new();
+        root.file_queue
+            .add_file(FileItem::from_path("first", "/tmp/one.mp4", 1));
+        root.file_queue
+            .update_status("first", FileStatus::Queued, 0);
+        root.active_conversion_task_ids = vec!["first".to_string()];
+        pending_watch_folder_file(&mut root, "watched", "/tmp/watch/render.mov");
+
+        root.cancel_all_conversions();
+
+        assert!(root.pending_conversion_tasks.is_empty());
+        assert!(root.conversion_processes.is_cancelled("first"));
+    }
+
+    #[test]
+    fn running_outputs_are_excluded_and_finished_ones_recorded() {
+        let folder =
+            std::env::temp_dir().join(format!("frame-watch-output-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).expect("watch folder should be created");
+        let output = folder.join("render 1080p.mp4");
+        std::fs::write(&output, b"out").expect("output should be written");
+        let mut root = FrameRoot::new();
+        root.watch_folders = vec![WatchFolder::new(
+            folder.clone(),
+            ConversionConfig::default(),
+            folder.clone(),
+        )];
+        root.file_queue
+            .add_file(FileItem::from_path("first", "/tmp/one.mp4", 1));
+
+        root.apply_conversion_event(ConversionEvent::started("first", output.to_string_lossy()));
+        assert_eq!(root.conversion_output_paths.get("first"), Some(&output));
+
+        root.apply_conversion_event(ConversionEvent::completed(
+            "first",
+            output.to_string_lossy(),
+        ));
+        let _ = std::fs::remove_dir_all(&folder);
+
+        assert!(root.conversion_output_paths.is_empty());
+        assert_eq!(
+            root.watch_folders[0]
+                .processed_files
+                .iter()
+                .map(|file| &file.path)
+                .collect::<Vec<_>>(),
+            [&output]
+        );
+    }
+
+    #[test]
+    fn watch_folders_take_the_selected_output_settings_only() {
+        let folder = std::env::temp_dir();
+        let mut root = FrameRoot::new();
+        let mut file = FileItem::from_path("first", "/tmp/one.mp4", 1);
+        file.config.container = "webm".to_string();
+        file.config.start_time = Some("00:00:05".to_string());
+        root.file_queue.add_file(file);
+
+        assert_eq!(
+            root.add_watch_folder_with_selected_settings(folder.clone()),
+            Err("Choose a default output folder first.".to_string())
+        );
+
+        root.default_output_directory = Some(PathBuf::from("/tmp/frame-output"));
+        assert_eq!(
+            root.add_watch_folder_with_selected_settings(folder.clone()),
+            Ok(())
+        );
+        assert_eq!(root.watch_folders[0].config.container, "webm");
+        assert_eq!(root.watch_folders[0].config.start_time, None);
+        assert!(
+            root.add_watch_folder_with_selected_settings(folder)
+                .is_err()
+        );
+    }
+
     #[test]
     fn cancel_conversion_task_keeps_source_until_runner_confirms_cancellation() {
         let mut root = FrameRoot::new();
@@ -593,6 +847,53 @@ mod frame_root_conversion {
         assert_eq!(root.settings_ui.next_custom_preset_sequence, 7);
     }
 
+    #[test]
+    fn container_defaults_persist_and_apply_when_switching_container() {
+        let persistence = AppPersistence::from_settings_path(test_settings_path());
+        let mut root = FrameRoot::new_with_persistence(persistence.clone());
+        root.file_queue
+            .add_file(FileItem::from_path("first", "/tmp/one.mp4", 1));
+        root.set_container_defaults(
+            "GIF",
+            &ConversionConfig {
+                fps: "12".to_string(),
+                gif_colors: 64,
+                ..ConversionConfig::default()
+            },
+        )
+        .expect("container defaults should persist");
+        root.update_selected_config(|config| {
+            config.start_time = Some("00:00:03".to_string());
+            true
+        });
+
+        assert!(root.apply_container_to_selected("gif"));
+        let config = root.selected_config().expect("file should be selected");
+        assert_eq!(config.container, "gif");
+        assert_eq!(config.video_codec, "gif");
+        assert_eq!(config.gif_colors, 64);
+        assert_eq!(config.start_time.as_deref(), Some("00:00:03"));
+
+        root.update_selected_config(|config| {
+            config.gif_colors = 128;
+            true
+        });
+        root.apply_container_to_selected("gif");
+        assert_eq!(
+            root.selected_config().map(|config| config.gif_colors),
+            Some(128)
+        );
+
+        let reloaded = FrameRoot::new_with_persistence(persistence);
+        assert_eq!(
+            reloaded
+                .container_defaults("gif")
+                .map(|config| (config.container.as_str(), config.fps.as_str())),
+            Some(("gif", "12"))
+        );
+        assert!(reloaded.container_defaults("mp4").is_none());
+    }
+
     #[test]
     fn default_output_directory_persists_for_future_sessions() {
         let persistence = AppPersistence::from_settings_path(test_settings_path());
@@ -614,6 +915,102 @@ mod frame_root_conversion {
         );
     }
 
+    #[test]
+    fn overwrite_policy_persists_and_applies_to_new_tasks() {
+        let persistence = AppPersistence::from_settings_path(test_settings_path());
+        let mut root = FrameRoot::new_with_persistence(persistence.clone());
+
+        root.set_overwrite_policy(OverwritePolicy::Fail)
+            .expect("overwrite policy should persist");
+
+        let reloaded = FrameRoot::new_with_persistence(persistence);
+        assert_eq!(reloaded.overwrite_policy, OverwritePolicy::Fail);
+        let task = reloaded.conversion_task_for_file(
+            &FileItem::from_path("clip", "/tmp/clip.mov", 1),
+            "/tmp/frame-output",
+            &reloaded.app_settings(),
+        );
+        assert_eq!(task.config.overwrite_policy, OverwritePolicy::Fail);
+    }
+
+    #[test]
+    fn scratch_directory_is_validated_before_it_persists() {
+        let persistence = AppPersistence::from_settings_path(test_settings_path());
+        let mut root = FrameRoot::new_with_persistence(persistence.clone());
+        let scratch = std::env::temp_dir()
+            .canonicalize()
+            .expect("temp directory should resolve");
+
+        root.set_scratch_directory(Some(scratch.clone()))
+            .expect("temp directory should be accepted");
+        let missing = root.set_scratch_directory(Some(scratch.join("frame-missing-scratch")));
+
+        assert!(matches!(
+            missing,
+            Err(crate::app_persistence::AppPersistenceError::InvalidScratchDirectory(_))
+        ));
+        assert_eq!(
+            FrameRoot::new_with_persistence(persistence.clone()).scratch_directory,
+            Some(scratch)
+        );
+        root.set_scratch_directory(None)
+            .expect("scratch directory should reset");
+        assert_eq!(
+            FrameRoot::new_with_persistence(persistence).scratch_directory,
+            None
+        );
+    }
+
+    #[test]
+    fn chosen_media_tools_persist_until_the_bundled_ones_are_restored() {
+        let persistence = AppPersistence::from_settings_path(test_settings_path());
+        let mut root = FrameRoot::new_with_persistence(persistence.clone());
+        let tools = MediaToolPaths::beside_ffmpeg(PathBuf::from("/usr/bin/ffmpeg"));
+
+        root.set_media_tools(Some(tools.clone()))
+            .expect("media tools should be saved");
+
+        let saved = persistence.load().expect("settings should be readable");
+        assert_eq!(saved.ffprobe_path, Some(PathBuf::from("/usr/bin/ffprobe")));
+        assert_eq!(
+            FrameRoot::new_with_persistence(persistence.clone()).media_tools,
+            Some(tools)
+        );
+        root.set_media_tools(None)
+            .expect("bundled media tools should be restored");
+        assert_eq!(
+            FrameRoot::new_with_persistence(persistence).media_tools,
+            None
+        );
+    }
+
+    #[test]
+    fn dialog_directories_are_remembered_per_purpose_and_forgotten() {
+        let persistence = AppPersistence::from_settings_path(test_settings_path());
+        let mut root = FrameRoot::new_with_persistence(persistence.clone());
+        let sources = std::env::temp_dir();
+
+        root.remember_dialog_directory(DialogPurpose::OpenInput, &sources.join("clip.mov"));
+
+        let mut reopened = FrameRoot::new_with_persistence(persistence.clone());
+        assert_eq!(
+            reopened.recent_dialog_directory(DialogPurpose::OpenInput),
+            Some(sources)
+        );
+        assert_eq!(
+            reopened.recent_dialog_directory(DialogPurpose::SubtitleFile),
+            dialog_start_directory(None)
+        );
+        reopened
+            .forget_dialog_directories()
+            .expect("recent folders should be cleared");
+        assert!(
+            FrameRoot::new_with_persistence(persistence)
+                .recent_dialog_directories
+                .is_empty()
+        );
+    }
+
     #[test]
     fn apply_max_concurrency_draft_updates_live_controller_limit() {
         let mut root = FrameRoot::new();
@@ -2206,6 +2603,9 @@ mod frame_root_config {
                     label: None,
                     bitrate_kbps: None,
                     sample_rate: Some("48000".to_string()),
+                    channel_layout: None,
+                    is_default: false,
+                    is_forced: false,
                 }],
                 ..SourceMetadata::default()
             },
@@ -3626,6 +4026,7 @@ mod preview_shell {
             metadata_error: None,
             settings_disabled: false,
             output_name: "",
+            output_directory: None,
             output_name_focus: None,
             audio_bitrate_focus: None,
             video_width_focus: None,
@@ -3660,6 +4061,9 @@ mod preview_shell {
             subtitle_fonts: &[],
             available_encoders: empty_encoders(),
             available_filters: empty_filters(),
+            hardware_capabilities: None,
+            ffmpeg_capabilities: None,
+            hw_devices: &[],
         }
     }
 
@@ -51,6 +51,8 @@ impl FrameRoot {
         self.conversion_events = ConversionEventState::new();
         self.source_metadata = SourceMetadataStore::default();
         self.active_conversion_task_ids.clear();
+        self.conversion_batch_started_at = None;
+        self.conversion_task_started_at.clear();
         self.is_processing = false;
 
         if let Err(error) = store.consume() {
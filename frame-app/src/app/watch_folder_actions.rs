@@ -0,0 +1,236 @@
+use super::*;
+
+impl FrameRoot {
+    /// Watches `path` and queues new media files with `config`, writing
+    /// outputs to `output_directory`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when an update is being installed or the watch folder
+    /// list cannot be saved.
+    pub fn add_watch_folder(
+        &mut self,
+        path: PathBuf,
+        config: ConversionConfig,
+        output_directory: PathBuf,
+    ) -> Result<bool, crate::app_persistence::AppPersistenceError> {
+        if self.update_installation_in_progress() {
+            return Err(crate::app_persistence::AppPersistenceError::InstallationInProgress);
+        }
+        if !path.is_dir() || self.watch_folders.iter().any(|folder| folder.path == path) {
+            return Ok(false);
+        }
+
+        self.watch_folders
+            .push(WatchFolder::new(path, config, output_directory));
+        if let Err(error) = self.persist_app_settings() {
+            self.watch_folders.pop();
+            return Err(error);
+        }
+
+        Ok(true)
+    }
+
+    /// Stops watching `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the watch folder list cannot be saved.
+    pub fn remove_watch_folder(
+        &mut self,
+        path: &std::path::Path,
+    ) -> Result<bool, crate::app_persistence::AppPersistenceError> {
+        let Some(index) = self
+            .watch_folders
+            .iter()
+            .position(|folder| folder.path == path)
+        else {
+            return Ok(false);
+        };
+
+        let removed = self.watch_folders.remove(index);
+        if let Err(error) = self.persist_app_settings() {
+            self.watch_folders.insert(index, removed);
+            return Err(error);
+        }
+
+        Ok(true)
+    }
+
+    pub(super) fn prompt_add_watch_folder(&self, window: &Window, cx: &Context<Self>) {
+        let Some(claim) = claim_native_dialog() else {
+            return;
+        };
+        let dialog = watch_folder_dialog(window, self.default_output_directory.as_deref());
+        cx.spawn(async move |this, cx| {
+            let path = pick_output_folder(dialog).await;
+            drop(claim);
+            let Some(path) = path else {
+                return;
+            };
+
+            this.update(cx, |root, cx| {
+                root.settings_ui.watch_folders_error =
+                    root.add_watch_folder_with_selected_settings(path).err();
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Watches `path` with the selected file's output settings, or the
+    /// defaults when no file is selected, writing to the default output
+    /// folder. Trims, tracks and other settings tied to the selected source
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message for the settings sheet when there is no default
+    /// output folder, the folder is already watched or settings cannot be
+    /// saved.
+    pub(super) fn add_watch_folder_with_selected_settings(
+        &mut self,
+        path: PathBuf,
+    ) -> Result<(), String> {
+        let Some(output_directory) = self.default_output_directory.clone() else {
+            return Err("Choose a default output folder first.".to_string());
+        };
+        let mut config = self.selected_config().cloned().unwrap_or_default();
+        copy_source_settings(&ConversionConfig::default(), &mut config);
+
+        match self.add_watch_folder(path, config, output_directory) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("That folder is already watched or no longer exists.".to_string()),
+            Err(error) => Err(format!("Failed to save settings: {error}")),
+        }
+    }
+
+    pub fn start_watch_folder_polling(&self, cx: &Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let mut scanner = WatchFolderScanner::default();
+            loop {
+                cx.background_executor()
+                    .timer(WATCH_FOLDER_POLL_INTERVAL)
+                    .await;
+                let Ok((folders, excluded)) = this.update(cx, |root, _cx| {
+                    let excluded = root
+                        .conversion_output_paths
+                        .values()
+                        .cloned()
+                        .collect::<HashSet<_>>();
+                    (root.watch_folders.clone(), excluded)
+                }) else {
+                    return;
+                };
+                if folders.is_empty() {
+                    continue;
+                }
+
+                let (candidates, missing, returned_scanner) = cx
+                    .background_spawn(async move {
+                        let candidates = scanner.scan(&folders, &excluded, Instant::now());
+                        let missing = missing_processed_files(&folders);
+                        (candidates, missing, scanner)
+                    })
+                    .await;
+                scanner = returned_scanner;
+                if candidates.is_empty() && missing.is_empty() {
+                    continue;
+                }
+
+                if this
+                    .update(cx, |root, cx| {
+                        root.queue_watch_folder_candidates(candidates, &missing, cx);
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Queues `candidates` to start once no batch is running, after
+    /// forgetting the processed files in `missing` that were deleted.
+    fn queue_watch_folder_candidates(
+        &mut self,
+        candidates: Vec<WatchFolderCandidate>,
+        missing: &HashSet<PathBuf>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.update_installation_in_progress() {
+            return;
+        }
+
+        for folder in &mut self.watch_folders {
+            folder.forget_processed(missing);
+        }
+        let settings = self.app_settings();
+        for candidate in candidates {
+            let Some(folder) = self
+                .watch_folders
+                .iter_mut()
+                .find(|folder| folder.path == candidate.folder_path)
+            else {
+                continue;
+            };
+            folder.mark_processed(candidate.file_path.clone(), candidate.modified_at);
+            let config = folder.config.clone();
+            let output_directory = folder.output_directory.to_string_lossy().into_owned();
+
+            let id = self.next_file_id();
+            let mut file = FileItem::from_os_path(id, &candidate.file_path);
+            file.config = config;
+            file.status = FileStatus::Queued;
+            let mut task = self.conversion_task_for_file(&file, &output_directory, &settings);
+            // Watched files always take the naming template.
+            task.output_template.clone_from(&self.output_name_template);
+
+            let (file_id, file_path) = (file.id.clone(), file.path.clone());
+            self.file_queue.add_file(file);
+            self.queue_source_metadata_probe(file_id, file_path, cx);
+            self.pending_conversion_tasks.push(task);
+        }
+
+        if let Err(error) = self.persist_app_settings() {
+            eprintln!("Failed to save watch folder state: {error}");
+        }
+        self.start_pending_conversion_tasks(cx);
+        cx.notify();
+    }
+
+    /// Remembers the output each running task writes, so watch folder
+    /// scans skip it, and marks finished outputs as processed in the watch
+    /// folders they were written to.
+    pub(super) fn track_conversion_output(&mut self, event: &ConversionEvent) {
+        match event {
+            ConversionEvent::Started(payload) => {
+                self.conversion_output_paths
+                    .insert(payload.id.clone(), PathBuf::from(&payload.output_path));
+            }
+            ConversionEvent::Completed(payload) => {
+                self.conversion_output_paths.remove(&payload.id);
+                let mut recorded = false;
+                for output in std::iter::once(&payload.output_path).chain(&payload.outputs) {
+                    recorded |=
+                        record_watch_folder_output(&mut self.watch_folders, Path::new(output));
+                }
+                if recorded && let Err(error) = self.persist_app_settings() {
+                    eprintln!("Failed to save watch folder state: {error}");
+                }
+            }
+            ConversionEvent::Error(payload) => {
+                self.conversion_output_paths.remove(&payload.id);
+            }
+            ConversionEvent::Cancelled(payload) => {
+                self.conversion_output_paths.remove(&payload.id);
+            }
+            ConversionEvent::Progress(_)
+            | ConversionEvent::Log(_)
+            | ConversionEvent::LogBatch(_)
+            | ConversionEvent::Warning(_) => {}
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+
+/// Closing the window while conversions run, which stops them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) enum WindowCloseState {
+    #[default]
+    Open,
+    /// The stop-conversions prompt is shown, or the conversions it stopped
+    /// are still exiting.
+    Confirming,
+    /// The user chose to close anyway.
+    Confirmed,
+}
+
+impl FrameRoot {
+    /// Shows the progress of the running batch in the window title, which
+    /// stays visible in the taskbar or dock while the window is minimized.
+    pub(super) fn sync_window_title(&mut self, window: &mut Window) {
+        let summary = self
+            .is_processing
+            .then(|| queue_status_for_task_ids(&self.file_queue, &self.active_conversion_task_ids))
+            .flatten()
+            .map(|status| {
+                status.summary(
+                    self.conversion_batch_started_at
+                        .map_or(Duration::ZERO, |started_at| started_at.elapsed()),
+                )
+            });
+        let title = queue_window_title(summary.as_deref());
+        if self.window_title.as_deref() != Some(title.as_str()) {
+            window.set_window_title(&title);
+            self.window_title = Some(title);
+        }
+    }
+
+    /// Whether the window may close now. While conversions run the user is
+    /// asked first; once they confirm, the conversions are cancelled and the
+    /// window closes after their processes exit.
+    pub(super) fn window_should_close(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        match self.window_close_state {
+            WindowCloseState::Confirmed => return true,
+            WindowCloseState::Confirming => return false,
+            WindowCloseState::Open if !self.is_processing => return true,
+            WindowCloseState::Open => {}
+        }
+
+        let running = queue_status_for_task_ids(&self.file_queue, &self.active_conversion_task_ids)
+            .map_or(0, |status| status.unsettled());
+        let detail = match running {
+            0 | 1 => "A conversion is still running. Closing Frame stops it.".to_string(),
+            count => format!("{count} conversions are still running. Closing Frame stops them."),
+        };
+        self.window_close_state = WindowCloseState::Confirming;
+        let receiver = window.prompt(
+            PromptLevel::Warning,
+            "Stop conversions and close?",
+            Some(&detail),
+            &[
+                PromptButton::ok("Close"),
+                PromptButton::cancel("Keep Running"),
+            ],
+            cx,
+        );
+
+        cx.spawn_in(window, async move |this, cx| {
+            let confirmed = matches!(receiver.await, Ok(0));
+            let Ok(Some(controller)) = this.update_in(cx, |root, _, _| {
+                if confirmed {
+                    root.cancel_all_conversions();
+                    Some(root.conversion_processes.clone())
+                } else {
+                    root.window_close_state = WindowCloseState::Open;
+                    None
+                }
+            }) else {
+                return;
+            };
+
+            // Closing the last window quits, so the process trees get a
+            // moment to exit and clean up their partial outputs first.
+            let exited = cx
+                .background_spawn(async move {
+                    controller.wait_for_active_processes(CANCEL_EXIT_TIMEOUT)
+                })
+                .await;
+            if !matches!(exited, Ok(true)) {
+                eprintln!("Closing Frame before every conversion process exited");
+            }
+            this.update_in(cx, |root, window, _| {
+                root.window_close_state = WindowCloseState::Confirmed;
+                window.remove_window();
+            })
+            .ok();
+        })
+        .detach();
+        false
+    }
+}
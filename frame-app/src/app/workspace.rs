@@ -108,9 +108,9 @@ pub(super) fn welcome_view(window: &mut Window, cx: &mut Context<FrameRoot>) ->
                                 cx,
                             )
                             .on_click(cx.listener(
-                                |_root, _: &ClickEvent, window, cx| {
+                                |root, _: &ClickEvent, window, cx| {
                                     cx.stop_propagation();
-                                    FrameRoot::prompt_add_source(window, cx);
+                                    root.prompt_add_source(window, cx);
                                 },
                             )),
                         )
@@ -126,9 +126,9 @@ pub(super) fn welcome_view(window: &mut Window, cx: &mut Context<FrameRoot>) ->
                                 cx,
                             )
                             .on_click(cx.listener(
-                                |_root, _: &ClickEvent, window, cx| {
+                                |root, _: &ClickEvent, window, cx| {
                                     cx.stop_propagation();
-                                    FrameRoot::prompt_add_source_folder(window, cx);
+                                    root.prompt_add_source_folder(window, cx);
                                 },
                             )),
                         ),
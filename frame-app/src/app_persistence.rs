@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     fs,
     fs::File,
     io::{self, Write},
@@ -7,15 +7,25 @@ use std::{
 };
 
 use directories::ProjectDirs;
-use frame_core::types::DEFAULT_MAX_CONCURRENCY;
+use frame_core::types::{DEFAULT_MAX_CONCURRENCY, OverwritePolicy, PostAction};
 use frame_updater::UpdateChannel;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::settings::PresetDefinition;
+use crate::{
+    native_dialogs::DialogPurpose,
+    notifications::NotificationMode,
+    runtime_binaries::MediaToolPaths,
+    settings::{ConversionConfig, PresetDefinition},
+    watch_folders::WatchFolder,
+};
 
+/// Bumped only when a persisted field changes meaning or format. Added
+/// fields are read with `#[serde(default)]` and leave it unchanged.
 const APP_SETTINGS_VERSION: u32 = 3;
 const SETTINGS_FILE_NAME: &str = "settings.json";
+const LOG_DIRECTORY_NAME: &str = "logs";
+const CONVERSION_LOG_DIRECTORY_NAME: &str = "conversions";
 const LEGACY_APP_SETTINGS_FILE_NAME: &str = "app-settings.dat";
 const LEGACY_PRESETS_FILE_NAME: &str = "presets.dat";
 
@@ -28,35 +38,97 @@ pub struct AppSettings {
     pub update_channel: UpdateChannel,
     pub skipped_update_version: Option<String>,
     pub last_update_check_at: Option<u64>,
+    pub overwrite_policy: OverwritePolicy,
+    /// Filename template applied to files whose output name was not edited.
+    pub output_name_template: Option<String>,
+    /// Copies source file times to finished outputs.
+    pub preserve_file_times: bool,
+    /// Applied to each source after its output is verified.
+    pub post_action: PostAction,
+    /// Directory for large intermediates; `None` uses the OS temp directory.
+    pub scratch_directory: Option<PathBuf>,
+    /// `FFmpeg` used instead of the bundled one; `None` uses the bundled one.
+    pub ffmpeg_path: Option<PathBuf>,
+    /// `FFprobe` paired with [`Self::ffmpeg_path`].
+    pub ffprobe_path: Option<PathBuf>,
+    /// Which finished conversions are announced with a notification.
+    pub notification_mode: NotificationMode,
+    /// Settings applied when the output container is switched, keyed by
+    /// lowercase container.
+    pub container_defaults: BTreeMap<String, ConversionConfig>,
+    pub watch_folders: Vec<WatchFolder>,
+    /// Last directory chosen in each kind of file dialog.
+    pub recent_dialog_directories: BTreeMap<DialogPurpose, PathBuf>,
+}
+
+/// Runtime state of the app that is saved as [`AppSettings`].
+#[derive(Clone, Copy, Debug)]
+pub struct RuntimeAppSettings<'a> {
+    pub max_concurrency: usize,
+    pub default_output_directory: Option<&'a Path>,
+    /// Every preset of the app; only the custom ones are saved.
+    pub presets: &'a [PresetDefinition],
+    pub auto_update_check: bool,
+    pub update_channel: UpdateChannel,
+    pub skipped_update_version: Option<&'a str>,
+    pub last_update_check_at: Option<u64>,
+    pub overwrite_policy: OverwritePolicy,
+    pub output_name_template: Option<&'a str>,
+    pub preserve_file_times: bool,
+    pub post_action: PostAction,
+    pub scratch_directory: Option<&'a Path>,
+    pub media_tools: Option<&'a MediaToolPaths>,
+    pub notification_mode: NotificationMode,
+    pub container_defaults: &'a BTreeMap<String, ConversionConfig>,
+    pub watch_folders: &'a [WatchFolder],
+    pub recent_dialog_directories: &'a BTreeMap<DialogPurpose, PathBuf>,
 }
 
 impl AppSettings {
     #[must_use]
-    pub fn from_runtime(
-        max_concurrency: usize,
-        default_output_directory: Option<PathBuf>,
-        presets: &[PresetDefinition],
-        auto_update_check: bool,
-        update_channel: UpdateChannel,
-        skipped_update_version: Option<String>,
-        last_update_check_at: Option<u64>,
-    ) -> Self {
+    pub fn from_runtime(runtime: &RuntimeAppSettings<'_>) -> Self {
         Self {
-            max_concurrency: valid_max_concurrency(max_concurrency),
-            default_output_directory,
+            max_concurrency: valid_max_concurrency(runtime.max_concurrency),
+            default_output_directory: runtime.default_output_directory.map(Path::to_path_buf),
             custom_presets: normalize_custom_presets(
-                presets
+                runtime
+                    .presets
                     .iter()
                     .filter(|preset| !preset.built_in)
                     .cloned()
                     .collect(),
             ),
-            auto_update_check,
-            update_channel,
-            skipped_update_version,
-            last_update_check_at,
+            auto_update_check: runtime.auto_update_check,
+            update_channel: runtime.update_channel,
+            skipped_update_version: runtime.skipped_update_version.map(str::to_string),
+            last_update_check_at: runtime.last_update_check_at,
+            overwrite_policy: runtime.overwrite_policy,
+            output_name_template: runtime.output_name_template.map(str::to_string),
+            preserve_file_times: runtime.preserve_file_times,
+            post_action: runtime.post_action,
+            scratch_directory: runtime.scratch_directory.map(Path::to_path_buf),
+            ffmpeg_path: runtime.media_tools.map(|tools| tools.ffmpeg.clone()),
+            ffprobe_path: runtime.media_tools.map(|tools| tools.ffprobe.clone()),
+            notification_mode: runtime.notification_mode,
+            container_defaults: runtime.container_defaults.clone(),
+            watch_folders: runtime.watch_folders.to_vec(),
+            recent_dialog_directories: runtime.recent_dialog_directories.clone(),
         }
     }
+
+    /// Tools chosen instead of the bundled ones, or `None` for the bundled
+    /// ones.
+    #[must_use]
+    pub fn media_tools(&self) -> Option<MediaToolPaths> {
+        let ffmpeg = self.ffmpeg_path.clone()?;
+        Some(match &self.ffprobe_path {
+            Some(ffprobe) => MediaToolPaths {
+                ffmpeg,
+                ffprobe: ffprobe.clone(),
+            },
+            None => MediaToolPaths::beside_ffmpeg(ffmpeg),
+        })
+    }
 }
 
 impl Default for AppSettings {
@@ -69,6 +141,17 @@ impl Default for AppSettings {
             update_channel: UpdateChannel::Stable,
             skipped_update_version: None,
             last_update_check_at: None,
+            overwrite_policy: OverwritePolicy::default(),
+            output_name_template: None,
+            preserve_file_times: false,
+            post_action: PostAction::Keep,
+            scratch_directory: None,
+            ffmpeg_path: None,
+            ffprobe_path: None,
+            notification_mode: NotificationMode::default(),
+            container_defaults: BTreeMap::new(),
+            watch_folders: Vec::new(),
+            recent_dialog_directories: BTreeMap::new(),
         }
     }
 }
@@ -76,6 +159,7 @@ impl Default for AppSettings {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AppPersistence {
     settings_path: PathBuf,
+    conversion_log_directory: Option<PathBuf>,
 }
 
 impl AppPersistence {
@@ -88,15 +172,24 @@ impl AppPersistence {
     pub fn platform() -> Result<Self, AppPersistenceError> {
         let project_dirs = ProjectDirs::from("", "", "Frame")
             .ok_or(AppPersistenceError::ConfigDirectoryUnavailable)?;
-        Ok(Self::from_settings_path(
-            project_dirs.config_dir().join(SETTINGS_FILE_NAME),
-        ))
+        Ok(Self {
+            settings_path: project_dirs.config_dir().join(SETTINGS_FILE_NAME),
+            conversion_log_directory: Some(
+                project_dirs
+                    .data_local_dir()
+                    .join(LOG_DIRECTORY_NAME)
+                    .join(CONVERSION_LOG_DIRECTORY_NAME),
+            ),
+        })
     }
 
+    /// Builds a persistence handle for an explicit settings file; conversion
+    /// logs stay in memory.
     #[must_use]
     pub fn from_settings_path(path: impl Into<PathBuf>) -> Self {
         Self {
             settings_path: path.into(),
+            conversion_log_directory: None,
         }
     }
 
@@ -105,6 +198,12 @@ impl AppPersistence {
         &self.settings_path
     }
 
+    /// Directory each conversion task's log file is written to.
+    #[must_use]
+    pub fn conversion_log_directory(&self) -> Option<&Path> {
+        self.conversion_log_directory.as_deref()
+    }
+
     /// Loads persisted app settings, including legacy settings files.
     ///
     /// # Errors
@@ -178,6 +277,8 @@ pub enum AppPersistenceError {
     ConfigDirectoryUnavailable,
     #[error("update installation is in progress")]
     InstallationInProgress,
+    #[error("{0}")]
+    InvalidScratchDirectory(String),
     #[error("failed to read or write app settings: {0}")]
     Io(#[from] io::Error),
     #[error("failed to parse app settings: {0}")]
@@ -195,6 +296,17 @@ struct PersistedAppSettings {
     update_channel: UpdateChannel,
     skipped_update_version: Option<String>,
     last_update_check_at: Option<u64>,
+    overwrite_policy: OverwritePolicy,
+    output_name_template: Option<String>,
+    preserve_file_times: bool,
+    post_action: PostAction,
+    scratch_directory: Option<PathBuf>,
+    ffmpeg_path: Option<PathBuf>,
+    ffprobe_path: Option<PathBuf>,
+    notification_mode: NotificationMode,
+    container_defaults: BTreeMap<String, ConversionConfig>,
+    watch_folders: Vec<WatchFolder>,
+    recent_dialog_directories: BTreeMap<DialogPurpose, PathBuf>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -221,6 +333,17 @@ impl PersistedAppSettings {
             update_channel: settings.update_channel,
             skipped_update_version: settings.skipped_update_version.clone(),
             last_update_check_at: settings.last_update_check_at,
+            overwrite_policy: settings.overwrite_policy,
+            output_name_template: settings.output_name_template.clone(),
+            preserve_file_times: settings.preserve_file_times,
+            post_action: settings.post_action,
+            scratch_directory: settings.scratch_directory.clone(),
+            ffmpeg_path: settings.ffmpeg_path.clone(),
+            ffprobe_path: settings.ffprobe_path.clone(),
+            notification_mode: settings.notification_mode,
+            container_defaults: settings.container_defaults.clone(),
+            watch_folders: settings.watch_folders.clone(),
+            recent_dialog_directories: settings.recent_dialog_directories.clone(),
         }
     }
 
@@ -233,6 +356,17 @@ impl PersistedAppSettings {
             update_channel: self.update_channel,
             skipped_update_version: self.skipped_update_version,
             last_update_check_at: self.last_update_check_at,
+            overwrite_policy: self.overwrite_policy,
+            output_name_template: self.output_name_template,
+            preserve_file_times: self.preserve_file_times,
+            post_action: self.post_action,
+            scratch_directory: self.scratch_directory,
+            ffmpeg_path: self.ffmpeg_path,
+            ffprobe_path: self.ffprobe_path,
+            notification_mode: self.notification_mode,
+            container_defaults: normalize_container_defaults(self.container_defaults),
+            watch_folders: self.watch_folders,
+            recent_dialog_directories: self.recent_dialog_directories,
         }
     }
 }
@@ -248,6 +382,17 @@ impl Default for PersistedAppSettings {
             update_channel: UpdateChannel::Stable,
             skipped_update_version: None,
             last_update_check_at: None,
+            overwrite_policy: OverwritePolicy::default(),
+            output_name_template: None,
+            preserve_file_times: false,
+            post_action: PostAction::Keep,
+            scratch_directory: None,
+            ffmpeg_path: None,
+            ffprobe_path: None,
+            notification_mode: NotificationMode::default(),
+            container_defaults: BTreeMap::new(),
+            watch_folders: Vec::new(),
+            recent_dialog_directories: BTreeMap::new(),
         }
     }
 }
@@ -260,6 +405,24 @@ const fn valid_max_concurrency(value: usize) -> usize {
     }
 }
 
+/// Lowercases the container keys, dropping blank ones and pointing each
+/// config at the container it is stored under.
+fn normalize_container_defaults(
+    defaults: BTreeMap<String, ConversionConfig>,
+) -> BTreeMap<String, ConversionConfig> {
+    defaults
+        .into_iter()
+        .filter_map(|(container, mut config)| {
+            let container = container.trim().to_ascii_lowercase();
+            if container.is_empty() {
+                return None;
+            }
+            config.container.clone_from(&container);
+            Some((container, config))
+        })
+        .collect()
+}
+
 fn normalize_custom_presets(presets: Vec<PresetDefinition>) -> Vec<PresetDefinition> {
     let mut seen_ids = HashSet::new();
 
@@ -281,12 +444,36 @@ fn normalize_custom_presets(presets: Vec<PresetDefinition>) -> Vec<PresetDefinit
 }
 
 pub(crate) fn write_bytes_atomically(path: &Path, bytes: &[u8]) -> Result<(), io::Error> {
+    write_atomically_with(path, bytes, File::create)
+}
+
+/// Like [`write_bytes_atomically`], but on Unix only the current user can
+/// read or write the file, for records holding secrets.
+pub(crate) fn write_private_bytes_atomically(path: &Path, bytes: &[u8]) -> Result<(), io::Error> {
+    write_atomically_with(path, bytes, create_private_file)
+}
+
+fn create_private_file(path: &Path) -> Result<File, io::Error> {
+    // A leftover temp file would keep its old mode, so start from a new one.
+    let _ = fs::remove_file(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn write_atomically_with(
+    path: &Path,
+    bytes: &[u8],
+    create: impl FnOnce(&Path) -> Result<File, io::Error>,
+) -> Result<(), io::Error> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
 
     let temp_path = temp_path_for(path);
-    let mut file = File::create(&temp_path)?;
+    let mut file = create(&temp_path)?;
     file.write_all(bytes)?;
     file.sync_all()?;
     drop(file);
@@ -393,6 +580,38 @@ mod tests {
             update_channel: UpdateChannel::Stable,
             skipped_update_version: Some("0.2.0".to_string()),
             last_update_check_at: Some(1_800_000_000),
+            overwrite_policy: OverwritePolicy::Fail,
+            output_name_template: Some("{name}_{height}p".to_string()),
+            preserve_file_times: true,
+            post_action: PostAction::Trash,
+            scratch_directory: Some(PathBuf::from("/tmp/frame-scratch")),
+            ffmpeg_path: Some(PathBuf::from("/usr/bin/ffmpeg")),
+            ffprobe_path: Some(PathBuf::from("/usr/bin/ffprobe")),
+            notification_mode: NotificationMode::FailuresOnly,
+            container_defaults: BTreeMap::from([(
+                "gif".to_string(),
+                ConversionConfig {
+                    container: "gif".to_string(),
+                    video_codec: "gif".to_string(),
+                    fps: "15".to_string(),
+                    ..ConversionConfig::default()
+                },
+            )]),
+            watch_folders: vec![WatchFolder::new(
+                PathBuf::from("/tmp/frame-renders"),
+                ConversionConfig::default(),
+                PathBuf::from("/tmp/frame-output"),
+            )],
+            recent_dialog_directories: BTreeMap::from([
+                (
+                    DialogPurpose::OpenInput,
+                    PathBuf::from("/tmp/frame-sources"),
+                ),
+                (
+                    DialogPurpose::SaveOutput,
+                    PathBuf::from("/tmp/frame-output"),
+                ),
+            ]),
         };
 
         persistence
@@ -403,6 +622,29 @@ mod tests {
         assert_eq!(loaded, settings);
     }
 
+    #[test]
+    fn load_keys_container_defaults_by_lowercase_container() {
+        let path = test_settings_path();
+        let parent = path.parent().expect("test path should have parent");
+        fs::create_dir_all(parent).expect("test directory should be created");
+        fs::write(
+            &path,
+            r#"{"version":3,"containerDefaults":{" FLAC ":{"container":"mp4","audioCodec":"flac"},"":{}}}"#,
+        )
+        .expect("settings fixture should be written");
+
+        let settings = AppPersistence::from_settings_path(path)
+            .load()
+            .expect("settings should load");
+
+        assert_eq!(
+            settings.container_defaults.keys().collect::<Vec<_>>(),
+            ["flac"]
+        );
+        assert_eq!(settings.container_defaults["flac"].container, "flac");
+        assert_eq!(settings.container_defaults["flac"].audio_codec, "flac");
+    }
+
     #[test]
     fn load_accepts_settings_without_default_output_directory() {
         let path = test_settings_path();
@@ -419,6 +661,7 @@ mod tests {
             .expect("settings should load");
 
         assert_eq!(settings.default_output_directory, None);
+        assert_eq!(settings.overwrite_policy, OverwritePolicy::Rename);
     }
 
     #[test]
@@ -508,31 +751,45 @@ mod tests {
 
     #[test]
     fn from_runtime_persists_only_custom_presets() {
-        let settings = AppSettings::from_runtime(
-            3,
-            Some(PathBuf::from("/tmp/frame-output")),
-            &[
-                PresetDefinition::built_in(
-                    "balanced-mp4",
-                    "Balanced MP4",
-                    ConversionConfig::default(),
-                ),
-                PresetDefinition::custom(
-                    " custom-preset-1 ".to_string(),
-                    " Review MP4 ".to_string(),
-                    ConversionConfig::default(),
-                ),
-            ],
-            true,
-            UpdateChannel::Stable,
-            None,
-            Some(1_800_000_000),
-        );
+        let presets = [
+            PresetDefinition::built_in("balanced-mp4", "Balanced MP4", ConversionConfig::default()),
+            PresetDefinition::custom(
+                " custom-preset-1 ".to_string(),
+                " Review MP4 ".to_string(),
+                ConversionConfig::default(),
+            ),
+        ];
+        let media_tools = MediaToolPaths::beside_ffmpeg(PathBuf::from("/opt/ffmpeg/bin/ffmpeg"));
+        let settings = AppSettings::from_runtime(&RuntimeAppSettings {
+            max_concurrency: 3,
+            default_output_directory: Some(Path::new("/tmp/frame-output")),
+            presets: &presets,
+            auto_update_check: true,
+            update_channel: UpdateChannel::Stable,
+            skipped_update_version: None,
+            last_update_check_at: Some(1_800_000_000),
+            overwrite_policy: OverwritePolicy::Fail,
+            output_name_template: Some("{name}_{height}p"),
+            preserve_file_times: true,
+            post_action: PostAction::Keep,
+            scratch_directory: None,
+            media_tools: Some(&media_tools),
+            notification_mode: NotificationMode::default(),
+            container_defaults: &BTreeMap::new(),
+            watch_folders: &[],
+            recent_dialog_directories: &BTreeMap::new(),
+        });
 
         assert_eq!(settings.custom_presets.len(), 1);
         assert_eq!(settings.custom_presets[0].id, "custom-preset-1");
         assert_eq!(settings.custom_presets[0].name, "Review MP4");
         assert!(!settings.custom_presets[0].built_in);
+        assert_eq!(settings.overwrite_policy, OverwritePolicy::Fail);
+        assert_eq!(
+            settings.output_name_template.as_deref(),
+            Some("{name}_{height}p")
+        );
+        assert_eq!(settings.media_tools(), Some(media_tools));
     }
 
     fn test_settings_path() -> PathBuf {
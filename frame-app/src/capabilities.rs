@@ -1,16 +1,38 @@
 //! Runtime encoder capability detection for the native app.
 
 use std::{
-    io,
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::Path,
     process::{Command, Stdio},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
 use frame_core::capabilities::{
-    AvailableEncoders, AvailableFilters, ffmpeg_encoder_list_args, ffmpeg_filter_list_args,
-    parse_available_encoders, parse_available_filters,
+    AvailableEncoders, AvailableFilters, EncoderSelfTest, FfmpegCapabilities, FfmpegInfo,
+    HardwareCapabilities, MIN_FFMPEG_RELEASE, encoder_self_test_args, ffmpeg_decoder_list_args,
+    ffmpeg_encoder_list_args, ffmpeg_filter_list_args, ffmpeg_hwaccel_list_args,
+    ffmpeg_muxer_list_args, ffmpeg_protocol_list_args, ffmpeg_version_args,
+    parse_available_encoders, parse_available_filters, parse_ffmpeg_capabilities,
+    parse_ffmpeg_info, parse_hardware_capabilities,
 };
+use frame_core::hw_devices::{
+    HwDevice, MAX_DXGI_ADAPTERS, dxgi_adapter_probe_args, list_vaapi_render_nodes,
+    nvenc_gpu_list_args, parse_dxgi_adapter, parse_nvenc_gpu_list,
+};
+
+use crate::runtime_binaries::{MediaToolPaths, ffmpeg_executable};
+
+/// Detection results for the `FFmpeg` binary last asked about.
+static CAPABILITY_CACHE: Mutex<Option<Arc<CapabilityCache>>> = Mutex::new(None);
 
-use crate::runtime_binaries::ffmpeg_executable;
+/// Longest an encoder self-test may run before the encoder counts as
+/// unusable.
+pub const ENCODER_SELF_TEST_TIMEOUT: Duration = Duration::from_secs(15);
+const ENCODER_SELF_TEST_POLL_INTERVAL: Duration = Duration::from_millis(25);
 
 #[derive(Debug, thiserror::Error)]
 pub enum CapabilityDetectionError {
@@ -20,7 +42,136 @@ pub enum CapabilityDetectionError {
     Ffmpeg(String),
 }
 
-/// Detects `FFmpeg` encoders available to the bundled runtime.
+/// Why a chosen `FFmpeg` or `FFprobe` cannot replace the bundled one.
+#[derive(Debug, thiserror::Error)]
+pub enum MediaToolError {
+    #[error("could not run {path}: {source}")]
+    Launch { path: String, source: io::Error },
+    #[error("{0} is not an FFmpeg binary")]
+    NotFfmpeg(String),
+    #[error("{0} is not an FFprobe binary")]
+    NotFfprobe(String),
+    #[error(
+        "FFmpeg {version} is too old; Frame needs {}.{} or newer",
+        MIN_FFMPEG_RELEASE.0,
+        MIN_FFMPEG_RELEASE.1
+    )]
+    Outdated { version: String },
+}
+
+/// Path and modification time identifying the binary a cache belongs to,
+/// so switching to another `FFmpeg` or replacing it starts a fresh cache.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ExecutableStamp {
+    executable: String,
+    modified: Option<SystemTime>,
+}
+
+impl ExecutableStamp {
+    fn of(executable: &str) -> Self {
+        Self {
+            executable: executable.to_string(),
+            modified: fs::metadata(executable)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        }
+    }
+}
+
+/// One cached detection. Its lock is held while detecting, so concurrent
+/// first callers wait for a single probe instead of starting their own.
+struct CapabilitySlot<T>(Mutex<Option<T>>);
+
+impl<T> Default for CapabilitySlot<T> {
+    fn default() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+impl<T: Clone> CapabilitySlot<T> {
+    fn get(&self) -> Option<T> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn get_or_init(&self, detect: impl FnOnce() -> T) -> T {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(detect)
+            .clone()
+    }
+
+    /// Returns the cached value or runs `detect`; failures are not cached,
+    /// so the next call tries again.
+    fn get_or_try_init<E>(&self, detect: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        let mut value = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = value.as_ref() {
+            return Ok(value.clone());
+        }
+        let detected = detect()?;
+        *value = Some(detected.clone());
+        Ok(detected)
+    }
+}
+
+#[derive(Default)]
+struct CapabilityCache {
+    stamp: Option<ExecutableStamp>,
+    encoders: CapabilitySlot<AvailableEncoders>,
+    filters: CapabilitySlot<AvailableFilters>,
+    hardware: CapabilitySlot<HardwareCapabilities>,
+    ffmpeg: CapabilitySlot<FfmpegCapabilities>,
+    info: CapabilitySlot<FfmpegInfo>,
+    hw_devices: CapabilitySlot<Vec<HwDevice>>,
+    encoder_self_tests: Mutex<BTreeMap<String, Arc<CapabilitySlot<EncoderSelfTest>>>>,
+}
+
+impl CapabilityCache {
+    fn encoder_self_test(&self, encoder: &str) -> Arc<CapabilitySlot<EncoderSelfTest>> {
+        Arc::clone(
+            self.encoder_self_tests
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(encoder.to_string())
+                .or_default(),
+        )
+    }
+}
+
+/// Cache for `executable`, replacing the current one when it belongs to a
+/// different or since-modified binary.
+fn capability_cache(executable: &str) -> Arc<CapabilityCache> {
+    let stamp = ExecutableStamp::of(executable);
+    let mut cache = CAPABILITY_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(current) = cache.as_ref()
+        && current.stamp.as_ref() == Some(&stamp)
+    {
+        return Arc::clone(current);
+    }
+    let fresh = Arc::new(CapabilityCache {
+        stamp: Some(stamp),
+        ..CapabilityCache::default()
+    });
+    *cache = Some(Arc::clone(&fresh));
+    fresh
+}
+
+/// Drops every cached detection so the next call probes `FFmpeg` again,
+/// e.g. after GPU drivers were installed. Probes already running finish
+/// into the dropped cache.
+pub fn refresh_capabilities() {
+    *CAPABILITY_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Detects `FFmpeg` encoders available to the bundled runtime, reusing the
+/// cached listing of the current binary.
 ///
 /// # Errors
 ///
@@ -28,7 +179,9 @@ pub enum CapabilityDetectionError {
 /// listing command.
 pub fn detect_available_encoders() -> Result<AvailableEncoders, CapabilityDetectionError> {
     let executable = ffmpeg_executable();
-    detect_available_encoders_with_executable(&executable)
+    capability_cache(&executable)
+        .encoders
+        .get_or_try_init(|| detect_available_encoders_with_executable(&executable))
 }
 
 /// Detects `FFmpeg` encoders using a specific executable path.
@@ -48,7 +201,153 @@ pub fn detect_available_encoders_with_executable(
     available_encoders_from_output(output.status.success(), &output.stdout, &output.stderr)
 }
 
-/// Detects `FFmpeg` filters available to the bundled runtime.
+/// Detects encoders like [`detect_available_encoders`], then self-tests each
+/// listed hardware encoder and drops those that cannot encode.
+///
+/// # Errors
+///
+/// Returns an error when the encoder listing fails; failed self-tests only
+/// remove their encoder.
+pub fn detect_verified_encoders() -> Result<AvailableEncoders, CapabilityDetectionError> {
+    let mut encoders = detect_available_encoders()?;
+    for encoder in encoders.listed_hardware_encoders() {
+        let self_test = verify_encoder(encoder);
+        if !self_test.passed {
+            eprintln!(
+                "{}",
+                self_test
+                    .error
+                    .as_deref()
+                    .unwrap_or("Encoder self-test failed")
+            );
+            encoders.remove(encoder);
+        }
+    }
+    Ok(encoders)
+}
+
+/// Checks that `encoder` can encode a few frames with the bundled runtime,
+/// reusing an earlier result for the current binary.
+pub fn verify_encoder(encoder: &str) -> EncoderSelfTest {
+    let executable = ffmpeg_executable();
+    capability_cache(&executable)
+        .encoder_self_test(encoder)
+        .get_or_init(|| {
+            verify_encoder_with_executable(&executable, encoder, ENCODER_SELF_TEST_TIMEOUT)
+        })
+}
+
+/// Self-test of `encoder` run earlier for the current binary, if any.
+#[must_use]
+pub fn cached_encoder_self_test(encoder: &str) -> Option<EncoderSelfTest> {
+    capability_cache(&ffmpeg_executable())
+        .encoder_self_test(encoder)
+        .get()
+}
+
+/// Runs the self-test of `encoder` with a specific executable path, without
+/// caching. A test still running after `timeout` is killed and fails.
+#[must_use]
+pub fn verify_encoder_with_executable(
+    executable: &str,
+    encoder: &str,
+    timeout: Duration,
+) -> EncoderSelfTest {
+    let mut child = match Command::new(executable)
+        .args(encoder_self_test_args(encoder))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(error) => return EncoderSelfTest::failed(encoder, &error.to_string()),
+    };
+    let stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    });
+
+    let started_at = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started_at.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return EncoderSelfTest::failed(encoder, "the test encode timed out");
+            }
+            Ok(None) => thread::sleep(ENCODER_SELF_TEST_POLL_INTERVAL),
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return EncoderSelfTest::failed(encoder, &error.to_string());
+            }
+        }
+    };
+
+    let stderr = stderr_reader.join().unwrap_or_default();
+    EncoderSelfTest::from_output(encoder, status.success(), &stderr)
+}
+
+/// Enumerates CUDA devices, VAAPI render nodes and DXGI adapters, reusing
+/// the cached enumeration. Sources that cannot be queried add no devices.
+pub fn detect_hw_devices() -> Vec<HwDevice> {
+    let executable = ffmpeg_executable();
+    capability_cache(&executable)
+        .hw_devices
+        .get_or_init(|| detect_hw_devices_with_executable(&executable))
+}
+
+/// Devices found by [`detect_hw_devices`], or `None` while enumeration has
+/// not run for the current binary.
+#[must_use]
+pub fn cached_hw_devices() -> Option<Vec<HwDevice>> {
+    capability_cache(&ffmpeg_executable()).hw_devices.get()
+}
+
+/// Enumerates hardware devices using a specific executable path, without
+/// caching.
+#[must_use]
+pub fn detect_hw_devices_with_executable(executable: &str) -> Vec<HwDevice> {
+    // NVENC prints the device list and then fails on purpose.
+    let mut devices = Command::new(executable)
+        .args(nvenc_gpu_list_args())
+        .stdin(Stdio::null())
+        .output()
+        .map(|output| parse_nvenc_gpu_list(String::from_utf8_lossy(&output.stderr)))
+        .unwrap_or_default();
+    if cfg!(target_os = "linux") {
+        match list_vaapi_render_nodes(Path::new("/dev/dri")) {
+            Ok(nodes) => devices.extend(nodes),
+            Err(error) => eprintln!("Failed to list VAAPI render nodes: {error}"),
+        }
+    }
+    if cfg!(windows) {
+        for index in 0..MAX_DXGI_ADAPTERS {
+            let Some(adapter) = Command::new(executable)
+                .args(dxgi_adapter_probe_args(index))
+                .stdin(Stdio::null())
+                .output()
+                .ok()
+                .and_then(|output| {
+                    parse_dxgi_adapter(String::from_utf8_lossy(&output.stderr), index)
+                })
+            else {
+                break;
+            };
+            devices.push(adapter);
+        }
+    }
+    devices
+}
+
+/// Detects `FFmpeg` filters available to the bundled runtime, reusing the
+/// cached listing of the current binary.
 ///
 /// # Errors
 ///
@@ -56,7 +355,9 @@ pub fn detect_available_encoders_with_executable(
 /// listing command.
 pub fn detect_available_filters() -> Result<AvailableFilters, CapabilityDetectionError> {
     let executable = ffmpeg_executable();
-    detect_available_filters_with_executable(&executable)
+    capability_cache(&executable)
+        .filters
+        .get_or_try_init(|| detect_available_filters_with_executable(&executable))
 }
 
 /// Detects `FFmpeg` filters using a specific executable path.
@@ -76,6 +377,225 @@ pub fn detect_available_filters_with_executable(
     available_filters_from_output(output.status.success(), &output.stdout, &output.stderr)
 }
 
+/// Detects hwaccel methods and hardware decoders of the bundled runtime,
+/// reusing the cached result for the current binary.
+///
+/// # Errors
+///
+/// Returns an error when `FFmpeg` cannot be executed or reports a failed
+/// hwaccel or decoder listing command.
+pub fn detect_hardware_capabilities() -> Result<HardwareCapabilities, CapabilityDetectionError> {
+    let executable = ffmpeg_executable();
+    capability_cache(&executable)
+        .hardware
+        .get_or_try_init(|| detect_hardware_capabilities_with_executable(&executable))
+}
+
+/// Detects hardware decoding support using a specific executable path.
+///
+/// # Errors
+///
+/// Returns an error when the executable cannot be launched or exits with a
+/// non-zero status while listing hwaccels or decoders.
+pub fn detect_hardware_capabilities_with_executable(
+    executable: &str,
+) -> Result<HardwareCapabilities, CapabilityDetectionError> {
+    let hwaccels = Command::new(executable)
+        .args(ffmpeg_hwaccel_list_args())
+        .stdin(Stdio::null())
+        .output()?;
+    let hwaccels = listing_stdout(
+        hwaccels.status.success(),
+        &hwaccels.stdout,
+        &hwaccels.stderr,
+        "hwaccel",
+    )?;
+    let decoders = Command::new(executable)
+        .args(ffmpeg_decoder_list_args())
+        .stdin(Stdio::null())
+        .output()?;
+    let decoders = listing_stdout(
+        decoders.status.success(),
+        &decoders.stdout,
+        &decoders.stderr,
+        "decoder",
+    )?;
+
+    Ok(parse_hardware_capabilities(hwaccels, decoders))
+}
+
+/// Detects the filters, muxers and protocols of the bundled runtime,
+/// reusing the first successful detection for the current binary.
+///
+/// # Errors
+///
+/// Returns an error when `FFmpeg` cannot be executed or reports a failed
+/// listing command.
+pub fn detect_available_capabilities() -> Result<FfmpegCapabilities, CapabilityDetectionError> {
+    let executable = ffmpeg_executable();
+    capability_cache(&executable)
+        .ffmpeg
+        .get_or_try_init(|| detect_available_capabilities_with_executable(&executable))
+}
+
+/// Capabilities found by [`detect_available_capabilities`], or `None` while
+/// detection has not succeeded for the current binary.
+#[must_use]
+pub fn cached_ffmpeg_capabilities() -> Option<FfmpegCapabilities> {
+    capability_cache(&ffmpeg_executable()).ffmpeg.get()
+}
+
+/// Detects filters, muxers and protocols using a specific executable path,
+/// without caching.
+///
+/// # Errors
+///
+/// Returns an error when the executable cannot be launched or exits with a
+/// non-zero status while listing filters, muxers or protocols.
+pub fn detect_available_capabilities_with_executable(
+    executable: &str,
+) -> Result<FfmpegCapabilities, CapabilityDetectionError> {
+    let mut listings = Vec::with_capacity(3);
+    for (args, listing) in [
+        (ffmpeg_filter_list_args(), "filter"),
+        (ffmpeg_muxer_list_args(), "muxer"),
+        (ffmpeg_protocol_list_args(), "protocol"),
+    ] {
+        let output = Command::new(executable)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()?;
+        listings.push(listing_stdout(
+            output.status.success(),
+            &output.stdout,
+            &output.stderr,
+            listing,
+        )?);
+    }
+
+    Ok(parse_ffmpeg_capabilities(
+        &listings[0],
+        &listings[1],
+        &listings[2],
+    ))
+}
+
+/// Reads the version and build configuration of the bundled runtime,
+/// reusing the first successful detection for the current binary.
+///
+/// # Errors
+///
+/// Returns an error when `FFmpeg` cannot be executed, fails, or prints no
+/// version banner.
+pub fn detect_ffmpeg_info() -> Result<FfmpegInfo, CapabilityDetectionError> {
+    let executable = ffmpeg_executable();
+    capability_cache(&executable)
+        .info
+        .get_or_try_init(|| detect_ffmpeg_info_with_executable(&executable))
+}
+
+/// Version found by [`detect_ffmpeg_info`], or `None` while detection has
+/// not succeeded for the current binary.
+#[must_use]
+pub fn cached_ffmpeg_info() -> Option<FfmpegInfo> {
+    capability_cache(&ffmpeg_executable()).info.get()
+}
+
+/// Reads `FFmpeg` version information using a specific executable path,
+/// without caching.
+///
+/// # Errors
+///
+/// Returns an error when the executable cannot be launched, exits with a
+/// non-zero status, or prints no version banner.
+pub fn detect_ffmpeg_info_with_executable(
+    executable: &str,
+) -> Result<FfmpegInfo, CapabilityDetectionError> {
+    let output = Command::new(executable)
+        .args(ffmpeg_version_args())
+        .stdin(Stdio::null())
+        .output()?;
+
+    ffmpeg_info_from_output(output.status.success(), &output.stdout, &output.stderr)
+}
+
+/// Runs `-version` on both of `tools` to check they can replace the
+/// bundled binaries, returning the `FFmpeg` version found. Nothing is
+/// cached, so a binary replaced since is checked again.
+///
+/// # Errors
+///
+/// Returns an error when either binary cannot be run or does not print its
+/// version banner, or when `FFmpeg` is older than [`MIN_FFMPEG_RELEASE`].
+pub fn validate_media_tools(tools: &MediaToolPaths) -> Result<FfmpegInfo, MediaToolError> {
+    let info = version_stdout(&tools.ffmpeg)?
+        .and_then(parse_ffmpeg_info)
+        .ok_or_else(|| MediaToolError::NotFfmpeg(tools.ffmpeg.to_string_lossy().into_owned()))?;
+    if !info.meets_minimum_version() {
+        return Err(MediaToolError::Outdated {
+            version: info.version,
+        });
+    }
+
+    let is_ffprobe = version_stdout(&tools.ffprobe)?.is_some_and(|stdout| {
+        stdout
+            .lines()
+            .any(|line| line.trim_start().starts_with("ffprobe version "))
+    });
+    if !is_ffprobe {
+        return Err(MediaToolError::NotFfprobe(
+            tools.ffprobe.to_string_lossy().into_owned(),
+        ));
+    }
+    Ok(info)
+}
+
+/// Output of `executable -version`, or `None` when it exited with an error.
+fn version_stdout(executable: &Path) -> Result<Option<String>, MediaToolError> {
+    let output = Command::new(executable)
+        .args(ffmpeg_version_args())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|source| MediaToolError::Launch {
+            path: executable.to_string_lossy().into_owned(),
+            source,
+        })?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+fn ffmpeg_info_from_output(
+    success: bool,
+    stdout: &[u8],
+    stderr: &[u8],
+) -> Result<FfmpegInfo, CapabilityDetectionError> {
+    let stdout = listing_stdout(success, stdout, stderr, "version")?;
+    parse_ffmpeg_info(stdout).ok_or_else(|| {
+        CapabilityDetectionError::Ffmpeg("ffmpeg printed no version banner".to_string())
+    })
+}
+
+fn listing_stdout(
+    success: bool,
+    stdout: &[u8],
+    stderr: &[u8],
+    listing: &str,
+) -> Result<String, CapabilityDetectionError> {
+    if !success {
+        let message = String::from_utf8_lossy(stderr);
+        let message = message.trim();
+        return Err(CapabilityDetectionError::Ffmpeg(if message.is_empty() {
+            format!("unknown ffmpeg {listing} detection failure")
+        } else {
+            message.to_string()
+        }));
+    }
+
+    Ok(String::from_utf8_lossy(stdout).into_owned())
+}
+
 fn available_encoders_from_output(
     success: bool,
     stdout: &[u8],
@@ -116,6 +636,79 @@ fn available_filters_from_output(
 mod tests {
     use super::*;
 
+    #[test]
+    fn capability_slot_caches_successes_but_retries_failures() {
+        let slot = CapabilitySlot::<u32>::default();
+
+        assert_eq!(slot.get_or_try_init(|| Err::<u32, ()>(())), Err(()));
+        assert_eq!(slot.get(), None);
+        assert_eq!(slot.get_or_try_init(|| Ok::<u32, ()>(7)), Ok(7));
+        assert_eq!(
+            slot.get_or_try_init(|| -> Result<u32, ()> { panic!("cached value should be reused") }),
+            Ok(7)
+        );
+    }
+
+    #[test]
+    fn executable_stamp_changes_when_the_binary_is_replaced() {
+        let path =
+            std::env::temp_dir().join(format!("frame-capability-stamp-{}", std::process::id()));
+        let file = fs::File::create(&path).expect("stamp file should be created");
+        file.set_modified(SystemTime::UNIX_EPOCH)
+            .expect("stamp mtime should be set");
+        let executable = path.to_string_lossy();
+        let before = ExecutableStamp::of(&executable);
+
+        file.set_modified(SystemTime::now())
+            .expect("stamp mtime should be updated");
+        let after = ExecutableStamp::of(&executable);
+        let _ = fs::remove_file(&path);
+
+        assert_ne!(before, after);
+        assert_ne!(before, ExecutableStamp::of("/nonexistent/frame-ffmpeg"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn media_tools_must_print_their_banners_and_meet_the_minimum_version() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("frame-media-tools-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("tool directory should be created");
+        let script = |name: &str, banner: &str| {
+            let path = dir.join(name);
+            fs::write(&path, format!("#!/bin/sh\necho '{banner}'\n"))
+                .expect("tool script should be written");
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .expect("tool script should be executable");
+            path
+        };
+        let current = script("ffmpeg", "ffmpeg version 7.1 Copyright (c) 2000-2024");
+        let outdated = script("ffmpeg-4", "ffmpeg version 4.4.2 Copyright (c) 2000-2021");
+        let ffprobe = script("ffprobe", "ffprobe version 7.1 Copyright (c) 2000-2024");
+        let tools = |ffmpeg: &Path, ffprobe: &Path| MediaToolPaths {
+            ffmpeg: ffmpeg.to_path_buf(),
+            ffprobe: ffprobe.to_path_buf(),
+        };
+
+        let valid = validate_media_tools(&tools(&current, &ffprobe));
+        let old = validate_media_tools(&tools(&outdated, &ffprobe));
+        let swapped = validate_media_tools(&tools(&current, &current));
+        let missing = validate_media_tools(&tools(&dir.join("missing"), &ffprobe));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            valid.expect("current tools should be accepted").version,
+            "7.1"
+        );
+        assert_eq!(
+            old.expect_err("old ffmpeg should be rejected").to_string(),
+            "FFmpeg 4.4.2 is too old; Frame needs 6.0 or newer"
+        );
+        assert!(matches!(swapped, Err(MediaToolError::NotFfprobe(_))));
+        assert!(matches!(missing, Err(MediaToolError::Launch { .. })));
+    }
+
     #[test]
     fn available_encoders_from_output_parses_successful_ffmpeg_stdout() {
         let stdout =
@@ -139,6 +732,51 @@ mod tests {
         assert!(actual.deesser);
     }
 
+    #[test]
+    fn listing_stdout_reports_the_failed_listing() {
+        let error = listing_stdout(false, b"", b"", "hwaccel")
+            .expect_err("failed ffmpeg output should be an error");
+
+        assert_eq!(
+            error.to_string(),
+            "ffmpeg encoder detection failed: unknown ffmpeg hwaccel detection failure"
+        );
+    }
+
+    #[test]
+    fn verify_encoder_with_executable_fails_when_ffmpeg_cannot_start() {
+        let self_test = verify_encoder_with_executable(
+            "/nonexistent/frame-test-ffmpeg",
+            "h264_nvenc",
+            ENCODER_SELF_TEST_TIMEOUT,
+        );
+
+        assert!(!self_test.passed);
+        assert!(
+            self_test
+                .error
+                .is_some_and(|error| error.starts_with("NVENC listed but unusable: "))
+        );
+    }
+
+    #[test]
+    fn ffmpeg_info_from_output_requires_a_version_banner() {
+        let info = ffmpeg_info_from_output(
+            true,
+            b"ffmpeg version 7.1 Copyright (c) 2000-2024 the FFmpeg developers\n",
+            b"",
+        )
+        .expect("version banner should parse");
+        let error = ffmpeg_info_from_output(true, b"unexpected output\n", b"")
+            .expect_err("output without a banner should be an error");
+
+        assert_eq!(info.version, "7.1");
+        assert_eq!(
+            error.to_string(),
+            "ffmpeg encoder detection failed: ffmpeg printed no version banner"
+        );
+    }
+
     #[test]
     fn available_encoders_from_output_reports_stderr_on_failed_ffmpeg() {
         let error = available_encoders_from_output(false, b"", b"ffmpeg missing codec table\n")
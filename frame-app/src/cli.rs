@@ -0,0 +1,864 @@
+//! Headless command line. `frame convert` and `frame run-jobs` run
+//! conversions with the app's presets and settings and print their progress
+//! instead of opening a window.
+//!
+//! Arguments that do not start with one of these commands launch the app as
+//! usual, so opening files with Frame is unaffected.
+//!
+//! Interrupting a run cancels its tasks the way the app does, so `FFmpeg`
+//! stops and partial outputs are removed before Frame exits.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+#[cfg(unix)]
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use frame_core::{
+    dependencies::{
+        DEPENDENCY_OUTPUT_PLACEHOLDER, task_dependency_edges, validate_task_dependencies,
+    },
+    error::ConversionError,
+    events::ConversionEvent,
+    network_input::input_url_scheme,
+    types::ConversionTask,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    app_persistence::{AppPersistence, AppSettings},
+    conversion_runner::{
+        ConversionProcessController, conversion_task_with_settings, disambiguate_output_paths,
+        run_conversion_batch_with_control,
+    },
+    file_queue::{FileItem, file_name_from_path},
+    runtime_binaries::set_configured_media_tools,
+    settings::{ConversionConfig, PresetDefinition, merged_presets},
+};
+
+pub const CLI_USAGE: &str = "\
+Usage:
+  frame convert <input>... [--preset <name>] [--output-dir <dir>] [--json]
+  frame run-jobs <jobs.json> [--preset <name>] [--output-dir <dir>] [--json]
+
+Inputs may also be http or https URLs, which FFmpeg reads directly.
+Presets are matched by id or name; without one the default settings apply.
+Outputs go to --output-dir, then the default output folder of the app, then
+the folder of each input, or the current folder for URLs. A job with
+\"dependsOn\" converts the output of the job with that \"id\" once it
+completes. --json prints the queue and then one JSON event per line.";
+
+/// Exit status of a run whose arguments or jobs file could not be used.
+pub const CLI_USAGE_EXIT_CODE: u8 = 2;
+/// Exit status of a run in which a task failed or was cancelled.
+pub const CLI_FAILURE_EXIT_CODE: u8 = 1;
+/// Percent between two human-readable progress lines of a task.
+const PROGRESS_LINE_STEP: u32 = 10;
+/// How often the run checks whether it was interrupted.
+#[cfg(unix)]
+const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set once SIGINT, SIGTERM or SIGHUP arrives.
+#[cfg(unix)]
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CliOutput {
+    #[default]
+    Human,
+    /// One `{"event": ..., "payload": ...}` object per line, with the same
+    /// names and payloads as the app's conversion events.
+    Json,
+}
+
+/// Options shared by the commands. For `run-jobs` they apply to the jobs
+/// that do not set their own.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CliOptions {
+    pub preset: Option<String>,
+    pub output_directory: Option<PathBuf>,
+    pub output: CliOutput,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CliCommand {
+    Convert {
+        inputs: Vec<PathBuf>,
+        options: CliOptions,
+    },
+    RunJobs {
+        jobs_path: PathBuf,
+        options: CliOptions,
+    },
+    Help,
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("no preset has the id or name {0:?}")]
+    UnknownPreset(String),
+    #[error("no output folder for {0}; pass --output-dir")]
+    NoOutputDirectory(String),
+    #[error("failed to read the jobs file: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse the jobs file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Dependencies(#[from] ConversionError),
+}
+
+/// One conversion of a jobs file.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CliJob {
+    /// Name other jobs use in `dependsOn`; `cli-<position>` when unset.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Ignored when the job depends on another one.
+    #[serde(default)]
+    pub input: PathBuf,
+    /// Job whose output this one converts. It starts once that job
+    /// completes and fails or is cancelled along with it.
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    /// Preset id or name.
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Full settings, used instead of a preset.
+    #[serde(default)]
+    pub config: Option<ConversionConfig>,
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Output file name without its extension.
+    #[serde(default)]
+    pub output_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JobsFile {
+    jobs: Vec<CliJob>,
+}
+
+/// Reads the command from the arguments after the program name. Returns
+/// `None` when they do not start with a command, so the app opens instead.
+///
+/// # Errors
+///
+/// Returns [`CliError::Usage`] when a command is given with unknown
+/// options, missing values or the wrong number of paths.
+pub fn parse_cli_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Option<CliCommand>, CliError> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(None);
+    };
+    if !matches!(command.as_str(), "convert" | "run-jobs" | "help" | "--help") {
+        return Ok(None);
+    }
+
+    let mut paths = Vec::new();
+    let mut options = CliOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => options.output = CliOutput::Json,
+            "--preset" => options.preset = Some(option_value(&arg, args.next())?),
+            "--output-dir" => {
+                options.output_directory = Some(PathBuf::from(option_value(&arg, args.next())?));
+            }
+            "-h" | "--help" => return Ok(Some(CliCommand::Help)),
+            "--" => paths.extend(args.by_ref().map(PathBuf::from)),
+            _ if arg.starts_with('-') => {
+                return Err(CliError::Usage(format!("unknown option {arg}")));
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    match command.as_str() {
+        "convert" if paths.is_empty() => Err(CliError::Usage(
+            "convert needs at least one input file".to_string(),
+        )),
+        "convert" => Ok(Some(CliCommand::Convert {
+            inputs: paths,
+            options,
+        })),
+        "run-jobs" if paths.len() == 1 => Ok(Some(CliCommand::RunJobs {
+            jobs_path: paths.remove(0),
+            options,
+        })),
+        "run-jobs" => Err(CliError::Usage(
+            "run-jobs needs exactly one jobs file".to_string(),
+        )),
+        _ => Ok(Some(CliCommand::Help)),
+    }
+}
+
+fn option_value(option: &str, value: Option<String>) -> Result<String, CliError> {
+    value
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| CliError::Usage(format!("{option} needs a value")))
+}
+
+/// Reads a `{"jobs": [...]}` file. Relative paths in it are resolved against
+/// `base_directory`, the folder of the file.
+///
+/// # Errors
+///
+/// Returns an error when `bytes` is not a jobs file.
+pub fn parse_jobs_file(bytes: &[u8], base_directory: &Path) -> Result<Vec<CliJob>, CliError> {
+    let file: JobsFile = serde_json::from_slice(bytes)?;
+    Ok(file
+        .jobs
+        .into_iter()
+        .map(|mut job| {
+            if job.depends_on.is_none() && !is_url_input(&job.input) {
+                job.input = base_directory.join(&job.input);
+            }
+            job.output_dir = job.output_dir.map(|path| base_directory.join(path));
+            job
+        })
+        .collect())
+}
+
+/// Preset whose id matches `name`, or whose name matches it ignoring case.
+#[must_use]
+pub fn find_preset<'a>(
+    presets: &'a [PresetDefinition],
+    name: &str,
+) -> Option<&'a PresetDefinition> {
+    let name = name.trim();
+    presets.iter().find(|preset| preset.id == name).or_else(|| {
+        presets
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Builds the tasks of `jobs` the way the queue does, with the output
+/// settings of the app.
+///
+/// # Errors
+///
+/// Returns an error when a job names an unknown preset, has no output
+/// folder, or depends on an unknown job or on itself.
+pub fn build_cli_tasks(
+    jobs: Vec<CliJob>,
+    options: &CliOptions,
+    settings: &AppSettings,
+) -> Result<Vec<ConversionTask>, CliError> {
+    let presets = merged_presets(settings.custom_presets.clone());
+    let mut tasks = Vec::with_capacity(jobs.len());
+    for (index, job) in jobs.into_iter().enumerate() {
+        let config = match (job.config, job.preset.or_else(|| options.preset.clone())) {
+            (Some(config), _) => config,
+            (None, Some(name)) => find_preset(&presets, &name)
+                .ok_or(CliError::UnknownPreset(name))?
+                .config
+                .clone(),
+            (None, None) => ConversionConfig::default(),
+        };
+        let input = if job.depends_on.is_some() {
+            PathBuf::from(DEPENDENCY_OUTPUT_PLACEHOLDER)
+        } else {
+            job.input
+        };
+        let output_directory = job
+            .output_dir
+            .or_else(|| options.output_directory.clone())
+            .or_else(|| settings.default_output_directory.clone())
+            .or_else(|| {
+                let parent_id = job.depends_on.as_deref()?;
+                tasks
+                    .iter()
+                    .find(|task| task.id == parent_id)
+                    .map(|task| PathBuf::from(&task.output_directory))
+            })
+            .or_else(|| {
+                if is_url_input(&input) {
+                    return std::env::current_dir().ok();
+                }
+                input
+                    .parent()
+                    .filter(|parent| !parent.as_os_str().is_empty())
+                    .map(Path::to_path_buf)
+            })
+            .ok_or_else(|| CliError::NoOutputDirectory(input.display().to_string()))?;
+
+        let id = job.id.unwrap_or_else(|| format!("cli-{}", index + 1));
+        let mut file = FileItem::from_os_path(id, &input);
+        file.config = config;
+        if job.depends_on.is_some() {
+            // Named after the dependency's output once it is known.
+            file.output_name.clear();
+        }
+        if let Some(output_name) = job.output_name {
+            file.output_name = output_name;
+        }
+        let mut task =
+            conversion_task_with_settings(&file, &output_directory.to_string_lossy(), settings);
+        task.depends_on = job.depends_on;
+        tasks.push(task);
+    }
+    validate_task_dependencies(&tasks)?;
+    disambiguate_output_paths(&mut tasks);
+    Ok(tasks)
+}
+
+/// Runs `command` to completion and returns the exit status: zero when
+/// every task completed, [`CLI_FAILURE_EXIT_CODE`] when one failed or was
+/// cancelled, and [`CLI_USAGE_EXIT_CODE`] when the jobs could not be built.
+#[must_use]
+pub fn run_cli(command: CliCommand) -> ExitCode {
+    let (jobs, options) = match command {
+        CliCommand::Help => {
+            println!("{CLI_USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        CliCommand::Convert { inputs, options } => (
+            inputs
+                .into_iter()
+                .map(|input| CliJob {
+                    input: if is_url_input(&input) {
+                        input
+                    } else {
+                        std::path::absolute(&input).unwrap_or(input)
+                    },
+                    ..CliJob::default()
+                })
+                .collect(),
+            options,
+        ),
+        CliCommand::RunJobs { jobs_path, options } => {
+            let base_directory = std::path::absolute(&jobs_path)
+                .ok()
+                .and_then(|path| path.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            match fs::read(&jobs_path)
+                .map_err(CliError::from)
+                .and_then(|bytes| parse_jobs_file(&bytes, &base_directory))
+            {
+                Ok(jobs) => (jobs, options),
+                Err(error) => return usage_failure(&error),
+            }
+        }
+    };
+
+    let persistence = AppPersistence::platform().ok();
+    let settings = match persistence.as_ref().map(AppPersistence::load) {
+        Some(Ok(settings)) => settings,
+        Some(Err(error)) => {
+            eprintln!("frame: using the default settings: {error}");
+            AppSettings::default()
+        }
+        None => AppSettings::default(),
+    };
+    set_configured_media_tools(settings.media_tools());
+    let tasks = match build_cli_tasks(jobs, &options, &settings) {
+        Ok(tasks) => tasks,
+        Err(error) => return usage_failure(&error),
+    };
+
+    let controller = ConversionProcessController::default();
+    let configured = controller
+        .update_max_concurrency(settings.max_concurrency)
+        .and_then(|()| {
+            controller.set_task_log_directory(
+                persistence
+                    .as_ref()
+                    .and_then(AppPersistence::conversion_log_directory)
+                    .map(Path::to_path_buf),
+            )
+        });
+    let mut reporter = CliReporter::new(options.output, &tasks);
+    reporter.report_queue(&tasks);
+    cancel_tasks_on_interrupt(&controller, &tasks);
+    let result = configured.and_then(|()| {
+        run_conversion_batch_with_control(tasks, &controller, |event| reporter.report(&event))
+    });
+    if let Err(error) = result {
+        eprintln!("frame: error [{}]: {error}", error.code());
+        return ExitCode::from(CLI_FAILURE_EXIT_CODE);
+    }
+    reporter.finish()
+}
+
+/// Cancels every task of the run once it is interrupted, so the batch ends
+/// through the cancel path and the run exits with
+/// [`CLI_FAILURE_EXIT_CODE`]. A second signal stops Frame straight away.
+#[cfg(unix)]
+fn cancel_tasks_on_interrupt(controller: &ConversionProcessController, tasks: &[ConversionTask]) {
+    extern "C" fn record_interrupt(signal: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        // SAFETY: `signal` is async-signal-safe.
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+        }
+    }
+
+    // SAFETY: the handler only stores to an atomic and calls `signal`.
+    unsafe {
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+            libc::signal(
+                signal,
+                record_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+    let controller = controller.clone();
+    let task_ids = tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+    let spawned = thread::Builder::new()
+        .name("frame-cli-interrupt".to_string())
+        .spawn(move || {
+            while !INTERRUPTED.load(Ordering::SeqCst) {
+                thread::sleep(INTERRUPT_POLL_INTERVAL);
+            }
+            eprintln!("frame: interrupted, cancelling the conversions");
+            for id in &task_ids {
+                if let Err(error) = controller.cancel_task(id) {
+                    eprintln!("frame: failed to cancel {id}: {error}");
+                }
+            }
+        });
+    if let Err(error) = spawned {
+        eprintln!("frame: interrupting will not cancel the conversions: {error}");
+    }
+}
+
+/// Windows delivers Ctrl-C to every process of the console, `FFmpeg`
+/// included, so it stops on its own.
+#[cfg(not(unix))]
+const fn cancel_tasks_on_interrupt(
+    _controller: &ConversionProcessController,
+    _tasks: &[ConversionTask],
+) {
+}
+
+/// Whether an input names a URL rather than a local file.
+fn is_url_input(input: &Path) -> bool {
+    input_url_scheme(&input.to_string_lossy()).is_some()
+}
+
+fn usage_failure(error: &CliError) -> ExitCode {
+    eprintln!("frame: {error}");
+    if matches!(error, CliError::Usage(_)) {
+        eprintln!("{CLI_USAGE}");
+    }
+    ExitCode::from(CLI_USAGE_EXIT_CODE)
+}
+
+/// Serializes `event` as one line of `--json` output.
+#[must_use]
+pub fn event_json_line(event: &ConversionEvent) -> String {
+    let payload = match event {
+        ConversionEvent::Started(payload) => serde_json::to_value(payload),
+        ConversionEvent::Progress(payload) => serde_json::to_value(payload),
+        ConversionEvent::Completed(payload) => serde_json::to_value(payload),
+        ConversionEvent::Error(payload) => serde_json::to_value(payload),
+        ConversionEvent::Log(payload) => serde_json::to_value(payload),
+        ConversionEvent::LogBatch(payload) => serde_json::to_value(payload),
+        ConversionEvent::Cancelled(payload) => serde_json::to_value(payload),
+        ConversionEvent::Warning(payload) => serde_json::to_value(payload),
+    };
+    serde_json::json!({
+        "event": event.event_name(),
+        "payload": payload.unwrap_or_default(),
+    })
+    .to_string()
+}
+
+/// Serializes the tasks of a run and the dependencies between them as the
+/// first line of `--json` output.
+#[must_use]
+pub fn queue_json_line(tasks: &[ConversionTask]) -> String {
+    let entries = tasks
+        .iter()
+        .map(|task| {
+            serde_json::json!({
+                "id": task.id,
+                "input": task.file_path,
+                "dependsOn": task.depends_on,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "event": "queue",
+        "payload": {
+            "tasks": entries,
+            "dependencies": task_dependency_edges(tasks),
+        },
+    })
+    .to_string()
+}
+
+/// Prints the events of a run and counts how its tasks ended.
+struct CliReporter {
+    output: CliOutput,
+    /// Input file name of each task, or the id of a task that converts the
+    /// output of another, for human-readable lines.
+    names: HashMap<String, String>,
+    /// Last progress step printed for each task.
+    progress_steps: HashMap<String, u32>,
+    completed: usize,
+    failed: usize,
+    cancelled: usize,
+}
+
+impl CliReporter {
+    fn new(output: CliOutput, tasks: &[ConversionTask]) -> Self {
+        Self {
+            output,
+            names: tasks
+                .iter()
+                .map(|task| {
+                    let name = if task.depends_on.is_some() {
+                        task.id.as_str()
+                    } else {
+                        file_name_from_path(&task.file_path)
+                    };
+                    (task.id.clone(), name.to_string())
+                })
+                .collect(),
+            progress_steps: HashMap::new(),
+            completed: 0,
+            failed: 0,
+            cancelled: 0,
+        }
+    }
+
+    fn report_queue(&self, tasks: &[ConversionTask]) {
+        match self.output {
+            CliOutput::Json => println!("{}", queue_json_line(tasks)),
+            CliOutput::Human => {
+                for edge in task_dependency_edges(tasks) {
+                    println!(
+                        "{}: waits for {}",
+                        self.name(&edge.task_id),
+                        self.name(&edge.depends_on)
+                    );
+                }
+            }
+        }
+    }
+
+    fn name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.names.get(id).map_or(id, String::as_str)
+    }
+
+    fn report(&mut self, event: &ConversionEvent) {
+        match event {
+            ConversionEvent::Completed(_) => self.completed += 1,
+            ConversionEvent::Error(_) => self.failed += 1,
+            ConversionEvent::Cancelled(_) => self.cancelled += 1,
+            _ => {}
+        }
+        match self.output {
+            CliOutput::Json => println!("{}", event_json_line(event)),
+            CliOutput::Human => self.print_line(event),
+        }
+    }
+
+    fn print_line(&mut self, event: &ConversionEvent) {
+        let name = self.name(event.id()).to_string();
+        match event {
+            ConversionEvent::Started(payload) => {
+                println!("{name}: converting to {}", payload.output_path);
+            }
+            ConversionEvent::Progress(payload) => {
+                #[expect(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    reason = "progress is clamped to 0..=100"
+                )]
+                let percent = payload.progress.clamp(0.0, 100.0) as u32;
+                let step = percent / PROGRESS_LINE_STEP * PROGRESS_LINE_STEP;
+                let last = self.progress_steps.insert(payload.id.clone(), step);
+                // Each phase counts from zero again.
+                if last.is_none_or(|last| step != last) {
+                    let phase = payload
+                        .phase
+                        .map(|phase| format!("{phase:?} ").to_lowercase())
+                        .unwrap_or_default();
+                    println!("{name}: {phase}{step}%");
+                }
+            }
+            ConversionEvent::Completed(payload) => {
+                println!("{name}: done, wrote {}", payload.outputs.join(", "));
+            }
+            ConversionEvent::Error(payload) => {
+                eprintln!(
+                    "{name}: failed [{}]: {}",
+                    payload.code.code(),
+                    payload.error
+                );
+                if let Some(log_path) = &payload.log_path {
+                    eprintln!("{name}: log written to {log_path}");
+                }
+            }
+            ConversionEvent::Cancelled(_) => eprintln!("{name}: cancelled"),
+            ConversionEvent::Warning(payload) => {
+                eprintln!("{name}: warning: {}", payload.message);
+            }
+            ConversionEvent::Log(_) | ConversionEvent::LogBatch(_) => {}
+        }
+    }
+
+    fn finish(self) -> ExitCode {
+        if self.output == CliOutput::Human {
+            eprintln!(
+                "frame: {} completed, {} failed, {} cancelled",
+                self.completed, self.failed, self.cancelled
+            );
+        }
+        if self.failed + self.cancelled > 0 {
+            ExitCode::from(CLI_FAILURE_EXIT_CODE)
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn arguments_without_a_command_open_the_app() {
+        assert!(parse_cli_args(args(&[])).expect("should parse").is_none());
+        assert!(
+            parse_cli_args(args(&["/videos/clip.mp4"]))
+                .expect("should parse")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn convert_reads_inputs_and_options_in_any_order() {
+        let command = parse_cli_args(args(&[
+            "convert",
+            "a.mov",
+            "--preset",
+            "archive-hq",
+            "b.mov",
+            "--output-dir",
+            "/mnt/out",
+            "--json",
+        ]))
+        .expect("should parse");
+
+        assert_eq!(
+            command,
+            Some(CliCommand::Convert {
+                inputs: vec![PathBuf::from("a.mov"), PathBuf::from("b.mov")],
+                options: CliOptions {
+                    preset: Some("archive-hq".to_string()),
+                    output_directory: Some(PathBuf::from("/mnt/out")),
+                    output: CliOutput::Json,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_commands_are_usage_errors() {
+        for values in [
+            &["convert"][..],
+            &["convert", "a.mov", "--preset"],
+            &["convert", "a.mov", "--fast"],
+            &["run-jobs"],
+            &["run-jobs", "a.json", "b.json"],
+        ] {
+            assert!(
+                matches!(parse_cli_args(args(values)), Err(CliError::Usage(_))),
+                "{values:?} should be rejected"
+            );
+        }
+        assert_eq!(
+            parse_cli_args(args(&["run-jobs", "--help"])).expect("should parse"),
+            Some(CliCommand::Help)
+        );
+    }
+
+    #[test]
+    fn jobs_resolve_paths_against_the_jobs_file() {
+        let jobs = parse_jobs_file(
+            br#"{"jobs":[
+                {"input":"in/a.mov","preset":"discord","outputDir":"out"},
+                {"input":"/abs/b.mov","config":{"container":"mkv"},"outputName":"b-final"}
+            ]}"#,
+            Path::new("/jobs"),
+        )
+        .expect("jobs should parse");
+
+        assert_eq!(jobs[0].input, PathBuf::from("/jobs/in/a.mov"));
+        assert_eq!(jobs[0].output_dir, Some(PathBuf::from("/jobs/out")));
+        assert_eq!(jobs[1].input, PathBuf::from("/abs/b.mov"));
+        assert_eq!(
+            jobs[1]
+                .config
+                .as_ref()
+                .map(|config| config.container.as_str()),
+            Some("mkv")
+        );
+        assert!(parse_jobs_file(b"[]", Path::new("/jobs")).is_err());
+    }
+
+    #[test]
+    fn tasks_use_the_preset_and_the_app_output_settings() {
+        let settings = AppSettings {
+            default_output_directory: Some(PathBuf::from("/exports")),
+            output_name_template: Some("{name}_{height}p".to_string()),
+            ..AppSettings::default()
+        };
+        let jobs = vec![
+            CliJob {
+                input: PathBuf::from("/videos/a.mov"),
+                ..CliJob::default()
+            },
+            CliJob {
+                input: PathBuf::from("/videos/b.mov"),
+                preset: Some("balanced-mp4".to_string()),
+                output_dir: Some(PathBuf::from("/elsewhere")),
+                output_name: Some("b-final".to_string()),
+                ..CliJob::default()
+            },
+        ];
+        let options = CliOptions {
+            preset: Some("ARCHIVE H.265".to_string()),
+            ..CliOptions::default()
+        };
+
+        let tasks = build_cli_tasks(jobs, &options, &settings).expect("tasks should build");
+
+        assert_eq!(tasks[0].config.container, "mkv");
+        assert_eq!(tasks[0].output_directory, "/exports");
+        assert_eq!(
+            tasks[0].output_template.as_deref(),
+            Some("{name}_{height}p")
+        );
+        assert_eq!(tasks[1].config.container, "mp4");
+        assert_eq!(tasks[1].output_directory, "/elsewhere");
+        assert_eq!(tasks[1].output_name.as_deref(), Some("b-final"));
+        assert_eq!(tasks[1].output_template, None);
+    }
+
+    #[test]
+    fn url_inputs_stay_urls_and_write_to_the_current_folder() {
+        let url = "https://cdn.example.com/feeds/ep-12.mp3?token=abc";
+        let jobs = parse_jobs_file(
+            format!(r#"{{"jobs":[{{"input":"{url}"}}]}}"#).as_bytes(),
+            Path::new("/jobs"),
+        )
+        .expect("jobs should parse");
+
+        let tasks = build_cli_tasks(jobs, &CliOptions::default(), &AppSettings::default())
+            .expect("tasks should build");
+
+        assert_eq!(tasks[0].file_path, url);
+        assert_eq!(
+            Path::new(&tasks[0].output_directory),
+            std::env::current_dir().expect("tests run in a folder")
+        );
+        assert_eq!(tasks[0].output_name.as_deref(), Some("ep-12_converted"));
+    }
+
+    #[test]
+    fn unknown_presets_fail_before_any_task_runs() {
+        let jobs = vec![CliJob {
+            input: PathBuf::from("/videos/a.mov"),
+            preset: Some("no-such-preset".to_string()),
+            ..CliJob::default()
+        }];
+
+        assert!(matches!(
+            build_cli_tasks(jobs, &CliOptions::default(), &AppSettings::default()),
+            Err(CliError::UnknownPreset(name)) if name == "no-such-preset"
+        ));
+    }
+
+    #[test]
+    fn dependent_jobs_convert_the_output_of_their_dependency() {
+        let jobs = parse_jobs_file(
+            br#"{"jobs":[
+                {"id":"remux","input":"in/a.mov","outputDir":"out"},
+                {"id":"upscale","dependsOn":"remux","preset":"balanced-mp4"}
+            ]}"#,
+            Path::new("/jobs"),
+        )
+        .expect("jobs should parse");
+
+        let tasks = build_cli_tasks(jobs, &CliOptions::default(), &AppSettings::default())
+            .expect("tasks should build");
+
+        assert_eq!(tasks[0].id, "remux");
+        assert_eq!(tasks[1].id, "upscale");
+        assert_eq!(tasks[1].depends_on.as_deref(), Some("remux"));
+        assert_eq!(tasks[1].file_path, DEPENDENCY_OUTPUT_PLACEHOLDER);
+        assert_eq!(tasks[1].output_directory, "/jobs/out");
+        assert_eq!(tasks[1].output_name, None);
+
+        let line: serde_json::Value =
+            serde_json::from_str(&queue_json_line(&tasks)).expect("line should be JSON");
+        assert_eq!(line["event"], "queue");
+        assert_eq!(line["payload"]["tasks"][1]["dependsOn"], "remux");
+        assert_eq!(
+            line["payload"]["dependencies"],
+            serde_json::json!([{"taskId": "upscale", "dependsOn": "remux"}])
+        );
+    }
+
+    #[test]
+    fn unknown_and_circular_dependencies_fail_before_any_task_runs() {
+        for jobs in [
+            vec![CliJob {
+                input: PathBuf::from("/videos/a.mov"),
+                depends_on: Some("missing".to_string()),
+                output_dir: Some(PathBuf::from("/out")),
+                ..CliJob::default()
+            }],
+            vec![
+                CliJob {
+                    id: Some("a".to_string()),
+                    depends_on: Some("b".to_string()),
+                    output_dir: Some(PathBuf::from("/out")),
+                    ..CliJob::default()
+                },
+                CliJob {
+                    id: Some("b".to_string()),
+                    depends_on: Some("a".to_string()),
+                    output_dir: Some(PathBuf::from("/out")),
+                    ..CliJob::default()
+                },
+            ],
+        ] {
+            assert!(matches!(
+                build_cli_tasks(jobs, &CliOptions::default(), &AppSettings::default()),
+                Err(CliError::Dependencies(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn json_lines_mirror_the_event_names_and_payloads() {
+        let line = event_json_line(&ConversionEvent::progress("cli-1", 42.5));
+        let value: serde_json::Value = serde_json::from_str(&line).expect("line should be JSON");
+
+        assert_eq!(value["event"], "conversion-progress");
+        assert_eq!(value["payload"]["id"], "cli-1");
+        assert_eq!(value["payload"]["progress"], 42.5);
+    }
+}
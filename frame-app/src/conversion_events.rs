@@ -1,10 +1,18 @@
 //! GPUI-side reducers for backend conversion events.
 
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    path::Path,
+};
 
-use frame_core::events::ConversionEvent;
+use frame_core::{
+    event_queue::EventQueueStats,
+    events::ConversionEvent,
+    types::{ProgressStats, WarningPayload},
+};
 
-use crate::file_queue::{FileQueue, FileStatus};
+use crate::file_queue::{FileQueue, FileStatus, format_file_size};
 
 pub const LOG_STICKY_BOTTOM_THRESHOLD_PX: f64 = 25.0;
 
@@ -25,18 +33,51 @@ pub struct LogLine {
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct ConversionEventState {
     logs: BTreeMap<String, Vec<String>>,
+    /// On-disk logs reported with failures, keyed by task id.
+    log_file_paths: BTreeMap<String, String>,
+    /// Classified `FFmpeg` warnings of the latest run, keyed by task id.
+    warnings: BTreeMap<String, Vec<WarningPayload>>,
+    /// Index of the first log line of each task's latest run.
+    run_log_starts: BTreeMap<String, usize>,
+    /// Tasks whose latest run has not finished yet.
+    running_logs: BTreeSet<String>,
     selected_log_file_id: Option<String>,
+    /// Backpressure counters of the latest batch's event queue.
+    event_queue_stats: EventQueueStats,
 }
 
 impl ConversionEventState {
     #[must_use]
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             logs: BTreeMap::new(),
+            log_file_paths: BTreeMap::new(),
+            warnings: BTreeMap::new(),
+            run_log_starts: BTreeMap::new(),
+            running_logs: BTreeSet::new(),
             selected_log_file_id: None,
+            event_queue_stats: EventQueueStats::default(),
         }
     }
 
+    /// Note on the latest batch's event queue when it dropped progress or
+    /// log lines, or made the runner wait for the UI.
+    #[must_use]
+    pub fn event_queue_notice(&self) -> Option<String> {
+        let stats = self.event_queue_stats;
+        stats.saw_backpressure().then(|| {
+            format!(
+                "Conversion events fell behind: {} progress updates and {} log lines dropped, \
+                 {} sends waited for the UI",
+                stats.dropped_progress, stats.dropped_log_lines, stats.blocked_sends
+            )
+        })
+    }
+
+    pub const fn record_event_queue_stats(&mut self, stats: EventQueueStats) {
+        self.event_queue_stats = stats;
+    }
+
     #[must_use]
     pub fn selected_log_file_id(&self) -> Option<&str> {
         self.selected_log_file_id.as_deref()
@@ -47,13 +88,58 @@ impl ConversionEventState {
         self.logs.get(id).map_or(&[], Vec::as_slice)
     }
 
+    #[must_use]
+    pub fn log_file_path(&self, id: &str) -> Option<&str> {
+        self.log_file_paths.get(id).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn warnings_for(&self, id: &str) -> &[WarningPayload] {
+        self.warnings.get(id).map_or(&[], Vec::as_slice)
+    }
+
     pub fn remove_logs(&mut self, id: &str) {
         self.logs.remove(id);
+        self.log_file_paths.remove(id);
+        self.warnings.remove(id);
+        self.run_log_starts.remove(id);
+        self.running_logs.remove(id);
         if self.selected_log_file_id.as_deref() == Some(id) {
             self.selected_log_file_id = None;
         }
     }
 
+    /// Replaces the streamed log of `id`'s latest run with the complete log
+    /// the runner kept, when the stream is missing lines, keeping the
+    /// `[ERROR]` lines reported after it.
+    pub fn restore_log(&mut self, queue: &FileQueue, id: &str, retained: Vec<String>) {
+        if queue.file_by_id(id).is_none() || self.running_logs.contains(id) {
+            return;
+        }
+        let Some(logs) = self.logs.get_mut(id) else {
+            return;
+        };
+        let start = self
+            .run_log_starts
+            .get(id)
+            .copied()
+            .unwrap_or(0)
+            .min(logs.len());
+        let errors = logs[start..]
+            .iter()
+            .rev()
+            .take_while(|line| line.starts_with(ERROR_PREFIX))
+            .count();
+        let streamed = logs.len() - start - errors;
+        if retained.len() <= streamed {
+            return;
+        }
+        let errors = logs.split_off(logs.len() - errors);
+        logs.truncate(start);
+        logs.extend(retained);
+        logs.extend(errors);
+    }
+
     #[must_use]
     pub fn log_lines_for(&self, id: &str) -> Vec<LogLine> {
         self.log_line_window_for(id, 0..self.logs_for(id).len())
@@ -120,12 +206,23 @@ impl ConversionEventState {
     pub fn apply_conversion_event(&mut self, queue: &mut FileQueue, event: ConversionEvent) {
         match event {
             ConversionEvent::Started(payload) => {
+                self.log_file_paths.remove(&payload.id);
+                self.warnings.remove(&payload.id);
+                queue.update_output_size(&payload.id, None);
+                queue.update_warning_count(&payload.id, 0);
+                queue.update_progress_indeterminate(&payload.id, false);
                 if queue
                     .file_by_id(&payload.id)
                     .is_some_and(|file| file.status == FileStatus::Queued)
                 {
                     queue.update_status(&payload.id, FileStatus::Converting, 0);
                 }
+                if let Some(output_stem) = Path::new(&payload.output_path)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                {
+                    queue.update_output_name(&payload.id, output_stem);
+                }
             }
             ConversionEvent::Progress(payload) => {
                 if let Some(file) = queue.file_by_id(&payload.id) {
@@ -135,43 +232,103 @@ impl ConversionEventState {
                         file.status
                     };
                     queue.update_status(&payload.id, status, percent_to_u8(payload.progress));
+                    let detail = if payload.indeterminate {
+                        processed_label(&payload.stats)
+                    } else {
+                        payload.stats.speed_x.map(|speed| format!("{speed:.1}x"))
+                    };
+                    queue.update_progress_speed(&payload.id, detail);
+                    queue.update_progress_indeterminate(&payload.id, payload.indeterminate);
                 }
             }
             ConversionEvent::Completed(payload) => {
+                self.running_logs.remove(&payload.id);
                 queue.update_status(&payload.id, FileStatus::Completed, 100);
+                queue.update_output_size(&payload.id, payload.summary.output_size_bytes);
+                queue.update_warning_count(&payload.id, payload.summary.warning_count);
             }
             ConversionEvent::Error(payload) => {
+                self.running_logs.remove(&payload.id);
                 if queue.file_by_id(&payload.id).is_some() {
-                    self.logs
-                        .entry(payload.id.clone())
-                        .or_default()
-                        .push(format!("[ERROR] {}", payload.error));
+                    let logs = self.logs.entry(payload.id.clone()).or_default();
+                    append_missing_tail(logs, payload.log_tail);
+                    logs.push(format!("{ERROR_PREFIX}{}", payload.error));
+                    if let Some(log_path) = payload.log_path {
+                        self.log_file_paths.insert(payload.id.clone(), log_path);
+                    }
                 }
                 queue.update_error(&payload.id, payload.error);
             }
             ConversionEvent::Log(payload) => {
                 if queue.file_by_id(&payload.id).is_some() {
-                    self.logs.entry(payload.id).or_default().push(payload.line);
+                    self.run_logs(&payload.id).push(payload.line);
+                }
+            }
+            ConversionEvent::LogBatch(payload) => {
+                if queue.file_by_id(&payload.id).is_some() {
+                    self.run_logs(&payload.id).extend(payload.lines);
                 }
             }
             ConversionEvent::Cancelled(payload) => {
+                self.running_logs.remove(&payload.id);
                 queue.update_status(&payload.id, FileStatus::Idle, 0);
                 queue.clear_error(&payload.id);
             }
+            ConversionEvent::Warning(payload) => {
+                if queue.file_by_id(&payload.id).is_some() {
+                    // Repeats carry the first message with a higher count.
+                    let warnings = self.warnings.entry(payload.id.clone()).or_default();
+                    match warnings
+                        .iter_mut()
+                        .find(|warning| warning.message == payload.message)
+                    {
+                        Some(warning) => warning.count = payload.count,
+                        None => warnings.push(payload),
+                    }
+                }
+            }
         }
 
         self.ensure_selected_log_file(queue);
     }
+
+    /// Log of `id`, marking where its latest run starts when this line
+    /// begins a new run.
+    fn run_logs(&mut self, id: &str) -> &mut Vec<String> {
+        let logs = self.logs.entry(id.to_string()).or_default();
+        if self.running_logs.insert(id.to_string()) {
+            self.run_log_starts.insert(id.to_string(), logs.len());
+        }
+        logs
+    }
+}
+
+/// Prefix of the line recording why a task failed.
+const ERROR_PREFIX: &str = "[ERROR] ";
+
+/// Appends the `FFmpeg` output captured before a failure unless the streamed
+/// log already ends with it, as it does when no log lines were dropped.
+fn append_missing_tail(logs: &mut Vec<String>, tail: Vec<String>) {
+    if tail.is_empty() {
+        return;
+    }
+    let recent = &logs[logs.len().saturating_sub(tail.len() * 2)..];
+    if recent
+        .windows(tail.len())
+        .any(|window| window == tail.as_slice())
+    {
+        return;
+    }
+    logs.push(format!(
+        "[INFO] Last {} lines of FFmpeg output before the failure:",
+        tail.len()
+    ));
+    logs.extend(tail);
 }
 
 #[must_use]
 pub fn all_conversions_settled(queue: &FileQueue) -> bool {
-    queue.files().iter().all(|file| {
-        matches!(
-            file.status,
-            FileStatus::Completed | FileStatus::Error | FileStatus::Idle
-        )
-    })
+    queue.files().iter().all(|file| file.status.is_settled())
 }
 
 #[must_use]
@@ -183,6 +340,31 @@ pub fn should_stick_to_bottom(scroll_top: f64, scroll_height: f64, client_height
     scroll_height - scroll_top - client_height < LOG_STICKY_BOTTOM_THRESHOLD_PX
 }
 
+/// Time and bytes processed, such as `12:04 · 85.3 MB`, for sources whose
+/// length is unknown.
+fn processed_label(stats: &ProgressStats) -> Option<String> {
+    let time = stats.processed_seconds.map(|seconds| {
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "processed time stays far below u64::MAX seconds"
+        )]
+        #[expect(
+            clippy::cast_sign_loss,
+            reason = "FFmpeg progress timestamps are parsed as non-negative"
+        )]
+        let seconds = seconds as u64;
+        match seconds / 3600 {
+            0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+            hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60),
+        }
+    });
+    let parts: Vec<String> = time
+        .into_iter()
+        .chain(stats.written_bytes.map(format_file_size))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
 fn percent_to_u8(progress: f64) -> u8 {
     if !progress.is_finite() || progress <= 0.0 {
         return 0;
@@ -207,6 +389,10 @@ fn percent_to_u8(progress: f64) -> u8 {
 mod tests {
     use super::*;
     use crate::file_queue::FileItem;
+    use frame_core::{
+        error::ConversionError,
+        types::{ConversionWarningSeverity, OutputSummary},
+    };
 
     fn queue_with_file(status: FileStatus) -> FileQueue {
         let mut queue = FileQueue::new();
@@ -220,11 +406,16 @@ mod tests {
         let mut queue = queue_with_file(FileStatus::Queued);
         let mut state = ConversionEventState::new();
 
-        state.apply_conversion_event(&mut queue, ConversionEvent::started("task-1"));
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::started("task-1", "/tmp/out/source_converted_2.mp4"),
+        );
 
         assert_eq!(
-            queue.file_by_id("task-1").map(|file| file.status),
-            Some(FileStatus::Converting)
+            queue
+                .file_by_id("task-1")
+                .map(|file| (file.status, file.output_name.as_str())),
+            Some((FileStatus::Converting, "source_converted_2"))
         );
     }
 
@@ -240,6 +431,54 @@ mod tests {
         assert_eq!(file.progress_percent, 42);
     }
 
+    #[test]
+    fn apply_conversion_event_progress_tracks_latest_speed() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::progress_with_stats(
+                "task-1",
+                43.0,
+                ProgressStats {
+                    speed_x: Some(2.04),
+                    ..ProgressStats::default()
+                },
+            ),
+        );
+        let file = queue.file_by_id("task-1").expect("file should exist");
+        assert_eq!(file.row_state_label(), "43% · 2.0x");
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::progress("task-1", 44.0));
+        let file = queue.file_by_id("task-1").expect("file should exist");
+        assert_eq!(file.row_state_label(), "44%");
+    }
+
+    #[test]
+    fn apply_conversion_event_progress_of_unknown_length_shows_time_and_size() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::indeterminate_progress(
+                "task-1",
+                ProgressStats {
+                    processed_seconds: Some(3_725.4),
+                    written_bytes: Some(1_572_864),
+                    ..ProgressStats::default()
+                },
+            ),
+        );
+        let file = queue.file_by_id("task-1").expect("file should exist");
+        assert_eq!(file.row_state_label(), "1:02:05 · 1.5 MB");
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::progress("task-1", 44.0));
+        let file = queue.file_by_id("task-1").expect("file should exist");
+        assert_eq!(file.row_state_label(), "44%");
+    }
+
     #[test]
     fn apply_conversion_event_completed_marks_file_ready() {
         let mut queue = queue_with_file(FileStatus::Converting);
@@ -255,6 +494,77 @@ mod tests {
         assert_eq!(file.progress_percent, 100);
     }
 
+    #[test]
+    fn apply_conversion_event_completed_keeps_output_size_until_restarted() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::completed_with_summary(
+                "task-1",
+                "/tmp/output.mp4",
+                None,
+                OutputSummary {
+                    output_size_bytes: Some(1536),
+                    ..OutputSummary::default()
+                },
+            ),
+        );
+        let completed_label = queue
+            .file_by_id("task-1")
+            .expect("file should exist")
+            .row_state_label();
+        queue.update_status("task-1", FileStatus::Queued, 0);
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::started("task-1", "/tmp/output.mp4"),
+        );
+
+        assert_eq!(completed_label, "ready · 1.5 KB");
+        assert_eq!(
+            queue
+                .file_by_id("task-1")
+                .expect("file should exist")
+                .output_size_bytes,
+            None
+        );
+    }
+
+    #[test]
+    fn apply_conversion_event_warning_updates_repeat_count() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+        let warning = |message: &str, count| {
+            ConversionEvent::Warning(WarningPayload {
+                id: "task-1".to_string(),
+                severity: ConversionWarningSeverity::Warning,
+                message: message.to_string(),
+                count,
+            })
+        };
+
+        state.apply_conversion_event(&mut queue, warning("Non-monotonous DTS", 1));
+        state.apply_conversion_event(&mut queue, warning("Guessed Channel Layout", 1));
+        state.apply_conversion_event(&mut queue, warning("Non-monotonous DTS", 40));
+
+        assert_eq!(
+            state
+                .warnings_for("task-1")
+                .iter()
+                .map(|warning| (warning.message.as_str(), warning.count))
+                .collect::<Vec<_>>(),
+            [("Non-monotonous DTS", 40), ("Guessed Channel Layout", 1)]
+        );
+
+        queue.update_status("task-1", FileStatus::Queued, 0);
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::started("task-1", "/tmp/output.mp4"),
+        );
+        assert!(state.warnings_for("task-1").is_empty());
+    }
+
     #[test]
     fn apply_conversion_event_error_stores_message() {
         let mut queue = queue_with_file(FileStatus::Converting);
@@ -271,6 +581,124 @@ mod tests {
         assert_eq!(state.logs_for("task-1"), ["[ERROR] ffmpeg failed"]);
     }
 
+    #[test]
+    fn apply_conversion_event_error_keeps_log_file_path_until_restart() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::failed(
+                "task-1",
+                &ConversionError::Worker("ffmpeg failed".to_string()),
+                Vec::new(),
+                Some("/logs/task-1.log".to_string()),
+            ),
+        );
+        assert_eq!(state.log_file_path("task-1"), Some("/logs/task-1.log"));
+
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::started("task-1", "/tmp/output.mp4"),
+        );
+        assert_eq!(state.log_file_path("task-1"), None);
+    }
+
+    #[test]
+    fn apply_conversion_event_error_adds_log_tail_missing_from_stream() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+        let failed = |tail: &[&str]| {
+            ConversionEvent::error_with_log_tail(
+                "task-1",
+                "ffmpeg failed",
+                tail.iter().map(ToString::to_string).collect(),
+            )
+        };
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "frame=1"));
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "No space left"));
+        state.apply_conversion_event(&mut queue, failed(&["frame=1", "No space left"]));
+        assert_eq!(
+            state.logs_for("task-1"),
+            ["frame=1", "No space left", "[ERROR] ffmpeg failed"]
+        );
+
+        state.remove_logs("task-1");
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "frame=1"));
+        state.apply_conversion_event(&mut queue, failed(&["frame=9", "No space left"]));
+        assert_eq!(
+            state.logs_for("task-1"),
+            [
+                "frame=1",
+                "[INFO] Last 2 lines of FFmpeg output before the failure:",
+                "frame=9",
+                "No space left",
+                "[ERROR] ffmpeg failed"
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_log_replaces_only_the_latest_run() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+        let retained = || {
+            vec![
+                "line 1".to_string(),
+                "line 2".to_string(),
+                "line 3".to_string(),
+            ]
+        };
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "first run"));
+        state.apply_conversion_event(&mut queue, ConversionEvent::error("task-1", "failed"));
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "line 1"));
+        state.restore_log(&queue, "task-1", retained());
+        assert_eq!(state.logs_for("task-1").len(), 3);
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "line 3"));
+        state.apply_conversion_event(&mut queue, ConversionEvent::error("task-1", "failed again"));
+        state.restore_log(&queue, "task-1", retained());
+
+        assert_eq!(
+            state.logs_for("task-1"),
+            [
+                "first run",
+                "[ERROR] failed",
+                "line 1",
+                "line 2",
+                "line 3",
+                "[ERROR] failed again"
+            ]
+        );
+    }
+
+    #[test]
+    fn event_queue_notice_reports_backpressure_only() {
+        let mut state = ConversionEventState::new();
+        state.record_event_queue_stats(EventQueueStats {
+            coalesced_logs: 12,
+            peak_len: 40,
+            ..EventQueueStats::default()
+        });
+        assert_eq!(state.event_queue_notice(), None);
+
+        state.record_event_queue_stats(EventQueueStats {
+            dropped_progress: 3,
+            dropped_log_lines: 250,
+            blocked_sends: 1,
+            ..EventQueueStats::default()
+        });
+        assert_eq!(
+            state.event_queue_notice().as_deref(),
+            Some(
+                "Conversion events fell behind: 3 progress updates and 250 log lines dropped, \
+                 1 sends waited for the UI"
+            )
+        );
+    }
+
     #[test]
     fn apply_conversion_event_cancelled_resets_file_to_idle() {
         let mut queue = queue_with_file(FileStatus::Converting);
@@ -309,6 +737,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn apply_conversion_event_log_batch_appends_all_lines() {
+        let mut queue = queue_with_file(FileStatus::Converting);
+        let mut state = ConversionEventState::new();
+
+        state.apply_conversion_event(&mut queue, ConversionEvent::log("task-1", "first"));
+        state.apply_conversion_event(
+            &mut queue,
+            ConversionEvent::log_batch("task-1", vec!["second".to_string(), "third".to_string()]),
+        );
+
+        assert_eq!(state.logs_for("task-1"), ["first", "second", "third"]);
+    }
+
     #[test]
     fn apply_conversion_event_log_ignores_removed_files() {
         let mut queue = FileQueue::new();
@@ -0,0 +1,664 @@
+use std::{
+    fs,
+    io::Read,
+    process::{self, Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use frame_core::{
+    analysis::{
+        CropDetection, InterlaceAnalysis, LoudnessAnalysis, SceneAnalysis,
+        collect_scene_timestamps, crop_detect_args, crop_detect_sample_times, ebur128_frame_time,
+        interlace_analysis_args, loudness_analysis_args, parse_cropdetect_rectangle,
+        parse_ebur128_summary, parse_idet_summary, scene_analysis_args, showinfo_pts_time,
+        summarize_crop_samples,
+    },
+    bitrate::{
+        BitrateAnalysis, BitrateBuckets, bitrate_probe_args, parse_packet_line,
+        validate_bitrate_bucket,
+    },
+    error::ConversionError,
+    events::{ConversionEvent, ConversionEventCoalescer},
+    probe::classify_probe_failure,
+    quality::{
+        QualityComparison, QualityMetric, build_quality_comparison_args, parse_frame_scores,
+        parse_vmaf_log,
+    },
+    types::{ConversionPhase, ProbeMetadata},
+    utils::{TIME_REGEX, parse_time},
+};
+
+use super::{
+    ConversionProcessController,
+    output_paths::sanitized_task_id,
+    process::{isolate_process_tree, kill_process},
+    runner::probe_media_file,
+};
+use crate::{
+    capabilities::cached_ffmpeg_capabilities,
+    runtime_binaries::{ffmpeg_executable, ffprobe_executable},
+};
+
+/// Longest time an analysis pass may run before it is killed, so unreadable
+/// inputs cannot hold a worker slot indefinitely.
+pub const MEDIA_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(120);
+const MEDIA_ANALYSIS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest time a pass decoding a whole source of unknown length may run,
+/// such as a loudness scan or scene detection.
+pub const FULL_DECODE_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+/// Multiple of a source's length a full decode may take before it is killed;
+/// lossless audio and network shares can decode slower than real time.
+const FULL_DECODE_TIMEOUT_FACTOR: f64 = 4.0;
+
+/// Longest time a quality comparison may run. It decodes two files in full,
+/// and VMAF scores frames far slower than real time.
+pub const QUALITY_COMPARISON_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Longest time a bitrate analysis may run. Packets are listed without
+/// decoding, but very long files still hold millions of them.
+pub const BITRATE_ANALYSIS_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Runs the `idet` filter over the start of `file_path`.
+///
+/// The pass is tracked under `analysis_id`, so
+/// [`ConversionProcessController::cancel_task`] stops it like a conversion.
+///
+/// # Errors
+///
+/// Returns an error when `FFmpeg` cannot start, fails, is cancelled, exceeds
+/// [`MEDIA_ANALYSIS_TIMEOUT`], or reports no `idet` summary.
+pub fn analyze_interlacing(
+    analysis_id: &str,
+    file_path: &str,
+    controller: &ConversionProcessController,
+) -> Result<InterlaceAnalysis, ConversionError> {
+    let stderr = run_ffmpeg_analysis(
+        analysis_id,
+        &interlace_analysis_args(file_path),
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |_| {},
+    )?;
+
+    parse_idet_summary(&stderr).ok_or_else(|| {
+        ConversionError::Worker("ffmpeg did not report interlace detection results".to_string())
+    })
+}
+
+/// Measures EBU R128 loudness of audio stream `track_index`, or the first
+/// audio stream, of `file_path`.
+///
+/// Decode progress is emitted as [`ConversionEvent::Progress`] events under
+/// `analysis_id`, which also identifies the pass for cancellation.
+///
+/// # Errors
+///
+/// Returns an error when the source has no matching audio stream, or when
+/// `FFmpeg` fails, is cancelled, exceeds the [`full_decode_timeout`] of the
+/// source, or reports no loudness summary.
+pub fn analyze_loudness(
+    analysis_id: &str,
+    file_path: &str,
+    track_index: Option<u32>,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<LoudnessAnalysis, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let stream_index = match track_index {
+        Some(index) => probe
+            .audio_tracks
+            .iter()
+            .find(|track| track.index == index)
+            .map(|track| track.index)
+            .ok_or_else(|| {
+                ConversionError::InvalidInput(format!(
+                    "Source has no audio track with index {index}"
+                ))
+            })?,
+        None => probe
+            .audio_tracks
+            .first()
+            .map(|track| track.index)
+            .ok_or_else(|| {
+                ConversionError::InvalidInput("Source has no audio tracks".to_string())
+            })?,
+    };
+    let duration = source_duration_seconds(&probe);
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    emit(analysis_progress(analysis_id, 0.0));
+    let stderr = run_ffmpeg_analysis(
+        analysis_id,
+        &loudness_analysis_args(file_path, stream_index),
+        controller,
+        full_decode_timeout(duration),
+        &mut |line| {
+            if let (Some(time), Some(duration)) = (ebur128_frame_time(line), duration) {
+                let progress = (time / duration * 100.0).clamp(0.0, 100.0);
+                coalescer.push(
+                    analysis_progress(analysis_id, progress),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+
+    let analysis = parse_ebur128_summary(&stderr?).ok_or_else(|| {
+        ConversionError::Worker("ffmpeg did not report a loudness summary".to_string())
+    })?;
+    emit(analysis_progress(analysis_id, 100.0));
+    Ok(analysis)
+}
+
+/// Detects scene cuts in `file_path` whose change score exceeds `threshold`.
+///
+/// Decode progress is emitted as [`ConversionEvent::Progress`] events under
+/// `analysis_id`, which also identifies the pass for cancellation. At most
+/// [`SCENE_ANALYSIS_MAX_SCENES`](frame_core::analysis::SCENE_ANALYSIS_MAX_SCENES)
+/// cuts are returned.
+///
+/// # Errors
+///
+/// Returns an error when `threshold` is outside `0.0..=1.0`, or when `FFmpeg`
+/// fails, is cancelled, or exceeds the [`full_decode_timeout`] of the source.
+pub fn analyze_scenes(
+    analysis_id: &str,
+    file_path: &str,
+    threshold: f64,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<SceneAnalysis, ConversionError> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Scene threshold must be between 0 and 1, got {threshold}"
+        )));
+    }
+    let duration = source_duration_seconds(&probe_media_file(file_path)?);
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut cuts = Vec::new();
+    emit(analysis_progress(analysis_id, 0.0));
+    let result = run_ffmpeg_analysis(
+        analysis_id,
+        &scene_analysis_args(file_path, threshold),
+        controller,
+        full_decode_timeout(duration),
+        &mut |line| {
+            let cut = showinfo_pts_time(line);
+            cuts.extend(cut);
+            let time = cut.or_else(|| {
+                TIME_REGEX
+                    .captures(line)
+                    .and_then(|caps| caps.get(1))
+                    .and_then(|m| parse_time(m.as_str()))
+            });
+            if let (Some(time), Some(duration)) = (time, duration) {
+                let progress = (time / duration * 100.0).clamp(0.0, 100.0);
+                coalescer.push(
+                    analysis_progress(analysis_id, progress),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    result?;
+
+    emit(analysis_progress(analysis_id, 100.0));
+    Ok(collect_scene_timestamps(cuts))
+}
+
+/// Samples `cropdetect` at several points of `file_path` to suggest a crop
+/// rectangle that removes black bars.
+///
+/// Every sample runs under `analysis_id`, so cancelling it stops the
+/// remaining samples as well.
+///
+/// # Errors
+///
+/// Returns an error when the source has no video stream, or when `FFmpeg`
+/// fails, is cancelled, or exceeds [`MEDIA_ANALYSIS_TIMEOUT`] on a sample.
+pub fn detect_crop(
+    analysis_id: &str,
+    file_path: &str,
+    controller: &ConversionProcessController,
+) -> Result<CropDetection, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let (Some(width), Some(height)) = (probe.width, probe.height) else {
+        return Err(ConversionError::InvalidInput(
+            "Source has no video stream to detect crop on".to_string(),
+        ));
+    };
+
+    let mut samples = Vec::new();
+    for seek_seconds in crop_detect_sample_times(source_duration_seconds(&probe)) {
+        let stderr = run_ffmpeg_analysis(
+            analysis_id,
+            &crop_detect_args(file_path, seek_seconds),
+            controller,
+            MEDIA_ANALYSIS_TIMEOUT,
+            &mut |_| {},
+        )?;
+        samples.extend(parse_cropdetect_rectangle(&stderr));
+    }
+
+    Ok(summarize_crop_samples(&samples, width, height))
+}
+
+/// Scores how closely `distorted_path` matches `reference_path` with
+/// `metric`.
+///
+/// The distorted file is scaled, cropped and retimed to the reference as
+/// needed, and files of different lengths are compared over the start they
+/// share; each adjustment is listed in the result notes. Decode progress is
+/// emitted as [`ConversionEvent::Progress`] events under `analysis_id`,
+/// which also identifies the pass for cancellation.
+///
+/// # Errors
+///
+/// Returns an error when VMAF is asked of an `FFmpeg` build without
+/// `libvmaf`, either file has no video, or `FFmpeg` fails, is cancelled,
+/// exceeds [`QUALITY_COMPARISON_TIMEOUT`], or reports no scores.
+pub fn compare_quality(
+    analysis_id: &str,
+    reference_path: &str,
+    distorted_path: &str,
+    metric: QualityMetric,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<QualityComparison, ConversionError> {
+    if cached_ffmpeg_capabilities()
+        .is_some_and(|capabilities| !capabilities.has_filter(metric.filter_name()))
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "This FFmpeg build has no {} filter; compare with SSIM or PSNR instead",
+            metric.filter_name()
+        )));
+    }
+    let reference_probe = probe_media_file(reference_path)?;
+    let distorted_probe = probe_media_file(distorted_path)?;
+    let vmaf_log_path = std::env::temp_dir().join(format!(
+        "frame-vmaf-{}-{}.json",
+        process::id(),
+        sanitized_task_id(analysis_id)
+    ));
+    let plan = build_quality_comparison_args(
+        reference_path,
+        distorted_path,
+        metric,
+        &reference_probe,
+        &distorted_probe,
+        &vmaf_log_path.to_string_lossy(),
+    )?;
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    emit(analysis_progress(analysis_id, 0.0));
+    let result = run_ffmpeg_analysis(
+        analysis_id,
+        &plan.args,
+        controller,
+        QUALITY_COMPARISON_TIMEOUT,
+        &mut |line| {
+            let time = TIME_REGEX
+                .captures(line)
+                .and_then(|caps| caps.get(1))
+                .and_then(|m| parse_time(m.as_str()));
+            if let (Some(time), Some(duration)) = (time, plan.compared_seconds) {
+                let progress = (time / duration * 100.0).clamp(0.0, 100.0);
+                coalescer.push(
+                    analysis_progress(analysis_id, progress),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    let vmaf_log = fs::read_to_string(&vmaf_log_path);
+    let _ = fs::remove_file(&vmaf_log_path);
+    let stderr = result?;
+
+    let scores = match metric {
+        QualityMetric::Vmaf => vmaf_log
+            .ok()
+            .as_deref()
+            .and_then(parse_vmaf_log)
+            .map(|(mean, min, harmonic_mean, frames)| (mean, min, Some(harmonic_mean), frames)),
+        QualityMetric::Ssim | QualityMetric::Psnr => {
+            parse_frame_scores(metric, &stderr).map(|(mean, min, frames)| (mean, min, None, frames))
+        }
+    };
+    let (mean, min, harmonic_mean, frames) = scores.ok_or_else(|| {
+        ConversionError::Worker("ffmpeg did not report quality scores".to_string())
+    })?;
+    emit(analysis_progress(analysis_id, 100.0));
+    Ok(QualityComparison {
+        metric,
+        mean,
+        min,
+        harmonic_mean,
+        frames,
+        compared_seconds: plan.compared_seconds,
+        notes: plan.notes,
+    })
+}
+
+/// Measures the bitrate of the first video stream of `file_path` in windows
+/// of `bucket_seconds`, from the packet sizes `FFprobe` lists.
+///
+/// Packets are summed as `FFprobe` prints them, so the listing is never held
+/// in memory whole. Progress is emitted as [`ConversionEvent::Progress`]
+/// events under `analysis_id`, which also identifies the pass for
+/// cancellation.
+///
+/// # Errors
+///
+/// Returns an error when `bucket_seconds` is shorter than
+/// [`MIN_BITRATE_BUCKET_SECONDS`](frame_core::bitrate::MIN_BITRATE_BUCKET_SECONDS),
+/// the source has no video stream, or `FFprobe` fails, is cancelled or
+/// exceeds [`BITRATE_ANALYSIS_TIMEOUT`].
+pub fn analyze_bitrate(
+    analysis_id: &str,
+    file_path: &str,
+    bucket_seconds: f64,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<BitrateAnalysis, ConversionError> {
+    validate_bitrate_bucket(bucket_seconds)?;
+    let probe = probe_media_file(file_path)?;
+    if probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "Source has no video stream to measure the bitrate of".to_string(),
+        ));
+    }
+    let duration = source_duration_seconds(&probe);
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut buckets = BitrateBuckets::new(bucket_seconds);
+    emit(analysis_progress(analysis_id, 0.0));
+    let result = run_ffprobe_analysis(
+        analysis_id,
+        &bitrate_probe_args(file_path),
+        controller,
+        BITRATE_ANALYSIS_TIMEOUT,
+        &mut |line| {
+            let Some(packet) = parse_packet_line(line) else {
+                return;
+            };
+            buckets.push(packet);
+            if let Some(duration) = duration {
+                let progress = (buckets.end_seconds() / duration * 100.0).clamp(0.0, 100.0);
+                coalescer.push(
+                    analysis_progress(analysis_id, progress),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    result?;
+
+    emit(analysis_progress(analysis_id, 100.0));
+    Ok(buckets.finish())
+}
+
+fn source_duration_seconds(probe: &ProbeMetadata) -> Option<f64> {
+    probe
+        .duration
+        .as_deref()
+        .and_then(|duration| duration.trim().parse::<f64>().ok())
+        .filter(|duration| *duration > 0.0)
+}
+
+/// Time allowed for a pass that decodes a whole source lasting `duration`
+/// seconds: four times its length but at least [`MEDIA_ANALYSIS_TIMEOUT`],
+/// or [`FULL_DECODE_ANALYSIS_TIMEOUT`] when the length is unknown.
+#[must_use]
+pub fn full_decode_timeout(duration: Option<f64>) -> Duration {
+    duration
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds * FULL_DECODE_TIMEOUT_FACTOR).ok())
+        .map_or(FULL_DECODE_ANALYSIS_TIMEOUT, |timeout| {
+            timeout.max(MEDIA_ANALYSIS_TIMEOUT)
+        })
+}
+
+pub(super) fn analysis_progress(analysis_id: &str, progress: f64) -> ConversionEvent {
+    ConversionEvent::progress(analysis_id, progress).with_phase(ConversionPhase::Analyze)
+}
+
+/// Runs an `FFmpeg` analysis command to completion, passing each stderr line
+/// to `on_line`, and returns the full stderr output.
+pub(super) fn run_ffmpeg_analysis(
+    analysis_id: &str,
+    args: &[String],
+    controller: &ConversionProcessController,
+    timeout: Duration,
+    on_line: &mut impl FnMut(&str),
+) -> Result<String, ConversionError> {
+    if controller.take_cancelled(analysis_id)? {
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let mut child = isolate_process_tree(&mut Command::new(ffmpeg_executable()))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(ConversionError::Io)?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| ConversionError::Worker("ffmpeg stderr was not captured".to_string()))?;
+    let (line_tx, line_rx) = mpsc::channel();
+    let stderr_reader = thread::spawn(move || read_stderr_lines(stderr, &line_tx));
+
+    if controller.register_started_process(analysis_id, child.id())? {
+        let _ = child.wait();
+        let _ = stderr_reader.join();
+        let _ = controller.finish_task(analysis_id);
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let mut output = String::new();
+    let mut record_line = |line: String| {
+        on_line(&line);
+        output.push_str(&line);
+        output.push('\n');
+    };
+    let started_at = Instant::now();
+    let status = loop {
+        match line_rx.recv_timeout(MEDIA_ANALYSIS_POLL_INTERVAL) {
+            Ok(line) => record_line(line),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(MEDIA_ANALYSIS_POLL_INTERVAL),
+        }
+
+        if started_at.elapsed() >= timeout {
+            let _ = kill_process(child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+            break Err(ConversionError::Worker(format!(
+                "ffmpeg analysis timed out after {} seconds",
+                timeout.as_secs()
+            )));
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Err(ConversionError::Io(error));
+            }
+        }
+    };
+
+    let was_cancelled = controller.finish_task(analysis_id)?;
+    stderr_reader
+        .join()
+        .map_err(|_| ConversionError::Worker("ffmpeg stderr reader panicked".to_string()))?;
+    line_rx.try_iter().for_each(&mut record_line);
+    if was_cancelled {
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let status = status?;
+    if status.success() {
+        Ok(output)
+    } else {
+        let message = output
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map_or_else(
+                || format!("ffmpeg exited with status {status}"),
+                str::to_string,
+            );
+        Err(ConversionError::Worker(message))
+    }
+}
+
+/// Runs an `FFprobe` listing to completion, passing each stdout line to
+/// `on_line` as it arrives without keeping the output.
+fn run_ffprobe_analysis(
+    analysis_id: &str,
+    args: &[String],
+    controller: &ConversionProcessController,
+    timeout: Duration,
+    on_line: &mut impl FnMut(&str),
+) -> Result<(), ConversionError> {
+    if controller.take_cancelled(analysis_id)? {
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let mut child = isolate_process_tree(&mut Command::new(ffprobe_executable()))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(ConversionError::Io)?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ConversionError::Worker("ffprobe stdout was not captured".to_string()))?;
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| ConversionError::Worker("ffprobe stderr was not captured".to_string()))?;
+    let (line_tx, line_rx) = mpsc::channel();
+    let stdout_reader = thread::spawn(move || read_stderr_lines(stdout, &line_tx));
+    let stderr_reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    if controller.register_started_process(analysis_id, child.id())? {
+        let _ = child.wait();
+        drop(line_rx);
+        let _ = stdout_reader.join();
+        let _ = stderr_reader.join();
+        let _ = controller.finish_task(analysis_id);
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let started_at = Instant::now();
+    let status = loop {
+        match line_rx.recv_timeout(MEDIA_ANALYSIS_POLL_INTERVAL) {
+            Ok(line) => on_line(&line),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(MEDIA_ANALYSIS_POLL_INTERVAL),
+        }
+
+        if started_at.elapsed() >= timeout {
+            let _ = kill_process(child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+            break Err(ConversionError::Worker(format!(
+                "ffprobe analysis timed out after {} seconds",
+                timeout.as_secs()
+            )));
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Err(ConversionError::Io(error));
+            }
+        }
+    };
+
+    let was_cancelled = controller.finish_task(analysis_id)?;
+    stdout_reader
+        .join()
+        .map_err(|_| ConversionError::Worker("ffprobe stdout reader panicked".to_string()))?;
+    line_rx.try_iter().for_each(|line| on_line(&line));
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if was_cancelled {
+        return Err(analysis_cancelled(analysis_id));
+    }
+
+    let status = status?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ConversionError::ProbeFailed(classify_probe_failure(
+            &stderr,
+            &status.to_string(),
+        )))
+    }
+}
+
+/// Forwards stderr segments split on `\r` as well as `\n`, since `FFmpeg`
+/// rewrites its `time=` stats line in place.
+fn read_stderr_lines(mut stderr: impl Read, line_tx: &mpsc::Sender<String>) {
+    let mut buffer = [0_u8; 4096];
+    let mut pending = Vec::new();
+    loop {
+        let read = match stderr.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        pending.extend_from_slice(&buffer[..read]);
+        while let Some(separator_index) = pending
+            .iter()
+            .position(|byte| matches!(byte, b'\r' | b'\n'))
+        {
+            let segment: Vec<u8> = pending.drain(..=separator_index).collect();
+            let line = String::from_utf8_lossy(&segment).trim_end().to_string();
+            if !line.is_empty() && line_tx.send(line).is_err() {
+                return;
+            }
+        }
+    }
+
+    let line = String::from_utf8_lossy(&pending).trim_end().to_string();
+    if !line.is_empty() {
+        let _ = line_tx.send(line);
+    }
+}
+
+pub(super) fn analysis_cancelled(analysis_id: &str) -> ConversionError {
+    ConversionError::Worker(format!("Analysis {analysis_id} was cancelled"))
+}
+
+/// Reports whether `error` is the one [`run_ffmpeg_analysis`] returns when
+/// the pass under `analysis_id` is cancelled.
+pub(super) fn is_analysis_cancelled(error: &ConversionError, analysis_id: &str) -> bool {
+    matches!(
+        (error, analysis_cancelled(analysis_id)),
+        (ConversionError::Worker(message), ConversionError::Worker(expected)) if *message == expected
+    )
+}
@@ -0,0 +1,346 @@
+//! Parallel chunked encodes: the video chunks and the audio pass run as
+//! concurrent `FFmpeg` processes, and the runner then joins their files into
+//! the output with a stream-copy mux.
+
+use std::{
+    fs,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use frame_core::{
+    args::build_chunk_join_args,
+    chunked::{
+        ChunkProgress, ParallelChunk, build_chunk_audio_args, build_chunk_video_args,
+        parallel_chunk_count, plan_parallel_chunks,
+    },
+    concat::concat_list,
+    error::ConversionError,
+    events::{ConversionEvent, ConversionEventCoalescer},
+    progress::{FFMPEG_PROGRESS_ARGS, FfmpegProgressParser},
+    smart_cut::{keyframe_probe_args, parse_keyframe_times},
+    types::{ConversionPhase, ConversionTask, ProbeMetadata},
+};
+
+use crate::{
+    runtime_binaries::{ffmpeg_executable, ffprobe_executable},
+    source_metadata::run_ffprobe,
+};
+
+use super::{
+    analysis::{MEDIA_ANALYSIS_TIMEOUT, analysis_cancelled},
+    controller::ConversionProcessController,
+    process::{isolate_process_tree, kill_process},
+    runner::{ConcatScratch, ffmpeg_path_arg, read_output_segments},
+};
+
+/// How often the passes are checked for exits and cancellation.
+const CHUNK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Encoded video chunks and audio of a task, kept in a scratch directory
+/// that is removed with them.
+pub(super) struct ChunkedEncode {
+    scratch: ConcatScratch,
+    audio_path: Option<String>,
+}
+
+impl ChunkedEncode {
+    /// Arguments that join the chunks and the audio into `output`.
+    pub(super) fn join_args(
+        &self,
+        task: &ConversionTask,
+        probe: &ProbeMetadata,
+        output: &str,
+    ) -> Result<Vec<String>, ConversionError> {
+        let audio_path = self.audio_path.as_deref().map(ffmpeg_path_arg);
+        build_chunk_join_args(
+            &ffmpeg_path_arg(&self.scratch.list_path),
+            audio_path.as_deref(),
+            &ffmpeg_path_arg(&task.file_path),
+            output,
+            &task.config,
+            probe,
+        )
+    }
+}
+
+/// Encodes the chunks and audio of a task that asks for parallel chunks.
+/// Sources that cannot be split fall back to a single encode with a
+/// warning.
+///
+/// Any failed pass stops the others, and their files are removed with the
+/// scratch directory.
+pub(super) fn prepare_parallel_chunks(
+    task: &ConversionTask,
+    probe: &ProbeMetadata,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<Option<ChunkedEncode>, ConversionError> {
+    let Some(count) = parallel_chunk_count(&task.config) else {
+        return Ok(None);
+    };
+    if !task.concat_inputs.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Joining sources cannot be combined with parallel chunked encoding".to_string(),
+        ));
+    }
+    let (chunks, duration) = match plan_chunks(task, probe, count) {
+        Ok(plan) => plan,
+        Err(reason) => {
+            emit(ConversionEvent::log(
+                task.id.clone(),
+                format!("[WARN] Parallel chunks unavailable, encoding in one pass: {reason}"),
+            ));
+            return Ok(None);
+        }
+    };
+
+    let scratch = ConcatScratch::create(task, "chunks")?;
+    let chunk_paths: Vec<String> = (0..chunks.len())
+        .map(|index| {
+            scratch
+                .directory
+                .join(format!("chunk{index}.mkv"))
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let audio_path = scratch
+        .directory
+        .join("audio.mka")
+        .to_string_lossy()
+        .into_owned();
+    let input = ffmpeg_path_arg(&task.file_path);
+    let mut passes: Vec<(Option<usize>, Vec<String>)> = chunks
+        .iter()
+        .zip(&chunk_paths)
+        .enumerate()
+        .map(|(index, (chunk, path))| {
+            let args =
+                build_chunk_video_args(&input, &ffmpeg_path_arg(path), &task.config, probe, chunk);
+            (Some(index), args)
+        })
+        .collect();
+    let audio_args =
+        build_chunk_audio_args(&input, &ffmpeg_path_arg(&audio_path), &task.config, probe)?;
+    let has_audio = audio_args.is_some();
+    passes.extend(audio_args.map(|args| (None, args)));
+
+    emit(
+        ConversionEvent::log(
+            task.id.clone(),
+            format!(
+                "[INFO] Encoding {} chunks in parallel, split at {}",
+                chunks.len(),
+                chunks[1..]
+                    .iter()
+                    .map(|chunk| format!("{:.3}s", chunk.start))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .with_phase(ConversionPhase::Encode),
+    );
+    run_chunk_passes(
+        task,
+        passes,
+        ChunkProgress::new(&chunks, duration),
+        controller,
+        emit,
+    )?;
+    fs::write(&scratch.list_path, concat_list(&chunk_paths)).map_err(ConversionError::Io)?;
+    Ok(Some(ChunkedEncode {
+        scratch,
+        audio_path: has_audio.then_some(audio_path),
+    }))
+}
+
+/// Chunks of the source, cut at the keyframes read from the whole file,
+/// with the source duration.
+fn plan_chunks(
+    task: &ConversionTask,
+    probe: &ProbeMetadata,
+    count: u32,
+) -> Result<(Vec<ParallelChunk>, f64), String> {
+    if probe.video_codec.is_none() {
+        return Err("the source has no video stream".to_string());
+    }
+    let duration = probe
+        .duration_seconds()
+        .ok_or_else(|| "the source duration is unknown".to_string())?;
+    let stdout = run_ffprobe(
+        &ffprobe_executable(),
+        &keyframe_probe_args(&ffmpeg_path_arg(&task.file_path), 0.0, duration),
+        MEDIA_ANALYSIS_TIMEOUT,
+    )
+    .map_err(|error| format!("keyframes could not be read: {error}"))?;
+    let chunks = plan_parallel_chunks(duration, &parse_keyframe_times(&stdout), count);
+    if chunks.len() < 2 {
+        return Err("the source is too short or has too few keyframes to split".to_string());
+    }
+    Ok((chunks, duration))
+}
+
+/// Output of one pass, tagged with the pass it came from.
+enum PassOutput {
+    Log(usize, String),
+    Progress(usize, String),
+}
+
+/// A running pass: a video chunk when `chunk` is set, otherwise the audio.
+struct RunningPass {
+    child: Child,
+    chunk: Option<usize>,
+    parser: FfmpegProgressParser,
+    last_line: String,
+    status: Option<ExitStatus>,
+}
+
+impl RunningPass {
+    fn name(&self) -> String {
+        self.chunk.map_or_else(
+            || "Audio pass".to_string(),
+            |index| format!("Chunk {}", index + 1),
+        )
+    }
+}
+
+/// Runs every pass at once, reporting their combined progress, until all of
+/// them finish. A failed pass or a cancelled task stops the rest.
+fn run_chunk_passes(
+    task: &ConversionTask,
+    passes: Vec<(Option<usize>, Vec<String>)>,
+    mut progress: ChunkProgress,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<(), ConversionError> {
+    let (output_tx, output_rx) = mpsc::channel();
+    let mut running: Vec<RunningPass> = Vec::with_capacity(passes.len());
+    let mut readers = Vec::with_capacity(passes.len() * 2);
+    for (index, (chunk, args)) in passes.into_iter().enumerate() {
+        let spawned = isolate_process_tree(&mut Command::new(ffmpeg_executable()))
+            .args(FFMPEG_PROGRESS_ARGS)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(error) => {
+                stop_passes(&mut running);
+                return Err(ConversionError::Io(error));
+            }
+        };
+        if let Some(mut stderr) = child.stderr.take() {
+            let output_tx = output_tx.clone();
+            readers.push(thread::spawn(move || {
+                read_output_segments(&mut stderr, |line| {
+                    let _ = output_tx.send(PassOutput::Log(index, line));
+                })
+            }));
+        }
+        if let Some(mut stdout) = child.stdout.take() {
+            let output_tx = output_tx.clone();
+            readers.push(thread::spawn(move || {
+                read_output_segments(&mut stdout, |line| {
+                    let _ = output_tx.send(PassOutput::Progress(index, line));
+                })
+            }));
+        }
+        running.push(RunningPass {
+            child,
+            chunk,
+            parser: FfmpegProgressParser::default(),
+            last_line: String::new(),
+            status: None,
+        });
+    }
+    drop(output_tx);
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut handle_output = |output: PassOutput, running: &mut [RunningPass]| match output {
+        PassOutput::Log(index, line) => running[index].last_line = line,
+        PassOutput::Progress(index, line) => {
+            let pass = &mut running[index];
+            if let Some(chunk) = pass.chunk
+                && let Some(seconds) = pass
+                    .parser
+                    .push_line(&line)
+                    .and_then(|block| block.out_time_seconds)
+            {
+                let percent = progress.update(chunk, seconds);
+                coalescer.push(
+                    ConversionEvent::progress(task.id.clone(), percent)
+                        .with_phase(ConversionPhase::Encode),
+                    Instant::now(),
+                    emit,
+                );
+            }
+        }
+    };
+
+    let result = loop {
+        match output_rx.recv_timeout(CHUNK_POLL_INTERVAL) {
+            Ok(output) => handle_output(output, &mut running),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(CHUNK_POLL_INTERVAL),
+        }
+        for output in output_rx.try_iter() {
+            handle_output(output, &mut running);
+        }
+
+        if controller.is_cancelled(&task.id) {
+            let _ = controller.finish_task(&task.id);
+            break Err(analysis_cancelled(&task.id));
+        }
+        match poll_passes(&mut running) {
+            Ok(true) => break Ok(()),
+            Ok(false) => {}
+            Err(error) => break Err(error),
+        }
+    };
+
+    if result.is_err() {
+        stop_passes(&mut running);
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+    coalescer.flush(emit);
+    result
+}
+
+/// Records the passes that exited, returning whether all of them are done.
+fn poll_passes(running: &mut [RunningPass]) -> Result<bool, ConversionError> {
+    for pass in running.iter_mut().filter(|pass| pass.status.is_none()) {
+        let Some(status) = pass.child.try_wait().map_err(ConversionError::Io)? else {
+            continue;
+        };
+        pass.status = Some(status);
+        if !status.success() {
+            let detail = if pass.last_line.is_empty() {
+                format!("ffmpeg exited with status {status}")
+            } else {
+                pass.last_line.clone()
+            };
+            return Err(ConversionError::Worker(format!(
+                "{} of the parallel encode failed: {detail}",
+                pass.name()
+            )));
+        }
+    }
+    Ok(running.iter().all(|pass| pass.status.is_some()))
+}
+
+/// Kills the passes still running, with any process they spawned, and waits
+/// for them to exit.
+fn stop_passes(running: &mut [RunningPass]) {
+    for pass in running.iter_mut().filter(|pass| pass.status.is_none()) {
+        let _ = kill_process(pass.child.id());
+        let _ = pass.child.kill();
+        pass.status = pass.child.wait().ok();
+    }
+}
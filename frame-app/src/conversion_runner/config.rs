@@ -1,26 +1,30 @@
 use frame_core::{
+    error::ConversionError,
+    image_batch::{ImageBatchOptions, apply_image_batch_options},
     media_rules,
     types::{
         AudioFiltersConfig as CoreAudioFiltersConfig, ConversionConfig as CoreConversionConfig,
         ConversionTask, CropConfig, DeinterlaceMode as CoreDeinterlaceMode,
         FilterStrength as CoreFilterStrength, FilterValue as CoreFilterValue,
         MetadataConfig as CoreMetadataConfig, MetadataMode as CoreMetadataMode, OverlayConfig,
+        OverwritePolicy, PostAction, SeekMode as CoreSeekMode, TrimSegment as CoreTrimSegment,
         VideoColorFiltersConfig as CoreVideoColorFiltersConfig,
         VideoFiltersConfig as CoreVideoFiltersConfig,
     },
 };
 
 use crate::{
-    file_queue::FileItem,
+    app_persistence::AppSettings,
+    file_queue::{FileItem, derive_output_name},
     settings::{
         AudioFiltersConfig as GpuiAudioFiltersConfig, ConversionConfig as GpuiConversionConfig,
         CropSettings, DEFAULT_AUDIO_BITRATE, DEFAULT_AUDIO_BITRATE_MODE, DEFAULT_AUDIO_CHANNELS,
         DEFAULT_AUDIO_QUALITY, DEFAULT_FPS, DEFAULT_GIF_COLORS, DEFAULT_GIF_DITHER,
         DEFAULT_PIXEL_FORMAT, DEFAULT_PRESET, DEFAULT_RESOLUTION, DEFAULT_SCALING_ALGORITHM,
-        DEFAULT_VIDEO_BITRATE, DEFAULT_VIDEO_BITRATE_MODE, DEFAULT_VIDEO_CODEC,
-        DeinterlaceMode as GpuiDeinterlaceMode, FilterStrength as GpuiFilterStrength,
-        FilterValue as GpuiFilterValue, MetadataConfig as GpuiMetadataConfig,
-        MetadataMode as GpuiMetadataMode, OverlaySettings,
+        DEFAULT_VIDEO_BITRATE, DEFAULT_VIDEO_BITRATE_MODE, DeinterlaceMode as GpuiDeinterlaceMode,
+        FilterStrength as GpuiFilterStrength, FilterValue as GpuiFilterValue,
+        MetadataConfig as GpuiMetadataConfig, MetadataMode as GpuiMetadataMode, OverlaySettings,
+        SeekMode as GpuiSeekMode, TrimSegment as GpuiTrimSegment,
         VideoColorFiltersConfig as GpuiVideoColorFiltersConfig,
         VideoFiltersConfig as GpuiVideoFiltersConfig,
     },
@@ -30,22 +34,105 @@ use crate::{
 pub fn conversion_task_from_file(file: &FileItem, output_directory: &str) -> ConversionTask {
     let output_name = crate::settings::sanitize_output_name(&file.output_name);
 
+    // A folder chosen with Save As is used as is, without mirroring the
+    // source folder under it.
     ConversionTask {
         id: file.id.clone(),
         file_path: file.path.clone(),
-        output_directory: output_directory.to_string(),
+        output_directory: file
+            .output_directory
+            .clone()
+            .unwrap_or_else(|| output_directory.to_string()),
         output_name: (!output_name.is_empty()).then_some(output_name),
+        create_output_directory: false,
+        relative_subpath: file
+            .relative_subpath
+            .clone()
+            .filter(|_| file.output_directory.is_none()),
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
         config: core_config_from_gpui(&file.config),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
     }
 }
 
+/// Task converting `file` with the output settings of the app: overwrite
+/// policy, file times, post action, scratch folder, and the naming template
+/// for files whose output name was not edited.
+#[must_use]
+pub fn conversion_task_with_settings(
+    file: &FileItem,
+    output_directory: &str,
+    settings: &AppSettings,
+) -> ConversionTask {
+    let mut task = conversion_task_from_file(file, output_directory);
+    task.config.overwrite_policy = settings.overwrite_policy;
+    task.preserve_file_times = settings.preserve_file_times;
+    task.post_action = settings.post_action;
+    task.scratch_directory = settings
+        .scratch_directory
+        .as_ref()
+        .map(|path| path.to_string_lossy().into_owned());
+    if file.output_directory.is_none()
+        && (file.output_name.is_empty() || file.output_name == derive_output_name(&file.name))
+    {
+        task.output_template
+            .clone_from(&settings.output_name_template);
+    }
+    task
+}
+
+/// Task joining `files` in order into one output named after, and
+/// configured like, the first file. `None` when fewer than two are given.
+#[must_use]
+pub fn concat_task_from_files(
+    files: &[FileItem],
+    output_directory: &str,
+) -> Option<ConversionTask> {
+    let [first, rest @ ..] = files else {
+        return None;
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    let mut task = conversion_task_from_file(first, output_directory);
+    task.concat_inputs = rest.iter().map(|file| file.path.clone()).collect();
+    Some(task)
+}
+
+/// One still-image task per file, each configured like its file and then
+/// resized and re-encoded with the batch `options`.
+///
+/// # Errors
+///
+/// Returns an error when `options` name no still image format or hold an
+/// out of range value.
+pub fn image_batch_tasks_from_files(
+    files: &[FileItem],
+    options: &ImageBatchOptions,
+    output_directory: &str,
+) -> Result<Vec<ConversionTask>, ConversionError> {
+    files
+        .iter()
+        .map(|file| {
+            let mut task = conversion_task_from_file(file, output_directory);
+            apply_image_batch_options(&mut task.config, options)?;
+            Ok(task)
+        })
+        .collect()
+}
+
 #[must_use]
 pub fn core_config_from_gpui(config: &GpuiConversionConfig) -> CoreConversionConfig {
     CoreConversionConfig {
         processing_mode: config.processing_mode.id().to_string(),
         container: config.container.clone(),
         video_codec: if config.video_codec.is_empty() {
-            default_video_codec_for_container(&config.container)
+            media_rules::default_video_codec_for_container(&config.container).to_string()
         } else {
             config.video_codec.clone()
         },
@@ -73,11 +160,23 @@ pub fn core_config_from_gpui(config: &GpuiConversionConfig) -> CoreConversionCon
         custom_height: config.custom_height.clone(),
         scaling_algorithm: non_empty_or(&config.scaling_algorithm, DEFAULT_SCALING_ALGORITHM),
         fps: non_empty_or(&config.fps, DEFAULT_FPS),
+        input_framerate: config
+            .input_framerate
+            .as_deref()
+            .map(str::trim)
+            .filter(|rate| !rate.is_empty())
+            .map(str::to_string),
         crf: config.crf.min(51),
         quality: config.quality.clamp(1, 100),
         preset: non_empty_or(&config.preset, DEFAULT_PRESET),
         start_time: config.start_time.clone(),
         end_time: config.end_time.clone(),
+        segments: config.segments.iter().map(core_segment_from_gpui).collect(),
+        remove_segments: config
+            .remove_segments
+            .iter()
+            .map(core_segment_from_gpui)
+            .collect(),
         metadata: core_metadata_from_gpui(&config.metadata),
         rotation: config.rotation.clone(),
         flip_horizontal: config.flip_horizontal,
@@ -88,6 +187,7 @@ pub fn core_config_from_gpui(config: &GpuiConversionConfig) -> CoreConversionCon
         nvenc_temporal_aq: config.nvenc_temporal_aq,
         videotoolbox_allow_sw: config.videotoolbox_allow_sw,
         hw_decode: config.hw_decode,
+        hw_device: config.hw_device.clone(),
         pixel_format: non_empty_or(&config.pixel_format, DEFAULT_PIXEL_FORMAT),
         image_jpeg_quality: config.image_jpeg_quality.clamp(1, 100),
         image_jpeg_huffman: config.image_jpeg_huffman.clone(),
@@ -98,9 +198,21 @@ pub fn core_config_from_gpui(config: &GpuiConversionConfig) -> CoreConversionCon
         image_png_compression: config.image_png_compression.min(9),
         image_png_prediction: config.image_png_prediction.clone(),
         image_tiff_compression: config.image_tiff_compression.clone(),
+        image_avif_crf: config.image_avif_crf.min(63),
+        image_target_max_kb: config.image_target_max_kb.filter(|kb| *kb > 0),
         gif_colors: config.gif_colors.clamp(2, DEFAULT_GIF_COLORS),
         gif_dither: non_empty_or(&config.gif_dither, DEFAULT_GIF_DITHER),
         gif_loop: config.gif_loop,
+        overwrite_policy: OverwritePolicy::default(),
+        split: None,
+        split_by_chapters: config.split_by_chapters,
+        image_sequence_output: config.image_sequence_output,
+        smart_cut: config.smart_cut,
+        seek_mode: core_seek_mode_from_gpui(config.seek_mode),
+        parallel_chunks: config.parallel_chunks.filter(|chunks| *chunks > 1),
+        verify_output: config.verify_output,
+        extra_input_args: config.extra_input_args.clone(),
+        extra_output_args: config.extra_output_args.clone(),
     }
 }
 
@@ -171,6 +283,14 @@ const fn core_deinterlace_mode_from_gpui(mode: GpuiDeinterlaceMode) -> CoreDeint
     }
 }
 
+const fn core_seek_mode_from_gpui(mode: GpuiSeekMode) -> CoreSeekMode {
+    match mode {
+        GpuiSeekMode::Fast => CoreSeekMode::Fast,
+        GpuiSeekMode::Accurate => CoreSeekMode::Accurate,
+        GpuiSeekMode::Hybrid => CoreSeekMode::Hybrid,
+    }
+}
+
 fn non_empty_or(value: &str, fallback: &str) -> String {
     if value.is_empty() {
         fallback.to_string()
@@ -195,20 +315,11 @@ fn core_metadata_from_gpui(metadata: &GpuiMetadataConfig) -> CoreMetadataConfig
     }
 }
 
-fn default_video_codec_for_container(container: &str) -> String {
-    if media_rules::is_gif_container(container) {
-        return "gif".to_string();
+fn core_segment_from_gpui(segment: &GpuiTrimSegment) -> CoreTrimSegment {
+    CoreTrimSegment {
+        start: segment.start.clone(),
+        end: segment.end.clone(),
     }
-
-    media_rules::video_codec_fallback_order()
-        .iter()
-        .find(|codec| media_rules::is_video_codec_allowed(container, codec))
-        .cloned()
-        .or_else(|| {
-            media_rules::video_codecs_for_container(container)
-                .and_then(|codecs| codecs.first().cloned())
-        })
-        .unwrap_or_else(|| DEFAULT_VIDEO_CODEC.to_string())
 }
 
 fn core_crop_from_gpui(crop: &CropSettings) -> CropConfig {
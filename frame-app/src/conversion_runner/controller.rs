@@ -1,12 +1,27 @@
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
 };
 
-use frame_core::{error::ConversionError, types::DEFAULT_MAX_CONCURRENCY};
-use sysinfo::{Pid, ProcessesToUpdate, System};
+use frame_core::{error::ConversionError, task_log::TaskLogStore, types::DEFAULT_MAX_CONCURRENCY};
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
 
-use super::process::{pause_process, resume_process, terminate_process};
+use super::process::{
+    kill_orphaned_process_group, kill_process, pause_process, resume_process, terminate_process,
+};
+
+/// How long a cancelled process tree gets to exit before it is killed.
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// How often a cancelled process is checked for having exited.
+const TERMINATE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long cancelled process trees may take to exit, which covers the
+/// kill after [`TERMINATE_GRACE_PERIOD`].
+pub const CANCEL_EXIT_TIMEOUT: Duration = Duration::from_secs(4);
 
 #[derive(Clone, Debug, Default)]
 pub struct ConversionProcessController {
@@ -18,6 +33,12 @@ struct ConversionProcessState {
     active_processes: HashMap<String, ActiveConversionProcess>,
     cancelled_tasks: HashSet<String>,
     max_concurrency: usize,
+    task_logs: TaskLogStore,
+    /// Directory each task's on-disk log is written to; `None` keeps logs in
+    /// memory only.
+    task_log_directory: Option<PathBuf>,
+    /// Output paths claimed by running tasks, keyed case-insensitively.
+    reserved_outputs: HashMap<String, String>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -32,6 +53,9 @@ impl Default for ConversionProcessState {
             active_processes: HashMap::new(),
             cancelled_tasks: HashSet::new(),
             max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            task_logs: TaskLogStore::default(),
+            task_log_directory: None,
+            reserved_outputs: HashMap::new(),
         }
     }
 }
@@ -111,7 +135,7 @@ impl ConversionProcessController {
         };
 
         if was_cancelled && pid > 0 {
-            terminate_process(pid)?;
+            stop_process_tree(process)?;
         }
 
         Ok(was_cancelled)
@@ -145,12 +169,49 @@ impl ConversionProcessController {
             && process.pid > 0
         {
             ensure_same_process(id, process)?;
-            terminate_process(process.pid)?;
+            stop_process_tree(process)?;
         }
 
         Ok(())
     }
 
+    /// Cancels every task with an active process, conversions and media
+    /// analyses alike. Every task is cancelled even when one of them fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error [`Self::cancel_task`] returned.
+    pub fn cancel_active_tasks(&self) -> Result<(), ConversionError> {
+        let ids = self
+            .lock_state()?
+            .active_processes
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        ids.iter()
+            .map(|id| self.cancel_task(id))
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Waits until no task has an active process, returning whether that
+    /// happened within `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn wait_for_active_processes(&self, timeout: Duration) -> Result<bool, ConversionError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.active_process_count()? == 0 {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            thread::sleep(TERMINATE_POLL_INTERVAL);
+        }
+    }
+
     /// Pauses the process associated with a task.
     ///
     /// # Errors
@@ -189,6 +250,88 @@ impl ConversionProcessController {
         Ok(state.cancelled_tasks.remove(id))
     }
 
+    /// Retains the captured log of a finished task.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn store_task_log(&self, id: &str, lines: Vec<String>) -> Result<(), ConversionError> {
+        self.lock_state()?.task_logs.insert(id, lines);
+        Ok(())
+    }
+
+    /// Returns the retained log of a finished task, if it is still stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn task_log(&self, id: &str) -> Result<Option<Vec<String>>, ConversionError> {
+        Ok(self.lock_state()?.task_logs.get(id).map(<[String]>::to_vec))
+    }
+
+    /// Returns up to `count` trailing lines of a finished task's log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn task_log_tail(&self, id: &str, count: usize) -> Result<Vec<String>, ConversionError> {
+        Ok(self.lock_state()?.task_logs.tail(id, count))
+    }
+
+    /// Sets the directory task logs are written to, or disables on-disk logs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn set_task_log_directory(
+        &self,
+        directory: Option<PathBuf>,
+    ) -> Result<(), ConversionError> {
+        self.lock_state()?.task_log_directory = directory;
+        Ok(())
+    }
+
+    /// Returns the directory task logs are written to, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn task_log_directory(&self) -> Result<Option<PathBuf>, ConversionError> {
+        Ok(self.lock_state()?.task_log_directory.clone())
+    }
+
+    /// Claims `path` as the output of task `id`, returning `false` when another
+    /// task already holds it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn reserve_output_path(&self, id: &str, path: &str) -> Result<bool, ConversionError> {
+        let mut state = self.lock_state()?;
+        let key = path.to_lowercase();
+        if state
+            .reserved_outputs
+            .get(&key)
+            .is_some_and(|owner| owner != id)
+        {
+            return Ok(false);
+        }
+        state.reserved_outputs.insert(key, id.to_string());
+        Ok(true)
+    }
+
+    /// Releases every output path reserved by task `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the controller state mutex is poisoned.
+    pub fn release_output_paths(&self, id: &str) -> Result<(), ConversionError> {
+        self.lock_state()?
+            .reserved_outputs
+            .retain(|_, owner| owner != id);
+        Ok(())
+    }
+
     fn lock_state(&self) -> Result<MutexGuard<'_, ConversionProcessState>, ConversionError> {
         self.state.lock().map_err(|error| {
             ConversionError::Worker(format!("process controller poisoned: {error}"))
@@ -214,6 +357,47 @@ fn process_start_time(pid: u32) -> Option<u64> {
     system.process(target).map(sysinfo::Process::start_time)
 }
 
+/// Whether `process` is still running: its pid belongs to the same process
+/// and it has not exited waiting to be reaped.
+fn process_is_running(process: ActiveConversionProcess) -> bool {
+    if process.pid == 0 {
+        return false;
+    }
+
+    let target = Pid::from_u32(process.pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[target]));
+    system.process(target).is_some_and(|current| {
+        (process.start_time == 0 || current.start_time() == process.start_time)
+            && current.status() != ProcessStatus::Zombie
+    })
+}
+
+/// Asks the process tree of `process` to exit, then kills it from a
+/// background thread when it is still running after
+/// [`TERMINATE_GRACE_PERIOD`].
+///
+/// Children can outlive the process that spawned them, so the rest of its
+/// process group is killed even when the process itself exited in time.
+fn stop_process_tree(process: ActiveConversionProcess) -> Result<(), ConversionError> {
+    terminate_process(process.pid)?;
+    thread::Builder::new()
+        .name("frame-process-reaper".to_string())
+        .spawn(move || {
+            let deadline = Instant::now() + TERMINATE_GRACE_PERIOD;
+            while process_is_running(process) && Instant::now() < deadline {
+                thread::sleep(TERMINATE_POLL_INTERVAL);
+            }
+            if process_is_running(process) {
+                let _ = kill_process(process.pid);
+            } else {
+                kill_orphaned_process_group(process.pid);
+            }
+        })
+        .map_err(ConversionError::Io)?;
+    Ok(())
+}
+
 fn ensure_same_process(id: &str, process: ActiveConversionProcess) -> Result<(), ConversionError> {
     if process.start_time == 0 {
         return Ok(());
@@ -244,6 +428,92 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn cancel_task_kills_children_that_ignore_termination() {
+        use std::{
+            io::{BufRead, BufReader},
+            process::{Command, Stdio},
+        };
+
+        use super::super::process::isolate_process_tree;
+
+        // The shell and its background `sleep` both ignore SIGTERM, so only
+        // the escalation to SIGKILL of the whole group stops them.
+        let mut child = isolate_process_tree(&mut Command::new("sh"))
+            .args(["-c", "trap '' TERM; sleep 30 & echo $!; wait"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("test shell should start");
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().expect("stdout should be piped"))
+            .read_line(&mut line)
+            .expect("test shell should print the sleep pid");
+        let grandchild_pid = line.trim().parse().expect("sleep pid should be a number");
+        let grandchild = ActiveConversionProcess {
+            pid: grandchild_pid,
+            start_time: process_start_time(grandchild_pid)
+                .expect("sleep start time should be readable"),
+        };
+        let controller = ConversionProcessController::default();
+        controller
+            .register_started_process("task-1", child.id())
+            .expect("test process should be registered");
+
+        controller
+            .cancel_task("task-1")
+            .expect("test process should be cancelled");
+        let status = child.wait().expect("test shell should be reaped");
+        let deadline = Instant::now() + TERMINATE_GRACE_PERIOD;
+        while process_is_running(grandchild) && Instant::now() < deadline {
+            thread::sleep(TERMINATE_POLL_INTERVAL);
+        }
+
+        assert!(!status.success());
+        assert!(!process_is_running(grandchild));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn isolated_children_are_killed_when_their_parent_dies() {
+        use std::{
+            os::unix::process::ExitStatusExt,
+            process::{Command, Stdio},
+        };
+
+        use super::super::process::isolate_process_tree;
+
+        // The kernel treats the thread that spawned a child as its parent,
+        // so that thread exiting stands in for Frame dying.
+        let mut child = thread::spawn(|| {
+            isolate_process_tree(&mut Command::new("sleep"))
+                .arg("30")
+                .stdin(Stdio::null())
+                .spawn()
+                .expect("test process should start")
+        })
+        .join()
+        .expect("spawning thread should finish");
+        let deadline = Instant::now() + TERMINATE_GRACE_PERIOD;
+        let status = loop {
+            if let Some(status) = child.try_wait().expect("test process should be polled") {
+                break Some(status);
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            thread::sleep(TERMINATE_POLL_INTERVAL);
+        };
+
+        assert_eq!(
+            status.and_then(|status| status.signal()),
+            Some(libc::SIGKILL)
+        );
+    }
+
     #[test]
     fn active_process_count_tracks_registered_and_finished_processes() {
         let controller = ConversionProcessController::default();
@@ -274,6 +544,62 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn cancel_active_tasks_stops_every_process_before_the_wait_ends() {
+        use std::process::{Command, Stdio};
+
+        use super::super::process::isolate_process_tree;
+
+        let controller = ConversionProcessController::default();
+        let workers = ["task-1", "task-2"].map(|id| {
+            let mut child = isolate_process_tree(&mut Command::new("sleep"))
+                .arg("30")
+                .stdin(Stdio::null())
+                .spawn()
+                .expect("test process should start");
+            controller
+                .register_started_process(id, child.id())
+                .expect("test process should be registered");
+            let controller = controller.clone();
+            thread::spawn(move || {
+                let status = child.wait().expect("test process should be reaped");
+                controller
+                    .finish_task(id)
+                    .expect("test process should be finished");
+                status
+            })
+        });
+
+        controller
+            .cancel_active_tasks()
+            .expect("test processes should be cancelled");
+
+        assert!(
+            controller
+                .wait_for_active_processes(CANCEL_EXIT_TIMEOUT)
+                .expect("controller state should be readable")
+        );
+        for worker in workers {
+            let status = worker.join().expect("worker thread should finish");
+            assert!(!status.success());
+        }
+    }
+
+    #[test]
+    fn wait_for_active_processes_gives_up_after_the_timeout() {
+        let controller = ConversionProcessController::default();
+        controller
+            .register_started_process("task-1", 0)
+            .expect("test process should be registered");
+
+        assert!(
+            !controller
+                .wait_for_active_processes(Duration::ZERO)
+                .expect("controller state should be readable")
+        );
+    }
+
     #[test]
     fn active_process_count_reports_poisoned_controller_state() {
         let controller = ConversionProcessController::default();
@@ -308,4 +634,73 @@ mod tests {
             "unexpected error: {error}"
         );
     }
+
+    #[test]
+    fn reserve_output_path_rejects_paths_held_by_other_tasks() {
+        let controller = ConversionProcessController::default();
+
+        assert!(
+            controller
+                .reserve_output_path("task-1", "/out/Clip.mp4")
+                .expect("reservation should succeed")
+        );
+        assert!(
+            controller
+                .reserve_output_path("task-1", "/out/Clip.mp4")
+                .expect("owner may reserve again")
+        );
+        assert!(
+            !controller
+                .reserve_output_path("task-2", "/out/clip.mp4")
+                .expect("reservation should be checked")
+        );
+
+        controller
+            .release_output_paths("task-1")
+            .expect("reservations should be released");
+
+        assert!(
+            controller
+                .reserve_output_path("task-2", "/out/clip.mp4")
+                .expect("released path should be reservable")
+        );
+    }
+
+    #[test]
+    fn task_log_returns_stored_lines_and_tail() {
+        let controller = ConversionProcessController::default();
+        controller
+            .store_task_log(
+                "task-1",
+                vec![
+                    "first".to_string(),
+                    "second".to_string(),
+                    "third".to_string(),
+                ],
+            )
+            .expect("task log should be stored");
+
+        assert_eq!(
+            controller
+                .task_log("task-1")
+                .expect("controller state should be readable"),
+            Some(vec![
+                "first".to_string(),
+                "second".to_string(),
+                "third".to_string()
+            ])
+        );
+        assert_eq!(
+            controller
+                .task_log_tail("task-1", 2)
+                .expect("controller state should be readable"),
+            ["second", "third"]
+        );
+        assert_eq!(
+            controller
+                .task_log("missing")
+                .expect("controller state should be readable"),
+            None
+        );
+    }
 }
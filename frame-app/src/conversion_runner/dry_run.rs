@@ -0,0 +1,105 @@
+use frame_core::{
+    args::{
+        build_ffmpeg_args, extra_args_warning, validate_task_capabilities, validate_task_encoder,
+        validate_task_input,
+    },
+    chunked::parallel_chunk_count,
+    command_line::CommandPreview,
+    error::ConversionError,
+    hw_devices::validate_hw_device,
+    image_sequence::detect_image_sequence,
+    probe::fresh_preprobed_metadata,
+    segments::{resolve_remove_segments, validate_trim_bounds},
+    smart_cut::smart_cut_range,
+    timecode::resolve_frame_times,
+    types::ConversionTask,
+};
+
+use super::{
+    output_paths::{apply_output_template, task_output_path},
+    runner::{complete_media_probe, ffmpeg_path_arg, probe_media_file},
+};
+use crate::{
+    capabilities::{cached_encoder_self_test, cached_ffmpeg_capabilities, cached_hw_devices},
+    runtime_binaries::ffmpeg_executable,
+};
+
+/// Returns the `FFmpeg` command that converting `task` would run, without
+/// running it.
+///
+/// The task goes through the same validation and probing as a real run, but
+/// nothing else is spawned and neither the output nor its directory is
+/// created. The command writes straight to the final output path and leaves
+/// out the progress reporting arguments Frame adds for itself; passes that
+/// are planned from the source keyframes at run time are listed in the
+/// notes instead.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, the task fails
+/// validation, or its arguments cannot be built. Joined sources and image
+/// sequence inputs are rejected, since their commands depend on files
+/// written during the run.
+pub fn preview_command(mut task: ConversionTask) -> Result<CommandPreview, ConversionError> {
+    if !task.concat_inputs.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Command previews are not available when joining sources".to_string(),
+        ));
+    }
+    if detect_image_sequence(&task.file_path)?.is_some() {
+        return Err(ConversionError::InvalidInput(
+            "Command previews are not available for image sequence sources".to_string(),
+        ));
+    }
+    validate_task_input(&task.file_path, &task.config)?;
+    if let Some(capabilities) = cached_ffmpeg_capabilities() {
+        validate_task_capabilities(&task.config, &capabilities)?;
+    }
+    if let Some(self_test) = cached_encoder_self_test(&task.config.video_codec) {
+        validate_task_encoder(&task.config, &self_test)?;
+    }
+    if let Some(devices) = cached_hw_devices() {
+        validate_hw_device(&task.config, &devices)?;
+    }
+
+    let probe = match fresh_preprobed_metadata(task.preprobed.as_ref(), &task.file_path) {
+        Some(preprobed) => complete_media_probe(&task.file_path, preprobed.clone()),
+        None => probe_media_file(&task.file_path)?,
+    };
+    if resolve_frame_times(&mut task.config, probe.frame_rate)? {
+        validate_task_input(&task.file_path, &task.config)?;
+    }
+    if let Some(duration) = probe.duration_seconds() {
+        validate_trim_bounds(&task.config, duration)?;
+    }
+    resolve_remove_segments(&mut task.config, probe.duration_seconds())?;
+    apply_output_template(&mut task, &probe)?;
+
+    let args = build_ffmpeg_args(
+        &ffmpeg_path_arg(&task.file_path),
+        &ffmpeg_path_arg(&task_output_path(&task)),
+        &task.config,
+        &probe,
+    )?;
+    let mut preview = CommandPreview::new(&ffmpeg_executable(), args);
+    if let Some(warning) = extra_args_warning(&task.config) {
+        preview.notes.push(format!(
+            "{warning}; Frame does not check them against the arguments it generates"
+        ));
+    }
+    if smart_cut_range(&task.config).is_some() {
+        preview.notes.push(
+            "Smart cut plans its re-encoded edges from the source keyframes when the task runs; \
+             this is the keyframe-aligned trim it falls back to"
+                .to_string(),
+        );
+    }
+    if parallel_chunk_count(&task.config).is_some() {
+        preview.notes.push(
+            "Parallel chunks are split at the source keyframes when the task runs; this is the \
+             single-pass encode the chunks replace"
+                .to_string(),
+        );
+    }
+    Ok(preview)
+}
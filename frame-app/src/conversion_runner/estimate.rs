@@ -0,0 +1,167 @@
+use std::{fs, path::PathBuf, process};
+
+use frame_core::{
+    args::build_ffmpeg_args,
+    error::ConversionError,
+    estimate::{
+        BitrateCalculation, BitrateTarget, OutputSizeEstimate, SizeEstimateMethod,
+        effective_duration_seconds, estimate_bitrate_size, estimate_stream_copy_size,
+        extrapolate_sample_sizes, size_estimate_method, size_estimate_sample_ranges,
+    },
+    segments::{resolve_remove_segments, trim_ranges},
+    timecode::resolve_frame_times,
+    types::{ConversionConfig as CoreConversionConfig, OverwritePolicy, ProbeMetadata},
+};
+
+use super::{
+    ConversionProcessController,
+    analysis::{MEDIA_ANALYSIS_TIMEOUT, run_ffmpeg_analysis},
+    runner::probe_media_file,
+};
+
+/// Estimates the output size of converting `file_path` with `config`.
+///
+/// Stream copy and fixed-bitrate outputs are computed from probe data.
+/// Quality-based outputs encode short samples under `estimate_id`, which
+/// identifies them for cancellation; the samples are deleted afterwards.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, has no known duration,
+/// reports no usable bitrates, targets an image output, or when a sample
+/// encode fails, is cancelled, or exceeds [`MEDIA_ANALYSIS_TIMEOUT`].
+pub fn estimate_output_size(
+    estimate_id: &str,
+    file_path: &str,
+    config: &CoreConversionConfig,
+    controller: &ConversionProcessController,
+) -> Result<OutputSizeEstimate, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let mut resolved = config.clone();
+    resolve_frame_times(&mut resolved, probe.frame_rate)?;
+    resolve_remove_segments(&mut resolved, probe.duration_seconds())?;
+    let config = &resolved;
+    let method = size_estimate_method(config, &probe)?;
+    let duration = effective_duration_seconds(config, &probe).ok_or_else(|| {
+        ConversionError::InvalidInput(
+            "Cannot estimate output size without a known duration".to_string(),
+        )
+    })?;
+
+    match method {
+        SizeEstimateMethod::StreamCopy => estimate_stream_copy_size(config, &probe, duration)
+            .ok_or_else(|| {
+                ConversionError::InvalidInput("Source does not report its bitrate".to_string())
+            }),
+        SizeEstimateMethod::Bitrate => {
+            estimate_bitrate_size(config, &probe, duration).ok_or_else(|| {
+                ConversionError::InvalidInput("Configured bitrate is not a number".to_string())
+            })
+        }
+        SizeEstimateMethod::SampleEncode => {
+            estimate_from_samples(estimate_id, file_path, config, &probe, duration, controller)
+        }
+    }
+}
+
+/// Works out the video bitrate that makes converting `file_path` with
+/// `config` reach a target size, or the size a target video bitrate
+/// produces.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed or the calculation is
+/// rejected by [`calculate_bitrate`](frame_core::estimate::calculate_bitrate).
+pub fn calculate_bitrate(
+    file_path: &str,
+    config: &CoreConversionConfig,
+    target: BitrateTarget,
+) -> Result<BitrateCalculation, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let mut resolved = config.clone();
+    resolve_frame_times(&mut resolved, probe.frame_rate)?;
+    resolve_remove_segments(&mut resolved, probe.duration_seconds())?;
+    frame_core::estimate::calculate_bitrate(&resolved, &probe, target)
+}
+
+fn estimate_from_samples(
+    estimate_id: &str,
+    file_path: &str,
+    config: &CoreConversionConfig,
+    probe: &ProbeMetadata,
+    duration: f64,
+    controller: &ConversionProcessController,
+) -> Result<OutputSizeEstimate, ConversionError> {
+    let scratch = SampleScratchDir::create(estimate_id)?;
+    let start = trim_ranges(config).first().map_or(0.0, |range| range.start);
+
+    let mut samples = Vec::new();
+    for (index, (sample_start, sample_seconds)) in size_estimate_sample_ranges(start, duration)
+        .into_iter()
+        .enumerate()
+    {
+        let sample_path = scratch
+            .path
+            .join(format!("sample-{index}.{}", config.container));
+        let mut sample_config = config.clone();
+        sample_config.start_time = Some(format!("{sample_start:.3}"));
+        sample_config.end_time = Some(format!("{:.3}", sample_start + sample_seconds));
+        sample_config.segments.clear();
+        sample_config.overwrite_policy = OverwritePolicy::Overwrite;
+
+        let args = build_ffmpeg_args(
+            file_path,
+            &sample_path.to_string_lossy(),
+            &sample_config,
+            probe,
+        )?;
+        run_ffmpeg_analysis(
+            estimate_id,
+            &args,
+            controller,
+            MEDIA_ANALYSIS_TIMEOUT,
+            &mut |_| {},
+        )?;
+        let bytes = fs::metadata(&sample_path)
+            .map_err(ConversionError::Io)?
+            .len();
+        samples.push((bytes, sample_seconds));
+    }
+
+    extrapolate_sample_sizes(&samples, duration).ok_or_else(|| {
+        ConversionError::Worker("Sample encodes produced no measurable output".to_string())
+    })
+}
+
+/// Temporary directory for sample encodes, removed on drop so cancelled or
+/// failed estimates do not leave files behind.
+struct SampleScratchDir {
+    path: PathBuf,
+}
+
+impl SampleScratchDir {
+    fn create(estimate_id: &str) -> Result<Self, ConversionError> {
+        let sanitized_id: String = estimate_id
+            .chars()
+            .map(|character| {
+                if character.is_ascii_alphanumeric() {
+                    character
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!(
+            "frame-size-estimate-{}-{sanitized_id}",
+            process::id()
+        ));
+        fs::create_dir_all(&path).map_err(ConversionError::Io)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for SampleScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
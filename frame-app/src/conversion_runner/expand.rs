@@ -0,0 +1,24 @@
+use frame_core::{
+    error::ConversionError,
+    expand::{ExpandMode, ExpandedTask, expand_to_tasks},
+    types::ConversionConfig as CoreConversionConfig,
+};
+
+use super::runner::probe_media_file;
+
+/// Probes `file_path` and suggests one queue entry per chapter or segment,
+/// each starting from `config`. Entries are queued like any other task once
+/// they have been reviewed.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, has no chapters to
+/// expand, or a segment is invalid or lies beyond the source duration.
+pub fn expand_file_to_tasks(
+    file_path: &str,
+    config: &CoreConversionConfig,
+    mode: &ExpandMode,
+) -> Result<Vec<ExpandedTask>, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    expand_to_tasks(file_path, config, &probe, mode)
+}
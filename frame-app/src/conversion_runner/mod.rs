@@ -1,17 +1,32 @@
 //! Native GPUI conversion runner backed by the shared Frame ffmpeg argument builder.
 
+mod analysis;
+mod chunked;
 mod config;
 mod controller;
+mod dry_run;
+mod estimate;
+mod expand;
 mod output_paths;
+mod post_action;
 mod process;
 mod runner;
+mod sample_preview;
 #[cfg(test)]
 mod tests;
+mod thumbnail;
+mod verify;
 
+pub use analysis::*;
 pub use config::*;
 pub use controller::*;
+pub use dry_run::*;
+pub use estimate::*;
+pub use expand::*;
 pub use output_paths::*;
 pub use runner::*;
+pub use sample_preview::*;
+pub use thumbnail::*;
 
 #[cfg(test)]
 use crate::file_queue::FileItem;
@@ -19,8 +34,28 @@ use crate::file_queue::FileItem;
 use crate::settings::ConversionConfig as GpuiConversionConfig;
 #[cfg(test)]
 use frame_core::{
+    dependencies::DEPENDENCY_OUTPUT_PLACEHOLDER,
+    error::ConversionError,
     events::ConversionEvent,
-    types::{ConversionTask, DEFAULT_MAX_CONCURRENCY},
+    progress::FfmpegProgress,
+    task_log_file::{TaskLogStatus, list_task_logs},
+    types::{
+        ConversionPhase, ConversionTask, DEFAULT_MAX_CONCURRENCY, OverwritePolicy, PostAction,
+        ProbeMetadata,
+    },
+    verify::OutputVerification,
 };
 #[cfg(test)]
-use runner::{ffmpeg_progress_from_line, next_batch_launch_count};
+use output_paths::{
+    apply_output_template, copy_source_file_times, partial_output_path, task_output_path,
+};
+#[cfg(test)]
+use post_action::apply_post_action;
+#[cfg(test)]
+use runner::{
+    PartialOutput, ProgressState, ffmpeg_frame_progress_from_line, ffmpeg_progress_from_line,
+    next_batch_launch_count, read_output_segments, resolve_output_path_at_start,
+    structured_progress_percent, task_log_exit,
+};
+#[cfg(test)]
+use verify::reject_unverified_output;
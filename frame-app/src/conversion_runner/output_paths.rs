@@ -1,46 +1,156 @@
-use std::{collections::HashSet, path::Path};
+use std::{
+    collections::HashSet,
+    fs::{self, FileTimes},
+    io,
+    path::Path,
+    time::SystemTime,
+};
 
-use frame_core::{args::build_output_path, types::ConversionTask};
+use frame_core::{
+    args::{build_output_path, mirrored_output_directory},
+    error::ConversionError,
+    output_template::{OutputTemplateContext, expand_output_template},
+    types::{ConversionTask, OverwritePolicy, ProbeMetadata},
+};
+
+use crate::file_queue::file_name_from_path;
 
 /// Assigns deterministic suffixes to output names that would collide with an
-/// earlier task or an existing filesystem entry.
+/// earlier task, or with an existing filesystem entry when the task's
+/// overwrite policy is [`OverwritePolicy::Rename`].
 pub fn disambiguate_output_paths(tasks: &mut [ConversionTask]) {
     let mut claimed_paths = HashSet::with_capacity(tasks.len());
 
     for task in tasks {
-        let desired_path = task_output_path(task);
-        if output_path_is_available(&desired_path, &claimed_paths) {
-            claimed_paths.insert(output_path_key(&desired_path));
+        // Named after the dependency's output, which is not known yet.
+        if task.depends_on.is_some() {
             continue;
         }
+        let checks_filesystem = task.config.overwrite_policy == OverwritePolicy::Rename;
+        claim_output_path(task, |path| {
+            output_path_is_available(path, &claimed_paths, checks_filesystem)
+                && claimed_paths.insert(output_path_key(path))
+        });
+    }
+}
 
-        let output_stem = output_stem_from_path(&desired_path);
-        for suffix in 2_u64.. {
-            let output_name = format!("{output_stem}_{suffix}");
-            let candidate_path = build_output_path(
-                &task.output_directory,
-                &task.config.container,
-                Some(&output_name),
-            );
-            if output_path_is_available(&candidate_path, &claimed_paths) {
-                claimed_paths.insert(output_path_key(&candidate_path));
-                task.output_name = Some(output_name);
-                break;
-            }
+/// Returns the task's output path, or its first `_2`, `_3`, … variant that
+/// `try_claim` accepts, updating the task's output name to match.
+pub(super) fn claim_output_path(
+    task: &mut ConversionTask,
+    mut try_claim: impl FnMut(&str) -> bool,
+) -> String {
+    let desired_path = task_output_path(task);
+    if try_claim(&desired_path) {
+        return desired_path;
+    }
+
+    let output_stem = output_stem_from_path(&desired_path).to_string();
+    for suffix in 2_u64.. {
+        let output_name = format!("{output_stem}_{suffix}");
+        let candidate_path = build_output_path(
+            &task_output_directory(task),
+            &task.config.container,
+            Some(&output_name),
+        );
+        if try_claim(&candidate_path) {
+            task.output_name = Some(output_name);
+            return candidate_path;
         }
     }
+    unreachable!("output suffixes are unbounded")
+}
+
+/// Replaces the task's output name with its filename template, expanded
+/// against the probed source.
+pub(super) fn apply_output_template(
+    task: &mut ConversionTask,
+    probe: &ProbeMetadata,
+) -> Result<(), ConversionError> {
+    let Some(template) = task.output_template.as_deref() else {
+        return Ok(());
+    };
+    let input_stem = Path::new(file_name_from_path(&task.file_path))
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let output_name = expand_output_template(
+        template,
+        &OutputTemplateContext {
+            input_stem: &input_stem,
+            config: &task.config,
+            probe: Some(probe),
+            timestamp: SystemTime::now(),
+        },
+    )?;
+    task.output_name = Some(output_name);
+    Ok(())
+}
+
+/// Temporary path `FFmpeg` writes to before the finished output is renamed
+/// into place; it shares the output's directory so the rename is atomic.
+pub(super) fn partial_output_path(output_path: &str, task_id: &str) -> String {
+    format!("{output_path}.{}.part", sanitized_task_id(task_id))
+}
+
+/// Task id reduced to characters that are safe in file names.
+pub(super) fn sanitized_task_id(task_id: &str) -> String {
+    task_id
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_') {
+                character
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Copies the access and modification times of `source` to `output`, and on
+/// Unix its permission bits.
+pub(super) fn copy_source_file_times(source: &str, output: &str) -> io::Result<()> {
+    let metadata = fs::metadata(source)?;
+    let mut times = FileTimes::new().set_modified(metadata.modified()?);
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .open(output)?
+        .set_times(times)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = metadata.permissions().mode() & 0o777;
+        fs::set_permissions(output, fs::Permissions::from_mode(mode))?;
+    }
+
+    Ok(())
 }
 
-fn task_output_path(task: &ConversionTask) -> String {
+pub(super) fn task_output_path(task: &ConversionTask) -> String {
     build_output_path(
-        &task.output_directory,
+        &task_output_directory(task),
         &task.config.container,
         task.output_name.as_deref(),
     )
 }
 
-fn output_path_is_available(path: &str, claimed_paths: &HashSet<String>) -> bool {
-    !claimed_paths.contains(&output_path_key(path)) && !Path::new(path).exists()
+/// Directory the task writes into, including its mirrored source folder.
+pub(super) fn task_output_directory(task: &ConversionTask) -> String {
+    mirrored_output_directory(&task.output_directory, task.relative_subpath.as_deref())
+}
+
+fn output_path_is_available(
+    path: &str,
+    claimed_paths: &HashSet<String>,
+    checks_filesystem: bool,
+) -> bool {
+    !claimed_paths.contains(&output_path_key(path))
+        && !(checks_filesystem && Path::new(path).exists())
 }
 
 fn output_stem_from_path(path: &str) -> &str {
@@ -0,0 +1,108 @@
+#[cfg(any(unix, windows))]
+use std::process::Command;
+use std::{fs, io, path::Path};
+
+use frame_core::types::{PostAction, PostActionOutcome};
+
+/// Applies `action` to `source` once `output` has been committed.
+///
+/// The source is only touched when the output exists, is non-empty, and is
+/// not the source itself; otherwise the outcome carries the reason it was
+/// skipped. Trash never falls back to a permanent delete.
+pub(super) fn apply_post_action(
+    source: &str,
+    output: &str,
+    action: PostAction,
+) -> PostActionOutcome {
+    let result = match action {
+        PostAction::Keep => Ok(()),
+        PostAction::Trash => {
+            verify_output(source, output).and_then(|()| move_to_trash(Path::new(source)))
+        }
+        PostAction::Delete => verify_output(source, output).and_then(|()| fs::remove_file(source)),
+    };
+    let error = result.err().map(|error| error.to_string());
+    PostActionOutcome { action, error }
+}
+
+fn verify_output(source: &str, output: &str) -> io::Result<()> {
+    let metadata = fs::metadata(output)?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "output is missing or empty; source left in place",
+        ));
+    }
+    if fs::canonicalize(source)? == fs::canonicalize(output)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "output is the source file; source left in place",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(any(unix, windows))]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let output = trash_command(&path).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(io::Error::other(format!(
+            "could not move source to trash ({}): {}",
+            output.status,
+            stderr.trim()
+        )))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn move_to_trash(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "moving files to trash is not supported on this platform",
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn trash_command(path: &Path) -> Command {
+    let mut command = Command::new("osascript");
+    command
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "tell application \"Finder\" to delete (POSIX file (item 1 of argv))",
+            "-e",
+            "end run",
+        ])
+        .arg(path);
+    command
+}
+
+#[cfg(windows)]
+fn trash_command(path: &Path) -> Command {
+    // The path travels through the environment so it is never parsed as
+    // PowerShell source.
+    let mut command = Command::new("powershell");
+    command
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName Microsoft.VisualBasic; \
+             [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteFile(\
+             $env:FRAME_TRASH_PATH, 'OnlyErrorDialogs', 'SendToRecycleBin')",
+        ])
+        .env("FRAME_TRASH_PATH", path);
+    command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_command(path: &Path) -> Command {
+    let mut command = Command::new("gio");
+    command.arg("trash").arg("--").arg(path);
+    command
+}
@@ -1,3 +1,11 @@
+//! Signalling the `FFmpeg` processes of running tasks.
+//!
+//! Workers are started in their own process tree so pausing and cancelling
+//! reach any helper process they spawn, not only the process the runner
+//! holds a handle to.
+
+use std::process::Command;
+
 use frame_core::error::ConversionError;
 
 #[cfg(windows)]
@@ -12,9 +20,48 @@ use windows::{
     core::s,
 };
 
+/// Starts the process spawned by `command` as the root of its own process
+/// group, so every signal sent to the task reaches its children too.
+///
+/// Outside Frame's group, Ctrl-C and hangups from the terminal no longer
+/// reach the process, so on Linux the kernel kills it once the thread that
+/// started it exits, which includes Frame dying.
+#[cfg(unix)]
+pub(super) fn isolate_process_tree(command: &mut Command) -> &mut Command {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: `getpid` has no preconditions.
+        let parent = unsafe { libc::getpid() };
+        // SAFETY: only async-signal-safe calls run between fork and exec,
+        // and nothing is allocated there.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                // Frame may have died before the signal was armed.
+                if libc::getppid() != parent {
+                    return Err(std::io::Error::from_raw_os_error(libc::ESRCH));
+                }
+                Ok(())
+            });
+        }
+    }
+    command
+}
+
+/// `taskkill /T` walks the children of the process, so no setup is needed.
+#[cfg(not(unix))]
+pub(super) fn isolate_process_tree(command: &mut Command) -> &mut Command {
+    command
+}
+
 #[cfg(unix)]
 pub(super) fn pause_process(pid: u32) -> Result<(), ConversionError> {
-    signal_process(pid, libc::SIGSTOP, "SIGSTOP")
+    signal_process_tree(pid, libc::SIGSTOP, "SIGSTOP")
 }
 
 #[cfg(windows)]
@@ -31,7 +78,7 @@ pub(super) fn pause_process(_pid: u32) -> Result<(), ConversionError> {
 
 #[cfg(unix)]
 pub(super) fn resume_process(pid: u32) -> Result<(), ConversionError> {
-    signal_process(pid, libc::SIGCONT, "SIGCONT")
+    signal_process_tree(pid, libc::SIGCONT, "SIGCONT")
 }
 
 #[cfg(windows)]
@@ -46,21 +93,63 @@ pub(super) fn resume_process(_pid: u32) -> Result<(), ConversionError> {
     ))
 }
 
+/// Asks the process tree of `pid` to exit; [`kill_process`] follows when it
+/// does not.
 #[cfg(unix)]
 pub(super) fn terminate_process(pid: u32) -> Result<(), ConversionError> {
-    let unix_pid = pid_to_unix_pid(pid)?;
-    unsafe {
-        let _ = libc::kill(unix_pid, libc::SIGCONT);
-        if libc::kill(unix_pid, libc::SIGKILL) != 0 {
-            return Err(ConversionError::Shell("Failed to send SIGKILL".to_string()));
+    let _ = signal_process_tree(pid, libc::SIGCONT, "SIGCONT");
+    signal_process_tree(pid, libc::SIGTERM, "SIGTERM")
+}
+
+/// Windows has no polite stop for a console process, so terminating kills
+/// the tree straight away.
+#[cfg(not(unix))]
+pub(super) fn terminate_process(pid: u32) -> Result<(), ConversionError> {
+    kill_process(pid)
+}
+
+/// Kills the process tree of `pid`, including children that outlived it.
+#[cfg(unix)]
+pub(super) fn kill_process(pid: u32) -> Result<(), ConversionError> {
+    signal_process_tree(pid, libc::SIGKILL, "SIGKILL")
+}
+
+/// Kills what is left of the process group once led by `pid`, which has
+/// already exited. The process itself is never signalled, since its pid may
+/// have been reused.
+#[cfg(unix)]
+pub(super) fn kill_orphaned_process_group(pid: u32) {
+    if let Ok(unix_pid) = pid_to_unix_pid(pid) {
+        unsafe {
+            let _ = libc::kill(-unix_pid, libc::SIGKILL);
         }
     }
-    Ok(())
 }
 
+/// `taskkill /T` already took the children down with the process.
+#[cfg(not(unix))]
+pub(super) const fn kill_orphaned_process_group(_pid: u32) {}
+
+/// Kills the process tree of `pid` with `taskkill /T`, then the process
+/// itself in case `taskkill` is unavailable.
 #[cfg(windows)]
-pub(super) fn terminate_process(pid: u32) -> Result<(), ConversionError> {
+pub(super) fn kill_process(pid: u32) -> Result<(), ConversionError> {
+    use std::{os::windows::process::CommandExt, process::Stdio};
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
     let _ = windows_suspend_resume(pid, false);
+    let tree_killed = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .is_ok_and(|status| status.success());
+    if tree_killed {
+        return Ok(());
+    }
 
     unsafe {
         let process_handle = OpenProcess(PROCESS_TERMINATE, false, pid).map_err(|error| {
@@ -75,17 +164,19 @@ pub(super) fn terminate_process(pid: u32) -> Result<(), ConversionError> {
 }
 
 #[cfg(not(any(unix, windows)))]
-pub(super) fn terminate_process(_pid: u32) -> Result<(), ConversionError> {
+pub(super) fn kill_process(_pid: u32) -> Result<(), ConversionError> {
     Err(ConversionError::Shell(
         "Cancelling running conversions is not supported on this platform yet".to_string(),
     ))
 }
 
+/// Sends `signal` to the process group led by `pid`, or to `pid` alone when
+/// it was not started with [`isolate_process_tree`].
 #[cfg(unix)]
-fn signal_process(pid: u32, signal: libc::c_int, label: &str) -> Result<(), ConversionError> {
+fn signal_process_tree(pid: u32, signal: libc::c_int, label: &str) -> Result<(), ConversionError> {
     let unix_pid = pid_to_unix_pid(pid)?;
     unsafe {
-        if libc::kill(unix_pid, signal) != 0 {
+        if libc::kill(-unix_pid, signal) != 0 && libc::kill(unix_pid, signal) != 0 {
             return Err(ConversionError::Shell(format!("Failed to send {label}")));
         }
     }
@@ -1,24 +1,90 @@
 use std::{
-    collections::VecDeque,
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fs,
     io::Read,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
     sync::mpsc::{self, RecvTimeoutError},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use frame_core::{
-    args::{build_ffmpeg_args, build_output_path, validate_task_input},
+    args::{
+        build_concat_reencode_args, build_ffmpeg_args, build_stream_copy_concat_args,
+        extra_args_warning, frame_progress_applies, hdr_encoding_warning,
+        validate_output_directory, validate_stream_copy_compatibility, validate_task_capabilities,
+        validate_task_encoder, validate_task_input,
+    },
+    concat::{ConcatMethod, ConcatPlan, concat_list, concat_reencode_warning, plan_concat},
+    dependencies::{apply_dependency_output, validate_task_dependencies},
     error::ConversionError,
-    events::ConversionEvent,
-    probe::{ffprobe_json_args, parse_ffprobe_stdout},
-    types::{ConversionConfig as CoreConversionConfig, ConversionTask, ProbeMetadata},
-    utils::{DURATION_REGEX, TIME_REGEX, parse_time},
+    events::{ConversionEvent, ConversionEventCoalescer},
+    failure::classify_conversion_failure,
+    hw_devices::validate_hw_device,
+    image_batch::{QualitySearch, image_quality, set_image_quality},
+    image_sequence::{
+        ImageSequence, SequenceNumbering, build_sequence_ffmpeg_args, check_sequence_frame_sizes,
+        detect_image_sequence, ffprobe_sequence_frame_size_args, sequence_concat_list,
+        sequence_frame_rate, sequence_output_directory, sequence_output_pattern,
+        sequence_probe_metadata, validate_sequence_output_directory, validate_sequence_task,
+        written_sequence_totals,
+    },
+    network_input::is_network_input,
+    probe::{
+        ffprobe_count_packets_args, ffprobe_json_args, frame_count_scan_allowed,
+        fresh_preprobed_metadata, parse_ffprobe_stdout, parse_packet_count,
+        set_animated_frame_count,
+    },
+    progress::{FFMPEG_PROGRESS_ARGS, FfmpegProgress, FfmpegProgressParser},
+    scratch::scratch_directory,
+    segments::{
+        TrimRange, has_trim_times, kept_duration_seconds, resolve_remove_segments, trim_ranges,
+        validate_trim_bounds,
+    },
+    smart_cut::{
+        SMART_CUT_KEYFRAME_WINDOW_SECONDS, keyframe_probe_args, parse_keyframe_times,
+        plan_smart_cut, smart_cut_encoder, smart_cut_piece_args, smart_cut_range,
+    },
+    split::{
+        chapter_part_paths, split_keyframe_warning, split_part_path, split_part_pattern,
+        splits_output,
+    },
+    task_log::{TASK_LOG_FAILURE_TAIL_LINES, TaskLogBuffer},
+    task_log_file::{TaskLogFile, TaskLogStatus, task_log_path},
+    timecode::resolve_frame_times,
+    types::{
+        ConversionConfig as CoreConversionConfig, ConversionPhase, ConversionTask, OutputSummary,
+        OverwritePolicy, PostAction, ProbeMetadata,
+    },
+    utils::{DURATION_REGEX, FRAME_REGEX, TIME_REGEX, extended_length_path, parse_time},
+    verify::{OutputVerification, expected_output_duration},
+    warnings::ConversionWarningTracker,
 };
 
-use crate::runtime_binaries::{ffmpeg_executable, ffprobe_executable};
+use crate::{
+    capabilities::{
+        cached_encoder_self_test, cached_ffmpeg_capabilities, cached_ffmpeg_info, cached_hw_devices,
+    },
+    numeric::u64_to_f64,
+    runtime_binaries::{ffmpeg_executable, ffprobe_executable},
+    source_metadata::{DEFAULT_PROBE_TIMEOUT, explain_probe_error, run_ffprobe},
+};
 
-use super::{controller::ConversionProcessController, output_paths::disambiguate_output_paths};
+use super::{
+    analysis::{MEDIA_ANALYSIS_TIMEOUT, is_analysis_cancelled, run_ffmpeg_analysis},
+    chunked::prepare_parallel_chunks,
+    controller::ConversionProcessController,
+    output_paths::{
+        apply_output_template, claim_output_path, copy_source_file_times,
+        disambiguate_output_paths, partial_output_path, sanitized_task_id, task_output_directory,
+        task_output_path,
+    },
+    post_action::apply_post_action,
+    process::isolate_process_tree,
+    verify::{reject_unverified_output, verify_task_output},
+};
 
 /// Runs a single conversion task with a default process controller.
 ///
@@ -33,39 +99,72 @@ pub fn run_conversion_task(
     run_conversion_task_with_control(task, &ConversionProcessController::default(), &mut emit)
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum BatchTaskOutcome {
+    Completed(String),
+    Failed,
+    Cancelled,
+}
+
 /// Runs conversion tasks with shared process control and concurrency limits.
 ///
+/// Tasks with a `depends_on` parent are held until the parent completes and
+/// then read the parent's output; they are cancelled or failed along with it.
+///
 /// # Errors
 ///
-/// Returns an error when the controller state cannot be read or the worker
-/// channel disconnects before all tasks complete.
+/// Returns an error when the task dependencies are invalid, the controller
+/// state cannot be read, or the worker channel disconnects before all tasks
+/// complete.
 pub fn run_conversion_batch_with_control(
     mut tasks: Vec<ConversionTask>,
     controller: &ConversionProcessController,
     mut emit: impl FnMut(ConversionEvent),
 ) -> Result<(), ConversionError> {
+    validate_task_dependencies(&tasks)?;
     disambiguate_output_paths(&mut tasks);
+    let mut outcomes = HashMap::new();
+    // Encoders known to be unusable fail at once rather than after their
+    // turn in the queue.
+    tasks.retain(|task| match validate_cached_encoder_self_test(task) {
+        Ok(()) => true,
+        Err(error) => {
+            outcomes.insert(task.id.clone(), BatchTaskOutcome::Failed);
+            emit(ConversionEvent::failed(
+                task.id.clone(),
+                &error,
+                Vec::new(),
+                None,
+            ));
+            false
+        }
+    });
     let mut pending = VecDeque::from(tasks);
     let mut running_count = 0_usize;
     let (event_tx, event_rx) = mpsc::channel::<ConversionEvent>();
     let (done_tx, done_rx) = mpsc::channel::<(String, Result<(), ConversionError>)>();
 
     while !pending.is_empty() || running_count > 0 {
+        settle_blocked_dependents(&mut pending, &mut outcomes, &mut emit);
+        let ready_count = pending
+            .iter()
+            .filter(|task| dependency_is_ready(task, &outcomes))
+            .count();
         let launch_count = next_batch_launch_count(
-            pending.len(),
+            ready_count,
             running_count,
             controller.current_max_concurrency()?,
         );
 
         for _ in 0..launch_count {
-            let Some(task) = pending.pop_front() else {
+            let Some(task) = take_ready_task(&mut pending, &outcomes) else {
                 break;
             };
             running_count += 1;
             spawn_batch_worker(task, controller.clone(), event_tx.clone(), done_tx.clone());
         }
 
-        drain_batch_events(&event_rx, &mut emit);
+        drain_batch_events(&event_rx, &mut outcomes, &mut emit);
         if running_count == 0 {
             continue;
         }
@@ -73,9 +172,22 @@ pub fn run_conversion_batch_with_control(
         match done_rx.recv_timeout(Duration::from_millis(50)) {
             Ok((task_id, result)) => {
                 running_count = running_count.saturating_sub(1);
-                drain_batch_events(&event_rx, &mut emit);
+                drain_batch_events(&event_rx, &mut outcomes, &mut emit);
                 if let Err(error) = result {
-                    emit(ConversionEvent::error(task_id, error.to_string()));
+                    outcomes.insert(task_id.clone(), BatchTaskOutcome::Failed);
+                    let log_tail = controller
+                        .task_log_tail(&task_id, TASK_LOG_FAILURE_TAIL_LINES)
+                        .unwrap_or_default();
+                    let log_path = controller
+                        .task_log_directory()
+                        .ok()
+                        .flatten()
+                        .map(|directory| task_log_path(&directory, &task_id))
+                        .filter(|path| path.is_file())
+                        .map(|path| path.to_string_lossy().into_owned());
+                    emit(ConversionEvent::failed(task_id, &error, log_tail, log_path));
+                } else {
+                    outcomes.entry(task_id).or_insert(BatchTaskOutcome::Failed);
                 }
             }
             Err(RecvTimeoutError::Timeout) => {}
@@ -87,7 +199,7 @@ pub fn run_conversion_batch_with_control(
         }
     }
 
-    drain_batch_events(&event_rx, &mut emit);
+    drain_batch_events(&event_rx, &mut outcomes, &mut emit);
     Ok(())
 }
 
@@ -107,75 +219,923 @@ pub fn run_conversion_task_with_control(
 }
 
 fn run_prepared_conversion_task_with_control(
-    task: ConversionTask,
+    mut task: ConversionTask,
     controller: &ConversionProcessController,
     emit: &mut impl FnMut(ConversionEvent),
 ) -> Result<(), ConversionError> {
+    let started_at = Instant::now();
     if controller.take_cancelled(&task.id)? {
         emit_cancelled_task(&task.id, emit);
         return Ok(());
     }
 
-    validate_task_input(&task.file_path, &task.config)?;
-    let probe = probe_media_file(&task.file_path)?;
+    let sequence = detect_image_sequence(&task.file_path)?;
+    let input_path = sequence
+        .as_ref()
+        .map_or(task.file_path.as_str(), ImageSequence::first_file)
+        .to_string();
+    validate_task_input(&input_path, &task.config)?;
+    if sequence.is_some() {
+        validate_sequence_task(&task)?;
+    }
+    if let Some(capabilities) = cached_ffmpeg_capabilities() {
+        validate_task_capabilities(&task.config, &capabilities)?;
+    }
+    validate_cached_encoder_self_test(&task)?;
+    if let Some(devices) = cached_hw_devices() {
+        validate_hw_device(&task.config, &devices)?;
+    }
+    validate_output_directory(&task.output_directory, task.create_output_directory)?;
+    if task.relative_subpath.is_some() {
+        validate_output_directory(&task_output_directory(&task), true)?;
+    }
+    let sequence = sequence
+        .map(|sequence| SequenceSource::prepare(&task, sequence))
+        .transpose()?;
+    let probe = match &sequence {
+        Some(source) => source.probe(&task.config)?,
+        None => match fresh_preprobed_metadata(task.preprobed.as_ref(), &task.file_path) {
+            Some(preprobed) => {
+                emit(ConversionEvent::log(
+                    task.id.clone(),
+                    "[INFO] Using the source metadata probed when the task was queued".to_string(),
+                ));
+                complete_media_probe(&task.file_path, preprobed.clone())
+            }
+            None => probe_media_file(&task.file_path)?,
+        },
+    };
+    if resolve_frame_times(&mut task.config, probe.frame_rate)? {
+        validate_task_input(&input_path, &task.config)?;
+    }
+    match probe.duration_seconds() {
+        Some(duration) => validate_trim_bounds(&task.config, duration)?,
+        None if has_trim_times(&task.config) => emit(ConversionEvent::log(
+            task.id.clone(),
+            "[WARN] Source duration is unknown; trim times were not checked against it".to_string(),
+        )),
+        None => {}
+    }
+    resolve_remove_segments(&mut task.config, probe.duration_seconds())?;
+    let concat = plan_task_concat(&task, &probe)?;
 
-    let output_path = build_output_path(
-        &task.output_directory,
-        &task.config.container,
-        task.output_name.as_deref(),
+    apply_output_template(&mut task, &probe)?;
+    let _reservation = OutputReservation {
+        controller,
+        task_id: task.id.clone(),
+    };
+    let output_path = resolve_output_path_at_start(&mut task, controller, &probe)?;
+    let partial_output = if task.config.image_sequence_output {
+        let directory = sequence_output_directory(&output_path, &task.config.container);
+        validate_sequence_output_directory(&directory)?;
+        PartialOutput::frame_folder(&directory, &task.id, &task.config.container)?
+    } else {
+        PartialOutput::new(&output_path, &task.id, splits_output(&task.config))
+            .with_part_paths(chapter_part_paths(&output_path, &task.config, &probe))
+    };
+    let (mut args, _concat_scratch) = match (&concat, &sequence) {
+        (Some(plan), _) => build_task_concat_args(&task, plan, &probe, &partial_output.path)?,
+        (None, Some(source)) => (
+            build_sequence_ffmpeg_args(
+                &source.sequence,
+                &ffmpeg_path_arg(&source.input),
+                &ffmpeg_path_arg(&partial_output.path),
+                &task.config,
+                &probe,
+            )?,
+            None,
+        ),
+        (None, None) => (
+            build_ffmpeg_args(
+                &ffmpeg_path_arg(&task.file_path),
+                &ffmpeg_path_arg(&partial_output.path),
+                &task.config,
+                &probe,
+            )?,
+            None,
+        ),
+    };
+    let smart_cut = match prepare_smart_cut(&task, &probe, controller, emit) {
+        Err(error) if is_analysis_cancelled(&error, &task.id) => {
+            emit_cancelled_task(&task.id, emit);
+            return Ok(());
+        }
+        result => result?,
+    };
+    if let Some(smart_cut) = &smart_cut {
+        args = build_stream_copy_concat_args(
+            &ffmpeg_path_arg(&smart_cut.list_path),
+            &ffmpeg_path_arg(&task.file_path),
+            &ffmpeg_path_arg(&partial_output.path),
+            &task.config,
+        );
+    }
+    let chunked = match prepare_parallel_chunks(&task, &probe, controller, emit) {
+        Err(error) if is_analysis_cancelled(&error, &task.id) => {
+            emit_cancelled_task(&task.id, emit);
+            return Ok(());
+        }
+        result => result?,
+    };
+    if let Some(chunked) = &chunked {
+        args = chunked.join_args(&task, &probe, &ffmpeg_path_arg(&partial_output.path))?;
+    }
+    args.splice(0..0, FFMPEG_PROGRESS_ARGS.map(str::to_string));
+    let progress = ProgressState::new(&task, expected_frame_count(&task, &probe), &probe)
+        .with_joined_duration(concat.as_ref().and_then(|plan| plan.duration));
+    let expected_duration = concat.as_ref().map_or_else(
+        || expected_output_duration(&task.config, probe.duration_seconds()),
+        |plan| plan.duration,
     );
-    let args = build_ffmpeg_args(&task.file_path, &output_path, &task.config, &probe)?;
     let executable = ffmpeg_executable();
+    let mut task_log = TaskLog::open(controller, &task.id, &executable, &args, emit)?;
 
-    emit(ConversionEvent::log(
-        task.id.clone(),
-        format!("[INFO] Running {executable} {}", args.join(" ")),
-    ));
+    for warning in hdr_encoding_warning(&task.config, &probe)
+        .into_iter()
+        .chain(split_keyframe_warning(&task.config))
+        .chain(extra_args_warning(&task.config))
+        .chain(concat.as_ref().and_then(concat_reencode_warning))
+    {
+        let line = format!("[WARN] {warning}");
+        task_log.push(line.clone());
+        emit(ConversionEvent::log(task.id.clone(), line));
+    }
+
+    let command_line = format!("[INFO] Running {executable} {}", args.join(" "));
+    task_log.push(command_line.clone());
+    emit(ConversionEvent::log(task.id.clone(), command_line).with_phase(ConversionPhase::Encode));
 
-    let mut child = Command::new(&executable)
+    let mut child = isolate_process_tree(&mut Command::new(&executable))
         .args(&args)
         .stdin(Stdio::null())
-        .stdout(Stdio::null())
+        .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
         .map_err(ConversionError::Io)?;
 
     let started_cancelled = controller.register_started_process(&task.id, child.id())?;
     if started_cancelled {
-        let _ = child.wait();
+        let status = child.wait().map_err(ConversionError::Io);
+        let (log_status, log_detail) = task_log_exit(&status, true);
+        task_log.finish(log_status, &log_detail);
         let _ = controller.finish_task(&task.id);
         emit_cancelled_task(&task.id, emit);
         return Ok(());
     }
 
-    emit(ConversionEvent::started(task.id.clone()));
-    emit(ConversionEvent::progress(task.id.clone(), 0.0));
+    emit(ConversionEvent::started(
+        task.id.clone(),
+        partial_output.output_path().to_string(),
+    ));
+    emit(ConversionEvent::progress(task.id.clone(), 0.0).with_phase(ConversionPhase::Encode));
 
-    let mut stderr = child
+    let stderr = child
         .stderr
         .take()
         .ok_or_else(|| ConversionError::Worker("ffmpeg stderr was not captured".to_string()))?;
-    let stream_result = stream_ffmpeg_stderr(&mut stderr, &task, emit);
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ConversionError::Worker("ffmpeg stdout was not captured".to_string()))?;
+    let stream_result = stream_ffmpeg_output(stderr, stdout, &task, progress, &mut task_log, emit);
 
     let status = child.wait().map_err(ConversionError::Io);
     let was_cancelled = controller.finish_task(&task.id)?;
+    let (log_status, log_detail) = task_log_exit(&status, was_cancelled);
+    let log_lines = task_log.finish(log_status, &log_detail);
+    let failure_tail = ffmpeg_failure_tail(&log_lines);
+    controller.store_task_log(&task.id, log_lines)?;
     if was_cancelled {
         emit_cancelled_task(&task.id, emit);
         return Ok(());
     }
 
-    stream_result?;
+    let warning_count = stream_result?;
     let status = status?;
     if status.success() {
-        emit(ConversionEvent::completed(task.id, output_path));
-        Ok(())
+        if concat.is_none()
+            && sequence.is_none()
+            && !fit_image_to_budget(&mut task, &partial_output, &probe, controller, emit)?
+        {
+            emit_cancelled_task(&task.id, emit);
+            return Ok(());
+        }
+        let verification =
+            match verify_task_output(&task, &partial_output, expected_duration, controller, emit) {
+                Err(error) if is_analysis_cancelled(&error, &task.id) => {
+                    emit_cancelled_task(&task.id, emit);
+                    return Ok(());
+                }
+                result => result?,
+            };
+        if let Some(verification) = &verification
+            && !verification.passed()
+        {
+            return Err(reject_unverified_output(partial_output, verification));
+        }
+        complete_task(
+            &task,
+            partial_output,
+            started_at,
+            warning_count,
+            verification,
+            emit,
+        )
     } else {
-        Err(ConversionError::Worker(format!(
-            "ffmpeg exited with status {status}"
+        Err(ConversionError::Failed(classify_conversion_failure(
+            &failure_tail,
+            &status.to_string(),
+            killed_by_signal(status),
         )))
     }
 }
 
+/// Commits the finished output, then reports its summary and applies the
+/// task's post action to the source. Split outputs are summarized as a whole
+/// and probed through their first part; frame folders report the frames
+/// they hold.
+fn complete_task(
+    task: &ConversionTask,
+    partial_output: PartialOutput,
+    started_at: Instant,
+    warning_count: usize,
+    verification: Option<OutputVerification>,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<(), ConversionError> {
+    let outputs = partial_output.commit()?;
+    let output_path = outputs.first().cloned().ok_or_else(|| {
+        ConversionError::Worker("ffmpeg finished without writing an output".to_string())
+    })?;
+    if task.preserve_file_times {
+        let result = outputs
+            .iter()
+            .try_for_each(|output| copy_source_file_times(&task.file_path, output));
+        let line = match result {
+            Ok(()) => "[INFO] Copied source file times to the output".to_string(),
+            Err(error) => format!("[WARN] Could not copy source file times: {error}"),
+        };
+        emit(ConversionEvent::log(task.id.clone(), line));
+    }
+    let mut summary = OutputSummary {
+        output_size_bytes: outputs
+            .iter()
+            .map(|output| fs::metadata(output).ok().map(|metadata| metadata.len()))
+            .sum(),
+        input_size_bytes: source_size_bytes(&task.file_path),
+        image_quality: image_quality(&task.config),
+        verification,
+        warning_count,
+        ..OutputSummary::default()
+    };
+    if task.config.image_sequence_output {
+        let totals = written_sequence_totals(Path::new(&output_path)).ok();
+        summary.frame_count = totals.map(|(frames, _)| frames);
+        summary.output_size_bytes = totals.map(|(_, bytes)| bytes);
+    } else {
+        match probe_media_file(&output_path) {
+            Ok(output_probe) => summary.apply_probe(&output_probe),
+            Err(error) => emit(ConversionEvent::log(
+                task.id.clone(),
+                format!("[WARN] Could not probe the output for its summary: {error}"),
+            )),
+        }
+    }
+    let post_action = (task.post_action != PostAction::Keep).then(|| {
+        let outcome = apply_post_action(&task.file_path, &output_path, task.post_action);
+        let line = outcome.error.as_ref().map_or_else(
+            || format!("[INFO] Applied {:?} to the source file", outcome.action),
+            |error| {
+                format!(
+                    "[WARN] Skipped {:?} of the source file: {error}",
+                    outcome.action
+                )
+            },
+        );
+        emit(ConversionEvent::log(task.id.clone(), line));
+        outcome
+    });
+    summary.elapsed_seconds = Some(started_at.elapsed().as_secs_f64());
+    emit(
+        ConversionEvent::completed_with_summary(task.id.clone(), output_path, post_action, summary)
+            .with_outputs(outputs),
+    );
+    Ok(())
+}
+
+/// Re-encodes a JPEG or lossy WebP output at lower qualities until it fits
+/// the task's size budget or reaches the quality floor, and leaves the task
+/// configured with the quality the output was written at. Returns `false`
+/// when the task is cancelled meanwhile.
+fn fit_image_to_budget(
+    task: &mut ConversionTask,
+    partial_output: &PartialOutput,
+    probe: &ProbeMetadata,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<bool, ConversionError> {
+    let Some(budget_kb) = task.config.image_target_max_kb else {
+        return Ok(true);
+    };
+    let Some(quality) = image_quality(&task.config) else {
+        emit(ConversionEvent::log(
+            task.id.clone(),
+            "[WARN] The size budget only applies to JPEG and lossy WebP outputs".to_string(),
+        ));
+        return Ok(true);
+    };
+    let budget = budget_kb.saturating_mul(1024);
+    let mut size = fs::metadata(&partial_output.path)
+        .map_err(ConversionError::Io)?
+        .len();
+    if size <= budget {
+        return Ok(true);
+    }
+
+    let mut search = QualitySearch::new(quality);
+    let mut encoded = quality;
+    let mut next = search.record(quality, false);
+    while let Some(target) = next {
+        let Some(target_size) = encode_image_at(task, target, partial_output, probe, controller)?
+        else {
+            return Ok(false);
+        };
+        emit(ConversionEvent::log(
+            task.id.clone(),
+            format!(
+                "[INFO] Quality {target} wrote {} KB",
+                target_size.div_ceil(1024)
+            ),
+        ));
+        (encoded, size) = (target, target_size);
+        next = search.record(target, size <= budget);
+    }
+    let settled = search.settled_quality();
+    if encoded != settled {
+        let Some(settled_size) = encode_image_at(task, settled, partial_output, probe, controller)?
+        else {
+            return Ok(false);
+        };
+        size = settled_size;
+    }
+    if !search.found_fit() {
+        emit(ConversionEvent::log(
+            task.id.clone(),
+            format!(
+                "[WARN] The output is {} KB at the quality floor of {settled}, over the {budget_kb} KB budget",
+                size.div_ceil(1024)
+            ),
+        ));
+    }
+    Ok(true)
+}
+
+/// Encodes the task's still again at `quality` over the partial output and
+/// returns its size, or `None` when the task is cancelled.
+fn encode_image_at(
+    task: &mut ConversionTask,
+    quality: u32,
+    partial_output: &PartialOutput,
+    probe: &ProbeMetadata,
+    controller: &ConversionProcessController,
+) -> Result<Option<u64>, ConversionError> {
+    set_image_quality(&mut task.config, quality);
+    let _ = fs::remove_file(&partial_output.path);
+    let args = build_ffmpeg_args(
+        &ffmpeg_path_arg(&task.file_path),
+        &ffmpeg_path_arg(&partial_output.path),
+        &task.config,
+        probe,
+    )?;
+    match run_ffmpeg_analysis(
+        &task.id,
+        &args,
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |_| {},
+    ) {
+        Err(error) if is_analysis_cancelled(&error, &task.id) => return Ok(None),
+        result => {
+            result?;
+        }
+    }
+    let size = fs::metadata(&partial_output.path)
+        .map_err(ConversionError::Io)?
+        .len();
+    Ok(Some(size))
+}
+
+/// `FFmpeg`'s last output lines for failure classification. Frame's own
+/// tagged lines, such as the command line, are left out so paths in them
+/// cannot be mistaken for `FFmpeg` errors.
+fn ffmpeg_failure_tail(log_lines: &[String]) -> Vec<String> {
+    let mut tail = log_lines
+        .iter()
+        .filter(|line| !line.starts_with("[INFO] ") && !line.starts_with("[WARN] "))
+        .cloned()
+        .collect::<Vec<_>>();
+    tail.drain(..tail.len().saturating_sub(TASK_LOG_FAILURE_TAIL_LINES));
+    tail
+}
+
+#[cfg(unix)]
+fn killed_by_signal(status: ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+
+    status.signal().is_some()
+}
+
+#[cfg(not(unix))]
+const fn killed_by_signal(_status: ExitStatus) -> bool {
+    false
+}
+
+/// Rejects the task when its video encoder failed a self-test earlier in
+/// the session. Encoders that were never tested are let through.
+fn validate_cached_encoder_self_test(task: &ConversionTask) -> Result<(), ConversionError> {
+    cached_encoder_self_test(&task.config.video_codec).map_or(Ok(()), |self_test| {
+        validate_task_encoder(&task.config, &self_test)
+    })
+}
+
+/// Applies the task's overwrite policy against the filesystem as it is when
+/// the worker starts, since earlier tasks may have created the output since
+/// the batch was queued.
+///
+/// The chosen path is reserved in `controller` so concurrently running tasks
+/// never write to the same file; callers release it once the task finishes.
+/// A split output counts as existing when its first part does, and a
+/// chapter split when any of its chapter files does.
+pub(super) fn resolve_output_path_at_start(
+    task: &mut ConversionTask,
+    controller: &ConversionProcessController,
+    probe: &ProbeMetadata,
+) -> Result<String, ConversionError> {
+    let task_id = task.id.clone();
+    let config = task.config.clone();
+    let output_exists = |path: &str| {
+        if config.split_by_chapters {
+            chapter_part_paths(path, &config, probe)
+                .iter()
+                .any(|part_path| Path::new(part_path).exists())
+        } else if config.split.is_some() {
+            Path::new(&split_part_path(path, 1)).exists()
+        } else {
+            Path::new(path).exists()
+        }
+    };
+    if task.config.overwrite_policy == OverwritePolicy::Rename {
+        let mut reservation_error = None;
+        let output_path = claim_output_path(task, |path| {
+            if output_exists(path) {
+                return false;
+            }
+            controller
+                .reserve_output_path(&task_id, path)
+                .unwrap_or_else(|error| {
+                    reservation_error = Some(error);
+                    true
+                })
+        });
+        return reservation_error.map_or(Ok(output_path), Err);
+    }
+
+    let output_path = task_output_path(task);
+    let exists = output_exists(&output_path);
+    if (exists && task.config.overwrite_policy == OverwritePolicy::Fail)
+        || !controller.reserve_output_path(&task_id, &output_path)?
+    {
+        return Err(ConversionError::OutputExists(output_path));
+    }
+    Ok(output_path)
+}
+
+/// Writes the pieces of a smart cut when the task asks for one. Sources the
+/// encoder cannot match fall back to a keyframe copy trim with a warning.
+fn prepare_smart_cut(
+    task: &ConversionTask,
+    probe: &ProbeMetadata,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<Option<ConcatScratch>, ConversionError> {
+    let Some(range) = smart_cut_range(&task.config) else {
+        return Ok(None);
+    };
+    let plan = smart_cut_encoder(probe).and_then(|encoder| {
+        let keyframes = probe_keyframes(&task.file_path, range)?;
+        let pieces = plan_smart_cut(range, &keyframes).ok_or_else(|| {
+            format!(
+                "no keyframe found within {SMART_CUT_KEYFRAME_WINDOW_SECONDS:.0} seconds of the start"
+            )
+        })?;
+        Ok((encoder, pieces))
+    });
+    let (encoder, pieces) = match plan {
+        Ok(plan) => plan,
+        Err(reason) => {
+            emit(ConversionEvent::log(
+                task.id.clone(),
+                format!("[WARN] Smart cut unavailable, trimming at keyframes instead: {reason}"),
+            ));
+            return Ok(None);
+        }
+    };
+
+    let smart_cut = ConcatScratch::create(task, "smart-cut")?;
+    let mut piece_paths = Vec::with_capacity(pieces.len());
+    for (index, piece) in pieces.iter().enumerate() {
+        let piece_path = smart_cut
+            .directory
+            .join(format!("piece{index}.{}", encoder.extension()))
+            .to_string_lossy()
+            .into_owned();
+        let action = if piece.reencode {
+            "Re-encoding"
+        } else {
+            "Copying"
+        };
+        let end = piece
+            .end
+            .map_or_else(|| "the end".to_string(), |end| format!("{end:.3}s"));
+        emit(
+            ConversionEvent::log(
+                task.id.clone(),
+                format!("[INFO] Smart cut: {action} {:.3}s to {end}", piece.start),
+            )
+            .with_phase(ConversionPhase::Encode),
+        );
+        // Pieces are bounded by the trim rather than by a fixed time, so
+        // only cancellation stops them early.
+        run_ffmpeg_analysis(
+            &task.id,
+            &smart_cut_piece_args(
+                &ffmpeg_path_arg(&task.file_path),
+                &ffmpeg_path_arg(&piece_path),
+                &task.config,
+                piece,
+                &encoder,
+            ),
+            controller,
+            Duration::MAX,
+            &mut |_| {},
+        )?;
+        piece_paths.push(piece_path);
+    }
+    fs::write(&smart_cut.list_path, concat_list(&piece_paths)).map_err(ConversionError::Io)?;
+    Ok(Some(smart_cut))
+}
+
+/// Keyframes of `file_path` near the start and end of `range`.
+fn probe_keyframes(file_path: &str, range: TrimRange) -> Result<Vec<f64>, String> {
+    let mut spans = vec![(range.start, range.start + SMART_CUT_KEYFRAME_WINDOW_SECONDS)];
+    if let Some(end) = range.end {
+        spans.push((
+            (end - SMART_CUT_KEYFRAME_WINDOW_SECONDS).max(range.start),
+            end,
+        ));
+    }
+    let mut keyframes = Vec::new();
+    for (from, to) in spans {
+        let stdout = run_ffprobe(
+            &ffprobe_executable(),
+            &keyframe_probe_args(&ffmpeg_path_arg(file_path), from, to),
+            MEDIA_ANALYSIS_TIMEOUT,
+        )
+        .map_err(|error| format!("keyframes could not be read: {error}"))?;
+        keyframes.extend(parse_keyframe_times(&stdout));
+    }
+    keyframes.sort_by(f64::total_cmp);
+    keyframes.dedup();
+    Ok(keyframes)
+}
+
+/// Probes the further sources of a join and plans how they are combined.
+fn plan_task_concat(
+    task: &ConversionTask,
+    probe: &ProbeMetadata,
+) -> Result<Option<ConcatPlan>, ConversionError> {
+    if task.concat_inputs.is_empty() {
+        return Ok(None);
+    }
+    let mut probes = vec![probe.clone()];
+    for input in &task.concat_inputs {
+        validate_task_input(input, &task.config)?;
+        probes.push(probe_media_file(input)?);
+    }
+    plan_concat(&task.config, &probes).map(Some)
+}
+
+/// Arguments joining the sources of `task` into `output`. A stream-copy
+/// join also returns the scratch list the concat demuxer reads.
+fn build_task_concat_args(
+    task: &ConversionTask,
+    plan: &ConcatPlan,
+    probe: &ProbeMetadata,
+    output: &str,
+) -> Result<(Vec<String>, Option<ConcatScratch>), ConversionError> {
+    let inputs: Vec<String> = std::iter::once(&task.file_path)
+        .chain(&task.concat_inputs)
+        .map(|path| ffmpeg_path_arg(path).into_owned())
+        .collect();
+    let output = ffmpeg_path_arg(output);
+    if plan.method == ConcatMethod::Reencode {
+        let args = build_concat_reencode_args(&inputs, &output, &task.config, plan);
+        return Ok((args, None));
+    }
+
+    validate_stream_copy_compatibility(&task.config, probe)?;
+    let scratch = ConcatScratch::create(task, "concat")?;
+    fs::write(&scratch.list_path, concat_list(&inputs)).map_err(ConversionError::Io)?;
+    let args = build_stream_copy_concat_args(
+        &ffmpeg_path_arg(&scratch.list_path),
+        &inputs[0],
+        &output,
+        &task.config,
+    );
+    Ok((args, Some(scratch)))
+}
+
+/// An image sequence source and the pattern or frame list `FFmpeg` reads it
+/// from.
+struct SequenceSource {
+    sequence: ImageSequence,
+    input: String,
+    _list: Option<ConcatScratch>,
+}
+
+impl SequenceSource {
+    fn prepare(task: &ConversionTask, sequence: ImageSequence) -> Result<Self, ConversionError> {
+        let (input, list) = match &sequence.numbering {
+            SequenceNumbering::Contiguous { pattern, .. } => (pattern.clone(), None),
+            SequenceNumbering::Listed => {
+                let scratch = ConcatScratch::create(task, "sequence")?;
+                let list = sequence_concat_list(&sequence, sequence_frame_rate(&task.config))
+                    .unwrap_or_default();
+                fs::write(&scratch.list_path, list).map_err(ConversionError::Io)?;
+                (scratch.list_path.clone(), Some(scratch))
+            }
+        };
+        Ok(Self {
+            sequence,
+            input,
+            _list: list,
+        })
+    }
+
+    /// Probes the first frame for stream details and reads every frame once
+    /// to check they share its size.
+    fn probe(&self, config: &CoreConversionConfig) -> Result<ProbeMetadata, ConversionError> {
+        let first_frame = probe_media_file(self.sequence.first_file())?;
+        let stdout = run_ffprobe(
+            &ffprobe_executable(),
+            &ffprobe_sequence_frame_size_args(
+                &self.sequence,
+                &ffmpeg_path_arg(&self.input),
+                config,
+            ),
+            MEDIA_ANALYSIS_TIMEOUT,
+        )?;
+        let size = check_sequence_frame_sizes(&self.sequence, &stdout)?;
+        Ok(sequence_probe_metadata(
+            &self.sequence,
+            first_frame,
+            sequence_frame_rate(config),
+            size,
+        ))
+    }
+}
+
+/// Bytes read from the source: every frame of an image sequence, otherwise
+/// the source file.
+fn source_size_bytes(file_path: &str) -> Option<u64> {
+    match detect_image_sequence(file_path) {
+        Ok(Some(sequence)) => sequence
+            .files
+            .iter()
+            .map(|file| fs::metadata(file).ok().map(|metadata| metadata.len()))
+            .sum(),
+        _ => fs::metadata(file_path).ok().map(|metadata| metadata.len()),
+    }
+}
+
+/// Scratch directory holding a concat demuxer list and any files it joins;
+/// removed however the task ends.
+pub(super) struct ConcatScratch {
+    pub(super) directory: PathBuf,
+    pub(super) list_path: String,
+}
+
+impl ConcatScratch {
+    pub(super) fn create(task: &ConversionTask, purpose: &str) -> Result<Self, ConversionError> {
+        let directory = scratch_directory(task.scratch_directory.as_deref().map(Path::new))
+            .join(format!("frame-{purpose}-{}", sanitized_task_id(&task.id)));
+        fs::create_dir_all(&directory).map_err(ConversionError::Io)?;
+        Ok(Self {
+            list_path: directory
+                .join(format!("{purpose}.ffconcat"))
+                .to_string_lossy()
+                .into_owned(),
+            directory,
+        })
+    }
+}
+
+impl Drop for ConcatScratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+/// Path form handed to `FFmpeg`; on Windows, paths beyond `MAX_PATH` use the
+/// extended-length prefix so deep folder trees still open.
+pub(super) fn ffmpeg_path_arg(path: &str) -> Cow<'_, str> {
+    if cfg!(windows) {
+        extended_length_path(path)
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// `FFmpeg` target that is renamed to the final output on success and deleted
+/// otherwise, so failed or cancelled encodes never leave a partial file at
+/// the advertised path.
+///
+/// A split output writes numbered partial parts. When the encode stops early
+/// the parts it finished are still committed and only the last, unfinished
+/// part is deleted.
+///
+/// An image sequence output writes its frames into a partial folder, which is
+/// renamed into place whole or deleted with every frame in it.
+pub(super) struct PartialOutput {
+    output_path: String,
+    task_id: String,
+    pub(super) path: String,
+    split: bool,
+    frame_folder: bool,
+    /// Final names of the parts, in order, when they are not numbered.
+    part_paths: Vec<String>,
+    committed: bool,
+}
+
+impl PartialOutput {
+    pub(super) fn new(output_path: &str, task_id: &str, split: bool) -> Self {
+        let target = if split {
+            split_part_pattern(output_path)
+        } else {
+            output_path.to_string()
+        };
+        let partial = Self {
+            output_path: output_path.to_string(),
+            task_id: task_id.to_string(),
+            path: partial_output_path(&target, task_id),
+            split,
+            frame_folder: false,
+            part_paths: Vec::new(),
+            committed: false,
+        };
+        for (partial_path, _) in partial.files() {
+            let _ = fs::remove_file(partial_path);
+        }
+        partial
+    }
+
+    /// Frames written into a fresh partial folder beside `directory`, named
+    /// after it.
+    pub(super) fn frame_folder(
+        directory: &str,
+        task_id: &str,
+        container: &str,
+    ) -> Result<Self, ConversionError> {
+        let partial_directory = partial_output_path(directory, task_id);
+        let _ = fs::remove_dir_all(&partial_directory);
+        fs::create_dir_all(&partial_directory).map_err(ConversionError::Io)?;
+        let name = Path::new(directory)
+            .file_name()
+            .map_or_else(|| "frame".into(), |name| name.to_string_lossy());
+        Ok(Self {
+            output_path: directory.to_string(),
+            task_id: task_id.to_string(),
+            path: sequence_output_pattern(&partial_directory, &name, container),
+            split: false,
+            frame_folder: true,
+            part_paths: Vec::new(),
+            committed: false,
+        })
+    }
+
+    /// Path the output is committed to; the folder for frame outputs.
+    pub(super) fn output_path(&self) -> &str {
+        &self.output_path
+    }
+
+    pub(super) const fn is_frame_folder(&self) -> bool {
+        self.frame_folder
+    }
+
+    /// Partial files written so far, in order.
+    pub(super) fn partial_paths(&self) -> Vec<String> {
+        self.files()
+            .into_iter()
+            .map(|(partial_path, _)| partial_path)
+            .collect()
+    }
+
+    pub(super) fn with_part_paths(mut self, part_paths: Vec<String>) -> Self {
+        self.part_paths = part_paths;
+        self
+    }
+
+    /// Partial files written so far, paired with their final paths.
+    fn files(&self) -> Vec<(String, String)> {
+        if self.frame_folder {
+            return vec![(
+                partial_output_path(&self.output_path, &self.task_id),
+                self.output_path.clone(),
+            )];
+        }
+        if !self.split {
+            return vec![(self.path.clone(), self.output_path.clone())];
+        }
+        (1..)
+            .map(|part| {
+                let numbered_path = split_part_path(&self.output_path, part);
+                let output_path = usize::try_from(part - 1)
+                    .ok()
+                    .and_then(|index| self.part_paths.get(index))
+                    .map_or_else(|| numbered_path.clone(), Clone::clone);
+                (
+                    partial_output_path(&numbered_path, &self.task_id),
+                    output_path,
+                )
+            })
+            .take_while(|(partial_path, _)| Path::new(partial_path).exists())
+            .collect()
+    }
+
+    /// Renames every partial file into place and returns the final paths.
+    pub(super) fn commit(mut self) -> Result<Vec<String>, ConversionError> {
+        let mut outputs = Vec::new();
+        for (partial_path, output_path) in self.files() {
+            if self.frame_folder && Path::new(&output_path).is_dir() {
+                fs::remove_dir(&output_path).map_err(ConversionError::Io)?;
+            }
+            fs::rename(&partial_path, &output_path).map_err(ConversionError::Io)?;
+            outputs.push(output_path);
+        }
+        self.committed = true;
+        Ok(outputs)
+    }
+
+    /// Renames every partial file to `rename` of its final path instead of
+    /// committing it, so a rejected output is kept aside, and returns the
+    /// paths the files were kept at.
+    pub(super) fn keep_as(
+        mut self,
+        rename: impl Fn(&str) -> String,
+    ) -> Result<Vec<String>, ConversionError> {
+        let mut kept = Vec::new();
+        for (partial_path, output_path) in self.files() {
+            let kept_path = rename(&output_path);
+            fs::rename(&partial_path, &kept_path).map_err(ConversionError::Io)?;
+            kept.push(kept_path);
+        }
+        self.committed = true;
+        Ok(kept)
+    }
+}
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        if self.frame_folder {
+            let _ = fs::remove_dir_all(partial_output_path(&self.output_path, &self.task_id));
+            return;
+        }
+        let mut files = self.files();
+        if let Some((unfinished_path, _)) = files.pop() {
+            let _ = fs::remove_file(unfinished_path);
+        }
+        if self.split {
+            for (partial_path, output_path) in files {
+                let _ = fs::rename(partial_path, output_path);
+            }
+        }
+    }
+}
+
+/// Releases a task's output reservation however the task ends.
+struct OutputReservation<'a> {
+    controller: &'a ConversionProcessController,
+    task_id: String,
+}
+
+impl Drop for OutputReservation<'_> {
+    fn drop(&mut self) {
+        let _ = self.controller.release_output_paths(&self.task_id);
+    }
+}
+
 fn spawn_batch_worker(
     task: ConversionTask,
     controller: ConversionProcessController,
@@ -193,13 +1153,89 @@ fn spawn_batch_worker(
 
 fn drain_batch_events(
     event_rx: &mpsc::Receiver<ConversionEvent>,
+    outcomes: &mut HashMap<String, BatchTaskOutcome>,
     emit: &mut impl FnMut(ConversionEvent),
 ) {
     while let Ok(event) = event_rx.try_recv() {
+        let outcome = match &event {
+            ConversionEvent::Completed(payload) => {
+                Some(BatchTaskOutcome::Completed(payload.output_path.clone()))
+            }
+            ConversionEvent::Error(_) => Some(BatchTaskOutcome::Failed),
+            ConversionEvent::Cancelled(_) => Some(BatchTaskOutcome::Cancelled),
+            _ => None,
+        };
+        if let Some(outcome) = outcome {
+            outcomes.insert(event.id().to_string(), outcome);
+        }
         emit(event);
     }
 }
 
+fn dependency_is_ready(
+    task: &ConversionTask,
+    outcomes: &HashMap<String, BatchTaskOutcome>,
+) -> bool {
+    task.depends_on.as_deref().is_none_or(|parent_id| {
+        matches!(
+            outcomes.get(parent_id),
+            Some(BatchTaskOutcome::Completed(_))
+        )
+    })
+}
+
+fn take_ready_task(
+    pending: &mut VecDeque<ConversionTask>,
+    outcomes: &HashMap<String, BatchTaskOutcome>,
+) -> Option<ConversionTask> {
+    let index = pending
+        .iter()
+        .position(|task| dependency_is_ready(task, outcomes))?;
+    let mut task = pending.remove(index)?;
+    if let Some(BatchTaskOutcome::Completed(output_path)) = task
+        .depends_on
+        .as_deref()
+        .and_then(|parent_id| outcomes.get(parent_id))
+    {
+        apply_dependency_output(&mut task, output_path);
+    }
+    Some(task)
+}
+
+fn settle_blocked_dependents(
+    pending: &mut VecDeque<ConversionTask>,
+    outcomes: &mut HashMap<String, BatchTaskOutcome>,
+    emit: &mut impl FnMut(ConversionEvent),
+) {
+    while let Some(index) = pending.iter().position(|task| {
+        task.depends_on.as_deref().is_some_and(|parent_id| {
+            matches!(
+                outcomes.get(parent_id),
+                Some(BatchTaskOutcome::Failed | BatchTaskOutcome::Cancelled)
+            )
+        })
+    }) {
+        let Some(task) = pending.remove(index) else {
+            break;
+        };
+        let parent_id = task.depends_on.unwrap_or_default();
+        if outcomes.get(&parent_id) == Some(&BatchTaskOutcome::Cancelled) {
+            emit(ConversionEvent::log(
+                task.id.clone(),
+                format!("[INFO] Task cancelled because dependency {parent_id} was cancelled"),
+            ));
+            emit(ConversionEvent::cancelled(task.id.clone()));
+            outcomes.insert(task.id, BatchTaskOutcome::Cancelled);
+        } else {
+            emit(ConversionEvent::error(
+                task.id.clone(),
+                format!("Dependency {parent_id} did not complete"),
+            ));
+            outcomes.insert(task.id, BatchTaskOutcome::Failed);
+        }
+    }
+}
+
 pub(super) fn next_batch_launch_count(
     pending_count: usize,
     running_count: usize,
@@ -217,77 +1253,337 @@ fn emit_cancelled_task(id: &str, emit: &mut impl FnMut(ConversionEvent)) {
     emit(ConversionEvent::cancelled(id.to_string()));
 }
 
-fn probe_media_file(file_path: &str) -> Result<ProbeMetadata, ConversionError> {
-    let output = Command::new(ffprobe_executable())
-        .args(ffprobe_json_args(file_path))
-        .output()
-        .map_err(ConversionError::Io)?;
+pub(super) fn probe_media_file(file_path: &str) -> Result<ProbeMetadata, ConversionError> {
+    let stdout = run_ffprobe(
+        &ffprobe_executable(),
+        &ffprobe_json_args(&ffmpeg_path_arg(file_path)),
+        DEFAULT_PROBE_TIMEOUT,
+    )
+    .map_err(|error| explain_probe_error(file_path, error))?;
+    let probe = parse_ffprobe_stdout(file_path, stdout)?;
+    Ok(complete_media_probe(file_path, probe))
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let message = if stderr.trim().is_empty() {
-            format!("ffprobe exited with status {}", output.status)
-        } else {
-            stderr.trim().to_string()
-        };
-        return Err(ConversionError::Probe(message));
+/// Fills in what the JSON probe of `file_path` leaves out.
+pub(super) fn complete_media_probe(file_path: &str, mut probe: ProbeMetadata) -> ProbeMetadata {
+    // Animated images rarely store a frame count, and their nominal rate is
+    // the timestamp tick, so the frames are counted to get the real rate.
+    if probe.animated
+        && probe.frame_count.is_none()
+        && !is_network_input(file_path)
+        && probe
+            .duration_seconds()
+            .is_some_and(frame_count_scan_allowed)
+        && let Ok(frames) = count_frames(file_path, None, None)
+    {
+        set_animated_frame_count(&mut probe, frames);
+    }
+    probe
+}
+
+/// Counts the video packets of `file_path` between `start_seconds` and
+/// `end_seconds`, for sources whose container stores no frame count.
+///
+/// # Errors
+///
+/// Returns an error when `FFprobe` cannot start, fails, exceeds
+/// [`MEDIA_ANALYSIS_TIMEOUT`], or prints no packet count.
+pub fn count_frames(
+    file_path: &str,
+    start_seconds: Option<f64>,
+    end_seconds: Option<f64>,
+) -> Result<u64, ConversionError> {
+    let stdout = run_ffprobe(
+        &ffprobe_executable(),
+        &ffprobe_count_packets_args(file_path, start_seconds, end_seconds),
+        MEDIA_ANALYSIS_TIMEOUT,
+    )?;
+
+    parse_packet_count(&stdout)
+        .ok_or_else(|| ConversionError::Probe("ffprobe did not report a packet count".to_string()))
+}
+
+/// Uses the container frame count for full-length conversions and counts
+/// packets over trimmed ranges, skipping scans longer than
+/// [`frame_count_scan_allowed`] permits.
+fn expected_frame_count(task: &ConversionTask, probe: &ProbeMetadata) -> Option<u64> {
+    if !frame_progress_applies(&task.config) || !task.concat_inputs.is_empty() {
+        return None;
+    }
+
+    let ranges = trim_ranges(&task.config);
+    if ranges.is_empty() && probe.frame_count.is_some() {
+        return probe.frame_count;
+    }
+    // Counting would download a network source once more.
+    if is_network_input(&task.file_path) {
+        return None;
+    }
+
+    let source_duration = probe
+        .duration
+        .as_deref()
+        .and_then(|duration| duration.trim().parse::<f64>().ok());
+    let scan_seconds = kept_duration_seconds(&task.config, source_duration)?;
+    if !frame_count_scan_allowed(scan_seconds) {
+        return None;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_ffprobe_stdout(file_path, stdout)
+    if ranges.is_empty() {
+        return count_frames(&task.file_path, None, None)
+            .ok()
+            .filter(|frames| *frames > 0);
+    }
+    ranges
+        .iter()
+        .map(|range| count_frames(&task.file_path, Some(range.start), range.end).ok())
+        .sum::<Option<u64>>()
+        .filter(|frames| *frames > 0)
+}
+
+/// Output captured for one task: the bounded in-memory tail, mirrored to the
+/// task's log file when a log directory is configured.
+#[derive(Default)]
+struct TaskLog {
+    lines: TaskLogBuffer,
+    file: Option<TaskLogFile>,
+}
+
+impl TaskLog {
+    /// Starts the task's log, mirrored to a file when the controller has a
+    /// log directory. A file that cannot be created is reported and skipped.
+    fn open(
+        controller: &ConversionProcessController,
+        task_id: &str,
+        executable: &str,
+        args: &[String],
+        emit: &mut impl FnMut(ConversionEvent),
+    ) -> Result<Self, ConversionError> {
+        let mut task_log = Self::default();
+        if let Some(directory) = controller.task_log_directory()? {
+            let ffmpeg_version = cached_ffmpeg_info().map(|info| info.version);
+            match TaskLogFile::create(
+                &directory,
+                task_id,
+                executable,
+                args,
+                ffmpeg_version.as_deref(),
+            ) {
+                Ok(file) => task_log.file = Some(file),
+                Err(error) => {
+                    let line = format!("[WARN] Could not create the task log file: {error}");
+                    task_log.push(line.clone());
+                    emit(ConversionEvent::log(task_id.to_string(), line));
+                }
+            }
+        }
+        Ok(task_log)
+    }
+
+    fn push(&mut self, line: String) {
+        // A failed write leaves a truncated file rather than retrying on
+        // every line.
+        if let Some(file) = &mut self.file
+            && file.write_line(&line).is_err()
+        {
+            self.file = None;
+        }
+        self.lines.push(line);
+    }
+
+    fn finish(self, status: TaskLogStatus, detail: &str) -> Vec<String> {
+        if let Some(file) = self.file {
+            let _ = file.finish(status, detail);
+        }
+        self.lines.into_lines()
+    }
+}
+
+/// Exit line recorded in the task log once `FFmpeg` has stopped.
+pub(super) fn task_log_exit(
+    status: &Result<ExitStatus, ConversionError>,
+    was_cancelled: bool,
+) -> (TaskLogStatus, String) {
+    let detail = match status {
+        Ok(status) => status.to_string(),
+        Err(error) => error.to_string(),
+    };
+    let log_status = if was_cancelled {
+        TaskLogStatus::Cancelled
+    } else if status.as_ref().is_ok_and(ExitStatus::success) {
+        TaskLogStatus::Completed
+    } else {
+        TaskLogStatus::Failed
+    };
+    (log_status, detail)
 }
 
-fn stream_ffmpeg_stderr(
-    stderr: &mut impl Read,
+/// Output of a running `FFmpeg`: log lines from stderr and `-progress`
+/// lines from stdout.
+enum FfmpegOutput {
+    Log(String),
+    Progress(String),
+}
+
+/// Forwards `FFmpeg`'s output as log, progress and warning events, returning
+/// how many distinct warnings it printed.
+fn stream_ffmpeg_output(
+    stderr: impl Read + Send + 'static,
+    stdout: impl Read + Send + 'static,
     task: &ConversionTask,
+    mut progress: ProgressState,
+    task_log: &mut TaskLog,
     emit: &mut impl FnMut(ConversionEvent),
+) -> Result<usize, ConversionError> {
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut coalesced_emit = |event: ConversionEvent| {
+        let event = event.with_phase(ConversionPhase::Encode);
+        if let ConversionEvent::Log(payload) = &event {
+            task_log.push(payload.line.clone());
+        }
+        coalescer.push(event, Instant::now(), emit);
+    };
+
+    let (output_tx, output_rx) = mpsc::channel();
+    let log_reader = spawn_output_reader(stderr, output_tx.clone(), FfmpegOutput::Log);
+    let progress_reader = spawn_output_reader(stdout, output_tx, FfmpegOutput::Progress);
+    let mut parser = FfmpegProgressParser::default();
+    let mut warnings = ConversionWarningTracker::new();
+    for output in output_rx {
+        match output {
+            FfmpegOutput::Log(line) => {
+                handle_ffmpeg_line(&line, task, &mut progress, &mut coalesced_emit);
+                if let Some(warning) = warnings.push(&task.id, &line, Instant::now()) {
+                    coalesced_emit(warning);
+                }
+            }
+            FfmpegOutput::Progress(line) => {
+                if let Some(block) = parser.push_line(&line) {
+                    progress.structured = true;
+                    if let Some(percent) = structured_progress_percent(&block, &progress) {
+                        coalesced_emit(ConversionEvent::progress_with_stats(
+                            task.id.clone(),
+                            percent,
+                            block.stats(),
+                        ));
+                    } else if !progress.has_total() {
+                        coalesced_emit(ConversionEvent::indeterminate_progress(
+                            task.id.clone(),
+                            block.stats(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    warnings.flush(Instant::now(), &mut coalesced_emit);
+    let result = join_output_reader(log_reader).and(join_output_reader(progress_reader));
+    coalescer.flush(emit);
+    result.map(|()| warnings.warning_count())
+}
+
+fn spawn_output_reader(
+    mut reader: impl Read + Send + 'static,
+    output_tx: mpsc::Sender<FfmpegOutput>,
+    wrap: fn(String) -> FfmpegOutput,
+) -> thread::JoinHandle<std::io::Result<()>> {
+    thread::spawn(move || {
+        read_output_segments(&mut reader, |segment| {
+            let _ = output_tx.send(wrap(segment));
+        })
+    })
+}
+
+fn join_output_reader(
+    reader: thread::JoinHandle<std::io::Result<()>>,
 ) -> Result<(), ConversionError> {
+    reader
+        .join()
+        .map_err(|_| ConversionError::Worker("ffmpeg output reader panicked".to_string()))?
+        .map_err(ConversionError::Io)
+}
+
+/// Splits `reader` into trimmed, non-empty segments at `\r` and `\n`, since
+/// `FFmpeg` redraws status lines with carriage returns.
+pub(super) fn read_output_segments(
+    reader: &mut impl Read,
+    mut on_segment: impl FnMut(String),
+) -> std::io::Result<()> {
     let mut buffer = [0_u8; 4096];
     let mut pending = String::new();
-    let mut total_duration = None;
-    let expected_duration = expected_duration_seconds(&task.config);
 
     loop {
-        let read = stderr.read(&mut buffer).map_err(ConversionError::Io)?;
+        let read = reader.read(&mut buffer)?;
         if read == 0 {
             break;
         }
 
         pending.push_str(&String::from_utf8_lossy(&buffer[..read]));
-        drain_ffmpeg_segments(
-            &mut pending,
-            task,
-            expected_duration,
-            &mut total_duration,
-            emit,
-        );
+        while let Some(separator_index) = pending.find(['\r', '\n']) {
+            let segment = pending[..separator_index].trim().to_string();
+            pending.drain(..=separator_index);
+            if !segment.is_empty() {
+                on_segment(segment);
+            }
+        }
     }
 
-    if !pending.trim().is_empty() {
-        handle_ffmpeg_line(
-            pending.trim(),
-            task,
-            expected_duration,
-            &mut total_duration,
-            emit,
-        );
+    let rest = pending.trim();
+    if !rest.is_empty() {
+        on_segment(rest.to_string());
     }
-
     Ok(())
 }
 
-fn drain_ffmpeg_segments(
-    pending: &mut String,
-    task: &ConversionTask,
+/// Progress inputs for one `FFmpeg` run; a known frame count takes precedence
+/// over timestamps, which drift on variable frame rate sources.
+pub(super) struct ProgressState {
     expected_duration: f64,
-    total_duration: &mut Option<f64>,
-    emit: &mut impl FnMut(ConversionEvent),
-) {
-    while let Some(separator_index) = pending.find(['\r', '\n']) {
-        let segment = pending[..separator_index].trim().to_string();
-        pending.drain(..=separator_index);
-        if !segment.is_empty() {
-            handle_ffmpeg_line(&segment, task, expected_duration, total_duration, emit);
+    expected_frames: Option<u64>,
+    total_duration: Option<f64>,
+    /// A `-progress` block has arrived, so stderr stats are no longer
+    /// scraped for progress.
+    structured: bool,
+}
+
+impl ProgressState {
+    pub(super) fn new(
+        task: &ConversionTask,
+        expected_frames: Option<u64>,
+        probe: &ProbeMetadata,
+    ) -> Self {
+        Self {
+            expected_duration: expected_duration_seconds(&task.config),
+            expected_frames,
+            total_duration: probe
+                .duration
+                .as_deref()
+                .and_then(|duration| duration.trim().parse::<f64>().ok())
+                .filter(|duration| *duration > 0.0),
+            structured: false,
+        }
+    }
+
+    /// Measures a join against the summed length of its sources.
+    const fn with_joined_duration(mut self, duration: Option<f64>) -> Self {
+        if let Some(duration) = duration {
+            self.expected_duration = duration;
+        }
+        self
+    }
+
+    /// Whether a share of the work can be computed; sources of unknown
+    /// length, such as live streams, report time and bytes processed instead.
+    pub(super) fn has_total(&self) -> bool {
+        self.expected_frames.is_some_and(|frames| frames > 0) || self.duration() > 0.0
+    }
+
+    fn duration(&self) -> f64 {
+        if self.expected_duration > 0.0 {
+            self.expected_duration
+        } else {
+            self.total_duration.unwrap_or(0.0)
         }
     }
 }
@@ -295,27 +1591,59 @@ fn drain_ffmpeg_segments(
 fn handle_ffmpeg_line(
     line: &str,
     task: &ConversionTask,
-    expected_duration: f64,
-    total_duration: &mut Option<f64>,
+    progress: &mut ProgressState,
     emit: &mut impl FnMut(ConversionEvent),
 ) {
     emit(ConversionEvent::log(task.id.clone(), line));
-    if let Some(progress) = ffmpeg_progress_from_line(line, expected_duration, total_duration) {
+    if progress.structured {
+        return;
+    }
+    let frame_progress = progress
+        .expected_frames
+        .and_then(|expected_frames| ffmpeg_frame_progress_from_line(line, expected_frames));
+    let time_progress = || {
+        ffmpeg_progress_from_line(
+            line,
+            progress.expected_duration,
+            &mut progress.total_duration,
+        )
+    };
+    if let Some(progress) = frame_progress.or_else(time_progress) {
         emit(ConversionEvent::progress(task.id.clone(), progress));
     }
 }
 
+/// Percent complete for a `-progress` block, from the encoded frame count
+/// when the expected total is known and from the output timestamp otherwise.
+pub(super) fn structured_progress_percent(
+    block: &FfmpegProgress,
+    progress: &ProgressState,
+) -> Option<f64> {
+    if let Some(expected_frames) = progress.expected_frames.filter(|frames| *frames > 0)
+        && let Some(frame) = block.frame
+    {
+        return Some((u64_to_f64(frame) / u64_to_f64(expected_frames) * 100.0).clamp(0.0, 100.0));
+    }
+
+    let current_time = block.out_time_seconds?;
+    let duration = progress.duration();
+    (duration > 0.0).then(|| (current_time / duration * 100.0).clamp(0.0, 100.0))
+}
+
 fn expected_duration_seconds(config: &CoreConversionConfig) -> f64 {
-    let start = config
-        .start_time
-        .as_deref()
-        .and_then(parse_time)
-        .unwrap_or(0.0);
-    let Some(end) = config.end_time.as_deref().and_then(parse_time) else {
-        return 0.0;
-    };
+    kept_duration_seconds(config, None).unwrap_or(0.0)
+}
+
+pub(super) fn ffmpeg_frame_progress_from_line(line: &str, expected_frames: u64) -> Option<f64> {
+    if expected_frames == 0 || !line.contains("time=") {
+        return None;
+    }
+    let frame = FRAME_REGEX
+        .captures(line)
+        .and_then(|caps| caps.get(1))
+        .and_then(|m| m.as_str().parse::<u64>().ok())?;
 
-    (end - start).max(0.0)
+    Some((u64_to_f64(frame) / u64_to_f64(expected_frames) * 100.0).clamp(0.0, 100.0))
 }
 
 pub(super) fn ffmpeg_progress_from_line(
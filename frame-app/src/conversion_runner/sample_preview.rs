@@ -0,0 +1,212 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process,
+    sync::{Mutex, TryLockError},
+    time::Instant,
+};
+
+use frame_core::{
+    args::build_ffmpeg_args,
+    comparison_clip::{ComparisonClipOptions, ComparisonLayout, build_comparison_clip_args},
+    error::ConversionError,
+    estimate::{effective_duration_seconds, extrapolate_sample_sizes},
+    events::{ConversionEvent, ConversionEventCoalescer},
+    sample_preview::{SamplePreview, sample_preview_config, sample_preview_range},
+    segments::resolve_remove_segments,
+    timecode::resolve_frame_times,
+    types::ConversionConfig as CoreConversionConfig,
+    utils::{TIME_REGEX, parse_time},
+};
+
+use super::{
+    ConversionProcessController,
+    analysis::{MEDIA_ANALYSIS_TIMEOUT, analysis_progress, run_ffmpeg_analysis},
+    output_paths::sanitized_task_id,
+    runner::{ffmpeg_path_arg, probe_media_file},
+};
+use crate::capabilities::cached_ffmpeg_capabilities;
+
+/// Held while a preview encodes, so only one runs at a time.
+static SAMPLE_PREVIEW_SLOT: Mutex<()> = Mutex::new(());
+
+/// Encodes a short preview of converting `file_path` with `config`.
+///
+/// The preview lasts `duration_seconds` from `at_seconds` into the source,
+/// defaulting to ten seconds a quarter of the way in, and is written to a
+/// temporary file that [`remove_sample_previews`] deletes. Previews run
+/// outside the conversion queue and its concurrency limit, one at a time,
+/// and are cancelled through `controller` under `preview_id`.
+///
+/// # Errors
+///
+/// Returns an error when another preview is still encoding, the source
+/// cannot be probed, the settings cannot be previewed, or the encode fails,
+/// is cancelled, or exceeds [`MEDIA_ANALYSIS_TIMEOUT`].
+pub fn preview_conversion(
+    preview_id: &str,
+    file_path: &str,
+    config: &CoreConversionConfig,
+    at_seconds: Option<f64>,
+    duration_seconds: Option<f64>,
+    controller: &ConversionProcessController,
+) -> Result<SamplePreview, ConversionError> {
+    let _slot = match SAMPLE_PREVIEW_SLOT.try_lock() {
+        Ok(slot) => slot,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            return Err(ConversionError::InvalidInput(
+                "Another preview is still encoding; cancel it or wait for it to finish".to_string(),
+            ));
+        }
+    };
+
+    let probe = probe_media_file(file_path)?;
+    let mut resolved = config.clone();
+    resolve_frame_times(&mut resolved, probe.frame_rate)?;
+    resolve_remove_segments(&mut resolved, probe.duration_seconds())?;
+    let (start, length) =
+        sample_preview_range(probe.duration_seconds(), at_seconds, duration_seconds)?;
+    let preview_config = sample_preview_config(&resolved, start, length)?;
+
+    let directory = sample_preview_directory();
+    fs::create_dir_all(&directory).map_err(ConversionError::Io)?;
+    let path = directory.join(format!(
+        "{}.{}",
+        sanitized_task_id(preview_id),
+        preview_config.container
+    ));
+    let path_text = path.to_string_lossy().into_owned();
+    let args = build_ffmpeg_args(
+        &ffmpeg_path_arg(file_path),
+        &ffmpeg_path_arg(&path_text),
+        &preview_config,
+        &probe,
+    )?;
+    if let Err(error) = run_ffmpeg_analysis(
+        preview_id,
+        &args,
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |_| {},
+    ) {
+        let _ = fs::remove_file(&path);
+        return Err(error);
+    }
+
+    let bytes = fs::metadata(&path).map_err(ConversionError::Io)?.len();
+    let estimated_full_size = effective_duration_seconds(&resolved, &probe)
+        .and_then(|duration| extrapolate_sample_sizes(&[(bytes, length)], duration));
+    Ok(SamplePreview {
+        path: path_text,
+        start_seconds: start,
+        duration_seconds: length,
+        bytes,
+        estimated_full_size,
+    })
+}
+
+/// Encodes a clip playing `duration_seconds` of `reference` and `encoded`
+/// together from `at_seconds`, arranged by `layout`, and returns its path.
+///
+/// The section is picked like a conversion preview's from the reference's
+/// duration, and the clip is written next to the previews, so
+/// [`remove_sample_previews`] deletes it too. Progress is emitted under
+/// `comparison_id`, which also identifies the pass for cancellation, and
+/// notes about the clip are emitted as log lines.
+///
+/// # Errors
+///
+/// Returns an error when either file cannot be probed or has no video, the
+/// section is invalid, or the encode fails, is cancelled, or exceeds
+/// [`MEDIA_ANALYSIS_TIMEOUT`].
+#[expect(
+    clippy::too_many_arguments,
+    reason = "mirrors the other analysis commands, which take the id, controller and emitter"
+)]
+pub fn generate_comparison(
+    comparison_id: &str,
+    reference: &str,
+    encoded: &str,
+    at_seconds: Option<f64>,
+    duration_seconds: Option<f64>,
+    layout: ComparisonLayout,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<String, ConversionError> {
+    let reference_probe = probe_media_file(reference)?;
+    let encoded_probe = probe_media_file(encoded)?;
+    let (start, length) = sample_preview_range(
+        reference_probe.duration_seconds(),
+        at_seconds,
+        duration_seconds,
+    )?;
+
+    let directory = sample_preview_directory();
+    fs::create_dir_all(&directory).map_err(ConversionError::Io)?;
+    let path = directory.join(format!(
+        "{}-comparison.mp4",
+        sanitized_task_id(comparison_id)
+    ));
+    let path_text = path.to_string_lossy().into_owned();
+    let plan = build_comparison_clip_args(
+        &ffmpeg_path_arg(reference),
+        &ffmpeg_path_arg(encoded),
+        &ffmpeg_path_arg(&path_text),
+        ComparisonClipOptions {
+            start_seconds: start,
+            duration_seconds: length,
+            layout,
+            labels: cached_ffmpeg_capabilities()
+                .is_none_or(|capabilities| capabilities.has_filter("drawtext")),
+        },
+        &reference_probe,
+        &encoded_probe,
+    )?;
+    for note in &plan.notes {
+        emit(ConversionEvent::log(
+            comparison_id,
+            format!("[WARN] {note}"),
+        ));
+    }
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    emit(analysis_progress(comparison_id, 0.0));
+    let result = run_ffmpeg_analysis(
+        comparison_id,
+        &plan.args,
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |line| {
+            let time = TIME_REGEX
+                .captures(line)
+                .and_then(|caps| caps.get(1))
+                .and_then(|m| parse_time(m.as_str()));
+            if let Some(time) = time {
+                let progress = (time / length * 100.0).clamp(0.0, 100.0);
+                coalescer.push(
+                    analysis_progress(comparison_id, progress),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    if let Err(error) = result {
+        let _ = fs::remove_file(&path);
+        return Err(error);
+    }
+
+    emit(analysis_progress(comparison_id, 100.0));
+    Ok(path_text)
+}
+
+/// Deletes every preview and comparison clip written by this run of the app.
+pub fn remove_sample_previews() {
+    let _ = fs::remove_dir_all(sample_preview_directory());
+}
+
+fn sample_preview_directory() -> PathBuf {
+    std::env::temp_dir().join(format!("frame-previews-{}", process::id()))
+}
@@ -6,12 +6,13 @@
 use super::*;
 use crate::settings::{
     AudioFiltersConfig, CropSettings, DeinterlaceMode, FilterStrength, FilterValue, MetadataConfig,
-    MetadataMode, ProcessingMode, VideoColorFiltersConfig, VideoFiltersConfig,
+    MetadataMode, ProcessingMode, TrimSegment, VideoColorFiltersConfig, VideoFiltersConfig,
 };
+use frame_core::image_batch::ImageBatchOptions;
 use std::{
     fs,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus},
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -117,6 +118,19 @@ fn core_config_from_gpui_preserves_active_conversion_fields() {
         },
         start_time: Some("00:00:05.000".to_string()),
         end_time: Some("00:00:15.000".to_string()),
+        segments: vec![TrimSegment {
+            start: "00:00:05.000".to_string(),
+            end: "00:00:15.000".to_string(),
+        }],
+        remove_segments: Vec::new(),
+        split_by_chapters: true,
+        image_sequence_output: true,
+        smart_cut: true,
+        seek_mode: crate::settings::SeekMode::Hybrid,
+        parallel_chunks: Some(1),
+        verify_output: true,
+        extra_input_args: vec!["-thread_queue_size".to_string(), "512".to_string()],
+        extra_output_args: vec!["-movflags".to_string(), "+faststart".to_string()],
         metadata: MetadataConfig {
             mode: MetadataMode::Replace,
             title: Some("Render Title".to_string()),
@@ -153,6 +167,7 @@ fn core_config_from_gpui_preserves_active_conversion_fields() {
         custom_height: Some("1080".to_string()),
         scaling_algorithm: "lanczos".to_string(),
         fps: "30".to_string(),
+        input_framerate: Some(" 24000/1001 ".to_string()),
         crf: 18,
         quality: 60,
         preset: "slow".to_string(),
@@ -166,6 +181,8 @@ fn core_config_from_gpui_preserves_active_conversion_fields() {
         image_png_compression: 8,
         image_png_prediction: "mixed".to_string(),
         image_tiff_compression: "deflate".to_string(),
+        image_avif_crf: 22,
+        image_target_max_kb: Some(0),
         gif_colors: 128,
         gif_dither: "floyd_steinberg".to_string(),
         gif_loop: 3,
@@ -173,6 +190,7 @@ fn core_config_from_gpui_preserves_active_conversion_fields() {
         nvenc_temporal_aq: false,
         videotoolbox_allow_sw: false,
         hw_decode: false,
+        hw_device: None,
     };
 
     let core = core_config_from_gpui(&config);
@@ -204,11 +222,24 @@ fn core_config_from_gpui_preserves_active_conversion_fields() {
     assert_eq!(core.image_png_compression, 8);
     assert_eq!(core.image_png_prediction, "mixed");
     assert_eq!(core.image_tiff_compression, "deflate");
+    assert_eq!(core.image_avif_crf, 22);
+    assert_eq!(core.image_target_max_kb, None);
     assert_eq!(core.gif_colors, 128);
     assert_eq!(core.gif_dither, "floyd_steinberg");
     assert_eq!(core.gif_loop, 3);
     assert_eq!(core.start_time.as_deref(), Some("00:00:05.000"));
     assert_eq!(core.end_time.as_deref(), Some("00:00:15.000"));
+    assert_eq!(core.segments.len(), 1);
+    assert_eq!(core.segments[0].end, "00:00:15.000");
+    assert!(core.split_by_chapters);
+    assert!(core.image_sequence_output);
+    assert!(core.smart_cut);
+    assert_eq!(core.parallel_chunks, None);
+    assert!(core.verify_output);
+    assert_eq!(core.extra_input_args, ["-thread_queue_size", "512"]);
+    assert_eq!(core.extra_output_args, ["-movflags", "+faststart"]);
+    assert_eq!(core.seek_mode, frame_core::types::SeekMode::Hybrid);
+    assert_eq!(core.input_framerate.as_deref(), Some("24000/1001"));
     assert_eq!(core.rotation, "90");
     assert!(core.flip_horizontal);
     assert!(core.flip_vertical);
@@ -241,6 +272,106 @@ fn conversion_task_from_file_sanitizes_output_name() {
     assert_eq!(task.output_directory, "/tmp/frame-output");
 }
 
+#[test]
+fn conversion_task_from_file_uses_save_as_folder_without_mirroring() {
+    let mut file = FileItem::from_path("file-1", "/tmp/in/clips/source.mov", 1);
+    file.relative_subpath = Some("clips".to_string());
+    file.output_name = "final.mp4".to_string();
+    file.output_directory = Some("/Volumes/Renders".to_string());
+
+    let task = conversion_task_from_file(&file, "/tmp/frame-output");
+
+    assert_eq!(task.output_directory, "/Volumes/Renders");
+    assert_eq!(task.relative_subpath, None);
+    assert_eq!(task_output_path(&task), "/Volumes/Renders/final.mp4");
+}
+
+#[test]
+fn conversion_task_with_settings_applies_output_settings_of_the_app() {
+    let settings = crate::app_persistence::AppSettings {
+        overwrite_policy: OverwritePolicy::Rename,
+        output_name_template: Some("{name}_{vcodec}".to_string()),
+        preserve_file_times: true,
+        post_action: PostAction::Trash,
+        scratch_directory: Some(PathBuf::from("/scratch")),
+        ..Default::default()
+    };
+    let file = FileItem::from_path("file-1", "/tmp/in/source.mov", 1);
+    let mut renamed = file.clone();
+    renamed.output_name = "final".to_string();
+
+    let task = conversion_task_with_settings(&file, "/tmp/frame-output", &settings);
+    let renamed_task = conversion_task_with_settings(&renamed, "/tmp/frame-output", &settings);
+
+    assert_eq!(task.config.overwrite_policy, OverwritePolicy::Rename);
+    assert!(task.preserve_file_times);
+    assert_eq!(task.post_action, PostAction::Trash);
+    assert_eq!(task.scratch_directory.as_deref(), Some("/scratch"));
+    assert_eq!(task.output_template.as_deref(), Some("{name}_{vcodec}"));
+    assert_eq!(renamed_task.output_template, None);
+}
+
+#[test]
+fn concat_task_from_files_joins_the_rest_into_the_first_task() {
+    let mut first = FileItem::from_path("file-1", "/tmp/GX010042.MP4", 1);
+    first.output_name = "ride.mp4".to_string();
+    let files = [
+        first,
+        FileItem::from_path("file-2", "/tmp/GX020042.MP4", 1),
+        FileItem::from_path("file-3", "/tmp/GX030042.MP4", 1),
+    ];
+
+    let task = concat_task_from_files(&files, "/tmp/frame-output").expect("task should build");
+
+    assert_eq!(task.id, "file-1");
+    assert_eq!(task.file_path, "/tmp/GX010042.MP4");
+    assert_eq!(task.output_name.as_deref(), Some("ride.mp4"));
+    assert_eq!(
+        task.concat_inputs,
+        ["/tmp/GX020042.MP4", "/tmp/GX030042.MP4"]
+    );
+    assert!(concat_task_from_files(&files[..1], "/tmp/frame-output").is_none());
+}
+
+#[test]
+fn image_batch_tasks_from_files_turn_every_file_into_a_bounded_still() {
+    let files = [
+        FileItem::from_path("file-1", "/tmp/IMG_0001.HEIC", 1),
+        FileItem::from_path("file-2", "/tmp/IMG_0002.png", 1),
+    ];
+    let options = ImageBatchOptions {
+        max_width: Some(2048),
+        max_height: Some(2048),
+        format: "jpg".to_string(),
+        quality: 85,
+        strip_metadata: true,
+        target_max_kb: Some(500),
+    };
+
+    let tasks = image_batch_tasks_from_files(&files, &options, "/tmp/frame-output")
+        .expect("batch tasks should build");
+
+    assert_eq!(tasks.len(), 2);
+    assert!(tasks.iter().all(|task| {
+        task.config.container == "jpg"
+            && task.config.resolution == "fit"
+            && task.config.image_jpeg_quality == 85
+            && task.config.image_target_max_kb == Some(500)
+    }));
+    assert_eq!(tasks[1].file_path, "/tmp/IMG_0002.png");
+
+    let error = image_batch_tasks_from_files(
+        &files,
+        &ImageBatchOptions {
+            format: "mp4".to_string(),
+            ..options
+        },
+        "/tmp/frame-output",
+    )
+    .expect_err("video formats should be rejected");
+    assert!(error.to_string().contains("not a still image format"));
+}
+
 #[test]
 fn disambiguate_output_paths_suffixes_same_stem_files_from_different_directories() {
     let sandbox = ConversionRunnerSandbox::new("duplicate-output-names");
@@ -263,6 +394,33 @@ fn disambiguate_output_paths_suffixes_same_stem_files_from_different_directories
     );
 }
 
+#[test]
+fn disambiguate_output_paths_keeps_mirrored_folders_apart() {
+    let sandbox = ConversionRunnerSandbox::new("mirrored-output-names");
+    let output_directory = sandbox.root.to_string_lossy();
+    let mut first = FileItem::from_path("week-1", "/Lectures/week 1/intro.mov", 1);
+    first.relative_subpath = Some("week 1".to_string());
+    let mut second = FileItem::from_path("week-2", "/Lectures/week 2/intro.mov", 1);
+    second.relative_subpath = Some("week 2".to_string());
+    let mut tasks = vec![
+        conversion_task_from_file(&first, &output_directory),
+        conversion_task_from_file(&second, &output_directory),
+    ];
+
+    disambiguate_output_paths(&mut tasks);
+
+    assert_eq!(
+        tasks
+            .iter()
+            .map(|task| PathBuf::from(task_output_path(task)))
+            .collect::<Vec<_>>(),
+        [
+            sandbox.root.join("week 1").join("intro_converted.mp4"),
+            sandbox.root.join("week 2").join("intro_converted.mp4"),
+        ]
+    );
+}
+
 #[test]
 fn disambiguate_output_paths_preserves_single_non_conflicting_name() {
     let sandbox = ConversionRunnerSandbox::new("single-output-name");
@@ -319,6 +477,337 @@ fn disambiguate_output_paths_uses_next_free_suffix_deterministically() {
     );
 }
 
+#[test]
+fn disambiguate_output_paths_ignores_existing_files_for_overwrite_policy() {
+    let sandbox = ConversionRunnerSandbox::new("overwrite-output-name");
+    fs::write(sandbox.path("clip_converted.mp4"), b"replace")
+        .expect("existing output fixture should be written");
+    let file = FileItem::from_path("mov", "/A/clip.mov", 1);
+    let mut task = conversion_task_from_file(&file, &sandbox.root.to_string_lossy());
+    task.config.overwrite_policy = OverwritePolicy::Overwrite;
+    let mut tasks = vec![task];
+
+    disambiguate_output_paths(&mut tasks);
+
+    assert_eq!(tasks[0].output_name.as_deref(), Some("clip_converted"));
+}
+
+#[test]
+fn resolve_output_path_at_start_renames_outputs_created_after_queueing() {
+    let sandbox = ConversionRunnerSandbox::new("start-time-rename");
+    let file = FileItem::from_path("mov", "/A/clip.mov", 1);
+    let mut tasks = vec![conversion_task_from_file(
+        &file,
+        &sandbox.root.to_string_lossy(),
+    )];
+    disambiguate_output_paths(&mut tasks);
+    fs::write(sandbox.path("clip_converted.mp4"), b"keep")
+        .expect("conflicting output fixture should be written");
+
+    let output_path = resolve_output_path_at_start(
+        &mut tasks[0],
+        &ConversionProcessController::default(),
+        &ProbeMetadata::default(),
+    )
+    .expect("rename policy should resolve");
+
+    assert_eq!(
+        PathBuf::from(output_path),
+        sandbox.path("clip_converted_2.mp4")
+    );
+}
+
+#[test]
+fn resolve_output_path_at_start_reports_existing_output_for_fail_policy() {
+    let sandbox = ConversionRunnerSandbox::new("start-time-fail");
+    fs::write(sandbox.path("clip_converted.mp4"), b"keep")
+        .expect("conflicting output fixture should be written");
+    let file = FileItem::from_path("mov", "/A/clip.mov", 1);
+    let mut task = conversion_task_from_file(&file, &sandbox.root.to_string_lossy());
+    task.config.overwrite_policy = OverwritePolicy::Fail;
+
+    let error = resolve_output_path_at_start(
+        &mut task,
+        &ConversionProcessController::default(),
+        &ProbeMetadata::default(),
+    )
+    .expect_err("fail policy should reject");
+
+    assert!(
+        matches!(error, ConversionError::OutputExists(path) if path.ends_with("clip_converted.mp4"))
+    );
+}
+
+#[test]
+fn resolve_output_path_at_start_skips_paths_reserved_by_running_tasks() {
+    let sandbox = ConversionRunnerSandbox::new("start-time-reservation");
+    let controller = ConversionProcessController::default();
+    let directory = sandbox.root.to_string_lossy();
+    let mut first =
+        conversion_task_from_file(&FileItem::from_path("a", "/A/clip.mov", 1), &directory);
+    let mut second =
+        conversion_task_from_file(&FileItem::from_path("b", "/B/clip.mkv", 1), &directory);
+    let mut overwrite =
+        conversion_task_from_file(&FileItem::from_path("c", "/C/clip.avi", 1), &directory);
+    overwrite.config.overwrite_policy = OverwritePolicy::Overwrite;
+
+    let first_path =
+        resolve_output_path_at_start(&mut first, &controller, &ProbeMetadata::default())
+            .expect("first task should resolve");
+    let second_path =
+        resolve_output_path_at_start(&mut second, &controller, &ProbeMetadata::default())
+            .expect("second task should resolve");
+    let overwrite_error =
+        resolve_output_path_at_start(&mut overwrite, &controller, &ProbeMetadata::default())
+            .expect_err("overwrite must not share a running task's output");
+
+    assert_eq!(
+        PathBuf::from(first_path),
+        sandbox.path("clip_converted.mp4")
+    );
+    assert_eq!(
+        PathBuf::from(second_path),
+        sandbox.path("clip_converted_2.mp4")
+    );
+    assert!(matches!(overwrite_error, ConversionError::OutputExists(_)));
+
+    controller
+        .release_output_paths("a")
+        .expect("reservations should be released");
+    resolve_output_path_at_start(&mut overwrite, &controller, &ProbeMetadata::default())
+        .expect("released output should be reusable");
+}
+
+#[test]
+fn partial_output_path_stays_beside_output_with_sanitized_task_id() {
+    assert_eq!(
+        partial_output_path("/out/clip_converted.mp4", "file 1/2"),
+        "/out/clip_converted.mp4.file-1-2.part"
+    );
+}
+
+#[test]
+fn partial_split_output_keeps_finished_parts_when_stopped_early() {
+    let sandbox = ConversionRunnerSandbox::new("split-partial-output");
+    let output_path = sandbox.path("talk.mp4").to_string_lossy().into_owned();
+    let partial = PartialOutput::new(&output_path, "task-1", true);
+    assert!(partial.path.ends_with("talk-part%03d.mp4.task-1.part"));
+    for part in ["talk-part001.mp4", "talk-part002.mp4", "talk-part003.mp4"] {
+        fs::write(sandbox.path(&format!("{part}.task-1.part")), b"part")
+            .expect("partial part fixture should be written");
+    }
+
+    drop(partial);
+
+    assert!(sandbox.path("talk-part001.mp4").is_file());
+    assert!(sandbox.path("talk-part002.mp4").is_file());
+    assert!(!sandbox.path("talk-part003.mp4").exists());
+    assert!(!sandbox.path("talk-part003.mp4.task-1.part").exists());
+}
+
+#[test]
+fn partial_split_output_commits_every_part() {
+    let sandbox = ConversionRunnerSandbox::new("split-output-commit");
+    let output_path = sandbox.path("talk.mkv").to_string_lossy().into_owned();
+    let partial = PartialOutput::new(&output_path, "task-1", true);
+    for part in ["talk-part001.mkv", "talk-part002.mkv"] {
+        fs::write(sandbox.path(&format!("{part}.task-1.part")), b"part")
+            .expect("partial part fixture should be written");
+    }
+
+    let outputs = partial.commit().expect("parts should be committed");
+
+    assert_eq!(
+        outputs.iter().map(PathBuf::from).collect::<Vec<_>>(),
+        [
+            sandbox.path("talk-part001.mkv"),
+            sandbox.path("talk-part002.mkv")
+        ]
+    );
+}
+
+#[test]
+fn partial_split_output_commits_chapter_parts_under_their_titles() {
+    let sandbox = ConversionRunnerSandbox::new("chapter-output-commit");
+    let output_path = sandbox.path("show.m4a").to_string_lossy().into_owned();
+    let chapter_paths = vec![
+        sandbox
+            .path("show - 01 Intro.m4a")
+            .to_string_lossy()
+            .into_owned(),
+        sandbox.path("show - 02.m4a").to_string_lossy().into_owned(),
+    ];
+    let partial =
+        PartialOutput::new(&output_path, "task-1", true).with_part_paths(chapter_paths.clone());
+    for part in ["show-part001.m4a", "show-part002.m4a"] {
+        fs::write(sandbox.path(&format!("{part}.task-1.part")), b"part")
+            .expect("partial part fixture should be written");
+    }
+
+    let outputs = partial.commit().expect("chapter parts should be committed");
+
+    assert_eq!(outputs, chapter_paths);
+    assert!(sandbox.path("show - 02.m4a").is_file());
+}
+
+#[test]
+fn partial_output_rejected_by_verification_is_kept_as_suspect() {
+    let sandbox = ConversionRunnerSandbox::new("suspect-output");
+    let output_path = sandbox.path("clip.mp4").to_string_lossy().into_owned();
+    let partial = PartialOutput::new(&output_path, "task-1", false);
+    fs::write(&partial.path, b"truncated").expect("partial output should be written");
+    let mut verification = OutputVerification::default();
+    verification.set_durations(Some(30.0), Some(12.0));
+
+    let error = reject_unverified_output(partial, &verification);
+
+    assert_eq!(error.code(), "worker.verification_failed");
+    assert!(sandbox.path("clip.mp4.suspect").is_file());
+    assert!(!sandbox.path("clip.mp4").exists());
+    assert!(!sandbox.path("clip.mp4.task-1.part").exists());
+    assert!(error.to_string().contains("kept as"));
+}
+
+#[test]
+fn partial_frame_folder_is_renamed_whole_or_deleted_with_its_frames() {
+    let sandbox = ConversionRunnerSandbox::new("frame-folder-output");
+    let directory = sandbox.path("clip").to_string_lossy().into_owned();
+    fs::create_dir(&directory).expect("empty target folder should be created");
+    let partial =
+        PartialOutput::frame_folder(&directory, "task-1", "png").expect("folder should be made");
+    assert!(partial.path.ends_with("clip_%06d.png"));
+    let partial_directory = sandbox.path("clip.task-1.part");
+    fs::write(partial_directory.join("clip_000001.png"), b"frame")
+        .expect("frame fixture should be written");
+
+    let outputs = partial.commit().expect("frame folder should be committed");
+
+    assert_eq!(outputs, [directory.clone()]);
+    assert!(sandbox.path("clip").join("clip_000001.png").is_file());
+
+    let cancelled =
+        PartialOutput::frame_folder(&sandbox.path("take").to_string_lossy(), "task-2", "jpg")
+            .expect("folder should be made");
+    fs::write(
+        sandbox.path("take.task-2.part").join("take_000001.jpg"),
+        b"frame",
+    )
+    .expect("frame fixture should be written");
+    drop(cancelled);
+
+    assert!(!sandbox.path("take.task-2.part").exists());
+    assert!(!sandbox.path("take").exists());
+}
+
+#[test]
+fn copy_source_file_times_mirrors_modification_time() {
+    let sandbox = ConversionRunnerSandbox::new("preserve-file-times");
+    let source = sandbox.path("source.mov");
+    let output = sandbox.path("output.mp4");
+    fs::write(&source, b"source").expect("source fixture should be written");
+    fs::write(&output, b"output").expect("output fixture should be written");
+    let modified = UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&source)
+        .and_then(|file| file.set_modified(modified))
+        .expect("source time should be set");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&source, fs::Permissions::from_mode(0o640))
+            .expect("source permissions should be set");
+    }
+
+    copy_source_file_times(&source.to_string_lossy(), &output.to_string_lossy())
+        .expect("file times should be copied");
+
+    let metadata = fs::metadata(&output).expect("output metadata should be readable");
+    assert_eq!(metadata.modified().ok(), Some(modified));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+    }
+}
+
+#[test]
+fn apply_post_action_deletes_source_after_verified_output() {
+    let sandbox = ConversionRunnerSandbox::new("post-action-delete");
+    let source = sandbox.path("source.mov");
+    let output = sandbox.path("output.mp4");
+    fs::write(&source, b"source").expect("source fixture should be written");
+    fs::write(&output, b"output").expect("output fixture should be written");
+
+    let outcome = apply_post_action(
+        &source.to_string_lossy(),
+        &output.to_string_lossy(),
+        PostAction::Delete,
+    );
+
+    assert_eq!(outcome.action, PostAction::Delete);
+    assert_eq!(outcome.error, None);
+    assert!(!source.exists());
+    assert!(output.exists());
+}
+
+#[test]
+fn apply_post_action_keeps_source_when_output_is_unverified() {
+    let sandbox = ConversionRunnerSandbox::new("post-action-refused");
+    let source = sandbox.path("source.mov");
+    let empty_output = sandbox.path("empty.mp4");
+    fs::write(&source, b"source").expect("source fixture should be written");
+    fs::write(&empty_output, b"").expect("output fixture should be written");
+    let missing_output = sandbox.path("missing.mp4");
+    let source_path = source.to_string_lossy();
+
+    for (output, action) in [
+        (empty_output.to_string_lossy(), PostAction::Delete),
+        (missing_output.to_string_lossy(), PostAction::Trash),
+        (source_path.clone(), PostAction::Delete),
+    ] {
+        let outcome = apply_post_action(&source_path, &output, action);
+
+        assert!(outcome.error.is_some(), "{output} should be refused");
+        assert!(source.exists());
+    }
+    assert_eq!(
+        apply_post_action(&source_path, &source_path, PostAction::Keep).error,
+        None
+    );
+}
+
+#[test]
+fn apply_output_template_renames_task_from_probed_source() {
+    let file = FileItem::from_path("mkv", "/A/show.S01E01.mkv", 1);
+    let mut task = conversion_task_from_file(&file, "/tmp/frame-output");
+    task.config.video_codec = "libx265".to_string();
+    task.output_template = Some("{name}_{height}p_{vcodec}".to_string());
+    let probe = ProbeMetadata {
+        width: Some(1920),
+        height: Some(1080),
+        ..ProbeMetadata::default()
+    };
+
+    apply_output_template(&mut task, &probe).expect("template should expand");
+
+    assert_eq!(
+        task.output_name.as_deref(),
+        Some("show.S01E01_1080p_libx265")
+    );
+}
+
+#[test]
+fn apply_output_template_keeps_output_name_without_template() {
+    let file = FileItem::from_path("mkv", "/A/clip.mkv", 1);
+    let mut task = conversion_task_from_file(&file, "/tmp/frame-output");
+
+    apply_output_template(&mut task, &ProbeMetadata::default())
+        .expect("missing template should be a no-op");
+
+    assert_eq!(task.output_name.as_deref(), Some("clip_converted"));
+}
+
 #[test]
 fn ffmpeg_progress_uses_duration_line_before_time_line() {
     let mut duration = None;
@@ -344,6 +833,120 @@ fn ffmpeg_progress_prefers_trim_expected_duration() {
     assert_eq!(progress, Some(50.0));
 }
 
+#[test]
+fn ffmpeg_frame_progress_reads_frame_counter_from_progress_lines() {
+    assert_eq!(
+        ffmpeg_frame_progress_from_line("frame=  360 fps=60 time=00:00:05.00 speed=1x", 1440),
+        Some(25.0)
+    );
+    assert_eq!(
+        ffmpeg_frame_progress_from_line("frame=1500 time=00:01:02.00 speed=1x", 1440),
+        Some(100.0)
+    );
+    assert_eq!(
+        ffmpeg_frame_progress_from_line("[Parsed_idet_0] frame=12", 1440),
+        None
+    );
+}
+
+#[test]
+fn structured_progress_uses_frames_then_output_time() {
+    let file = FileItem::from_path("audio", "/A/podcast.wav", 1);
+    let mut task = conversion_task_from_file(&file, "/tmp/frame-output");
+    let probe = ProbeMetadata {
+        duration: Some("40.0".to_string()),
+        ..ProbeMetadata::default()
+    };
+    let block = FfmpegProgress {
+        frame: Some(360),
+        out_time_seconds: Some(10.0),
+        ..FfmpegProgress::default()
+    };
+
+    let by_frames = ProgressState::new(&task, Some(1440), &probe);
+    let by_time = ProgressState::new(&task, None, &probe);
+    task.config.end_time = Some("20".to_string());
+    let by_trim = ProgressState::new(&task, None, &probe);
+    let unknown = ProgressState::new(&task, None, &ProbeMetadata::default());
+
+    assert_eq!(structured_progress_percent(&block, &by_frames), Some(25.0));
+    assert_eq!(structured_progress_percent(&block, &by_time), Some(25.0));
+    assert_eq!(structured_progress_percent(&block, &by_trim), Some(50.0));
+    assert_eq!(
+        structured_progress_percent(&FfmpegProgress::default(), &unknown),
+        None
+    );
+}
+
+#[test]
+fn sources_of_unknown_length_have_no_progress_total() {
+    let file = FileItem::from_path("stream", "https://example.com/live/index.m3u8", 0);
+    let mut task = conversion_task_from_file(&file, "/tmp/frame-output");
+    let block = FfmpegProgress {
+        out_time_seconds: Some(10.0),
+        total_size: Some(4096),
+        ..FfmpegProgress::default()
+    };
+
+    let live = ProgressState::new(&task, None, &ProbeMetadata::default());
+    task.config.end_time = Some("20".to_string());
+    let trimmed = ProgressState::new(&task, None, &ProbeMetadata::default());
+
+    assert!(!live.has_total());
+    assert_eq!(structured_progress_percent(&block, &live), None);
+    assert_eq!(block.stats().written_bytes, Some(4096));
+    assert!(trimmed.has_total());
+}
+
+#[test]
+fn structured_progress_measures_against_summed_trim_segments() {
+    let file = FileItem::from_path("video", "/A/lecture.mp4", 1);
+    let mut task = conversion_task_from_file(&file, "/tmp/frame-output");
+    task.config.segments = vec![
+        frame_core::types::TrimSegment {
+            start: "0:00".to_string(),
+            end: "0:15".to_string(),
+        },
+        frame_core::types::TrimSegment {
+            start: "1:00".to_string(),
+            end: "1:25".to_string(),
+        },
+    ];
+    let probe = ProbeMetadata {
+        duration: Some("600.0".to_string()),
+        ..ProbeMetadata::default()
+    };
+    let block = FfmpegProgress {
+        out_time_seconds: Some(10.0),
+        ..FfmpegProgress::default()
+    };
+
+    let progress = ProgressState::new(&task, None, &probe);
+
+    assert_eq!(structured_progress_percent(&block, &progress), Some(25.0));
+}
+
+#[test]
+fn read_output_segments_splits_progress_and_carriage_return_lines() {
+    let mut segments = Vec::new();
+
+    read_output_segments(
+        &mut "frame=1\nprogress=continue\r\nsize=  1kB time=00:00:01.00\rtail".as_bytes(),
+        |segment| segments.push(segment),
+    )
+    .expect("in-memory output should read");
+
+    assert_eq!(
+        segments,
+        [
+            "frame=1",
+            "progress=continue",
+            "size=  1kB time=00:00:01.00",
+            "tail"
+        ]
+    );
+}
+
 #[test]
 fn controller_tracks_registered_process_pid() {
     let controller = ConversionProcessController::default();
@@ -459,7 +1062,16 @@ fn run_conversion_task_with_control_emits_cancelled_when_cancelled_before_valida
         file_path: "/definitely/missing.mov".to_string(),
         output_directory: "/tmp/frame-output".to_string(),
         output_name: None,
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
         config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
     };
     let mut events = Vec::new();
 
@@ -484,6 +1096,75 @@ fn run_conversion_batch_with_control_accepts_empty_batches() {
     assert!(events.is_empty());
 }
 
+#[test]
+fn run_conversion_batch_with_control_fails_dependents_of_failed_tasks() {
+    let controller = ConversionProcessController::default();
+    let parent = ConversionTask {
+        id: "remux".to_string(),
+        file_path: "/definitely/missing.mov".to_string(),
+        output_directory: "/tmp/frame-output".to_string(),
+        output_name: None,
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
+        config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
+    };
+    let dependent = ConversionTask {
+        id: "encode".to_string(),
+        file_path: DEPENDENCY_OUTPUT_PLACEHOLDER.to_string(),
+        depends_on: Some("remux".to_string()),
+        ..parent.clone()
+    };
+    let mut events = Vec::new();
+
+    let result = run_conversion_batch_with_control(vec![dependent, parent], &controller, |event| {
+        events.push(event);
+    });
+
+    assert!(result.is_ok());
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ConversionEvent::Error(payload)
+            if payload.id == "encode" && payload.error.contains("Dependency remux")
+    )));
+}
+
+#[test]
+fn run_conversion_batch_with_control_rejects_circular_dependencies() {
+    let controller = ConversionProcessController::default();
+    let first = ConversionTask {
+        id: "a".to_string(),
+        file_path: DEPENDENCY_OUTPUT_PLACEHOLDER.to_string(),
+        output_directory: "/tmp/frame-output".to_string(),
+        output_name: None,
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
+        config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: Some("b".to_string()),
+        preprobed: None,
+    };
+    let second = ConversionTask {
+        id: "b".to_string(),
+        depends_on: Some("a".to_string()),
+        ..first.clone()
+    };
+
+    let result = run_conversion_batch_with_control(vec![first, second], &controller, |_| {});
+
+    assert!(matches!(result, Err(ConversionError::InvalidInput(_))));
+}
+
 #[test]
 fn next_batch_launch_count_respects_live_concurrency_limit() {
     assert_eq!(next_batch_launch_count(5, 1, 2), 1);
@@ -504,7 +1185,16 @@ fn run_conversion_task_should_emit_completed_for_real_ffmpeg_job() {
         file_path: input.to_string_lossy().into_owned(),
         output_directory: sandbox.root.to_string_lossy().into_owned(),
         output_name: Some(output_name.to_string()),
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
         config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
     };
     let mut events = Vec::new();
 
@@ -518,6 +1208,16 @@ fn run_conversion_task_should_emit_completed_for_real_ffmpeg_job() {
             .any(|event| matches!(event, ConversionEvent::Started(_))),
         "runner should emit a Started event"
     );
+    assert!(
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ConversionEvent::Progress(payload) => Some(payload.phase),
+                _ => None,
+            })
+            .all(|phase| phase == Some(ConversionPhase::Encode)),
+        "runner progress should be tagged with the encode phase"
+    );
     assert!(
         events
             .iter()
@@ -527,6 +1227,402 @@ fn run_conversion_task_should_emit_completed_for_real_ffmpeg_job() {
     );
 }
 
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn run_conversion_task_writes_task_log_file_for_real_ffmpeg_job() {
+    let sandbox = ConversionRunnerSandbox::new("real-ffmpeg-log");
+    let input = sandbox.path("source.mp4");
+    let log_directory = sandbox.path("logs");
+    generate_runner_source(&input);
+    let task = ConversionTask {
+        id: "task-logged".to_string(),
+        file_path: input.to_string_lossy().into_owned(),
+        output_directory: sandbox.root.to_string_lossy().into_owned(),
+        output_name: Some("logged-output.mp4".to_string()),
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
+        config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
+    };
+    let controller = ConversionProcessController::default();
+    controller
+        .set_task_log_directory(Some(log_directory.clone()))
+        .expect("log directory should be set");
+
+    run_conversion_task_with_control(task, &controller, &mut |_| {})
+        .expect("real ffmpeg conversion should succeed");
+
+    let logs = list_task_logs(&log_directory).expect("task logs should be listed");
+    let contents = fs::read_to_string(&logs[0].path).expect("task log should be readable");
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].id, "task-logged");
+    assert_eq!(logs[0].status, TaskLogStatus::Completed);
+    assert!(
+        contents
+            .lines()
+            .skip_while(|line| line.starts_with("[FFMPEG] "))
+            .nth(1)
+            .is_some_and(|line| line.starts_with("[ARGS] ["))
+    );
+}
+
+#[test]
+fn task_log_exit_prefers_cancellation_over_exit_status() {
+    let exited = Ok(ExitStatus::default());
+    let wait_failed = Err(ConversionError::Worker("wait failed".to_string()));
+
+    assert_eq!(task_log_exit(&exited, false).0, TaskLogStatus::Completed);
+    assert_eq!(task_log_exit(&exited, true).0, TaskLogStatus::Cancelled);
+    assert_eq!(
+        task_log_exit(&wait_failed, false),
+        (
+            TaskLogStatus::Failed,
+            "Worker process error: wait failed".to_string()
+        )
+    );
+}
+
+#[test]
+fn analyze_interlacing_skips_ffmpeg_for_cancelled_analysis() {
+    let controller = ConversionProcessController::default();
+    controller
+        .cancel_task("analysis-1")
+        .expect("pending analysis should be cancellable");
+
+    let error = analyze_interlacing("analysis-1", "/missing/source.mov", &controller)
+        .expect_err("cancelled analysis should not run");
+
+    assert!(error.to_string().contains("cancelled"));
+    assert!(!controller.is_cancelled("analysis-1"));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn analyze_interlacing_should_report_progressive_real_source() {
+    let sandbox = ConversionRunnerSandbox::new("real-idet");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+
+    let analysis = analyze_interlacing("idet-real", &input.to_string_lossy(), &controller)
+        .expect("idet analysis should succeed");
+
+    assert!(analysis.progressive > 0);
+    assert!(!analysis.is_interlaced());
+    assert_eq!(controller.active_process_count().ok(), Some(0));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn analyze_loudness_should_measure_requested_real_audio_track() {
+    let sandbox = ConversionRunnerSandbox::new("real-ebur128");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+    let mut events = Vec::new();
+
+    let analysis = analyze_loudness(
+        "loudness-real",
+        &input.to_string_lossy(),
+        Some(1),
+        &controller,
+        |event| events.push(event),
+    )
+    .expect("ebur128 analysis should succeed");
+
+    assert!(analysis.integrated_lufs.is_finite());
+    assert!(events.iter().all(
+        |event| matches!(event, ConversionEvent::Progress(payload) if payload.id == "loudness-real" && payload.phase == Some(ConversionPhase::Analyze))
+    ));
+    assert!(
+        analyze_loudness(
+            "loudness-missing",
+            &input.to_string_lossy(),
+            Some(9),
+            &controller,
+            |_| {},
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn full_decode_timeout_scales_with_source_length() {
+    assert_eq!(full_decode_timeout(Some(10.0)), MEDIA_ANALYSIS_TIMEOUT);
+    assert_eq!(
+        full_decode_timeout(Some(2.0 * 60.0 * 60.0)),
+        std::time::Duration::from_secs(8 * 60 * 60)
+    );
+    assert_eq!(full_decode_timeout(None), FULL_DECODE_ANALYSIS_TIMEOUT);
+    assert_eq!(
+        full_decode_timeout(Some(f64::INFINITY)),
+        FULL_DECODE_ANALYSIS_TIMEOUT
+    );
+}
+
+#[test]
+fn analyze_scenes_rejects_out_of_range_threshold() {
+    let controller = ConversionProcessController::default();
+
+    let error = analyze_scenes("scenes-1", "/missing/source.mov", 1.5, &controller, |_| {})
+        .expect_err("threshold above 1 should be rejected");
+
+    assert!(matches!(error, ConversionError::InvalidInput(_)));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn analyze_scenes_should_report_sorted_real_cuts() {
+    let sandbox = ConversionRunnerSandbox::new("real-scenes");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+    let mut events = Vec::new();
+
+    let analysis = analyze_scenes(
+        "scenes-real",
+        &input.to_string_lossy(),
+        0.0,
+        &controller,
+        |event| events.push(event),
+    )
+    .expect("scene analysis should succeed");
+
+    assert!(!analysis.timestamps.is_empty());
+    assert!(analysis.timestamps.is_sorted());
+    assert!(events.iter().any(
+        |event| matches!(event, ConversionEvent::Progress(payload) if payload.progress >= 100.0)
+    ));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn preview_command_should_build_the_command_without_creating_the_output() {
+    let sandbox = ConversionRunnerSandbox::new("real-dry-run");
+    let input = sandbox.path("my clip.mp4");
+    generate_runner_source(&input);
+    let output_directory = sandbox.path("not created");
+    let task = ConversionTask {
+        id: "task-dry-run".to_string(),
+        file_path: input.to_string_lossy().into_owned(),
+        output_directory: output_directory.to_string_lossy().into_owned(),
+        output_name: Some("dry run".to_string()),
+        create_output_directory: true,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
+        config: core_config_from_gpui(&GpuiConversionConfig::default()),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
+    };
+
+    let preview = preview_command(task).expect("command should build");
+
+    assert!(preview.args.contains(&input.to_string_lossy().into_owned()));
+    assert!(
+        preview
+            .posix
+            .contains(&format!("'{}'", input.to_string_lossy()))
+    );
+    assert!(preview.powershell.starts_with("& "));
+    assert!(!output_directory.exists());
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn compare_quality_should_score_a_file_against_itself_as_identical() {
+    let sandbox = ConversionRunnerSandbox::new("real-quality");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let input = input.to_string_lossy();
+    let controller = ConversionProcessController::default();
+    let mut events = Vec::new();
+
+    let comparison = compare_quality(
+        "quality-real",
+        &input,
+        &input,
+        frame_core::quality::QualityMetric::Ssim,
+        &controller,
+        |event| events.push(event),
+    )
+    .expect("quality comparison should succeed");
+
+    assert!(comparison.frames > 0);
+    assert!((comparison.mean - 1.0).abs() < 1e-3);
+    assert!(comparison.min <= comparison.mean);
+    assert!(comparison.notes.is_empty());
+    assert!(events.iter().any(
+        |event| matches!(event, ConversionEvent::Progress(payload) if payload.progress >= 100.0)
+    ));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn analyze_bitrate_should_stream_packets_into_windows() {
+    let sandbox = ConversionRunnerSandbox::new("real-bitrate");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+    let mut events = Vec::new();
+
+    let analysis = analyze_bitrate(
+        "bitrate-real",
+        &input.to_string_lossy(),
+        0.5,
+        &controller,
+        |event| events.push(event),
+    )
+    .expect("bitrate analysis should succeed");
+
+    assert!(analysis.packets > 0);
+    assert!(!analysis.points.is_empty());
+    assert!(analysis.points[0].is_keyframe_present);
+    assert!(analysis.peak_kbps >= analysis.percentile_kbps);
+    assert!(events.iter().any(
+        |event| matches!(event, ConversionEvent::Progress(payload) if payload.progress >= 100.0)
+    ));
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn estimate_output_size_should_sample_encode_quality_outputs_and_clean_up() {
+    let sandbox = ConversionRunnerSandbox::new("real-size-estimate");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+    let config = core_config_from_gpui(&GpuiConversionConfig::default());
+
+    let estimate = estimate_output_size(
+        "estimate-real",
+        &input.to_string_lossy(),
+        &config,
+        &controller,
+    )
+    .expect("sample-based estimate should succeed");
+
+    assert_eq!(
+        estimate.method,
+        frame_core::estimate::SizeEstimateMethod::SampleEncode
+    );
+    assert!(estimate.low_bytes <= estimate.bytes && estimate.bytes <= estimate.high_bytes);
+    assert!(estimate.bytes > 0);
+    assert!(
+        !std::env::temp_dir()
+            .join(format!(
+                "frame-size-estimate-{}-estimate-real",
+                std::process::id()
+            ))
+            .exists()
+    );
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn calculate_bitrate_should_use_the_probed_duration() {
+    let sandbox = ConversionRunnerSandbox::new("real-bitrate-calculation");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let config = core_config_from_gpui(&GpuiConversionConfig::default());
+
+    let calculation = calculate_bitrate(
+        &input.to_string_lossy(),
+        &config,
+        frame_core::estimate::BitrateTarget::Bitrate { kbps: 2000.0 },
+    )
+    .expect("bitrate calculation should succeed");
+
+    assert!(calculation.duration_seconds > 0.0);
+    assert!(calculation.size_bytes > 0);
+    assert!((calculation.video_kbps - 2000.0).abs() < f64::EPSILON);
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn preview_conversion_should_encode_a_short_sample_until_previews_are_removed() {
+    let sandbox = ConversionRunnerSandbox::new("real-sample-preview");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+    let config = core_config_from_gpui(&GpuiConversionConfig::default());
+
+    let preview = preview_conversion(
+        "preview-real",
+        &input.to_string_lossy(),
+        &config,
+        None,
+        Some(0.5),
+        &controller,
+    )
+    .expect("preview should encode");
+
+    assert!((preview.duration_seconds - 0.5).abs() < f64::EPSILON);
+    assert!(preview.bytes > 0);
+    assert!(
+        preview
+            .estimated_full_size
+            .is_some_and(|estimate| estimate.bytes >= preview.bytes)
+    );
+    assert!(std::path::Path::new(&preview.path).exists());
+    remove_sample_previews();
+    assert!(!std::path::Path::new(&preview.path).exists());
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn generate_comparison_should_encode_a_split_wipe_clip() {
+    let sandbox = ConversionRunnerSandbox::new("real-comparison");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let input = input.to_string_lossy();
+    let controller = ConversionProcessController::default();
+    let mut events = Vec::new();
+
+    let path = generate_comparison(
+        "comparison-real",
+        &input,
+        &input,
+        None,
+        Some(0.5),
+        frame_core::comparison_clip::ComparisonLayout::SplitWipe,
+        &controller,
+        |event| events.push(event),
+    )
+    .expect("comparison clip should encode");
+
+    assert!(std::path::Path::new(&path).is_file());
+    assert!(events.iter().any(
+        |event| matches!(event, ConversionEvent::Progress(payload) if payload.progress >= 100.0)
+    ));
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn detect_crop_should_sample_real_source_within_frame_bounds() {
+    let sandbox = ConversionRunnerSandbox::new("real-cropdetect");
+    let input = sandbox.path("source.mp4");
+    generate_runner_source(&input);
+    let controller = ConversionProcessController::default();
+
+    let detection = detect_crop("crop-real", &input.to_string_lossy(), &controller)
+        .expect("cropdetect analysis should succeed");
+
+    assert_eq!((detection.source_width, detection.source_height), (64, 48));
+    assert!(detection.crop.x + detection.crop.width <= detection.source_width);
+    assert!(detection.crop.y + detection.crop.height <= detection.source_height);
+    assert_eq!(controller.active_process_count().ok(), Some(0));
+}
+
 #[test]
 #[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
 fn run_conversion_task_should_emit_completed_for_real_image_encoding_job() {
@@ -549,7 +1645,16 @@ fn run_conversion_task_should_emit_completed_for_real_image_encoding_job() {
         file_path: input.to_string_lossy().into_owned(),
         output_directory: sandbox.root.to_string_lossy().into_owned(),
         output_name: Some(output_name.to_string()),
+        create_output_directory: false,
+        relative_subpath: None,
+        scratch_directory: None,
+        output_template: None,
+        preserve_file_times: false,
+        post_action: PostAction::Keep,
         config: core_config_from_gpui(&config),
+        concat_inputs: Vec::new(),
+        depends_on: None,
+        preprobed: None,
     };
     let mut events = Vec::new();
 
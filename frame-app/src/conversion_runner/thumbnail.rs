@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use frame_core::{
+    analysis::showinfo_pts_time,
+    contact_sheet::{ContactSheetOptions, build_contact_sheet_args},
+    error::ConversionError,
+    events::{ConversionEvent, ConversionEventCoalescer},
+    failure::classify_conversion_failure,
+    image_sequence::{validate_sequence_output_directory, written_sequence_totals},
+    keyframes::{
+        ExtractedKeyframes, KeyframeExtractOptions, build_keyframe_extract_args,
+        keyframe_output_pattern, refined_keyframe_estimate,
+    },
+    thumbnail::{ExtractedFrame, FrameExtractOptions, build_frame_extract_args},
+};
+
+use super::{
+    ConversionProcessController, MEDIA_ANALYSIS_TIMEOUT,
+    analysis::{analysis_progress, is_analysis_cancelled, run_ffmpeg_analysis},
+    runner::{ffmpeg_path_arg, probe_media_file},
+};
+use crate::{capabilities::cached_ffmpeg_capabilities, runtime_binaries::ffmpeg_executable};
+
+/// Writes the frame of `file_path` at `timestamp` to `output_path` as a
+/// `format` still, scaled to `width` when given.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, the request is
+/// invalid, or `FFmpeg` fails or writes no frame.
+pub fn extract_frame(
+    file_path: &str,
+    timestamp: &str,
+    output_path: &str,
+    format: &str,
+    quality: u32,
+    width: Option<u32>,
+) -> Result<ExtractedFrame, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let options = FrameExtractOptions {
+        timestamp: timestamp.to_string(),
+        format: format.to_string(),
+        quality,
+        width,
+        tonemapping: cached_ffmpeg_capabilities()
+            .is_some_and(|capabilities| capabilities.flags.tonemapping),
+    };
+    let plan = build_frame_extract_args(
+        &ffmpeg_path_arg(file_path),
+        &ffmpeg_path_arg(output_path),
+        &options,
+        &probe,
+    )?;
+
+    let output = Command::new(ffmpeg_executable())
+        .args(&plan.args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(ConversionError::Io)?;
+    if !output.status.success() {
+        let lines: Vec<String> = String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        return Err(ConversionError::Failed(classify_conversion_failure(
+            &lines,
+            &output.status.to_string(),
+            false,
+        )));
+    }
+    if !Path::new(output_path).is_file() {
+        return Err(ConversionError::Worker(
+            "ffmpeg finished without writing a frame".to_string(),
+        ));
+    }
+
+    Ok(ExtractedFrame {
+        output_path: output_path.to_string(),
+        seconds: plan.seconds,
+        notes: plan.notes,
+    })
+}
+
+/// Writes a `columns` by `rows` contact sheet of `file_path` to
+/// `output_path`, with cells `cell_width` pixels wide and labelled with their
+/// timestamps when the `FFmpeg` build can draw text. Returns the sheet path.
+///
+/// Progress counts the sampled frames and is emitted as
+/// [`ConversionEvent::Progress`] events under `sheet_id`, which also
+/// identifies the pass for cancellation. Notes about the sheet are emitted as
+/// log lines.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, the layout is
+/// invalid, or `FFmpeg` fails, is cancelled, exceeds
+/// [`MEDIA_ANALYSIS_TIMEOUT`], or writes no sheet.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "mirrors the other analysis commands, which take the id, controller and emitter"
+)]
+pub fn generate_contact_sheet(
+    sheet_id: &str,
+    file_path: &str,
+    columns: u32,
+    rows: u32,
+    cell_width: u32,
+    output_path: &str,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<String, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    let options = ContactSheetOptions {
+        columns,
+        rows,
+        cell_width,
+        labels: cached_ffmpeg_capabilities()
+            .is_none_or(|capabilities| capabilities.has_filter("drawtext")),
+    };
+    let plan = build_contact_sheet_args(
+        &ffmpeg_path_arg(file_path),
+        &ffmpeg_path_arg(output_path),
+        options,
+        &probe,
+    )?;
+    for note in &plan.notes {
+        emit(ConversionEvent::log(sheet_id, format!("[WARN] {note}")));
+    }
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut sampled = 0_u32;
+    emit(analysis_progress(sheet_id, 0.0));
+    let result = run_ffmpeg_analysis(
+        sheet_id,
+        &plan.args,
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |line| {
+            if showinfo_pts_time(line).is_some() {
+                sampled += 1;
+                let progress = f64::from(sampled) / f64::from(plan.frames) * 100.0;
+                coalescer.push(
+                    analysis_progress(sheet_id, progress.min(100.0)),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    result?;
+    if !Path::new(output_path).is_file() {
+        return Err(ConversionError::Worker(
+            "ffmpeg finished without writing a contact sheet".to_string(),
+        ));
+    }
+
+    emit(analysis_progress(sheet_id, 100.0));
+    Ok(output_path.to_string())
+}
+
+/// Writes every keyframe of `file_path` into `output_dir` as `format`
+/// stills named by presentation time, taking every Nth keyframe when the
+/// source likely holds more than `max_count`.
+///
+/// `output_dir` is created when missing and must otherwise be empty.
+/// Progress is emitted as [`ConversionEvent::Progress`] events under
+/// `extract_id`, against a keyframe count estimated from the duration and
+/// refined as frames arrive; `extract_id` also identifies the pass for
+/// cancellation. A cancelled pass keeps its frames and reports them as
+/// incomplete, while a failed pass leaves the folder as it found it.
+///
+/// # Errors
+///
+/// Returns an error when the source cannot be probed, the request is
+/// invalid, `output_dir` cannot be used, or `FFmpeg` fails or exceeds
+/// [`MEDIA_ANALYSIS_TIMEOUT`].
+#[expect(
+    clippy::too_many_arguments,
+    reason = "mirrors the other analysis commands, which take the id, controller and emitter"
+)]
+pub fn extract_keyframes(
+    extract_id: &str,
+    file_path: &str,
+    output_dir: &str,
+    format: &str,
+    max_count: Option<u32>,
+    controller: &ConversionProcessController,
+    mut emit: impl FnMut(ConversionEvent),
+) -> Result<ExtractedKeyframes, ConversionError> {
+    let probe = probe_media_file(file_path)?;
+    validate_sequence_output_directory(output_dir)?;
+    let stem = Path::new(file_path)
+        .file_stem()
+        .map_or_else(|| "keyframe".into(), |stem| stem.to_string_lossy());
+    let pattern = keyframe_output_pattern(output_dir, &stem, format);
+    let options = KeyframeExtractOptions {
+        format: format.to_string(),
+        max_count,
+    };
+    let plan = build_keyframe_extract_args(
+        &ffmpeg_path_arg(file_path),
+        &ffmpeg_path_arg(&pattern),
+        &options,
+        &probe,
+    )?;
+    if plan.sample_every > 1 {
+        emit(ConversionEvent::log(
+            extract_id,
+            format!(
+                "[INFO] Taking one keyframe in every {} to stay within {} files",
+                plan.sample_every,
+                max_count.unwrap_or_default()
+            ),
+        ));
+    }
+    let created_dir = !Path::new(output_dir).exists();
+    fs::create_dir_all(output_dir).map_err(ConversionError::Io)?;
+
+    let duration = probe.duration_seconds().unwrap_or_default();
+    let mut coalescer = ConversionEventCoalescer::new();
+    let mut written = 0_u64;
+    emit(analysis_progress(extract_id, 0.0));
+    let result = run_ffmpeg_analysis(
+        extract_id,
+        &plan.args,
+        controller,
+        MEDIA_ANALYSIS_TIMEOUT,
+        &mut |line| {
+            if let Some(seconds) = showinfo_pts_time(line) {
+                written += 1;
+                let total = refined_keyframe_estimate(
+                    plan.estimated_frames,
+                    written,
+                    seconds,
+                    duration,
+                    max_count,
+                );
+                #[expect(
+                    clippy::cast_precision_loss,
+                    reason = "frame counts stay far below 2^52"
+                )]
+                let progress = written as f64 / total as f64 * 100.0;
+                coalescer.push(
+                    analysis_progress(extract_id, progress.min(99.0)),
+                    Instant::now(),
+                    &mut emit,
+                );
+            }
+        },
+    );
+    coalescer.flush(&mut emit);
+    let complete = match result {
+        Ok(_) => true,
+        Err(error) if is_analysis_cancelled(&error, extract_id) => false,
+        Err(error) => {
+            let _ = fs::remove_dir_all(output_dir);
+            if !created_dir {
+                let _ = fs::create_dir(output_dir);
+            }
+            return Err(error);
+        }
+    };
+
+    let (frames, bytes) =
+        written_sequence_totals(Path::new(output_dir)).map_err(ConversionError::Io)?;
+    if complete {
+        emit(analysis_progress(extract_id, 100.0));
+    }
+    Ok(ExtractedKeyframes {
+        output_directory: output_dir.to_string(),
+        frames,
+        bytes,
+        complete,
+    })
+}
@@ -0,0 +1,194 @@
+//! Output verification: once the encoder exits cleanly, the partial output is
+//! decoded in full and its length compared with the expected one before the
+//! task counts as completed.
+
+use std::time::{Duration, Instant};
+
+use frame_core::{
+    error::ConversionError,
+    events::{ConversionEvent, ConversionEventCoalescer},
+    progress::FfmpegProgressParser,
+    task_log_file::{TaskLogFile, TaskLogStatus},
+    types::{ConversionPhase, ConversionTask},
+    verify::{
+        OutputVerification, is_decode_error_line, output_verification_args, suspect_output_path,
+    },
+};
+
+use super::{
+    analysis::{is_analysis_cancelled, run_ffmpeg_analysis},
+    controller::ConversionProcessController,
+    runner::{PartialOutput, ffmpeg_path_arg, probe_media_file},
+};
+
+/// Decodes every file of `partial_output` when the task asks for
+/// verification. Frame folders are not verified.
+///
+/// Progress and log lines are emitted in the [`ConversionPhase::Verify`]
+/// phase, and the outcome is appended to the task's log file. Returns the
+/// analysis cancellation error when the task is cancelled meanwhile.
+pub(super) fn verify_task_output(
+    task: &ConversionTask,
+    partial_output: &PartialOutput,
+    expected_duration: Option<f64>,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<Option<OutputVerification>, ConversionError> {
+    if !task.config.verify_output {
+        return Ok(None);
+    }
+    let mut log = VerifyLog {
+        task_id: &task.id,
+        file: controller
+            .task_log_directory()?
+            .and_then(|directory| TaskLogFile::reopen(&directory, &task.id).ok()),
+    };
+    if partial_output.is_frame_folder() {
+        log.push("[INFO] Frame folder outputs are not verified", emit);
+        log.finish(TaskLogStatus::Completed, "verification skipped");
+        return Ok(None);
+    }
+
+    log.push("[INFO] Verifying the output", emit);
+    let result = decode_partial_files(task, partial_output, expected_duration, controller, emit);
+    let (status, detail) = match &result {
+        Ok(verification) if verification.passed() => {
+            log.push(
+                format!(
+                    "[INFO] Verified the output: {}",
+                    verification.summary_line()
+                ),
+                emit,
+            );
+            (TaskLogStatus::Completed, "output verified".to_string())
+        }
+        Ok(verification) => {
+            log.push(
+                format!(
+                    "[WARN] Output failed verification: {}",
+                    verification.summary_line()
+                ),
+                emit,
+            );
+            for sample in &verification.error_samples {
+                log.push(format!("[WARN] {sample}"), emit);
+            }
+            (
+                TaskLogStatus::Failed,
+                "output failed verification".to_string(),
+            )
+        }
+        Err(error) if is_analysis_cancelled(error, &task.id) => (
+            TaskLogStatus::Cancelled,
+            "cancelled during verification".to_string(),
+        ),
+        Err(error) => (TaskLogStatus::Failed, error.to_string()),
+    };
+    log.finish(status, &detail);
+    result.map(Some)
+}
+
+/// Verification lines, emitted as events and appended to the task's log
+/// file when it has one.
+struct VerifyLog<'a> {
+    task_id: &'a str,
+    file: Option<TaskLogFile>,
+}
+
+impl VerifyLog<'_> {
+    fn push(&mut self, line: impl Into<String>, emit: &mut impl FnMut(ConversionEvent)) {
+        let line = line.into();
+        if let Some(file) = &mut self.file {
+            let _ = file.write_line(&line);
+        }
+        emit(ConversionEvent::log(self.task_id, line).with_phase(ConversionPhase::Verify));
+    }
+
+    /// Writes the exit line that supersedes the encoder's.
+    fn finish(self, status: TaskLogStatus, detail: &str) {
+        if let Some(file) = self.file {
+            let _ = file.finish(status, detail);
+        }
+    }
+}
+
+/// Keeps the files of an output that failed verification under their final
+/// names with the suspect suffix, and returns the task's failure.
+pub(super) fn reject_unverified_output(
+    partial_output: PartialOutput,
+    verification: &OutputVerification,
+) -> ConversionError {
+    match partial_output.keep_as(suspect_output_path) {
+        Ok(suspect_paths) => ConversionError::Failed(verification.failure(&suspect_paths)),
+        Err(error) => error,
+    }
+}
+
+fn decode_partial_files(
+    task: &ConversionTask,
+    partial_output: &PartialOutput,
+    expected_duration: Option<f64>,
+    controller: &ConversionProcessController,
+    emit: &mut impl FnMut(ConversionEvent),
+) -> Result<OutputVerification, ConversionError> {
+    let mut verification = OutputVerification::default();
+    let paths = partial_output.partial_paths();
+    let mut durations = Vec::with_capacity(paths.len());
+    for path in &paths {
+        match probe_media_file(path) {
+            Ok(probe) => durations.push(probe.duration_seconds()),
+            Err(error) => {
+                verification.record_error(&error.to_string());
+                durations.push(None);
+            }
+        }
+    }
+    let output_duration = durations.iter().copied().sum::<Option<f64>>();
+    verification.set_durations(expected_duration, output_duration);
+
+    let mut coalescer = ConversionEventCoalescer::new();
+    emit(verify_progress(&task.id, 0.0));
+    let mut decoded_seconds = 0.0;
+    for (path, duration) in paths.iter().zip(durations) {
+        let mut parser = FfmpegProgressParser::default();
+        // The decode is bounded by the output itself, so only cancellation
+        // stops it early.
+        let result = run_ffmpeg_analysis(
+            &task.id,
+            &output_verification_args(&ffmpeg_path_arg(path)),
+            controller,
+            Duration::MAX,
+            &mut |line| {
+                if is_decode_error_line(line) {
+                    verification.record_error(line);
+                } else if let (Some(block), Some(total)) = (parser.push_line(line), output_duration)
+                    && let Some(seconds) = block.out_time_seconds
+                {
+                    let progress = ((decoded_seconds + seconds) / total * 100.0).clamp(0.0, 100.0);
+                    coalescer.push(
+                        verify_progress(&task.id, progress),
+                        Instant::now(),
+                        &mut *emit,
+                    );
+                }
+            },
+        );
+        coalescer.flush(&mut *emit);
+        match result {
+            Err(error) if is_analysis_cancelled(&error, &task.id) => return Err(error),
+            // A decode that exits with an error has already printed it;
+            // anything else is recorded so the output cannot pass.
+            Err(error) if verification.decode_errors == 0 => {
+                verification.record_error(&error.to_string());
+            }
+            Err(_) | Ok(_) => {}
+        }
+        decoded_seconds += duration.unwrap_or(0.0);
+    }
+    emit(verify_progress(&task.id, 100.0));
+    Ok(verification)
+}
+
+fn verify_progress(task_id: &str, progress: f64) -> ConversionEvent {
+    ConversionEvent::progress(task_id, progress).with_phase(ConversionPhase::Verify)
+}
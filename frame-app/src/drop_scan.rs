@@ -0,0 +1,330 @@
+//! Scans dropped files and folders before they are queued, so a folder drop
+//! is confirmed against a summary of what it holds.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use frame_core::{error::ConversionError, types::PreprobedSource};
+
+use crate::{
+    file_filters::{SkipReason, SkippedSource, SourceScanReport, discover_dropped_sources},
+    file_queue::format_file_size,
+    settings::format_source_duration,
+    source_metadata::probe_source,
+};
+
+/// Most `FFprobe` processes a drop scan runs at once.
+pub const DROP_SCAN_PROBE_CONCURRENCY: usize = 4;
+/// Skipped entries named in the confirmation before the rest are counted.
+const LISTED_SKIPPED_ENTRIES: usize = 5;
+
+/// A playable file found by a drop scan, with the probe reused when it is
+/// queued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DroppedSource {
+    pub path: PathBuf,
+    pub relative_subpath: Option<String>,
+    pub preprobed: PreprobedSource,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DropScanSummary {
+    pub sources: Vec<DroppedSource>,
+    pub skipped: Vec<SkippedSource>,
+    pub report: SourceScanReport,
+    pub total_size_bytes: u64,
+    pub total_duration_seconds: f64,
+}
+
+impl DropScanSummary {
+    #[must_use]
+    pub fn title(&self) -> String {
+        match self.sources.len() {
+            0 => "No playable files found".to_string(),
+            1 => "Add 1 file?".to_string(),
+            count => format!("Add {count} files?"),
+        }
+    }
+
+    /// Size and running time of the playable files, followed by each
+    /// skipped entry and why it was skipped.
+    #[must_use]
+    pub fn detail(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.sources.is_empty() {
+            let size = format_file_size(self.total_size_bytes);
+            if self.total_duration_seconds > 0.0 {
+                let duration =
+                    format_source_duration(Some(&self.total_duration_seconds.to_string()));
+                lines.push(format!("{size} · {duration} total"));
+            } else {
+                lines.push(size);
+            }
+        }
+        if let Some(summary) = self.report.summary() {
+            lines.push(format!("{summary}:"));
+        }
+        for skipped in self.skipped.iter().take(LISTED_SKIPPED_ENTRIES) {
+            let name = skipped.path.file_name().map_or_else(
+                || skipped.path.to_string_lossy(),
+                |name| name.to_string_lossy(),
+            );
+            lines.push(format!("{name} — {}", skipped.reason.label()));
+        }
+        if self.skipped.len() > LISTED_SKIPPED_ENTRIES {
+            lines.push(format!(
+                "and {} more",
+                self.skipped.len() - LISTED_SKIPPED_ENTRIES
+            ));
+        }
+        if self.report.truncated {
+            lines.push("Only the first files found were scanned.".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Expands dropped paths into supported sources, probes them in parallel
+/// and summarizes the playable ones alongside everything skipped.
+#[must_use]
+pub fn scan_dropped_paths(paths: Vec<PathBuf>, recursive: bool) -> DropScanSummary {
+    scan_dropped_paths_with(paths, recursive, DROP_SCAN_PROBE_CONCURRENCY, probe_source)
+}
+
+/// [`scan_dropped_paths`] with the probe and its concurrency supplied.
+#[must_use]
+pub fn scan_dropped_paths_with<F>(
+    paths: Vec<PathBuf>,
+    recursive: bool,
+    concurrency: usize,
+    probe: F,
+) -> DropScanSummary
+where
+    F: Fn(&str) -> Result<PreprobedSource, ConversionError> + Sync,
+{
+    let mut scan = discover_dropped_sources(paths, recursive);
+    let candidates = std::mem::take(&mut scan.sources);
+    let candidate_paths = candidates
+        .iter()
+        .map(|source| source.path.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let probes = probe_in_parallel(&candidate_paths, concurrency, &probe);
+
+    let mut summary = DropScanSummary::default();
+    for (source, probe) in candidates.into_iter().zip(probes) {
+        match probe {
+            Ok(preprobed) => {
+                summary.total_size_bytes += preprobed.fingerprint.size_bytes;
+                summary.total_duration_seconds +=
+                    preprobed.metadata.duration_seconds().unwrap_or_default();
+                summary.sources.push(DroppedSource {
+                    path: source.path,
+                    relative_subpath: source.relative_subpath,
+                    preprobed,
+                });
+            }
+            Err(error) => scan.skip(source.path, SkipReason::NotPlayable(error.to_string())),
+        }
+    }
+    summary.skipped = scan.skipped;
+    summary.report = scan.report;
+    summary
+}
+
+/// Runs `probe` over `paths` on up to `concurrency` threads, returning the
+/// results in the order of `paths`.
+fn probe_in_parallel<F>(
+    paths: &[String],
+    concurrency: usize,
+    probe: &F,
+) -> Vec<Result<PreprobedSource, ConversionError>>
+where
+    F: Fn(&str) -> Result<PreprobedSource, ConversionError> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(paths.len()));
+    thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, paths.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let result = probe(path);
+                    results
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use frame_core::types::{ProbeMetadata, SourceFingerprint};
+
+    use super::*;
+
+    fn probed(size_bytes: u64, duration: Option<&str>) -> PreprobedSource {
+        PreprobedSource {
+            metadata: ProbeMetadata {
+                duration: duration.map(str::to_string),
+                ..ProbeMetadata::default()
+            },
+            fingerprint: SourceFingerprint {
+                size_bytes,
+                modified: None,
+            },
+        }
+    }
+
+    fn unique_test_dir(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+
+        std::env::temp_dir().join(format!(
+            "frame-drop-scan-{name}-{}-{nanos}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn summary_totals_playable_files_and_reports_the_rest() {
+        let root = unique_test_dir("summary");
+        let season = root.join("season 1");
+        std::fs::create_dir_all(&season).expect("test media directory should be created");
+        for name in ["a.mp4", "broken.mkv", "notes.txt", ".b.mp4"] {
+            std::fs::write(root.join(name), b"").expect("test file should be written");
+        }
+        std::fs::write(season.join("e01.mov"), b"").expect("test video should be written");
+        std::fs::write(season.join("e01_converted.mp4"), b"")
+            .expect("test output should be written");
+
+        let summary = scan_dropped_paths_with(vec![root.clone()], true, 2, |path| {
+            if path.ends_with("broken.mkv") {
+                Err(ConversionError::InvalidInput(
+                    "moov atom not found".to_string(),
+                ))
+            } else if path.ends_with("a.mp4") {
+                Ok(probed(1024, Some("90.5")))
+            } else {
+                Ok(probed(2048, Some("30")))
+            }
+        });
+
+        std::fs::remove_dir_all(&root).expect("test media directory should be removed");
+        assert_eq!(
+            summary
+                .sources
+                .iter()
+                .map(|source| (source.path.clone(), source.relative_subpath.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (root.join("a.mp4"), None),
+                (season.join("e01.mov"), Some("season 1".to_string())),
+            ]
+        );
+        assert_eq!(summary.total_size_bytes, 3072);
+        assert!((summary.total_duration_seconds - 120.5).abs() < f64::EPSILON);
+        assert_eq!(
+            summary
+                .skipped
+                .iter()
+                .map(|skipped| skipped.path.clone())
+                .collect::<Vec<_>>(),
+            [
+                root.join(".b.mp4"),
+                root.join("notes.txt"),
+                season.join("e01_converted.mp4"),
+                root.join("broken.mkv"),
+            ]
+        );
+        assert!(matches!(
+            &summary.skipped[3].reason,
+            SkipReason::NotPlayable(error) if error.contains("moov atom")
+        ));
+        assert_eq!(summary.title(), "Add 2 files?");
+        assert_eq!(
+            summary.detail().lines().next(),
+            Some("3 KB · 00:02:00 total")
+        );
+    }
+
+    #[test]
+    fn probes_never_exceed_the_concurrency_cap() {
+        let paths = (0..12)
+            .map(|index| format!("/tmp/{index}.mp4"))
+            .collect::<Vec<_>>();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let results = probe_in_parallel(&paths, 3, &|path: &str| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+            let index = path
+                .trim_start_matches("/tmp/")
+                .trim_end_matches(".mp4")
+                .parse::<u64>()
+                .expect("test path should hold its index");
+            Ok(probed(index, None))
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert_eq!(
+            results
+                .into_iter()
+                .map(|result| result.expect("probe should succeed").fingerprint.size_bytes)
+                .collect::<Vec<_>>(),
+            (0..12).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn an_empty_drop_asks_nothing_and_lists_why() {
+        let summary = DropScanSummary {
+            skipped: (0..7)
+                .map(|index| SkippedSource {
+                    path: PathBuf::from(format!("/tmp/{index}.txt")),
+                    reason: SkipReason::Unsupported,
+                })
+                .collect(),
+            report: SourceScanReport {
+                unsupported_files: 7,
+                ..SourceScanReport::default()
+            },
+            ..DropScanSummary::default()
+        };
+
+        assert_eq!(summary.title(), "No playable files found");
+        assert_eq!(
+            summary.detail().lines().collect::<Vec<_>>(),
+            [
+                "Skipped 7 unsupported files:",
+                "0.txt — not a supported media file",
+                "1.txt — not a supported media file",
+                "2.txt — not a supported media file",
+                "3.txt — not a supported media file",
+                "4.txt — not a supported media file",
+                "and 2 more",
+            ]
+        );
+    }
+}
@@ -1,19 +1,149 @@
 //! File extension filters for native source and subtitle pickers.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::watch_folders::is_converted_output_path;
 
 pub const VIDEO_FILE_EXTENSIONS: &[&str] = &["mp4", "mov", "mkv", "avi", "webm", "gif"];
 pub const AUDIO_FILE_EXTENSIONS: &[&str] = &["mp3", "m4a", "wav", "flac"];
 pub const IMAGE_FILE_EXTENSIONS: &[&str] = &[
-    "png", "jpg", "jpeg", "webp", "bmp", "tif", "tiff", "avif", "heic", "heif",
+    "png", "jpg", "jpeg", "webp", "bmp", "tif", "tiff", "avif", "heic", "heif", "hif", "dng",
 ];
 pub const SOURCE_FILE_EXTENSIONS: &[&str] = &[
     "mp4", "mov", "mkv", "avi", "webm", "gif", "mp3", "m4a", "wav", "flac", "png", "jpg", "jpeg",
-    "webp", "bmp", "tif", "tiff", "avif", "heic", "heif",
+    "webp", "bmp", "tif", "tiff", "avif", "heic", "heif", "hif", "dng",
 ];
 
 pub const SUBTITLE_FILE_EXTENSIONS: &[&str] = &["srt", "ass", "vtt"];
 
+/// Most sources one import collects, so a huge tree cannot stall it.
+pub const MAX_SCANNED_SOURCE_FILES: usize = 10_000;
+/// Deepest folder level a scan descends to below the folder it starts at.
+pub const MAX_SCAN_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScannedSource {
+    pub path: PathBuf,
+    /// Folder holding the file relative to the scanned root, `/`-separated;
+    /// `None` for files at the root or imported directly.
+    pub relative_subpath: Option<String>,
+}
+
+/// Why a scan passed over an entry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    Unsupported,
+    /// A dot-file or folder, or one marked hidden on Windows.
+    Hidden,
+    /// A `_converted` output Frame wrote earlier.
+    ConvertedOutput,
+    Unreadable(String),
+    SymlinkLoop,
+    /// A folder more than [`MAX_SCAN_DEPTH`] levels down.
+    TooDeep,
+    /// `FFprobe` could not read the file.
+    NotPlayable(String),
+}
+
+impl SkipReason {
+    /// Short explanation shown next to the skipped path.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            Self::Unsupported => "not a supported media file".to_string(),
+            Self::Hidden => "hidden".to_string(),
+            Self::ConvertedOutput => "an earlier Frame output".to_string(),
+            Self::Unreadable(error) => format!("could not be read: {error}"),
+            Self::SymlinkLoop => "links back to a scanned folder".to_string(),
+            Self::TooDeep => format!("more than {MAX_SCAN_DEPTH} folders deep"),
+            Self::NotPlayable(error) => format!("not playable: {error}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedSource {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// Entries a scan passed over, counted by reason.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SourceScanReport {
+    pub unsupported_files: usize,
+    pub unreadable_entries: usize,
+    pub symlink_loops: usize,
+    pub hidden_entries: usize,
+    pub converted_outputs: usize,
+    pub too_deep_folders: usize,
+    pub unplayable_files: usize,
+    /// The scan stopped at [`MAX_SCANNED_SOURCE_FILES`].
+    pub truncated: bool,
+}
+
+impl SourceScanReport {
+    /// One-line summary of skipped entries, or `None` when nothing was
+    /// skipped.
+    #[must_use]
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.unsupported_files > 0 {
+            parts.push(format!("{} unsupported files", self.unsupported_files));
+        }
+        if self.unreadable_entries > 0 {
+            parts.push(format!("{} unreadable entries", self.unreadable_entries));
+        }
+        if self.symlink_loops > 0 {
+            parts.push(format!("{} symlink loops", self.symlink_loops));
+        }
+        if self.hidden_entries > 0 {
+            parts.push(format!("{} hidden entries", self.hidden_entries));
+        }
+        if self.converted_outputs > 0 {
+            parts.push(format!("{} earlier outputs", self.converted_outputs));
+        }
+        if self.too_deep_folders > 0 {
+            parts.push(format!("{} folders too deep", self.too_deep_folders));
+        }
+        if self.unplayable_files > 0 {
+            parts.push(format!("{} unplayable files", self.unplayable_files));
+        }
+        if self.truncated {
+            parts.push(format!("stopped after {MAX_SCANNED_SOURCE_FILES} files"));
+        }
+        (!parts.is_empty()).then(|| format!("Skipped {}", parts.join(", ")))
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceScan {
+    pub sources: Vec<ScannedSource>,
+    pub report: SourceScanReport,
+    /// Every entry counted in `report`, with its reason.
+    pub skipped: Vec<SkippedSource>,
+}
+
+impl SourceScan {
+    /// Records an entry passed over for `reason`.
+    pub fn skip(&mut self, path: PathBuf, reason: SkipReason) {
+        let report = &mut self.report;
+        match reason {
+            SkipReason::Unsupported => report.unsupported_files += 1,
+            SkipReason::Hidden => report.hidden_entries += 1,
+            SkipReason::ConvertedOutput => report.converted_outputs += 1,
+            SkipReason::Unreadable(_) => report.unreadable_entries += 1,
+            SkipReason::SymlinkLoop => report.symlink_loops += 1,
+            SkipReason::TooDeep => report.too_deep_folders += 1,
+            SkipReason::NotPlayable(_) => report.unplayable_files += 1,
+        }
+        self.skipped.push(SkippedSource { path, reason });
+    }
+}
+
 #[must_use]
 pub fn is_supported_source_path(path: &Path) -> bool {
     path_has_extension(path, SOURCE_FILE_EXTENSIONS)
@@ -39,46 +169,204 @@ pub fn filter_supported_source_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
 
 #[must_use]
 pub fn discover_supported_source_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
-    paths
+    discover_supported_sources(paths)
+        .sources
         .into_iter()
-        .flat_map(discover_supported_source_path)
+        .map(|source| source.path)
         .collect()
 }
 
-fn discover_supported_source_path(path: PathBuf) -> Vec<PathBuf> {
-    if path.is_dir() {
-        return supported_source_paths_in_directory(&path);
+/// Expands dropped or picked paths into supported sources, scanning
+/// directories recursively and recording each file's folder relative to
+/// the directory it was found under.
+#[must_use]
+pub fn discover_supported_sources(paths: Vec<PathBuf>) -> SourceScan {
+    discover_sources(paths, false, true)
+}
+
+/// Like [`discover_supported_sources`], scanning only the top level of
+/// folders unless `recursive` is set.
+#[must_use]
+pub fn discover_dropped_sources(paths: Vec<PathBuf>, recursive: bool) -> SourceScan {
+    discover_sources(paths, false, recursive)
+}
+
+/// Like [`discover_supported_sources`], but keeps files named directly in
+/// `paths` whatever their extension, as picked through a dialog's "All
+/// Files" filter. Folders are still scanned for supported extensions.
+#[must_use]
+pub fn discover_picked_sources(paths: Vec<PathBuf>) -> SourceScan {
+    discover_sources(paths, true, true)
+}
+
+fn discover_sources(paths: Vec<PathBuf>, keep_unlisted_files: bool, recursive: bool) -> SourceScan {
+    let mut scan = SourceScan::default();
+    for path in paths {
+        if scan.report.truncated {
+            break;
+        }
+        if path.is_dir() {
+            scan_folder_into(&path, recursive, SOURCE_FILE_EXTENSIONS, &mut scan);
+        } else if keep_unlisted_files || is_supported_source_path(&path) {
+            push_scanned_source(&mut scan, path, None);
+        } else {
+            scan.skip(path, SkipReason::Unsupported);
+        }
     }
+    scan
+}
 
-    filter_supported_source_paths(vec![path])
+/// Walks `root` in path order and collects files whose extension is in
+/// `extensions`, with their folder relative to `root`.
+///
+/// Symlinked folders are followed once. Links back into a folder already
+/// visited, folders below [`MAX_SCAN_DEPTH`], hidden entries, earlier
+/// `_converted` outputs, unreadable entries and other files are skipped
+/// and reported. The scan stops at [`MAX_SCANNED_SOURCE_FILES`].
+#[must_use]
+pub fn scan_folder(root: &Path, recursive: bool, extensions: &[&str]) -> SourceScan {
+    let mut scan = SourceScan::default();
+    scan_folder_into(root, recursive, extensions, &mut scan);
+    scan
 }
 
-fn supported_source_paths_in_directory(root: &Path) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    collect_supported_source_paths_in_directory(root, &mut paths);
-    paths.sort();
-    paths
+/// Folder a walk is in, relative to the folder it started at.
+struct WalkLevel<'a> {
+    relative_subpath: Option<&'a str>,
+    depth: usize,
 }
 
-fn collect_supported_source_paths_in_directory(root: &Path, paths: &mut Vec<PathBuf>) {
-    let Ok(entries) = std::fs::read_dir(root) else {
-        return;
+fn scan_folder_into(root: &Path, recursive: bool, extensions: &[&str], scan: &mut SourceScan) {
+    let canonical_root = match fs::canonicalize(root) {
+        Ok(canonical_root) => canonical_root,
+        Err(error) => {
+            scan.skip(
+                root.to_path_buf(),
+                SkipReason::Unreadable(error.to_string()),
+            );
+            return;
+        }
+    };
+    let mut visited = HashSet::from([canonical_root]);
+    let root_level = WalkLevel {
+        relative_subpath: None,
+        depth: 0,
     };
+    walk_folder(root, &root_level, recursive, extensions, &mut visited, scan);
+}
 
-    for entry in entries.filter_map(Result::ok) {
-        let path = entry.path();
-        let Ok(file_type) = entry.file_type() else {
-            continue;
+fn walk_folder(
+    folder: &Path,
+    level: &WalkLevel<'_>,
+    recursive: bool,
+    extensions: &[&str],
+    visited: &mut HashSet<PathBuf>,
+    scan: &mut SourceScan,
+) {
+    let entries = match fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(error) => {
+            scan.skip(
+                folder.to_path_buf(),
+                SkipReason::Unreadable(error.to_string()),
+            );
+            return;
+        }
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(error) => scan.skip(
+                folder.to_path_buf(),
+                SkipReason::Unreadable(error.to_string()),
+            ),
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        if scan.report.truncated {
+            return;
+        }
+        // Follows symlinks so linked folders and files are scanned too.
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                scan.skip(path, SkipReason::Unreadable(error.to_string()));
+                continue;
+            }
         };
+        if metadata.is_dir() && !recursive {
+            continue;
+        }
+        if is_hidden_entry(&path, &metadata) {
+            scan.skip(path, SkipReason::Hidden);
+            continue;
+        }
 
-        if file_type.is_dir() {
-            collect_supported_source_paths_in_directory(&path, paths);
-        } else if file_type.is_file() && is_supported_source_path(&path) {
-            paths.push(path);
+        if metadata.is_dir() {
+            if level.depth >= MAX_SCAN_DEPTH {
+                scan.skip(path, SkipReason::TooDeep);
+                continue;
+            }
+            let canonical = match fs::canonicalize(&path) {
+                Ok(canonical) => canonical,
+                Err(error) => {
+                    scan.skip(path, SkipReason::Unreadable(error.to_string()));
+                    continue;
+                }
+            };
+            if !visited.insert(canonical) {
+                scan.skip(path, SkipReason::SymlinkLoop);
+                continue;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let child_subpath = level
+                .relative_subpath
+                .map_or_else(|| name.to_string(), |parent| format!("{parent}/{name}"));
+            let child_level = WalkLevel {
+                relative_subpath: Some(&child_subpath),
+                depth: level.depth + 1,
+            };
+            walk_folder(&path, &child_level, recursive, extensions, visited, scan);
+        } else if !metadata.is_file() || !path_has_extension(&path, extensions) {
+            scan.skip(path, SkipReason::Unsupported);
+        } else if is_converted_output_path(&path) {
+            scan.skip(path, SkipReason::ConvertedOutput);
+        } else {
+            push_scanned_source(scan, path, level.relative_subpath.map(str::to_string));
         }
     }
 }
 
+/// Dot-files and folders, and on Windows entries with the hidden attribute.
+fn is_hidden_entry(path: &Path, metadata: &fs::Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
+            return true;
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = metadata;
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn push_scanned_source(scan: &mut SourceScan, path: PathBuf, relative_subpath: Option<String>) {
+    if scan.sources.len() >= MAX_SCANNED_SOURCE_FILES {
+        scan.report.truncated = true;
+        return;
+    }
+    scan.sources.push(ScannedSource {
+        path,
+        relative_subpath,
+    });
+}
+
 fn path_has_extension(path: &Path, allowed_extensions: &[&str]) -> bool {
     path.extension()
         .and_then(|extension| extension.to_str())
@@ -161,6 +449,131 @@ mod tests {
         assert_eq!(paths, [root.join("clip.mp4"), nested.join("still.PNG")]);
     }
 
+    #[test]
+    fn discover_picked_sources_keeps_unlisted_file_extensions() {
+        let root = unique_test_dir("picked-discovery");
+        std::fs::create_dir_all(&root).expect("test media directory should be created");
+        std::fs::write(root.join("clip.mp4"), b"").expect("test video should be written");
+        std::fs::write(root.join("notes.txt"), b"").expect("test text file should be written");
+
+        let picked = discover_picked_sources(vec![PathBuf::from("/tmp/capture.mts"), root.clone()]);
+
+        std::fs::remove_dir_all(&root).expect("test media directory should be removed");
+        assert_eq!(
+            picked
+                .sources
+                .into_iter()
+                .map(|source| source.path)
+                .collect::<Vec<_>>(),
+            [PathBuf::from("/tmp/capture.mts"), root.join("clip.mp4")]
+        );
+        assert_eq!(picked.report.unsupported_files, 1);
+    }
+
+    #[test]
+    fn scan_folder_records_relative_subpaths_and_skipped_entries() {
+        let root = unique_test_dir("scan-relative");
+        let week = root.join("week 1").join("day 2");
+        std::fs::create_dir_all(&week).expect("test media directory should be created");
+        std::fs::write(root.join("intro.mp4"), b"").expect("test video should be written");
+        std::fs::write(week.join("lecture.mov"), b"").expect("test video should be written");
+        std::fs::write(week.join("notes.txt"), b"").expect("test text file should be written");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, week.join("loop"))
+            .expect("test symlink should be created");
+
+        let recursive = scan_folder(&root, true, SOURCE_FILE_EXTENSIONS);
+        let flat = scan_folder(&root, false, SOURCE_FILE_EXTENSIONS);
+
+        std::fs::remove_dir_all(&root).expect("test media directory should be removed");
+        assert_eq!(
+            recursive.sources,
+            [
+                ScannedSource {
+                    path: root.join("intro.mp4"),
+                    relative_subpath: None,
+                },
+                ScannedSource {
+                    path: week.join("lecture.mov"),
+                    relative_subpath: Some("week 1/day 2".to_string()),
+                },
+            ]
+        );
+        assert_eq!(recursive.report.unsupported_files, 1);
+        assert_eq!(recursive.report.symlink_loops, usize::from(cfg!(unix)));
+        assert!(!recursive.report.truncated);
+        assert_eq!(flat.sources.len(), 1);
+    }
+
+    #[test]
+    fn dropped_folder_scans_report_hidden_outputs_and_deep_folders() {
+        let root = unique_test_dir("drop-skips");
+        let hidden_folder = root.join(".cache");
+        let deep =
+            (0..=MAX_SCAN_DEPTH).fold(root.clone(), |path, level| path.join(format!("{level}")));
+        std::fs::create_dir_all(&hidden_folder).expect("test hidden folder should be created");
+        std::fs::create_dir_all(&deep).expect("test nested folders should be created");
+        std::fs::write(root.join("clip.mp4"), b"").expect("test video should be written");
+        std::fs::write(root.join(".clip.mp4"), b"").expect("test hidden video should be written");
+        std::fs::write(root.join("clip_converted.mp4"), b"")
+            .expect("test output should be written");
+        std::fs::write(hidden_folder.join("thumb.png"), b"").expect("test image should be written");
+        std::fs::write(deep.join("buried.mp4"), b"").expect("test video should be written");
+
+        let recursive = discover_dropped_sources(vec![root.clone()], true);
+        let flat = discover_dropped_sources(vec![root.clone()], false);
+
+        std::fs::remove_dir_all(&root).expect("test media directory should be removed");
+        assert_eq!(
+            recursive
+                .sources
+                .iter()
+                .map(|source| source.path.clone())
+                .collect::<Vec<_>>(),
+            [root.join("clip.mp4")]
+        );
+        assert_eq!(
+            recursive
+                .skipped
+                .iter()
+                .map(|skipped| (skipped.path.clone(), skipped.reason.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (root.join(".cache"), SkipReason::Hidden),
+                (root.join(".clip.mp4"), SkipReason::Hidden),
+                (deep, SkipReason::TooDeep),
+                (root.join("clip_converted.mp4"), SkipReason::ConvertedOutput),
+            ]
+        );
+        assert_eq!(
+            recursive.report.summary().as_deref(),
+            Some("Skipped 2 hidden entries, 1 earlier outputs, 1 folders too deep")
+        );
+        assert_eq!(flat.sources.len(), 1);
+        assert_eq!(flat.report.hidden_entries, 1);
+    }
+
+    #[test]
+    fn scan_folder_reports_missing_root_as_unreadable() {
+        let scan = scan_folder(
+            &unique_test_dir("scan-missing"),
+            true,
+            SOURCE_FILE_EXTENSIONS,
+        );
+
+        assert!(scan.sources.is_empty());
+        assert_eq!(scan.report.unreadable_entries, 1);
+        assert_eq!(
+            scan.report.summary().as_deref(),
+            Some("Skipped 1 unreadable entries")
+        );
+    }
+
+    #[test]
+    fn source_scan_report_summary_is_empty_when_nothing_was_skipped() {
+        assert_eq!(SourceScanReport::default().summary(), None);
+    }
+
     #[test]
     fn source_file_extensions_match_original_dialog_groups() {
         let grouped = VIDEO_FILE_EXTENSIONS
@@ -1,5 +1,10 @@
 use std::path::Path;
 
+use frame_core::{
+    args::{DEFAULT_OUTPUT_SUFFIX, default_output_stem},
+    network_input::{input_url_scheme, network_input_file_name},
+};
+
 use crate::numeric::u64_to_f64;
 
 const FILE_SIZE_UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
@@ -12,8 +17,12 @@ pub fn file_size_bytes(path: &Path) -> u64 {
         .map_or(0, |metadata| metadata.len())
 }
 
+/// File name of a path, or of a URL without its query.
 #[must_use]
 pub fn file_name_from_path(path: &str) -> &str {
+    if input_url_scheme(path).is_some() {
+        return network_input_file_name(path);
+    }
     path.rsplit(['/', '\\'])
         .next()
         .filter(|name| !name.is_empty())
@@ -30,20 +39,7 @@ pub fn original_format_from_name(name: &str) -> &str {
 
 #[must_use]
 pub fn derive_output_name(file_name: &str) -> String {
-    let base = file_name.rfind('.').map_or(file_name, |dot_index| {
-        let extension = &file_name[dot_index + 1..];
-        if extension.is_empty() || extension.contains(['/', '\\', '.']) {
-            file_name
-        } else {
-            &file_name[..dot_index]
-        }
-    });
-
-    if base.is_empty() {
-        "output_converted".to_string()
-    } else {
-        format!("{base}_converted")
-    }
+    default_output_stem(file_name, DEFAULT_OUTPUT_SUFFIX)
 }
 
 #[must_use]
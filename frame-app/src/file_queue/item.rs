@@ -3,7 +3,10 @@ use std::path::Path;
 use crate::settings::ConversionConfig;
 
 use super::{
-    format::{derive_output_name, file_name_from_path, file_size_bytes, original_format_from_name},
+    format::{
+        derive_output_name, file_name_from_path, file_size_bytes, format_file_size,
+        original_format_from_name,
+    },
     status::{
         FileStateTone, FileStatus, RowActionAvailability, RowPrimaryAction, RowSecondaryAction,
     },
@@ -16,12 +19,27 @@ pub struct FileItem {
     pub size_bytes: u64,
     pub status: FileStatus,
     pub progress_percent: u8,
+    /// Latest encoding speed, formatted for the row label. For sources of
+    /// unknown length it holds the time and bytes processed instead.
+    pub progress_speed: Option<String>,
+    /// The source's length is unknown, so the row shows no percentage.
+    pub progress_indeterminate: bool,
+    /// Size of the converted output once the task has completed.
+    pub output_size_bytes: Option<u64>,
+    /// Distinct `FFmpeg` warnings reported by the completed conversion.
+    pub warning_count: usize,
     pub original_format: String,
     pub output_name: String,
+    /// Folder chosen with Save As; replaces the default output folder for
+    /// this file.
+    pub output_directory: Option<String>,
     pub config: ConversionConfig,
     pub path: String,
     pub is_selected_for_conversion: bool,
     pub conversion_error: Option<String>,
+    /// Folder of the source relative to an imported directory, mirrored
+    /// under the output directory.
+    pub relative_subpath: Option<String>,
 }
 
 impl FileItem {
@@ -33,14 +51,20 @@ impl FileItem {
             id: id.into(),
             original_format: original_format_from_name(&name).to_string(),
             output_name: derive_output_name(&name),
+            output_directory: None,
             config: ConversionConfig::default(),
             name,
             size_bytes,
             status: FileStatus::Idle,
             progress_percent: 0,
+            progress_speed: None,
+            progress_indeterminate: false,
+            output_size_bytes: None,
+            warning_count: 0,
             path,
             is_selected_for_conversion: true,
             conversion_error: None,
+            relative_subpath: None,
         }
     }
 
@@ -57,10 +81,27 @@ impl FileItem {
     #[must_use]
     pub fn row_state_label(&self) -> String {
         match self.status {
-            FileStatus::Converting | FileStatus::Paused | FileStatus::Cancelling => {
+            FileStatus::Converting if self.progress_indeterminate => self
+                .progress_speed
+                .clone()
+                .unwrap_or_else(|| "converting".to_string()),
+            FileStatus::Converting => self.progress_speed.as_ref().map_or_else(
+                || format!("{}%", self.progress_percent),
+                |speed| format!("{}% · {speed}", self.progress_percent),
+            ),
+            FileStatus::Paused | FileStatus::Cancelling => {
                 format!("{}%", self.progress_percent)
             }
-            FileStatus::Completed => "ready".to_string(),
+            FileStatus::Completed => {
+                let mut parts = vec!["ready".to_string()];
+                parts.extend(self.output_size_bytes.map(format_file_size));
+                match self.warning_count {
+                    0 => {}
+                    1 => parts.push("1 warning".to_string()),
+                    count => parts.push(format!("{count} warnings")),
+                }
+                parts.join(" · ")
+            }
             FileStatus::Queued => "queued".to_string(),
             FileStatus::Error => "error".to_string(),
             FileStatus::Idle => "idle".to_string(),
@@ -310,6 +310,56 @@ impl FileQueue {
         }
     }
 
+    pub fn update_progress_speed(&mut self, id: &str, speed: Option<String>) -> bool {
+        match self.files.iter_mut().find(|file| file.id == id) {
+            Some(file) if file.progress_speed != speed => {
+                file.progress_speed = speed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update_progress_indeterminate(&mut self, id: &str, indeterminate: bool) -> bool {
+        match self.files.iter_mut().find(|file| file.id == id) {
+            Some(file) if file.progress_indeterminate != indeterminate => {
+                file.progress_indeterminate = indeterminate;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update_output_size(&mut self, id: &str, size_bytes: Option<u64>) -> bool {
+        match self.files.iter_mut().find(|file| file.id == id) {
+            Some(file) if file.output_size_bytes != size_bytes => {
+                file.output_size_bytes = size_bytes;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update_warning_count(&mut self, id: &str, warning_count: usize) -> bool {
+        match self.files.iter_mut().find(|file| file.id == id) {
+            Some(file) if file.warning_count != warning_count => {
+                file.warning_count = warning_count;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update_output_name(&mut self, id: &str, output_name: &str) -> bool {
+        match self.files.iter_mut().find(|file| file.id == id) {
+            Some(file) if file.output_name != output_name => {
+                output_name.clone_into(&mut file.output_name);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn clear_error(&mut self, id: &str) -> bool {
         if let Some(file) = self.files.iter_mut().find(|file| file.id == id) {
             file.conversion_error = None;
@@ -353,6 +403,32 @@ impl FileQueue {
         true
     }
 
+    /// Points the selected file at the exact output chosen with Save As.
+    pub fn set_selected_output_target(&mut self, directory: String, file_name: &str) -> bool {
+        let Some(file) = self.selected_file_mut() else {
+            return false;
+        };
+
+        let output_name = sanitize_output_name(file_name);
+        if output_name.is_empty() {
+            return false;
+        }
+        let directory = Some(directory);
+        if file.output_name == output_name && file.output_directory == directory {
+            return false;
+        }
+
+        file.output_name = output_name;
+        file.output_directory = directory;
+        true
+    }
+
+    /// Sends the selected file back to the default output folder.
+    pub fn clear_selected_output_directory(&mut self) -> bool {
+        self.selected_file_mut()
+            .is_some_and(|file| file.output_directory.take().is_some())
+    }
+
     pub fn queue_selected_pending_conversions(&mut self) -> Vec<FileItem> {
         let mut pending_files = Vec::new();
 
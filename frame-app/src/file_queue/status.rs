@@ -28,6 +28,12 @@ impl FileStatus {
         matches!(self, Self::Idle | Self::Completed | Self::Error)
     }
 
+    /// Whether no conversion is running or waiting for the file.
+    #[must_use]
+    pub const fn is_settled(self) -> bool {
+        matches!(self, Self::Idle | Self::Completed | Self::Error)
+    }
+
     #[must_use]
     pub const fn is_actionable_for_conversion(self) -> bool {
         matches!(self, Self::Idle | Self::Error)
@@ -125,6 +125,18 @@ mod file_item {
         assert_eq!(file.row_state_tone(), FileStateTone::Blue);
     }
 
+    #[test]
+    fn completed_row_state_shows_output_size_and_warnings() {
+        let mut file = FileItem::from_path("1", "/tmp/video.mp4", 10);
+        file.status = FileStatus::Completed;
+        file.output_size_bytes = Some(1536);
+
+        assert_eq!(file.row_state_label(), "ready · 1.5 KB");
+
+        file.warning_count = 3;
+        assert_eq!(file.row_state_label(), "ready · 1.5 KB · 3 warnings");
+    }
+
     #[test]
     fn error_row_state_uses_red_tone() {
         let mut file = FileItem::from_path("1", "/tmp/video.mp4", 10);
@@ -725,6 +737,27 @@ mod file_queue {
         );
     }
 
+    #[test]
+    fn set_selected_output_target_sets_folder_and_name_until_cleared() {
+        let mut queue = FileQueue::new();
+        queue.add_file(sample_file("first", "/tmp/one.mp4", 10));
+
+        assert!(queue.set_selected_output_target("/Volumes/Renders".to_string(), "final.mov"));
+        assert!(!queue.set_selected_output_target("/Volumes/Renders".to_string(), "final.mov"));
+        assert!(!queue.set_selected_output_target("/Volumes/Renders".to_string(), ".."));
+
+        let file = queue.selected_file().expect("file should be selected");
+        assert_eq!(file.output_name, "final.mov");
+        assert_eq!(file.output_directory.as_deref(), Some("/Volumes/Renders"));
+
+        assert!(queue.clear_selected_output_directory());
+        assert!(!queue.clear_selected_output_directory());
+        assert_eq!(
+            queue.selected_file().map(|file| file.output_name.as_str()),
+            Some("final.mov")
+        );
+    }
+
     #[test]
     fn queue_selected_pending_conversions_marks_only_selected_pending_files() {
         let mut queue = FileQueue::new();
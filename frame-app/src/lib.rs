@@ -5,22 +5,28 @@ pub mod app_info;
 pub mod app_persistence;
 pub mod assets;
 pub mod capabilities;
+pub mod cli;
 pub mod conversion_events;
 pub mod conversion_runner;
+pub mod drop_scan;
 pub mod file_filters;
 pub mod file_queue;
 pub mod native_dialogs;
 pub mod notifications;
 pub(crate) mod numeric;
+pub mod preset_transfer;
 pub mod preview;
 pub mod preview_engine;
+pub mod queue_status;
 pub mod runtime_binaries;
 pub(crate) mod runtime_environment;
 pub mod settings;
+pub mod single_instance;
 pub mod source_metadata;
 pub mod theme;
 pub mod update_runtime;
 pub(crate) mod update_session;
+pub mod watch_folders;
 
 use file_queue::FileQueue;
 use numeric::u64_to_f64;
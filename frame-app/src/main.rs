@@ -1,15 +1,44 @@
+use std::process::ExitCode;
+
 use frame_app::{
     app::{init_app, open_frame_window},
     app_info::FRAME_APP_NAME,
     assets::{self, FrameAssets},
+    cli::{self, CLI_USAGE, CLI_USAGE_EXIT_CODE},
+    single_instance::{self, file_url_to_path, launch_paths},
 };
 
-fn main() {
-    gpui_platform::application()
-        .with_assets(FrameAssets)
-        .run(|cx| {
-            assets::load_frame_fonts(cx).expect("failed to load Frame fonts");
-            open_frame_window(cx);
-            init_app(cx, FRAME_APP_NAME);
-        });
+fn main() -> ExitCode {
+    match cli::parse_cli_args(std::env::args().skip(1)) {
+        Ok(Some(command)) => return cli::run_cli(command),
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("frame: {error}\n{CLI_USAGE}");
+            return ExitCode::from(CLI_USAGE_EXIT_CODE);
+        }
+    }
+
+    let Some(forwarded_files) =
+        single_instance::claim_platform_instance(launch_paths(std::env::args_os().skip(1)))
+    else {
+        return ExitCode::SUCCESS;
+    };
+
+    let application = gpui_platform::application().with_assets(FrameAssets);
+    let opened_files = forwarded_files.sender();
+    application.on_open_urls(move |urls| {
+        let paths = urls
+            .iter()
+            .filter_map(|url| file_url_to_path(url))
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            let _ = opened_files.send(paths);
+        }
+    });
+    application.run(|cx| {
+        assets::load_frame_fonts(cx).expect("failed to load Frame fonts");
+        open_frame_window(cx, forwarded_files);
+        init_app(cx, FRAME_APP_NAME);
+    });
+    ExitCode::SUCCESS
 }
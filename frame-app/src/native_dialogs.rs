@@ -1,13 +1,32 @@
 //! Cross-platform native dialogs used by the GPUI frontend.
 
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use crate::file_filters::{
-    AUDIO_FILE_EXTENSIONS, IMAGE_FILE_EXTENSIONS, SOURCE_FILE_EXTENSIONS, SUBTITLE_FILE_EXTENSIONS,
-    VIDEO_FILE_EXTENSIONS,
+use crate::{
+    file_filters::{
+        AUDIO_FILE_EXTENSIONS, IMAGE_FILE_EXTENSIONS, SOURCE_FILE_EXTENSIONS,
+        SUBTITLE_FILE_EXTENSIONS, VIDEO_FILE_EXTENSIONS,
+    },
+    preset_transfer::{DEFAULT_PRESET_FILE_NAME, PRESET_FILE_EXTENSION},
 };
 use gpui::Window;
 use rfd::{AsyncFileDialog, FileHandle};
+use serde::{Deserialize, Serialize};
+
+/// What a dialog is opened for; each purpose remembers its own last-used
+/// directory.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DialogPurpose {
+    OpenInput,
+    SaveOutput,
+    SubtitleFile,
+    OverlayImage,
+    PresetFile,
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct NativeDialogFilterSpec {
@@ -15,6 +34,29 @@ pub struct NativeDialogFilterSpec {
     pub extensions: &'static [&'static str],
 }
 
+/// Kind of file a source dialog offers first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NativeDialogFilterSet {
+    Video,
+    Audio,
+    Subtitle,
+    Image,
+    AnyMedia,
+}
+
+impl NativeDialogFilterSet {
+    #[must_use]
+    pub const fn filters(self) -> &'static [NativeDialogFilterSpec] {
+        match self {
+            Self::Video => &VIDEO_FILE_DIALOG_FILTERS,
+            Self::Audio => &AUDIO_FILE_DIALOG_FILTERS,
+            Self::Subtitle => &SUBTITLE_FILE_DIALOG_FILTERS,
+            Self::Image => &OVERLAY_IMAGE_DIALOG_FILTERS,
+            Self::AnyMedia => &SOURCE_FILE_DIALOG_FILTERS,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct NativeDialogSpec {
     pub title: &'static str,
@@ -41,6 +83,23 @@ pub const SOURCE_FILE_DIALOG_FILTERS: [NativeDialogFilterSpec; 4] = [
     },
 ];
 
+pub const VIDEO_FILE_DIALOG_FILTERS: [NativeDialogFilterSpec; 1] = [NativeDialogFilterSpec {
+    label: "Videos",
+    extensions: VIDEO_FILE_EXTENSIONS,
+}];
+
+pub const AUDIO_FILE_DIALOG_FILTERS: [NativeDialogFilterSpec; 1] = [NativeDialogFilterSpec {
+    label: "Audio",
+    extensions: AUDIO_FILE_EXTENSIONS,
+}];
+
+/// Last filter of source dialogs, for valid media with an unlisted
+/// extension.
+pub const ALL_FILES_DIALOG_FILTER: NativeDialogFilterSpec = NativeDialogFilterSpec {
+    label: "All Files",
+    extensions: &["*"],
+};
+
 pub const SUBTITLE_FILE_DIALOG_FILTERS: [NativeDialogFilterSpec; 1] = [NativeDialogFilterSpec {
     label: "Subtitles",
     extensions: SUBTITLE_FILE_EXTENSIONS,
@@ -51,6 +110,11 @@ pub const OVERLAY_IMAGE_DIALOG_FILTERS: [NativeDialogFilterSpec; 1] = [NativeDia
     extensions: IMAGE_FILE_EXTENSIONS,
 }];
 
+pub const PRESET_FILE_DIALOG_FILTERS: [NativeDialogFilterSpec; 1] = [NativeDialogFilterSpec {
+    label: "Frame Presets",
+    extensions: &[PRESET_FILE_EXTENSION],
+}];
+
 pub const SOURCE_FILE_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
     title: "Add Source",
     filters: &SOURCE_FILE_DIALOG_FILTERS,
@@ -60,7 +124,7 @@ pub const SOURCE_FILE_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
 pub const SOURCE_FOLDER_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
     title: "Open Folder",
     filters: &[],
-    allows_multiple: false,
+    allows_multiple: true,
 };
 
 pub const OUTPUT_FOLDER_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
@@ -69,6 +133,26 @@ pub const OUTPUT_FOLDER_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
     allows_multiple: false,
 };
 
+pub const SCRATCH_FOLDER_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
+    title: "Choose Scratch Folder",
+    filters: &[],
+    allows_multiple: false,
+};
+
+pub const WATCH_FOLDER_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
+    title: "Choose Folder to Watch",
+    filters: &[],
+    allows_multiple: false,
+};
+
+pub const FFMPEG_BINARY_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
+    title: "Choose FFmpeg",
+    filters: &[],
+    allows_multiple: false,
+};
+
+pub const SAVE_OUTPUT_DIALOG_TITLE: &str = "Save Output As";
+
 pub const SUBTITLE_FILE_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
     title: "Select subtitle file",
     filters: &SUBTITLE_FILE_DIALOG_FILTERS,
@@ -81,6 +165,64 @@ pub const OVERLAY_IMAGE_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
     allows_multiple: false,
 };
 
+pub const IMPORT_PRESETS_DIALOG_SPEC: NativeDialogSpec = NativeDialogSpec {
+    title: "Import Presets",
+    filters: &PRESET_FILE_DIALOG_FILTERS,
+    allows_multiple: false,
+};
+
+pub const EXPORT_PRESETS_DIALOG_TITLE: &str = "Export Presets";
+
+/// Set while a native dialog is shown; see [`claim_native_dialog`].
+static NATIVE_DIALOG_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Marks a native dialog as shown until it is dropped.
+#[derive(Debug)]
+pub struct NativeDialogClaim(());
+
+impl Drop for NativeDialogClaim {
+    fn drop(&mut self) {
+        NATIVE_DIALOG_OPEN.store(false, Ordering::Release);
+    }
+}
+
+/// Claims the single native dialog slot, or returns `None` while another
+/// dialog is still open so prompts are not stacked. Keep the claim alive
+/// until the dialog has returned.
+#[must_use]
+pub fn claim_native_dialog() -> Option<NativeDialogClaim> {
+    NATIVE_DIALOG_OPEN
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .ok()
+        .map(|_| NativeDialogClaim(()))
+}
+
+/// Directory a dialog should open in: `directory` when it still exists,
+/// otherwise `None` so the platform default is used.
+#[must_use]
+pub fn initial_dialog_directory(directory: Option<&Path>) -> Option<&Path> {
+    directory.filter(|directory| directory.is_dir())
+}
+
+/// Directory a dialog for a remembered location should open in: the
+/// remembered directory while it exists, otherwise the home directory.
+#[must_use]
+pub fn dialog_start_directory(remembered: Option<&Path>) -> Option<PathBuf> {
+    initial_dialog_directory(remembered).map_or_else(
+        || directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf()),
+        |directory| Some(directory.to_path_buf()),
+    )
+}
+
+/// Directory to remember after `selected` was picked: its parent folder.
+#[must_use]
+pub fn remembered_dialog_directory(selected: &Path) -> Option<PathBuf> {
+    selected
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
 pub async fn pick_source_files(dialog: AsyncFileDialog) -> Option<Vec<PathBuf>> {
     dialog
         .pick_files()
@@ -89,14 +231,22 @@ pub async fn pick_source_files(dialog: AsyncFileDialog) -> Option<Vec<PathBuf>>
         .map(file_handles_to_paths)
 }
 
-pub async fn pick_source_folder(dialog: AsyncFileDialog) -> Option<PathBuf> {
-    dialog.pick_folder().await.as_ref().map(file_handle_to_path)
+pub async fn pick_source_folders(dialog: AsyncFileDialog) -> Option<Vec<PathBuf>> {
+    dialog
+        .pick_folders()
+        .await
+        .as_deref()
+        .map(file_handles_to_paths)
 }
 
 pub async fn pick_output_folder(dialog: AsyncFileDialog) -> Option<PathBuf> {
     dialog.pick_folder().await.as_ref().map(file_handle_to_path)
 }
 
+pub async fn pick_save_output_path(dialog: AsyncFileDialog) -> Option<PathBuf> {
+    dialog.save_file().await.as_ref().map(file_handle_to_path)
+}
+
 pub async fn pick_subtitle_file(dialog: AsyncFileDialog) -> Option<PathBuf> {
     dialog.pick_file().await.as_ref().map(file_handle_to_path)
 }
@@ -105,29 +255,149 @@ pub async fn pick_overlay_image_file(dialog: AsyncFileDialog) -> Option<PathBuf>
     dialog.pick_file().await.as_ref().map(file_handle_to_path)
 }
 
+pub async fn pick_ffmpeg_binary(dialog: AsyncFileDialog) -> Option<PathBuf> {
+    dialog.pick_file().await.as_ref().map(file_handle_to_path)
+}
+
+pub async fn pick_preset_file(dialog: AsyncFileDialog) -> Option<PathBuf> {
+    dialog.pick_file().await.as_ref().map(file_handle_to_path)
+}
+
+pub async fn pick_preset_export_path(dialog: AsyncFileDialog) -> Option<PathBuf> {
+    dialog.save_file().await.as_ref().map(file_handle_to_path)
+}
+
+/// Source picker offering the filters of `filter_set`, followed by
+/// [`ALL_FILES_DIALOG_FILTER`].
 #[must_use]
-pub fn source_file_dialog(parent: &Window) -> AsyncFileDialog {
-    file_dialog_from_spec(SOURCE_FILE_DIALOG_SPEC).set_parent(parent)
+pub fn source_file_dialog(
+    parent: &Window,
+    filter_set: NativeDialogFilterSet,
+    initial_directory: Option<&Path>,
+) -> AsyncFileDialog {
+    let dialog = file_dialog_from_spec(NativeDialogSpec {
+        filters: filter_set.filters(),
+        ..SOURCE_FILE_DIALOG_SPEC
+    })
+    .add_filter(
+        ALL_FILES_DIALOG_FILTER.label,
+        ALL_FILES_DIALOG_FILTER.extensions,
+    );
+    with_initial_directory(dialog, initial_directory).set_parent(parent)
 }
 
 #[must_use]
-pub fn source_folder_dialog(parent: &Window) -> AsyncFileDialog {
-    file_dialog_from_spec(SOURCE_FOLDER_DIALOG_SPEC).set_parent(parent)
+pub fn source_folder_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(SOURCE_FOLDER_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
 }
 
 #[must_use]
-pub fn output_folder_dialog(parent: &Window) -> AsyncFileDialog {
-    file_dialog_from_spec(OUTPUT_FOLDER_DIALOG_SPEC).set_parent(parent)
+pub fn output_folder_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(OUTPUT_FOLDER_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
 }
 
 #[must_use]
-pub fn subtitle_file_dialog(parent: &Window) -> AsyncFileDialog {
-    file_dialog_from_spec(SUBTITLE_FILE_DIALOG_SPEC).set_parent(parent)
+pub fn scratch_folder_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(SCRATCH_FOLDER_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
 }
 
 #[must_use]
-pub fn overlay_image_dialog(parent: &Window) -> AsyncFileDialog {
-    file_dialog_from_spec(OVERLAY_IMAGE_DIALOG_SPEC).set_parent(parent)
+pub fn watch_folder_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(WATCH_FOLDER_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
+}
+
+/// File dialog for an `FFmpeg` binary to use instead of the bundled one,
+/// opening in the directory of the current choice.
+#[must_use]
+pub fn ffmpeg_binary_dialog(parent: &Window, current: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(FFMPEG_BINARY_DIALOG_SPEC),
+        current.and_then(Path::parent),
+    )
+    .set_parent(parent)
+}
+
+/// Save dialog for one output, suggesting `output_name` with the extension
+/// of `container` and filtering on that container.
+#[must_use]
+pub fn save_output_dialog(
+    parent: &Window,
+    output_name: &str,
+    container: &str,
+    initial_directory: Option<&Path>,
+) -> AsyncFileDialog {
+    let dialog = AsyncFileDialog::new()
+        .set_title(SAVE_OUTPUT_DIALOG_TITLE)
+        .set_file_name(format!("{output_name}.{container}"))
+        .add_filter(container.to_ascii_uppercase(), &[container]);
+    with_initial_directory(dialog, initial_directory).set_parent(parent)
+}
+
+/// Splits a path chosen in the save dialog into its folder and file name.
+/// A file name whose extension names another container is corrected when
+/// the output path is built.
+#[must_use]
+pub fn save_path_output_target(path: &Path) -> Option<(PathBuf, String)> {
+    let directory = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())?;
+    let file_name = path.file_name()?.to_str()?;
+    Some((directory.to_path_buf(), file_name.to_string()))
+}
+
+#[must_use]
+pub fn subtitle_file_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(SUBTITLE_FILE_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
+}
+
+#[must_use]
+pub fn overlay_image_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(OVERLAY_IMAGE_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
+}
+
+#[must_use]
+pub fn import_presets_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    with_initial_directory(
+        file_dialog_from_spec(IMPORT_PRESETS_DIALOG_SPEC),
+        initial_directory,
+    )
+    .set_parent(parent)
+}
+
+/// Save dialog for a preset file, suggesting [`DEFAULT_PRESET_FILE_NAME`].
+#[must_use]
+pub fn export_presets_dialog(parent: &Window, initial_directory: Option<&Path>) -> AsyncFileDialog {
+    let mut dialog = AsyncFileDialog::new()
+        .set_title(EXPORT_PRESETS_DIALOG_TITLE)
+        .set_file_name(DEFAULT_PRESET_FILE_NAME);
+    for filter in &PRESET_FILE_DIALOG_FILTERS {
+        dialog = dialog.add_filter(filter.label, filter.extensions);
+    }
+    with_initial_directory(dialog, initial_directory).set_parent(parent)
 }
 
 fn file_dialog_from_spec(spec: NativeDialogSpec) -> AsyncFileDialog {
@@ -138,6 +408,13 @@ fn file_dialog_from_spec(spec: NativeDialogSpec) -> AsyncFileDialog {
     dialog
 }
 
+fn with_initial_directory(dialog: AsyncFileDialog, directory: Option<&Path>) -> AsyncFileDialog {
+    match initial_dialog_directory(directory) {
+        Some(directory) => dialog.set_directory(directory),
+        None => dialog,
+    }
+}
+
 fn file_handles_to_paths(handles: &[FileHandle]) -> Vec<PathBuf> {
     handles.iter().map(file_handle_to_path).collect()
 }
@@ -208,11 +485,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn filter_sets_reuse_the_runtime_extension_lists() {
+        assert_eq!(
+            NativeDialogFilterSet::AnyMedia.filters(),
+            SOURCE_FILE_DIALOG_SPEC.filters
+        );
+        assert_eq!(
+            NativeDialogFilterSet::Video.filters()[0].extensions,
+            VIDEO_FILE_EXTENSIONS
+        );
+        assert_eq!(
+            NativeDialogFilterSet::Audio.filters()[0].extensions,
+            AUDIO_FILE_EXTENSIONS
+        );
+        assert_eq!(
+            NativeDialogFilterSet::Subtitle.filters(),
+            SUBTITLE_FILE_DIALOG_SPEC.filters
+        );
+        assert_eq!(
+            NativeDialogFilterSet::Image.filters(),
+            OVERLAY_IMAGE_DIALOG_SPEC.filters
+        );
+    }
+
     #[test]
     fn dialog_specs_capture_selection_mode() {
         const {
             assert!(SOURCE_FILE_DIALOG_SPEC.allows_multiple);
-            assert!(!SOURCE_FOLDER_DIALOG_SPEC.allows_multiple);
+            assert!(SOURCE_FOLDER_DIALOG_SPEC.allows_multiple);
             assert!(!SUBTITLE_FILE_DIALOG_SPEC.allows_multiple);
             assert!(!OVERLAY_IMAGE_DIALOG_SPEC.allows_multiple);
         }
@@ -225,8 +526,75 @@ mod tests {
             NativeDialogSpec {
                 title: "Open Folder",
                 filters: &[],
-                allows_multiple: false,
+                allows_multiple: true,
             }
         );
     }
+
+    #[test]
+    fn native_dialog_claim_rejects_a_second_dialog_until_released() {
+        let claim = claim_native_dialog().expect("no dialog should be open");
+
+        assert!(claim_native_dialog().is_none());
+        drop(claim);
+        assert!(claim_native_dialog().is_some());
+    }
+
+    #[test]
+    fn save_path_output_target_splits_folder_and_file_name() {
+        assert_eq!(
+            save_path_output_target(Path::new("/Volumes/Renders/final cut.mov")),
+            Some((
+                PathBuf::from("/Volumes/Renders"),
+                "final cut.mov".to_string()
+            ))
+        );
+        assert_eq!(save_path_output_target(Path::new("final.mov")), None);
+        assert_eq!(save_path_output_target(Path::new("/")), None);
+    }
+
+    #[test]
+    fn initial_dialog_directory_skips_missing_directories() {
+        let existing = std::env::temp_dir();
+        let missing = existing.join(format!("frame-missing-dialog-dir-{}", std::process::id()));
+
+        assert_eq!(
+            initial_dialog_directory(Some(&existing)),
+            Some(existing.as_path())
+        );
+        assert_eq!(initial_dialog_directory(Some(&missing)), None);
+        assert_eq!(initial_dialog_directory(None), None);
+    }
+
+    #[test]
+    fn dialog_start_directory_falls_back_to_home_for_missing_directories() {
+        let existing = std::env::temp_dir();
+        let missing = existing.join(format!("frame-missing-recent-dir-{}", std::process::id()));
+        let home = directories::UserDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+
+        assert_eq!(dialog_start_directory(Some(&existing)), Some(existing));
+        assert_eq!(dialog_start_directory(Some(&missing)), home);
+        assert_eq!(dialog_start_directory(None), home);
+    }
+
+    #[test]
+    fn remembered_dialog_directory_uses_the_selected_parent() {
+        assert_eq!(
+            remembered_dialog_directory(Path::new("/Volumes/Media/clip.mov")),
+            Some(PathBuf::from("/Volumes/Media"))
+        );
+        assert_eq!(
+            remembered_dialog_directory(Path::new("/Volumes/Media/Shoot")),
+            Some(PathBuf::from("/Volumes/Media"))
+        );
+        assert_eq!(remembered_dialog_directory(Path::new("clip.mov")), None);
+    }
+
+    #[test]
+    fn dialog_purpose_serializes_in_camel_case() {
+        assert_eq!(
+            serde_json::to_value(DialogPurpose::OpenInput).expect("purpose should serialize"),
+            serde_json::json!("openInput")
+        );
+    }
 }
@@ -1,14 +1,20 @@
 //! Native desktop notifications for app-level events.
 
-use std::{sync::Arc, thread};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
 
 #[cfg(target_os = "macos")]
 use std::{
     sync::Once,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use notify_rust::{Notification, Timeout};
+use serde::{Deserialize, Serialize};
 
 #[cfg(target_os = "linux")]
 use ashpd::{
@@ -23,7 +29,7 @@ use ashpd::{
 
 use crate::{
     app_info::FRAME_APP_NAME,
-    file_queue::{FileQueue, FileStatus},
+    file_queue::{FileQueue, FileStatus, format_file_size},
 };
 
 #[cfg(target_os = "linux")]
@@ -33,11 +39,69 @@ const CONVERSION_FINISHED_TITLE: &str = "Queue Finished";
 const FRAME_NOTIFICATION_ICON: &str = "frame";
 #[cfg(any(target_os = "linux", test))]
 const CONVERSION_FINISHED_NOTIFICATION_ID: &str = "conversion-finished";
+#[cfg(any(target_os = "linux", test))]
+const FILES_NOT_OPENED_NOTIFICATION_ID: &str = "files-not-opened";
+#[cfg(any(target_os = "linux", test))]
+const TASK_FINISHED_NOTIFICATION_ID: &str = "task-finished";
+/// Action id notification servers report for a click on the notification.
+#[cfg(all(unix, not(target_os = "macos")))]
+const DEFAULT_NOTIFICATION_ACTION: &str = "default";
+
+/// Which finished conversions are announced with a notification.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationMode {
+    /// Every task that completes or fails.
+    PerTask,
+    /// One summary once the whole queue has finished.
+    #[default]
+    QueueComplete,
+    /// Only tasks that fail.
+    FailuresOnly,
+    Off,
+}
+
+impl NotificationMode {
+    pub const ALL: [Self; 4] = [
+        Self::PerTask,
+        Self::QueueComplete,
+        Self::FailuresOnly,
+        Self::Off,
+    ];
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::PerTask => "Every file",
+            Self::QueueComplete => "Queue finished",
+            Self::FailuresOnly => "Failures only",
+            Self::Off => "Off",
+        }
+    }
+
+    /// Whether a task that finished, or failed when `failed` is set, is
+    /// announced on its own.
+    #[must_use]
+    pub const fn notifies_task(self, failed: bool) -> bool {
+        match self {
+            Self::PerTask => true,
+            Self::FailuresOnly => failed,
+            Self::QueueComplete | Self::Off => false,
+        }
+    }
+
+    #[must_use]
+    pub const fn notifies_queue(self) -> bool {
+        matches!(self, Self::QueueComplete)
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ConversionNotificationSummary {
     pub completed_count: usize,
     pub error_count: usize,
+    /// Time since the queue was started.
+    pub elapsed: Option<Duration>,
 }
 
 impl ConversionNotificationSummary {
@@ -49,10 +113,19 @@ impl ConversionNotificationSummary {
             Some(Self {
                 completed_count,
                 error_count,
+                elapsed: None,
             })
         }
     }
 
+    #[must_use]
+    pub const fn with_elapsed(self, elapsed: Duration) -> Self {
+        Self {
+            elapsed: Some(elapsed),
+            ..self
+        }
+    }
+
     #[must_use]
     pub const fn title(self) -> &'static str {
         CONVERSION_FINISHED_TITLE
@@ -64,16 +137,128 @@ impl ConversionNotificationSummary {
         let file_suffix = if processed_count == 1 { "" } else { "s" };
         let error_suffix = if self.error_count == 1 { "" } else { "s" };
 
+        let elapsed = self
+            .elapsed
+            .map(|elapsed| format!(" in {}", format_elapsed(elapsed)))
+            .unwrap_or_default();
+
         format!(
-            "Processed {} file{file_suffix} with {} error{error_suffix}.",
+            "Processed {} file{file_suffix} with {} error{error_suffix}{elapsed}.",
             processed_count, self.error_count,
         )
     }
 }
 
+/// One task that completed or failed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaskNotification {
+    pub file_name: String,
+    /// Time since the task started converting.
+    pub elapsed: Option<Duration>,
+    pub outcome: TaskNotificationOutcome,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskNotificationOutcome {
+    Completed {
+        output_path: PathBuf,
+        output_size_bytes: Option<u64>,
+    },
+    /// The classified reason the task failed.
+    Failed { reason: String },
+}
+
+impl TaskNotification {
+    #[must_use]
+    pub const fn title(&self) -> &'static str {
+        match self.outcome {
+            TaskNotificationOutcome::Completed { .. } => "Conversion Finished",
+            TaskNotificationOutcome::Failed { .. } => "Conversion Failed",
+        }
+    }
+
+    /// e.g. `clip.mov · 1 min 12 s · 24.3 MB`, or `clip.mov: The output
+    /// drive is full`.
+    #[must_use]
+    pub fn body(&self) -> String {
+        match &self.outcome {
+            TaskNotificationOutcome::Completed {
+                output_size_bytes, ..
+            } => [
+                Some(self.file_name.clone()),
+                self.elapsed.map(format_elapsed),
+                output_size_bytes.map(format_file_size),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" · "),
+            TaskNotificationOutcome::Failed { reason } => format!("{}: {reason}", self.file_name),
+        }
+    }
+
+    /// Output to reveal in the file manager when the notification is
+    /// clicked.
+    #[must_use]
+    pub fn output_path(&self) -> Option<&Path> {
+        match &self.outcome {
+            TaskNotificationOutcome::Completed { output_path, .. } => Some(output_path),
+            TaskNotificationOutcome::Failed { .. } => None,
+        }
+    }
+}
+
+/// Whole seconds, minutes and hours, e.g. `45 s`, `1 min 12 s` or `2 h 5 min`.
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, seconds) => format!("{seconds} s"),
+        (0, minutes, seconds) => format!("{minutes} min {seconds} s"),
+        (hours, minutes, _) => format!("{hours} h {minutes} min"),
+    }
+}
+
+/// Files handed to Frame from outside the window, such as by "Open with",
+/// that could not be opened.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilesNotOpenedSummary {
+    /// Names of the files, in the order they were given.
+    pub file_names: Vec<String>,
+    pub reason: String,
+}
+
+impl FilesNotOpenedSummary {
+    #[must_use]
+    pub fn title(&self) -> &'static str {
+        if self.file_names.len() == 1 {
+            "Could Not Open File"
+        } else {
+            "Could Not Open Files"
+        }
+    }
+
+    #[must_use]
+    pub fn body(&self) -> String {
+        match self.file_names.as_slice() {
+            [] => self.reason.clone(),
+            [name] => format!("{name}: {}", self.reason),
+            [first, rest @ ..] => {
+                let suffix = if rest.len() == 1 { "" } else { "s" };
+                format!(
+                    "{first} and {} other file{suffix}: {}",
+                    rest.len(),
+                    self.reason
+                )
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AppNotifier {
     conversion_finished_handler: Arc<dyn Fn(ConversionNotificationSummary) + Send + Sync + 'static>,
+    files_not_opened_handler: Arc<dyn Fn(FilesNotOpenedSummary) + Send + Sync + 'static>,
+    task_finished_handler: Arc<dyn Fn(TaskNotification) + Send + Sync + 'static>,
 }
 
 impl AppNotifier {
@@ -84,21 +269,55 @@ impl AppNotifier {
 
     #[must_use]
     pub fn system() -> Self {
-        Self::from_conversion_finished_handler(send_system_conversion_finished_notification)
+        Self {
+            conversion_finished_handler: Arc::new(|summary: ConversionNotificationSummary| {
+                send_system_notification(SystemNotification::from(summary));
+            }),
+            files_not_opened_handler: Arc::new(|summary: FilesNotOpenedSummary| {
+                send_system_notification(SystemNotification::from(summary));
+            }),
+            task_finished_handler: Arc::new(|notification: TaskNotification| {
+                send_system_notification(SystemNotification::from(notification));
+            }),
+        }
     }
 
+    /// Notifier that reports finished queues to `handler` and drops other
+    /// notifications.
     #[must_use]
     pub fn from_conversion_finished_handler(
         handler: impl Fn(ConversionNotificationSummary) + Send + Sync + 'static,
     ) -> Self {
         Self {
             conversion_finished_handler: Arc::new(handler),
+            files_not_opened_handler: Arc::new(|_| {}),
+            task_finished_handler: Arc::new(|_| {}),
+        }
+    }
+
+    /// Reports finished and failed tasks to `handler` as well.
+    #[must_use]
+    pub fn with_task_finished_handler(
+        self,
+        handler: impl Fn(TaskNotification) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            task_finished_handler: Arc::new(handler),
+            ..self
         }
     }
 
     pub fn notify_conversion_finished(&self, summary: ConversionNotificationSummary) {
         (self.conversion_finished_handler)(summary);
     }
+
+    pub fn notify_files_not_opened(&self, summary: FilesNotOpenedSummary) {
+        (self.files_not_opened_handler)(summary);
+    }
+
+    pub fn notify_task_finished(&self, notification: TaskNotification) {
+        (self.task_finished_handler)(notification);
+    }
 }
 
 impl Default for AppNotifier {
@@ -134,17 +353,70 @@ pub fn conversion_finished_notification_for_task_ids(
     ConversionNotificationSummary::from_counts(completed_count, error_count)
 }
 
-fn send_system_conversion_finished_notification(summary: ConversionNotificationSummary) {
+/// A notification as handed to the platform.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SystemNotification {
+    /// Portal id; a newer notification with the same id replaces the older.
+    #[cfg(any(target_os = "linux", test))]
+    id: &'static str,
+    title: String,
+    body: String,
+    /// File shown in the file manager when the notification is clicked;
+    /// only notification servers reached directly report clicks.
+    #[cfg(any(all(unix, not(target_os = "macos")), test))]
+    reveal_path: Option<PathBuf>,
+}
+
+impl From<ConversionNotificationSummary> for SystemNotification {
+    fn from(summary: ConversionNotificationSummary) -> Self {
+        Self {
+            #[cfg(any(target_os = "linux", test))]
+            id: CONVERSION_FINISHED_NOTIFICATION_ID,
+            title: summary.title().to_string(),
+            body: summary.body(),
+            #[cfg(any(all(unix, not(target_os = "macos")), test))]
+            reveal_path: None,
+        }
+    }
+}
+
+impl From<FilesNotOpenedSummary> for SystemNotification {
+    fn from(summary: FilesNotOpenedSummary) -> Self {
+        Self {
+            #[cfg(any(target_os = "linux", test))]
+            id: FILES_NOT_OPENED_NOTIFICATION_ID,
+            title: summary.title().to_string(),
+            body: summary.body(),
+            #[cfg(any(all(unix, not(target_os = "macos")), test))]
+            reveal_path: None,
+        }
+    }
+}
+
+impl From<TaskNotification> for SystemNotification {
+    fn from(notification: TaskNotification) -> Self {
+        Self {
+            #[cfg(any(target_os = "linux", test))]
+            id: TASK_FINISHED_NOTIFICATION_ID,
+            title: notification.title().to_string(),
+            body: notification.body(),
+            #[cfg(any(all(unix, not(target_os = "macos")), test))]
+            reveal_path: notification.output_path().map(Path::to_path_buf),
+        }
+    }
+}
+
+fn send_system_notification(notification: SystemNotification) {
     if let Err(error) = thread::Builder::new()
         .name("frame-notification".to_string())
-        .spawn(move || deliver_system_conversion_finished_notification(summary))
+        .spawn(move || deliver_system_notification(&notification))
     {
-        eprintln!("Failed to spawn conversion notification: {error}");
+        eprintln!("Failed to spawn notification: {error}");
     }
 }
 
 #[cfg(target_os = "linux")]
-fn deliver_system_conversion_finished_notification(summary: ConversionNotificationSummary) {
+fn deliver_system_notification(notification: &SystemNotification) {
     let runtime = if runtime_environment::is_flatpak() {
         LinuxRuntime::Flatpak
     } else {
@@ -153,8 +425,8 @@ fn deliver_system_conversion_finished_notification(summary: ConversionNotificati
 
     match deliver_linux_notification(
         runtime,
-        || show_portal_conversion_finished_notification(summary),
-        || show_direct_conversion_finished_notification(summary),
+        || show_portal_notification(notification),
+        || show_direct_notification(notification),
     ) {
         LinuxDeliveryOutcome::Portal => {}
         LinuxDeliveryOutcome::FreedesktopFallback { portal_error } => {
@@ -164,7 +436,7 @@ fn deliver_system_conversion_finished_notification(summary: ConversionNotificati
         }
         LinuxDeliveryOutcome::PortalFailedInFlatpak { portal_error } => {
             eprintln!(
-                "Failed to show conversion notification through the desktop portal: {portal_error}; runtime=flatpak; direct fallback disabled"
+                "Failed to show notification through the desktop portal: {portal_error}; runtime=flatpak; direct fallback disabled"
             );
         }
         LinuxDeliveryOutcome::BothFailed {
@@ -172,29 +444,26 @@ fn deliver_system_conversion_finished_notification(summary: ConversionNotificati
             fallback_error,
         } => {
             eprintln!(
-                "Failed to show conversion notification: portal error: {portal_error}; fallback error: {fallback_error}"
+                "Failed to show notification: portal error: {portal_error}; fallback error: {fallback_error}"
             );
         }
     }
 }
 
 #[cfg(target_os = "linux")]
-fn show_portal_conversion_finished_notification(
-    summary: ConversionNotificationSummary,
-) -> Result<(), PortalError> {
-    let body = summary.body();
+fn show_portal_notification(notification: &SystemNotification) -> Result<(), PortalError> {
     let flatpak_id = std::env::var("FLATPAK_ID").ok();
     let icon_names = portal_icon_names(flatpak_id.as_deref());
 
     smol::block_on(async move {
         let proxy = NotificationProxy::new().await?;
-        let notification = PortalNotification::new(summary.title())
-            .body(body.as_str())
+        let portal_notification = PortalNotification::new(&notification.title)
+            .body(notification.body.as_str())
             .priority(PortalPriority::Normal)
             .icon(Icon::with_names(icon_names));
 
         proxy
-            .add_notification(CONVERSION_FINISHED_NOTIFICATION_ID, notification)
+            .add_notification(notification.id, portal_notification)
             .await
     })
 }
@@ -262,40 +531,62 @@ fn portal_icon_names(flatpak_id: Option<&str>) -> Vec<String> {
 }
 
 #[cfg(not(target_os = "macos"))]
-fn show_direct_conversion_finished_notification(
-    summary: ConversionNotificationSummary,
-) -> notify_rust::error::Result<()> {
-    Notification::new()
+fn show_direct_notification(notification: &SystemNotification) -> notify_rust::error::Result<()> {
+    let mut direct = Notification::new();
+    direct
         .appname(FRAME_APP_NAME)
-        .summary(summary.title())
-        .body(&summary.body())
+        .summary(&notification.title)
+        .body(&notification.body)
         .icon(FRAME_NOTIFICATION_ICON)
-        .timeout(Timeout::Default)
-        .show()?;
+        .timeout(Timeout::Default);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(path) = &notification.reveal_path {
+        direct.action(DEFAULT_NOTIFICATION_ACTION, "Show in Folder");
+        direct.show()?.wait_for_action(|action| {
+            if action == DEFAULT_NOTIFICATION_ACTION {
+                reveal_in_file_manager(path);
+            }
+        });
+        return Ok(());
+    }
 
+    direct.show()?;
     Ok(())
 }
 
+/// Opens the folder holding `path`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal_in_file_manager(path: &Path) {
+    let folder = path.parent().unwrap_or(path);
+    if let Err(error) = std::process::Command::new("xdg-open").arg(folder).status() {
+        eprintln!(
+            "Failed to show {} in the file manager: {error}",
+            path.display()
+        );
+    }
+}
+
 #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-fn deliver_system_conversion_finished_notification(summary: ConversionNotificationSummary) {
-    if let Err(error) = show_direct_conversion_finished_notification(summary) {
-        eprintln!("Failed to show conversion notification: {error}");
+fn deliver_system_notification(notification: &SystemNotification) {
+    if let Err(error) = show_direct_notification(notification) {
+        eprintln!("Failed to show notification: {error}");
     }
 }
 
 #[cfg(target_os = "macos")]
-fn deliver_system_conversion_finished_notification(summary: ConversionNotificationSummary) {
+fn deliver_system_notification(notification: &SystemNotification) {
     initialize_macos_notification_application();
 
     if let Err(error) = Notification::new()
         .appname(FRAME_APP_NAME)
-        .summary(summary.title())
-        .body(&summary.body())
+        .summary(&notification.title)
+        .body(&notification.body)
         .icon(FRAME_NOTIFICATION_ICON)
         .timeout(Timeout::Default)
         .schedule_raw(macos_delivery_timestamp())
     {
-        eprintln!("Failed to show conversion notification: {error}");
+        eprintln!("Failed to show notification: {error}");
     }
 }
 
@@ -356,6 +647,7 @@ mod tests {
             Some(ConversionNotificationSummary {
                 completed_count: 1,
                 error_count: 1,
+                elapsed: None,
             })
         );
     }
@@ -386,6 +678,7 @@ mod tests {
             let summary = ConversionNotificationSummary {
                 completed_count,
                 error_count,
+                elapsed: None,
             };
 
             assert_eq!(summary.title(), "Queue Finished");
@@ -511,4 +804,94 @@ mod tests {
     fn portal_notification_id_is_stable() {
         assert_eq!(CONVERSION_FINISHED_NOTIFICATION_ID, "conversion-finished");
     }
+
+    #[test]
+    fn files_not_opened_summary_names_the_first_file_and_counts_the_rest() {
+        let summary = |names: &[&str]| FilesNotOpenedSummary {
+            file_names: names.iter().map(ToString::to_string).collect(),
+            reason: "not a supported media file".to_string(),
+        };
+
+        assert_eq!(summary(&["notes.txt"]).title(), "Could Not Open File");
+        assert_eq!(
+            summary(&["notes.txt"]).body(),
+            "notes.txt: not a supported media file"
+        );
+        assert_eq!(
+            summary(&["a.txt", "b.txt"]).body(),
+            "a.txt and 1 other file: not a supported media file"
+        );
+        assert_eq!(
+            summary(&["a.txt", "b.txt", "c.txt"]).body(),
+            "a.txt and 2 other files: not a supported media file"
+        );
+        assert_eq!(
+            SystemNotification::from(summary(&["a.txt", "b.txt"])),
+            SystemNotification {
+                id: FILES_NOT_OPENED_NOTIFICATION_ID,
+                title: "Could Not Open Files".to_string(),
+                body: "a.txt and 1 other file: not a supported media file".to_string(),
+                reveal_path: None,
+            }
+        );
+    }
+
+    #[test]
+    fn notification_modes_pick_task_or_queue_notifications() {
+        assert!(NotificationMode::PerTask.notifies_task(false));
+        assert!(NotificationMode::FailuresOnly.notifies_task(true));
+        assert!(!NotificationMode::FailuresOnly.notifies_task(false));
+        assert!(!NotificationMode::QueueComplete.notifies_task(true));
+        assert!(NotificationMode::QueueComplete.notifies_queue());
+        assert!(!NotificationMode::Off.notifies_task(true));
+        assert!(!NotificationMode::Off.notifies_queue());
+        assert_eq!(NotificationMode::default(), NotificationMode::QueueComplete);
+        assert_eq!(
+            serde_json::to_value(NotificationMode::FailuresOnly).expect("mode should serialize"),
+            serde_json::json!("failuresOnly")
+        );
+    }
+
+    #[test]
+    fn queue_summary_reports_the_total_time() {
+        let summary = ConversionNotificationSummary::from_counts(3, 1)
+            .expect("summary should exist")
+            .with_elapsed(Duration::from_secs(252));
+
+        assert_eq!(
+            summary.body(),
+            "Processed 4 files with 1 error in 4 min 12 s."
+        );
+    }
+
+    #[test]
+    fn task_notifications_describe_the_output_or_the_failure() {
+        let completed = TaskNotification {
+            file_name: "clip.mov".to_string(),
+            elapsed: Some(Duration::from_secs(72)),
+            outcome: TaskNotificationOutcome::Completed {
+                output_path: PathBuf::from("/tmp/out/clip.mp4"),
+                output_size_bytes: Some(3 * 1024 * 1024),
+            },
+        };
+        let failed = TaskNotification {
+            file_name: "clip.mov".to_string(),
+            elapsed: None,
+            outcome: TaskNotificationOutcome::Failed {
+                reason: "The output drive is full".to_string(),
+            },
+        };
+
+        assert_eq!(completed.title(), "Conversion Finished");
+        assert_eq!(completed.body(), "clip.mov · 1 min 12 s · 3 MB");
+        assert_eq!(
+            SystemNotification::from(completed).reveal_path,
+            Some(PathBuf::from("/tmp/out/clip.mp4"))
+        );
+        assert_eq!(failed.title(), "Conversion Failed");
+        assert_eq!(failed.body(), "clip.mov: The output drive is full");
+        assert_eq!(SystemNotification::from(failed).reveal_path, None);
+        assert_eq!(format_elapsed(Duration::from_secs(45)), "45 s");
+        assert_eq!(format_elapsed(Duration::from_secs(7500)), "2 h 5 min");
+    }
 }
@@ -0,0 +1,277 @@
+//! Preset files shared between installs. Export writes the custom presets;
+//! import reads them back, skipping the ones this version cannot convert
+//! with, and reports why.
+//!
+//! Each preset is read on its own and missing config fields take their
+//! defaults, so files written before a field was added still import.
+
+use std::{fs, path::Path};
+
+use frame_core::args::validate_conversion_settings;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_persistence::{AppPersistenceError, write_bytes_atomically},
+    conversion_runner::core_config_from_gpui,
+    settings::{ConversionConfig, PresetDefinition},
+};
+
+/// Version written to exported preset files.
+pub const PRESET_FILE_VERSION: u32 = 1;
+/// Extension offered for exported preset files.
+pub const PRESET_FILE_EXTENSION: &str = "json";
+/// File name suggested when presets are exported.
+pub const DEFAULT_PRESET_FILE_NAME: &str = "frame-presets.json";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PresetFile<'a> {
+    version: u32,
+    presets: Vec<ExportedPreset<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedPreset<'a> {
+    name: &'a str,
+    config: &'a ConversionConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ImportedPresetFile {
+    presets: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportedPreset {
+    #[serde(default)]
+    name: String,
+    config: ConversionConfig,
+}
+
+/// A preset left out of an import.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedPreset {
+    /// Name of the preset, or its position in the file when it has none.
+    pub name: String,
+    pub reason: String,
+}
+
+/// Presets read from a file, ready to be given ids and added to the
+/// library.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PresetImport {
+    /// Names and configs of the presets to add; ids are left empty.
+    pub presets: Vec<PresetDefinition>,
+    pub skipped: Vec<SkippedPreset>,
+}
+
+impl PresetImport {
+    /// One-line account of the import for the presets notice.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let imported = match self.presets.len() {
+            1 => "Imported 1 preset".to_string(),
+            count => format!("Imported {count} presets"),
+        };
+        if self.skipped.is_empty() {
+            return imported;
+        }
+        let skipped = self
+            .skipped
+            .iter()
+            .map(|skipped| format!("{} ({})", skipped.name, skipped.reason))
+            .collect::<Vec<_>>()
+            .join("; ");
+        format!("{imported}; skipped {skipped}")
+    }
+}
+
+/// Writes the custom presets among `presets` to `path` and returns how many
+/// were written. Built-in presets ship with every install and are left out.
+///
+/// # Errors
+///
+/// Returns an error when the file cannot be written.
+pub fn export_presets(
+    path: &Path,
+    presets: &[PresetDefinition],
+) -> Result<usize, AppPersistenceError> {
+    let file = PresetFile {
+        version: PRESET_FILE_VERSION,
+        presets: presets
+            .iter()
+            .filter(|preset| !preset.built_in)
+            .map(|preset| ExportedPreset {
+                name: &preset.name,
+                config: &preset.config,
+            })
+            .collect(),
+    };
+    let bytes = serde_json::to_vec_pretty(&file)?;
+    write_bytes_atomically(path, &bytes)?;
+    Ok(file.presets.len())
+}
+
+/// Reads the presets exported to `path`; see [`parse_preset_file`].
+///
+/// # Errors
+///
+/// Returns an error when the file cannot be read or is not a preset file.
+pub fn import_presets(
+    path: &Path,
+    existing: &[PresetDefinition],
+) -> Result<PresetImport, AppPersistenceError> {
+    let bytes = fs::read(path)?;
+    Ok(parse_preset_file(&bytes, existing)?)
+}
+
+/// Reads the presets of an exported file.
+///
+/// A preset is skipped when it cannot be read, has no name, shares its name
+/// with one in `existing` or earlier in the file, or breaks the container
+/// and codec rules conversions are checked against.
+///
+/// # Errors
+///
+/// Returns an error when `bytes` is not a JSON preset file.
+pub fn parse_preset_file(
+    bytes: &[u8],
+    existing: &[PresetDefinition],
+) -> Result<PresetImport, serde_json::Error> {
+    let file: ImportedPresetFile = serde_json::from_slice(bytes)?;
+    let mut import = PresetImport::default();
+    for (index, value) in file.presets.into_iter().enumerate() {
+        let fallback_name = format!("Preset {}", index + 1);
+        let preset = match serde_json::from_value::<ImportedPreset>(value) {
+            Ok(preset) => preset,
+            Err(error) => {
+                import.skipped.push(SkippedPreset {
+                    name: fallback_name,
+                    reason: format!("unreadable: {error}"),
+                });
+                continue;
+            }
+        };
+        let name = preset.name.trim().to_string();
+        let reason = if name.is_empty() {
+            Some("it has no name".to_string())
+        } else if existing
+            .iter()
+            .chain(&import.presets)
+            .any(|other| other.name.eq_ignore_ascii_case(&name))
+        {
+            Some("a preset with this name already exists".to_string())
+        } else {
+            validate_conversion_settings(&core_config_from_gpui(&preset.config))
+                .err()
+                .map(|error| error.to_string())
+        };
+        match reason {
+            Some(reason) => import.skipped.push(SkippedPreset {
+                name: if name.is_empty() { fallback_name } else { name },
+                reason,
+            }),
+            None => {
+                import
+                    .presets
+                    .push(PresetDefinition::custom(String::new(), name, preset.config));
+            }
+        }
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::default_presets;
+
+    #[test]
+    fn exported_presets_import_back_without_built_ins() {
+        let mut presets = default_presets();
+        presets.push(PresetDefinition::custom(
+            "custom-preset-1".to_string(),
+            "Archive HEVC".to_string(),
+            ConversionConfig {
+                container: "mkv".to_string(),
+                video_codec: "libx265".to_string(),
+                ..ConversionConfig::default()
+            },
+        ));
+        let path =
+            std::env::temp_dir().join(format!("frame-preset-export-{}.json", std::process::id()));
+
+        let written = export_presets(&path, &presets).expect("presets should export");
+        let import = import_presets(&path, &[]).expect("presets should import");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(written, 1);
+        assert_eq!(import.presets.len(), 1);
+        assert_eq!(import.presets[0].name, "Archive HEVC");
+        assert_eq!(import.presets[0].config.video_codec, "libx265");
+        assert!(!import.presets[0].built_in);
+        assert!(import.skipped.is_empty());
+    }
+
+    #[test]
+    fn presets_missing_newer_fields_import_with_defaults() {
+        let import = parse_preset_file(
+            br#"{"version":1,"presets":[{"name":"Podcast","config":{
+                "container":"mp3","audioCodec":"mp3","audioBitrate":"96"
+            }}]}"#,
+            &[],
+        )
+        .expect("file should parse");
+
+        assert_eq!(import.presets.len(), 1);
+        assert_eq!(import.presets[0].config.audio_bitrate, "96");
+        assert_eq!(
+            import.presets[0].config.resolution,
+            ConversionConfig::default().resolution
+        );
+    }
+
+    #[test]
+    fn invalid_duplicate_and_unreadable_presets_are_skipped_with_reasons() {
+        let existing = default_presets();
+        let import = parse_preset_file(
+            br#"{"presets":[
+                {"name":"Broken WebM","config":{"container":"webm","videoCodec":"libx264"}},
+                {"name":"discord","config":{"container":"mp4"}},
+                {"name":"No config"},
+                {"name":"  ","config":{}},
+                {"name":"Share","config":{"container":"mp4"}},
+                {"name":"SHARE","config":{"container":"mp4"}}
+            ]}"#,
+            &existing,
+        )
+        .expect("file should parse");
+
+        assert_eq!(import.presets.len(), 1);
+        assert_eq!(import.presets[0].name, "Share");
+        let skipped: Vec<&str> = import
+            .skipped
+            .iter()
+            .map(|skipped| skipped.name.as_str())
+            .collect();
+        assert_eq!(
+            skipped,
+            ["Broken WebM", "discord", "Preset 3", "Preset 4", "SHARE"]
+        );
+        assert!(import.skipped[1].reason.contains("already exists"));
+        assert!(import.skipped[2].reason.starts_with("unreadable"));
+        assert!(
+            import
+                .summary()
+                .starts_with("Imported 1 preset; skipped Broken WebM (")
+        );
+    }
+
+    #[test]
+    fn files_that_are_not_json_fail_to_import() {
+        assert!(parse_preset_file(b"not presets", &[]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Progress of the running batch as one line. It is shown in the window
+//! title, which the taskbar or dock keeps visible while Frame is minimized.
+
+use std::time::Duration;
+
+use crate::{
+    app_info::FRAME_APP_NAME,
+    file_queue::{FileQueue, FileStatus},
+    numeric::u64_to_f64,
+};
+
+/// Share of a batch that must be done before the time left is estimated.
+const MIN_ESTIMATE_PROGRESS: f64 = 0.02;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueueStatus {
+    /// Tasks that completed, failed or were cancelled.
+    pub settled: usize,
+    pub total: usize,
+    /// Share of the batch done, from 0 to 1, with unfinished tasks counted
+    /// by their progress.
+    pub progress: f64,
+    /// Whether every unfinished task is paused.
+    pub paused: bool,
+}
+
+/// Status of the tasks in `task_ids`, or `None` when none of them is in
+/// the queue.
+#[must_use]
+pub fn queue_status_for_task_ids(queue: &FileQueue, task_ids: &[String]) -> Option<QueueStatus> {
+    let mut settled = 0;
+    let mut total = 0;
+    let mut done = 0.0;
+    let mut paused = true;
+    for file in queue
+        .files()
+        .iter()
+        .filter(|file| task_ids.contains(&file.id))
+    {
+        total += 1;
+        match file.status {
+            FileStatus::Completed | FileStatus::Error | FileStatus::Idle => {
+                settled += 1;
+                done += 1.0;
+            }
+            FileStatus::Paused => done += f64::from(file.progress_percent) / 100.0,
+            FileStatus::Queued | FileStatus::Converting | FileStatus::Cancelling => {
+                paused = false;
+                done += f64::from(file.progress_percent) / 100.0;
+            }
+        }
+    }
+    if total == 0 {
+        return None;
+    }
+    Some(QueueStatus {
+        settled,
+        total,
+        progress: done / u64_to_f64(total as u64),
+        paused: paused && settled < total,
+    })
+}
+
+impl QueueStatus {
+    /// Tasks still queued or running.
+    #[must_use]
+    pub const fn unsettled(&self) -> usize {
+        self.total - self.settled
+    }
+
+    /// Time left if the batch keeps the pace it had over `elapsed`; `None`
+    /// until enough of it is done to tell.
+    #[must_use]
+    pub fn remaining(&self, elapsed: Duration) -> Option<Duration> {
+        if self.paused || self.progress < MIN_ESTIMATE_PROGRESS || self.progress >= 1.0 {
+            return None;
+        }
+        Duration::try_from_secs_f64(elapsed.as_secs_f64() * (1.0 - self.progress) / self.progress)
+            .ok()
+    }
+
+    /// One-line account such as `3 of 7 done, ~41 min left`.
+    #[must_use]
+    pub fn summary(&self, elapsed: Duration) -> String {
+        let done = format!("{} of {} done", self.settled, self.total);
+        if self.paused {
+            return format!("{done}, paused");
+        }
+        match self.remaining(elapsed) {
+            Some(remaining) => format!("{done}, {}", format_time_left(remaining)),
+            None => done,
+        }
+    }
+}
+
+/// Window title: the app name, followed by the batch summary while one runs.
+#[must_use]
+pub fn queue_window_title(summary: Option<&str>) -> String {
+    match summary {
+        Some(summary) => format!("{FRAME_APP_NAME} — {summary}"),
+        None => FRAME_APP_NAME.to_string(),
+    }
+}
+
+fn format_time_left(remaining: Duration) -> String {
+    let minutes = remaining.as_secs().div_ceil(60);
+    match minutes {
+        0 | 1 => "under a minute left".to_string(),
+        2..60 => format!("~{minutes} min left"),
+        _ => format!("~{} h {} min left", minutes / 60, minutes % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_queue::FileItem;
+
+    fn queue_with(statuses: &[(&str, FileStatus, u8)]) -> FileQueue {
+        let mut queue = FileQueue::new();
+        for (id, status, progress) in statuses {
+            queue.add_file(FileItem::from_path(*id, format!("/tmp/{id}.mp4"), 1024));
+            queue.update_status(id, *status, *progress);
+        }
+        queue
+    }
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn running_tasks_count_by_their_progress() {
+        let queue = queue_with(&[
+            ("first", FileStatus::Completed, 100),
+            ("second", FileStatus::Converting, 50),
+            ("third", FileStatus::Queued, 0),
+            ("other", FileStatus::Converting, 10),
+        ]);
+
+        let status = queue_status_for_task_ids(&queue, &ids(&["first", "second", "third"]))
+            .expect("batch should have a status");
+
+        assert_eq!(status.settled, 1);
+        assert_eq!(status.total, 3);
+        assert_eq!(status.unsettled(), 2);
+        assert!((status.progress - 0.5).abs() < 1e-9);
+        assert!(!status.paused);
+        assert_eq!(queue_status_for_task_ids(&queue, &[]), None);
+    }
+
+    #[test]
+    fn time_left_follows_the_pace_so_far() {
+        let status = QueueStatus {
+            settled: 3,
+            total: 7,
+            progress: 0.5,
+            paused: false,
+        };
+
+        assert_eq!(
+            status.summary(Duration::from_secs(41 * 60)),
+            "3 of 7 done, ~41 min left"
+        );
+        assert_eq!(
+            status.summary(Duration::from_secs(90 * 60)),
+            "3 of 7 done, ~1 h 30 min left"
+        );
+        assert_eq!(
+            status.summary(Duration::from_secs(20)),
+            "3 of 7 done, under a minute left"
+        );
+    }
+
+    #[test]
+    fn paused_and_barely_started_batches_have_no_estimate() {
+        let queue = queue_with(&[
+            ("first", FileStatus::Paused, 40),
+            ("second", FileStatus::Completed, 100),
+        ]);
+        let paused = queue_status_for_task_ids(&queue, &ids(&["first", "second"]))
+            .expect("batch should have a status");
+        let starting = QueueStatus {
+            settled: 0,
+            total: 4,
+            progress: 0.01,
+            paused: false,
+        };
+
+        assert_eq!(
+            paused.summary(Duration::from_secs(600)),
+            "1 of 2 done, paused"
+        );
+        assert_eq!(starting.remaining(Duration::from_secs(600)), None);
+        assert_eq!(starting.summary(Duration::from_secs(600)), "0 of 4 done");
+    }
+
+    #[test]
+    fn window_title_shows_the_summary_only_while_a_batch_runs() {
+        assert_eq!(queue_window_title(None), "Frame");
+        assert_eq!(
+            queue_window_title(Some("1 of 2 done")),
+            "Frame — 1 of 2 done"
+        );
+    }
+}
@@ -2,7 +2,9 @@
 
 use std::{
     env,
+    ffi::OsStr,
     path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
 };
 
 pub const BINARIES_RESOURCE_DIR: &str = "resources/binaries";
@@ -31,26 +33,78 @@ const SETUP_TARGET_TRIPLE: Option<&str> = Some("x86_64-pc-windows-msvc");
 )))]
 const SETUP_TARGET_TRIPLE: Option<&str> = None;
 
+/// Tools chosen in the app settings, used instead of the bundled ones.
+static CONFIGURED_MEDIA_TOOLS: RwLock<Option<MediaToolPaths>> = RwLock::new(None);
+
+/// `FFmpeg` and `FFprobe` binaries picked by the user, e.g. a distribution
+/// build with encoders the bundled one lacks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MediaToolPaths {
+    pub ffmpeg: PathBuf,
+    pub ffprobe: PathBuf,
+}
+
+impl MediaToolPaths {
+    /// Tools for `ffmpeg`, with `FFprobe` expected in the same directory.
+    #[must_use]
+    pub fn beside_ffmpeg(ffmpeg: PathBuf) -> Self {
+        let ffprobe = ffprobe_beside(&ffmpeg);
+        Self { ffmpeg, ffprobe }
+    }
+}
+
+/// Makes `tools` the binaries every conversion, probe and detection runs;
+/// `None` returns to the bundled ones. The environment overrides still
+/// take precedence.
+pub fn set_configured_media_tools(tools: Option<MediaToolPaths>) {
+    *CONFIGURED_MEDIA_TOOLS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = tools;
+}
+
+#[must_use]
+pub fn configured_media_tools() -> Option<MediaToolPaths> {
+    CONFIGURED_MEDIA_TOOLS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
 #[must_use]
 pub fn ffmpeg_executable() -> String {
-    resolve_tool_executable(FFMPEG_ENV_VAR, "ffmpeg")
+    let configured = configured_media_tools().map(|tools| tools.ffmpeg);
+    resolve_tool_executable(FFMPEG_ENV_VAR, "ffmpeg", configured.as_deref())
 }
 
 #[must_use]
 pub fn ffprobe_executable() -> String {
-    resolve_tool_executable(FFPROBE_ENV_VAR, "ffprobe")
+    let configured = configured_media_tools().map(|tools| tools.ffprobe);
+    resolve_tool_executable(FFPROBE_ENV_VAR, "ffprobe", configured.as_deref())
 }
 
-fn resolve_tool_executable(env_var: &str, tool_name: &str) -> String {
+fn resolve_tool_executable(env_var: &str, tool_name: &str, configured: Option<&Path>) -> String {
     let env_value = env::var(env_var).ok();
-    resolve_tool_executable_with_mode(env_value.as_deref(), tool_name, use_system_media_tools())
+    resolve_tool_executable_with_mode(
+        env_value.as_deref(),
+        configured,
+        tool_name,
+        use_system_media_tools(),
+    )
 }
 
+/// A configured binary is used even when it has since gone missing, so the
+/// failure shows instead of another `FFmpeg` silently taking its place.
 fn resolve_tool_executable_with_mode(
     env_value: Option<&str>,
+    configured: Option<&Path>,
     tool_name: &str,
     system_media_tools: bool,
 ) -> String {
+    let configured = configured.map(path_to_string);
+    let env_value = env_value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .or(configured.as_deref());
     if system_media_tools {
         return resolved_executable(env_value, tool_name, &[]);
     }
@@ -118,6 +172,35 @@ fn binary_candidates(file_name: &str) -> Vec<PathBuf> {
     candidates
 }
 
+/// Directory the running application is installed in: the `.app` bundle on
+/// macOS, otherwise the executable's directory.
+#[must_use]
+pub fn app_install_directory() -> Option<PathBuf> {
+    let current_exe = env::current_exe().ok()?;
+    let exe_dir = current_exe.parent()?;
+    let bundle = exe_dir.ancestors().find(|ancestor| {
+        ancestor
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("app"))
+    });
+    Some(bundle.unwrap_or(exe_dir).to_path_buf())
+}
+
+/// `FFprobe` expected next to `ffmpeg`, named the same way, e.g.
+/// `ffprobe-7.exe` for `ffmpeg-7.exe`.
+#[must_use]
+pub fn ffprobe_beside(ffmpeg: &Path) -> PathBuf {
+    let file_name = ffmpeg
+        .file_name()
+        .map(OsStr::to_string_lossy)
+        .filter(|name| name.contains("ffmpeg"))
+        .map_or_else(
+            || format!("ffprobe{}", executable_extension()),
+            |name| name.replacen("ffmpeg", "ffprobe", 1),
+        );
+    ffmpeg.with_file_name(file_name)
+}
+
 fn path_to_string(path: &Path) -> String {
     path.to_string_lossy().into_owned()
 }
@@ -186,15 +269,50 @@ mod tests {
     #[test]
     fn system_media_tools_mode_skips_bundled_candidates() {
         assert_eq!(
-            resolve_tool_executable_with_mode(None, "ffmpeg", true),
+            resolve_tool_executable_with_mode(None, None, "ffmpeg", true),
             "ffmpeg"
         );
         assert_eq!(
-            resolve_tool_executable_with_mode(Some(" /custom/ffmpeg "), "ffmpeg", true),
+            resolve_tool_executable_with_mode(Some(" /custom/ffmpeg "), None, "ffmpeg", true),
             "/custom/ffmpeg"
         );
     }
 
+    #[test]
+    fn configured_tools_replace_bundled_ones_below_env_overrides() {
+        let configured = Path::new("/does/not/exist/ffmpeg");
+
+        assert_eq!(
+            resolve_tool_executable_with_mode(None, Some(configured), "ffmpeg", false),
+            "/does/not/exist/ffmpeg"
+        );
+        assert_eq!(
+            resolve_tool_executable_with_mode(
+                Some("/custom/ffmpeg"),
+                Some(configured),
+                "ffmpeg",
+                false
+            ),
+            "/custom/ffmpeg"
+        );
+    }
+
+    #[test]
+    fn ffprobe_is_expected_next_to_the_chosen_ffmpeg() {
+        assert_eq!(
+            ffprobe_beside(Path::new("/usr/bin/ffmpeg")),
+            PathBuf::from("/usr/bin/ffprobe")
+        );
+        assert_eq!(
+            ffprobe_beside(Path::new("/opt/jellyfin-ffmpeg/ffmpeg-7")),
+            PathBuf::from("/opt/jellyfin-ffmpeg/ffprobe-7")
+        );
+        assert_eq!(
+            ffprobe_beside(Path::new("/opt/tools/avconv")),
+            PathBuf::from(format!("/opt/tools/ffprobe{}", executable_extension()))
+        );
+    }
+
     #[test]
     fn binary_candidates_include_macos_bundle_resource_path() {
         let candidates = binary_candidates("ffmpeg-test");
@@ -19,6 +19,7 @@ pub const DEFAULT_IMAGE_WEBP_PRESET: &str = "default";
 pub const DEFAULT_IMAGE_PNG_COMPRESSION: u32 = 9;
 pub const DEFAULT_IMAGE_PNG_PREDICTION: &str = "paeth";
 pub const DEFAULT_IMAGE_TIFF_COMPRESSION: &str = "packbits";
+pub const DEFAULT_IMAGE_AVIF_CRF: u32 = 30;
 pub const DEFAULT_GIF_COLORS: u16 = 256;
 pub const DEFAULT_GIF_DITHER: &str = "sierra2_4a";
 pub const DEFAULT_GIF_LOOP: u16 = 0;
@@ -48,6 +49,7 @@ pub(super) const MAX_IMAGE_JPEG_QUALITY: u32 = 100;
 pub(super) const MAX_IMAGE_WEBP_QUALITY: u32 = 100;
 pub(super) const MAX_IMAGE_WEBP_COMPRESSION: u32 = 6;
 pub(super) const MAX_IMAGE_PNG_COMPRESSION: u32 = 9;
+pub(super) const MAX_IMAGE_AVIF_CRF: u32 = 63;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SettingsTab {
@@ -144,6 +146,15 @@ pub enum DeinterlaceMode {
     On,
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SeekMode {
+    #[default]
+    Fast,
+    Accurate,
+    Hybrid,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct VideoColorFiltersConfig {
@@ -626,6 +637,28 @@ pub struct ConversionConfig {
     pub audio_filters: AudioFiltersConfig,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
+    /// Ranges kept and joined in order; replaces the start and end times
+    /// when set.
+    pub segments: Vec<TrimSegment>,
+    /// Ranges cut out of the source before the rest is joined.
+    pub remove_segments: Vec<TrimSegment>,
+    /// Writes one output per source chapter.
+    pub split_by_chapters: bool,
+    /// Writes every frame of an image output into a folder.
+    pub image_sequence_output: bool,
+    /// Re-encodes the partial GOPs at copy-mode cut points so trims are
+    /// frame accurate.
+    pub smart_cut: bool,
+    /// Where trims seek: before the input, after it, or both.
+    pub seek_mode: SeekMode,
+    /// Number of chunks the video is encoded in concurrently.
+    pub parallel_chunks: Option<u32>,
+    /// Decodes the finished output to check it before the task completes.
+    pub verify_output: bool,
+    /// `FFmpeg` arguments added by hand before the source input.
+    pub extra_input_args: Vec<String>,
+    /// `FFmpeg` arguments added by hand before the output path.
+    pub extra_output_args: Vec<String>,
     pub metadata: MetadataConfig,
     pub subtitle_burn_path: Option<String>,
     pub subtitle_font_name: Option<String>,
@@ -645,6 +678,8 @@ pub struct ConversionConfig {
     pub custom_height: Option<String>,
     pub scaling_algorithm: String,
     pub fps: String,
+    /// Rate image sequence frames are read at; `None` uses 25 fps.
+    pub input_framerate: Option<String>,
     pub crf: u8,
     pub quality: u32,
     pub preset: String,
@@ -658,6 +693,9 @@ pub struct ConversionConfig {
     pub image_png_compression: u32,
     pub image_png_prediction: String,
     pub image_tiff_compression: String,
+    pub image_avif_crf: u32,
+    /// Largest JPEG or lossy WebP output size in kilobytes.
+    pub image_target_max_kb: Option<u64>,
     pub gif_colors: u16,
     pub gif_dither: String,
     pub gif_loop: u16,
@@ -665,6 +703,9 @@ pub struct ConversionConfig {
     pub nvenc_temporal_aq: bool,
     pub videotoolbox_allow_sw: bool,
     pub hw_decode: bool,
+    /// Device the hardware encoder binds to; see
+    /// [`frame_core::hw_devices::HwDevice::config_value`].
+    pub hw_device: Option<String>,
 }
 
 impl Default for ConversionConfig {
@@ -686,6 +727,16 @@ impl Default for ConversionConfig {
             audio_filters: AudioFiltersConfig::default(),
             start_time: None,
             end_time: None,
+            segments: Vec::new(),
+            remove_segments: Vec::new(),
+            split_by_chapters: false,
+            image_sequence_output: false,
+            smart_cut: false,
+            seek_mode: SeekMode::default(),
+            parallel_chunks: None,
+            verify_output: false,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
             metadata: MetadataConfig::default(),
             subtitle_burn_path: None,
             subtitle_font_name: None,
@@ -705,6 +756,7 @@ impl Default for ConversionConfig {
             custom_height: None,
             scaling_algorithm: DEFAULT_SCALING_ALGORITHM.to_string(),
             fps: DEFAULT_FPS.to_string(),
+            input_framerate: None,
             crf: DEFAULT_CRF,
             quality: DEFAULT_QUALITY,
             preset: DEFAULT_PRESET.to_string(),
@@ -718,6 +770,8 @@ impl Default for ConversionConfig {
             image_png_compression: DEFAULT_IMAGE_PNG_COMPRESSION,
             image_png_prediction: DEFAULT_IMAGE_PNG_PREDICTION.to_string(),
             image_tiff_compression: DEFAULT_IMAGE_TIFF_COMPRESSION.to_string(),
+            image_avif_crf: DEFAULT_IMAGE_AVIF_CRF,
+            image_target_max_kb: None,
             gif_colors: DEFAULT_GIF_COLORS,
             gif_dither: DEFAULT_GIF_DITHER.to_string(),
             gif_loop: DEFAULT_GIF_LOOP,
@@ -725,10 +779,18 @@ impl Default for ConversionConfig {
             nvenc_temporal_aq: false,
             videotoolbox_allow_sw: false,
             hw_decode: false,
+            hw_device: None,
         }
     }
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrimSegment {
+    pub start: String,
+    pub end: String,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct CropSettings {
@@ -895,6 +957,9 @@ pub struct AudioTrack {
     pub label: Option<String>,
     pub bitrate_kbps: Option<f64>,
     pub sample_rate: Option<String>,
+    pub channel_layout: Option<String>,
+    pub is_default: bool,
+    pub is_forced: bool,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -903,6 +968,9 @@ pub struct SubtitleTrack {
     pub codec: String,
     pub language: Option<String>,
     pub label: Option<String>,
+    pub is_default: bool,
+    pub is_forced: bool,
+    pub is_bitmap: bool,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -11,7 +11,7 @@ use super::{
         OutputModeOption, PresetDefinition, PresetOption, ProcessingMode, RESOLUTION_OPTIONS,
         SCALING_ALGORITHM_OPTIONS, SUBTITLE_FONT_SIZES, SUBTITLE_POSITIONS, SourceKind,
         SourceMetadata, SubtitleFontOption, SubtitleFontSizeOption, SubtitlePosition,
-        SubtitlePositionOption, SubtitleTrackOption, VIDEO_CODEC_DEFINITIONS,
+        SubtitlePositionOption, SubtitleTrack, SubtitleTrackOption, VIDEO_CODEC_DEFINITIONS,
         VIDEO_PIXEL_FORMAT_DEFINITIONS, VIDEO_PRESETS, VideoCodecCapability, VideoCodecOption,
         VideoPixelFormatOption, VideoPresetOption,
     },
@@ -160,10 +160,7 @@ pub fn subtitle_track_options(
                     index: track.index,
                     index_label: format!("#{}", track.index),
                     codec: display_source_value(Some(&track.codec)),
-                    detail: subtitle_track_detail(
-                        track.language.as_deref(),
-                        track.label.as_deref(),
-                    ),
+                    detail: subtitle_track_detail(track),
                     is_selected: config.selected_subtitle_tracks.contains(&track.index),
                     is_disabled: disabled,
                 })
@@ -454,6 +451,21 @@ pub fn default_presets() -> Vec<PresetDefinition> {
     ]
 }
 
+/// Built-in presets followed by the custom ones that do not reuse a
+/// built-in id.
+#[must_use]
+pub fn merged_presets(custom_presets: Vec<PresetDefinition>) -> Vec<PresetDefinition> {
+    let mut presets = default_presets();
+
+    for preset in custom_presets {
+        if !presets.iter().any(|existing| existing.id == preset.id) {
+            presets.push(preset);
+        }
+    }
+
+    presets
+}
+
 #[must_use]
 pub fn preset_options(
     config: &ConversionConfig,
@@ -859,11 +871,22 @@ fn youtube_preset_config(
     }
 }
 
-fn subtitle_track_detail(language: Option<&str>, label: Option<&str>) -> String {
-    [language, label]
+fn subtitle_track_detail(track: &SubtitleTrack) -> String {
+    let flags = [
+        (track.is_bitmap, "Bitmap"),
+        (track.is_default, "Default"),
+        (track.is_forced, "Forced"),
+    ];
+
+    [track.language.as_deref(), track.label.as_deref()]
         .into_iter()
         .flatten()
         .filter(|value| !value.trim().is_empty())
+        .chain(
+            flags
+                .into_iter()
+                .filter_map(|(is_set, label)| is_set.then_some(label)),
+        )
         .collect::<Vec<_>>()
         .join(" • ")
 }
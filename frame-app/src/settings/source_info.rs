@@ -283,7 +283,14 @@ fn source_audio_track_rows(track: &AudioTrack) -> Vec<SourceInfoRow> {
 pub(super) fn audio_track_detail(track: &AudioTrack) -> String {
     let mut parts = Vec::new();
     if let Some(channels) = track.channels.as_deref().filter(|value| !value.is_empty()) {
-        parts.push(format!("{channels} channels"));
+        match track
+            .channel_layout
+            .as_deref()
+            .filter(|value| !value.is_empty())
+        {
+            Some(layout) => parts.push(format!("{channels} channels ({layout})")),
+            None => parts.push(format!("{channels} channels")),
+        }
     }
     if let Some(language) = track.language.as_deref().filter(|value| !value.is_empty()) {
         parts.push(language.to_string());
@@ -294,6 +301,12 @@ pub(super) fn audio_track_detail(track: &AudioTrack) -> String {
     if track.bitrate_kbps.is_some_and(|bitrate| bitrate > 0.0) {
         parts.push(format_source_bitrate_kbps(track.bitrate_kbps));
     }
+    if track.is_default {
+        parts.push("Default".to_string());
+    }
+    if track.is_forced {
+        parts.push("Forced".to_string());
+    }
 
     if parts.is_empty() {
         "Source track".to_string()
@@ -125,7 +125,7 @@ mod output_options {
     fn visible_output_containers_for_images_match_original_image_and_gif_set() {
         assert_eq!(
             visible_output_containers(Some(&image_metadata())),
-            vec!["gif", "png", "jpg", "webp", "bmp", "tiff"]
+            vec!["gif", "png", "jpg", "webp", "bmp", "tiff", "avif"]
         );
     }
 
@@ -290,6 +290,25 @@ mod audio_track_options {
         assert_eq!(options[0].detail, "2 channels • eng • Main • 192 kb/s");
     }
 
+    #[test]
+    fn track_detail_includes_channel_layout_and_dispositions() {
+        let metadata = SourceMetadata {
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                codec: "eac3".to_string(),
+                channels: Some("6".to_string()),
+                channel_layout: Some("5.1(side)".to_string()),
+                is_default: true,
+                ..AudioTrack::default()
+            }],
+            ..SourceMetadata::default()
+        };
+
+        let options = audio_track_options(&ConversionConfig::default(), Some(&metadata), false);
+
+        assert_eq!(options[0].detail, "6 channels (5.1(side)) • Default");
+    }
+
     #[test]
     fn propagates_disabled_state_to_all_tracks() {
         let options = audio_track_options(
@@ -335,12 +354,18 @@ mod subtitle_options {
                     codec: "subrip".to_string(),
                     language: Some("eng".to_string()),
                     label: Some("Dialogue".to_string()),
+                    is_default: false,
+                    is_forced: false,
+                    is_bitmap: false,
                 },
                 SubtitleTrack {
                     index: 3,
                     codec: "ass".to_string(),
                     language: Some("jpn".to_string()),
                     label: Some("Signs".to_string()),
+                    is_default: false,
+                    is_forced: false,
+                    is_bitmap: false,
                 },
             ],
             ..SourceMetadata::default()
@@ -382,6 +407,25 @@ mod subtitle_options {
         assert_eq!(options[0].detail, "eng • Dialogue");
     }
 
+    #[test]
+    fn subtitle_track_options_mark_bitmap_and_forced_tracks() {
+        let metadata = SourceMetadata {
+            subtitle_tracks: vec![SubtitleTrack {
+                index: 4,
+                codec: "hdmv_pgs_subtitle".to_string(),
+                language: Some("eng".to_string()),
+                is_bitmap: true,
+                is_forced: true,
+                ..SubtitleTrack::default()
+            }],
+            ..SourceMetadata::default()
+        };
+
+        let options = subtitle_track_options(&ConversionConfig::default(), Some(&metadata), false);
+
+        assert_eq!(options[0].detail, "eng • Bitmap • Forced");
+    }
+
     #[test]
     fn toggle_subtitle_track_selection_removes_selected_track() {
         let mut config = ConversionConfig {
@@ -524,6 +568,32 @@ mod preset_options {
         assert_eq!(config.container, "mp3");
         assert_eq!(config.audio_codec, "mp3");
     }
+
+    #[test]
+    fn container_defaults_keep_the_source_specific_settings() {
+        let defaults = ConversionConfig {
+            container: "webm".to_string(),
+            video_codec: "vp9".to_string(),
+            audio_codec: "libopus".to_string(),
+            crf: 31,
+            start_time: Some("00:00:05".to_string()),
+            ..ConversionConfig::default()
+        };
+        let mut config = ConversionConfig {
+            container: "webm".to_string(),
+            start_time: Some("00:01:00".to_string()),
+            selected_audio_tracks: vec![1],
+            ..ConversionConfig::default()
+        };
+
+        assert!(apply_container_defaults(&mut config, &defaults, None));
+
+        assert_eq!(config.video_codec, "vp9");
+        assert_eq!(config.crf, 31);
+        assert_eq!(config.start_time.as_deref(), Some("00:01:00"));
+        assert_eq!(config.selected_audio_tracks, [1]);
+        assert!(!apply_container_defaults(&mut config, &defaults, None));
+    }
 }
 
 mod metadata_options {
@@ -919,6 +989,7 @@ mod video_options {
         assert_eq!(config.image_png_compression, 9);
         assert_eq!(config.image_png_prediction, "paeth");
         assert_eq!(config.image_tiff_compression, "packbits");
+        assert_eq!(config.image_avif_crf, 30);
         assert_eq!(config.gif_colors, 256);
         assert_eq!(config.gif_dither, "sierra2_4a");
         assert_eq!(config.gif_loop, 0);
@@ -973,6 +1044,23 @@ mod video_options {
         assert_eq!(config.pixel_format, "auto");
     }
 
+    #[test]
+    fn hw_device_is_kept_only_for_encoders_that_take_a_device() {
+        let mut config = ConversionConfig {
+            video_codec: "hevc_nvenc".to_string(),
+            ..ConversionConfig::default()
+        };
+
+        assert!(apply_hw_device(&mut config, Some("1")));
+        assert!(!apply_hw_device(&mut config, Some("1")));
+        assert_eq!(config.hw_device.as_deref(), Some("1"));
+
+        config.video_codec = "libx264".to_string();
+        assert!(!apply_hw_device(&mut config, Some("0")));
+        assert!(normalize_video_config(&mut config, None));
+        assert_eq!(config.hw_device, None);
+    }
+
     #[test]
     fn normalize_video_config_for_gif_forces_original_gif_contract() {
         let mut config = ConversionConfig {
@@ -1075,6 +1163,7 @@ mod image_encoding {
             image_png_compression: 12,
             image_png_prediction: "adaptive".to_string(),
             image_tiff_compression: "zip".to_string(),
+            image_avif_crf: 80,
             ..ConversionConfig::default()
         };
 
@@ -1088,6 +1177,21 @@ mod image_encoding {
         assert_eq!(config.image_png_compression, 9);
         assert_eq!(config.image_png_prediction, "paeth");
         assert_eq!(config.image_tiff_compression, "packbits");
+        assert_eq!(config.image_avif_crf, 63);
+    }
+
+    #[test]
+    fn image_sequence_output_is_limited_to_still_frame_formats() {
+        let mut config = ConversionConfig {
+            container: "png".to_string(),
+            ..ConversionConfig::default()
+        };
+
+        assert!(apply_image_sequence_output(&mut config, true));
+        config.container = "gif".to_string();
+        assert!(normalize_video_config(&mut config, None));
+        assert!(!config.image_sequence_output);
+        assert!(!apply_image_sequence_output(&mut config, true));
     }
 }
 
@@ -1,3 +1,6 @@
+use frame_core::hw_devices::hw_device_kind;
+use frame_core::image_sequence::supports_sequence_output;
+
 use super::{
     model::{
         AUDIO_CHANNEL_DEFINITIONS, AUDIO_CODEC_DEFINITIONS, AudioQualityRange, ConversionConfig,
@@ -7,7 +10,7 @@ use super::{
         DEFAULT_PIXEL_FORMAT, DEFAULT_RESOLUTION, DEFAULT_VIDEO_BITRATE_MODE, FPS_OPTIONS,
         GIF_DITHER_OPTIONS, GIF_FPS_OPTIONS, IMAGE_JPEG_HUFFMAN_OPTIONS,
         IMAGE_PNG_PREDICTION_OPTIONS, IMAGE_TIFF_COMPRESSION_OPTIONS, IMAGE_WEBP_PRESET_OPTIONS,
-        MAX_AUDIO_VOLUME, MAX_GIF_COLORS, MAX_GIF_LOOP, MAX_IMAGE_JPEG_QUALITY,
+        MAX_AUDIO_VOLUME, MAX_GIF_COLORS, MAX_GIF_LOOP, MAX_IMAGE_AVIF_CRF, MAX_IMAGE_JPEG_QUALITY,
         MAX_IMAGE_PNG_COMPRESSION, MAX_IMAGE_WEBP_COMPRESSION, MAX_IMAGE_WEBP_QUALITY,
         MetadataField, MetadataMode, PresetDefinition, ProcessingMode, RESOLUTION_OPTIONS,
         SCALING_ALGORITHM_OPTIONS, SUBTITLE_FONT_SIZES, SourceKind, SourceMetadata,
@@ -280,6 +283,39 @@ pub fn apply_preset(
     before != *config
 }
 
+/// Replaces `config` with the defaults saved for its new container,
+/// keeping what belongs to the source: trims, tracks, crop, metadata and
+/// the burned subtitle file.
+pub fn apply_container_defaults(
+    config: &mut ConversionConfig,
+    defaults: &ConversionConfig,
+    metadata: Option<&SourceMetadata>,
+) -> bool {
+    let before = config.clone();
+    let source = std::mem::replace(config, defaults.clone());
+    config.container.clone_from(&source.container);
+    copy_source_settings(&source, config);
+    normalize_output_config(config, metadata);
+
+    before != *config
+}
+
+/// Copies the settings that belong to one source rather than to an output
+/// format: trims, tracks, crop, metadata and the burned subtitle file.
+pub fn copy_source_settings(from: &ConversionConfig, to: &mut ConversionConfig) {
+    to.start_time.clone_from(&from.start_time);
+    to.end_time.clone_from(&from.end_time);
+    to.segments.clone_from(&from.segments);
+    to.remove_segments.clone_from(&from.remove_segments);
+    to.selected_audio_tracks
+        .clone_from(&from.selected_audio_tracks);
+    to.selected_subtitle_tracks
+        .clone_from(&from.selected_subtitle_tracks);
+    to.subtitle_burn_path.clone_from(&from.subtitle_burn_path);
+    to.crop.clone_from(&from.crop);
+    to.metadata.clone_from(&from.metadata);
+}
+
 pub fn apply_resolution(config: &mut ConversionConfig, resolution: &str) -> bool {
     let resolution = resolution.to_ascii_lowercase();
     if !RESOLUTION_OPTIONS.contains(&resolution.as_str()) {
@@ -582,6 +618,27 @@ pub fn apply_image_tiff_compression(config: &mut ConversionConfig, compression:
     true
 }
 
+pub fn apply_image_avif_crf(config: &mut ConversionConfig, crf: u32) -> bool {
+    let crf = crf.min(MAX_IMAGE_AVIF_CRF);
+    if config.image_avif_crf == crf {
+        return false;
+    }
+
+    config.image_avif_crf = crf;
+    true
+}
+
+pub fn apply_image_sequence_output(config: &mut ConversionConfig, enabled: bool) -> bool {
+    if config.image_sequence_output == enabled
+        || (enabled && !supports_sequence_output(&config.container))
+    {
+        return false;
+    }
+
+    config.image_sequence_output = enabled;
+    true
+}
+
 pub fn apply_nvenc_spatial_aq(config: &mut ConversionConfig, enabled: bool) -> bool {
     if !is_nvenc_video_codec(&config.video_codec) || config.nvenc_spatial_aq == enabled {
         return false;
@@ -619,6 +676,17 @@ pub fn apply_hw_decode(config: &mut ConversionConfig, enabled: bool) -> bool {
     true
 }
 
+/// Binds the hardware encoder to `device`, or lets `FFmpeg` pick with
+/// `None`. Ignored for encoders that take no device.
+pub fn apply_hw_device(config: &mut ConversionConfig, device: Option<&str>) -> bool {
+    if hw_device_kind(&config.video_codec).is_none() || config.hw_device.as_deref() == device {
+        return false;
+    }
+
+    config.hw_device = device.map(str::to_string);
+    true
+}
+
 pub fn apply_processing_mode(
     config: &mut ConversionConfig,
     metadata: Option<&SourceMetadata>,
@@ -773,6 +841,7 @@ pub fn normalize_video_config(
         config.video_codec = "gif".to_string();
         config.video_bitrate_mode = DEFAULT_VIDEO_BITRATE_MODE.to_string();
         config.hw_decode = false;
+        config.hw_device = None;
         config.nvenc_spatial_aq = false;
         config.nvenc_temporal_aq = false;
         config.videotoolbox_allow_sw = false;
@@ -805,6 +874,9 @@ pub fn normalize_video_config(
     if !is_hardware_video_codec(&config.video_codec) {
         config.hw_decode = false;
     }
+    if hw_device_kind(&config.video_codec).is_none() {
+        config.hw_device = None;
+    }
 
     normalize_image_encoding_settings(config);
 
@@ -856,6 +928,11 @@ fn normalize_image_encoding_settings(config: &mut ConversionConfig) {
     ) {
         config.image_tiff_compression = DEFAULT_IMAGE_TIFF_COMPRESSION.to_string();
     }
+
+    config.image_avif_crf = config.image_avif_crf.min(MAX_IMAGE_AVIF_CRF);
+    if !supports_sequence_output(&config.container) {
+        config.image_sequence_output = false;
+    }
 }
 
 fn reset_audio_filter_settings(config: &mut ConversionConfig) {
@@ -885,6 +962,7 @@ fn reset_video_filter_settings(config: &mut ConversionConfig) {
     config.flip_vertical = false;
     config.crop = None;
     config.hw_decode = false;
+    config.hw_device = None;
     config.nvenc_spatial_aq = false;
     config.nvenc_temporal_aq = false;
     config.videotoolbox_allow_sw = false;
@@ -0,0 +1,386 @@
+//! One Frame per user. The first launch listens on a loopback port recorded
+//! in the config directory; later launches hand their file arguments to it
+//! and exit, so "Open with Frame" adds files to the running window.
+//!
+//! Requests carry a token read from the same record, so other local
+//! programs that find the port cannot add files.
+
+use std::{
+    collections::hash_map::RandomState,
+    ffi::OsString,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_persistence::{AppPersistence, write_private_bytes_atomically};
+
+/// File in the config directory recording the running instance.
+pub const INSTANCE_FILE_NAME: &str = "instance.json";
+
+/// How long a launch waits for the running instance before starting its own.
+const FORWARD_TIMEOUT: Duration = Duration::from_millis(750);
+/// Largest request the running instance reads.
+const MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+const ACKNOWLEDGEMENT: &str = "ok";
+
+#[derive(Deserialize, Serialize)]
+struct InstanceRecord {
+    port: u16,
+    token: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ForwardRequest {
+    token: String,
+    paths: Vec<PathBuf>,
+}
+
+/// Outcome of [`claim_instance`].
+pub enum InstanceClaim {
+    /// This launch is the running instance; files opened later arrive here.
+    Primary(ForwardedFiles),
+    /// Another instance took the files; this launch should exit.
+    Forwarded,
+}
+
+/// Files handed to the running instance by later launches and by the
+/// platform's open-file events. An empty request still asks for the window
+/// to be brought to the front.
+pub struct ForwardedFiles {
+    sender: Sender<Vec<PathBuf>>,
+    receiver: Receiver<Vec<PathBuf>>,
+}
+
+impl ForwardedFiles {
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+
+    /// Handle for sources that deliver files from other threads or
+    /// callbacks.
+    #[must_use]
+    pub fn sender(&self) -> Sender<Vec<PathBuf>> {
+        self.sender.clone()
+    }
+
+    /// Paths of every request received since the last call, or `None` when
+    /// there was no request.
+    #[must_use]
+    pub fn try_take(&self) -> Option<Vec<PathBuf>> {
+        let mut requested = None::<Vec<PathBuf>>;
+        while let Ok(paths) = self.receiver.try_recv() {
+            requested.get_or_insert_default().extend(paths);
+        }
+        requested
+    }
+}
+
+impl Default for ForwardedFiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Claims the instance in the platform config directory for a launch asked
+/// to open `paths`. Returns `None` when a running instance took them.
+///
+/// When no claim can be made, this launch runs on its own.
+#[must_use]
+pub fn claim_platform_instance(paths: Vec<PathBuf>) -> Option<ForwardedFiles> {
+    let directory = AppPersistence::platform()
+        .ok()
+        .and_then(|persistence| persistence.settings_path().parent().map(Path::to_path_buf));
+    let claim = directory.map(|directory| claim_instance(&directory, &paths));
+    let files = match claim {
+        Some(Ok(InstanceClaim::Forwarded)) => return None,
+        Some(Ok(InstanceClaim::Primary(files))) => files,
+        Some(Err(error)) => {
+            eprintln!("Failed to set up single-instance handling: {error}");
+            ForwardedFiles::new()
+        }
+        None => ForwardedFiles::new(),
+    };
+    if !paths.is_empty() {
+        let _ = files.sender.send(paths);
+    }
+    Some(files)
+}
+
+/// Hands `paths` to the instance recorded in `directory`, or becomes that
+/// instance when none answers.
+///
+/// # Errors
+///
+/// Returns an error when the loopback listener cannot be started or the
+/// record cannot be written.
+pub fn claim_instance(directory: &Path, paths: &[PathBuf]) -> io::Result<InstanceClaim> {
+    let record_path = directory.join(INSTANCE_FILE_NAME);
+    if let Some(record) = read_record(&record_path)
+        && forward_paths(&record, paths).is_ok()
+    {
+        return Ok(InstanceClaim::Forwarded);
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let record = InstanceRecord {
+        port: listener.local_addr()?.port(),
+        token: new_token(),
+    };
+    write_private_bytes_atomically(&record_path, &serde_json::to_vec(&record)?)?;
+
+    let files = ForwardedFiles::new();
+    let sender = files.sender();
+    thread::Builder::new()
+        .name("frame-single-instance".to_string())
+        .spawn(move || serve_forward_requests(&listener, &record.token, &sender))?;
+    Ok(InstanceClaim::Primary(files))
+}
+
+/// Paths among the command-line arguments of a launch, made absolute so the
+/// running instance, which has its own working directory, finds them.
+///
+/// macOS process serial numbers (`-psn_…`) are dropped; other arguments
+/// starting with `-` are kept only when a file by that name exists.
+#[must_use]
+pub fn launch_paths(args: impl IntoIterator<Item = OsString>) -> Vec<PathBuf> {
+    args.into_iter()
+        .filter(|arg| is_launch_path(arg))
+        .map(PathBuf::from)
+        .map(|path| std::path::absolute(&path).unwrap_or(path))
+        .collect()
+}
+
+fn is_launch_path(arg: &OsString) -> bool {
+    let text = arg.to_string_lossy();
+    if text.is_empty() || text.starts_with("-psn_") {
+        return false;
+    }
+    !text.starts_with('-') || Path::new(arg).exists()
+}
+
+/// Path of a `file://` URL, as delivered by the platform's open-file
+/// events; `None` for other URLs.
+#[must_use]
+pub fn file_url_to_path(url: &str) -> Option<PathBuf> {
+    let rest = url.strip_prefix("file://")?;
+    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !path.starts_with('/') {
+        return None;
+    }
+    let decoded = String::from_utf8(percent_decode(path)?).ok()?;
+    #[cfg(windows)]
+    let decoded = decoded
+        .strip_prefix('/')
+        .map(str::to_string)
+        .unwrap_or(decoded);
+    Some(PathBuf::from(decoded))
+}
+
+fn percent_decode(value: &str) -> Option<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value.get(index + 1..index + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    Some(decoded)
+}
+
+fn read_record(path: &Path) -> Option<InstanceRecord> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn forward_paths(record: &InstanceRecord, paths: &[PathBuf]) -> io::Result<()> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, record.port));
+    let mut stream = TcpStream::connect_timeout(&address, FORWARD_TIMEOUT)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.set_write_timeout(Some(FORWARD_TIMEOUT))?;
+
+    let mut request = serde_json::to_vec(&ForwardRequest {
+        token: record.token.clone(),
+        paths: paths.to_vec(),
+    })?;
+    request.push(b'\n');
+    stream.write_all(&request)?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    if response.trim_end() == ACKNOWLEDGEMENT {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the running instance did not accept the files",
+        ))
+    }
+}
+
+fn serve_forward_requests(listener: &TcpListener, token: &str, sender: &Sender<Vec<PathBuf>>) {
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let Ok(request) = read_forward_request(&stream) else {
+            continue;
+        };
+        if request.token != token {
+            continue;
+        }
+        if sender.send(request.paths).is_err() {
+            return;
+        }
+        let _ = writeln!(stream, "{ACKNOWLEDGEMENT}");
+    }
+}
+
+fn read_forward_request(stream: &TcpStream) -> io::Result<ForwardRequest> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(stream.take(MAX_REQUEST_BYTES)).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+fn new_token() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    format!(
+        "{:016x}{:016x}",
+        hasher.finish(),
+        RandomState::new().hash_one(std::process::id())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "frame-single-instance-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    #[test]
+    fn file_urls_decode_spaces_and_unicode() {
+        assert_eq!(
+            file_url_to_path("file:///Users/me/My%20Clips/z%C3%BCrich.mov"),
+            Some(PathBuf::from("/Users/me/My Clips/zürich.mov"))
+        );
+        assert_eq!(
+            file_url_to_path("file://localhost/tmp/a.mp4"),
+            Some(PathBuf::from("/tmp/a.mp4"))
+        );
+        assert_eq!(file_url_to_path("https://example.com/a.mp4"), None);
+        assert_eq!(file_url_to_path("file:///tmp/bad%2"), None);
+    }
+
+    #[test]
+    fn launch_paths_skip_flags_and_become_absolute() {
+        let paths = launch_paths([
+            OsString::from("-psn_0_12345"),
+            OsString::from("clip one.mp4"),
+            OsString::from("/tmp/vidéo.mkv"),
+        ]);
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].is_absolute());
+        assert!(paths[0].ends_with("clip one.mp4"));
+        assert_eq!(paths[1], PathBuf::from("/tmp/vidéo.mkv"));
+    }
+
+    #[test]
+    fn launch_paths_keep_existing_files_that_start_with_a_dash() {
+        let directory = unique_test_dir("dash");
+        fs::create_dir_all(&directory).expect("test dir should be created");
+        let clip = directory.join("-clip.mp4");
+        fs::write(&clip, b"").expect("clip should be written");
+
+        let paths = launch_paths([
+            clip.clone().into_os_string(),
+            OsString::from("--no-such-flag"),
+        ]);
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(paths, vec![clip]);
+    }
+
+    #[test]
+    fn second_launch_forwards_its_paths_to_the_first() {
+        let directory = unique_test_dir("forward");
+        let InstanceClaim::Primary(files) =
+            claim_instance(&directory, &[]).expect("first launch should claim")
+        else {
+            panic!("first launch should become the running instance");
+        };
+        let forwarded = vec![PathBuf::from("/tmp/My Clips/zürich.mov")];
+
+        let claim = claim_instance(&directory, &forwarded).expect("second launch should claim");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(matches!(claim, InstanceClaim::Forwarded));
+        assert_eq!(files.try_take(), Some(forwarded));
+        assert_eq!(files.try_take(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn instance_record_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = unique_test_dir("private");
+        let claim = claim_instance(&directory, &[]).expect("first launch should claim");
+        let mode = fs::metadata(directory.join(INSTANCE_FILE_NAME))
+            .expect("record should be written")
+            .permissions()
+            .mode();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(matches!(claim, InstanceClaim::Primary(_)));
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn requests_with_the_wrong_token_are_ignored() {
+        let directory = unique_test_dir("token");
+        let InstanceClaim::Primary(files) =
+            claim_instance(&directory, &[]).expect("first launch should claim")
+        else {
+            panic!("first launch should become the running instance");
+        };
+        let mut record =
+            read_record(&directory.join(INSTANCE_FILE_NAME)).expect("record should be written");
+        record.token = "wrong".to_string();
+
+        let result = forward_paths(&record, &[PathBuf::from("/tmp/a.mp4")]);
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(result.is_err());
+        assert_eq!(files.try_take(), None);
+    }
+}
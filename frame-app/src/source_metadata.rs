@@ -1,19 +1,34 @@
 //! Source metadata state and ffprobe integration for the GPUI app.
 
-use std::{collections::HashMap, process::Command};
+use std::{
+    collections::HashMap,
+    io::Read,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 
 use frame_core::{
-    error::ConversionError,
-    probe::{ffprobe_json_args, parse_ffprobe_stdout},
-    types::{FfprobeTags, ProbeMetadata},
+    error::{ConversionError, ProbeFailure},
+    probe::{
+        classify_probe_failure, explain_unsupported_still, ffprobe_json_args, parse_ffprobe_stdout,
+        source_fingerprint,
+    },
+    types::{FfprobeTags, PreprobedSource, ProbeMetadata, SubtitleFormat},
 };
 
 use crate::{
+    capabilities::cached_ffmpeg_info,
     file_queue::FileQueue,
     runtime_binaries::ffprobe_executable,
     settings::{AudioTrack, SourceKind, SourceMetadata, SourceTags, SubtitleTrack},
 };
 
+/// Longest time a metadata probe may run before `FFprobe` is killed, so a
+/// hung read cannot hold a worker slot.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+const PROBE_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum MetadataStatus {
     #[default]
@@ -33,6 +48,8 @@ pub struct SourceMetadataEntry {
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct SourceMetadataStore {
     entries: HashMap<String, SourceMetadataEntry>,
+    /// Full probe results, handed to conversions so they can skip probing.
+    probes: HashMap<String, PreprobedSource>,
 }
 
 impl SourceMetadataStore {
@@ -55,9 +72,17 @@ impl SourceMetadataStore {
             .and_then(|entry| entry.metadata.as_ref())
     }
 
+    /// Probe result of a source, for queueing its conversion.
+    #[must_use]
+    pub fn preprobed_for(&self, id: &str) -> Option<&PreprobedSource> {
+        self.probes.get(id)
+    }
+
     pub fn mark_loading(&mut self, id: impl Into<String>) {
+        let id = id.into();
+        self.probes.remove(&id);
         self.entries.insert(
-            id.into(),
+            id,
             SourceMetadataEntry {
                 status: MetadataStatus::Loading,
                 metadata: None,
@@ -67,8 +92,10 @@ impl SourceMetadataStore {
     }
 
     pub fn mark_ready(&mut self, id: impl Into<String>, metadata: SourceMetadata) {
+        let id = id.into();
+        self.probes.remove(&id);
         self.entries.insert(
-            id.into(),
+            id,
             SourceMetadataEntry {
                 status: MetadataStatus::Ready,
                 metadata: Some(metadata),
@@ -77,9 +104,22 @@ impl SourceMetadataStore {
         );
     }
 
+    /// Marks a source ready from its probe, keeping the probe for its
+    /// conversion.
+    pub fn mark_probed(&mut self, id: impl Into<String>, source: PreprobedSource) {
+        let id = id.into();
+        self.mark_ready(
+            id.clone(),
+            source_metadata_from_probe(source.metadata.clone()),
+        );
+        self.probes.insert(id, source);
+    }
+
     pub fn mark_error(&mut self, id: impl Into<String>, error: impl Into<String>) {
+        let id = id.into();
+        self.probes.remove(&id);
         self.entries.insert(
-            id.into(),
+            id,
             SourceMetadataEntry {
                 status: MetadataStatus::Error,
                 metadata: None,
@@ -90,6 +130,7 @@ impl SourceMetadataStore {
 
     pub fn remove(&mut self, id: &str) {
         self.entries.remove(id);
+        self.probes.remove(id);
     }
 }
 
@@ -117,6 +158,9 @@ pub fn source_metadata_from_probe(probe: ProbeMetadata) -> SourceMetadata {
                 label: track.label,
                 bitrate_kbps: track.bitrate_kbps,
                 sample_rate: track.sample_rate,
+                channel_layout: track.channel_layout,
+                is_default: track.is_default,
+                is_forced: track.is_forced,
             })
             .collect(),
         subtitle_tracks: probe
@@ -127,6 +171,9 @@ pub fn source_metadata_from_probe(probe: ProbeMetadata) -> SourceMetadata {
                 codec: track.codec,
                 language: track.language,
                 label: track.label,
+                is_default: track.is_default,
+                is_forced: track.is_forced,
+                is_bitmap: track.format == Some(SubtitleFormat::Bitmap),
             })
             .collect(),
         tags: probe.tags.map(source_tags_from_probe),
@@ -138,44 +185,124 @@ pub fn source_metadata_from_probe(probe: ProbeMetadata) -> SourceMetadata {
     }
 }
 
-/// Probes source metadata with the bundled ffprobe executable.
+/// Probes a source with the bundled ffprobe executable.
 ///
 /// # Errors
 ///
-/// Returns an error when ffprobe cannot be executed, exits unsuccessfully, or
+/// Returns an error when the file cannot be read, ffprobe cannot be
+/// executed, exits unsuccessfully, exceeds [`DEFAULT_PROBE_TIMEOUT`], or
 /// emits metadata that cannot be parsed.
-pub fn probe_source_metadata(file_path: &str) -> Result<SourceMetadata, ConversionError> {
+pub fn probe_source(file_path: &str) -> Result<PreprobedSource, ConversionError> {
     let executable = ffprobe_executable();
-    probe_source_metadata_with_executable(file_path, &executable)
+    probe_source_with_executable(file_path, &executable, DEFAULT_PROBE_TIMEOUT)
 }
 
-/// Probes source metadata with a specific ffprobe executable.
+/// Probes a source with a specific ffprobe executable.
+///
+/// The fingerprint is read before probing, so a file changed during the
+/// probe is probed again by its conversion.
 ///
 /// # Errors
 ///
-/// Returns an error when the executable cannot be launched, exits with a
-/// non-zero status, or emits invalid probe JSON.
-pub fn probe_source_metadata_with_executable(
+/// Returns an error when the file cannot be read, the executable cannot be
+/// launched, exits with a non-zero status, exceeds `timeout`, or emits
+/// invalid probe JSON.
+pub fn probe_source_with_executable(
     file_path: &str,
     executable: &str,
-) -> Result<SourceMetadata, ConversionError> {
-    let output = Command::new(executable)
-        .args(ffprobe_json_args(file_path))
-        .output()
+    timeout: Duration,
+) -> Result<PreprobedSource, ConversionError> {
+    let fingerprint = source_fingerprint(file_path).map_err(ConversionError::Io)?;
+    let stdout = run_ffprobe(executable, &ffprobe_json_args(file_path), timeout)
+        .map_err(|error| explain_probe_error(file_path, error))?;
+    let metadata = parse_ffprobe_stdout(file_path, stdout)?;
+    for warning in &metadata.warnings {
+        eprintln!("ffprobe metadata for {file_path}: {warning}");
+    }
+    Ok(PreprobedSource {
+        metadata,
+        fingerprint,
+    })
+}
+
+/// Names HEIC/HEIF and camera RAW sources in probe failures, which `FFprobe`
+/// otherwise reports as unrecognized data.
+#[must_use]
+pub fn explain_probe_error(file_path: &str, error: ConversionError) -> ConversionError {
+    match error {
+        ConversionError::ProbeFailed(failure) => {
+            ConversionError::ProbeFailed(explain_unsupported_still(
+                failure,
+                file_path,
+                cached_ffmpeg_info().map(|info| info.supports_heif_input()),
+            ))
+        }
+        error => error,
+    }
+}
+
+/// Runs `executable` with `args` and returns its stdout.
+///
+/// Non-zero exits and timeouts are reported as [`ConversionError::ProbeFailed`]
+/// with the failure category read from stderr.
+///
+/// # Errors
+///
+/// Returns an error when the executable cannot be launched, exits with a
+/// non-zero status, or is still running after `timeout`.
+pub fn run_ffprobe(
+    executable: &str,
+    args: &[String],
+    timeout: Duration,
+) -> Result<String, ConversionError> {
+    let mut child = Command::new(executable)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(ConversionError::Io)?;
+    let stdout_reader = read_pipe_to_string(child.stdout.take());
+    let stderr_reader = read_pipe_to_string(child.stderr.take());
+
+    let started_at = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started_at.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(ConversionError::ProbeFailed(ProbeFailure::timeout(timeout)));
+            }
+            Ok(None) => thread::sleep(PROBE_POLL_INTERVAL),
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(ConversionError::Io(error));
+            }
+        }
+    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let message = if stderr.trim().is_empty() {
-            format!("ffprobe exited with status {}", output.status)
-        } else {
-            stderr.trim().to_string()
-        };
-        return Err(ConversionError::Probe(message));
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if status.success() {
+        Ok(stdout)
+    } else {
+        Err(ConversionError::ProbeFailed(classify_probe_failure(
+            &stderr,
+            &status.to_string(),
+        )))
     }
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_ffprobe_stdout(file_path, stdout).map(source_metadata_from_probe)
+fn read_pipe_to_string(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
 }
 
 fn source_kind_from_probe(kind: &str) -> Option<SourceKind> {
@@ -239,12 +366,20 @@ mod tests {
                     label: Some("Main".to_string()),
                     bitrate_kbps: Some(192.0),
                     sample_rate: Some("48000".to_string()),
+                    channel_layout: Some("stereo".to_string()),
+                    is_default: true,
+                    ..ProbeAudioTrack::default()
                 }],
                 ..ProbeMetadata::default()
             });
 
             assert_eq!(metadata.audio_tracks[0].label.as_deref(), Some("Main"));
             assert_eq!(metadata.audio_tracks[0].channels.as_deref(), Some("2"));
+            assert_eq!(
+                metadata.audio_tracks[0].channel_layout.as_deref(),
+                Some("stereo")
+            );
+            assert!(metadata.audio_tracks[0].is_default);
         }
 
         #[test]
@@ -255,11 +390,16 @@ mod tests {
                     codec: "subrip".to_string(),
                     language: Some("eng".to_string()),
                     label: Some("Captions".to_string()),
+                    format: Some(SubtitleFormat::Text),
+                    is_forced: true,
+                    ..ProbeSubtitleTrack::default()
                 }],
                 ..ProbeMetadata::default()
             });
 
             assert_eq!(metadata.subtitle_tracks[0].codec, "subrip");
+            assert!(metadata.subtitle_tracks[0].is_forced);
+            assert!(!metadata.subtitle_tracks[0].is_bitmap);
             assert_eq!(
                 metadata.subtitle_tracks[0].label.as_deref(),
                 Some("Captions")
@@ -342,6 +482,29 @@ mod tests {
             assert_eq!(entry.error.as_deref(), Some("probe failed"));
         }
 
+        #[test]
+        fn mark_probed_keeps_the_probe_until_the_source_is_probed_again() {
+            let mut store = SourceMetadataStore::default();
+            let source = PreprobedSource {
+                metadata: ProbeMetadata {
+                    media_kind: "video".to_string(),
+                    video_codec: Some("hevc".to_string()),
+                    ..ProbeMetadata::default()
+                },
+                fingerprint: frame_core::types::SourceFingerprint {
+                    size_bytes: 2048,
+                    modified: None,
+                },
+            };
+
+            store.mark_probed("file-1", source.clone());
+            let probed = store.preprobed_for("file-1").cloned();
+            store.mark_loading("file-1");
+
+            assert_eq!(probed, Some(source));
+            assert_eq!(store.preprobed_for("file-1"), None);
+        }
+
         #[test]
         fn remove_deletes_entry() {
             let mut store = SourceMetadataStore::default();
@@ -352,4 +515,69 @@ mod tests {
             assert_eq!(store.entry_for("file-1").status, MetadataStatus::Idle);
         }
     }
+
+    #[cfg(unix)]
+    mod run_ffprobe {
+        use super::*;
+        use frame_core::error::ProbeFailureKind;
+
+        fn shell_args(script: &str) -> Vec<String> {
+            vec!["-c".to_string(), script.to_string()]
+        }
+
+        #[test]
+        fn classifies_failed_runs_from_stderr() {
+            let error = run_ffprobe(
+                "sh",
+                &shell_args("echo '/tmp/missing.mkv: No such file or directory' >&2; exit 1"),
+                DEFAULT_PROBE_TIMEOUT,
+            )
+            .expect_err("non-zero exit should fail");
+
+            assert_eq!(
+                error.probe_failure_kind(),
+                Some(ProbeFailureKind::FileNotFound)
+            );
+            assert!(error.to_string().contains("/tmp/missing.mkv"));
+        }
+
+        #[test]
+        fn names_heic_sources_the_build_cannot_decode() {
+            let error = run_ffprobe(
+                "sh",
+                &shell_args(
+                    "echo '/tmp/IMG_0420.HEIC: Invalid data found when processing input' >&2; exit 1",
+                ),
+                DEFAULT_PROBE_TIMEOUT,
+            )
+            .expect_err("non-zero exit should fail");
+
+            let error = explain_probe_error("/tmp/IMG_0420.HEIC", error);
+
+            assert_eq!(
+                error.probe_failure_kind(),
+                Some(ProbeFailureKind::UnsupportedImage)
+            );
+            assert!(error.to_string().contains("HEIC/HEIF"));
+        }
+
+        #[test]
+        fn kills_runs_that_exceed_the_timeout() {
+            let started_at = Instant::now();
+
+            let error = run_ffprobe("sh", &shell_args("sleep 5"), Duration::from_millis(100))
+                .expect_err("slow probe should time out");
+
+            assert_eq!(error.probe_failure_kind(), Some(ProbeFailureKind::Timeout));
+            assert!(started_at.elapsed() < Duration::from_secs(5));
+        }
+
+        #[test]
+        fn returns_stdout_on_success() {
+            let stdout = run_ffprobe("sh", &shell_args("echo '{}'"), DEFAULT_PROBE_TIMEOUT)
+                .expect("successful run should return stdout");
+
+            assert_eq!(stdout.trim(), "{}");
+        }
+    }
 }
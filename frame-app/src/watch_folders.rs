@@ -0,0 +1,386 @@
+//! Watch folders that queue newly exported media with a stored conversion config.
+//!
+//! Folders are polled rather than watched through OS change notifications:
+//! a file is only queued once its size has held still for
+//! [`WATCH_FOLDER_STABLE_DURATION`], which takes repeated checks either way,
+//! and polling behaves the same on network shares, where change
+//! notifications are unreliable.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use frame_core::args::DEFAULT_OUTPUT_SUFFIX;
+use serde::{Deserialize, Serialize};
+
+use crate::{file_filters::is_supported_source_path, settings::ConversionConfig};
+
+pub const WATCH_FOLDER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const WATCH_FOLDER_STABLE_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WatchFolder {
+    pub path: PathBuf,
+    pub output_directory: PathBuf,
+    pub config: ConversionConfig,
+    pub processed_files: Vec<ProcessedWatchFile>,
+}
+
+impl WatchFolder {
+    #[must_use]
+    pub const fn new(path: PathBuf, config: ConversionConfig, output_directory: PathBuf) -> Self {
+        Self {
+            path,
+            output_directory,
+            config,
+            processed_files: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn has_processed(&self, path: &Path, modified_at: u64) -> bool {
+        self.processed_files
+            .iter()
+            .any(|file| file.path == path && file.modified_at == modified_at)
+    }
+
+    pub fn mark_processed(&mut self, path: PathBuf, modified_at: u64) {
+        self.processed_files.retain(|file| file.path != path);
+        self.processed_files
+            .push(ProcessedWatchFile { path, modified_at });
+    }
+
+    /// Drops the processed entries for `paths`, returning whether any were
+    /// dropped.
+    pub fn forget_processed(&mut self, paths: &HashSet<PathBuf>) -> bool {
+        let count = self.processed_files.len();
+        self.processed_files
+            .retain(|file| !paths.contains(&file.path));
+        self.processed_files.len() != count
+    }
+}
+
+/// Processed entries of `folders` whose file no longer exists. Folders
+/// that are themselves missing, such as an unmounted drive, keep theirs.
+#[must_use]
+pub fn missing_processed_files(folders: &[WatchFolder]) -> HashSet<PathBuf> {
+    folders
+        .iter()
+        .filter(|folder| folder.path.is_dir())
+        .flat_map(|folder| &folder.processed_files)
+        .filter(|file| !file.path.exists())
+        .map(|file| file.path.clone())
+        .collect()
+}
+
+/// Marks a finished output as processed in every folder watching the
+/// directory it was written to, so Frame never queues its own outputs.
+/// Returns whether any folder changed.
+pub fn record_watch_folder_output(folders: &mut [WatchFolder], output_path: &Path) -> bool {
+    let Some(modified_at) = fs::metadata(output_path)
+        .ok()
+        .and_then(|metadata| modified_millis(&metadata))
+    else {
+        return false;
+    };
+
+    let mut changed = false;
+    for folder in folders
+        .iter_mut()
+        .filter(|folder| output_path.parent() == Some(folder.path.as_path()))
+    {
+        folder.mark_processed(output_path.to_path_buf(), modified_at);
+        changed = true;
+    }
+    changed
+}
+
+/// A file already queued from a watch folder, keyed by path and modification
+/// time in milliseconds since the Unix epoch.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProcessedWatchFile {
+    pub path: PathBuf,
+    pub modified_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WatchFolderCandidate {
+    pub folder_path: PathBuf,
+    pub file_path: PathBuf,
+    pub modified_at: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct FileObservation {
+    size_bytes: u64,
+    modified_at: u64,
+    stable_since: Instant,
+}
+
+/// Tracks file sizes between polls so files still being written are only
+/// queued once they stop changing.
+#[derive(Debug, Default)]
+pub struct WatchFolderScanner {
+    observations: HashMap<PathBuf, FileObservation>,
+}
+
+impl WatchFolderScanner {
+    /// Files in `folders` that have stopped changing and were not queued
+    /// before. `excluded` holds outputs Frame is still writing.
+    pub fn scan(
+        &mut self,
+        folders: &[WatchFolder],
+        excluded: &HashSet<PathBuf>,
+        now: Instant,
+    ) -> Vec<WatchFolderCandidate> {
+        let mut candidates = Vec::new();
+        let mut seen_paths = HashSet::new();
+
+        for folder in folders {
+            for (file_path, size_bytes, modified_at) in watch_folder_entries(&folder.path) {
+                if excluded.contains(&file_path) || folder.has_processed(&file_path, modified_at) {
+                    continue;
+                }
+                seen_paths.insert(file_path.clone());
+
+                let observation =
+                    self.observations
+                        .entry(file_path.clone())
+                        .or_insert(FileObservation {
+                            size_bytes,
+                            modified_at,
+                            stable_since: now,
+                        });
+                if observation.size_bytes != size_bytes || observation.modified_at != modified_at {
+                    *observation = FileObservation {
+                        size_bytes,
+                        modified_at,
+                        stable_since: now,
+                    };
+                    continue;
+                }
+                if now.duration_since(observation.stable_since) < WATCH_FOLDER_STABLE_DURATION {
+                    continue;
+                }
+
+                self.observations.remove(&file_path);
+                candidates.push(WatchFolderCandidate {
+                    folder_path: folder.path.clone(),
+                    file_path,
+                    modified_at,
+                });
+            }
+        }
+
+        self.observations
+            .retain(|path, _| seen_paths.contains(path));
+        candidates
+    }
+}
+
+fn watch_folder_entries(folder: &Path) -> Vec<(PathBuf, u64, u64)> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    let mut files = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file()
+                || !is_supported_source_path(&path)
+                || is_converted_output_path(&path)
+            {
+                return None;
+            }
+            let modified_at = modified_millis(&metadata)?;
+            Some((path, metadata.len(), modified_at))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|duration| u64::try_from(duration.as_millis()).ok())
+}
+
+/// Matches Frame's default `<stem>_converted` output names, including the
+/// numbered `_converted_2` variants used to avoid collisions.
+#[must_use]
+pub fn is_converted_output_path(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        return false;
+    };
+    let stem = stem
+        .rsplit_once('_')
+        .filter(|(_, suffix)| !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit()))
+        .map_or(stem, |(base, _)| base);
+
+    stem.ends_with(DEFAULT_OUTPUT_SUFFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_SANDBOX: AtomicU64 = AtomicU64::new(0);
+
+    fn sandbox() -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "frame-watch-folder-test-{}-{}",
+            std::process::id(),
+            NEXT_SANDBOX.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("watch folder sandbox should be created");
+        path
+    }
+
+    fn watch_folder(path: &Path) -> WatchFolder {
+        WatchFolder::new(
+            path.to_path_buf(),
+            ConversionConfig::default(),
+            path.join("out"),
+        )
+    }
+
+    #[test]
+    fn scan_waits_until_file_size_is_stable() {
+        let root = sandbox();
+        fs::write(root.join("render.mov"), b"partial").expect("fixture should be written");
+        let folders = [watch_folder(&root)];
+        let mut scanner = WatchFolderScanner::default();
+        let start = Instant::now();
+
+        assert!(scanner.scan(&folders, &HashSet::new(), start).is_empty());
+        let candidates = scanner.scan(
+            &folders,
+            &HashSet::new(),
+            start + WATCH_FOLDER_STABLE_DURATION,
+        );
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].file_path, root.join("render.mov"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn scan_restarts_stability_window_when_file_grows() {
+        let root = sandbox();
+        let file = root.join("render.mov");
+        fs::write(&file, b"partial").expect("fixture should be written");
+        let folders = [watch_folder(&root)];
+        let mut scanner = WatchFolderScanner::default();
+        let start = Instant::now();
+
+        assert!(scanner.scan(&folders, &HashSet::new(), start).is_empty());
+        fs::write(&file, b"partial render grew").expect("fixture should be rewritten");
+
+        assert!(
+            scanner
+                .scan(
+                    &folders,
+                    &HashSet::new(),
+                    start + WATCH_FOLDER_STABLE_DURATION
+                )
+                .is_empty()
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn scan_ignores_processed_non_media_and_converted_outputs() {
+        let root = sandbox();
+        fs::write(root.join("notes.txt"), b"text").expect("fixture should be written");
+        fs::write(root.join("render_converted.mp4"), b"out").expect("fixture should be written");
+        fs::write(root.join("render_converted_2.mp4"), b"out").expect("fixture should be written");
+        fs::write(root.join("done.mov"), b"done").expect("fixture should be written");
+        let mut folder = watch_folder(&root);
+        let (_, _, modified_at) = watch_folder_entries(&root)
+            .into_iter()
+            .find(|(path, _, _)| path.ends_with("done.mov"))
+            .expect("processed fixture should be listed");
+        folder.mark_processed(root.join("done.mov"), modified_at);
+        let folders = [folder];
+        let mut scanner = WatchFolderScanner::default();
+        let start = Instant::now();
+
+        scanner.scan(&folders, &HashSet::new(), start);
+
+        assert!(
+            scanner
+                .scan(
+                    &folders,
+                    &HashSet::new(),
+                    start + WATCH_FOLDER_STABLE_DURATION
+                )
+                .is_empty()
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn scan_skips_outputs_being_written_and_recorded_outputs() {
+        let root = sandbox();
+        let writing = root.join("render.webm");
+        let finished = root.join("render 1080p.mp4");
+        fs::write(&writing, b"out").expect("fixture should be written");
+        fs::write(&finished, b"out").expect("fixture should be written");
+        let mut folders = [watch_folder(&root)];
+        assert!(record_watch_folder_output(&mut folders, &finished));
+        let excluded = HashSet::from([writing]);
+        let mut scanner = WatchFolderScanner::default();
+        let start = Instant::now();
+
+        scanner.scan(&folders, &excluded, start);
+
+        assert!(
+            scanner
+                .scan(&folders, &excluded, start + WATCH_FOLDER_STABLE_DURATION)
+                .is_empty()
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn processed_entries_for_deleted_files_are_forgotten() {
+        let root = sandbox();
+        fs::write(root.join("kept.mov"), b"kept").expect("fixture should be written");
+        let mut folder = watch_folder(&root);
+        folder.mark_processed(root.join("kept.mov"), 1);
+        folder.mark_processed(root.join("deleted.mov"), 2);
+
+        let missing = missing_processed_files(std::slice::from_ref(&folder));
+
+        assert_eq!(missing, HashSet::from([root.join("deleted.mov")]));
+        assert!(folder.forget_processed(&missing));
+        assert_eq!(folder.processed_files.len(), 1);
+        assert!(!folder.forget_processed(&missing));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn is_converted_output_path_matches_numbered_outputs_only() {
+        assert!(is_converted_output_path(Path::new("/a/clip_converted.mp4")));
+        assert!(is_converted_output_path(Path::new(
+            "/a/clip_converted_3.mkv"
+        )));
+        assert!(!is_converted_output_path(Path::new("/a/clip_2.mp4")));
+        assert!(!is_converted_output_path(Path::new(
+            "/a/converted_clip.mp4"
+        )));
+    }
+}
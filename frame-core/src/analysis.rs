@@ -0,0 +1,559 @@
+//! `FFmpeg` analysis passes that inspect decoded frames without writing output.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::Regex;
+use serde::Serialize;
+
+/// Number of frames decoded by the `idet` interlace check.
+pub const INTERLACE_ANALYSIS_FRAMES: u32 = 500;
+
+static IDET_MULTI_FRAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"Multi frame detection:\s*TFF:\s*(\d+)\s*BFF:\s*(\d+)\s*Progressive:\s*(\d+)\s*Undetermined:\s*(\d+)",
+    )
+    .unwrap()
+});
+
+/// Number of timestamps sampled by `cropdetect`, spread across the duration.
+pub const CROP_DETECT_SAMPLES: u32 = 5;
+/// Frames decoded at each `cropdetect` sample point.
+pub const CROP_DETECT_SAMPLE_FRAMES: u32 = 10;
+/// Share of samples that must report the same rectangle for a confident
+/// result.
+const CROP_DETECT_MIN_AGREEMENT: f64 = 0.6;
+
+static CROPDETECT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"crop=(\d+):(\d+):(\d+):(\d+)").unwrap());
+
+/// Scene change score used when the caller does not pick one.
+pub const DEFAULT_SCENE_THRESHOLD: f64 = 0.4;
+/// Most scene cuts returned by one scan, so strobing content cannot flood
+/// callers with thousands of timestamps.
+pub const SCENE_ANALYSIS_MAX_SCENES: usize = 500;
+
+static SHOWINFO_PTS_TIME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"pts_time:\s*(-?\d+(?:\.\d+)?)").unwrap());
+
+static EBUR128_FRAME_TIME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bt:\s*(\d+(?:\.\d+)?)").unwrap());
+
+/// Frame tallies reported by the `idet` filter's multi-frame detection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterlaceAnalysis {
+    pub top_field_first: u64,
+    pub bottom_field_first: u64,
+    pub progressive: u64,
+    pub undetermined: u64,
+}
+
+impl InterlaceAnalysis {
+    /// Treats the source as interlaced when interlaced frames outnumber
+    /// progressive ones.
+    #[must_use]
+    pub const fn is_interlaced(&self) -> bool {
+        self.top_field_first + self.bottom_field_first > self.progressive
+    }
+}
+
+#[must_use]
+pub fn interlace_analysis_args(file_path: &str) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+        "-vf".to_string(),
+        "idet".to_string(),
+        "-frames:v".to_string(),
+        INTERLACE_ANALYSIS_FRAMES.to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reads the final `idet` multi-frame summary from `FFmpeg` stderr.
+#[must_use]
+pub fn parse_idet_summary(stderr: &str) -> Option<InterlaceAnalysis> {
+    let captures = IDET_MULTI_FRAME_REGEX.captures_iter(stderr).last()?;
+    let count = |index: usize| captures[index].parse::<u64>().ok();
+
+    Some(InterlaceAnalysis {
+        top_field_first: count(1)?,
+        bottom_field_first: count(2)?,
+        progressive: count(3)?,
+        undetermined: count(4)?,
+    })
+}
+
+/// EBU R128 measurements reported by the `ebur128` filter summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessAnalysis {
+    pub integrated_lufs: f64,
+    pub loudness_range_lu: f64,
+    /// Absent for silent sources, where `FFmpeg` reports `-inf`.
+    pub true_peak_dbfs: Option<f64>,
+}
+
+/// Builds a full-decode `ebur128` scan of one audio stream, addressed by its
+/// absolute stream index.
+#[must_use]
+pub fn loudness_analysis_args(file_path: &str, stream_index: u32) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-map".to_string(),
+        format!("0:{stream_index}"),
+        "-af".to_string(),
+        "ebur128=framelog=verbose:peak=true".to_string(),
+        "-vn".to_string(),
+        "-sn".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reads the media timestamp from an `ebur128` verbose frame log line.
+#[must_use]
+pub fn ebur128_frame_time(line: &str) -> Option<f64> {
+    if !line.contains("Parsed_ebur128") {
+        return None;
+    }
+    EBUR128_FRAME_TIME_REGEX.captures(line)?[1].parse().ok()
+}
+
+/// Reads integrated loudness, loudness range, and true peak from the final
+/// `ebur128` summary in `FFmpeg` stderr.
+#[must_use]
+pub fn parse_ebur128_summary(stderr: &str) -> Option<LoudnessAnalysis> {
+    let (_, summary) = stderr.rsplit_once("Summary:")?;
+    let mut integrated_lufs = None;
+    let mut loudness_range_lu = None;
+    let mut true_peak_dbfs = None;
+
+    for line in summary.lines().map(str::trim) {
+        let Some((label, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value
+            .split_whitespace()
+            .next()
+            .and_then(|value| value.parse::<f64>().ok());
+        match label {
+            "I" => integrated_lufs = value,
+            "LRA" => loudness_range_lu = value,
+            "Peak" => true_peak_dbfs = value.filter(|peak| peak.is_finite()),
+            _ => {}
+        }
+    }
+
+    Some(LoudnessAnalysis {
+        integrated_lufs: integrated_lufs?,
+        loudness_range_lu: loudness_range_lu?,
+        true_peak_dbfs,
+    })
+}
+
+/// Scene cut timestamps, in seconds, sorted ascending.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneAnalysis {
+    pub timestamps: Vec<f64>,
+    /// Set when more than [`SCENE_ANALYSIS_MAX_SCENES`] cuts were detected.
+    pub truncated: bool,
+}
+
+/// Builds a full-decode scan that logs every frame whose scene change score
+/// exceeds `threshold`.
+#[must_use]
+pub fn scene_analysis_args(file_path: &str, threshold: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+        "-vf".to_string(),
+        format!("select='gt(scene,{threshold:.3})',showinfo"),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reads the presentation time of a frame logged by the `showinfo` filter.
+#[must_use]
+pub fn showinfo_pts_time(line: &str) -> Option<f64> {
+    if !line.contains("Parsed_showinfo") {
+        return None;
+    }
+    SHOWINFO_PTS_TIME_REGEX.captures(line)?[1].parse().ok()
+}
+
+/// Sorts and deduplicates detected cut times, keeping at most
+/// [`SCENE_ANALYSIS_MAX_SCENES`].
+#[must_use]
+pub fn collect_scene_timestamps(times: impl IntoIterator<Item = f64>) -> SceneAnalysis {
+    let mut timestamps: Vec<f64> = times.into_iter().filter(|time| time.is_finite()).collect();
+    timestamps.sort_by(f64::total_cmp);
+    timestamps.dedup_by(|next, previous| (*next - *previous).abs() < f64::EPSILON);
+
+    let truncated = timestamps.len() > SCENE_ANALYSIS_MAX_SCENES;
+    timestamps.truncate(SCENE_ANALYSIS_MAX_SCENES);
+    SceneAnalysis {
+        timestamps,
+        truncated,
+    }
+}
+
+/// Visible picture area reported by `cropdetect`, in source pixels.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRectangle {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl CropRectangle {
+    #[must_use]
+    pub const fn full_frame(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CropConfidence {
+    /// Most samples agreed on the same rectangle.
+    High,
+    /// Samples disagreed, so the full frame is returned instead.
+    Low,
+}
+
+/// Black bar detection result used to pre-fill manual crop controls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropDetection {
+    pub crop: CropRectangle,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub confidence: CropConfidence,
+}
+
+/// Picks `cropdetect` seek points spread evenly inside the source, avoiding
+/// the very start and end where fades are common.
+#[must_use]
+pub fn crop_detect_sample_times(duration_seconds: Option<f64>) -> Vec<f64> {
+    let Some(duration) = duration_seconds.filter(|duration| *duration > 0.0) else {
+        return vec![0.0];
+    };
+
+    let step = duration / f64::from(CROP_DETECT_SAMPLES + 1);
+    (1..=CROP_DETECT_SAMPLES)
+        .map(|sample| step * f64::from(sample))
+        .collect()
+}
+
+/// Builds a short `cropdetect` pass over the frames following `seek_seconds`.
+#[must_use]
+pub fn crop_detect_args(file_path: &str, seek_seconds: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-ss".to_string(),
+        format!("{seek_seconds:.3}"),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+        "-vf".to_string(),
+        "cropdetect=24:2:0".to_string(),
+        "-frames:v".to_string(),
+        CROP_DETECT_SAMPLE_FRAMES.to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reads the last `crop=w:h:x:y` rectangle from `cropdetect` stderr.
+///
+/// The filter runs without resets, so the last line covers every decoded
+/// frame of the sample.
+#[must_use]
+pub fn parse_cropdetect_rectangle(stderr: &str) -> Option<CropRectangle> {
+    let captures = CROPDETECT_REGEX.captures_iter(stderr).last()?;
+    let value = |index: usize| captures[index].parse::<u32>().ok();
+
+    Some(CropRectangle {
+        width: value(1)?,
+        height: value(2)?,
+        x: value(3)?,
+        y: value(4)?,
+    })
+}
+
+/// Picks the most frequent sampled rectangle, falling back to the full frame
+/// with [`CropConfidence::Low`] when samples disagree.
+#[must_use]
+pub fn summarize_crop_samples(
+    samples: &[CropRectangle],
+    source_width: u32,
+    source_height: u32,
+) -> CropDetection {
+    let mut counts: HashMap<CropRectangle, usize> = HashMap::new();
+    for sample in samples {
+        *counts.entry(*sample).or_default() += 1;
+    }
+    let most_frequent = counts
+        .into_iter()
+        .max_by_key(|(rectangle, count)| (*count, rectangle.width * rectangle.height));
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "sample counts are bounded by CROP_DETECT_SAMPLES"
+    )]
+    let agreed = most_frequent
+        .filter(|(_, count)| *count as f64 / samples.len() as f64 >= CROP_DETECT_MIN_AGREEMENT);
+
+    match agreed {
+        Some((crop, _)) => CropDetection {
+            crop,
+            source_width,
+            source_height,
+            confidence: CropConfidence::High,
+        },
+        None => CropDetection {
+            crop: CropRectangle::full_frame(source_width, source_height),
+            source_width,
+            source_height,
+            confidence: CropConfidence::Low,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDET_STDERR: &str = "\
+frame=  500 fps=0.0 q=-0.0 Lsize=N/A time=00:00:20.00 bitrate=N/A speed=41.2x
+[Parsed_idet_0 @ 0x7f8c1c004a80] Repeated Fields: Neither:   501 Top:     0 Bottom:     0
+[Parsed_idet_0 @ 0x7f8c1c004a80] Single frame detection: TFF:   312 BFF:     0 Progressive:    41 Undetermined:   148
+[Parsed_idet_0 @ 0x7f8c1c004a80] Multi frame detection: TFF:   458 BFF:     0 Progressive:    38 Undetermined:     5
+";
+
+    const EBUR128_STDERR: &str = "\
+[Parsed_ebur128_0 @ 0x600002a0c000] t: 59.9      TARGET:-23 LUFS    M: -17.2 S: -18.4     I: -19.6 LUFS       LRA:   5.4 LU  FTPK: -1.2 dBFS  TPK: -0.5 dBFS
+[Parsed_ebur128_0 @ 0x600002a0c000] Summary:
+
+  Integrated loudness:
+    I:         -19.6 LUFS
+    Threshold: -30.0 LUFS
+
+  Loudness range:
+    LRA:         5.4 LU
+    Threshold: -40.0 LUFS
+    LRA low:   -23.6 LUFS
+    LRA high:  -18.2 LUFS
+
+  True peak:
+    Peak:       -0.5 dBFS
+";
+
+    #[test]
+    fn scene_analysis_args_select_frames_above_threshold() {
+        let args = scene_analysis_args("/tmp/film.mkv", 0.35);
+
+        assert!(
+            args.windows(2)
+                .any(|pair| pair == ["-vf", "select='gt(scene,0.350)',showinfo"])
+        );
+        assert_eq!(args.last().map(String::as_str), Some("-"));
+    }
+
+    #[test]
+    fn showinfo_pts_time_reads_selected_frame_times() {
+        let line = "[Parsed_showinfo_1 @ 0x6000] n:   3 pts: 151151 pts_time:12.6126 duration:   1001 pos: 4420330 fmt:yuv420p";
+
+        assert!(showinfo_pts_time(line).is_some_and(|time| (time - 12.6126).abs() < 1e-9));
+        assert_eq!(showinfo_pts_time("frame=  300 time=00:00:12.61"), None);
+    }
+
+    #[test]
+    fn collect_scene_timestamps_sorts_dedupes_and_caps() {
+        let analysis = collect_scene_timestamps([4.0, 1.5, 4.0, f64::NAN]);
+        let capped = collect_scene_timestamps((0..600).map(f64::from));
+
+        assert_eq!(analysis.timestamps.len(), 2);
+        assert!((analysis.timestamps[0] - 1.5).abs() < 1e-9);
+        assert!(!analysis.truncated);
+        assert_eq!(capped.timestamps.len(), SCENE_ANALYSIS_MAX_SCENES);
+        assert!(capped.truncated);
+    }
+
+    #[test]
+    fn crop_detect_sample_times_spread_across_duration() {
+        let times = crop_detect_sample_times(Some(60.0));
+
+        assert_eq!(times.len(), 5);
+        assert!((times[0] - 10.0).abs() < 1e-9);
+        assert!(times.last().is_some_and(|time| (time - 50.0).abs() < 1e-9));
+        assert_eq!(crop_detect_sample_times(None), vec![0.0]);
+    }
+
+    #[test]
+    fn crop_detect_args_seek_before_input() {
+        let args = crop_detect_args("/tmp/film.mkv", 12.5);
+
+        assert_eq!(&args[2..6], ["-ss", "12.500", "-i", "/tmp/film.mkv"]);
+        assert!(
+            args.windows(2)
+                .any(|pair| pair == ["-vf", "cropdetect=24:2:0"])
+        );
+    }
+
+    #[test]
+    fn parse_cropdetect_rectangle_reads_last_crop_line() {
+        let stderr = "\
+[Parsed_cropdetect_0 @ 0x6000] x1:0 x2:1919 y1:138 y2:941 w:1920 h:800 x:0 y:140 pts:1 t:0.04 crop=1920:800:0:140
+[Parsed_cropdetect_0 @ 0x6000] x1:0 x2:1919 y1:132 y2:947 w:1920 h:816 x:0 y:132 pts:2 t:0.08 crop=1920:816:0:132
+";
+
+        assert_eq!(
+            parse_cropdetect_rectangle(stderr),
+            Some(CropRectangle {
+                width: 1920,
+                height: 816,
+                x: 0,
+                y: 132,
+            })
+        );
+        assert_eq!(parse_cropdetect_rectangle("frame=10 fps=0.0"), None);
+    }
+
+    #[test]
+    fn summarize_crop_samples_requires_agreement() {
+        let letterbox = CropRectangle {
+            width: 1920,
+            height: 800,
+            x: 0,
+            y: 140,
+        };
+        let fade = CropRectangle {
+            width: 1280,
+            height: 528,
+            x: 320,
+            y: 276,
+        };
+
+        let agreed = summarize_crop_samples(&[letterbox, letterbox, fade, letterbox], 1920, 1080);
+        let disagreed = summarize_crop_samples(&[letterbox, fade], 1920, 1080);
+
+        assert_eq!(agreed.crop, letterbox);
+        assert_eq!(agreed.confidence, CropConfidence::High);
+        assert_eq!(disagreed.crop, CropRectangle::full_frame(1920, 1080));
+        assert_eq!(disagreed.confidence, CropConfidence::Low);
+        assert_eq!(
+            summarize_crop_samples(&[], 1920, 1080).confidence,
+            CropConfidence::Low
+        );
+    }
+
+    #[test]
+    fn interlace_analysis_args_decode_only_the_first_video_stream() {
+        let args = interlace_analysis_args("/tmp/tape.mpg");
+
+        assert_eq!(args[3], "/tmp/tape.mpg");
+        assert!(args.windows(2).any(|pair| pair == ["-vf", "idet"]));
+        assert!(args.windows(2).any(|pair| pair == ["-frames:v", "500"]));
+        assert_eq!(args.last().map(String::as_str), Some("-"));
+    }
+
+    #[test]
+    fn parse_idet_summary_reads_multi_frame_counts() {
+        let analysis = parse_idet_summary(IDET_STDERR).expect("summary should parse");
+
+        assert_eq!(
+            analysis,
+            InterlaceAnalysis {
+                top_field_first: 458,
+                bottom_field_first: 0,
+                progressive: 38,
+                undetermined: 5,
+            }
+        );
+        assert!(analysis.is_interlaced());
+    }
+
+    #[test]
+    fn parse_idet_summary_returns_none_without_idet_output() {
+        assert_eq!(
+            parse_idet_summary("Invalid data found when processing input"),
+            None
+        );
+    }
+
+    #[test]
+    fn loudness_analysis_args_map_the_requested_stream() {
+        let args = loudness_analysis_args("/tmp/movie.mkv", 3);
+
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:3"]));
+        assert!(
+            args.windows(2)
+                .any(|pair| pair == ["-af", "ebur128=framelog=verbose:peak=true"])
+        );
+    }
+
+    #[test]
+    fn parse_ebur128_summary_reads_loudness_measurements() {
+        let analysis = parse_ebur128_summary(EBUR128_STDERR).expect("summary should parse");
+
+        assert!((analysis.integrated_lufs + 19.6).abs() < 1e-9);
+        assert!((analysis.loudness_range_lu - 5.4).abs() < 1e-9);
+        assert!(
+            analysis
+                .true_peak_dbfs
+                .is_some_and(|peak| (peak + 0.5).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn parse_ebur128_summary_drops_infinite_peak_for_silence() {
+        let stderr = EBUR128_STDERR.replace("-0.5 dBFS\n", "-inf dBFS\n");
+
+        let analysis = parse_ebur128_summary(&stderr).expect("summary should parse");
+
+        assert_eq!(analysis.true_peak_dbfs, None);
+    }
+
+    #[test]
+    fn ebur128_frame_time_reads_verbose_frame_log_timestamps() {
+        let frame_line = EBUR128_STDERR.lines().next().unwrap_or_default();
+
+        assert!(ebur128_frame_time(frame_line).is_some_and(|time| (time - 59.9).abs() < 1e-9));
+        assert_eq!(ebur128_frame_time("size=N/A time=00:00:59.90"), None);
+    }
+}
@@ -1,37 +1,58 @@
-use std::path::Path;
+use std::{fs, ops::RangeInclusive, path::Path};
 
+use crate::capabilities::{EncoderSelfTest, FfmpegCapabilities};
+use crate::chunked::{parallel_chunk_count, validate_parallel_chunks};
 use crate::codec::{
-    add_audio_codec_args, add_fps_args, add_subtitle_codec_args, add_video_codec_args,
-    audio_codec_supports_vbr,
+    MAX_AVIF_CRF, add_audio_codec_args, add_fps_args, add_subtitle_codec_args,
+    add_video_codec_args, audio_codec_supports_vbr,
 };
-use crate::error::ConversionError;
+use crate::concat::ConcatPlan;
+use crate::error::{CodecMismatch, ConversionError, StreamKind};
 use crate::filters::{
-    build_audio_filters, build_encode_overlay_filter_complex, build_encode_video_filters,
-    build_overlay_filter_complex, build_video_filters, has_overlay,
+    DISPLAY_P3_TO_SRGB_FILTER, VIDEO_OUTPUT_LABEL, build_audio_filters,
+    build_overlay_filter_complex, build_pre_seeked_encode_overlay_filter_complex,
+    build_pre_seeked_encode_video_filters, build_segment_concat_filter_complex,
+    build_source_concat_filter_complex, build_video_filters, has_overlay,
 };
+use crate::hw_devices::hw_device_input_args;
+use crate::image_sequence::{parse_input_frame_rate, validate_sequence_output};
 use crate::media_filters::validate_media_filters;
 use crate::media_rules::{
     all_containers, container_supports_audio, container_supports_subtitles, is_audio_codec_allowed,
-    is_audio_stream_codec_allowed, is_image_container, is_subtitle_codec_allowed,
+    is_audio_stream_codec_allowed, is_gif_container, is_image_container, is_subtitle_codec_allowed,
     is_video_codec_allowed, is_video_only_container, is_video_pixel_format_allowed,
-    is_video_stream_codec_allowed,
+    is_video_stream_codec_allowed, video_encoder_supports_high_bit_depth,
 };
+use crate::network_input::{
+    input_url_scheme, network_input_args, network_input_file_name, validate_network_input,
+};
+use crate::output_template::replace_invalid_file_name_characters;
+use crate::probe::hdr_transfer_name;
+use crate::segments::{
+    TrimRange, trim_ranges, uses_segment_concat, validate_remove_segments, validate_trim_segments,
+};
+use crate::smart_cut::smart_cut_range;
+use crate::split::{SplitCuts, split_cuts, split_output_args, validate_output_split};
+use crate::timecode::parse_time_input;
 use crate::types::{
-    AudioTrack, ConversionConfig, MetadataConfig, MetadataMode, ProbeMetadata, SubtitleTrack,
-    VOLUME_EPSILON,
+    AudioTrack, ConversionConfig, MetadataConfig, MetadataMode, OverwritePolicy, ProbeMetadata,
+    SeekMode, SubtitleTrack, VOLUME_EPSILON,
+};
+use crate::utils::{
+    get_hwaccel_args, is_audio_only_container, is_text_subtitle_codec, parse_time,
+    pixel_format_bit_depth,
 };
-use crate::utils::{get_hwaccel_args, is_audio_only_container, parse_time};
 
 fn is_copy_mode(config: &ConversionConfig) -> bool {
     config.processing_mode == "copy"
 }
 
-fn has_custom_pixel_format(config: &ConversionConfig) -> bool {
+pub(crate) fn has_custom_pixel_format(config: &ConversionConfig) -> bool {
     let pixel_format = config.pixel_format.trim();
     !pixel_format.is_empty() && pixel_format != "auto"
 }
 
-fn collect_selected_audio_tracks<'a>(
+pub(crate) fn collect_selected_audio_tracks<'a>(
     config: &ConversionConfig,
     probe: &'a ProbeMetadata,
 ) -> Result<Vec<&'a AudioTrack>, ConversionError> {
@@ -116,33 +137,7 @@ fn subtitle_can_be_encoded_for_container(container: &str, codec: &str) -> bool {
     ) && is_text_subtitle_codec(codec)
 }
 
-fn is_text_subtitle_codec(codec: &str) -> bool {
-    matches!(
-        codec.trim().to_ascii_lowercase().as_str(),
-        "text"
-            | "ssa"
-            | "mov_text"
-            | "srt"
-            | "microdvd"
-            | "eia_608"
-            | "jacosub"
-            | "sami"
-            | "realtext"
-            | "stl"
-            | "subviewer1"
-            | "subviewer"
-            | "subrip"
-            | "webvtt"
-            | "mpl2"
-            | "vplayer"
-            | "pjs"
-            | "ass"
-            | "hdmv_text_subtitle"
-            | "ttml"
-    )
-}
-
-fn add_track_maps<T>(args: &mut Vec<String>, tracks: &[&T], index: impl Fn(&T) -> u32) {
+pub(crate) fn add_track_maps<T>(args: &mut Vec<String>, tracks: &[&T], index: impl Fn(&T) -> u32) {
     for track in tracks {
         args.push("-map".to_string());
         args.push(format!("0:{}", index(track)));
@@ -174,10 +169,12 @@ pub fn validate_stream_copy_compatibility(
         }
         for track in selected_audio {
             if !is_audio_stream_codec_allowed(&config.container, &track.codec) {
-                return Err(ConversionError::InvalidInput(format!(
-                    "Audio codec '{}' from source track #{} is incompatible with container '{}'",
-                    track.codec, track.index, config.container
-                )));
+                return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+                    stream: StreamKind::Audio,
+                    codec: track.codec.clone(),
+                    container: config.container.clone(),
+                    source_track: Some(track.index),
+                }));
             }
         }
         return Ok(());
@@ -189,19 +186,23 @@ pub fn validate_stream_copy_compatibility(
         )
     })?;
     if !is_video_stream_codec_allowed(&config.container, video_codec) {
-        return Err(ConversionError::InvalidInput(format!(
-            "Video codec '{}' is incompatible with container '{}'",
-            video_codec, config.container
-        )));
+        return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+            stream: StreamKind::Video,
+            codec: video_codec.to_string(),
+            container: config.container.clone(),
+            source_track: None,
+        }));
     }
 
     if container_supports_audio(&config.container) {
         for track in collect_selected_audio_tracks(config, probe)? {
             if !is_audio_stream_codec_allowed(&config.container, &track.codec) {
-                return Err(ConversionError::InvalidInput(format!(
-                    "Audio codec '{}' from source track #{} is incompatible with container '{}'",
-                    track.codec, track.index, config.container
-                )));
+                return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+                    stream: StreamKind::Audio,
+                    codec: track.codec.clone(),
+                    container: config.container.clone(),
+                    source_track: Some(track.index),
+                }));
             }
         }
     }
@@ -209,10 +210,12 @@ pub fn validate_stream_copy_compatibility(
     if container_supports_subtitles(&config.container) {
         for track in collect_selected_subtitle_tracks(config, probe)? {
             if !is_subtitle_codec_allowed(&config.container, &track.codec) {
-                return Err(ConversionError::InvalidInput(format!(
-                    "Subtitle codec '{}' from source track #{} is incompatible with container '{}'",
-                    track.codec, track.index, config.container
-                )));
+                return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+                    stream: StreamKind::Subtitle,
+                    codec: track.codec.clone(),
+                    container: config.container.clone(),
+                    source_track: Some(track.index),
+                }));
             }
         }
     }
@@ -220,12 +223,135 @@ pub fn validate_stream_copy_compatibility(
     Ok(())
 }
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "FFmpeg command assembly stays in one place to keep ordering guarantees explicit"
-)]
+/// Warns when a PQ or HLG source is re-encoded with an 8-bit pixel format,
+/// which discards the HDR range of the source.
+#[must_use]
+pub fn hdr_encoding_warning(config: &ConversionConfig, probe: &ProbeMetadata) -> Option<String> {
+    if is_copy_mode(config) || is_audio_only_container(&config.container) {
+        return None;
+    }
+    let transfer = hdr_transfer_name(probe.color_transfer.as_deref()?)?;
+
+    let is_eight_bit_target = if has_custom_pixel_format(config) {
+        pixel_format_bit_depth(&config.pixel_format).is_some_and(|bits| bits <= 8)
+    } else {
+        !video_encoder_supports_high_bit_depth(&config.container, &config.video_codec)
+    };
+    if !is_eight_bit_target {
+        return None;
+    }
+
+    Some(format!(
+        "Source uses {transfer} HDR, but '{}' will encode 8-bit SDR output; expect clipped highlights and washed-out colors",
+        config.video_codec
+    ))
+}
+
+/// Input options for animated GIF and APNG sources: they play once, whatever
+/// loop count the file asks for, so the output lasts as long as one pass of
+/// the animation and never needs `-stream_loop` to be stopped.
+fn animated_input_args(probe: &ProbeMetadata) -> Vec<String> {
+    let loops_on_demux = matches!(probe.video_codec.as_deref(), Some("gif" | "apng"));
+    if probe.animated && loops_on_demux {
+        vec!["-ignore_loop".to_string(), "1".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether the source is a Display P3 YUV picture, as HEIC photos are; the
+/// `colorspace` filter only converts YUV input.
+fn is_display_p3_yuv_source(probe: &ProbeMetadata) -> bool {
+    probe.color_primaries.as_deref() == Some("smpte432")
+        && probe
+            .pixel_format
+            .as_deref()
+            .is_some_and(|format| format.starts_with("yuv"))
+}
+
+/// Keeps the per-frame delays of an animated image as variable timestamps,
+/// instead of duplicating or dropping frames to fit the nominal rate, unless
+/// an output frame rate was chosen.
+fn add_animated_timing_args(
+    args: &mut Vec<String>,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) {
+    if probe.animated && config.fps == "original" {
+        args.push("-fps_mode".to_string());
+        args.push("vfr".to_string());
+    }
+}
+
+/// Palette and RGB sources such as GIFs would otherwise be encoded by H.264
+/// encoders in 4:4:4 or RGB modes that most players cannot decode.
+pub(crate) fn needs_h264_compatible_pixel_format(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> bool {
+    let is_h264 = config.video_codec == "libx264" || config.video_codec.starts_with("h264_");
+    let is_palette_or_rgb = probe.animated
+        || probe.pixel_format.as_deref().is_some_and(|format| {
+            matches!(
+                format,
+                "pal8" | "rgb24" | "bgr24" | "rgba" | "bgra" | "argb" | "abgr" | "rgb0" | "bgr0"
+            )
+        });
+    is_h264 && is_palette_or_rgb
+}
+
+/// Reports whether `FFmpeg`'s `frame=` counter tracks source frames one to
+/// one, so progress can be measured against a source frame count.
+#[must_use]
+pub fn frame_progress_applies(config: &ConversionConfig) -> bool {
+    config.fps == "original"
+        && !is_audio_only_container(&config.container)
+        && (!is_image_container(&config.container) || config.image_sequence_output)
+        && !is_gif_container(&config.container)
+}
+
+/// Seconds the hybrid seek mode seeks the input short of the trim start.
+pub const HYBRID_PRE_SEEK_SECONDS: f64 = 5.0;
+
+/// Split of a trim start between the input seek, placed before `-i`, and
+/// the output seek, placed after the inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct SeekPlan {
+    mode: SeekMode,
+    input: f64,
+    output: f64,
+}
+
+impl SeekPlan {
+    fn new(mode: SeekMode, start: f64) -> Self {
+        let input = match mode {
+            SeekMode::Fast => start,
+            SeekMode::Accurate => 0.0,
+            SeekMode::Hybrid => (start - HYBRID_PRE_SEEK_SECONDS).max(0.0),
+        };
+        Self {
+            mode,
+            input,
+            output: start - input,
+        }
+    }
+
+    /// Offset that puts burned subtitles back on source time. The input
+    /// seek resets timestamps to zero; fast mode keeps its historical
+    /// timing.
+    const fn subtitle_offset(self) -> f64 {
+        match self.mode {
+            SeekMode::Fast => 0.0,
+            SeekMode::Accurate | SeekMode::Hybrid => self.input,
+        }
+    }
+}
+
 /// Builds probe-aware `FFmpeg` arguments for one conversion.
 ///
+/// The task's extra arguments go into this command only; smart-cut edges,
+/// chunk joins and other helper passes leave them out.
+///
 /// # Errors
 ///
 /// Returns [`ConversionError`] when a selected source stream is missing or
@@ -236,206 +362,606 @@ pub fn build_ffmpeg_args(
     config: &ConversionConfig,
     probe: &ProbeMetadata,
 ) -> Result<Vec<String>, ConversionError> {
-    let mut args = Vec::new();
+    let mut command = FfmpegCommand::new(config, probe)?;
+    command.input_with_trim(input).apply_metadata();
+    command.map_streams()?;
+    Ok(command.finish(output))
+}
+
+/// What a conversion writes, which decides how its streams are mapped and
+/// encoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OutputKind {
+    StreamCopy,
+    AudioOnly,
+    Gif,
+    Image,
+    Video,
+}
+
+/// `FFmpeg` arguments of one conversion, assembled step by step.
+///
+/// The steps append to the command in the order `FFmpeg` reads it:
+/// [`Self::input_with_trim`], [`Self::apply_metadata`],
+/// [`Self::map_streams`], which runs [`Self::rate_control`] where the
+/// output needs it, and [`Self::finish`].
+#[derive(Debug)]
+pub struct FfmpegCommand<'a> {
+    config: &'a ConversionConfig,
+    probe: &'a ProbeMetadata,
+    args: Vec<String>,
+    kind: OutputKind,
+    /// Kept ranges; several are cut by the filter graph, a single one seeks
+    /// the input instead.
+    ranges: Vec<TrimRange>,
+    concat_segments: bool,
+    seek: SeekPlan,
+    split_cuts: Option<SplitCuts>,
+}
 
-    // Hardware decode acceleration (must be before -i)
-    if config.hw_decode {
-        args.extend(get_hwaccel_args(&config.video_codec));
+impl<'a> FfmpegCommand<'a> {
+    /// Starts an empty command for `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError::InvalidInput`] when the trim segments or
+    /// the output split do not fit the source.
+    pub fn new(
+        config: &'a ConversionConfig,
+        probe: &'a ProbeMetadata,
+    ) -> Result<Self, ConversionError> {
+        let ranges = trim_ranges(config);
+        let concat_segments = uses_segment_concat(config);
+        if concat_segments {
+            validate_trim_segments(config, probe.duration_seconds())?;
+        }
+        let seek = ranges
+            .first()
+            .filter(|_| !concat_segments)
+            .map_or_else(SeekPlan::default, |range| {
+                SeekPlan::new(config.seek_mode, range.start)
+            });
+        let split_cuts = split_cuts(config, probe)?;
+        Ok(Self {
+            config,
+            probe,
+            args: Vec::new(),
+            kind: output_kind(config),
+            ranges,
+            concat_segments,
+            seek,
+            split_cuts,
+        })
     }
 
-    if let Some(start) = &config.start_time
-        && !start.is_empty()
-    {
-        args.push("-ss".to_string());
-        args.push(start.clone());
+    /// Arguments added so far.
+    #[must_use]
+    pub fn args(&self) -> &[String] {
+        &self.args
     }
 
-    args.push("-i".to_string());
-    args.push(input.to_string());
+    fn push(&mut self, flag: &str, value: impl Into<String>) {
+        self.args.push(flag.to_string());
+        self.args.push(value.into());
+    }
 
-    if has_overlay(config)
-        && let Some(overlay) = &config.overlay
-    {
-        args.push("-i".to_string());
-        args.push(overlay.path.clone());
+    fn seek_range(&self) -> Option<TrimRange> {
+        self.ranges
+            .first()
+            .copied()
+            .filter(|_| !self.concat_segments)
     }
 
-    if let Some(end_str) = &config.end_time
-        && !end_str.is_empty()
-    {
-        if let Some(start_str) = &config.start_time {
-            if start_str.is_empty() {
-                args.push("-to".to_string());
-                args.push(end_str.clone());
-            } else if let (Some(start_t), Some(end_t)) =
-                (parse_time(start_str), parse_time(end_str))
-            {
-                let duration = end_t - start_t;
-                if duration > 0.0 {
-                    args.push("-t".to_string());
-                    args.push(format!("{duration:.3}"));
-                }
-            }
-        } else {
-            args.push("-to".to_string());
-            args.push(end_str.clone());
-        }
+    /// Whether the overlay graph feeds the video output; audio-only and
+    /// GIF outputs leave the overlay out.
+    fn use_overlay(&self) -> bool {
+        matches!(self.kind, OutputKind::Image | OutputKind::Video)
+            && !self.config.container.eq_ignore_ascii_case("gif")
+            && has_overlay(self.config)
     }
 
-    match config.metadata.mode {
-        MetadataMode::Clean => {
-            args.push("-map_metadata".to_string());
-            args.push("-1".to_string());
+    /// Adds the hardware decode options, the task's extra input arguments,
+    /// the source and overlay inputs, and the seek and duration of a single
+    /// trim range around them.
+    pub fn input_with_trim(&mut self, input: &str) -> &mut Self {
+        let config = self.config;
+        // Hardware decode acceleration (must be before -i)
+        if config.hw_decode {
+            self.args.extend(get_hwaccel_args(&config.video_codec));
         }
-        MetadataMode::Replace => {
-            args.push("-map_metadata".to_string());
-            args.push("-1".to_string());
-            add_metadata_flags(&mut args, &config.metadata);
+        self.args.extend(hw_device_input_args(config));
+
+        if self.seek.input > 0.0 {
+            self.push("-ss", format!("{:.3}", self.seek.input));
         }
-        MetadataMode::Preserve => {
-            add_metadata_flags(&mut args, &config.metadata);
+        self.args.extend(animated_input_args(self.probe));
+        self.args.extend(network_input_args(input));
+        self.args.extend(config.extra_input_args.iter().cloned());
+        self.push("-i", input);
+        if has_overlay(config)
+            && let Some(overlay) = &config.overlay
+        {
+            self.push("-i", overlay.path.clone());
+        }
+
+        if self.seek.output > 0.0 {
+            self.push("-ss", format!("{:.3}", self.seek.output));
+        }
+        if let Some(duration) = self.seek_range().and_then(|range| range.duration(None)) {
+            self.push("-t", format!("{duration:.3}"));
         }
+        self
+    }
+
+    /// Keeps, clears or replaces the source metadata per the task's
+    /// metadata mode.
+    pub fn apply_metadata(&mut self) -> &mut Self {
+        add_metadata_mode_args(&mut self.args, self.config);
+        self
+    }
+
+    /// Maps the source streams into the output and adds their codecs and
+    /// filters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError`] when a selected source stream is missing
+    /// or cannot be written to the output container.
+    pub fn map_streams(&mut self) -> Result<&mut Self, ConversionError> {
+        match self.kind {
+            OutputKind::StreamCopy => self.map_copied_streams()?,
+            OutputKind::AudioOnly => self.map_audio_only()?,
+            OutputKind::Gif => self.map_gif(),
+            OutputKind::Image => self.map_image(),
+            OutputKind::Video => self.map_video()?,
+        }
+        Ok(self)
     }
 
-    let is_audio_only = is_audio_only_container(&config.container);
-    let is_video_only = is_video_only_container(&config.container);
-    let is_image_output = is_image_container(&config.container);
-    let is_gif_output = config.container.eq_ignore_ascii_case("gif");
-    let use_overlay = has_overlay(config) && !is_audio_only && !is_gif_output;
-    let has_burn_subtitles = config
-        .subtitle_burn_path
-        .as_ref()
-        .is_some_and(|path| !path.trim().is_empty());
+    /// Adds the video encoder, its quality settings and the output pixel
+    /// format. Stream copies and audio and GIF outputs have none.
+    pub fn rate_control(&mut self) -> &mut Self {
+        let (config, probe) = (self.config, self.probe);
+        if !matches!(self.kind, OutputKind::Image | OutputKind::Video) {
+            return self;
+        }
+        add_video_codec_args(&mut self.args, config);
+        if has_custom_pixel_format(config) {
+            self.push("-pix_fmt", config.pixel_format.trim());
+        } else if self.kind == OutputKind::Video
+            && needs_h264_compatible_pixel_format(config, probe)
+        {
+            self.push("-pix_fmt", "yuv420p");
+        }
+        if self.kind == OutputKind::Video {
+            add_animated_timing_args(&mut self.args, config, probe);
+        }
+        self
+    }
+
+    /// Adds the audio filters, the overwrite guard, the task's extra output
+    /// arguments and the output target, returning the finished arguments.
+    #[must_use]
+    pub fn finish(mut self, output: &str) -> Vec<String> {
+        let config = self.config;
+        if matches!(self.kind, OutputKind::AudioOnly | OutputKind::Video) && !self.concat_segments {
+            let audio_filters = build_audio_filters(config);
+            if !audio_filters.is_empty() {
+                self.push("-af", audio_filters.join(","));
+            }
+        }
 
-    if is_copy_mode(config) {
+        self.args.push("-dn".to_string());
+        self.args.push(overwrite_flag(config).to_string());
+        self.args.extend(config.extra_output_args.iter().cloned());
+        push_output_target(&mut self.args, output, config, self.split_cuts.as_ref());
+        self.args
+    }
+
+    fn map_copied_streams(&mut self) -> Result<(), ConversionError> {
+        let (config, probe) = (self.config, self.probe);
         validate_stream_copy_compatibility(config, probe)?;
 
-        if !is_audio_only {
-            args.push("-map".to_string());
-            args.push("0:v?".to_string());
+        if !is_audio_only_container(&config.container) {
+            self.push("-map", "0:V?");
         }
-
         if container_supports_audio(&config.container) {
             let audio_tracks = collect_selected_audio_tracks(config, probe)?;
-            add_track_maps(&mut args, &audio_tracks, |track| track.index);
+            add_track_maps(&mut self.args, &audio_tracks, |track| track.index);
         }
-
         if container_supports_subtitles(&config.container) {
             let subtitle_tracks = collect_selected_subtitle_tracks(config, probe)?;
-            add_track_maps(&mut args, &subtitle_tracks, |track| track.index);
+            add_track_maps(&mut self.args, &subtitle_tracks, |track| track.index);
         }
-
-        args.push("-c".to_string());
-        args.push("copy".to_string());
-        args.push("-dn".to_string());
-        args.push("-n".to_string());
-        args.push(output.to_string());
-        return Ok(args);
+        self.push("-c", "copy");
+        Ok(())
     }
 
-    if is_audio_only {
-        args.push("-vn".to_string());
+    fn map_audio_only(&mut self) -> Result<(), ConversionError> {
+        let config = self.config;
+        self.args.push("-vn".to_string());
 
-        let audio_tracks = collect_selected_audio_tracks(config, probe)?;
-        add_track_maps(&mut args, &audio_tracks, |track| track.index);
+        let audio_tracks = collect_selected_audio_tracks(config, self.probe)?;
+        if self.concat_segments {
+            add_segment_concat_graph(&mut self.args, config, &self.ranges, false, &audio_tracks);
+        } else {
+            add_track_maps(&mut self.args, &audio_tracks, |track| track.index);
+        }
+        add_audio_codec_args(&mut self.args, config);
+        Ok(())
+    }
 
-        add_audio_codec_args(&mut args, config);
-    } else if is_video_only && is_gif_output {
-        args.push("-filter_complex".to_string());
-        args.push(build_gif_filter_complex(config));
+    fn map_gif(&mut self) {
+        let config = self.config;
+        self.push("-filter_complex", build_gif_filter_complex(config));
+        self.push("-map", "[gif_out]");
+        self.args.push("-an".to_string());
+        self.push("-c:v", "gif");
+        self.push("-loop", config.gif_loop.to_string());
+        self.push("-f", "gif");
+    }
 
-        args.push("-map".to_string());
-        args.push("[gif_out]".to_string());
-        args.push("-an".to_string());
+    fn map_image(&mut self) {
+        let (config, probe) = (self.config, self.probe);
+        self.rate_control();
 
-        args.push("-c:v".to_string());
-        args.push("gif".to_string());
+        if self.use_overlay() {
+            self.push("-filter_complex", build_overlay_filter_complex(config));
+        } else {
+            let mut video_filters = build_video_filters(config, true);
+            if is_display_p3_yuv_source(probe) {
+                video_filters.insert(0, DISPLAY_P3_TO_SRGB_FILTER.to_string());
+            }
+            if !video_filters.is_empty() {
+                self.push("-vf", video_filters.join(","));
+            }
+        }
 
-        args.push("-loop".to_string());
-        args.push(config.gif_loop.to_string());
-        args.push("-f".to_string());
-        args.push("gif".to_string());
-    } else if is_image_output {
-        add_video_codec_args(&mut args, config);
-        if has_custom_pixel_format(config) {
-            args.push("-pix_fmt".to_string());
-            args.push(config.pixel_format.trim().to_string());
+        self.push("-map", self.video_map_target());
+        if config.image_sequence_output {
+            add_fps_args(&mut self.args, config);
+            self.push("-f", "image2");
+        } else {
+            self.push("-frames:v", "1");
+            self.push("-update", "1");
         }
+    }
+
+    fn map_video(&mut self) -> Result<(), ConversionError> {
+        let (config, probe) = (self.config, self.probe);
+        self.rate_control();
 
-        if use_overlay {
-            args.push("-filter_complex".to_string());
-            args.push(build_overlay_filter_complex(config));
+        let audio_tracks = collect_selected_audio_tracks(config, probe)?;
+        if self.concat_segments {
+            add_segment_concat_graph(&mut self.args, config, &self.ranges, true, &audio_tracks);
+            add_fps_args(&mut self.args, config);
         } else {
-            let video_filters = build_video_filters(config, true);
-            if !video_filters.is_empty() {
-                args.push("-vf".to_string());
-                args.push(video_filters.join(","));
+            let subtitle_offset = self.seek.subtitle_offset();
+            if self.use_overlay() {
+                self.push(
+                    "-filter_complex",
+                    build_pre_seeked_encode_overlay_filter_complex(config, subtitle_offset),
+                );
+            } else {
+                let video_filters = build_pre_seeked_encode_video_filters(config, subtitle_offset);
+                if !video_filters.is_empty() {
+                    self.push("-vf", video_filters.join(","));
+                }
             }
+
+            add_fps_args(&mut self.args, config);
+            self.push("-map", self.video_map_target());
+            add_track_maps(&mut self.args, &audio_tracks, |track| track.index);
         }
 
-        args.push("-map".to_string());
-        args.push(if use_overlay {
-            "[vout]".to_string()
+        add_audio_codec_args(&mut self.args, config);
+
+        // Soft subtitles cannot follow the cuts of joined segments.
+        let has_burn_subtitles = config
+            .subtitle_burn_path
+            .as_ref()
+            .is_some_and(|path| !path.trim().is_empty());
+        if !self.concat_segments
+            && (!config.selected_subtitle_tracks.is_empty() || !has_burn_subtitles)
+        {
+            let subtitle_tracks = collect_reencode_subtitle_tracks(config, probe)?;
+            if !subtitle_tracks.is_empty() {
+                add_track_maps(&mut self.args, &subtitle_tracks, |track| track.index);
+                add_subtitle_codec_args(&mut self.args, config);
+            }
+        }
+        Ok(())
+    }
+
+    /// The overlay graph's output, or the source's primary video stream.
+    fn video_map_target(&self) -> &'static str {
+        if self.use_overlay() {
+            "[vout]"
         } else {
-            "0:v:0".to_string()
-        });
-        args.push("-frames:v".to_string());
-        args.push("1".to_string());
-        args.push("-update".to_string());
+            "0:V:0"
+        }
+    }
+}
+
+fn output_kind(config: &ConversionConfig) -> OutputKind {
+    if is_copy_mode(config) {
+        OutputKind::StreamCopy
+    } else if is_audio_only_container(&config.container) {
+        OutputKind::AudioOnly
+    } else if is_video_only_container(&config.container)
+        && config.container.eq_ignore_ascii_case("gif")
+    {
+        OutputKind::Gif
+    } else if is_image_container(&config.container) {
+        OutputKind::Image
+    } else {
+        OutputKind::Video
+    }
+}
+
+/// Builds a stream-copy join of the files listed in `list_path`.
+///
+/// `source` supplies the metadata kept by the task's metadata mode. Smart
+/// cuts and joins of matching sources both end here.
+#[must_use]
+pub fn build_stream_copy_concat_args(
+    list_path: &str,
+    source: &str,
+    output: &str,
+    config: &ConversionConfig,
+) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        list_path.to_string(),
+        "-i".to_string(),
+        source.to_string(),
+        "-map".to_string(),
+        "0".to_string(),
+        "-map_chapters".to_string(),
+        "-1".to_string(),
+    ];
+    if config.metadata.mode == MetadataMode::Preserve {
+        args.push("-map_metadata".to_string());
         args.push("1".to_string());
+    }
+    add_metadata_mode_args(&mut args, config);
+    args.push("-c".to_string());
+    args.push("copy".to_string());
+    args.push("-dn".to_string());
+    args.push(overwrite_flag(config).to_string());
+    push_output_target(&mut args, output, config, None);
+    args
+}
+
+/// Builds the final mux of a parallel chunked encode.
+///
+/// The video chunks listed in `list_path` are joined by the concat demuxer
+/// and muxed with the audio pass in `audio` and the subtitles, chapters and
+/// metadata of `source`. Every stream is copied except subtitles, which are converted for the
+/// output container as in a plain re-encode.
+///
+/// # Errors
+///
+/// Returns [`ConversionError`] when a selected subtitle track is missing or
+/// cannot be converted for the output container.
+pub fn build_chunk_join_args(
+    list_path: &str,
+    audio: Option<&str>,
+    source: &str,
+    output: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Result<Vec<String>, ConversionError> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        list_path.to_string(),
+    ];
+    if let Some(audio) = audio {
+        args.push("-i".to_string());
+        args.push(audio.to_string());
+    }
+    let source_index = if audio.is_some() { 2 } else { 1 };
+    args.push("-i".to_string());
+    args.push(source.to_string());
+
+    args.push("-map".to_string());
+    args.push("0:v:0".to_string());
+    if audio.is_some() {
+        args.push("-map".to_string());
+        args.push("1:a".to_string());
+    }
+    let has_burn_subtitles = config
+        .subtitle_burn_path
+        .as_ref()
+        .is_some_and(|path| !path.trim().is_empty());
+    let subtitle_tracks = if !config.selected_subtitle_tracks.is_empty() || !has_burn_subtitles {
+        collect_reencode_subtitle_tracks(config, probe)?
     } else {
+        Vec::new()
+    };
+    for track in &subtitle_tracks {
+        args.push("-map".to_string());
+        args.push(format!("{source_index}:{}", track.index));
+    }
+    args.push("-map_chapters".to_string());
+    args.push(source_index.to_string());
+    if config.metadata.mode == MetadataMode::Preserve {
+        args.push("-map_metadata".to_string());
+        args.push(source_index.to_string());
+    }
+    add_metadata_mode_args(&mut args, config);
+
+    args.push("-c".to_string());
+    args.push("copy".to_string());
+    if !subtitle_tracks.is_empty() {
+        add_subtitle_codec_args(&mut args, config);
+    }
+    args.push("-dn".to_string());
+    args.push(overwrite_flag(config).to_string());
+    push_output_target(&mut args, output, config, None);
+    Ok(args)
+}
+
+/// Builds the re-encoding join of `inputs` planned by
+/// [`crate::concat::plan_concat`]:
+/// every input feeds the concat graph, and chapters, which only describe
+/// the first input, are dropped.
+#[must_use]
+pub fn build_concat_reencode_args(
+    inputs: &[String],
+    output: &str,
+    config: &ConversionConfig,
+    plan: &ConcatPlan,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    for input in inputs {
+        args.push("-i".to_string());
+        args.push(input.clone());
+    }
+    add_metadata_mode_args(&mut args, config);
+    args.push("-map_chapters".to_string());
+    args.push("-1".to_string());
+
+    if plan.size.is_some() {
         add_video_codec_args(&mut args, config);
         if has_custom_pixel_format(config) {
             args.push("-pix_fmt".to_string());
             args.push(config.pixel_format.trim().to_string());
         }
-
-        if use_overlay {
-            args.push("-filter_complex".to_string());
-            args.push(build_encode_overlay_filter_complex(config));
-        } else {
-            let video_filters = build_encode_video_filters(config, true);
-            if !video_filters.is_empty() {
-                args.push("-vf".to_string());
-                args.push(video_filters.join(","));
-            }
-        }
-
+    }
+    args.push("-filter_complex".to_string());
+    args.push(build_source_concat_filter_complex(config, plan));
+    if plan.size.is_some() {
         add_fps_args(&mut args, config);
         args.push("-map".to_string());
-        args.push(if use_overlay {
-            "[vout]".to_string()
-        } else {
-            "0:v:0".to_string()
-        });
-
-        let audio_tracks = collect_selected_audio_tracks(config, probe)?;
-        add_track_maps(&mut args, &audio_tracks, |track| track.index);
-
+        args.push(format!("[{VIDEO_OUTPUT_LABEL}]"));
+    }
+    if plan.audio.is_some() {
+        args.push("-map".to_string());
+        args.push("[aout0]".to_string());
         add_audio_codec_args(&mut args, config);
+    }
 
-        if !config.selected_subtitle_tracks.is_empty() || !has_burn_subtitles {
-            let subtitle_tracks = collect_reencode_subtitle_tracks(config, probe)?;
-            if !subtitle_tracks.is_empty() {
-                add_track_maps(&mut args, &subtitle_tracks, |track| track.index);
-                add_subtitle_codec_args(&mut args, config);
-            }
+    args.push("-dn".to_string());
+    args.push(overwrite_flag(config).to_string());
+    push_output_target(&mut args, output, config, None);
+    args
+}
+
+fn add_metadata_mode_args(args: &mut Vec<String>, config: &ConversionConfig) {
+    match config.metadata.mode {
+        MetadataMode::Clean => {
+            args.push("-map_metadata".to_string());
+            args.push("-1".to_string());
+        }
+        MetadataMode::Replace => {
+            args.push("-map_metadata".to_string());
+            args.push("-1".to_string());
+            add_metadata_flags(args, &config.metadata);
+        }
+        MetadataMode::Preserve => {
+            add_metadata_flags(args, &config.metadata);
         }
     }
+}
 
-    if !is_video_only && !is_image_output {
-        let audio_filters = build_audio_filters(config);
-        if !audio_filters.is_empty() {
-            args.push("-af".to_string());
-            args.push(audio_filters.join(","));
-        }
+/// Adds the trim and concat graph of a task keeping several ranges and maps
+/// its outputs; audio filters run inside the graph.
+fn add_segment_concat_graph(
+    args: &mut Vec<String>,
+    config: &ConversionConfig,
+    ranges: &[TrimRange],
+    include_video: bool,
+    audio_tracks: &[&AudioTrack],
+) {
+    let audio_streams: Vec<u32> = audio_tracks.iter().map(|track| track.index).collect();
+    args.push("-filter_complex".to_string());
+    args.push(build_segment_concat_filter_complex(
+        config,
+        ranges,
+        include_video,
+        &audio_streams,
+    ));
+    if include_video {
+        args.push("-map".to_string());
+        args.push("[vout]".to_string());
     }
+    for track in 0..audio_streams.len() {
+        args.push("-map".to_string());
+        args.push(format!("[aout{track}]"));
+    }
+}
 
-    args.push("-dn".to_string());
-    args.push("-n".to_string());
+/// Returns the `FFmpeg` muxer that writes `container`.
+#[must_use]
+pub fn output_muxer(container: &str) -> Option<&'static str> {
+    let muxer = match container.to_ascii_lowercase().as_str() {
+        "mp4" => "mp4",
+        "mkv" => "matroska",
+        "webm" => "webm",
+        "mov" => "mov",
+        "gif" => "gif",
+        "png" | "jpg" | "webp" | "bmp" | "tiff" => "image2",
+        "avif" => "avif",
+        "mp3" => "mp3",
+        "m4a" => "ipod",
+        "wav" => "wav",
+        "flac" => "flac",
+        _ => return None,
+    };
+    Some(muxer)
+}
+
+/// Appends the output path, naming the muxer explicitly when the path does
+/// not end in the container's extension, as with temporary `.part` files.
+/// Split outputs go through the segment muxer, with `output` as the part
+/// pattern.
+fn push_output_target(
+    args: &mut Vec<String>,
+    output: &str,
+    config: &ConversionConfig,
+    split_cuts: Option<&SplitCuts>,
+) {
+    if let Some(cuts) = split_cuts {
+        args.extend(split_output_args(config, cuts));
+        args.push(output.to_string());
+        return;
+    }
+    let has_container_extension = output
+        .rsplit_once('.')
+        .is_some_and(|(_, extension)| extension.eq_ignore_ascii_case(&config.container));
+    let output_options = args
+        .iter()
+        .rposition(|arg| arg == "-i")
+        .map_or(0, |input_index| input_index + 2);
+    let has_explicit_format = args
+        .get(output_options..)
+        .is_some_and(|options| options.iter().any(|arg| arg == "-f"));
+    if !has_container_extension
+        && !has_explicit_format
+        && let Some(muxer) = output_muxer(&config.container)
+    {
+        args.push("-f".to_string());
+        args.push(muxer.to_string());
+    }
     args.push(output.to_string());
+}
 
-    Ok(args)
+/// Only the `overwrite` policy lets `FFmpeg` replace an existing output; the
+/// other policies resolve conflicts before spawning and keep `-n` as a guard.
+const fn overwrite_flag(config: &ConversionConfig) -> &'static str {
+    match config.overwrite_policy {
+        OverwritePolicy::Overwrite => "-y",
+        OverwritePolicy::Rename | OverwritePolicy::Fail => "-n",
+    }
 }
 
 fn normalize_gif_dither(dither: &str) -> &'static str {
@@ -463,7 +989,7 @@ fn build_gif_filter_complex(config: &ConversionConfig) -> String {
     let dither = normalize_gif_dither(&config.gif_dither);
 
     format!(
-        "[0:v:0]{chain};[gif_palette_src]palettegen=max_colors={colors}:stats_mode=single[gif_palette];[gif_src][gif_palette]paletteuse=dither={dither}:new=1[gif_out]"
+        "[0:V:0]{chain};[gif_palette_src]palettegen=max_colors={colors}:stats_mode=single[gif_palette];[gif_src][gif_palette]paletteuse=dither={dither}:new=1[gif_out]"
     )
 }
 
@@ -506,6 +1032,42 @@ pub fn add_metadata_flags(args: &mut Vec<String>, metadata: &MetadataConfig) {
     }
 }
 
+/// Suffix appended to the input stem when no output name is chosen.
+pub const DEFAULT_OUTPUT_SUFFIX: &str = "_converted";
+
+/// Derives the default output name for `input`: its file name with only the
+/// final extension removed, followed by `suffix`.
+///
+/// Inputs whose stem would be empty, such as dotfiles, fall back to `output`.
+#[must_use]
+pub fn default_output_stem(input: &str, suffix: &str) -> String {
+    let file_name = if input_url_scheme(input).is_some() {
+        network_input_file_name(input)
+    } else {
+        input.rsplit(['/', '\\']).next().unwrap_or_default()
+    };
+    let base = file_name
+        .rsplit_once('.')
+        .filter(|(_, extension)| !extension.is_empty())
+        .map_or(file_name, |(stem, _)| stem);
+
+    if base.is_empty() {
+        format!("output{suffix}")
+    } else {
+        format!("{base}{suffix}")
+    }
+}
+
+/// Longest file name, in bytes, accepted by common filesystems.
+const MAX_OUTPUT_FILE_NAME_BYTES: usize = 255;
+
+const RESERVED_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Reduces a user-supplied output name to a single file name that is valid on
+/// Windows as well as Unix, or `None` when nothing usable remains.
 fn sanitize_output_name(raw: &str) -> Option<String> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
@@ -518,9 +1080,45 @@ fn sanitize_output_name(raw: &str) -> Option<String> {
         return None;
     }
 
+    let replaced = replace_invalid_file_name_characters(candidate);
+    let candidate = trim_file_name_end(&replaced);
+    if candidate.is_empty() {
+        return None;
+    }
+
     Some(candidate.to_string())
 }
 
+/// Strips the trailing dots and spaces that Windows silently drops.
+fn trim_file_name_end(value: &str) -> &str {
+    value.trim_end_matches(['.', ' ']).trim_start()
+}
+
+/// Shortens `stem` so `stem.container` fits the file name limit and escapes
+/// Windows device names such as `CON` or `LPT1`.
+fn fit_output_stem(stem: &str, container: &str) -> String {
+    let budget = MAX_OUTPUT_FILE_NAME_BYTES.saturating_sub(container.len() + 1);
+    let mut end = stem.len().min(budget);
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    let stem = trim_file_name_end(&stem[..end]);
+    if stem.is_empty() {
+        return format!("output{DEFAULT_OUTPUT_SUFFIX}");
+    }
+
+    let device_name_end = stem.find('.').unwrap_or(stem.len());
+    let device_name = stem[..device_name_end].trim_end();
+    if RESERVED_DEVICE_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device_name))
+    {
+        format!("{}_{}", &stem[..device_name_end], &stem[device_name_end..])
+    } else {
+        stem.to_string()
+    }
+}
+
 pub fn build_output_path(
     output_directory: &str,
     container: &str,
@@ -528,7 +1126,7 @@ pub fn build_output_path(
 ) -> String {
     let output_name = output_name
         .and_then(sanitize_output_name)
-        .unwrap_or_else(|| "output_converted".to_string());
+        .unwrap_or_else(|| format!("output{DEFAULT_OUTPUT_SUFFIX}"));
     let output_stem = output_name
         .rsplit_once('.')
         .filter(|(stem, extension)| {
@@ -538,20 +1136,93 @@ pub fn build_output_path(
                     .any(|known| known.eq_ignore_ascii_case(extension))
         })
         .map_or(output_name.as_str(), |(stem, _)| stem);
-    let separator = if output_directory.contains('\\') && !output_directory.contains('/') {
+    let output_stem = fit_output_stem(output_stem, container);
+    let separator = output_path_separator(output_directory);
+    let directory = output_directory.trim_end_matches(['/', '\\']);
+
+    format!("{directory}{separator}{output_stem}.{container}")
+}
+
+/// Appends a source's folder, relative to the scanned root, under
+/// `output_directory` so converted trees mirror their source layout.
+///
+/// Each component is sanitized like an output name; empty, `.` and `..`
+/// components are dropped so the result never leaves `output_directory`.
+#[must_use]
+pub fn mirrored_output_directory(output_directory: &str, relative_subpath: Option<&str>) -> String {
+    let components = relative_subpath
+        .into_iter()
+        .flat_map(|subpath| subpath.split(['/', '\\']))
+        .filter_map(sanitize_output_name)
+        .map(|component| fit_output_stem(&component, ""))
+        .collect::<Vec<_>>();
+    if components.is_empty() {
+        return output_directory.to_string();
+    }
+
+    let separator = output_path_separator(output_directory);
+    let directory = output_directory.trim_end_matches(['/', '\\']);
+    format!("{directory}{separator}{}", components.join(separator))
+}
+
+/// Separator to join onto `output_directory`, keeping Windows-style paths
+/// consistent.
+fn output_path_separator(output_directory: &str) -> &'static str {
+    if output_directory.contains('\\') && !output_directory.contains('/') {
         "\\"
     } else {
         "/"
-    };
-    let directory = output_directory.trim_end_matches(['/', '\\']);
+    }
+}
 
-    format!("{directory}{separator}{output_stem}.{container}")
+/// Checks that `output_directory` exists and accepts new files, creating it
+/// first when `create` is set.
+///
+/// Writability is tested by creating and removing a probe file, since
+/// permission bits do not reflect read-only mounts or network share ACLs.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the directory is missing
+/// and `create` is unset, when the path is not a directory, or when it cannot
+/// be created or written to.
+pub fn validate_output_directory(
+    output_directory: &str,
+    create: bool,
+) -> Result<(), ConversionError> {
+    let directory = Path::new(output_directory);
+    if !directory.exists() {
+        if !create {
+            return Err(ConversionError::InvalidInput(format!(
+                "Output directory does not exist: {output_directory}"
+            )));
+        }
+        fs::create_dir_all(directory).map_err(|error| {
+            ConversionError::InvalidInput(format!(
+                "Cannot create output directory {output_directory}: {error}"
+            ))
+        })?;
+    }
+    if !directory.is_dir() {
+        return Err(ConversionError::InvalidInput(format!(
+            "Output path is not a directory: {output_directory}"
+        )));
+    }
+
+    let probe_path = directory.join(format!(".frame-write-check-{}", std::process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe_path)
+        .map_err(|error| {
+            ConversionError::InvalidInput(format!(
+                "Output directory is not writable: {output_directory}: {error}"
+            ))
+        })?;
+    let _ = fs::remove_file(&probe_path);
+    Ok(())
 }
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "Validation intentionally mirrors UI options in one function for consistent backend guardrails"
-)]
 /// Validates a source path and conversion configuration before running `FFmpeg`.
 ///
 /// # Errors
@@ -564,17 +1235,62 @@ pub fn validate_task_input(
     config: &ConversionConfig,
 ) -> Result<(), ConversionError> {
     let input_path = Path::new(file_path);
-    if !input_path.exists() {
+    if input_url_scheme(file_path).is_some() {
+        validate_network_input(file_path)?;
+        validate_network_task(config)?;
+    } else if !input_path.exists() {
         return Err(ConversionError::InvalidInput(format!(
             "Input file does not exist: {file_path}"
         )));
-    }
-    if !input_path.is_file() {
+    } else if !input_path.is_file() {
         return Err(ConversionError::InvalidInput(format!(
             "Input path is not a file: {file_path}"
         )));
     }
+    if let Some(overlay) = config
+        .overlay
+        .as_ref()
+        .filter(|overlay| overlay.enabled && !overlay.path.trim().is_empty())
+        && !Path::new(&overlay.path).exists()
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Overlay image does not exist: {}",
+            overlay.path
+        )));
+    }
+
+    validate_conversion_settings(config)
+}
+
+/// Rejects settings that read a source more than once, which a network
+/// source would download again for every pass.
+fn validate_network_task(config: &ConversionConfig) -> Result<(), ConversionError> {
+    let unsupported = |feature: &str| {
+        Err(ConversionError::InvalidInput(format!(
+            "{feature} is not available for URL sources"
+        )))
+    };
+    if smart_cut_range(config).is_some() {
+        return unsupported("Smart cut");
+    }
+    if parallel_chunk_count(config).is_some() {
+        return unsupported("Parallel chunked encoding");
+    }
+    Ok(())
+}
 
+#[expect(
+    clippy::too_many_lines,
+    reason = "Validation intentionally mirrors UI options in one function for consistent backend guardrails"
+)]
+/// Validates the container, codec and option rules of `config` without
+/// touching any file, so settings can be checked before a source is chosen.
+///
+/// # Errors
+///
+/// Returns [`ConversionError`] when trim bounds are malformed or output
+/// settings are incompatible.
+pub fn validate_conversion_settings(config: &ConversionConfig) -> Result<(), ConversionError> {
     let start_time = config
         .start_time
         .as_deref()
@@ -593,10 +1309,31 @@ pub fn validate_task_input(
         )));
     }
     validate_media_filters(config)?;
+    validate_extra_args("input", &config.extra_input_args)?;
+    validate_extra_args("output", &config.extra_output_args)?;
+    if (!config.extra_input_args.is_empty() || !config.extra_output_args.is_empty())
+        && smart_cut_range(config).is_some()
+    {
+        return Err(ConversionError::InvalidInput(
+            "Extra FFmpeg arguments cannot be combined with smart cut".to_string(),
+        ));
+    }
     let is_copy_mode = processing_mode == "copy";
 
+    if let Some(rate) = config
+        .input_framerate
+        .as_deref()
+        .map(str::trim)
+        .filter(|rate| !rate.is_empty())
+        && parse_input_frame_rate(rate).is_none()
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Invalid input frame rate: {rate}"
+        )));
+    }
+
     if let Some(start) = start_time
-        && parse_time(start).is_none()
+        && parse_time_input(start).is_none()
     {
         return Err(ConversionError::InvalidInput(format!(
             "Invalid start time: {start}"
@@ -604,7 +1341,7 @@ pub fn validate_task_input(
     }
 
     if let Some(end) = end_time
-        && parse_time(end).is_none()
+        && parse_time_input(end).is_none()
     {
         return Err(ConversionError::InvalidInput(format!(
             "Invalid end time: {end}"
@@ -619,8 +1356,13 @@ pub fn validate_task_input(
             "End time must be greater than start time".to_string(),
         ));
     }
+    validate_trim_segments(config, None)?;
+    validate_remove_segments(config)?;
+    validate_output_split(config)?;
+    validate_parallel_chunks(config)?;
 
-    if !is_copy_mode && config.resolution == "custom" {
+    let is_fit = config.resolution == "fit";
+    if !is_copy_mode && (config.resolution == "custom" || is_fit) {
         let w_str = config.custom_width.as_deref().unwrap_or("-1");
         let h_str = config.custom_height.as_deref().unwrap_or("-1");
 
@@ -641,6 +1383,11 @@ pub fn validate_task_input(
                 "Resolution dimensions cannot be negative (except -1 for auto)".to_string(),
             ));
         }
+        if is_fit && w == -1 && h == -1 {
+            return Err(ConversionError::InvalidInput(
+                "Fit resolution needs a maximum width or height".to_string(),
+            ));
+        }
     }
 
     if !is_copy_mode
@@ -670,25 +1417,28 @@ pub fn validate_task_input(
         && !is_audio_only
         && !is_video_codec_allowed(&config.container, &config.video_codec)
     {
-        return Err(ConversionError::InvalidInput(format!(
-            "Video codec '{}' is not compatible with container '{}'",
-            config.video_codec, config.container
-        )));
+        return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+            stream: StreamKind::Video,
+            codec: config.video_codec.clone(),
+            container: config.container.clone(),
+            source_track: None,
+        }));
     }
 
     if !is_copy_mode
         && supports_audio
         && !is_audio_codec_allowed(&config.container, &config.audio_codec)
     {
-        return Err(ConversionError::InvalidInput(format!(
-            "Audio codec '{}' is not compatible with container '{}'",
-            config.audio_codec, config.container
-        )));
+        return Err(ConversionError::ContainerCodecMismatch(CodecMismatch {
+            stream: StreamKind::Audio,
+            codec: config.audio_codec.clone(),
+            container: config.container.clone(),
+            source_track: None,
+        }));
     }
 
     if !is_copy_mode && supports_audio {
-        let lossless_audio = ["flac", "alac", "pcm_s16le"];
-        let is_lossless = lossless_audio.contains(&config.audio_codec.as_str());
+        let is_lossless = LOSSLESS_AUDIO_CODECS.contains(&config.audio_codec.as_str());
         match config.audio_bitrate_mode.as_str() {
             "bitrate" => {
                 if !is_lossless {
@@ -738,19 +1488,11 @@ pub fn validate_task_input(
         ));
     }
 
-    if let Some(overlay) = config
+    if config
         .overlay
         .as_ref()
-        .filter(|overlay| overlay.enabled && !overlay.path.trim().is_empty())
+        .is_some_and(|overlay| overlay.enabled && !overlay.path.trim().is_empty())
     {
-        let overlay_path = Path::new(&overlay.path);
-        if !overlay_path.exists() {
-            return Err(ConversionError::InvalidInput(format!(
-                "Overlay image does not exist: {}",
-                overlay.path
-            )));
-        }
-
         if is_audio_only {
             return Err(ConversionError::InvalidInput(
                 "Overlay is not available for audio-only outputs".to_string(),
@@ -863,17 +1605,16 @@ pub fn validate_task_input(
     }
 
     if is_video_only && config.container.eq_ignore_ascii_case("gif") {
-        if !(2..=256).contains(&config.gif_colors) {
+        if !GIF_PALETTE_SIZES.contains(&config.gif_colors) {
             return Err(ConversionError::InvalidInput(format!(
-                "GIF palette size must be between 2 and 256 colors: {}",
+                "GIF palette size must be between {} and {} colors: {}",
+                GIF_PALETTE_SIZES.start(),
+                GIF_PALETTE_SIZES.end(),
                 config.gif_colors
             )));
         }
 
-        if !matches!(
-            config.gif_dither.as_str(),
-            "none" | "bayer" | "floyd_steinberg" | "sierra2_4a"
-        ) {
+        if !GIF_DITHER_MODES.contains(&config.gif_dither.as_str()) {
             return Err(ConversionError::InvalidInput(format!(
                 "Invalid GIF dither mode: {}",
                 config.gif_dither
@@ -884,20 +1625,196 @@ pub fn validate_task_input(
     if is_image_output {
         validate_image_encoding_settings(config)?;
     }
+    validate_sequence_output(config)?;
+    if config.image_target_max_kb == Some(0) {
+        return Err(ConversionError::InvalidInput(
+            "Image size budget must be greater than zero".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Most extra arguments a task may add before its input, and before its
+/// output.
+pub const MAX_EXTRA_ARGS: usize = 32;
+
+/// Rejects extra arguments that would fight the ones Frame generates: the
+/// overwrite flags, which the overwrite policy owns, another input, and
+/// values that follow no option, which `FFmpeg` would take as a second
+/// output.
+fn validate_extra_args(position: &str, args: &[String]) -> Result<(), ConversionError> {
+    if args.len() > MAX_EXTRA_ARGS {
+        return Err(ConversionError::InvalidInput(format!(
+            "At most {MAX_EXTRA_ARGS} extra {position} arguments are allowed"
+        )));
+    }
+    let mut option_needs_value = false;
+    for arg in args {
+        let arg = arg.as_str();
+        if arg.trim().is_empty() {
+            return Err(ConversionError::InvalidInput(format!(
+                "Extra {position} arguments cannot be empty"
+            )));
+        }
+        if matches!(arg, "-y" | "-n") {
+            return Err(ConversionError::InvalidInput(format!(
+                "Extra {position} arguments cannot include {arg}; the overwrite policy sets it"
+            )));
+        }
+        if arg == "-i" {
+            return Err(ConversionError::InvalidInput(format!(
+                "Extra {position} arguments cannot add another input"
+            )));
+        }
+        if is_option_name(arg) {
+            option_needs_value = true;
+        } else if option_needs_value {
+            option_needs_value = false;
+        } else {
+            return Err(ConversionError::InvalidInput(format!(
+                "Extra {position} argument {arg} follows no option and would be read as another output"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `arg` names an option rather than giving a value; negative
+/// numbers are values.
+fn is_option_name(arg: &str) -> bool {
+    arg.strip_prefix('-').is_some_and(|name| {
+        name.chars()
+            .next()
+            .is_some_and(|first| !first.is_ascii_digit() && first != '.')
+    })
+}
+
+/// Warning shown before the command of a task that adds its own arguments,
+/// since they are not checked against the ones Frame generates.
+#[must_use]
+pub fn extra_args_warning(config: &ConversionConfig) -> Option<String> {
+    let parts = [
+        ("before the input", &config.extra_input_args),
+        ("before the output", &config.extra_output_args),
+    ]
+    .into_iter()
+    .filter(|(_, args)| !args.is_empty())
+    .map(|(position, args)| format!("{position}: {}", args.join(" ")))
+    .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(format!("Extra FFmpeg arguments added {}", parts.join("; ")))
+}
+
+/// Libraries behind the optional filters Frame can request, named in the
+/// error when an `FFmpeg` build lacks the filter.
+const FILTER_LIBRARIES: [(&str, &str); 5] = [
+    ("subtitles", "libass"),
+    ("vidstabdetect", "libvidstab"),
+    ("vidstabtransform", "libvidstab"),
+    ("zscale", "libzimg"),
+    ("drawtext", "libfreetype"),
+];
+
+/// Checks that the `FFmpeg` build described by `capabilities` has every
+/// filter and the muxer `config` needs.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] naming the missing library,
+/// filter or muxer.
+pub fn validate_task_capabilities(
+    config: &ConversionConfig,
+    capabilities: &FfmpegCapabilities,
+) -> Result<(), ConversionError> {
+    let filters = build_video_filters(config, true)
+        .into_iter()
+        .chain(build_audio_filters(config));
+    for filter in filters {
+        let name = filter
+            .split(['=', ':', ','])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        if name.is_empty() || capabilities.has_filter(name) {
+            continue;
+        }
+        return Err(ConversionError::InvalidInput(
+            FILTER_LIBRARIES
+                .iter()
+                .find(|(filter, _)| *filter == name)
+                .map_or_else(
+                    || format!("Your FFmpeg build lacks the {name} filter"),
+                    |(_, library)| format!("Your FFmpeg build lacks {library}"),
+                ),
+        ));
+    }
+
+    if let Some(muxer) = output_muxer(&config.container)
+        && !capabilities.has_muxer(muxer)
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Your FFmpeg build cannot write {} files",
+            config.container
+        )));
+    }
 
     Ok(())
 }
 
+/// Rejects a task whose video encoder failed its self-test, so it fails at
+/// once with the reason instead of after waiting in the queue.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] carrying the self-test error
+/// when `self_test` is a failed test of the task's video encoder.
+pub fn validate_task_encoder(
+    config: &ConversionConfig,
+    self_test: &EncoderSelfTest,
+) -> Result<(), ConversionError> {
+    if self_test.passed
+        || self_test.encoder != config.video_codec
+        || is_audio_only_container(&config.container)
+    {
+        return Ok(());
+    }
+    Err(ConversionError::InvalidInput(
+        self_test
+            .error
+            .clone()
+            .unwrap_or_else(|| format!("{} is unusable", self_test.encoder)),
+    ))
+}
+
+/// Audio codecs stored without loss, which take no bitrate or VBR quality.
+pub(crate) const LOSSLESS_AUDIO_CODECS: [&str; 3] = ["flac", "alac", "pcm_s16le"];
+pub(crate) const GIF_PALETTE_SIZES: RangeInclusive<u16> = 2..=256;
+pub(crate) const GIF_DITHER_MODES: [&str; 4] = ["none", "bayer", "floyd_steinberg", "sierra2_4a"];
+pub(crate) const JPEG_QUALITIES: RangeInclusive<u32> = 1..=100;
+pub(crate) const JPEG_HUFFMAN_MODES: [&str; 2] = ["default", "optimal"];
+pub(crate) const MAX_WEBP_QUALITY: u32 = 100;
+pub(crate) const MAX_WEBP_COMPRESSION: u32 = 6;
+pub(crate) const WEBP_PRESETS: [&str; 6] =
+    ["default", "picture", "photo", "drawing", "icon", "text"];
+pub(crate) const MAX_PNG_COMPRESSION: u32 = 9;
+pub(crate) const PNG_PREDICTION_MODES: [&str; 6] = ["none", "sub", "up", "avg", "paeth", "mixed"];
+pub(crate) const TIFF_COMPRESSION_MODES: [&str; 4] = ["packbits", "raw", "lzw", "deflate"];
+
 fn validate_image_encoding_settings(config: &ConversionConfig) -> Result<(), ConversionError> {
     match config.video_codec.as_str() {
         "mjpeg" => {
-            if !(1..=100).contains(&config.image_jpeg_quality) {
+            if !JPEG_QUALITIES.contains(&config.image_jpeg_quality) {
                 return Err(ConversionError::InvalidInput(format!(
-                    "JPEG quality must be between 1 and 100: {}",
+                    "JPEG quality must be between {} and {}: {}",
+                    JPEG_QUALITIES.start(),
+                    JPEG_QUALITIES.end(),
                     config.image_jpeg_quality
                 )));
             }
-            if !matches!(config.image_jpeg_huffman.as_str(), "default" | "optimal") {
+            if !JPEG_HUFFMAN_MODES.contains(&config.image_jpeg_huffman.as_str()) {
                 return Err(ConversionError::InvalidInput(format!(
                     "Invalid JPEG Huffman mode: {}",
                     config.image_jpeg_huffman
@@ -905,22 +1822,19 @@ fn validate_image_encoding_settings(config: &ConversionConfig) -> Result<(), Con
             }
         }
         "libwebp" => {
-            if config.image_webp_quality > 100 {
+            if config.image_webp_quality > MAX_WEBP_QUALITY {
                 return Err(ConversionError::InvalidInput(format!(
-                    "WebP quality must be between 0 and 100: {}",
+                    "WebP quality must be between 0 and {MAX_WEBP_QUALITY}: {}",
                     config.image_webp_quality
                 )));
             }
-            if config.image_webp_compression > 6 {
+            if config.image_webp_compression > MAX_WEBP_COMPRESSION {
                 return Err(ConversionError::InvalidInput(format!(
-                    "WebP compression effort must be between 0 and 6: {}",
+                    "WebP compression effort must be between 0 and {MAX_WEBP_COMPRESSION}: {}",
                     config.image_webp_compression
                 )));
             }
-            if !matches!(
-                config.image_webp_preset.as_str(),
-                "default" | "picture" | "photo" | "drawing" | "icon" | "text"
-            ) {
+            if !WEBP_PRESETS.contains(&config.image_webp_preset.as_str()) {
                 return Err(ConversionError::InvalidInput(format!(
                     "Invalid WebP preset: {}",
                     config.image_webp_preset
@@ -928,28 +1842,26 @@ fn validate_image_encoding_settings(config: &ConversionConfig) -> Result<(), Con
             }
         }
         "png" => {
-            if config.image_png_compression > 9 {
+            if config.image_png_compression > MAX_PNG_COMPRESSION {
                 return Err(ConversionError::InvalidInput(format!(
-                    "PNG compression level must be between 0 and 9: {}",
+                    "PNG compression level must be between 0 and {MAX_PNG_COMPRESSION}: {}",
                     config.image_png_compression
                 )));
             }
-            if !matches!(
-                config.image_png_prediction.as_str(),
-                "none" | "sub" | "up" | "avg" | "paeth" | "mixed"
-            ) {
+            if !PNG_PREDICTION_MODES.contains(&config.image_png_prediction.as_str()) {
                 return Err(ConversionError::InvalidInput(format!(
                     "Invalid PNG prediction mode: {}",
                     config.image_png_prediction
                 )));
             }
         }
-        "tiff"
-            if !matches!(
-                config.image_tiff_compression.as_str(),
-                "packbits" | "raw" | "lzw" | "deflate"
-            ) =>
-        {
+        "libaom-av1" if config.image_avif_crf > MAX_AVIF_CRF => {
+            return Err(ConversionError::InvalidInput(format!(
+                "AVIF CRF must be between 0 and {MAX_AVIF_CRF}: {}",
+                config.image_avif_crf
+            )));
+        }
+        "tiff" if !TIFF_COMPRESSION_MODES.contains(&config.image_tiff_compression.as_str()) => {
             return Err(ConversionError::InvalidInput(format!(
                 "Invalid TIFF compression mode: {}",
                 config.image_tiff_compression
@@ -965,6 +1877,7 @@ fn validate_image_encoding_settings(config: &ConversionConfig) -> Result<(), Con
 mod tests {
     use super::*;
     use crate::filters::EVEN_DIMENSIONS_FILTER;
+    use crate::types::TrimSegment;
     use std::{
         fs,
         path::PathBuf,
@@ -1000,11 +1913,14 @@ mod tests {
             custom_height: None,
             scaling_algorithm: "bicubic".to_string(),
             fps: "original".to_string(),
+            input_framerate: None,
             crf: 23,
             quality: 50,
             preset: "medium".to_string(),
             start_time: None,
             end_time: None,
+            segments: Vec::new(),
+            remove_segments: Vec::new(),
             metadata: MetadataConfig::default(),
             rotation: "0".to_string(),
             flip_horizontal: false,
@@ -1015,6 +1931,7 @@ mod tests {
             nvenc_temporal_aq: false,
             videotoolbox_allow_sw: false,
             hw_decode: false,
+            hw_device: None,
             pixel_format: "auto".to_string(),
             image_jpeg_quality: 85,
             image_jpeg_huffman: "optimal".to_string(),
@@ -1025,24 +1942,333 @@ mod tests {
             image_png_compression: 9,
             image_png_prediction: "paeth".to_string(),
             image_tiff_compression: "packbits".to_string(),
+            image_avif_crf: 30,
+            image_target_max_kb: None,
             gif_colors: 256,
             gif_dither: "sierra2_4a".to_string(),
             gif_loop: 0,
+            overwrite_policy: OverwritePolicy::default(),
+            split: None,
+            split_by_chapters: false,
+            image_sequence_output: false,
+            smart_cut: false,
+            seek_mode: SeekMode::default(),
+            parallel_chunks: None,
+            verify_output: false,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+        }
+    }
+
+    fn sample_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            media_kind: "video".to_string(),
+            video_codec: Some("h264".to_string()),
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                codec: "aac".to_string(),
+                channels: "2".to_string(),
+                ..AudioTrack::default()
+            }],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    fn pq_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            color_transfer: Some("smpte2084".to_string()),
+            bit_depth: Some(10),
+            ..sample_probe()
         }
     }
 
-    fn sample_probe() -> ProbeMetadata {
-        ProbeMetadata {
-            media_kind: "video".to_string(),
-            video_codec: Some("h264".to_string()),
-            audio_tracks: vec![AudioTrack {
-                index: 1,
-                codec: "aac".to_string(),
-                channels: "2".to_string(),
-                ..AudioTrack::default()
-            }],
-            ..ProbeMetadata::default()
-        }
+    #[test]
+    fn hdr_encoding_warning_flags_eight_bit_targets_for_hdr_sources() {
+        let mut config = sample_config("mp4", "libx264");
+        config.pixel_format = "yuv420p".to_string();
+
+        let warning = hdr_encoding_warning(&config, &pq_probe()).expect("warning expected");
+        assert!(warning.contains("PQ HDR"));
+
+        let nvenc = sample_config("mp4", "h264_nvenc");
+        assert!(hdr_encoding_warning(&nvenc, &pq_probe()).is_some());
+    }
+
+    #[test]
+    fn hdr_encoding_warning_ignores_high_bit_depth_targets_and_sdr_sources() {
+        let hevc = sample_config("mp4", "libx265");
+        assert!(hdr_encoding_warning(&hevc, &pq_probe()).is_none());
+
+        let mut ten_bit = sample_config("mp4", "libx264");
+        ten_bit.pixel_format = "yuv420p10le".to_string();
+        assert!(hdr_encoding_warning(&ten_bit, &pq_probe()).is_none());
+
+        let mut eight_bit = sample_config("mp4", "libx264");
+        eight_bit.pixel_format = "yuv420p".to_string();
+        assert!(hdr_encoding_warning(&eight_bit, &sample_probe()).is_none());
+    }
+
+    fn cover_art_probe() -> ProbeMetadata {
+        crate::probe::parse_ffprobe_stdout(
+            "/tmp/track.mp3",
+            include_str!("../tests/fixtures/ffprobe_mp3_cover_art.json"),
+        )
+        .expect("cover art fixture should parse")
+    }
+
+    #[test]
+    fn validate_stream_copy_compatibility_ignores_cover_art() {
+        let mut config = sample_config("mp3", "libx264");
+        config.processing_mode = "copy".to_string();
+        config.audio_codec = "mp3".to_string();
+
+        assert!(validate_stream_copy_compatibility(&config, &cover_art_probe()).is_ok());
+    }
+
+    #[test]
+    fn build_ffmpeg_args_maps_primary_video_without_attached_pictures() {
+        let config = sample_config("mp4", "libx264");
+
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:V:0"]));
+        assert!(!args.iter().any(|arg| arg == "0:v:0"));
+    }
+
+    #[test]
+    fn build_ffmpeg_args_seeks_the_input_for_a_single_trim_range() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("00:00:05".to_string());
+        config.end_time = Some("00:00:30".to_string());
+
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+
+        assert!(args_contains_pair(&args, "-ss", "5.000"));
+        assert!(args_contains_pair(&args, "-t", "25.000"));
+        assert!(!args.iter().any(|arg| arg.contains("concat=")));
+    }
+
+    fn seek_positions(args: &[String]) -> (Vec<(usize, &str)>, usize) {
+        let input_index = args.iter().position(|arg| arg == "-i").unwrap();
+        let seeks = args
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] == "-ss")
+            .map(|(index, pair)| (index, pair[1].as_str()))
+            .collect();
+        (seeks, input_index)
+    }
+
+    #[test]
+    fn build_ffmpeg_args_places_the_seek_for_each_seek_mode() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("00:00:30".to_string());
+        config.end_time = Some("00:00:40".to_string());
+
+        config.seek_mode = SeekMode::Fast;
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+        let (seeks, input_index) = seek_positions(&args);
+        assert_eq!(seeks.len(), 1);
+        assert!(seeks[0].0 < input_index);
+        assert_eq!(seeks[0].1, "30.000");
+
+        config.seek_mode = SeekMode::Accurate;
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+        let (seeks, input_index) = seek_positions(&args);
+        assert_eq!(seeks.len(), 1);
+        assert!(seeks[0].0 > input_index);
+        assert_eq!(seeks[0].1, "30.000");
+        assert!(args_contains_pair(&args, "-t", "10.000"));
+
+        config.seek_mode = SeekMode::Hybrid;
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+        let (seeks, input_index) = seek_positions(&args);
+        assert_eq!(seeks.len(), 2);
+        assert!(seeks[0].0 < input_index);
+        assert_eq!(seeks[0].1, "25.000");
+        assert!(seeks[1].0 > input_index);
+        assert_eq!(seeks[1].1, "5.000");
+        assert!(args_contains_pair(&args, "-t", "10.000"));
+    }
+
+    #[test]
+    fn build_ffmpeg_args_hybrid_seek_near_the_start_skips_the_pre_seek() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("00:00:03".to_string());
+        config.seek_mode = SeekMode::Hybrid;
+
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+
+        let (seeks, input_index) = seek_positions(&args);
+        assert_eq!(seeks.len(), 1);
+        assert!(seeks[0].0 > input_index);
+        assert_eq!(seeks[0].1, "3.000");
+    }
+
+    #[test]
+    fn build_ffmpeg_args_rebases_burned_subtitles_after_a_hybrid_pre_seek() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("00:00:30".to_string());
+        config.subtitle_burn_path = Some("/tmp/sub.srt".to_string());
+        config.seek_mode = SeekMode::Hybrid;
+
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+        let filters = args
+            .windows(2)
+            .find(|pair| pair[0] == "-vf")
+            .map(|pair| pair[1].as_str())
+            .expect("burn-in should add a video filter");
+        assert!(filters.contains("setpts=PTS+25.000/TB,subtitles="));
+        assert!(filters.contains("setpts=PTS-25.000/TB"));
+
+        config.seek_mode = SeekMode::Accurate;
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+        assert!(!args.iter().any(|arg| arg.contains("setpts=PTS+")));
+    }
+
+    #[test]
+    fn ffmpeg_command_input_with_trim_seeks_around_the_input() {
+        let mut config = sample_config("mp4", "libx264");
+        config.seek_mode = SeekMode::Hybrid;
+        config.start_time = Some("20".to_string());
+        config.end_time = Some("30".to_string());
+        let probe = sample_probe();
+
+        let mut command = FfmpegCommand::new(&config, &probe).expect("command should start");
+        command.input_with_trim("input.mp4");
+
+        assert_eq!(
+            command.args(),
+            [
+                "-ss",
+                "15.000",
+                "-i",
+                "input.mp4",
+                "-ss",
+                "5.000",
+                "-t",
+                "10.000"
+            ]
+        );
+    }
+
+    #[test]
+    fn ffmpeg_command_apply_metadata_follows_the_metadata_mode() {
+        let mut config = sample_config("mp4", "libx264");
+        config.metadata.mode = MetadataMode::Replace;
+        config.metadata.title = Some("Trip".to_string());
+        let probe = sample_probe();
+
+        let mut command = FfmpegCommand::new(&config, &probe).expect("command should start");
+        command.apply_metadata();
+
+        assert_eq!(
+            command.args(),
+            ["-map_metadata", "-1", "-metadata", "title=Trip"]
+        );
+    }
+
+    #[test]
+    fn ffmpeg_command_map_streams_copies_the_selected_tracks() {
+        let mut config = sample_config("mkv", "libx264");
+        config.processing_mode = "copy".to_string();
+        let probe = sample_probe();
+
+        let mut command = FfmpegCommand::new(&config, &probe).expect("command should start");
+        command.map_streams().expect("streams should map");
+
+        assert_eq!(
+            command.args(),
+            ["-map", "0:V?", "-map", "0:1", "-c", "copy"]
+        );
+    }
+
+    #[test]
+    fn ffmpeg_command_rate_control_only_applies_to_video_encodes() {
+        let mut config = sample_config("mp4", "libx264");
+        config.pixel_format = "yuv420p10le".to_string();
+        let probe = sample_probe();
+        let audio = sample_config("mp3", "libx264");
+
+        let mut video = FfmpegCommand::new(&config, &probe).expect("command should start");
+        video.rate_control();
+        let mut audio_only = FfmpegCommand::new(&audio, &probe).expect("command should start");
+        audio_only.rate_control();
+
+        assert_eq!(
+            video.args(),
+            [
+                "-c:v",
+                "libx264",
+                "-crf",
+                "23",
+                "-preset",
+                "medium",
+                "-pix_fmt",
+                "yuv420p10le"
+            ]
+        );
+        assert!(audio_only.args().is_empty());
+    }
+
+    #[test]
+    fn ffmpeg_command_finish_adds_audio_filters_and_the_output_target() {
+        let mut config = sample_config("m4a", "libx264");
+        config.audio_volume = 50.0;
+        let probe = sample_probe();
+
+        let command = FfmpegCommand::new(&config, &probe).expect("command should start");
+
+        assert_eq!(
+            command.finish("/out/clip.m4a.part"),
+            [
+                "-af",
+                "volume=0.500",
+                "-dn",
+                "-n",
+                "-f",
+                "ipod",
+                "/out/clip.m4a.part"
+            ]
+        );
+    }
+
+    #[test]
+    fn build_ffmpeg_args_joins_multiple_trim_segments_with_concat() {
+        let mut config = sample_config("mp4", "libx264");
+        config.segments = vec![
+            TrimSegment {
+                start: "0:00".to_string(),
+                end: "0:02".to_string(),
+            },
+            TrimSegment {
+                start: "0:04".to_string(),
+                end: "0:06".to_string(),
+            },
+        ];
+
+        let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+            .expect("arguments should build");
+
+        assert!(!args.iter().any(|arg| arg == "-ss" || arg == "-t"));
+        assert!(!args.iter().any(|arg| arg == "-vf" || arg == "-af"));
+        let graph = args
+            .windows(2)
+            .find(|pair| pair[0] == "-filter_complex")
+            .map(|pair| pair[1].as_str())
+            .expect("segments should use a filter graph");
+        assert!(graph.contains("concat=n=2:v=1:a=1[vcat][acat0]"));
+        assert!(args_contains_pair(&args, "-map", "[vout]"));
+        assert!(args_contains_pair(&args, "-map", "[aout0]"));
+        assert!(!args_contains_pair(&args, "-map", "0:1"));
     }
 
     #[test]
@@ -1056,6 +2282,58 @@ mod tests {
         assert_eq!(args[vf_index + 1], EVEN_DIMENSIONS_FILTER);
     }
 
+    #[test]
+    fn build_ffmpeg_args_plays_animated_gifs_once_at_their_own_timing() {
+        let probe = crate::probe::parse_ffprobe_stdout(
+            "reaction.gif",
+            include_str!("../tests/fixtures/ffprobe_animated_gif.json"),
+        )
+        .expect("fixture should parse");
+        let mp4 = build_ffmpeg_args(
+            "reaction.gif",
+            "reaction.mp4",
+            &sample_config("mp4", "libx264"),
+            &probe,
+        )
+        .expect("mp4 arguments should build");
+        let webm = build_ffmpeg_args(
+            "reaction.gif",
+            "reaction.webm",
+            &sample_config("webm", "vp9"),
+            &probe,
+        )
+        .expect("webm arguments should build");
+
+        assert_eq!(mp4[..4], ["-ignore_loop", "1", "-i", "reaction.gif"]);
+        assert!(mp4.windows(2).any(|pair| pair == ["-pix_fmt", "yuv420p"]));
+        assert!(mp4.windows(2).any(|pair| pair == ["-fps_mode", "vfr"]));
+        assert!(
+            mp4.windows(2)
+                .any(|pair| pair == ["-vf", EVEN_DIMENSIONS_FILTER])
+        );
+        assert!(webm.windows(2).any(|pair| pair == ["-fps_mode", "vfr"]));
+        assert!(!webm.iter().any(|arg| arg == "-pix_fmt"));
+    }
+
+    #[test]
+    fn build_ffmpeg_args_uses_a_fixed_rate_for_animated_sources_when_asked() {
+        let probe = ProbeMetadata {
+            video_codec: Some("gif".to_string()),
+            animated: true,
+            ..ProbeMetadata::default()
+        };
+        let mut config = sample_config("mp4", "libx264");
+        config.fps = "30".to_string();
+        config.pixel_format = "yuv444p".to_string();
+
+        let args = build_ffmpeg_args("in.gif", "out.mp4", &config, &probe)
+            .expect("arguments should build");
+
+        assert!(!args.iter().any(|arg| arg == "-fps_mode"));
+        assert!(args.windows(2).any(|pair| pair == ["-r", "30"]));
+        assert!(args.windows(2).any(|pair| pair == ["-pix_fmt", "yuv444p"]));
+    }
+
     #[test]
     fn build_ffmpeg_args_does_not_add_even_dimensions_guard_for_image_output() {
         let config = sample_config("png", "png");
@@ -1066,6 +2344,28 @@ mod tests {
         assert!(!args.iter().any(|arg| arg == EVEN_DIMENSIONS_FILTER));
     }
 
+    #[test]
+    fn build_ffmpeg_args_converts_display_p3_photos_to_srgb_stills() {
+        let probe = ProbeMetadata {
+            media_kind: "image".to_string(),
+            video_codec: Some("hevc".to_string()),
+            pixel_format: Some("yuvj420p".to_string()),
+            color_primaries: Some("smpte432".to_string()),
+            rotation: Some(90.0),
+            ..ProbeMetadata::default()
+        };
+        let mut config = sample_config("jpg", "mjpeg");
+        config.rotation = "90".to_string();
+
+        let args = build_ffmpeg_args("IMG_0420.HEIC", "IMG_0420.jpg", &config, &probe)
+            .expect("arguments should build");
+
+        assert!(args.windows(2).any(|pair| {
+            pair[0] == "-vf" && pair[1] == format!("{DISPLAY_P3_TO_SRGB_FILTER},transpose=1")
+        }));
+        assert!(args.windows(2).any(|pair| pair == ["-frames:v", "1"]));
+    }
+
     #[test]
     fn build_output_path_preserves_periods_in_output_name_on_unc_share() {
         let output = build_output_path(
@@ -1087,6 +2387,178 @@ mod tests {
         assert_eq!(output, "/tmp/render.mp4");
     }
 
+    #[test]
+    fn default_output_stem_strips_only_the_final_extension() {
+        let cases = [
+            ("/media/movie.mkv", "_converted", "movie_converted"),
+            (
+                "show.S01E01.1080p.mkv",
+                "_converted",
+                "show.S01E01.1080p_converted",
+            ),
+            (
+                "/media/no_extension",
+                "_converted",
+                "no_extension_converted",
+            ),
+            ("/media/.hidden", "_converted", "output_converted"),
+            ("/media/trailing.", "_converted", "trailing._converted"),
+            (r"C:\Videos\clip.MOV", "_converted", "clip_converted"),
+            ("/media/movie.mkv", "-small", "movie-small"),
+            ("/media/movie.mkv", "", "movie"),
+            ("", "_converted", "output_converted"),
+        ];
+
+        for (input, suffix, expected) in cases {
+            assert_eq!(
+                default_output_stem(input, suffix),
+                expected,
+                "input {input:?} with suffix {suffix:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn build_output_path_handles_directory_roots_and_separators() {
+        let cases = [
+            ("/exports/", Some("clip"), "/exports/clip.mp4"),
+            ("/", Some("clip"), "/clip.mp4"),
+            (r"C:\", Some("clip"), r"C:\clip.mp4"),
+            ("C:/", Some("clip"), "C:/clip.mp4"),
+            (r"D:\Exports\\", Some("clip"), r"D:\Exports\clip.mp4"),
+            (r"\\server\share\", Some("clip"), r"\\server\share\clip.mp4"),
+            ("/exports", Some("../escape"), "/exports/escape.mp4"),
+            ("/exports", Some("  "), "/exports/output_converted.mp4"),
+            ("/exports", None, "/exports/output_converted.mp4"),
+            (
+                "/exports",
+                Some("show.S01E01.mkv"),
+                "/exports/show.S01E01.mp4",
+            ),
+            ("/exports", Some("show.S01E01"), "/exports/show.S01E01.mp4"),
+        ];
+
+        for (directory, output_name, expected) in cases {
+            assert_eq!(
+                build_output_path(directory, "mp4", output_name),
+                expected,
+                "directory {directory:?} with name {output_name:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn build_ffmpeg_args_names_muxer_for_temporary_output_paths() {
+        let config = sample_config("mkv", "libx264");
+
+        let final_args = build_ffmpeg_args("input.mov", "/out/clip.mkv", &config, &sample_probe())
+            .expect("arguments should build");
+        let part_args = build_ffmpeg_args(
+            "input.mov",
+            "/out/clip.mkv.task-1.part",
+            &config,
+            &sample_probe(),
+        )
+        .expect("arguments should build");
+
+        assert!(!final_args.iter().any(|arg| arg == "-f"));
+        assert!(args_contains_pair(&part_args, "-f", "matroska"));
+        assert_eq!(
+            part_args.last().map(String::as_str),
+            Some("/out/clip.mkv.task-1.part")
+        );
+    }
+
+    #[test]
+    fn build_ffmpeg_args_keeps_single_format_flag_for_gif_part_files() {
+        let config = sample_config("gif", "gif");
+
+        let args = build_ffmpeg_args(
+            "input.mov",
+            "/out/clip.gif.task-1.part",
+            &config,
+            &sample_probe(),
+        )
+        .expect("arguments should build");
+
+        assert_eq!(args.iter().filter(|arg| *arg == "-f").count(), 1);
+    }
+
+    #[test]
+    fn build_output_path_escapes_invalid_and_reserved_names() {
+        let cases = [
+            ("my:video?", "my_video_.mp4"),
+            ("a<b>c|d*e\"f", "a_b_c_d_e_f.mp4"),
+            ("tab\tname", "tab_name.mp4"),
+            ("trailing. . ", "trailing.mp4"),
+            ("CON", "CON_.mp4"),
+            ("con.mp4", "con_.mp4"),
+            ("Lpt9.backup", "Lpt9_.backup.mp4"),
+            ("COM10", "COM10.mp4"),
+            ("console", "console.mp4"),
+            ("...", "output_converted.mp4"),
+        ];
+
+        for (output_name, expected) in cases {
+            assert_eq!(
+                build_output_path("/exports", "mp4", Some(output_name)),
+                format!("/exports/{expected}"),
+                "output name {output_name:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn build_output_path_keeps_nasty_names_inside_the_directory() {
+        let long_ascii = "x".repeat(400);
+        let long_unicode = "\u{1F3AC}".repeat(120);
+        let nasty = [
+            "",
+            " ",
+            ".",
+            "..",
+            "../../etc/passwd",
+            "..\\..\\boot.ini",
+            "C:\\evil",
+            "a/b\\c",
+            "\u{0}\u{1}\u{7f}",
+            "NUL.txt",
+            " aux ",
+            "name\r\n",
+            long_ascii.as_str(),
+            long_unicode.as_str(),
+            "\u{e9}t\u{e9} 2024.mkv",
+        ];
+
+        for name in nasty {
+            let output = build_output_path("/exports", "webm", Some(name));
+            let file_name = output
+                .strip_prefix("/exports/")
+                .unwrap_or_else(|| panic!("{name:?} escaped the directory: {output}"));
+            let stem = file_name
+                .strip_suffix(".webm")
+                .unwrap_or_else(|| panic!("{name:?} lost the extension: {output}"));
+
+            assert!(file_name.len() <= MAX_OUTPUT_FILE_NAME_BYTES, "{name:?}");
+            assert!(!stem.is_empty() && !stem.ends_with(['.', ' ']), "{name:?}");
+            assert!(
+                !file_name.chars().any(|character| character.is_control()
+                    || matches!(
+                        character,
+                        '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*'
+                    )),
+                "{name:?} kept an invalid character: {output}"
+            );
+            let device_name = stem.split('.').next().unwrap_or_default().trim_end();
+            assert!(
+                !RESERVED_DEVICE_NAMES
+                    .iter()
+                    .any(|reserved| reserved.eq_ignore_ascii_case(device_name)),
+                "{name:?} produced a reserved name: {output}"
+            );
+        }
+    }
+
     #[test]
     fn build_output_path_uses_selected_output_directory() {
         let output = build_output_path("/exports", "mp4", Some("render"));
@@ -1094,6 +2566,21 @@ mod tests {
         assert_eq!(output, "/exports/render.mp4");
     }
 
+    #[test]
+    fn mirrored_output_directory_keeps_subfolders_inside_the_directory() {
+        let cases = [
+            ("/exports", None, "/exports"),
+            ("/exports/", Some("week 1/day:2"), "/exports/week 1/day_2"),
+            ("/exports", Some("../../etc/./CON"), "/exports/etc/CON_"),
+            (r"C:\Renders", Some(r"week 1\lab"), r"C:\Renders\week 1\lab"),
+            ("/exports", Some("/.."), "/exports"),
+        ];
+
+        for (directory, subpath, expected) in cases {
+            assert_eq!(mirrored_output_directory(directory, subpath), expected);
+        }
+    }
+
     #[test]
     fn build_ffmpeg_args_disables_output_overwrite_for_reencode() {
         let config = sample_config("mp4", "libx264");
@@ -1110,6 +2597,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_ffmpeg_args_allows_output_overwrite_for_overwrite_policy() {
+        let mut config = sample_config("mp4", "libx264");
+        config.overwrite_policy = OverwritePolicy::Overwrite;
+
+        let args = build_ffmpeg_args("input.mov", "output.mp4", &config, &sample_probe())
+            .expect("re-encode arguments should build");
+
+        assert_eq!(
+            (
+                args.iter().any(|arg| arg == "-n"),
+                args.iter().any(|arg| arg == "-y")
+            ),
+            (false, true)
+        );
+    }
+
     #[test]
     fn build_ffmpeg_args_disables_output_overwrite_for_stream_copy() {
         let mut config = sample_config("mp4", "libx264");
@@ -1170,6 +2674,24 @@ mod tests {
         assert!(args_contains_pair(&args, "-preset", "photo"));
     }
 
+    #[test]
+    fn build_ffmpeg_args_encodes_a_single_avif_still_with_crf() {
+        let mut config = sample_config("avif", "libaom-av1");
+        config.image_avif_crf = 24;
+        config.crf = 18;
+        config.video_bitrate_mode = "bitrate".to_string();
+
+        let args = build_ffmpeg_args("photo.png", "photo.avif.part", &config, &sample_probe())
+            .expect("arguments should build");
+
+        assert!(args_contains_pair(&args, "-c:v", "libaom-av1"));
+        assert!(args_contains_pair(&args, "-crf", "24"));
+        assert!(args_contains_pair(&args, "-still-picture", "1"));
+        assert!(args_contains_pair(&args, "-frames:v", "1"));
+        assert!(args_contains_pair(&args, "-f", "avif"));
+        assert!(!args.iter().any(|arg| arg == "-b:v" || arg == "-c:a"));
+    }
+
     #[test]
     fn build_ffmpeg_args_adds_tiff_compression_option() {
         let mut config = sample_config("tiff", "tiff");
@@ -1274,6 +2796,172 @@ mod tests {
         assert!(args_contains_pair(&args, "-c:s", "copy"));
     }
 
+    #[test]
+    fn build_ffmpeg_args_reencodes_audio_whenever_audio_filters_are_active() {
+        for (selected_tracks, normalize) in [
+            (vec![], false),
+            (vec![], true),
+            (vec![1], false),
+            (vec![1], true),
+        ] {
+            let mut config = sample_config("mp4", "libx264");
+            config.selected_audio_tracks.clone_from(&selected_tracks);
+            config.audio_normalize = normalize;
+
+            let args = build_ffmpeg_args("input.mp4", "output.mp4", &config, &sample_probe())
+                .expect("re-encode arguments should build");
+
+            assert!(args_contains_pair(&args, "-map", "0:1"));
+            assert!(args_contains_pair(&args, "-c:a", "aac"));
+            assert!(!args_contains_pair(&args, "-c:a", "copy"));
+            assert_eq!(
+                args.iter().any(|arg| arg == "-af"),
+                normalize,
+                "tracks {selected_tracks:?}, normalize {normalize}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_output_directory_creates_missing_directory_only_when_requested() {
+        let file = temporary_input_file("output-dir-parent");
+        let directory = file.with_extension("out");
+        let directory_str = directory.to_string_lossy().into_owned();
+
+        let error = validate_output_directory(&directory_str, false)
+            .expect_err("missing directory should be rejected");
+        assert!(error.to_string().contains("does not exist"));
+
+        validate_output_directory(&directory_str, true).expect("directory should be created");
+        assert!(directory.is_dir());
+        assert_eq!(
+            fs::read_dir(&directory)
+                .expect("directory should be readable")
+                .count(),
+            0
+        );
+
+        let error = validate_output_directory(&file.to_string_lossy(), true)
+            .expect_err("file path should be rejected");
+        assert!(error.to_string().contains("not a directory"));
+
+        let _ = fs::remove_dir(directory);
+        let _ = fs::remove_file(file);
+    }
+
+    #[test]
+    fn validate_task_input_rejects_audio_filters_in_stream_copy_mode() {
+        let path = temporary_input_file("copy-audio-filters");
+        let mut config = sample_config("mp4", "libx264");
+        config.processing_mode = "copy".to_string();
+        config.audio_normalize = true;
+
+        let error = validate_task_input(&path.to_string_lossy(), &config)
+            .expect_err("stream copy cannot apply audio filters");
+
+        let _ = fs::remove_file(path);
+        assert!(error.to_string().contains("re-encod"));
+    }
+
+    #[test]
+    fn url_sources_skip_file_checks_but_not_multi_pass_settings() {
+        let url = "https://cdn.example.com/feeds/ep-12.mp3?token=abc";
+        let mut smart_cut = sample_config("mp4", "libx264");
+        smart_cut.processing_mode = "copy".to_string();
+        smart_cut.smart_cut = true;
+        smart_cut.start_time = Some("10".to_string());
+        let mut chunked = sample_config("mp4", "libx264");
+        chunked.parallel_chunks = Some(4);
+
+        assert!(validate_task_input(url, &sample_config("mp4", "libx264")).is_ok());
+        for (config, feature) in [(smart_cut, "Smart cut"), (chunked, "Parallel chunked")] {
+            let error =
+                validate_task_input(url, &config).expect_err("multi-pass settings need a file");
+            assert!(
+                error
+                    .to_string()
+                    .starts_with(&format!("Invalid input: {feature}"))
+            );
+        }
+        let error =
+            validate_task_input("ftp://example.com/a.mp4", &sample_config("mp4", "libx264"))
+                .expect_err("only http sources are read");
+        assert!(error.to_string().contains("Unsupported URL scheme"));
+        assert_eq!(default_output_stem(url, "_converted"), "ep-12_converted");
+    }
+
+    #[test]
+    fn url_sources_read_with_network_timeouts() {
+        let url = "https://example.com/live/index.m3u8";
+        let args = build_ffmpeg_args(
+            url,
+            "output.mp4",
+            &sample_config("mp4", "libx264"),
+            &sample_probe(),
+        )
+        .expect("re-encode arguments should build");
+        let input_index = args.iter().position(|arg| arg == "-i").unwrap();
+
+        assert!(args_contains_pair(
+            &args[..input_index],
+            "-rw_timeout",
+            "15000000"
+        ));
+        assert_eq!(args[input_index + 1], url);
+    }
+
+    #[test]
+    fn validate_conversion_settings_checks_codecs_without_a_source_file() {
+        assert!(validate_conversion_settings(&sample_config("mp4", "libx264")).is_ok());
+
+        let error = validate_conversion_settings(&sample_config("webm", "libx264"))
+            .expect_err("webm cannot hold H.264");
+
+        assert!(matches!(error, ConversionError::ContainerCodecMismatch(_)));
+    }
+
+    #[test]
+    fn validate_task_input_accepts_frame_based_trim_times_before_probing() {
+        let path = temporary_input_file("frame-trim-times");
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("1234f".to_string());
+        config.end_time = Some("00:00:41:16".to_string());
+        config.segments = vec![
+            TrimSegment {
+                start: "00:00:01:00".to_string(),
+                end: "240f".to_string(),
+            },
+            TrimSegment {
+                start: "00:00:20".to_string(),
+                end: "00:00:30".to_string(),
+            },
+        ];
+        let accepted = validate_task_input(&path.to_string_lossy(), &config);
+
+        config.end_time = Some("00:00:41;16".to_string());
+        let rejected = validate_task_input(&path.to_string_lossy(), &config);
+
+        let _ = fs::remove_file(path);
+        assert!(accepted.is_ok(), "{accepted:?}");
+        assert!(
+            rejected
+                .expect_err("drop-frame timecodes are not accepted")
+                .to_string()
+                .contains("Invalid end time: 00:00:41;16")
+        );
+    }
+
+    #[test]
+    fn frame_progress_applies_only_when_frames_map_one_to_one() {
+        let mut resampled = sample_config("mp4", "libx264");
+        resampled.fps = "30".to_string();
+
+        assert!(frame_progress_applies(&sample_config("mp4", "libx264")));
+        assert!(!frame_progress_applies(&resampled));
+        assert!(!frame_progress_applies(&sample_config("gif", "gif")));
+        assert!(!frame_progress_applies(&sample_config("mp3", "libx264")));
+    }
+
     #[test]
     fn validate_task_input_rejects_invalid_webp_compression_level() {
         let path = temporary_input_file("invalid-webp-compression");
@@ -1287,6 +2975,328 @@ mod tests {
         assert!(error.to_string().contains("WebP compression effort"));
     }
 
+    #[test]
+    fn validate_task_input_rejects_unreadable_input_frame_rates() {
+        let path = temporary_input_file("invalid-input-framerate");
+        let mut config = sample_config("mp4", "libx264");
+        config.input_framerate = Some("24000/1001".to_string());
+        let accepted = validate_task_input(&path.to_string_lossy(), &config);
+        config.input_framerate = Some("fast".to_string());
+
+        let error = validate_task_input(&path.to_string_lossy(), &config)
+            .expect_err("unreadable frame rate should be rejected");
+
+        let _ = fs::remove_file(path);
+        assert!(accepted.is_ok());
+        assert!(error.to_string().contains("Invalid input frame rate: fast"));
+    }
+
+    #[test]
+    fn validate_task_input_rejects_out_of_range_avif_crf() {
+        let path = temporary_input_file("invalid-avif-crf");
+        let mut config = sample_config("avif", "libaom-av1");
+        config.image_avif_crf = 64;
+
+        let error = validate_task_input(&path.to_string_lossy(), &config)
+            .expect_err("out of range AVIF CRF should be rejected");
+
+        let _ = fs::remove_file(path);
+        assert!(
+            error
+                .to_string()
+                .contains("AVIF CRF must be between 0 and 63")
+        );
+    }
+
+    #[test]
+    fn image_sequence_output_writes_every_frame_with_image2() {
+        let mut config = sample_config("png", "png");
+        config.image_sequence_output = true;
+        config.fps = "10".to_string();
+
+        let args = build_ffmpeg_args(
+            "input.mov",
+            "/out/clip/clip_%06d.png",
+            &config,
+            &sample_probe(),
+        )
+        .expect("arguments should build");
+
+        assert!(
+            !args
+                .iter()
+                .any(|arg| arg == "-frames:v" || arg == "-update")
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-r", "10"]));
+        assert!(args.windows(2).any(|pair| pair == ["-f", "image2"]));
+        assert_eq!(
+            args.last().map(String::as_str),
+            Some("/out/clip/clip_%06d.png")
+        );
+        assert!(frame_progress_applies(&ConversionConfig {
+            fps: "original".to_string(),
+            ..config
+        }));
+    }
+
+    #[test]
+    fn validate_task_input_rejects_image_sequence_output_for_animated_formats() {
+        let path = temporary_input_file("gif-image-sequence");
+        let mut config = sample_config("gif", "gif");
+        config.image_sequence_output = true;
+
+        let error = validate_task_input(&path.to_string_lossy(), &config)
+            .expect_err("GIF cannot be written as a frame sequence");
+
+        let _ = fs::remove_file(path);
+        assert!(
+            error
+                .to_string()
+                .contains("Image sequence output is not available for container 'gif'")
+        );
+    }
+
+    #[test]
+    fn stream_copy_concat_args_join_the_list_with_source_metadata() {
+        let mut config = sample_config("mp4", "libx264");
+        let args = build_stream_copy_concat_args(
+            "/tmp/cut/pieces.ffconcat",
+            "/in/clip.mov",
+            "/out/clip.mp4.part",
+            &config,
+        );
+
+        assert_eq!(
+            args[..8],
+            [
+                "-f",
+                "concat",
+                "-safe",
+                "0",
+                "-i",
+                "/tmp/cut/pieces.ffconcat",
+                "-i",
+                "/in/clip.mov"
+            ]
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-map_metadata", "1"]));
+        assert_eq!(args[args.len() - 3..], ["-f", "mp4", "/out/clip.mp4.part"]);
+
+        config.metadata.mode = MetadataMode::Clean;
+        let clean = build_stream_copy_concat_args("/l", "/in/clip.mov", "/out/clip.mp4", &config);
+        assert!(clean.windows(2).any(|pair| pair == ["-map_metadata", "-1"]));
+        assert!(!clean.windows(2).any(|pair| pair == ["-map_metadata", "1"]));
+    }
+
+    #[test]
+    fn concat_reencode_args_normalize_every_source_before_joining() {
+        use crate::concat::{ConcatAudio, ConcatMethod, ConcatSource};
+
+        let config = sample_config("mp4", "libx264");
+        let plan = ConcatPlan {
+            method: ConcatMethod::Reencode,
+            copy_mismatch: None,
+            sources: vec![
+                ConcatSource {
+                    audio_stream: Some(1),
+                    duration: Some(10.0),
+                },
+                ConcatSource {
+                    audio_stream: None,
+                    duration: Some(4.5),
+                },
+                ConcatSource {
+                    audio_stream: Some(2),
+                    duration: Some(8.0),
+                },
+            ],
+            size: Some((1920, 1080)),
+            frame_rate: Some(30.0),
+            audio: Some(ConcatAudio {
+                sample_rate: 48_000,
+                channel_layout: "stereo".to_string(),
+            }),
+            duration: Some(22.5),
+        };
+        let inputs = ["/in/a.mp4", "/in/b.mov", "/in/c.mp4"].map(str::to_string);
+
+        let args = build_concat_reencode_args(&inputs, "/out/joined.mp4", &config, &plan);
+
+        let input_paths: Vec<&str> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "-i")
+            .map(|pair| pair[1].as_str())
+            .collect();
+        assert_eq!(input_paths, ["/in/a.mp4", "/in/b.mov", "/in/c.mp4"]);
+        assert!(args_contains_pair(&args, "-map_chapters", "-1"));
+        assert!(args_contains_pair(&args, "-map", "[vout]"));
+        assert!(args_contains_pair(&args, "-map", "[aout0]"));
+        let graph = args
+            .windows(2)
+            .find(|pair| pair[0] == "-filter_complex")
+            .map(|pair| pair[1].as_str())
+            .expect("joins use a filter graph");
+        assert!(graph.contains(
+            "[1:V:0]scale=1920:1080:force_original_aspect_ratio=decrease,\
+             pad=1920:1080:(ow-iw)/2:(oh-ih)/2,setsar=1,fps=30[v1]"
+        ));
+        assert!(graph.contains(
+            "[2:2]aresample=48000,aformat=sample_rates=48000:channel_layouts=stereo[a2]"
+        ));
+        assert!(
+            graph.contains(
+                "anullsrc=channel_layout=stereo:sample_rate=48000,atrim=duration=4.500[a1]"
+            )
+        );
+        assert!(graph.contains("[v0][a0][v1][a1][v2][a2]concat=n=3:v=1:a=1[vcat][acat0]"));
+        assert!(graph.contains("[vcat]"));
+        assert!(graph.ends_with("[acat0]anull[aout0]"));
+    }
+
+    #[test]
+    fn concat_reencode_args_join_audio_only_outputs_without_video() {
+        use crate::concat::{ConcatAudio, ConcatMethod, ConcatSource};
+
+        let mut config = sample_config("mp3", "libx264");
+        config.audio_codec = "mp3".to_string();
+        let source = ConcatSource {
+            audio_stream: Some(0),
+            duration: Some(60.0),
+        };
+        let plan = ConcatPlan {
+            method: ConcatMethod::Reencode,
+            copy_mismatch: None,
+            sources: vec![source.clone(), source],
+            size: None,
+            frame_rate: None,
+            audio: Some(ConcatAudio {
+                sample_rate: 44_100,
+                channel_layout: "mono".to_string(),
+            }),
+            duration: Some(120.0),
+        };
+        let inputs = ["/in/part1.m4a", "/in/part2.m4a"].map(str::to_string);
+
+        let args = build_concat_reencode_args(&inputs, "/out/lecture.mp3", &config, &plan);
+
+        assert!(!args_contains_pair(&args, "-map", "[vout]"));
+        assert!(!args.iter().any(|arg| arg == "-c:v"));
+        let graph = args
+            .windows(2)
+            .find(|pair| pair[0] == "-filter_complex")
+            .map(|pair| pair[1].as_str())
+            .expect("joins use a filter graph");
+        assert!(graph.contains("[a0][a1]concat=n=2:v=0:a=1[acat0]"));
+        assert!(!graph.contains(":V:0"));
+    }
+
+    #[test]
+    fn validate_task_capabilities_names_the_missing_library() {
+        let capabilities = crate::capabilities::parse_ffmpeg_capabilities(
+            " ... scale  V->V  Scale the input video size.\n",
+            "  E  mp4   MP4 (MPEG-4 Part 14)\n",
+            "",
+        );
+        let plain = sample_config("mp4", "libx264");
+        let mut burned = sample_config("mp4", "libx264");
+        burned.subtitle_burn_path = Some("/tmp/sub.srt".to_string());
+        let mut matroska = sample_config("mkv", "libx264");
+        matroska.resolution = "720p".to_string();
+
+        let missing_library = validate_task_capabilities(&burned, &capabilities)
+            .expect_err("subtitle burn-in needs libass");
+        let missing_muxer = validate_task_capabilities(&matroska, &capabilities)
+            .expect_err("mkv output needs the matroska muxer");
+
+        assert!(validate_task_capabilities(&plain, &capabilities).is_ok());
+        assert_eq!(
+            missing_library.to_string(),
+            "Invalid input: Your FFmpeg build lacks libass"
+        );
+        assert!(missing_muxer.to_string().contains("cannot write mkv files"));
+    }
+
+    #[test]
+    fn validate_task_encoder_rejects_failed_self_tests_of_the_video_encoder() {
+        let failed = EncoderSelfTest::failed("h264_nvenc", "driver too old");
+        let nvenc = sample_config("mp4", "h264_nvenc");
+        let software = sample_config("mp4", "libx264");
+        let audio_only = sample_config("mp3", "h264_nvenc");
+
+        let error = validate_task_encoder(&nvenc, &failed)
+            .expect_err("a failed self-test should reject the task");
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: NVENC listed but unusable: driver too old"
+        );
+        assert!(validate_task_encoder(&software, &failed).is_ok());
+        assert!(validate_task_encoder(&audio_only, &failed).is_ok());
+        assert!(
+            validate_task_encoder(
+                &nvenc,
+                &EncoderSelfTest::from_output("h264_nvenc", true, "")
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn extra_args_sit_before_the_input_and_the_output_path() {
+        let mut config = sample_config("mp4", "libx264");
+        config.extra_input_args = vec!["-thread_queue_size".to_string(), "512".to_string()];
+        config.extra_output_args = vec!["-x264-params".to_string(), "aq-mode=3".to_string()];
+
+        let args = build_ffmpeg_args("input.mov", "output.mp4", &config, &sample_probe())
+            .expect("args should build");
+        let input = args
+            .iter()
+            .position(|arg| arg == "-i")
+            .expect("args should have an input");
+
+        assert_eq!(args[input - 2..input], ["-thread_queue_size", "512"]);
+        assert!(args.ends_with(&[
+            "-n".to_string(),
+            "-x264-params".to_string(),
+            "aq-mode=3".to_string(),
+            "output.mp4".to_string(),
+        ]));
+        assert_eq!(
+            extra_args_warning(&config).as_deref(),
+            Some(
+                "Extra FFmpeg arguments added before the input: -thread_queue_size 512; \
+                 before the output: -x264-params aq-mode=3"
+            )
+        );
+        assert_eq!(extra_args_warning(&sample_config("mp4", "libx264")), None);
+    }
+
+    #[test]
+    fn extra_args_that_conflict_with_generated_ones_are_rejected() {
+        let with_output_args = |args: &[&str]| {
+            let mut config = sample_config("mp4", "libx264");
+            config.extra_output_args = args.iter().map(ToString::to_string).collect();
+            validate_conversion_settings(&config)
+        };
+
+        assert!(with_output_args(&["-threads", "-1", "-movflags", "+faststart"]).is_ok());
+        for rejected in [
+            &["-y"][..],
+            &["-metadata", "title=x", "second.mp4"],
+            &["-i", "other.mov"],
+            &["  "],
+        ] {
+            assert!(with_output_args(rejected).is_err(), "{rejected:?}");
+        }
+        let too_many = vec!["-an"; MAX_EXTRA_ARGS + 1];
+        assert!(with_output_args(&too_many).is_err());
+
+        let mut config = sample_config("mp4", "libx264");
+        config.extra_input_args = vec!["-n".to_string()];
+        let error = validate_conversion_settings(&config).expect_err("-n should be rejected");
+        assert!(error.to_string().contains("overwrite policy"));
+    }
+
     fn args_contains_pair(args: &[String], key: &str, value: &str) -> bool {
         args.windows(2)
             .any(|window| window[0] == key && window[1] == value)
@@ -0,0 +1,351 @@
+//! Bitrate over time of a file's primary video stream, from the packet sizes
+//! `FFprobe` lists.
+//!
+//! Packets are read one line at a time and summed into fixed time windows as
+//! they arrive, so files with millions of packets never hold more than one
+//! counter per window in memory.
+
+use serde::Serialize;
+
+use crate::error::ConversionError;
+
+/// Shortest time window a bitrate analysis may use.
+pub const MIN_BITRATE_BUCKET_SECONDS: f64 = 0.1;
+/// Most windows an analysis keeps; packets timed beyond them are ignored so
+/// broken timestamps cannot exhaust memory.
+pub const MAX_BITRATE_BUCKETS: usize = 1_000_000;
+/// Percentile reported with the bitrate summary.
+pub const BITRATE_PERCENTILE: f64 = 95.0;
+
+/// Builds a packet listing of the first video stream of `file_path`, one
+/// `key=value|...` line per packet.
+#[must_use]
+pub fn bitrate_probe_args(file_path: &str) -> Vec<String> {
+    vec![
+        "-v".to_string(),
+        "error".to_string(),
+        "-select_streams".to_string(),
+        "v:0".to_string(),
+        "-show_packets".to_string(),
+        "-show_entries".to_string(),
+        "packet=pts_time,dts_time,duration_time,size,flags".to_string(),
+        "-of".to_string(),
+        "compact=p=0".to_string(),
+        file_path.to_string(),
+    ]
+}
+
+/// Rejects time windows that are too short or not finite.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when `bucket_seconds` is below
+/// [`MIN_BITRATE_BUCKET_SECONDS`].
+pub fn validate_bitrate_bucket(bucket_seconds: f64) -> Result<(), ConversionError> {
+    if bucket_seconds.is_finite() && bucket_seconds >= MIN_BITRATE_BUCKET_SECONDS {
+        Ok(())
+    } else {
+        Err(ConversionError::InvalidInput(format!(
+            "Bitrate windows must be at least {MIN_BITRATE_BUCKET_SECONDS} seconds long, got {bucket_seconds}"
+        )))
+    }
+}
+
+/// One packet listed by [`bitrate_probe_args`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PacketSample {
+    /// Presentation time, or decode time when the packet has none.
+    pub time: f64,
+    pub duration: Option<f64>,
+    pub size_bytes: u64,
+    pub keyframe: bool,
+}
+
+/// Reads one packet line; lines without a time or size are skipped.
+#[must_use]
+pub fn parse_packet_line(line: &str) -> Option<PacketSample> {
+    let mut pts = None;
+    let mut dts = None;
+    let mut duration = None;
+    let mut size_bytes = None;
+    let mut keyframe = false;
+    for field in line.trim().split('|') {
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        let seconds = || value.parse::<f64>().ok().filter(|time| time.is_finite());
+        match key {
+            "pts_time" => pts = seconds(),
+            "dts_time" => dts = seconds(),
+            "duration_time" => duration = seconds().filter(|duration| *duration > 0.0),
+            "size" => size_bytes = value.parse().ok(),
+            "flags" => keyframe = value.starts_with('K'),
+            _ => {}
+        }
+    }
+    Some(PacketSample {
+        time: pts.or(dts)?,
+        duration,
+        size_bytes: size_bytes?,
+        keyframe,
+    })
+}
+
+/// Bitrate of one time window.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitratePoint {
+    /// Start of the window, in seconds from the first packet.
+    pub time: f64,
+    pub kbps: f64,
+    pub is_keyframe_present: bool,
+}
+
+/// Bitrate over time of a video stream and its summary.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitrateAnalysis {
+    pub bucket_seconds: f64,
+    pub points: Vec<BitratePoint>,
+    pub peak_kbps: f64,
+    /// Total size over the analyzed duration.
+    pub average_kbps: f64,
+    /// The [`BITRATE_PERCENTILE`]th percentile of the window bitrates.
+    pub percentile_kbps: f64,
+    pub duration_seconds: f64,
+    pub packets: u64,
+}
+
+/// Sums packet sizes into windows of `bucket_seconds` as packets arrive.
+#[derive(Clone, Debug)]
+pub struct BitrateBuckets {
+    bucket_seconds: f64,
+    start: Option<f64>,
+    end: f64,
+    /// Bytes and keyframe presence of each window.
+    buckets: Vec<(u64, bool)>,
+    packets: u64,
+}
+
+impl BitrateBuckets {
+    #[must_use]
+    pub const fn new(bucket_seconds: f64) -> Self {
+        Self {
+            bucket_seconds,
+            start: None,
+            end: 0.0,
+            buckets: Vec::new(),
+            packets: 0,
+        }
+    }
+
+    /// Adds one packet. Packets timed before the first one land in the
+    /// first window, and packets past [`MAX_BITRATE_BUCKETS`] windows are
+    /// skipped.
+    pub fn push(&mut self, packet: PacketSample) {
+        let start = *self.start.get_or_insert(packet.time);
+        let offset = (packet.time - start).max(0.0);
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "the offset is non-negative and bounded by the file duration"
+        )]
+        let index = (offset / self.bucket_seconds).floor() as usize;
+        if index >= MAX_BITRATE_BUCKETS {
+            return;
+        }
+        if self.buckets.len() <= index {
+            self.buckets.resize(index + 1, (0, false));
+        }
+        let bucket = &mut self.buckets[index];
+        bucket.0 += packet.size_bytes;
+        bucket.1 |= packet.keyframe;
+        self.end = self.end.max(offset + packet.duration.unwrap_or(0.0));
+        self.packets += 1;
+    }
+
+    /// Time reached so far, in seconds from the first packet.
+    #[must_use]
+    pub const fn end_seconds(&self) -> f64 {
+        self.end
+    }
+
+    /// Points and summary of the packets pushed. The last window is measured
+    /// over the part of it the stream covers.
+    #[must_use]
+    pub fn finish(self) -> BitrateAnalysis {
+        let last_index = self.buckets.len().saturating_sub(1);
+        let points: Vec<BitratePoint> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(index, &(bytes, keyframe))| {
+                let time = usize_to_f64(index) * self.bucket_seconds;
+                let span = if index == last_index && self.end > time {
+                    (self.end - time).min(self.bucket_seconds)
+                } else {
+                    self.bucket_seconds
+                };
+                BitratePoint {
+                    time,
+                    kbps: kbps(bytes, span),
+                    is_keyframe_present: keyframe,
+                }
+            })
+            .collect();
+        let total_bytes = self.buckets.iter().map(|(bytes, _)| bytes).sum();
+        let duration_seconds = self.end.max(usize_to_f64(last_index) * self.bucket_seconds);
+        let mut rates: Vec<f64> = points.iter().map(|point| point.kbps).collect();
+        rates.sort_by(f64::total_cmp);
+        BitrateAnalysis {
+            bucket_seconds: self.bucket_seconds,
+            peak_kbps: rates.last().copied().unwrap_or(0.0),
+            average_kbps: if duration_seconds > 0.0 {
+                kbps(total_bytes, duration_seconds)
+            } else {
+                0.0
+            },
+            percentile_kbps: nearest_rank(&rates, BITRATE_PERCENTILE),
+            duration_seconds,
+            packets: self.packets,
+            points,
+        }
+    }
+}
+
+fn kbps(bytes: u64, seconds: f64) -> f64 {
+    u64_to_f64(bytes) * 8.0 / 1000.0 / seconds
+}
+
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "byte counts stay far below the f64 mantissa limit"
+)]
+const fn u64_to_f64(value: u64) -> f64 {
+    value as f64
+}
+
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "window counts stay far below the f64 mantissa limit"
+)]
+const fn usize_to_f64(value: usize) -> f64 {
+    value as f64
+}
+
+/// Nearest-rank `percentile` of ascending `values`.
+fn nearest_rank(values: &[f64], percentile: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the rank is between 1 and the number of values"
+    )]
+    let rank = (percentile / 100.0 * usize_to_f64(values.len())).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(time: f64, size_bytes: u64, keyframe: bool) -> PacketSample {
+        PacketSample {
+            time,
+            duration: Some(0.5),
+            size_bytes,
+            keyframe,
+        }
+    }
+
+    #[test]
+    fn packet_lines_fall_back_to_decode_time() {
+        assert_eq!(
+            parse_packet_line(
+                "pts_time=1.500000|dts_time=1.460000|duration_time=0.040000|size=5120|flags=K__"
+            ),
+            Some(PacketSample {
+                time: 1.5,
+                duration: Some(0.04),
+                size_bytes: 5120,
+                keyframe: true,
+            })
+        );
+        assert_eq!(
+            parse_packet_line(
+                "pts_time=N/A|dts_time=2.000000|duration_time=N/A|size=800|flags=___"
+            ),
+            Some(PacketSample {
+                time: 2.0,
+                duration: None,
+                size_bytes: 800,
+                keyframe: false,
+            })
+        );
+        assert_eq!(
+            parse_packet_line("pts_time=N/A|dts_time=N/A|size=800"),
+            None
+        );
+        assert_eq!(parse_packet_line(""), None);
+    }
+
+    #[test]
+    fn packets_are_summed_into_windows_from_the_first_packet() {
+        let mut buckets = BitrateBuckets::new(1.0);
+        for sample in [
+            packet(10.0, 125_000, true),
+            packet(10.5, 125_000, false),
+            packet(11.0, 25_000, false),
+            packet(11.5, 25_000, false),
+            packet(12.0, 62_500, true),
+        ] {
+            buckets.push(sample);
+        }
+
+        let analysis = buckets.finish();
+
+        assert_eq!(analysis.packets, 5);
+        assert!((analysis.duration_seconds - 2.5).abs() < 1e-9);
+        assert_eq!(
+            analysis.points,
+            [
+                BitratePoint {
+                    time: 0.0,
+                    kbps: 2000.0,
+                    is_keyframe_present: true,
+                },
+                BitratePoint {
+                    time: 1.0,
+                    kbps: 400.0,
+                    is_keyframe_present: false,
+                },
+                BitratePoint {
+                    time: 2.0,
+                    kbps: 1000.0,
+                    is_keyframe_present: true,
+                },
+            ]
+        );
+        assert!((analysis.peak_kbps - 2000.0).abs() < 1e-9);
+        assert!((analysis.average_kbps - 1160.0).abs() < 1e-9);
+        assert!((analysis.percentile_kbps - 2000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let rates: Vec<f64> = (1..=20).map(f64::from).collect();
+
+        assert!((nearest_rank(&rates, 95.0) - 19.0).abs() < 1e-9);
+        assert!((nearest_rank(&rates, 50.0) - 10.0).abs() < 1e-9);
+        assert!((nearest_rank(&[], 95.0) - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn windows_shorter_than_the_minimum_are_rejected() {
+        assert!(validate_bitrate_bucket(1.0).is_ok());
+        assert!(validate_bitrate_bucket(0.01).is_err());
+        assert!(validate_bitrate_bucket(f64::NAN).is_err());
+    }
+}
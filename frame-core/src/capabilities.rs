@@ -1,7 +1,53 @@
+use std::collections::BTreeSet;
+
 use regex::Regex;
 
+use crate::{types::ConversionConfig, utils::hwaccel_method};
+
 const FFMPEG_ENCODER_LIST_ARGS: [&str; 1] = ["-encoders"];
 const FFMPEG_FILTER_LIST_ARGS: [&str; 1] = ["-filters"];
+const FFMPEG_HWACCEL_LIST_ARGS: [&str; 1] = ["-hwaccels"];
+const FFMPEG_DECODER_LIST_ARGS: [&str; 1] = ["-decoders"];
+const FFMPEG_MUXER_LIST_ARGS: [&str; 1] = ["-muxers"];
+const FFMPEG_PROTOCOL_LIST_ARGS: [&str; 1] = ["-protocols"];
+const FFMPEG_VERSION_ARGS: [&str; 1] = ["-version"];
+/// Frames encoded by [`encoder_self_test_args`]; enough for an encoder to
+/// open its device and emit a packet.
+const ENCODER_SELF_TEST_FRAMES: &str = "10";
+
+/// Stderr fragments that explain why a listed hardware encoder cannot run,
+/// checked in order.
+const ENCODER_SELF_TEST_CAUSES: &[(&str, &str)] = &[
+    ("required nvenc api version", "driver too old"),
+    ("minimum required nvidia driver", "driver too old"),
+    ("no capable devices found", "no supported GPU found"),
+    ("cannot load libcuda", "NVIDIA driver not installed"),
+    ("cannot load nvcuda", "NVIDIA driver not installed"),
+    (
+        "cannot load libnvidia-encode",
+        "NVIDIA driver not installed",
+    ),
+    (
+        "openencodesessionex failed",
+        "GPU encoder sessions exhausted",
+    ),
+    ("device creation failed", "hardware device unavailable"),
+    (
+        "cannot create compression session",
+        "hardware encoder unavailable",
+    ),
+];
+
+/// Version of the [`FfmpegCapabilities`] layout.
+pub const FFMPEG_CAPABILITIES_VERSION: u32 = 1;
+
+/// Version of the [`HardwareCapabilities`] layout, bumped when fields change
+/// meaning so the frontend can tell old reports apart.
+pub const HARDWARE_CAPABILITIES_VERSION: u32 = 1;
+
+/// Decoder name suffixes of dedicated hardware decoders such as
+/// `h264_cuvid` or `hevc_qsv`.
+const HARDWARE_DECODER_SUFFIXES: [&str; 4] = ["_cuvid", "_qsv", "_mediacodec", "_v4l2m2m"];
 
 #[derive(serde::Serialize, Clone, Debug, Default, Eq, PartialEq)]
 #[expect(
@@ -18,6 +64,91 @@ pub struct AvailableEncoders {
     pub libmp3lame: bool,
 }
 
+impl AvailableEncoders {
+    /// Listed hardware video encoders, which may still be unusable on this
+    /// machine; see [`encoder_self_test_args`].
+    #[must_use]
+    pub fn listed_hardware_encoders(&self) -> Vec<&'static str> {
+        [
+            ("h264_videotoolbox", self.h264_videotoolbox),
+            ("h264_nvenc", self.h264_nvenc),
+            ("hevc_videotoolbox", self.hevc_videotoolbox),
+            ("hevc_nvenc", self.hevc_nvenc),
+            ("av1_nvenc", self.av1_nvenc),
+        ]
+        .into_iter()
+        .filter_map(|(encoder, listed)| listed.then_some(encoder))
+        .collect()
+    }
+
+    /// Marks `encoder` as unavailable, e.g. after its self-test failed.
+    pub fn remove(&mut self, encoder: &str) {
+        match encoder {
+            "h264_videotoolbox" => self.h264_videotoolbox = false,
+            "h264_nvenc" => self.h264_nvenc = false,
+            "hevc_videotoolbox" => self.hevc_videotoolbox = false,
+            "hevc_nvenc" => self.hevc_nvenc = false,
+            "av1_nvenc" => self.av1_nvenc = false,
+            "libfdk_aac" => self.libfdk_aac = false,
+            "libmp3lame" => self.libmp3lame = false,
+            _ => {}
+        }
+    }
+}
+
+/// Outcome of encoding a few frames with one encoder.
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderSelfTest {
+    pub encoder: String,
+    pub passed: bool,
+    /// Why the encoder is unusable, e.g. `NVENC listed but unusable: driver
+    /// too old`; `None` when it passed.
+    pub error: Option<String>,
+}
+
+impl EncoderSelfTest {
+    /// Interprets the exit and stderr of an [`encoder_self_test_args`] run.
+    #[must_use]
+    pub fn from_output(encoder: &str, success: bool, stderr: &str) -> Self {
+        if success {
+            return Self {
+                encoder: encoder.to_string(),
+                passed: true,
+                error: None,
+            };
+        }
+
+        let lowercase = stderr.to_ascii_lowercase();
+        let cause = ENCODER_SELF_TEST_CAUSES
+            .iter()
+            .find(|(pattern, _)| lowercase.contains(pattern))
+            .map(|(_, cause)| (*cause).to_string())
+            .or_else(|| {
+                stderr
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|line| !line.is_empty())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| "the test encode failed".to_string());
+        Self::failed(encoder, &cause)
+    }
+
+    /// A failed test whose encoder is unusable because of `cause`.
+    #[must_use]
+    pub fn failed(encoder: &str, cause: &str) -> Self {
+        Self {
+            encoder: encoder.to_string(),
+            passed: false,
+            error: Some(format!(
+                "{} listed but unusable: {cause}",
+                encoder_family_label(encoder)
+            )),
+        }
+    }
+}
+
 #[derive(serde::Serialize, Clone, Debug, Default, Eq, PartialEq)]
 #[expect(
     clippy::struct_excessive_bools,
@@ -46,6 +177,195 @@ pub struct AvailableFilters {
     pub alimiter: bool,
 }
 
+#[derive(serde::Serialize, Clone, Debug, Default, Eq, PartialEq)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "hwaccel availability is represented as explicit frontend feature flags"
+)]
+pub struct AvailableHwaccels {
+    pub cuda: bool,
+    pub qsv: bool,
+    pub vaapi: bool,
+    pub videotoolbox: bool,
+    pub d3d11va: bool,
+}
+
+impl AvailableHwaccels {
+    /// Whether the `-hwaccel` method `method` was listed.
+    #[must_use]
+    pub fn contains(&self, method: &str) -> bool {
+        match method {
+            "cuda" => self.cuda,
+            "qsv" => self.qsv,
+            "vaapi" => self.vaapi,
+            "videotoolbox" => self.videotoolbox,
+            "d3d11va" => self.d3d11va,
+            _ => false,
+        }
+    }
+}
+
+/// Hardware decoding support of the `FFmpeg` runtime.
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareCapabilities {
+    /// [`HARDWARE_CAPABILITIES_VERSION`] of the reporting build.
+    pub version: u32,
+    pub hwaccels: AvailableHwaccels,
+    /// Dedicated hardware decoders, e.g. `h264_cuvid`, in listing order.
+    pub hardware_decoders: Vec<String>,
+}
+
+impl Default for HardwareCapabilities {
+    fn default() -> Self {
+        Self {
+            version: HARDWARE_CAPABILITIES_VERSION,
+            hwaccels: AvailableHwaccels::default(),
+            hardware_decoders: Vec::new(),
+        }
+    }
+}
+
+impl HardwareCapabilities {
+    /// Whether hardware decoding can be requested for outputs encoded with
+    /// `video_codec`, i.e. the hwaccel it pairs with is present.
+    #[must_use]
+    pub fn supports_hw_decode(&self, video_codec: &str) -> bool {
+        hwaccel_method(video_codec).is_some_and(|method| self.hwaccels.contains(method))
+    }
+}
+
+/// Optional features that depend on how `FFmpeg` was built.
+#[derive(serde::Serialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "build features are represented as explicit frontend feature flags"
+)]
+pub struct CapabilityFlags {
+    /// The libass `subtitles` filter.
+    pub subtitle_burn_in: bool,
+    /// The libvidstab `vidstabdetect` and `vidstabtransform` filters.
+    pub stabilization: bool,
+    /// The libzimg `zscale` filter together with `tonemap`.
+    pub tonemapping: bool,
+    /// The animated `webp` muxer.
+    pub webp_output: bool,
+    pub apng_output: bool,
+}
+
+/// Filters, muxers and protocols of the `FFmpeg` runtime.
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCapabilities {
+    /// [`FFMPEG_CAPABILITIES_VERSION`] of the reporting build.
+    pub version: u32,
+    pub filters: BTreeSet<String>,
+    pub muxers: BTreeSet<String>,
+    /// Input and output protocols, e.g. `file` or `https`.
+    pub protocols: BTreeSet<String>,
+    pub flags: CapabilityFlags,
+}
+
+impl FfmpegCapabilities {
+    #[must_use]
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    #[must_use]
+    pub fn has_muxer(&self, name: &str) -> bool {
+        self.muxers.contains(name)
+    }
+
+    #[must_use]
+    pub fn has_protocol(&self, name: &str) -> bool {
+        self.protocols.contains(name)
+    }
+}
+
+/// Version and build configuration printed by `ffmpeg -version`.
+#[derive(serde::Serialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegInfo {
+    /// Version as printed, e.g. `7.1`, `6.1.1-3ubuntu5` or the
+    /// `N-113000-g1a2b3c4d` of a git snapshot.
+    pub version: String,
+    /// Release number parsed from [`Self::version`]; `None` for git
+    /// snapshots.
+    pub major: Option<u32>,
+    pub minor: Option<u32>,
+    /// Last year of the copyright banner, the closest a snapshot build has
+    /// to a release date.
+    pub year: Option<u32>,
+    /// `./configure` flags of the build.
+    pub configuration: Vec<String>,
+    /// Libraries enabled with `--enable-lib*`, e.g. `libx264`.
+    pub enabled_libraries: BTreeSet<String>,
+}
+
+/// Oldest `FFmpeg` release Frame supports as its conversion binary.
+pub const MIN_FFMPEG_RELEASE: (u32, u32) = (6, 0);
+/// Oldest copyright year accepted from git snapshots, which carry no
+/// release number; `FFmpeg` 6.0 shipped in 2023.
+pub const MIN_FFMPEG_SNAPSHOT_YEAR: u32 = 2023;
+
+impl FfmpegInfo {
+    /// Whether the build is release `major.minor` or newer. Snapshots
+    /// without a release number never qualify.
+    #[must_use]
+    pub fn is_at_least(&self, major: u32, minor: u32) -> bool {
+        self.major
+            .is_some_and(|own| (own, self.minor.unwrap_or(0)) >= (major, minor))
+    }
+
+    /// Whether the build is new enough to replace the bundled one: release
+    /// [`MIN_FFMPEG_RELEASE`] or newer, or a snapshot from
+    /// [`MIN_FFMPEG_SNAPSHOT_YEAR`] on.
+    #[must_use]
+    pub fn meets_minimum_version(&self) -> bool {
+        let (major, minor) = MIN_FFMPEG_RELEASE;
+        self.is_at_least(major, minor)
+            || (self.major.is_none()
+                && self
+                    .year
+                    .is_some_and(|year| year >= MIN_FFMPEG_SNAPSHOT_YEAR))
+    }
+
+    /// Whether the build accepts the `-display_rotation` input option added
+    /// in `FFmpeg` 7.0.
+    #[must_use]
+    pub fn supports_display_rotation(&self) -> bool {
+        self.is_at_least(7, 0)
+    }
+
+    /// Whether the build decodes HEIC/HEIF photos. `FFmpeg` 7.1 added the
+    /// tiled HEIF images that phone cameras write.
+    #[must_use]
+    pub fn supports_heif_input(&self) -> bool {
+        self.is_at_least(7, 1)
+    }
+
+    #[must_use]
+    pub fn has_library(&self, name: &str) -> bool {
+        self.enabled_libraries.contains(name)
+    }
+}
+
+/// Turns off hardware decoding in `config` when `capabilities` lacks the
+/// hwaccel it needs, so the input is decoded in software instead of failing
+/// mid-task. Returns whether the config changed.
+pub fn downgrade_unsupported_hw_decode(
+    config: &mut ConversionConfig,
+    capabilities: &HardwareCapabilities,
+) -> bool {
+    if !config.hw_decode || capabilities.supports_hw_decode(&config.video_codec) {
+        return false;
+    }
+    config.hw_decode = false;
+    true
+}
+
 #[must_use]
 pub const fn ffmpeg_encoder_list_args() -> [&'static str; 1] {
     FFMPEG_ENCODER_LIST_ARGS
@@ -56,6 +376,55 @@ pub const fn ffmpeg_filter_list_args() -> [&'static str; 1] {
     FFMPEG_FILTER_LIST_ARGS
 }
 
+#[must_use]
+pub const fn ffmpeg_hwaccel_list_args() -> [&'static str; 1] {
+    FFMPEG_HWACCEL_LIST_ARGS
+}
+
+#[must_use]
+pub const fn ffmpeg_decoder_list_args() -> [&'static str; 1] {
+    FFMPEG_DECODER_LIST_ARGS
+}
+
+#[must_use]
+pub const fn ffmpeg_muxer_list_args() -> [&'static str; 1] {
+    FFMPEG_MUXER_LIST_ARGS
+}
+
+#[must_use]
+pub const fn ffmpeg_protocol_list_args() -> [&'static str; 1] {
+    FFMPEG_PROTOCOL_LIST_ARGS
+}
+
+#[must_use]
+pub const fn ffmpeg_version_args() -> [&'static str; 1] {
+    FFMPEG_VERSION_ARGS
+}
+
+/// Arguments that encode a short black clip with `encoder` and discard the
+/// result, proving the encoder can open its device.
+#[must_use]
+pub fn encoder_self_test_args(encoder: &str) -> Vec<String> {
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-f",
+        "lavfi",
+        "-i",
+        "color=black:s=256x256",
+        "-frames:v",
+        ENCODER_SELF_TEST_FRAMES,
+        "-c:v",
+        encoder,
+        "-f",
+        "null",
+        "-",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
 #[must_use]
 pub fn parse_available_encoders(ffmpeg_encoders_stdout: impl AsRef<str>) -> AvailableEncoders {
     let stdout = ffmpeg_encoders_stdout.as_ref();
@@ -99,6 +468,186 @@ pub fn parse_available_filters(ffmpeg_filters_stdout: impl AsRef<str>) -> Availa
     }
 }
 
+/// Parses the method list printed by `ffmpeg -hwaccels`, one name per line
+/// after its heading.
+#[must_use]
+pub fn parse_available_hwaccels(ffmpeg_hwaccels_stdout: impl AsRef<str>) -> AvailableHwaccels {
+    let methods: Vec<&str> = ffmpeg_hwaccels_stdout
+        .as_ref()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .collect();
+    let listed = |method: &str| methods.contains(&method);
+
+    AvailableHwaccels {
+        cuda: listed("cuda"),
+        qsv: listed("qsv"),
+        vaapi: listed("vaapi"),
+        videotoolbox: listed("videotoolbox"),
+        d3d11va: listed("d3d11va"),
+    }
+}
+
+/// Combines the output of `ffmpeg -hwaccels` and `ffmpeg -decoders`.
+#[must_use]
+pub fn parse_hardware_capabilities(
+    ffmpeg_hwaccels_stdout: impl AsRef<str>,
+    ffmpeg_decoders_stdout: impl AsRef<str>,
+) -> HardwareCapabilities {
+    let row = Regex::new(r"^\s*[A-Z.]{6}\s+(\S+)\s").ok();
+    let hardware_decoders = ffmpeg_decoders_stdout
+        .as_ref()
+        .lines()
+        .filter_map(|line| {
+            row.as_ref()?
+                .captures(line)
+                .and_then(|captures| captures.get(1))
+        })
+        .map(|name| name.as_str())
+        .filter(|name| {
+            HARDWARE_DECODER_SUFFIXES
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+        })
+        .map(str::to_string)
+        .collect();
+
+    HardwareCapabilities {
+        version: HARDWARE_CAPABILITIES_VERSION,
+        hwaccels: parse_available_hwaccels(ffmpeg_hwaccels_stdout),
+        hardware_decoders,
+    }
+}
+
+/// Combines the output of `ffmpeg -filters`, `-muxers` and `-protocols`.
+///
+/// Rows are matched by shape rather than by column, so the flag columns that
+/// changed between `FFmpeg` 6 and 7 parse alike.
+#[must_use]
+pub fn parse_ffmpeg_capabilities(
+    ffmpeg_filters_stdout: impl AsRef<str>,
+    ffmpeg_muxers_stdout: impl AsRef<str>,
+    ffmpeg_protocols_stdout: impl AsRef<str>,
+) -> FfmpegCapabilities {
+    let filters = list_row_names(
+        ffmpeg_filters_stdout.as_ref(),
+        r"^\s*[A-Z.|]{1,4}\s+([a-z0-9_]+)\s+\S*->\S*",
+    );
+    let muxers = list_row_names(
+        ffmpeg_muxers_stdout.as_ref(),
+        r"^\s*[DEd.]{1,3}\s+([a-z0-9_,]+)\s",
+    );
+    let protocols = ffmpeg_protocols_stdout
+        .as_ref()
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && line
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '_')
+        })
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>();
+    let flags = CapabilityFlags {
+        subtitle_burn_in: filters.contains("subtitles"),
+        stabilization: filters.contains("vidstabdetect") && filters.contains("vidstabtransform"),
+        tonemapping: filters.contains("zscale") && filters.contains("tonemap"),
+        webp_output: muxers.contains("webp"),
+        apng_output: muxers.contains("apng"),
+    };
+
+    FfmpegCapabilities {
+        version: FFMPEG_CAPABILITIES_VERSION,
+        filters,
+        muxers,
+        protocols,
+        flags,
+    }
+}
+
+/// Parses `ffmpeg -version` output, returning `None` when it has no version
+/// banner.
+///
+/// Distribution builds may print extra lines or append their own suffix to
+/// the version, e.g. `4.4.2-0ubuntu0.22.04.1`; only the leading release
+/// number is parsed.
+#[must_use]
+pub fn parse_ffmpeg_info(ffmpeg_version_stdout: impl AsRef<str>) -> Option<FfmpegInfo> {
+    let stdout = ffmpeg_version_stdout.as_ref();
+    let banner = Regex::new(r"(?m)^\s*ffmpeg version (\S+)(.*)$").ok()?;
+    let captures = banner.captures(stdout)?;
+    let version = captures.get(1)?.as_str().to_string();
+    let release = Regex::new(r"^n?(\d+)(?:\.(\d+))?")
+        .ok()
+        .and_then(|release| release.captures(&version));
+    let release_number = |index| {
+        release
+            .as_ref()
+            .and_then(|release| release.get(index))
+            .and_then(|number| number.as_str().parse::<u32>().ok())
+    };
+    let major = release_number(1);
+    let minor = release_number(2);
+    let year = Regex::new(r"\(c\)\s*(?:\d{4}-)?(\d{4})")
+        .ok()
+        .and_then(|copyright| copyright.captures(captures.get(2)?.as_str()))
+        .and_then(|copyright| copyright.get(1)?.as_str().parse().ok());
+
+    let configuration = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("configuration:"))
+        .map(|flags| {
+            flags
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let enabled_libraries = configuration
+        .iter()
+        .filter_map(|flag| flag.strip_prefix("--enable-"))
+        .filter(|library| library.starts_with("lib"))
+        .map(str::to_string)
+        .collect();
+
+    Some(FfmpegInfo {
+        version,
+        major,
+        minor,
+        year,
+        configuration,
+        enabled_libraries,
+    })
+}
+
+fn encoder_family_label(encoder: &str) -> &str {
+    match encoder.rsplit_once('_').map(|(_, family)| family) {
+        Some("nvenc") => "NVENC",
+        Some("qsv") => "Quick Sync",
+        Some("videotoolbox") => "VideoToolbox",
+        Some("amf") => "AMF",
+        Some("vaapi") => "VAAPI",
+        _ => encoder,
+    }
+}
+
+/// Names captured by `row_pattern` across `stdout`, split on commas for
+/// rows that list aliases together.
+fn list_row_names(stdout: &str, row_pattern: &str) -> BTreeSet<String> {
+    let Ok(row) = Regex::new(row_pattern) else {
+        return BTreeSet::new();
+    };
+    stdout
+        .lines()
+        .filter_map(|line| row.captures(line)?.get(1))
+        .flat_map(|names| names.as_str().split(','))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 fn encoder_list_contains(stdout: &str, name: &str) -> bool {
     let pattern = format!(r"(?m)^\s*[A-Z.]+\s+{}\s+", regex::escape(name));
     Regex::new(&pattern).map_or_else(|_| stdout.contains(name), |re| re.is_match(stdout))
@@ -112,6 +661,7 @@ fn filter_list_contains(stdout: &str, name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::sample_config;
 
     #[test]
     fn ffmpeg_encoder_list_args_match_sidecar_contract() {
@@ -123,6 +673,326 @@ mod tests {
         assert_eq!(ffmpeg_filter_list_args(), ["-filters"]);
     }
 
+    #[test]
+    fn ffmpeg_hardware_list_args_match_sidecar_contract() {
+        assert_eq!(ffmpeg_hwaccel_list_args(), ["-hwaccels"]);
+        assert_eq!(ffmpeg_decoder_list_args(), ["-decoders"]);
+    }
+
+    #[test]
+    fn ffmpeg_version_args_match_sidecar_contract() {
+        assert_eq!(ffmpeg_version_args(), ["-version"]);
+    }
+
+    #[test]
+    fn parse_ffmpeg_info_reads_release_builds() {
+        let stdout = "\
+ffmpeg version 7.1 Copyright (c) 2000-2024 the FFmpeg developers
+built with Apple clang version 16.0.0 (clang-1600.0.26.4)
+configuration: --prefix=/opt/homebrew/Cellar/ffmpeg/7.1 --enable-shared --enable-gpl --enable-libx264 --enable-libass --enable-videotoolbox
+libavutil      59. 39.100 / 59. 39.100
+";
+
+        let info = parse_ffmpeg_info(stdout).expect("release banner should parse");
+
+        assert_eq!(info.version, "7.1");
+        assert_eq!(
+            (info.major, info.minor, info.year),
+            (Some(7), Some(1), Some(2024))
+        );
+        assert_eq!(info.configuration.len(), 6);
+        assert_eq!(
+            info.enabled_libraries.iter().collect::<Vec<_>>(),
+            ["libass", "libx264"]
+        );
+        assert!(info.has_library("libass"));
+        assert!(info.supports_display_rotation());
+        assert!(info.supports_heif_input());
+    }
+
+    #[test]
+    fn parse_ffmpeg_info_tolerates_distribution_and_snapshot_banners() {
+        let ubuntu = parse_ffmpeg_info(
+            "ffmpeg version 4.4.2-0ubuntu0.22.04.1 Copyright (c) 2000-2021 the FFmpeg developers\n\
+             \x20 configuration: --prefix=/usr --extra-version=0ubuntu0.22.04.1 --enable-libvidstab\n",
+        )
+        .expect("distribution banner should parse");
+        let tagged = parse_ffmpeg_info("ffmpeg version n6.1.1 Copyright (c) 2000-2023\n")
+            .expect("tagged banner should parse");
+        let snapshot = parse_ffmpeg_info(
+            "ffmpeg version N-113000-g1a2b3c4d5e Copyright (c) 2000-2024 the FFmpeg developers\n",
+        )
+        .expect("snapshot banner should parse");
+
+        assert_eq!(
+            (ubuntu.major, ubuntu.minor, ubuntu.year),
+            (Some(4), Some(4), Some(2021))
+        );
+        assert!(ubuntu.has_library("libvidstab"));
+        assert!(!ubuntu.supports_display_rotation());
+        assert_eq!((tagged.major, tagged.minor), (Some(6), Some(1)));
+        assert!(!tagged.supports_heif_input());
+        assert_eq!(snapshot.version, "N-113000-g1a2b3c4d5e");
+        assert_eq!((snapshot.major, snapshot.year), (None, Some(2024)));
+        assert!(!snapshot.is_at_least(4, 0));
+        assert!(!ubuntu.meets_minimum_version());
+        assert!(tagged.meets_minimum_version());
+        assert!(snapshot.meets_minimum_version());
+        assert_eq!(parse_ffmpeg_info("ffprobe: command not found\n"), None);
+    }
+
+    #[test]
+    fn encoder_self_test_args_encode_a_short_null_clip() {
+        assert_eq!(
+            encoder_self_test_args("h264_nvenc").join(" "),
+            "-hide_banner -nostdin -f lavfi -i color=black:s=256x256 -frames:v 10 \
+             -c:v h264_nvenc -f null -"
+        );
+    }
+
+    #[test]
+    fn encoder_self_test_explains_known_hardware_failures() {
+        let passed = EncoderSelfTest::from_output("hevc_nvenc", true, "");
+        let old_driver = EncoderSelfTest::from_output(
+            "h264_nvenc",
+            false,
+            "[h264_nvenc @ 0x55d0] Driver does not support the required nvenc API version. \
+             Required: 12.1 Found: 11.1\n\
+             [h264_nvenc @ 0x55d0] The minimum required Nvidia driver for nvenc is 530.41.03 \
+             or newer\n\
+             Error while opening encoder - maybe incorrect parameters such as bit_rate, rate, \
+             width or height\n",
+        );
+        let unknown = EncoderSelfTest::from_output(
+            "hevc_videotoolbox",
+            false,
+            "Error: something else went wrong\n\n",
+        );
+
+        assert!(passed.passed);
+        assert_eq!(passed.error, None);
+        assert!(!old_driver.passed);
+        assert_eq!(
+            old_driver.error.as_deref(),
+            Some("NVENC listed but unusable: driver too old")
+        );
+        assert_eq!(
+            unknown.error.as_deref(),
+            Some("VideoToolbox listed but unusable: Error: something else went wrong")
+        );
+    }
+
+    #[test]
+    fn available_encoders_list_and_remove_hardware_encoders() {
+        let mut encoders = AvailableEncoders {
+            h264_nvenc: true,
+            hevc_videotoolbox: true,
+            libmp3lame: true,
+            ..AvailableEncoders::default()
+        };
+
+        encoders.remove("h264_nvenc");
+
+        assert_eq!(encoders.listed_hardware_encoders(), ["hevc_videotoolbox"]);
+        assert!(encoders.libmp3lame);
+    }
+
+    #[test]
+    fn parse_hardware_capabilities_detects_methods_and_decoders() {
+        let hwaccels = "\
+Hardware acceleration methods:
+vdpau
+cuda
+vaapi
+qsv
+drm
+opencl
+vulkan
+
+";
+        let decoders = "\
+Decoders:
+ V..... = Video
+ ------
+ VFS..D h264                 H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10
+ V....D h264_qsv             H264 video (Intel Quick Sync Video acceleration) (codec h264)
+ V..... h264_cuvid           Nvidia CUVID H264 decoder (codec h264)
+ V..... hevc_cuvid           Nvidia CUVID HEVC decoder (codec hevc)
+";
+
+        let actual = parse_hardware_capabilities(hwaccels, decoders);
+
+        assert_eq!(
+            actual,
+            HardwareCapabilities {
+                version: HARDWARE_CAPABILITIES_VERSION,
+                hwaccels: AvailableHwaccels {
+                    cuda: true,
+                    qsv: true,
+                    vaapi: true,
+                    videotoolbox: false,
+                    d3d11va: false,
+                },
+                hardware_decoders: vec![
+                    "h264_qsv".to_string(),
+                    "h264_cuvid".to_string(),
+                    "hevc_cuvid".to_string(),
+                ],
+            }
+        );
+        assert!(actual.supports_hw_decode("h264_nvenc"));
+        assert!(!actual.supports_hw_decode("hevc_videotoolbox"));
+        assert!(!actual.supports_hw_decode("libx264"));
+    }
+
+    fn hw_decode_config(video_codec: &str) -> ConversionConfig {
+        let mut config = sample_config("mp4", video_codec);
+        config.hw_decode = true;
+        config
+    }
+
+    #[test]
+    fn downgrade_unsupported_hw_decode_keeps_available_methods() {
+        let capabilities = HardwareCapabilities {
+            hwaccels: AvailableHwaccels {
+                cuda: true,
+                ..AvailableHwaccels::default()
+            },
+            ..HardwareCapabilities::default()
+        };
+        let mut nvenc = hw_decode_config("h264_nvenc");
+        let mut videotoolbox = hw_decode_config("hevc_videotoolbox");
+
+        assert!(!downgrade_unsupported_hw_decode(&mut nvenc, &capabilities));
+        assert!(nvenc.hw_decode);
+        assert!(downgrade_unsupported_hw_decode(
+            &mut videotoolbox,
+            &capabilities
+        ));
+        assert!(!videotoolbox.hw_decode);
+    }
+
+    #[test]
+    fn parse_available_hwaccels_ignores_heading_and_unknown_methods() {
+        let actual = parse_available_hwaccels(
+            "Hardware acceleration methods:\nvideotoolbox\nd3d11va_extra\n",
+        );
+
+        assert_eq!(
+            actual,
+            AvailableHwaccels {
+                videotoolbox: true,
+                ..AvailableHwaccels::default()
+            }
+        );
+    }
+
+    const FFMPEG_6_FILTERS: &str = "\
+Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  V = Video input/output
+  N = Dynamic number and/or type of input/output
+  | = Source or sink filter
+ ... abench            A->A       Benchmark part of a filtergraph.
+ ... subtitles         V->V       Render text subtitles onto input video using the libass library.
+ ... vidstabdetect     V->V       Extract relative transformations, pass 1 of 2 for stabilization (see vidstabtransform for pass 2).
+ T.. vidstabtransform  V->V       Transform the frames, pass 2 of 2 for stabilization (see vidstabdetect for pass 1).
+ .S. zscale            V->V       Apply resizing, colorspace and bit depth conversion.
+ ... tonemap           V->V       Conversion to/from different dynamic ranges.
+ ... nullsrc           |->V       Null video source, return unprocessed video frames.
+";
+
+    const FFMPEG_7_MUXERS: &str = "\
+Formats:
+ D.. = Demuxing supported
+ .E. = Muxing supported
+ ..d = Is a device
+ ---
+  E  apng            Animated Portable Network Graphics
+  E  image2          image2 sequence
+  E  mp4             MP4 (MPEG-4 Part 14)
+  E  webm            WebM
+  E  audiotoolbox    AudioToolbox output device
+";
+
+    const FFMPEG_PROTOCOLS: &str = "\
+Supported file protocols:
+Input:
+  async
+  file
+  https
+Output:
+  file
+  pipe
+";
+
+    #[test]
+    fn ffmpeg_format_list_args_match_sidecar_contract() {
+        assert_eq!(ffmpeg_muxer_list_args(), ["-muxers"]);
+        assert_eq!(ffmpeg_protocol_list_args(), ["-protocols"]);
+    }
+
+    #[test]
+    fn parse_ffmpeg_capabilities_reads_filters_muxers_and_protocols() {
+        let actual = parse_ffmpeg_capabilities(FFMPEG_6_FILTERS, FFMPEG_7_MUXERS, FFMPEG_PROTOCOLS);
+
+        assert_eq!(
+            actual
+                .filters
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            [
+                "abench",
+                "nullsrc",
+                "subtitles",
+                "tonemap",
+                "vidstabdetect",
+                "vidstabtransform",
+                "zscale"
+            ]
+        );
+        assert_eq!(
+            actual.muxers.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["apng", "audiotoolbox", "image2", "mp4", "webm"]
+        );
+        assert!(actual.has_protocol("https"));
+        assert!(actual.has_protocol("pipe"));
+        assert!(!actual.has_protocol("Input"));
+        assert_eq!(
+            actual.flags,
+            CapabilityFlags {
+                subtitle_burn_in: true,
+                stabilization: true,
+                tonemapping: true,
+                webp_output: false,
+                apng_output: true,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_ffmpeg_capabilities_accepts_ffmpeg_6_muxer_rows() {
+        let muxers = "\
+File formats:
+ D. = Demuxing supported
+ .E = Muxing supported
+ --
+  E matroska        Matroska
+  E webp            WebP
+";
+
+        let actual = parse_ffmpeg_capabilities("", muxers, "");
+
+        assert!(actual.has_muxer("matroska"));
+        assert!(actual.flags.webp_output);
+        assert!(!actual.flags.subtitle_burn_in);
+        assert!(actual.protocols.is_empty());
+    }
+
     #[test]
     fn parse_available_encoders_detects_ffmpeg_encoder_rows() {
         let stdout = "\
@@ -0,0 +1,489 @@
+//! Parallel chunked encoding: the video is cut at source keyframes into
+//! chunks that separate `FFmpeg` processes encode at the same time, while
+//! audio is encoded once in a pass of its own.
+//!
+//! Software encoders rarely keep every core busy on their own, so long
+//! encodes finish sooner this way. The chunks are joined with the concat
+//! demuxer and muxed with the audio, so nothing is encoded twice. Settings
+//! that depend on the whole file, such as loudness normalization, cannot be
+//! split and are refused.
+
+use crate::args::{
+    add_track_maps, collect_selected_audio_tracks, has_custom_pixel_format,
+    needs_h264_compatible_pixel_format,
+};
+use crate::codec::{add_audio_codec_args, add_fps_args, add_video_codec_args};
+use crate::error::ConversionError;
+use crate::filters::{
+    build_audio_filters, build_pre_seeked_encode_overlay_filter_complex,
+    build_pre_seeked_encode_video_filters, has_overlay,
+};
+use crate::media_rules::{container_supports_audio, is_gif_container, is_image_container};
+use crate::segments::trim_ranges;
+use crate::split::splits_output;
+use crate::types::{ConversionConfig, ProbeMetadata};
+use crate::utils::is_audio_only_container;
+
+/// Most chunks a task may be split into.
+pub const MAX_PARALLEL_CHUNKS: u32 = 16;
+
+/// Shortest chunk worth a process of its own; shorter sources get fewer
+/// chunks.
+pub const MIN_PARALLEL_CHUNK_SECONDS: f64 = 10.0;
+
+/// Muxer of the chunk and audio files, which holds any codec the task may
+/// encode to.
+pub const CHUNK_MUXER: &str = "matroska";
+
+/// Encoder name suffixes of hardware encoders, which share one device
+/// rather than scaling with processes.
+const HARDWARE_ENCODER_SUFFIXES: [&str; 5] = ["_nvenc", "_videotoolbox", "_qsv", "_vaapi", "_amf"];
+
+/// One chunk of the video, in seconds from the start of the source. `end`
+/// is `None` for the last chunk, which runs to the end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParallelChunk {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+/// Number of chunks the task asks for, or `None` when it encodes in one
+/// process.
+#[must_use]
+pub fn parallel_chunk_count(config: &ConversionConfig) -> Option<u32> {
+    config.parallel_chunks.filter(|chunks| *chunks > 1)
+}
+
+/// Checks that a task asking for parallel chunks can be split.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for chunk counts out of range,
+/// stream copy, outputs other than video, hardware encoders, extra `FFmpeg`
+/// arguments, and settings that act on the whole file: loudness
+/// normalization, trimming and splitting.
+pub fn validate_parallel_chunks(config: &ConversionConfig) -> Result<(), ConversionError> {
+    let Some(chunks) = config.parallel_chunks else {
+        return Ok(());
+    };
+    if !(2..=MAX_PARALLEL_CHUNKS).contains(&chunks) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Parallel chunks must be between 2 and {MAX_PARALLEL_CHUNKS}"
+        )));
+    }
+    let unsupported = |feature: &str| {
+        Err(ConversionError::InvalidInput(format!(
+            "{feature} cannot be combined with parallel chunked encoding"
+        )))
+    };
+    if config.processing_mode == "copy" {
+        return unsupported("Stream copy");
+    }
+    if is_audio_only_container(&config.container)
+        || is_image_container(&config.container)
+        || is_gif_container(&config.container)
+        || config.image_sequence_output
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Parallel chunked encoding is only available for video outputs, not '{}'",
+            config.container
+        )));
+    }
+    if HARDWARE_ENCODER_SUFFIXES
+        .iter()
+        .any(|suffix| config.video_codec.ends_with(suffix))
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Parallel chunked encoding needs a software encoder, not '{}'",
+            config.video_codec
+        )));
+    }
+    if config.audio_normalize {
+        return unsupported("Loudness normalization");
+    }
+    if !trim_ranges(config).is_empty() || !config.remove_segments.is_empty() {
+        return unsupported("Trimming");
+    }
+    if splits_output(config) {
+        return unsupported("Splitting the output");
+    }
+    if !config.extra_input_args.is_empty() || !config.extra_output_args.is_empty() {
+        return unsupported("Extra FFmpeg arguments");
+    }
+    Ok(())
+}
+
+/// Splits a `duration` long source into at most `count` chunks that start
+/// on its `keyframes`, each near an equal share of the source.
+///
+/// Keyframe times are taken relative to the first keyframe, which is where
+/// input seeking counts from. Fewer chunks are planned when the source is
+/// short or has too few keyframes; a single chunk means the source cannot
+/// be split.
+#[must_use]
+pub fn plan_parallel_chunks(duration: f64, keyframes: &[f64], count: u32) -> Vec<ParallelChunk> {
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the ratio is clamped to the chunk limit before the cast"
+    )]
+    let by_length = (duration / MIN_PARALLEL_CHUNK_SECONDS)
+        .floor()
+        .clamp(1.0, f64::from(MAX_PARALLEL_CHUNKS)) as u32;
+    let count = count.clamp(1, by_length);
+    let origin = keyframes.first().copied().unwrap_or(0.0);
+    let half_chunk = duration / f64::from(count) / 2.0;
+
+    let mut starts = vec![0.0];
+    for index in 1..count {
+        let target = duration * f64::from(index) / f64::from(count);
+        let previous = starts.last().copied().unwrap_or(0.0);
+        let nearest = keyframes
+            .iter()
+            .map(|keyframe| keyframe - origin)
+            .filter(|start| *start >= previous + half_chunk && *start <= duration - half_chunk)
+            .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()));
+        if let Some(start) = nearest {
+            starts.push(start);
+        }
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, start)| ParallelChunk {
+            start: *start,
+            end: starts.get(index + 1).copied(),
+        })
+        .collect()
+}
+
+/// Arguments that encode the video of one chunk of `input` to `output`,
+/// with the task's encoder and filters and without audio, subtitles or
+/// metadata.
+///
+/// The input is seeked to the chunk's keyframe, so decoding starts there
+/// and burned subtitles are kept on source time.
+#[must_use]
+pub fn build_chunk_video_args(
+    input: &str,
+    output: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    chunk: &ParallelChunk,
+) -> Vec<String> {
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-y".to_string(),
+    ];
+    if chunk.start > 0.0 {
+        args.push("-ss".to_string());
+        args.push(format!("{:.6}", chunk.start));
+    }
+    args.push("-i".to_string());
+    args.push(input.to_string());
+    let use_overlay = has_overlay(config);
+    if use_overlay && let Some(overlay) = &config.overlay {
+        args.push("-i".to_string());
+        args.push(overlay.path.clone());
+    }
+    if let Some(end) = chunk.end {
+        args.push("-t".to_string());
+        args.push(format!("{:.6}", end - chunk.start));
+    }
+    args.extend([
+        "-map_metadata".to_string(),
+        "-1".to_string(),
+        "-map_chapters".to_string(),
+        "-1".to_string(),
+    ]);
+
+    add_video_codec_args(&mut args, config);
+    if has_custom_pixel_format(config) {
+        args.push("-pix_fmt".to_string());
+        args.push(config.pixel_format.trim().to_string());
+    } else if needs_h264_compatible_pixel_format(config, probe) {
+        args.push("-pix_fmt".to_string());
+        args.push("yuv420p".to_string());
+    }
+    if use_overlay {
+        args.push("-filter_complex".to_string());
+        args.push(build_pre_seeked_encode_overlay_filter_complex(
+            config,
+            chunk.start,
+        ));
+    } else {
+        let filters = build_pre_seeked_encode_video_filters(config, chunk.start);
+        if !filters.is_empty() {
+            args.push("-vf".to_string());
+            args.push(filters.join(","));
+        }
+    }
+    add_fps_args(&mut args, config);
+    args.push("-map".to_string());
+    args.push(if use_overlay {
+        "[vout]".to_string()
+    } else {
+        "0:V:0".to_string()
+    });
+    args.extend([
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-f".to_string(),
+        CHUNK_MUXER.to_string(),
+        output.to_string(),
+    ]);
+    args
+}
+
+/// Arguments that encode the selected audio tracks of `input` to `output`
+/// in one pass, or `None` when the output carries no audio.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when a selected audio track is
+/// missing from the source.
+pub fn build_chunk_audio_args(
+    input: &str,
+    output: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Result<Option<Vec<String>>, ConversionError> {
+    if !container_supports_audio(&config.container) {
+        return Ok(None);
+    }
+    let tracks = collect_selected_audio_tracks(config, probe)?;
+    if tracks.is_empty() {
+        return Ok(None);
+    }
+
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-y".to_string(),
+        "-i".to_string(),
+        input.to_string(),
+        "-map_metadata".to_string(),
+        "-1".to_string(),
+        "-map_chapters".to_string(),
+        "-1".to_string(),
+    ];
+    add_track_maps(&mut args, &tracks, |track| track.index);
+    add_audio_codec_args(&mut args, config);
+    let filters = build_audio_filters(config);
+    if !filters.is_empty() {
+        args.push("-af".to_string());
+        args.push(filters.join(","));
+    }
+    args.extend([
+        "-vn".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-f".to_string(),
+        CHUNK_MUXER.to_string(),
+        output.to_string(),
+    ]);
+    Ok(Some(args))
+}
+
+/// Combines the progress of concurrently encoded chunks into one share of
+/// the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkProgress {
+    lengths: Vec<f64>,
+    encoded: Vec<f64>,
+}
+
+impl ChunkProgress {
+    #[must_use]
+    pub fn new(chunks: &[ParallelChunk], duration: f64) -> Self {
+        Self {
+            lengths: chunks
+                .iter()
+                .map(|chunk| (chunk.end.unwrap_or(duration) - chunk.start).max(0.0))
+                .collect(),
+            encoded: vec![0.0; chunks.len()],
+        }
+    }
+
+    /// Records that chunk `index` has encoded `seconds` of its length and
+    /// returns the percent of the whole source encoded so far.
+    pub fn update(&mut self, index: usize, seconds: f64) -> f64 {
+        if let (Some(encoded), Some(length)) =
+            (self.encoded.get_mut(index), self.lengths.get(index))
+        {
+            *encoded = seconds.clamp(0.0, *length);
+        }
+        self.percent()
+    }
+
+    #[must_use]
+    pub fn percent(&self) -> f64 {
+        let total: f64 = self.lengths.iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (self.encoded.iter().sum::<f64>() / total * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AudioTrack, sample_config};
+
+    fn chunked_config() -> ConversionConfig {
+        let mut config = sample_config("mkv", "libx265");
+        config.audio_codec = "libopus".to_string();
+        config.crf = 22;
+        config.preset = "slow".to_string();
+        config.parallel_chunks = Some(4);
+        config
+    }
+
+    fn film_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("400.0".to_string()),
+            video_codec: Some("h264".to_string()),
+            pixel_format: Some("yuv420p".to_string()),
+            audio_tracks: vec![AudioTrack {
+                index: 1,
+                codec: "aac".to_string(),
+                ..AudioTrack::default()
+            }],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn chunks_start_on_the_keyframes_nearest_equal_shares() {
+        let keyframes: Vec<f64> = (0..80)
+            .map(|index| f64::from(index).mul_add(5.0, 1.4))
+            .collect();
+
+        let chunks = plan_parallel_chunks(400.0, &keyframes, 4);
+
+        let starts: Vec<f64> = chunks.iter().map(|chunk| chunk.start).collect();
+        assert_eq!(starts, [0.0, 100.0, 200.0, 300.0]);
+        assert_eq!(chunks[0].end, Some(100.0));
+        assert_eq!(chunks[3].end, None);
+    }
+
+    #[test]
+    fn short_or_sparse_sources_get_fewer_chunks() {
+        let short = plan_parallel_chunks(25.0, &[0.0, 4.0, 8.0, 14.0, 20.0], 8);
+        let sparse = plan_parallel_chunks(400.0, &[0.0, 390.0], 4);
+
+        assert_eq!(short.len(), 2);
+        assert_eq!(short[1].start.to_bits(), 14.0_f64.to_bits());
+        assert_eq!(sparse.len(), 1);
+    }
+
+    #[test]
+    fn chunk_args_seek_to_the_keyframe_and_encode_video_only() {
+        let chunk = ParallelChunk {
+            start: 100.0,
+            end: Some(200.0),
+        };
+
+        let args = build_chunk_video_args(
+            "/in/film.mkv",
+            "/tmp/chunk1.mkv",
+            &chunked_config(),
+            &film_probe(),
+            &chunk,
+        );
+
+        assert_eq!(
+            args[3..9],
+            [
+                "-ss",
+                "100.000000",
+                "-i",
+                "/in/film.mkv",
+                "-t",
+                "100.000000"
+            ]
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-c:v", "libx265"]));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:V:0"]));
+        assert!(args.iter().any(|arg| arg == "-an"));
+        assert_eq!(
+            args[args.len() - 3..],
+            ["-f", "matroska", "/tmp/chunk1.mkv"]
+        );
+    }
+
+    #[test]
+    fn audio_is_encoded_once_for_the_whole_source() {
+        let args = build_chunk_audio_args(
+            "/in/film.mkv",
+            "/tmp/audio.mka",
+            &chunked_config(),
+            &film_probe(),
+        )
+        .expect("audio args should build")
+        .expect("the source has audio");
+        let silent = build_chunk_audio_args(
+            "/in/film.mkv",
+            "/tmp/audio.mka",
+            &chunked_config(),
+            &ProbeMetadata {
+                audio_tracks: Vec::new(),
+                ..film_probe()
+            },
+        )
+        .expect("audio args should build");
+
+        assert!(!args.iter().any(|arg| arg == "-ss"));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:1"]));
+        assert!(args.windows(2).any(|pair| pair == ["-c:a", "libopus"]));
+        assert!(args.iter().any(|arg| arg == "-vn"));
+        assert_eq!(silent, None);
+    }
+
+    #[test]
+    fn whole_file_settings_refuse_parallel_chunks() {
+        let mut normalized = chunked_config();
+        normalized.audio_normalize = true;
+        let mut trimmed = chunked_config();
+        trimmed.start_time = Some("00:00:10".to_string());
+        let mut copied = chunked_config();
+        copied.processing_mode = "copy".to_string();
+        let mut hardware = chunked_config();
+        hardware.video_codec = "hevc_nvenc".to_string();
+        let mut too_many = chunked_config();
+        too_many.parallel_chunks = Some(64);
+        let mut extra_args = chunked_config();
+        extra_args.extra_output_args = vec!["-tune".to_string(), "film".to_string()];
+
+        for (config, expected) in [
+            (normalized, "Loudness normalization cannot be combined"),
+            (trimmed, "Trimming cannot be combined"),
+            (copied, "Stream copy cannot be combined"),
+            (hardware, "needs a software encoder"),
+            (too_many, "between 2 and 16"),
+            (extra_args, "Extra FFmpeg arguments cannot be combined"),
+        ] {
+            let error = validate_parallel_chunks(&config)
+                .expect_err("settings should be refused")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+        assert!(validate_parallel_chunks(&chunked_config()).is_ok());
+    }
+
+    #[test]
+    fn chunk_progress_sums_the_encoded_share_of_every_chunk() {
+        let chunks = plan_parallel_chunks(400.0, &[0.0, 100.0, 200.0, 300.0], 4);
+        let mut progress = ChunkProgress::new(&chunks, 400.0);
+
+        progress.update(0, 50.0);
+        progress.update(3, 150.0);
+        let percent = progress.update(1, 100.0);
+
+        assert_eq!(percent.to_bits(), 62.5_f64.to_bits());
+    }
+}
@@ -1,12 +1,16 @@
+use crate::hw_devices::nvenc_gpu_args;
 use crate::types::ConversionConfig;
 use crate::utils::{
     is_nvenc_codec, is_svt_av1_codec, is_videotoolbox_codec, map_nvenc_preset, map_svt_av1_preset,
 };
 
+/// Highest CRF `libaom-av1` accepts; lower values give better quality.
+pub const MAX_AVIF_CRF: u32 = 63;
+
 pub fn add_video_codec_args(args: &mut Vec<String>, config: &ConversionConfig) {
     let is_still_image_codec = matches!(
         config.video_codec.as_str(),
-        "png" | "mjpeg" | "libwebp" | "bmp" | "tiff"
+        "png" | "mjpeg" | "libwebp" | "bmp" | "tiff" | "libaom-av1"
     );
 
     let is_nvenc = is_nvenc_codec(&config.video_codec);
@@ -51,6 +55,7 @@ pub fn add_video_codec_args(args: &mut Vec<String>, config: &ConversionConfig) {
     }
 
     if is_nvenc {
+        args.extend(nvenc_gpu_args(config));
         if config.nvenc_spatial_aq {
             args.push("-spatial_aq".to_string());
             args.push("1".to_string());
@@ -91,6 +96,12 @@ fn add_still_image_codec_args(args: &mut Vec<String>, config: &ConversionConfig)
             args.push("-pred".to_string());
             args.push(normalize_png_prediction(&config.image_png_prediction).to_string());
         }
+        "libaom-av1" => {
+            args.push("-crf".to_string());
+            args.push(config.image_avif_crf.min(MAX_AVIF_CRF).to_string());
+            args.push("-still-picture".to_string());
+            args.push("1".to_string());
+        }
         "tiff" => {
             args.push("-compression_algo".to_string());
             args.push(normalize_tiff_compression(&config.image_tiff_compression).to_string());
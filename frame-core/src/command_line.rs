@@ -0,0 +1,202 @@
+//! Copyable renderings of an `FFmpeg` command for the user's shell.
+//!
+//! Each argument is quoted only when it has to be, so commands stay readable
+//! while paths with spaces, quotes or non-ASCII names survive a paste.
+
+use std::borrow::Cow;
+
+use serde::Serialize;
+
+/// Quote characters `PowerShell` accepts besides `'`, which a single-quoted
+/// string must double as well.
+const POWERSHELL_SINGLE_QUOTES: [char; 5] = ['\'', '\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'];
+
+/// A command Frame would run, ready to show or copy.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPreview {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Single line for `sh`, `bash` and `zsh`.
+    pub posix: String,
+    /// Single line for `PowerShell`.
+    pub powershell: String,
+    /// Parts of the real run the command leaves out.
+    pub notes: Vec<String>,
+}
+
+impl CommandPreview {
+    #[must_use]
+    pub fn new(program: &str, args: Vec<String>) -> Self {
+        Self {
+            posix: posix_command_line(program, &args),
+            powershell: powershell_command_line(program, &args),
+            program: program.to_string(),
+            args,
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// Renders `program` and `args` as one POSIX shell line.
+#[must_use]
+pub fn posix_command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(posix_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders `program` and `args` as one `PowerShell` line, run through the
+/// call operator so a quoted program path still executes.
+#[must_use]
+pub fn powershell_command_line(program: &str, args: &[String]) -> String {
+    let mut words = vec![Cow::Borrowed("&")];
+    words.extend(
+        std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .map(powershell_quote),
+    );
+    words.join(" ")
+}
+
+/// Quotes `arg` for a POSIX shell. Arguments made only of characters the
+/// shell never interprets are left bare; anything else is single-quoted,
+/// with embedded single quotes closed, escaped and reopened.
+#[must_use]
+pub fn posix_quote(arg: &str) -> Cow<'_, str> {
+    let bare = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "_-./:=+,@%".contains(character));
+    if bare {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+/// Quotes `arg` for `PowerShell`. Single-quoted strings are literal there,
+/// so only the quote characters themselves need doubling.
+///
+/// Options are left bare only when they are plain words: `PowerShell` splits
+/// a bare `-name:value.ext` into two arguments.
+#[must_use]
+pub fn powershell_quote(arg: &str) -> Cow<'_, str> {
+    let word = |character: char| character.is_ascii_alphanumeric() || "_-".contains(character);
+    let bare = !arg.is_empty()
+        && if arg.starts_with('-') {
+            arg.chars().all(word)
+        } else {
+            arg.chars()
+                .all(|character| word(character) || "./:\\=+".contains(character))
+        };
+    if bare {
+        return Cow::Borrowed(arg);
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('\'');
+    for character in arg.chars() {
+        if POWERSHELL_SINGLE_QUOTES.contains(&character) {
+            quoted.push(character);
+        }
+        quoted.push(character);
+    }
+    quoted.push('\'');
+    Cow::Owned(quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn plain_arguments_stay_bare() {
+        let args = args(&[
+            "-hide_banner",
+            "-i",
+            "/in/clip.mp4",
+            "-crf",
+            "23",
+            "out.mkv",
+        ]);
+
+        assert_eq!(
+            posix_command_line("ffmpeg", &args),
+            "ffmpeg -hide_banner -i /in/clip.mp4 -crf 23 out.mkv"
+        );
+        assert_eq!(
+            powershell_command_line("ffmpeg", &args),
+            "& ffmpeg -hide_banner -i /in/clip.mp4 -crf 23 out.mkv"
+        );
+    }
+
+    #[test]
+    fn spaces_and_shell_characters_are_quoted() {
+        for (arg, posix, powershell) in [
+            (
+                "My Videos/a b.mp4",
+                "'My Videos/a b.mp4'",
+                "'My Videos/a b.mp4'",
+            ),
+            ("$HOME`x", "'$HOME`x'", "'$HOME`x'"),
+            (
+                "scale=1280:-2,fps=30",
+                "scale=1280:-2,fps=30",
+                "'scale=1280:-2,fps=30'",
+            ),
+            ("", "''", "''"),
+            (
+                "C:\\Users\\Me\\clip.mp4",
+                "'C:\\Users\\Me\\clip.mp4'",
+                "C:\\Users\\Me\\clip.mp4",
+            ),
+            ("-metadata:s:a:0", "-metadata:s:a:0", "'-metadata:s:a:0'"),
+        ] {
+            assert_eq!(posix_quote(arg), posix, "{arg}");
+            assert_eq!(powershell_quote(arg), powershell, "{arg}");
+        }
+    }
+
+    #[test]
+    fn embedded_quotes_survive_both_shells() {
+        assert_eq!(posix_quote("John's \"cut\""), r#"'John'\''s "cut"'"#);
+        assert_eq!(powershell_quote("John's \"cut\""), r#"'John''s "cut"'"#);
+        assert_eq!(
+            powershell_quote("title=\u{2018}Live\u{2019}"),
+            "'title=\u{2018}\u{2018}Live\u{2019}\u{2019}'"
+        );
+    }
+
+    #[test]
+    fn unicode_paths_are_quoted_and_kept_intact() {
+        let path = "/Filmy/Żółć 日本語 🎬.mkv";
+
+        assert_eq!(posix_quote(path), format!("'{path}'"));
+        assert_eq!(powershell_quote(path), format!("'{path}'"));
+    }
+
+    #[test]
+    fn previews_render_every_shell_from_the_same_arguments() {
+        let preview = CommandPreview::new(
+            "/Applications/Frame.app/ffmpeg",
+            args(&["-i", "in put.mov", "out.mp4"]),
+        );
+
+        assert_eq!(
+            preview.posix,
+            "/Applications/Frame.app/ffmpeg -i 'in put.mov' out.mp4"
+        );
+        assert_eq!(
+            preview.powershell,
+            "& /Applications/Frame.app/ffmpeg -i 'in put.mov' out.mp4"
+        );
+        assert_eq!(preview.args.len(), 3);
+    }
+}
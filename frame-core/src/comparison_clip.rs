@@ -0,0 +1,334 @@
+//! Before and after comparison clips: a few seconds of a reference and its
+//! encode played together in one short H.264 file.
+//!
+//! Both inputs are fitted into panels of the reference's shape at the same
+//! height, padding whichever side does not match, so encodes with another
+//! aspect ratio still line up with the reference.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConversionError;
+use crate::types::ProbeMetadata;
+
+/// Tallest panel a comparison clip uses; taller references are scaled down.
+pub const COMPARISON_MAX_HEIGHT: u32 = 720;
+
+/// Label drawn on the reference panel.
+pub const COMPARISON_REFERENCE_LABEL: &str = "Reference";
+
+/// Label drawn on the encoded panel.
+pub const COMPARISON_ENCODED_LABEL: &str = "Encoded";
+
+/// Encoder quality of the clip; high enough that the clip itself does not
+/// hide the differences it shows.
+const COMPARISON_CRF: u32 = 16;
+
+/// How the two panels of a comparison clip are arranged.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonLayout {
+    /// Reference on the left, encode on the right.
+    SideBySide,
+    /// Reference above, encode below.
+    TopBottom,
+    /// One frame showing the left half of the reference and the right half
+    /// of the encode.
+    SplitWipe,
+}
+
+/// Section and layout of a comparison clip.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComparisonClipOptions {
+    pub start_seconds: f64,
+    pub duration_seconds: f64,
+    pub layout: ComparisonLayout,
+    /// Whether the `FFmpeg` build has the `drawtext` filter for the labels.
+    pub labels: bool,
+}
+
+/// `FFmpeg` arguments for one comparison clip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComparisonClipPlan {
+    pub args: Vec<String>,
+    /// Adjustments the caller should be told about.
+    pub notes: Vec<String>,
+}
+
+/// Builds the arguments that write `options.duration_seconds` of
+/// `reference` and `encoded`, from `options.start_seconds` in both, to
+/// `output` as an H.264 MP4.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when either file has no video,
+/// the reference size is unknown, or the section is empty.
+pub fn build_comparison_clip_args(
+    reference: &str,
+    encoded: &str,
+    output: &str,
+    options: ComparisonClipOptions,
+    reference_probe: &ProbeMetadata,
+    encoded_probe: &ProbeMetadata,
+) -> Result<ComparisonClipPlan, ConversionError> {
+    if reference_probe.video_codec.is_none() || encoded_probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "A comparison clip needs two files with video".to_string(),
+        ));
+    }
+    let (Some(width), Some(height)) = (reference_probe.width, reference_probe.height) else {
+        return Err(ConversionError::InvalidInput(
+            "The reference video size is unknown".to_string(),
+        ));
+    };
+    if width == 0 || height == 0 {
+        return Err(ConversionError::InvalidInput(
+            "The reference video size is unknown".to_string(),
+        ));
+    }
+    if !options.start_seconds.is_finite()
+        || options.start_seconds < 0.0
+        || !options.duration_seconds.is_finite()
+        || options.duration_seconds <= 0.0
+    {
+        return Err(ConversionError::InvalidInput(
+            "The comparison section must start at or after zero and last more than zero seconds"
+                .to_string(),
+        ));
+    }
+
+    let (panel_width, panel_height) = panel_size(width, height);
+    let mut notes = Vec::new();
+    if let (Some(encoded_width), Some(encoded_height)) = (encoded_probe.width, encoded_probe.height)
+        && u64::from(encoded_width) * u64::from(height)
+            != u64::from(encoded_height) * u64::from(width)
+    {
+        notes.push(format!(
+            "The encode is {encoded_width}x{encoded_height} and the reference {width}x{height}; \
+             the encode was padded to the reference's shape"
+        ));
+    }
+    if !options.labels {
+        notes.push("This FFmpeg build cannot draw text; the panels are not labelled".to_string());
+    }
+
+    let filter_complex = comparison_filter_complex(options, panel_width, panel_height);
+
+    let mut args = vec!["-hide_banner".to_string(), "-nostdin".to_string()];
+    for input in [reference, encoded] {
+        if options.start_seconds > 0.0 {
+            args.extend(["-ss".to_string(), format!("{:.3}", options.start_seconds)]);
+        }
+        args.extend([
+            "-t".to_string(),
+            format!("{:.3}", options.duration_seconds),
+            "-i".to_string(),
+            input.to_string(),
+        ]);
+    }
+    args.extend([
+        "-filter_complex".to_string(),
+        filter_complex,
+        "-map".to_string(),
+        "[out]".to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-c:v".to_string(),
+        "libx264".to_string(),
+        "-preset".to_string(),
+        "veryfast".to_string(),
+        "-crf".to_string(),
+        COMPARISON_CRF.to_string(),
+        "-pix_fmt".to_string(),
+        "yuv420p".to_string(),
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        "-y".to_string(),
+        output.to_string(),
+    ]);
+
+    Ok(ComparisonClipPlan { args, notes })
+}
+
+/// Fits both inputs into labelled panels and arranges them as `[out]`.
+fn comparison_filter_complex(
+    options: ComparisonClipOptions,
+    panel_width: u32,
+    panel_height: u32,
+) -> String {
+    let fit = format!(
+        "scale={panel_width}:{panel_height}:force_original_aspect_ratio=decrease:flags=bicubic,\
+         pad={panel_width}:{panel_height}:(ow-iw)/2:(oh-ih)/2:black,setsar=1,format=yuv420p,\
+         setpts=PTS-STARTPTS"
+    );
+    let font_size = (panel_height / 24).max(12);
+    let label = |text: &str, x: &str| {
+        if options.labels {
+            format!(
+                ",drawtext=text='{text}':fontsize={font_size}:fontcolor=white:\
+                 box=1:boxcolor=black@0.5:boxborderw={}:x={x}:y={font_size}",
+                font_size / 3
+            )
+        } else {
+            String::new()
+        }
+    };
+    let margin = font_size.to_string();
+    let right = format!("w-tw-{font_size}");
+    let reference_panel = format!(
+        "[0:v:0]{fit}{}[reference]",
+        label(COMPARISON_REFERENCE_LABEL, &margin)
+    );
+    let encoded_panel = format!(
+        "[1:v:0]{fit}{}[encoded]",
+        label(COMPARISON_ENCODED_LABEL, &right)
+    );
+    let half = panel_width / 2;
+    let combine = match options.layout {
+        ComparisonLayout::SideBySide => "[reference][encoded]hstack=inputs=2[out]".to_string(),
+        ComparisonLayout::TopBottom => "[reference][encoded]vstack=inputs=2[out]".to_string(),
+        ComparisonLayout::SplitWipe => format!(
+            "[encoded]crop={half}:{panel_height}:{half}:0[right];\
+             [reference][right]overlay={half}:0:shortest=1,\
+             drawbox=x={}:y=0:w=2:h=ih:color=white@0.8:t=fill[out]",
+            half - 1
+        ),
+    };
+    format!("{reference_panel};{encoded_panel};{combine}")
+}
+
+/// Even panel size with the reference's aspect ratio, no taller than
+/// [`COMPARISON_MAX_HEIGHT`].
+fn panel_size(width: u32, height: u32) -> (u32, u32) {
+    let panel_height = height.clamp(2, COMPARISON_MAX_HEIGHT) & !1;
+    let scaled_width = u64::from(width) * u64::from(panel_height) / u64::from(height);
+    let panel_width = u32::try_from(scaled_width).unwrap_or(u32::MAX).max(2) & !1;
+    (panel_width, panel_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_probe(width: u32, height: u32) -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("60".to_string()),
+            video_codec: Some("h264".to_string()),
+            width: Some(width),
+            height: Some(height),
+            ..ProbeMetadata::default()
+        }
+    }
+
+    fn options(layout: ComparisonLayout) -> ComparisonClipOptions {
+        ComparisonClipOptions {
+            start_seconds: 12.5,
+            duration_seconds: 10.0,
+            layout,
+            labels: true,
+        }
+    }
+
+    fn filter_complex(plan: &ComparisonClipPlan) -> &str {
+        let index = plan
+            .args
+            .iter()
+            .position(|arg| arg == "-filter_complex")
+            .expect("plan should have a filter graph");
+        &plan.args[index + 1]
+    }
+
+    #[test]
+    fn both_inputs_are_trimmed_to_the_same_section() {
+        let plan = build_comparison_clip_args(
+            "/in/source.mov",
+            "/out/encode.mp4",
+            "/tmp/comparison.mp4",
+            options(ComparisonLayout::SideBySide),
+            &video_probe(1920, 1080),
+            &video_probe(1920, 1080),
+        )
+        .expect("comparison should plan");
+
+        assert_eq!(
+            plan.args[..12].join(" "),
+            "-hide_banner -nostdin -ss 12.500 -t 10.000 -i /in/source.mov \
+             -ss 12.500 -t 10.000"
+        );
+        assert!(
+            plan.args
+                .ends_with(&["-y".to_string(), "/tmp/comparison.mp4".to_string()])
+        );
+        assert!(plan.notes.is_empty());
+    }
+
+    #[test]
+    fn panels_share_the_reference_shape_at_a_capped_height() {
+        let plan = build_comparison_clip_args(
+            "ref.mp4",
+            "enc.mp4",
+            "out.mp4",
+            options(ComparisonLayout::TopBottom),
+            &video_probe(1920, 1080),
+            &video_probe(720, 720),
+        )
+        .expect("comparison should plan");
+        let graph = filter_complex(&plan);
+
+        assert!(graph.contains(
+            "[1:v:0]scale=1280:720:force_original_aspect_ratio=decrease:flags=bicubic,\
+             pad=1280:720:(ow-iw)/2:(oh-ih)/2:black"
+        ));
+        assert!(graph.contains("text='Encoded'"));
+        assert!(graph.ends_with("[reference][encoded]vstack=inputs=2[out]"));
+        assert_eq!(plan.notes.len(), 1);
+        assert!(plan.notes[0].contains("padded"));
+    }
+
+    #[test]
+    fn split_wipe_overlays_the_right_half_of_the_encode() {
+        let mut options = options(ComparisonLayout::SplitWipe);
+        options.labels = false;
+        let plan = build_comparison_clip_args(
+            "ref.mp4",
+            "enc.mp4",
+            "out.mp4",
+            options,
+            &video_probe(640, 360),
+            &video_probe(640, 360),
+        )
+        .expect("comparison should plan");
+        let graph = filter_complex(&plan);
+
+        assert!(graph.contains("[encoded]crop=320:360:320:0[right]"));
+        assert!(graph.contains("[reference][right]overlay=320:0"));
+        assert!(!graph.contains("drawtext"));
+        assert_eq!(plan.notes.len(), 1);
+    }
+
+    #[test]
+    fn layouts_use_snake_case_names() {
+        let layout: ComparisonLayout =
+            serde_json::from_str("\"side_by_side\"").expect("layout should parse");
+
+        assert_eq!(layout, ComparisonLayout::SideBySide);
+        assert!(serde_json::from_str::<ComparisonLayout>("\"diagonal\"").is_err());
+    }
+
+    #[test]
+    fn files_without_video_are_rejected() {
+        let audio = ProbeMetadata::default();
+
+        assert!(
+            build_comparison_clip_args(
+                "ref.mp4",
+                "enc.m4a",
+                "out.mp4",
+                options(ComparisonLayout::SideBySide),
+                &video_probe(640, 360),
+                &audio,
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! Joining several sources into one output.
+//!
+//! Sources that agree on every stream parameter are joined by the concat
+//! demuxer without re-encoding when the task asks for stream copy.
+//! Everything else runs through the concat filter: each source is scaled
+//! and padded to the first one's display size and frame rate, its audio is
+//! resampled to the first audio's rate and channel layout, and silence
+//! stands in for sources without audio. The task's own filters then run on
+//! the joined streams.
+
+use crate::error::ConversionError;
+use crate::filters::has_overlay;
+use crate::media_rules::{container_supports_audio, is_gif_container, is_image_container};
+use crate::segments::has_trim_times;
+use crate::split::splits_output;
+use crate::types::{AudioTrack, ConversionConfig, ProbeMetadata};
+use crate::utils::is_audio_only_container;
+
+/// Frame rates closer than this are treated as equal when deciding whether
+/// sources can be stream copied.
+const FRAME_RATE_TOLERANCE: f64 = 0.01;
+/// Sample rate used when the first audio stream does not report one.
+const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+/// How the sources of a join are combined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConcatMethod {
+    /// Concat demuxer with `-c copy`; lossless and fast.
+    StreamCopy,
+    /// Concat filter over normalized sources, then a regular encode.
+    Reencode,
+}
+
+/// One source of a re-encoding join.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConcatSource {
+    /// Stream index of the joined audio, or `None` when silence stands in.
+    pub audio_stream: Option<u32>,
+    /// Source duration, which sets the length of stand-in silence.
+    pub duration: Option<f64>,
+}
+
+/// Audio format every joined source is resampled to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConcatAudio {
+    pub sample_rate: u32,
+    pub channel_layout: String,
+}
+
+/// How a task joins its sources, and what each is normalized to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConcatPlan {
+    pub method: ConcatMethod,
+    /// Why stream copy was requested but the sources had to be re-encoded.
+    pub copy_mismatch: Option<String>,
+    pub sources: Vec<ConcatSource>,
+    /// Display size of the first source; `None` for audio-only outputs.
+    pub size: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+    /// `None` when no source has audio or the output cannot carry it.
+    pub audio: Option<ConcatAudio>,
+    /// Summed duration of the sources, when every one is known.
+    pub duration: Option<f64>,
+}
+
+/// Plans the join of `probes`, the first of which is the task's own
+/// source.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when fewer than two sources
+/// are given, the output or task settings cannot be combined with a join,
+/// a source lacks the video the output needs, or a source without audio
+/// has no known duration to fill with silence.
+pub fn plan_concat(
+    config: &ConversionConfig,
+    probes: &[ProbeMetadata],
+) -> Result<ConcatPlan, ConversionError> {
+    validate_concat_config(config, probes.len())?;
+    let audio_only = is_audio_only_container(&config.container);
+    if !audio_only
+        && let Some(position) = probes.iter().position(|probe| probe.video_codec.is_none())
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Source {} has no video stream to join",
+            position + 1
+        )));
+    }
+
+    let mismatch = concat_mismatch(probes);
+    let copy_requested = config.processing_mode == "copy";
+    let method = if copy_requested && mismatch.is_none() {
+        ConcatMethod::StreamCopy
+    } else {
+        ConcatMethod::Reencode
+    };
+
+    let audio = container_supports_audio(&config.container)
+        .then(|| probes.iter().find_map(|probe| probe.audio_tracks.first()))
+        .flatten()
+        .map(concat_audio);
+    if audio_only && audio.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "None of the sources has an audio stream to join".to_string(),
+        ));
+    }
+
+    let sources = probes
+        .iter()
+        .enumerate()
+        .map(|(position, probe)| {
+            let source = ConcatSource {
+                audio_stream: probe.audio_tracks.first().map(|track| track.index),
+                duration: probe.duration_seconds(),
+            };
+            if method == ConcatMethod::Reencode
+                && audio.is_some()
+                && source.audio_stream.is_none()
+                && source.duration.is_none()
+            {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Source {} has no audio and an unknown duration, so silence cannot be added",
+                    position + 1
+                )));
+            }
+            Ok(source)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let first = &probes[0];
+    let size = if audio_only {
+        None
+    } else {
+        let (Some(width), Some(height)) = (first.width, first.height) else {
+            return Err(ConversionError::InvalidInput(
+                "The first source has no known video size".to_string(),
+            ));
+        };
+        Some((width, height))
+    };
+
+    Ok(ConcatPlan {
+        method,
+        copy_mismatch: mismatch.filter(|_| copy_requested),
+        duration: sources.iter().map(|source| source.duration).sum(),
+        sources,
+        size,
+        frame_rate: first
+            .frame_rate
+            .filter(|rate| rate.is_finite() && *rate > 0.0),
+        audio,
+    })
+}
+
+fn validate_concat_config(
+    config: &ConversionConfig,
+    source_count: usize,
+) -> Result<(), ConversionError> {
+    let unsupported = |feature: &str| {
+        Err(ConversionError::InvalidInput(format!(
+            "{feature} is not available when joining sources"
+        )))
+    };
+    if source_count < 2 {
+        return Err(ConversionError::InvalidInput(
+            "Joining needs at least two sources".to_string(),
+        ));
+    }
+    if is_gif_container(&config.container) || is_image_container(&config.container) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Joining sources is not available for container '{}'",
+            config.container
+        )));
+    }
+    if has_trim_times(config) {
+        return unsupported("Trimming");
+    }
+    if splits_output(config) {
+        return unsupported("Splitting the output");
+    }
+    if has_overlay(config) {
+        return unsupported("An overlay");
+    }
+    if config
+        .subtitle_burn_path
+        .as_deref()
+        .is_some_and(|path| !path.trim().is_empty())
+        || !config.selected_subtitle_tracks.is_empty()
+    {
+        return unsupported("Keeping or burning subtitles");
+    }
+    Ok(())
+}
+
+/// The first stream parameter that keeps `probes` from being joined by
+/// stream copy, or `None` when every source matches the first.
+#[must_use]
+pub fn concat_mismatch(probes: &[ProbeMetadata]) -> Option<String> {
+    let (first, rest) = probes.split_first()?;
+    rest.iter().enumerate().find_map(|(position, probe)| {
+        stream_difference(first, probe)
+            .map(|difference| format!("source {} differs in {difference}", position + 2))
+    })
+}
+
+fn stream_difference(first: &ProbeMetadata, other: &ProbeMetadata) -> Option<&'static str> {
+    let same_frame_rate = match (first.frame_rate, other.frame_rate) {
+        (Some(first), Some(other)) => (first - other).abs() < FRAME_RATE_TOLERANCE,
+        (first, other) => first.is_none() && other.is_none(),
+    };
+    let rotation = |probe: &ProbeMetadata| probe.rotation.unwrap_or(0.0);
+
+    if first.video_codec != other.video_codec {
+        Some("video codec")
+    } else if (first.width, first.height) != (other.width, other.height) {
+        Some("resolution")
+    } else if !same_frame_rate {
+        Some("frame rate")
+    } else if first.pixel_format != other.pixel_format {
+        Some("pixel format")
+    } else if first.profile != other.profile {
+        Some("video profile")
+    } else if (rotation(first) - rotation(other)).abs() > f64::EPSILON {
+        Some("rotation")
+    } else if first.audio_tracks.len() != other.audio_tracks.len() {
+        Some("number of audio streams")
+    } else {
+        first
+            .audio_tracks
+            .iter()
+            .zip(&other.audio_tracks)
+            .find_map(|(first, other)| audio_difference(first, other))
+    }
+}
+
+fn audio_difference(first: &AudioTrack, other: &AudioTrack) -> Option<&'static str> {
+    if first.codec != other.codec {
+        Some("audio codec")
+    } else if first.channels != other.channels {
+        Some("audio channels")
+    } else if first.sample_rate != other.sample_rate {
+        Some("audio sample rate")
+    } else {
+        None
+    }
+}
+
+fn concat_audio(track: &AudioTrack) -> ConcatAudio {
+    let sample_rate = track
+        .sample_rate
+        .as_deref()
+        .and_then(|rate| rate.trim().parse().ok())
+        .filter(|rate| *rate > 0)
+        .unwrap_or(DEFAULT_SAMPLE_RATE);
+    let channel_layout = track
+        .channel_layout
+        .as_deref()
+        .map(|layout| layout.split('(').next().unwrap_or(layout).trim())
+        .filter(|layout| !layout.is_empty() && !layout.eq_ignore_ascii_case("unknown"))
+        .map_or_else(
+            || channel_layout_for_count(track.channels.trim().parse().unwrap_or(2)),
+            str::to_string,
+        );
+    ConcatAudio {
+        sample_rate,
+        channel_layout,
+    }
+}
+
+fn channel_layout_for_count(channels: u32) -> String {
+    match channels {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        6 => "5.1".to_string(),
+        8 => "7.1".to_string(),
+        count => format!("{count}c"),
+    }
+}
+
+/// Concat demuxer list joining `files` in order.
+#[must_use]
+pub fn concat_list(files: &[String]) -> String {
+    files
+        .iter()
+        .fold("ffconcat version 1.0\n".to_string(), |mut list, file| {
+            list.push_str("file '");
+            list.push_str(&file.replace('\'', "'\\''"));
+            list.push_str("'\n");
+            list
+        })
+}
+
+/// Warning logged when stream copy was requested but the sources differ.
+#[must_use]
+pub fn concat_reencode_warning(plan: &ConcatPlan) -> Option<String> {
+    plan.copy_mismatch.as_ref().map(|mismatch| {
+        format!("Sources cannot be joined by stream copy ({mismatch}); re-encoding them instead")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn concat_config(processing_mode: &str, container: &str) -> ConversionConfig {
+        let mut config = sample_config(container, "libx264");
+        config.processing_mode = processing_mode.to_string();
+        config
+    }
+
+    fn audio_track(index: u32, channels: &str, layout: Option<&str>) -> AudioTrack {
+        AudioTrack {
+            index,
+            codec: "aac".to_string(),
+            channels: channels.to_string(),
+            sample_rate: Some("48000".to_string()),
+            channel_layout: layout.map(str::to_string),
+            ..AudioTrack::default()
+        }
+    }
+
+    fn gopro_chapter(duration: &str) -> ProbeMetadata {
+        ProbeMetadata {
+            media_kind: "video".to_string(),
+            duration: Some(duration.to_string()),
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            frame_rate: Some(59.94),
+            width: Some(1920),
+            height: Some(1080),
+            pixel_format: Some("yuv420p".to_string()),
+            profile: Some("High".to_string()),
+            audio_tracks: vec![audio_track(1, "2", Some("stereo"))],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn concat_list_quotes_every_file() {
+        assert_eq!(
+            concat_list(&["/tmp/a.ts".to_string(), "/tmp/it's.ts".to_string()]),
+            "ffconcat version 1.0\nfile '/tmp/a.ts'\nfile '/tmp/it'\\''s.ts'\n"
+        );
+    }
+
+    #[test]
+    fn matching_sources_are_joined_by_stream_copy() {
+        let probes = [
+            gopro_chapter("530.5"),
+            gopro_chapter("530.5"),
+            gopro_chapter("12.25"),
+        ];
+
+        let plan =
+            plan_concat(&concat_config("copy", "mp4"), &probes).expect("sources should join");
+
+        assert_eq!(plan.method, ConcatMethod::StreamCopy);
+        assert_eq!(plan.copy_mismatch, None);
+        assert_eq!(plan.duration, Some(1073.25));
+        assert_eq!(concat_reencode_warning(&plan), None);
+    }
+
+    #[test]
+    fn differing_sources_are_reencoded_and_the_difference_is_reported() {
+        type ProbeChange = fn(&mut ProbeMetadata);
+        let cases: [(ProbeChange, &str); 9] = [
+            (
+                |probe| probe.video_codec = Some("hevc".to_string()),
+                "video codec",
+            ),
+            (|probe| probe.width = Some(1280), "resolution"),
+            (|probe| probe.frame_rate = Some(29.97), "frame rate"),
+            (
+                |probe| probe.pixel_format = Some("yuv420p10le".to_string()),
+                "pixel format",
+            ),
+            (|probe| probe.rotation = Some(90.0), "rotation"),
+            (
+                |probe| probe.audio_tracks.clear(),
+                "number of audio streams",
+            ),
+            (
+                |probe| probe.audio_tracks[0].codec = "opus".to_string(),
+                "audio codec",
+            ),
+            (
+                |probe| probe.audio_tracks[0].channels = "6".to_string(),
+                "audio channels",
+            ),
+            (
+                |probe| probe.audio_tracks[0].sample_rate = Some("44100".to_string()),
+                "audio sample rate",
+            ),
+        ];
+
+        for (change, difference) in cases {
+            let mut second = gopro_chapter("10");
+            change(&mut second);
+            let probes = [gopro_chapter("10"), second];
+
+            let plan =
+                plan_concat(&concat_config("copy", "mkv"), &probes).expect("sources should join");
+
+            assert_eq!(plan.method, ConcatMethod::Reencode, "{difference}");
+            let expected = format!("source 2 differs in {difference}");
+            assert_eq!(plan.copy_mismatch.as_deref(), Some(expected.as_str()));
+            assert!(
+                concat_reencode_warning(&plan)
+                    .is_some_and(|warning| warning.contains("re-encoding them instead"))
+            );
+        }
+    }
+
+    #[test]
+    fn a_zero_rotation_matches_a_missing_one() {
+        let mut rotated = gopro_chapter("10");
+        rotated.rotation = Some(0.0);
+
+        assert_eq!(concat_mismatch(&[gopro_chapter("10"), rotated]), None);
+    }
+
+    #[test]
+    fn reencode_mode_always_reencodes_without_a_mismatch_warning() {
+        let mut second = gopro_chapter("10");
+        second.width = Some(1280);
+        let probes = [gopro_chapter("10"), second];
+
+        let plan =
+            plan_concat(&concat_config("reencode", "mp4"), &probes).expect("sources should join");
+
+        assert_eq!(plan.method, ConcatMethod::Reencode);
+        assert_eq!(plan.copy_mismatch, None);
+        assert_eq!(plan.size, Some((1920, 1080)));
+        assert_eq!(plan.frame_rate, Some(59.94));
+    }
+
+    #[test]
+    fn audio_follows_the_first_source_that_has_audio() {
+        let mut silent = gopro_chapter("4.5");
+        silent.audio_tracks.clear();
+        let mut surround = gopro_chapter("10");
+        surround.audio_tracks = vec![audio_track(2, "6", Some("5.1(side)"))];
+        let probes = [silent, surround, gopro_chapter("10")];
+
+        let plan =
+            plan_concat(&concat_config("reencode", "mp4"), &probes).expect("sources should join");
+
+        assert_eq!(
+            plan.audio,
+            Some(ConcatAudio {
+                sample_rate: 48_000,
+                channel_layout: "5.1".to_string(),
+            })
+        );
+        assert_eq!(
+            plan.sources,
+            [
+                ConcatSource {
+                    audio_stream: None,
+                    duration: Some(4.5)
+                },
+                ConcatSource {
+                    audio_stream: Some(2),
+                    duration: Some(10.0)
+                },
+                ConcatSource {
+                    audio_stream: Some(1),
+                    duration: Some(10.0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn channel_layouts_fall_back_to_the_channel_count() {
+        assert_eq!(
+            concat_audio(&audio_track(1, "1", None)).channel_layout,
+            "mono"
+        );
+        assert_eq!(
+            concat_audio(&audio_track(1, "2", Some("unknown"))).channel_layout,
+            "stereo"
+        );
+        assert_eq!(
+            concat_audio(&audio_track(1, "3", None)).channel_layout,
+            "3c"
+        );
+    }
+
+    #[test]
+    fn silent_sources_without_a_duration_cannot_be_padded() {
+        let mut silent = gopro_chapter("10");
+        silent.audio_tracks.clear();
+        silent.duration = None;
+
+        let error = plan_concat(
+            &concat_config("reencode", "mp4"),
+            &[gopro_chapter("10"), silent],
+        )
+        .expect_err("silence needs a duration");
+
+        assert!(error.to_string().contains("Source 2 has no audio"));
+    }
+
+    #[test]
+    fn all_silent_sources_join_without_audio() {
+        let mut first = gopro_chapter("10");
+        first.audio_tracks.clear();
+        let mut second = gopro_chapter("10");
+        second.audio_tracks.clear();
+        second.duration = None;
+
+        let plan = plan_concat(&concat_config("reencode", "mp4"), &[first, second])
+            .expect("silent sources should join");
+
+        assert_eq!(plan.audio, None);
+        assert_eq!(plan.duration, None);
+    }
+
+    #[test]
+    fn joins_reject_unsupported_outputs_and_sources() {
+        let two = [gopro_chapter("10"), gopro_chapter("10")];
+        let mut trimmed = concat_config("reencode", "mp4");
+        trimmed.start_time = Some("5".to_string());
+        let mut burned = concat_config("reencode", "mp4");
+        burned.subtitle_burn_path = Some("/tmp/sub.srt".to_string());
+        let mut audio_source = gopro_chapter("10");
+        audio_source.video_codec = None;
+
+        let cases = [
+            (
+                plan_concat(&concat_config("reencode", "mp4"), &two[..1]),
+                "at least two sources",
+            ),
+            (
+                plan_concat(&concat_config("reencode", "gif"), &two),
+                "not available for container 'gif'",
+            ),
+            (plan_concat(&trimmed, &two), "Trimming is not available"),
+            (plan_concat(&burned, &two), "subtitles is not available"),
+            (
+                plan_concat(
+                    &concat_config("reencode", "mp4"),
+                    &[gopro_chapter("10"), audio_source.clone()],
+                ),
+                "Source 2 has no video stream",
+            ),
+        ];
+        for (result, expected) in cases {
+            let error = result.expect_err("join should be rejected").to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+
+        let plan = plan_concat(
+            &concat_config("reencode", "mp3"),
+            &[audio_source, gopro_chapter("10")],
+        )
+        .expect("audio outputs accept sources without video");
+        assert_eq!(plan.size, None);
+    }
+}
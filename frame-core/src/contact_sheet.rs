@@ -0,0 +1,279 @@
+//! Contact sheets: one image holding a grid of frames sampled evenly across
+//! a source, each labelled with its timestamp.
+//!
+//! Frames are picked by time rather than by frame number, so variable frame
+//! rate sources are sampled evenly as well. Long sources only decode
+//! keyframes, which keeps the pass quick at the cost of landing on the
+//! keyframe after each sample point.
+
+use std::path::Path;
+
+use crate::error::ConversionError;
+use crate::thumbnail::frame_encoder_args;
+use crate::types::ProbeMetadata;
+
+/// Largest number of columns or rows of a sheet.
+pub const MAX_CONTACT_SHEET_SIDE: u32 = 12;
+
+/// Narrowest cell a sheet can have.
+pub const MIN_CONTACT_SHEET_CELL_WIDTH: u32 = 64;
+
+/// Sample spacing from which only keyframes are decoded.
+pub const CONTACT_SHEET_KEYFRAME_STEP_SECONDS: f64 = 10.0;
+
+/// Encoder quality of JPEG and WebP sheets.
+const CONTACT_SHEET_QUALITY: u32 = 90;
+
+/// Layout of a contact sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContactSheetOptions {
+    pub columns: u32,
+    pub rows: u32,
+    pub cell_width: u32,
+    /// Whether the `FFmpeg` build has the `drawtext` filter for the labels.
+    pub labels: bool,
+}
+
+/// `FFmpeg` arguments for one contact sheet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContactSheetPlan {
+    pub args: Vec<String>,
+    /// Frames the sheet is filled with; progress counts them as they are
+    /// logged by `showinfo`.
+    pub frames: u32,
+    /// Adjustments the caller should be told about.
+    pub notes: Vec<String>,
+}
+
+/// Builds the arguments that write a `columns` by `rows` sheet of `input`
+/// to `output`, encoded after the output's extension.
+///
+/// Cell `n` takes the first frame at or after the middle of the `n`th of
+/// equal spans of the source. Sources with fewer frames than cells use every
+/// frame and leave the remaining cells blank, which is reported in the
+/// plan's notes.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the source has no video or
+/// unknown duration, the layout is out of range, or the output is not a
+/// JPEG, PNG or WebP file.
+pub fn build_contact_sheet_args(
+    input: &str,
+    output: &str,
+    options: ContactSheetOptions,
+    probe: &ProbeMetadata,
+) -> Result<ContactSheetPlan, ConversionError> {
+    if probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "The source has no video stream to take frames from".to_string(),
+        ));
+    }
+    let side = 1..=MAX_CONTACT_SHEET_SIDE;
+    if !side.contains(&options.columns) || !side.contains(&options.rows) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Contact sheet columns and rows must be between 1 and {MAX_CONTACT_SHEET_SIDE}"
+        )));
+    }
+    if options.cell_width < MIN_CONTACT_SHEET_CELL_WIDTH {
+        return Err(ConversionError::InvalidInput(format!(
+            "Contact sheet cells must be at least {MIN_CONTACT_SHEET_CELL_WIDTH} pixels wide"
+        )));
+    }
+    let format = Path::new(output)
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let encoder_args = frame_encoder_args(&format, CONTACT_SHEET_QUALITY)?;
+    let duration = probe.duration_seconds().ok_or_else(|| {
+        ConversionError::InvalidInput(
+            "The source duration is unknown, so frames cannot be spaced across it".to_string(),
+        )
+    })?;
+
+    let cells = options.columns * options.rows;
+    let mut notes = Vec::new();
+    let mut filters = vec!["setpts=PTS-STARTPTS".to_string()];
+    let short_source = probe
+        .frame_count
+        .and_then(|count| u32::try_from(count).ok())
+        .filter(|count| *count < cells);
+    let step = duration / f64::from(cells);
+    let keyframes_only = short_source.is_none() && step >= CONTACT_SHEET_KEYFRAME_STEP_SECONDS;
+    if let Some(count) = short_source {
+        notes.push(format!(
+            "The source has only {count} frames; the remaining cells are left blank"
+        ));
+    } else {
+        filters.push(format!(
+            "select='gte(t\\,{:.3}+{step:.3}*selected_n)'",
+            step / 2.0
+        ));
+    }
+    filters.push("showinfo".to_string());
+    filters.push(format!("scale={}:-2", options.cell_width));
+    if options.labels {
+        filters.extend(label_filters(options.cell_width));
+    } else {
+        notes.push(
+            "This FFmpeg build cannot draw text; the cells are not labelled with their timestamps"
+                .to_string(),
+        );
+    }
+    filters.push(format!(
+        "tile={}x{}:padding=4:margin=4",
+        options.columns, options.rows
+    ));
+
+    let mut args = vec!["-hide_banner".to_string(), "-nostdin".to_string()];
+    if keyframes_only {
+        args.extend(["-skip_frame".to_string(), "nokey".to_string()]);
+    }
+    args.extend([
+        "-i".to_string(),
+        input.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+        "-vf".to_string(),
+        filters.join(","),
+    ]);
+    args.extend(encoder_args);
+    args.extend([
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-update".to_string(),
+        "1".to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-f".to_string(),
+        "image2".to_string(),
+        "-y".to_string(),
+        output.to_string(),
+    ]);
+
+    Ok(ContactSheetPlan {
+        args,
+        frames: short_source.unwrap_or(cells),
+        notes,
+    })
+}
+
+/// Adds a strip under each cell and writes the frame's timestamp into it.
+fn label_filters(cell_width: u32) -> [String; 2] {
+    let font_size = (cell_width / 16).max(12);
+    let strip = font_size * 2;
+    [
+        format!("pad=iw:ih+{strip}:0:0:black"),
+        format!(
+            "drawtext=text='%{{pts\\:hms}}':fontsize={font_size}:fontcolor=white:\
+             x=(w-tw)/2:y=h-{strip}+({strip}-th)/2"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> ContactSheetOptions {
+        ContactSheetOptions {
+            columns: 4,
+            rows: 4,
+            cell_width: 320,
+            labels: true,
+        }
+    }
+
+    fn video_probe(duration: &str, frame_count: Option<u64>) -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some(duration.to_string()),
+            video_codec: Some("h264".to_string()),
+            frame_count,
+            ..ProbeMetadata::default()
+        }
+    }
+
+    fn video_filter(plan: &ContactSheetPlan) -> &str {
+        let index = plan
+            .args
+            .iter()
+            .position(|arg| arg == "-vf")
+            .expect("sheet should have a filter");
+        &plan.args[index + 1]
+    }
+
+    #[test]
+    fn long_sources_are_sampled_by_time_from_keyframes() {
+        let plan = build_contact_sheet_args(
+            "/in/drive/clip.mov",
+            "/out/clip.jpg",
+            options(),
+            &video_probe("1600.0", Some(40_000)),
+        )
+        .expect("sheet args should build");
+
+        assert_eq!(plan.args[2..4], ["-skip_frame", "nokey"]);
+        assert_eq!(
+            video_filter(&plan),
+            "setpts=PTS-STARTPTS,select='gte(t\\,50.000+100.000*selected_n)',showinfo,\
+             scale=320:-2,pad=iw:ih+40:0:0:black,\
+             drawtext=text='%{pts\\:hms}':fontsize=20:fontcolor=white:x=(w-tw)/2:y=h-40+(40-th)/2,\
+             tile=4x4:padding=4:margin=4"
+        );
+        assert!(plan.args.windows(2).any(|pair| pair == ["-c:v", "mjpeg"]));
+        assert_eq!(plan.frames, 16);
+        assert!(plan.notes.is_empty());
+    }
+
+    #[test]
+    fn sources_with_fewer_frames_than_cells_use_every_frame() {
+        let mut unlabelled = options();
+        unlabelled.labels = false;
+
+        let plan = build_contact_sheet_args(
+            "/in/blip.mp4",
+            "/out/blip.png",
+            unlabelled,
+            &video_probe("0.4", Some(10)),
+        )
+        .expect("short sheets should build");
+
+        assert_eq!(plan.args[2], "-i");
+        assert!(!video_filter(&plan).contains("select"));
+        assert!(!video_filter(&plan).contains("drawtext"));
+        assert_eq!(plan.frames, 10);
+        assert_eq!(plan.notes.len(), 2);
+        assert!(plan.notes[0].contains("only 10 frames"));
+    }
+
+    #[test]
+    fn invalid_sheets_are_rejected() {
+        let mut too_wide = options();
+        too_wide.columns = 13;
+        let mut narrow = options();
+        narrow.cell_width = 32;
+        let probe = video_probe("60.0", None);
+        let cases = [
+            (too_wide, "/out/a.jpg", &probe, "between 1 and 12"),
+            (narrow, "/out/a.jpg", &probe, "at least 64 pixels"),
+            (options(), "/out/a.gif", &probe, "not 'gif'"),
+            (
+                options(),
+                "/out/a.jpg",
+                &ProbeMetadata {
+                    duration: None,
+                    ..probe.clone()
+                },
+                "duration is unknown",
+            ),
+        ];
+
+        for (options, output, probe, expected) in cases {
+            let error = build_contact_sheet_args("/in.mkv", output, options, probe)
+                .expect_err("sheet should be rejected")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Retargets a conversion config at another output container.
+
+use crate::args::{
+    GIF_DITHER_MODES, GIF_PALETTE_SIZES, JPEG_HUFFMAN_MODES, JPEG_QUALITIES, LOSSLESS_AUDIO_CODECS,
+    MAX_PNG_COMPRESSION, MAX_WEBP_COMPRESSION, MAX_WEBP_QUALITY, PNG_PREDICTION_MODES,
+    TIFF_COMPRESSION_MODES, WEBP_PRESETS, has_custom_pixel_format,
+};
+use crate::codec::{MAX_AVIF_CRF, audio_codec_supports_vbr};
+use crate::image_sequence::supports_sequence_output;
+use crate::media_rules::{
+    container_supports_audio, container_supports_subtitles, default_audio_codec_for_container,
+    default_video_codec_for_container, is_audio_codec_allowed, is_audio_only_container,
+    is_gif_container, is_image_container, is_video_codec_allowed, is_video_only_container,
+    is_video_pixel_format_allowed,
+};
+use crate::types::{AudioFiltersConfig, ConversionConfig, DeinterlaceMode, VideoFiltersConfig};
+
+/// Copy of `config` writing to `container`, with every setting the
+/// container cannot take dropped or replaced by its default, following the
+/// rules [`crate::args::validate_conversion_settings`] checks.
+///
+/// Audio-only containers lose the video settings, containers without an
+/// audio stream lose the audio ones, and containers without subtitles lose
+/// the subtitle ones. Encoders the container does not accept are switched
+/// to its preferred one from [`crate::media_rules`]. Stream copy into GIF
+/// and still image containers becomes a re-encode. Everything the
+/// container can use is kept.
+#[must_use]
+pub fn sanitize_config_for_container(
+    config: &ConversionConfig,
+    container: &str,
+) -> ConversionConfig {
+    let mut config = config.clone();
+    let container = container.trim().to_ascii_lowercase();
+    let is_audio_only = is_audio_only_container(&container);
+    let is_video_only = is_video_only_container(&container);
+    let is_image = is_image_container(&container);
+    let is_gif = is_gif_container(&container);
+
+    if (is_video_only || is_image) && config.processing_mode == "copy" {
+        config.processing_mode = "reencode".to_string();
+    }
+    if is_audio_only {
+        drop_video_settings(&mut config);
+    }
+    if !container_supports_audio(&container) {
+        drop_audio_settings(&mut config);
+    }
+    if !container_supports_subtitles(&container) {
+        drop_subtitle_settings(&mut config);
+    }
+    if (is_audio_only || is_video_only) && has_custom_pixel_format(&config) {
+        config.pixel_format = "auto".to_string();
+    }
+    if is_image {
+        config.video_filters.deinterlace = DeinterlaceMode::Off;
+    }
+    if is_gif {
+        config.overlay = None;
+    }
+    if is_image || is_gif {
+        config.split = None;
+        config.split_by_chapters = false;
+    }
+    if is_audio_only || is_image || is_gif {
+        config.parallel_chunks = None;
+    }
+    if !supports_sequence_output(&container) {
+        config.image_sequence_output = false;
+    }
+
+    config.container = container;
+    if config.processing_mode != "copy" {
+        if !is_audio_only {
+            fit_video_encoder(&mut config);
+        }
+        if container_supports_audio(&config.container) {
+            fit_audio_encoder(&mut config);
+        }
+    }
+    if is_image {
+        fit_image_settings(&mut config);
+    }
+    if is_gif {
+        fit_gif_settings(&mut config);
+    }
+    config
+}
+
+fn drop_video_settings(config: &mut ConversionConfig) {
+    config.video_filters = VideoFiltersConfig::default();
+    config.crop = None;
+    config.overlay = None;
+    config.rotation = "0".to_string();
+    config.flip_horizontal = false;
+    config.flip_vertical = false;
+    config.resolution = "original".to_string();
+    config.custom_width = None;
+    config.custom_height = None;
+    config.fps = "original".to_string();
+    config.pixel_format = "auto".to_string();
+    config.hw_decode = false;
+    config.hw_device = None;
+    config.nvenc_spatial_aq = false;
+    config.nvenc_temporal_aq = false;
+    config.videotoolbox_allow_sw = false;
+}
+
+fn drop_audio_settings(config: &mut ConversionConfig) {
+    config.selected_audio_tracks.clear();
+    config.audio_filters = AudioFiltersConfig::default();
+    config.audio_normalize = false;
+    config.audio_volume = 100.0;
+}
+
+fn drop_subtitle_settings(config: &mut ConversionConfig) {
+    config.selected_subtitle_tracks.clear();
+    config.subtitle_burn_path = None;
+    config.subtitle_font_name = None;
+    config.subtitle_font_size = None;
+    config.subtitle_font_color = None;
+    config.subtitle_outline_color = None;
+    config.subtitle_position = None;
+}
+
+/// Switches an encoder the container rejects to its preferred one, along
+/// with the options tied to the old encoder.
+fn fit_video_encoder(config: &mut ConversionConfig) {
+    if !is_video_codec_allowed(&config.container, &config.video_codec) {
+        config.video_codec = default_video_codec_for_container(&config.container).to_string();
+        config.hw_device = None;
+        config.nvenc_spatial_aq = false;
+        config.nvenc_temporal_aq = false;
+        config.videotoolbox_allow_sw = false;
+    }
+    if has_custom_pixel_format(config)
+        && !is_video_pixel_format_allowed(
+            &config.container,
+            &config.video_codec,
+            &config.pixel_format,
+        )
+    {
+        config.pixel_format = "auto".to_string();
+    }
+    let has_valid_bitrate = config
+        .video_bitrate
+        .parse::<f64>()
+        .is_ok_and(|bitrate| bitrate > 0.0);
+    if !is_video_only_container(&config.container)
+        && config.video_bitrate_mode == "bitrate"
+        && !has_valid_bitrate
+    {
+        config.video_bitrate_mode = "crf".to_string();
+    }
+}
+
+fn fit_audio_encoder(config: &mut ConversionConfig) {
+    if !is_audio_codec_allowed(&config.container, &config.audio_codec) {
+        config.audio_codec = default_audio_codec_for_container(&config.container).to_string();
+    }
+    let is_lossless = LOSSLESS_AUDIO_CODECS.contains(&config.audio_codec.as_str());
+    if config.audio_bitrate_mode == "vbr"
+        && (is_lossless || !audio_codec_supports_vbr(&config.audio_codec))
+    {
+        config.audio_bitrate_mode = "bitrate".to_string();
+    }
+}
+
+/// Brings the still image options the container's encoder reads into
+/// range; the others are left for a later switch back.
+fn fit_image_settings(config: &mut ConversionConfig) {
+    match config.video_codec.as_str() {
+        "mjpeg" => {
+            config.image_jpeg_quality = config
+                .image_jpeg_quality
+                .clamp(*JPEG_QUALITIES.start(), *JPEG_QUALITIES.end());
+            reset_unknown(
+                &mut config.image_jpeg_huffman,
+                &JPEG_HUFFMAN_MODES,
+                "optimal",
+            );
+        }
+        "libwebp" => {
+            config.image_webp_quality = config.image_webp_quality.min(MAX_WEBP_QUALITY);
+            config.image_webp_compression = config.image_webp_compression.min(MAX_WEBP_COMPRESSION);
+            reset_unknown(&mut config.image_webp_preset, &WEBP_PRESETS, "default");
+        }
+        "png" => {
+            config.image_png_compression = config.image_png_compression.min(MAX_PNG_COMPRESSION);
+            reset_unknown(
+                &mut config.image_png_prediction,
+                &PNG_PREDICTION_MODES,
+                "paeth",
+            );
+        }
+        "libaom-av1" => config.image_avif_crf = config.image_avif_crf.min(MAX_AVIF_CRF),
+        "tiff" => reset_unknown(
+            &mut config.image_tiff_compression,
+            &TIFF_COMPRESSION_MODES,
+            "packbits",
+        ),
+        _ => {}
+    }
+}
+
+fn fit_gif_settings(config: &mut ConversionConfig) {
+    config.gif_colors = config
+        .gif_colors
+        .clamp(*GIF_PALETTE_SIZES.start(), *GIF_PALETTE_SIZES.end());
+    reset_unknown(&mut config.gif_dither, &GIF_DITHER_MODES, "sierra2_4a");
+}
+
+fn reset_unknown(value: &mut String, known: &[&str], default: &str) {
+    if !known.contains(&value.as_str()) {
+        *value = default.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::validate_conversion_settings;
+    use crate::media_rules::all_containers;
+    use crate::types::{CropConfig, OutputSplit, OverlayConfig, sample_config};
+
+    fn base_config(container: &str, video_codec: &str, audio_codec: &str) -> ConversionConfig {
+        let mut config = sample_config(container, video_codec);
+        config.audio_codec = audio_codec.to_string();
+        config.audio_bitrate = "192".to_string();
+        config
+    }
+
+    /// A 10-bit HEVC MP4 using every video, audio and subtitle option.
+    fn full_video_config() -> ConversionConfig {
+        let mut config = base_config("mp4", "libx265", "mp3");
+        config.pixel_format = "yuv422p10le".to_string();
+        config.video_bitrate_mode = "bitrate".to_string();
+        config.audio_bitrate_mode = "vbr".to_string();
+        config.audio_volume = 150.0;
+        config.audio_normalize = true;
+        config.audio_filters.bass.enabled = true;
+        config.video_filters.grayscale = true;
+        config.video_filters.deinterlace = DeinterlaceMode::On;
+        config.selected_audio_tracks = vec![1];
+        config.selected_subtitle_tracks = vec![2];
+        config.subtitle_burn_path = Some("/media/subs.srt".to_string());
+        config.subtitle_font_name = Some("Inter".to_string());
+        config.rotation = "90".to_string();
+        config.flip_horizontal = true;
+        config.resolution = "custom".to_string();
+        config.custom_width = Some("1280".to_string());
+        config.custom_height = Some("-1".to_string());
+        config.fps = "30".to_string();
+        config.crop = Some(CropConfig {
+            enabled: true,
+            width: 640.0,
+            height: 360.0,
+            ..CropConfig::default()
+        });
+        config.overlay = Some(OverlayConfig {
+            enabled: true,
+            path: "/media/logo.png".to_string(),
+            width: 0.2,
+            opacity: 1.0,
+            anchor: "top-left".to_string(),
+            ..OverlayConfig::default()
+        });
+        config.split = Some(OutputSplit::Duration { seconds: 600.0 });
+        config
+    }
+
+    fn hardware_config() -> ConversionConfig {
+        let mut config = base_config("mov", "h264_nvenc", "pcm_s24le");
+        config.nvenc_spatial_aq = true;
+        config.nvenc_temporal_aq = true;
+        config.hw_decode = true;
+        config.hw_device = Some("0".to_string());
+        config
+    }
+
+    fn chunked_config() -> ConversionConfig {
+        let mut config = base_config("mkv", "libsvtav1", "libopus");
+        config.parallel_chunks = Some(4);
+        config
+    }
+
+    fn stream_copy_config() -> ConversionConfig {
+        let mut config = base_config("mkv", "libx264", "aac");
+        config.processing_mode = "copy".to_string();
+        config.selected_audio_tracks = vec![1, 2];
+        config.selected_subtitle_tracks = vec![3];
+        config
+    }
+
+    fn lossless_audio_config() -> ConversionConfig {
+        let mut config = base_config("flac", "libx264", "flac");
+        config.selected_audio_tracks = vec![1];
+        config.audio_filters.limiter.enabled = true;
+        config
+    }
+
+    fn gif_config() -> ConversionConfig {
+        let mut config = base_config("gif", "gif", "aac");
+        config.gif_colors = 64;
+        config.gif_dither = "bayer".to_string();
+        config.fps = "15".to_string();
+        config.video_bitrate = String::new();
+        config.video_bitrate_mode = "bitrate".to_string();
+        config
+    }
+
+    fn jpeg_sequence_config() -> ConversionConfig {
+        let mut config = base_config("jpg", "mjpeg", "aac");
+        config.image_sequence_output = true;
+        config.image_jpeg_quality = 92;
+        config.image_png_compression = 42;
+        config
+    }
+
+    fn source_configs() -> Vec<(&'static str, ConversionConfig)> {
+        vec![
+            ("full video", full_video_config()),
+            ("hardware", hardware_config()),
+            ("chunked", chunked_config()),
+            ("stream copy", stream_copy_config()),
+            ("lossless audio", lossless_audio_config()),
+            ("gif", gif_config()),
+            ("jpeg sequence", jpeg_sequence_config()),
+        ]
+    }
+
+    fn json(config: &ConversionConfig) -> serde_json::Value {
+        serde_json::to_value(config).expect("config should serialize")
+    }
+
+    #[test]
+    fn source_configs_are_valid_for_their_own_container() {
+        for (name, config) in source_configs() {
+            assert!(
+                validate_conversion_settings(&config).is_ok(),
+                "{name}: {:?}",
+                validate_conversion_settings(&config)
+            );
+        }
+    }
+
+    #[test]
+    fn sanitized_configs_pass_validation_for_every_container() {
+        for (name, config) in source_configs() {
+            for container in all_containers() {
+                let sanitized = sanitize_config_for_container(&config, container);
+                assert_eq!(&sanitized.container, container);
+                assert!(
+                    validate_conversion_settings(&sanitized).is_ok(),
+                    "{name} -> {container}: {:?}",
+                    validate_conversion_settings(&sanitized)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sanitizing_changes_nothing_validation_already_accepts() {
+        for (name, config) in source_configs() {
+            for container in all_containers() {
+                let mut retargeted = config.clone();
+                retargeted.container.clone_from(container);
+                if validate_conversion_settings(&retargeted).is_err() {
+                    continue;
+                }
+                let sanitized = sanitize_config_for_container(&config, container);
+                for field in ["videoCodec", "audioCodec", "pixelFormat", "processingMode"] {
+                    assert_eq!(
+                        json(&sanitized)[field],
+                        json(&retargeted)[field],
+                        "{name} -> {container}: {field}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sanitizing_twice_is_the_same_as_once() {
+        for (name, config) in source_configs() {
+            for container in all_containers() {
+                let once = sanitize_config_for_container(&config, container);
+                let twice = sanitize_config_for_container(&once, container);
+                assert_eq!(json(&once), json(&twice), "{name} -> {container}");
+            }
+        }
+    }
+
+    #[test]
+    fn compatible_containers_keep_every_setting() {
+        let config = full_video_config();
+
+        let sanitized = sanitize_config_for_container(&config, "MKV");
+
+        let mut expected = json(&config);
+        expected["container"] = "mkv".into();
+        assert_eq!(json(&sanitized), expected);
+    }
+
+    #[test]
+    fn audio_only_containers_drop_video_and_subtitle_settings() {
+        let sanitized = sanitize_config_for_container(&full_video_config(), "flac");
+
+        assert_eq!(sanitized.audio_codec, "flac");
+        assert_eq!(sanitized.audio_bitrate_mode, "bitrate");
+        assert_eq!(sanitized.pixel_format, "auto");
+        assert_eq!(sanitized.resolution, "original");
+        assert_eq!(sanitized.fps, "original");
+        assert_eq!(sanitized.rotation, "0");
+        assert!(!sanitized.flip_horizontal);
+        assert!(sanitized.crop.is_none() && sanitized.overlay.is_none());
+        assert_eq!(sanitized.video_filters, VideoFiltersConfig::default());
+        assert!(sanitized.selected_subtitle_tracks.is_empty());
+        assert!(sanitized.subtitle_burn_path.is_none() && sanitized.subtitle_font_name.is_none());
+        assert_eq!(sanitized.selected_audio_tracks, [1]);
+        assert!(sanitized.audio_normalize && sanitized.audio_filters.bass.enabled);
+        assert!(sanitized.split.is_some());
+    }
+
+    #[test]
+    fn incompatible_encoders_switch_to_the_container_defaults() {
+        let webm = sanitize_config_for_container(&full_video_config(), "webm");
+        assert_eq!(webm.video_codec, "vp9");
+        assert_eq!(webm.audio_codec, "libopus");
+        assert_eq!(webm.audio_bitrate_mode, "bitrate");
+        assert_eq!(webm.pixel_format, "auto");
+        assert_eq!(webm.selected_subtitle_tracks, [2]);
+
+        let mp4 = sanitize_config_for_container(&hardware_config(), "mp4");
+        assert_eq!(mp4.video_codec, "h264_nvenc");
+        assert_eq!(mp4.audio_codec, "aac");
+        assert!(mp4.nvenc_spatial_aq && mp4.hw_device.is_some());
+
+        let webm = sanitize_config_for_container(&hardware_config(), "webm");
+        assert_eq!(webm.video_codec, "vp9");
+        assert!(!webm.nvenc_spatial_aq && !webm.nvenc_temporal_aq);
+        assert!(webm.hw_device.is_none());
+        assert!(webm.hw_decode);
+    }
+
+    #[test]
+    fn gif_and_image_outputs_drop_audio_subtitles_and_stream_copy() {
+        let gif = sanitize_config_for_container(&stream_copy_config(), "gif");
+        assert_eq!(gif.processing_mode, "reencode");
+        assert_eq!(gif.video_codec, "gif");
+        assert!(gif.selected_audio_tracks.is_empty());
+        assert!(gif.selected_subtitle_tracks.is_empty());
+
+        let png = sanitize_config_for_container(&full_video_config(), "png");
+        assert_eq!(png.video_codec, "png");
+        assert_eq!(png.video_filters.deinterlace, DeinterlaceMode::Off);
+        assert!(png.video_filters.grayscale);
+        assert!(png.split.is_none());
+        assert!(!png.audio_normalize);
+        assert!(png.subtitle_burn_path.is_none());
+        assert!(png.crop.is_some());
+
+        let jpg = sanitize_config_for_container(&jpeg_sequence_config(), "tiff");
+        assert!(jpg.image_sequence_output);
+        let avif = sanitize_config_for_container(&jpeg_sequence_config(), "avif");
+        assert!(!avif.image_sequence_output);
+        assert_eq!(avif.video_codec, "libaom-av1");
+    }
+
+    #[test]
+    fn image_and_gif_options_are_brought_into_range() {
+        let mut config = jpeg_sequence_config();
+        config.image_jpeg_quality = 0;
+        config.image_jpeg_huffman = "fast".to_string();
+        config.gif_colors = 1;
+        config.gif_dither = "random".to_string();
+
+        let jpg = sanitize_config_for_container(&config, "jpg");
+        assert_eq!(jpg.image_jpeg_quality, 1);
+        assert_eq!(jpg.image_jpeg_huffman, "optimal");
+        let png = sanitize_config_for_container(&config, "png");
+        assert_eq!(png.image_png_compression, MAX_PNG_COMPRESSION);
+        let gif = sanitize_config_for_container(&config, "gif");
+        assert_eq!(gif.gif_colors, 2);
+        assert_eq!(gif.gif_dither, "sierra2_4a");
+    }
+
+    #[test]
+    fn video_only_bitrates_fall_back_to_crf_for_video_containers() {
+        let mp4 = sanitize_config_for_container(&gif_config(), "mp4");
+
+        assert_eq!(mp4.video_codec, "libx264");
+        assert_eq!(mp4.video_bitrate_mode, "crf");
+        assert_eq!(mp4.gif_colors, 64);
+    }
+}
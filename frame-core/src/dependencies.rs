@@ -0,0 +1,178 @@
+//! Dependency graph rules for chained conversion tasks.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::error::ConversionError;
+use crate::types::ConversionTask;
+
+/// Input path placeholder replaced with the dependency's output path once the
+/// parent task completes.
+pub const DEPENDENCY_OUTPUT_PLACEHOLDER: &str = "{dependency-output}";
+
+/// A single `task_id -> depends_on` edge exposed for queue visualisation.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDependencyEdge {
+    pub task_id: String,
+    pub depends_on: String,
+}
+
+/// Rejects batches with unknown, self-referencing, or circular dependencies
+/// and placeholder inputs on tasks without a dependency.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] describing the first invalid
+/// dependency found.
+pub fn validate_task_dependencies(tasks: &[ConversionTask]) -> Result<(), ConversionError> {
+    let parents = tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task.depends_on.as_deref()))
+        .collect::<HashMap<_, _>>();
+
+    for task in tasks {
+        let Some(parent_id) = task.depends_on.as_deref() else {
+            if task.file_path == DEPENDENCY_OUTPUT_PLACEHOLDER {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Task {} uses the dependency output as input but has no dependency",
+                    task.id
+                )));
+            }
+            continue;
+        };
+
+        if !parents.contains_key(parent_id) {
+            return Err(ConversionError::InvalidInput(format!(
+                "Task {} depends on unknown task {parent_id}",
+                task.id
+            )));
+        }
+
+        let mut visited = HashSet::from([task.id.as_str()]);
+        let mut current = Some(parent_id);
+        while let Some(id) = current {
+            if !visited.insert(id) {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Task {} has a circular dependency",
+                    task.id
+                )));
+            }
+            current = parents.get(id).copied().flatten();
+        }
+    }
+
+    Ok(())
+}
+
+/// Dependency edges of `tasks` in queue order.
+#[must_use]
+pub fn task_dependency_edges(tasks: &[ConversionTask]) -> Vec<TaskDependencyEdge> {
+    tasks
+        .iter()
+        .filter_map(|task| {
+            task.depends_on
+                .as_ref()
+                .map(|depends_on| TaskDependencyEdge {
+                    task_id: task.id.clone(),
+                    depends_on: depends_on.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Substitutes the parent's output path into a dependent task's input.
+pub fn apply_dependency_output(task: &mut ConversionTask, dependency_output: &str) {
+    if task.file_path == DEPENDENCY_OUTPUT_PLACEHOLDER {
+        task.file_path = dependency_output.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PostAction, sample_config};
+
+    fn task(id: &str, depends_on: Option<&str>) -> ConversionTask {
+        ConversionTask {
+            id: id.to_string(),
+            file_path: if depends_on.is_some() {
+                DEPENDENCY_OUTPUT_PLACEHOLDER.to_string()
+            } else {
+                format!("/tmp/{id}.mov")
+            },
+            output_directory: "/tmp".to_string(),
+            output_name: None,
+            create_output_directory: false,
+            relative_subpath: None,
+            scratch_directory: None,
+            output_template: None,
+            preserve_file_times: false,
+            post_action: PostAction::Keep,
+            config: sample_config("mp4", "libx264"),
+            concat_inputs: Vec::new(),
+            depends_on: depends_on.map(str::to_string),
+            preprobed: None,
+        }
+    }
+
+    #[test]
+    fn validate_task_dependencies_accepts_chains() {
+        let tasks = [
+            task("remux", None),
+            task("encode", Some("remux")),
+            task("thumbnail", Some("encode")),
+        ];
+
+        assert!(validate_task_dependencies(&tasks).is_ok());
+    }
+
+    #[test]
+    fn validate_task_dependencies_rejects_cycles() {
+        let tasks = [task("a", Some("b")), task("b", Some("a"))];
+
+        let error = validate_task_dependencies(&tasks).expect_err("cycle should be rejected");
+
+        assert!(error.to_string().contains("circular dependency"));
+    }
+
+    #[test]
+    fn validate_task_dependencies_rejects_self_and_unknown_parents() {
+        assert!(validate_task_dependencies(&[task("a", Some("a"))]).is_err());
+        assert!(validate_task_dependencies(&[task("a", Some("missing"))]).is_err());
+    }
+
+    #[test]
+    fn validate_task_dependencies_rejects_placeholder_without_parent() {
+        let mut orphan = task("a", None);
+        orphan.file_path = DEPENDENCY_OUTPUT_PLACEHOLDER.to_string();
+
+        assert!(validate_task_dependencies(&[orphan]).is_err());
+    }
+
+    #[test]
+    fn task_dependency_edges_lists_parent_links() {
+        let tasks = [task("remux", None), task("encode", Some("remux"))];
+
+        assert_eq!(
+            task_dependency_edges(&tasks),
+            vec![TaskDependencyEdge {
+                task_id: "encode".to_string(),
+                depends_on: "remux".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn apply_dependency_output_replaces_only_the_placeholder() {
+        let mut dependent = task("encode", Some("remux"));
+        let mut independent = task("other", None);
+
+        apply_dependency_output(&mut dependent, "/tmp/remux.mkv");
+        apply_dependency_output(&mut independent, "/tmp/remux.mkv");
+
+        assert_eq!(dependent.file_path, "/tmp/remux.mkv");
+        assert_eq!(independent.file_path, "/tmp/other.mov");
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fmt, time::Duration};
+
 use serde::Serialize;
 use thiserror::Error;
 
@@ -13,12 +15,115 @@ pub enum ConversionError {
     Channel(String),
     #[error("Probe failed: {0}")]
     Probe(String),
+    #[error("{0}")]
+    ProbeFailed(ProbeFailure),
     #[error("Worker process error: {0}")]
     Worker(String),
+    #[error("{0}")]
+    Failed(ConversionFailure),
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+    #[error("Invalid input: {0}")]
+    ContainerCodecMismatch(CodecMismatch),
     #[error("Task not found: {0}")]
     TaskNotFound(String),
+    #[error("Output already exists: {0}")]
+    OutputExists(String),
+}
+
+impl ConversionError {
+    /// Returns the probe failure category when `FFprobe` rejected the source.
+    #[must_use]
+    pub const fn probe_failure_kind(&self) -> Option<ProbeFailureKind> {
+        match self {
+            Self::ProbeFailed(failure) => Some(failure.kind),
+            _ => None,
+        }
+    }
+
+    /// Machine-readable failure code reported with conversion errors.
+    #[must_use]
+    pub fn failure_kind(&self) -> ConversionFailureKind {
+        match self {
+            Self::Failed(failure) => failure.kind,
+            Self::Io(error) => match error.kind() {
+                std::io::ErrorKind::StorageFull => ConversionFailureKind::DiskFull,
+                std::io::ErrorKind::PermissionDenied => ConversionFailureKind::PermissionDenied,
+                _ => ConversionFailureKind::Unknown,
+            },
+            Self::ProbeFailed(failure) => match failure.kind {
+                ProbeFailureKind::PermissionDenied => ConversionFailureKind::PermissionDenied,
+                ProbeFailureKind::UnrecognizedFormat
+                | ProbeFailureKind::Corrupt
+                | ProbeFailureKind::UnsupportedImage => ConversionFailureKind::InvalidData,
+                _ => ConversionFailureKind::Unknown,
+            },
+            _ => ConversionFailureKind::Unknown,
+        }
+    }
+
+    /// Stable identifier of the error kind, such as `io.not_found` or
+    /// `invalid_input.container_codec_mismatch`, for callers to branch on
+    /// and translate instead of parsing the message.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Shell(_) => "shell.command_failed",
+            Self::Io(error) => match error.kind() {
+                std::io::ErrorKind::NotFound => "io.not_found",
+                std::io::ErrorKind::PermissionDenied => "io.permission_denied",
+                std::io::ErrorKind::AlreadyExists => "io.already_exists",
+                std::io::ErrorKind::StorageFull => "io.storage_full",
+                _ => "io.other",
+            },
+            Self::Json(_) => "json.invalid",
+            Self::Channel(_) => "internal.channel",
+            Self::Probe(_) => "probe.failed",
+            Self::ProbeFailed(failure) => failure.kind.code(),
+            Self::Worker(_) => "worker.failed",
+            Self::Failed(failure) => failure.kind.code(),
+            Self::InvalidInput(_) => "invalid_input",
+            Self::ContainerCodecMismatch(_) => "invalid_input.container_codec_mismatch",
+            Self::TaskNotFound(_) => "task.not_found",
+            Self::OutputExists(_) => "output.exists",
+        }
+    }
+
+    /// The error's code, message and the values the message was built from.
+    #[must_use]
+    pub fn detail(&self) -> ErrorDetail {
+        let context = match self {
+            Self::ProbeFailed(failure) => ErrorContext {
+                details: failure.details.clone(),
+                ..ErrorContext::default()
+            },
+            Self::Failed(failure) => ErrorContext {
+                details: failure.details.clone(),
+                ..ErrorContext::default()
+            },
+            Self::ContainerCodecMismatch(mismatch) => ErrorContext {
+                stream: Some(mismatch.stream),
+                codec: Some(mismatch.codec.clone()),
+                container: Some(mismatch.container.clone()),
+                source_track: mismatch.source_track,
+                ..ErrorContext::default()
+            },
+            Self::TaskNotFound(id) => ErrorContext {
+                task_id: Some(id.clone()),
+                ..ErrorContext::default()
+            },
+            Self::OutputExists(path) => ErrorContext {
+                path: Some(path.clone()),
+                ..ErrorContext::default()
+            },
+            _ => ErrorContext::default(),
+        };
+        ErrorDetail {
+            code: self.code(),
+            message: self.to_string(),
+            context,
+        }
+    }
 }
 
 impl Serialize for ConversionError {
@@ -26,6 +131,318 @@ impl Serialize for ConversionError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        self.detail().serialize(serializer)
+    }
+}
+
+/// Serialized form of a [`ConversionError`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ErrorDetail {
+    /// See [`ConversionError::code`].
+    pub code: &'static str,
+    /// The English message the error displays as.
+    pub message: String,
+    #[serde(skip_serializing_if = "ErrorContext::is_empty")]
+    pub context: ErrorContext,
+}
+
+/// Values an error message was built from; only the fields that apply to
+/// the error are set.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ErrorContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_track: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// Tool output lines that explain the failure.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl ErrorContext {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Kind of stream a codec encodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+}
+
+impl StreamKind {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Video => "Video",
+            Self::Audio => "Audio",
+            Self::Subtitle => "Subtitle",
+        }
+    }
+}
+
+/// A codec the output container cannot hold.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CodecMismatch {
+    pub stream: StreamKind,
+    pub codec: String,
+    pub container: String,
+    /// Source track the codec would be copied from, in stream copies.
+    pub source_track: Option<u32>,
+}
+
+impl fmt::Display for CodecMismatch {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} codec '{}'", self.stream.label(), self.codec)?;
+        if let Some(track) = self.source_track {
+            write!(formatter, " from source track #{track}")?;
+        }
+        write!(
+            formatter,
+            " is not compatible with container '{}'",
+            self.container
+        )
+    }
+}
+
+/// Why `FFprobe` could not read a source.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeFailureKind {
+    FileNotFound,
+    PermissionDenied,
+    UnrecognizedFormat,
+    Corrupt,
+    Encrypted,
+    /// A HEIC/HEIF or camera RAW still the `FFmpeg` build cannot decode.
+    UnsupportedImage,
+    Timeout,
+    Other,
+}
+
+impl ProbeFailureKind {
+    /// User-facing sentence describing the failure.
+    #[must_use]
+    pub const fn summary(self) -> &'static str {
+        match self {
+            Self::FileNotFound => "This file could not be found",
+            Self::PermissionDenied => "Frame does not have permission to read this file",
+            Self::UnrecognizedFormat => "This file is not in a recognized media format",
+            Self::Corrupt => "This file appears to be corrupt or truncated",
+            Self::Encrypted => "This file appears to be DRM-protected",
+            Self::UnsupportedImage => "This FFmpeg build cannot decode this image format",
+            Self::Timeout => "Reading this file timed out",
+            Self::Other => "This file could not be read",
+        }
+    }
+
+    /// Error code of probes failing for this reason.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::FileNotFound => "probe.file_not_found",
+            Self::PermissionDenied => "probe.permission_denied",
+            Self::UnrecognizedFormat => "probe.unrecognized_format",
+            Self::Corrupt => "probe.corrupt",
+            Self::Encrypted => "probe.encrypted",
+            Self::UnsupportedImage => "probe.unsupported_image",
+            Self::Timeout => "probe.timeout",
+            Self::Other => "probe.other",
+        }
+    }
+}
+
+/// Categorized `FFprobe` failure with the stderr lines that explain it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeFailure {
+    pub kind: ProbeFailureKind,
+    pub details: Vec<String>,
+}
+
+impl ProbeFailure {
+    #[must_use]
+    pub fn timeout(timeout: Duration) -> Self {
+        Self {
+            kind: ProbeFailureKind::Timeout,
+            details: vec![format!(
+                "ffprobe did not finish within {} seconds",
+                timeout.as_secs()
+            )],
+        }
+    }
+}
+
+impl fmt::Display for ProbeFailure {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.kind.summary())?;
+        if !self.details.is_empty() {
+            write!(formatter, ": {}", self.details.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Why an `FFmpeg` conversion failed, as reported on error events.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionFailureKind {
+    DiskFull,
+    PermissionDenied,
+    EncoderMissing,
+    InvalidData,
+    /// A network source dropped, refused or stalled the connection.
+    Network,
+    /// The process was terminated by a signal rather than exiting.
+    Killed,
+    /// The output was written but did not decode cleanly or came out at the
+    /// wrong length.
+    VerificationFailed,
+    #[default]
+    Unknown,
+}
+
+impl ConversionFailureKind {
+    /// User-facing sentence describing the failure.
+    #[must_use]
+    pub const fn summary(self) -> &'static str {
+        match self {
+            Self::DiskFull => "The output drive is full",
+            Self::PermissionDenied => {
+                "Frame does not have permission to read the source or write the output"
+            }
+            Self::EncoderMissing => "The selected encoder is not available in this FFmpeg build",
+            Self::InvalidData => "The source contains data FFmpeg could not decode",
+            Self::Network => "The connection to the source was lost",
+            Self::Killed => "FFmpeg was stopped before it finished",
+            Self::VerificationFailed => "The output failed verification",
+            Self::Unknown => "FFmpeg could not convert this file",
+        }
+    }
+
+    /// Error code of conversions failing for this reason.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::DiskFull => "worker.disk_full",
+            Self::PermissionDenied => "worker.permission_denied",
+            Self::EncoderMissing => "worker.encoder_missing",
+            Self::InvalidData => "worker.invalid_data",
+            Self::Network => "worker.network",
+            Self::Killed => "worker.killed",
+            Self::VerificationFailed => "worker.verification_failed",
+            Self::Unknown => "worker.encoder_exit",
+        }
+    }
+
+    /// Whether running the same task again may succeed, as when a network
+    /// source dropped the connection part way through.
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::Network)
+    }
+}
+
+/// Categorized `FFmpeg` failure with the stderr lines that explain it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionFailure {
+    #[serde(rename = "code")]
+    pub kind: ConversionFailureKind,
+    pub details: Vec<String>,
+}
+
+impl fmt::Display for ConversionFailure {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.kind.summary())?;
+        if !self.details.is_empty() {
+            write!(formatter, ": {}", self.details.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(error: &ConversionError) -> serde_json::Value {
+        serde_json::to_value(error).expect("error should serialize")
+    }
+
+    #[test]
+    fn errors_serialize_with_a_stable_code_message_and_context() {
+        let mismatch = ConversionError::ContainerCodecMismatch(CodecMismatch {
+            stream: StreamKind::Audio,
+            codec: "opus".to_string(),
+            container: "mov".to_string(),
+            source_track: Some(2),
+        });
+        let missing = ConversionError::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+        let unreadable = ConversionError::ProbeFailed(ProbeFailure {
+            kind: ProbeFailureKind::Corrupt,
+            details: vec!["moov atom not found".to_string()],
+        });
+
+        assert_eq!(
+            json(&mismatch),
+            serde_json::json!({
+                "code": "invalid_input.container_codec_mismatch",
+                "message": "Invalid input: Audio codec 'opus' from source track #2 is not \
+                            compatible with container 'mov'",
+                "context": {
+                    "stream": "audio",
+                    "codec": "opus",
+                    "container": "mov",
+                    "source_track": 2
+                }
+            })
+        );
+        assert_eq!(
+            json(&missing),
+            serde_json::json!({"code": "io.not_found", "message": missing.to_string()})
+        );
+        assert_eq!(
+            json(&unreadable),
+            serde_json::json!({
+                "code": "probe.corrupt",
+                "message": "This file appears to be corrupt or truncated: moov atom not found",
+                "context": {"details": ["moov atom not found"]}
+            })
+        );
+        assert_eq!(
+            json(&ConversionError::OutputExists("/out/clip.mp4".to_string())),
+            serde_json::json!({
+                "code": "output.exists",
+                "message": "Output already exists: /out/clip.mp4",
+                "context": {"path": "/out/clip.mp4"}
+            })
+        );
+    }
+
+    #[test]
+    fn conversion_failures_map_to_worker_codes() {
+        let exit = ConversionError::Failed(ConversionFailure {
+            kind: ConversionFailureKind::Unknown,
+            details: Vec::new(),
+        });
+
+        assert_eq!(exit.code(), "worker.encoder_exit");
+        assert_eq!(
+            ConversionError::InvalidInput("CRF must be set".to_string()).code(),
+            "invalid_input"
+        );
     }
 }
@@ -0,0 +1,739 @@
+//! Output size estimates computed before a conversion starts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::codec::audio_codec_supports_vbr;
+use crate::error::ConversionError;
+use crate::media_rules::{container_supports_audio, is_image_container};
+use crate::output_template::predicted_output_dimensions;
+use crate::segments::kept_duration_seconds;
+use crate::types::{AudioTrack, ConversionConfig, ProbeMetadata};
+use crate::utils::{is_audio_only_container, parse_probe_bitrate};
+
+/// Number of segments encoded by the sample-based estimate.
+pub const SIZE_ESTIMATE_SAMPLE_COUNT: u32 = 3;
+/// Length of each sample segment, in seconds.
+pub const SIZE_ESTIMATE_SAMPLE_SECONDS: f64 = 5.0;
+
+const LOSSLESS_AUDIO_CODECS: [&str; 3] = ["flac", "alac", "pcm_s16le"];
+const STREAM_COPY_MARGIN: f64 = 0.1;
+const CONTAINER_BITRATE_MARGIN: f64 = 0.15;
+const TARGET_BITRATE_MARGIN: f64 = 0.05;
+const SAMPLE_ENCODE_MARGIN: f64 = 0.1;
+
+/// Share of the output taken by container headers, indexes and packet
+/// framing on top of the stream bitrates.
+pub const CONTAINER_OVERHEAD_FACTOR: f64 = 0.02;
+/// Bytes in a megabyte, as Frame displays file sizes.
+const BYTES_PER_MEGABYTE: f64 = 1024.0 * 1024.0;
+/// Video bitrate below which any codec falls apart, in kbps.
+const MIN_VIDEO_KBPS: f64 = 100.0;
+/// Frame rate assumed for bitrate floors when neither the settings nor the
+/// source report one.
+const FALLBACK_FRAME_RATE: f64 = 30.0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SizeEstimateMethod {
+    /// Sums the bitrates of the copied source streams.
+    StreamCopy,
+    /// Multiplies the configured target bitrates by the output duration.
+    Bitrate,
+    /// Encodes short samples and extrapolates their size.
+    SampleEncode,
+}
+
+/// Estimated output size with a confidence band, in bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSizeEstimate {
+    pub method: SizeEstimateMethod,
+    pub bytes: u64,
+    pub low_bytes: u64,
+    pub high_bytes: u64,
+}
+
+impl OutputSizeEstimate {
+    fn with_margin(method: SizeEstimateMethod, bytes: f64, margin: f64) -> Self {
+        Self {
+            method,
+            bytes: bytes_from_f64(bytes),
+            low_bytes: bytes_from_f64(bytes * (1.0 - margin)),
+            high_bytes: bytes_from_f64(bytes * (1.0 + margin)),
+        }
+    }
+}
+
+/// Chooses how the output size of `config` can be estimated.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for still image outputs, whose
+/// size does not scale with duration.
+pub fn size_estimate_method(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Result<SizeEstimateMethod, ConversionError> {
+    if is_image_container(&config.container) {
+        return Err(ConversionError::InvalidInput(
+            "Size estimates are not available for image outputs".to_string(),
+        ));
+    }
+    if config.processing_mode == "copy" {
+        return Ok(SizeEstimateMethod::StreamCopy);
+    }
+
+    let video_is_fixed = is_audio_only_container(&config.container)
+        || probe.video_codec.is_none()
+        || config.video_bitrate_mode == "bitrate";
+    let audio_is_fixed = !container_supports_audio(&config.container)
+        || probe.audio_tracks.is_empty()
+        || audio_bitrate_kbps(config).is_some();
+
+    Ok(if video_is_fixed && audio_is_fixed {
+        SizeEstimateMethod::Bitrate
+    } else {
+        SizeEstimateMethod::SampleEncode
+    })
+}
+
+/// Output duration after trimming, in seconds, summed over kept segments.
+#[must_use]
+pub fn effective_duration_seconds(config: &ConversionConfig, probe: &ProbeMetadata) -> Option<f64> {
+    let source_duration = probe
+        .duration
+        .as_deref()
+        .and_then(|duration| duration.trim().parse::<f64>().ok());
+    kept_duration_seconds(config, source_duration)
+}
+
+/// Sums the bitrates of the streams that stream copy maps into the output,
+/// falling back to the container bitrate when a stream bitrate is unknown.
+#[must_use]
+pub fn estimate_stream_copy_size(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    duration_seconds: f64,
+) -> Option<OutputSizeEstimate> {
+    let is_audio_only = is_audio_only_container(&config.container);
+    let video_kbps = if is_audio_only || probe.video_codec.is_none() {
+        Some(0.0)
+    } else {
+        probe.video_bitrate_kbps
+    };
+    let audio_kbps = if container_supports_audio(&config.container) {
+        output_audio_tracks(config, probe)
+            .map(|track| track.bitrate_kbps)
+            .sum::<Option<f64>>()
+    } else {
+        Some(0.0)
+    };
+
+    if let (Some(video_kbps), Some(audio_kbps)) = (video_kbps, audio_kbps) {
+        return Some(OutputSizeEstimate::with_margin(
+            SizeEstimateMethod::StreamCopy,
+            kbps_to_bytes(video_kbps + audio_kbps, duration_seconds),
+            STREAM_COPY_MARGIN,
+        ));
+    }
+
+    parse_probe_bitrate(probe.bitrate.as_deref()).map(|container_kbps| {
+        OutputSizeEstimate::with_margin(
+            SizeEstimateMethod::StreamCopy,
+            kbps_to_bytes(container_kbps, duration_seconds),
+            CONTAINER_BITRATE_MARGIN,
+        )
+    })
+}
+
+/// Multiplies the configured video and audio target bitrates by the output
+/// duration.
+#[must_use]
+pub fn estimate_bitrate_size(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    duration_seconds: f64,
+) -> Option<OutputSizeEstimate> {
+    let video_kbps = if is_audio_only_container(&config.container) || probe.video_codec.is_none() {
+        0.0
+    } else {
+        config.video_bitrate.trim().parse::<f64>().ok()?
+    };
+    let audio_kbps = if container_supports_audio(&config.container) {
+        let track_count = output_audio_tracks(config, probe).count();
+        if track_count == 0 {
+            0.0
+        } else {
+            #[expect(clippy::cast_precision_loss, reason = "audio track counts are tiny")]
+            let track_count = track_count as f64;
+            audio_bitrate_kbps(config)? * track_count
+        }
+    } else {
+        0.0
+    };
+
+    Some(OutputSizeEstimate::with_margin(
+        SizeEstimateMethod::Bitrate,
+        kbps_to_bytes(video_kbps + audio_kbps, duration_seconds),
+        TARGET_BITRATE_MARGIN,
+    ))
+}
+
+/// Picks `(start, length)` sample segments spread across the output range.
+///
+/// Short outputs are covered by a single segment spanning the whole range.
+#[must_use]
+pub fn size_estimate_sample_ranges(start_seconds: f64, duration_seconds: f64) -> Vec<(f64, f64)> {
+    let total_sample_seconds = SIZE_ESTIMATE_SAMPLE_SECONDS * f64::from(SIZE_ESTIMATE_SAMPLE_COUNT);
+    if duration_seconds <= total_sample_seconds {
+        return vec![(start_seconds, duration_seconds)];
+    }
+
+    let spacing = (duration_seconds - SIZE_ESTIMATE_SAMPLE_SECONDS)
+        / f64::from(SIZE_ESTIMATE_SAMPLE_COUNT + 1);
+    (1..=SIZE_ESTIMATE_SAMPLE_COUNT)
+        .map(|sample| {
+            (
+                start_seconds + spacing * f64::from(sample),
+                SIZE_ESTIMATE_SAMPLE_SECONDS,
+            )
+        })
+        .collect()
+}
+
+/// Extrapolates encoded `(bytes, seconds)` samples to the full output
+/// duration, using the spread between samples as the confidence band.
+#[must_use]
+pub fn extrapolate_sample_sizes(
+    samples: &[(u64, f64)],
+    duration_seconds: f64,
+) -> Option<OutputSizeEstimate> {
+    let rates: Vec<f64> = samples
+        .iter()
+        .filter(|(_, seconds)| *seconds > 0.0)
+        .map(|(bytes, seconds)| u64_to_f64(*bytes) / seconds)
+        .collect();
+    if rates.is_empty() {
+        return None;
+    }
+
+    let total_bytes: u64 = samples.iter().map(|(bytes, _)| bytes).sum();
+    let total_seconds: f64 = samples.iter().map(|(_, seconds)| seconds).sum();
+    let mean_rate = u64_to_f64(total_bytes) / total_seconds;
+    let low_rate = rates.iter().copied().fold(f64::INFINITY, f64::min);
+    let high_rate = rates.iter().copied().fold(0.0, f64::max);
+
+    Some(OutputSizeEstimate {
+        method: SizeEstimateMethod::SampleEncode,
+        bytes: bytes_from_f64(mean_rate * duration_seconds),
+        low_bytes: bytes_from_f64(low_rate * duration_seconds * (1.0 - SAMPLE_ENCODE_MARGIN)),
+        high_bytes: bytes_from_f64(high_rate * duration_seconds * (1.0 + SAMPLE_ENCODE_MARGIN)),
+    })
+}
+
+/// What a bitrate calculation starts from.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum BitrateTarget {
+    /// Total output size, in megabytes of 1024 × 1024 bytes.
+    Size { megabytes: f64 },
+    /// Video bitrate, in kbps.
+    Bitrate { kbps: f64 },
+}
+
+/// Video bitrate and output size that belong together for a conversion.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitrateCalculation {
+    pub video_kbps: f64,
+    /// Bitrate of all output audio tracks together.
+    pub audio_kbps: f64,
+    pub size_bytes: u64,
+    /// Output duration the calculation is based on, in seconds.
+    pub duration_seconds: f64,
+    pub warnings: Vec<String>,
+}
+
+/// Turns a target size into the video bitrate that reaches it, or a video
+/// bitrate into the size it produces.
+///
+/// The output duration accounts for trims and cut segments. The configured
+/// audio bitrate is counted once per output audio track, falling back to a
+/// track's source bitrate when the audio encoder picks its own, and
+/// [`CONTAINER_OVERHEAD_FACTOR`] is reserved for the container. Warnings
+/// flag video bitrates below what the codec needs at the output frame size.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the output has no video
+/// bitrate to set (image, audio-only and stream copy outputs), the duration
+/// is unknown, the target is not positive, or a target size leaves no room
+/// for video after the audio.
+pub fn calculate_bitrate(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    target: BitrateTarget,
+) -> Result<BitrateCalculation, ConversionError> {
+    if is_image_container(&config.container)
+        || is_audio_only_container(&config.container)
+        || probe.video_codec.is_none()
+    {
+        return Err(ConversionError::InvalidInput(
+            "Bitrate calculations need a video output".to_string(),
+        ));
+    }
+    if config.processing_mode == "copy" {
+        return Err(ConversionError::InvalidInput(
+            "Stream copy keeps the source bitrate".to_string(),
+        ));
+    }
+    let duration_seconds = effective_duration_seconds(config, probe)
+        .filter(|duration| *duration > 0.0)
+        .ok_or_else(|| {
+            ConversionError::InvalidInput(
+                "Cannot calculate a bitrate without a known duration".to_string(),
+            )
+        })?;
+
+    let mut warnings = Vec::new();
+    let audio_kbps = output_audio_kbps(config, probe, &mut warnings);
+    let overhead = 1.0 + CONTAINER_OVERHEAD_FACTOR;
+    let (video_kbps, size_bytes) = match target {
+        BitrateTarget::Size { megabytes } => {
+            if !megabytes.is_finite() || megabytes <= 0.0 {
+                return Err(ConversionError::InvalidInput(
+                    "The target size must be greater than zero".to_string(),
+                ));
+            }
+            let size_bytes = megabytes * BYTES_PER_MEGABYTE;
+            let video_kbps = size_bytes * 8.0 / 1000.0 / duration_seconds / overhead - audio_kbps;
+            if video_kbps <= 0.0 {
+                return Err(ConversionError::InvalidInput(format!(
+                    "{megabytes} MB leaves no room for video after {audio_kbps:.0} kbps of audio"
+                )));
+            }
+            (video_kbps, size_bytes)
+        }
+        BitrateTarget::Bitrate { kbps } => {
+            if !kbps.is_finite() || kbps <= 0.0 {
+                return Err(ConversionError::InvalidInput(
+                    "The target bitrate must be greater than zero".to_string(),
+                ));
+            }
+            (
+                kbps,
+                kbps_to_bytes(kbps + audio_kbps, duration_seconds) * overhead,
+            )
+        }
+    };
+
+    let floor = video_kbps_floor(config, probe);
+    if video_kbps < floor {
+        warnings.push(format!(
+            "{video_kbps:.0} kbps is below the {floor:.0} kbps {} usually needs at this frame \
+             size; expect visible artifacts",
+            config.video_codec
+        ));
+    }
+
+    Ok(BitrateCalculation {
+        video_kbps,
+        audio_kbps,
+        size_bytes: bytes_from_f64(size_bytes),
+        duration_seconds,
+        warnings,
+    })
+}
+
+fn output_audio_kbps(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    warnings: &mut Vec<String>,
+) -> f64 {
+    if !container_supports_audio(&config.container) {
+        return 0.0;
+    }
+    let configured = audio_bitrate_kbps(config);
+    output_audio_tracks(config, probe)
+        .map(|track| {
+            configured.or(track.bitrate_kbps).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "Audio track {} has no known bitrate and is left out of the calculation",
+                    track.index
+                ));
+                0.0
+            })
+        })
+        .sum()
+}
+
+/// Lowest video bitrate that keeps `config` watchable at its output frame
+/// size and rate, in kbps.
+fn video_kbps_floor(config: &ConversionConfig, probe: &ProbeMetadata) -> f64 {
+    let Some((width, height)) = predicted_output_dimensions(config, probe) else {
+        return MIN_VIDEO_KBPS;
+    };
+    let frame_rate = config
+        .fps
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|fps| *fps > 0.0)
+        .or(probe.frame_rate)
+        .unwrap_or(FALLBACK_FRAME_RATE);
+    let pixels_per_second = f64::from(width) * f64::from(height) * frame_rate;
+    (pixels_per_second * bits_per_pixel_floor(&config.video_codec) / 1000.0).max(MIN_VIDEO_KBPS)
+}
+
+/// Bits per pixel below which the codec family visibly breaks down.
+fn bits_per_pixel_floor(video_codec: &str) -> f64 {
+    let codec = video_codec.to_ascii_lowercase();
+    if ["av1", "hevc", "265", "vp9"]
+        .iter()
+        .any(|family| codec.contains(family))
+    {
+        0.025
+    } else if codec.contains("264") {
+        0.04
+    } else {
+        0.07
+    }
+}
+
+fn output_audio_tracks<'a>(
+    config: &'a ConversionConfig,
+    probe: &'a ProbeMetadata,
+) -> impl Iterator<Item = &'a AudioTrack> {
+    probe.audio_tracks.iter().filter(|track| {
+        config.selected_audio_tracks.is_empty()
+            || config.selected_audio_tracks.contains(&track.index)
+    })
+}
+
+/// Constant audio bitrate per track, or `None` when the encoder decides the
+/// bitrate itself (VBR and lossless codecs).
+fn audio_bitrate_kbps(config: &ConversionConfig) -> Option<f64> {
+    if LOSSLESS_AUDIO_CODECS.contains(&config.audio_codec.as_str())
+        || (config.audio_bitrate_mode == "vbr" && audio_codec_supports_vbr(&config.audio_codec))
+    {
+        return None;
+    }
+    config.audio_bitrate.trim().parse().ok()
+}
+
+fn kbps_to_bytes(kbps: f64, seconds: f64) -> f64 {
+    kbps * 1000.0 / 8.0 * seconds
+}
+
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "byte counts stay far below the f64 mantissa limit"
+)]
+const fn u64_to_f64(value: u64) -> f64 {
+    value as f64
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "estimates are clamped to non-negative values before rounding"
+)]
+const fn bytes_from_f64(value: f64) -> u64 {
+    value.max(0.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> ConversionConfig {
+        ConversionConfig {
+            video_bitrate_mode: "bitrate".to_string(),
+            video_bitrate: "4000".to_string(),
+            ..crate::types::sample_config("mp4", "libx264")
+        }
+    }
+
+    fn sample_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("600.0".to_string()),
+            bitrate: Some("6000000".to_string()),
+            video_codec: Some("h264".to_string()),
+            video_bitrate_kbps: Some(5000.0),
+            audio_tracks: vec![
+                AudioTrack {
+                    index: 1,
+                    bitrate_kbps: Some(192.0),
+                    ..AudioTrack::default()
+                },
+                AudioTrack {
+                    index: 2,
+                    bitrate_kbps: Some(96.0),
+                    ..AudioTrack::default()
+                },
+            ],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn size_estimate_method_follows_rate_control() {
+        let probe = sample_probe();
+        let mut copy = sample_config();
+        copy.processing_mode = "copy".to_string();
+        let mut crf = sample_config();
+        crf.video_bitrate_mode = "crf".to_string();
+        let mut image = sample_config();
+        image.container = "png".to_string();
+
+        assert_eq!(
+            size_estimate_method(&copy, &probe).ok(),
+            Some(SizeEstimateMethod::StreamCopy)
+        );
+        assert_eq!(
+            size_estimate_method(&sample_config(), &probe).ok(),
+            Some(SizeEstimateMethod::Bitrate)
+        );
+        assert_eq!(
+            size_estimate_method(&crf, &probe).ok(),
+            Some(SizeEstimateMethod::SampleEncode)
+        );
+        assert!(size_estimate_method(&image, &probe).is_err());
+    }
+
+    #[test]
+    fn effective_duration_seconds_applies_trim() {
+        let mut config = sample_config();
+        config.start_time = Some("00:01:00".to_string());
+        config.end_time = Some("00:03:00".to_string());
+
+        assert!(
+            effective_duration_seconds(&config, &sample_probe())
+                .is_some_and(|duration| (duration - 120.0).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn estimate_stream_copy_size_sums_selected_stream_bitrates() {
+        let mut config = sample_config();
+        config.selected_audio_tracks = vec![2];
+
+        let estimate = estimate_stream_copy_size(&config, &sample_probe(), 8.0)
+            .expect("stream bitrates are known");
+
+        assert_eq!(estimate.bytes, 5_096_000);
+        assert!(estimate.low_bytes < estimate.bytes && estimate.bytes < estimate.high_bytes);
+    }
+
+    #[test]
+    fn estimate_stream_copy_size_falls_back_to_container_bitrate() {
+        let mut probe = sample_probe();
+        probe.video_bitrate_kbps = None;
+
+        let estimate = estimate_stream_copy_size(&sample_config(), &probe, 8.0)
+            .expect("container bitrate is known");
+
+        assert_eq!(estimate.bytes, 6_000_000);
+    }
+
+    #[test]
+    fn estimate_bitrate_size_counts_every_output_audio_track() {
+        let estimate = estimate_bitrate_size(&sample_config(), &sample_probe(), 8.0)
+            .expect("target bitrates are fixed");
+
+        assert_eq!(estimate.method, SizeEstimateMethod::Bitrate);
+        assert_eq!(estimate.bytes, 4_256_000);
+    }
+
+    #[test]
+    fn size_estimate_sample_ranges_spread_segments() {
+        let ranges = size_estimate_sample_ranges(10.0, 85.0);
+
+        assert_eq!(ranges.len(), 3);
+        assert!((ranges[0].0 - 30.0).abs() < 1e-9);
+        assert!((ranges[2].0 - 70.0).abs() < 1e-9);
+        assert_eq!(size_estimate_sample_ranges(0.0, 9.0).len(), 1);
+    }
+
+    #[test]
+    fn extrapolate_sample_sizes_uses_sample_spread_as_band() {
+        let estimate = extrapolate_sample_sizes(&[(500_000, 5.0), (1_500_000, 5.0)], 100.0)
+            .expect("samples were encoded");
+
+        assert_eq!(estimate.bytes, 20_000_000);
+        assert_eq!(estimate.low_bytes, 9_000_000);
+        assert_eq!(estimate.high_bytes, 33_000_000);
+        assert_eq!(extrapolate_sample_sizes(&[], 100.0), None);
+    }
+
+    #[test]
+    fn calculate_bitrate_matches_the_encoder_math() {
+        for (source, trim, target, video_kbps, size_bytes) in [
+            (
+                "600.0",
+                None,
+                BitrateTarget::Size { megabytes: 700.0 },
+                9338.813,
+                734_003_200,
+            ),
+            (
+                "600.0",
+                Some(("0", "60")),
+                BitrateTarget::Size { megabytes: 50.0 },
+                6597.438,
+                52_428_800,
+            ),
+            (
+                "3600.0",
+                None,
+                BitrateTarget::Size { megabytes: 4000.0 },
+                8881.917,
+                4_194_304_000,
+            ),
+            (
+                "600.0",
+                None,
+                BitrateTarget::Bitrate { kbps: 4500.0 },
+                4500.0,
+                363_834_000,
+            ),
+            (
+                "600.0",
+                Some(("00:01:00", "00:03:00")),
+                BitrateTarget::Bitrate { kbps: 1000.0 },
+                1000.0,
+                19_216_800,
+            ),
+        ] {
+            let mut config = sample_config();
+            if let Some((start, end)) = trim {
+                config.start_time = Some(start.to_string());
+                config.end_time = Some(end.to_string());
+            }
+            let mut probe = sample_probe();
+            probe.duration = Some(source.to_string());
+
+            let calculation =
+                calculate_bitrate(&config, &probe, target).expect("target should be reachable");
+
+            assert!(
+                (calculation.video_kbps - video_kbps).abs() < 1e-3,
+                "{target:?}: {}",
+                calculation.video_kbps
+            );
+            assert_eq!(calculation.size_bytes, size_bytes, "{target:?}");
+            assert!((calculation.audio_kbps - 256.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn calculate_bitrate_round_trips_between_size_and_bitrate() {
+        let config = sample_config();
+        let probe = sample_probe();
+
+        let from_size =
+            calculate_bitrate(&config, &probe, BitrateTarget::Size { megabytes: 700.0 })
+                .expect("target should be reachable");
+        let from_bitrate = calculate_bitrate(
+            &config,
+            &probe,
+            BitrateTarget::Bitrate {
+                kbps: from_size.video_kbps,
+            },
+        )
+        .expect("bitrate should be valid");
+
+        assert_eq!(from_bitrate.size_bytes, from_size.size_bytes);
+    }
+
+    #[test]
+    fn calculate_bitrate_falls_back_to_source_audio_bitrates() {
+        let mut config = sample_config();
+        config.audio_codec = "flac".to_string();
+        let mut probe = sample_probe();
+        probe.audio_tracks[1].bitrate_kbps = None;
+
+        let calculation =
+            calculate_bitrate(&config, &probe, BitrateTarget::Bitrate { kbps: 4000.0 })
+                .expect("bitrate should be valid");
+
+        assert!((calculation.audio_kbps - 192.0).abs() < 1e-9);
+        assert!(calculation.warnings[0].contains("Audio track 2"));
+    }
+
+    #[test]
+    fn calculate_bitrate_warns_below_the_codec_floor() {
+        let mut probe = sample_probe();
+        probe.width = Some(1920);
+        probe.height = Some(1080);
+        probe.frame_rate = Some(30.0);
+        let mut hevc = sample_config();
+        hevc.video_codec = "libx265".to_string();
+
+        let starved = calculate_bitrate(
+            &sample_config(),
+            &probe,
+            BitrateTarget::Bitrate { kbps: 1000.0 },
+        )
+        .expect("bitrate should be valid");
+        let enough = calculate_bitrate(
+            &sample_config(),
+            &probe,
+            BitrateTarget::Bitrate { kbps: 4500.0 },
+        )
+        .expect("bitrate should be valid");
+        let efficient = calculate_bitrate(&hevc, &probe, BitrateTarget::Bitrate { kbps: 2000.0 })
+            .expect("bitrate should be valid");
+
+        assert!(
+            starved.warnings[0].contains("2488 kbps libx264"),
+            "{:?}",
+            starved.warnings
+        );
+        assert!(enough.warnings.is_empty());
+        assert!(efficient.warnings.is_empty());
+    }
+
+    #[test]
+    fn calculate_bitrate_rejects_unreachable_targets() {
+        let mut copy = sample_config();
+        copy.processing_mode = "copy".to_string();
+        let mut audio_only = sample_config();
+        audio_only.container = "mp3".to_string();
+        let mut unknown_duration = sample_probe();
+        unknown_duration.duration = None;
+
+        for (config, probe, target) in [
+            (
+                sample_config(),
+                sample_probe(),
+                BitrateTarget::Size { megabytes: 1.0 },
+            ),
+            (
+                sample_config(),
+                sample_probe(),
+                BitrateTarget::Bitrate { kbps: 0.0 },
+            ),
+            (
+                sample_config(),
+                unknown_duration,
+                BitrateTarget::Size { megabytes: 700.0 },
+            ),
+            (
+                copy,
+                sample_probe(),
+                BitrateTarget::Bitrate { kbps: 4000.0 },
+            ),
+            (
+                audio_only,
+                sample_probe(),
+                BitrateTarget::Bitrate { kbps: 4000.0 },
+            ),
+        ] {
+            assert!(
+                calculate_bitrate(&config, &probe, target).is_err(),
+                "{target:?}"
+            );
+        }
+    }
+}
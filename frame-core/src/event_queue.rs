@@ -0,0 +1,374 @@
+//! Bounded hand-off of conversion events from the runner thread to the UI.
+//!
+//! A task that floods stderr produces events faster than the UI drains them,
+//! so the queue never holds more than its capacity. Progress keeps only the
+//! latest value per task, log lines join the batch already waiting for their
+//! task, and lifecycle events are never dropped: once the queue is full of
+//! them the sender waits until the UI catches up.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::events::ConversionEvent;
+use crate::types::{ConversionPhase, LogBatchPayload};
+
+/// Events the UI queue holds before the sender has to make room or wait.
+pub const EVENT_QUEUE_CAPACITY: usize = 256;
+
+/// Most log lines one waiting batch may hold; older lines are dropped once
+/// it is full. The task log file keeps every line.
+pub const EVENT_QUEUE_MAX_BATCH_LINES: usize = 1000;
+
+/// Counters showing when backpressure kicked in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EventQueueStats {
+    /// Progress values replaced by a newer one before the UI read them.
+    pub dropped_progress: u64,
+    /// Log lines dropped from batches that reached
+    /// [`EVENT_QUEUE_MAX_BATCH_LINES`].
+    pub dropped_log_lines: u64,
+    /// Log events merged into a batch already waiting in the queue.
+    pub coalesced_logs: u64,
+    /// Sends that had to wait for the UI to drain the queue.
+    pub blocked_sends: u64,
+    /// Most events held at once.
+    pub peak_len: usize,
+}
+
+impl EventQueueStats {
+    /// Whether any event was dropped or any send had to wait.
+    #[must_use]
+    pub const fn saw_backpressure(&self) -> bool {
+        self.dropped_progress > 0 || self.dropped_log_lines > 0 || self.blocked_sends > 0
+    }
+}
+
+#[derive(Debug)]
+struct QueueState {
+    events: VecDeque<ConversionEvent>,
+    capacity: usize,
+    sender_closed: bool,
+    receiver_closed: bool,
+    stats: EventQueueStats,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<QueueState>,
+    drained: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sending half of a conversion event queue, owned by the runner thread.
+#[derive(Debug)]
+pub struct ConversionEventSender {
+    shared: Arc<Shared>,
+}
+
+/// Receiving half of a conversion event queue, polled by the UI.
+#[derive(Debug)]
+pub struct ConversionEventReceiver {
+    shared: Arc<Shared>,
+}
+
+/// Creates a queue holding at most `capacity` events.
+#[must_use]
+pub fn conversion_event_queue(capacity: usize) -> (ConversionEventSender, ConversionEventReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(QueueState {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            sender_closed: false,
+            receiver_closed: false,
+            stats: EventQueueStats::default(),
+        }),
+        drained: Condvar::new(),
+    });
+    (
+        ConversionEventSender {
+            shared: Arc::clone(&shared),
+        },
+        ConversionEventReceiver { shared },
+    )
+}
+
+impl ConversionEventSender {
+    /// Queues `event` for the UI.
+    ///
+    /// Progress replaces the value still waiting for its task and log lines
+    /// join a waiting batch, so neither grows the queue. When the queue is
+    /// full, the oldest waiting progress value is dropped to make room;
+    /// failing that, the call blocks until the UI drains the queue. Events
+    /// sent after the receiver is gone are discarded.
+    pub fn send(&self, event: ConversionEvent) {
+        let mut state = self.shared.lock();
+        if state.receiver_closed {
+            return;
+        }
+        let event = match event {
+            ConversionEvent::Progress(payload) => {
+                match latest_waiting(&state.events, &payload.id, |event| {
+                    matches!(event, ConversionEvent::Progress(_))
+                }) {
+                    Some(index) => {
+                        state.events[index] = ConversionEvent::Progress(payload);
+                        state.stats.dropped_progress += 1;
+                        return;
+                    }
+                    None => ConversionEvent::Progress(payload),
+                }
+            }
+            ConversionEvent::Log(payload) => {
+                match merge_log_lines(&mut state, &payload.id, payload.phase, vec![payload.line]) {
+                    Some(lines) => ConversionEvent::LogBatch(LogBatchPayload {
+                        id: payload.id,
+                        lines,
+                        phase: payload.phase,
+                    }),
+                    None => return,
+                }
+            }
+            ConversionEvent::LogBatch(payload) => {
+                match merge_log_lines(&mut state, &payload.id, payload.phase, payload.lines) {
+                    Some(lines) => ConversionEvent::LogBatch(LogBatchPayload { lines, ..payload }),
+                    None => return,
+                }
+            }
+            event => event,
+        };
+
+        let mut waited = false;
+        while state.events.len() >= state.capacity && !state.receiver_closed {
+            if let Some(index) = state
+                .events
+                .iter()
+                .position(|event| matches!(event, ConversionEvent::Progress(_)))
+            {
+                state.events.remove(index);
+                state.stats.dropped_progress += 1;
+                continue;
+            }
+            if !waited {
+                waited = true;
+                state.stats.blocked_sends += 1;
+            }
+            state = self
+                .shared
+                .drained
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        if state.receiver_closed {
+            return;
+        }
+        state.events.push_back(event);
+        state.stats.peak_len = state.stats.peak_len.max(state.events.len());
+    }
+}
+
+impl Drop for ConversionEventSender {
+    fn drop(&mut self) {
+        self.shared.lock().sender_closed = true;
+    }
+}
+
+impl ConversionEventReceiver {
+    /// Takes every waiting event, in order, and wakes a blocked sender.
+    #[must_use]
+    pub fn drain(&self) -> Vec<ConversionEvent> {
+        let events = self.shared.lock().events.drain(..).collect();
+        self.shared.drained.notify_all();
+        events
+    }
+
+    /// Whether the sender is gone, so no event will follow the waiting ones.
+    #[must_use]
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.lock().sender_closed
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> EventQueueStats {
+        self.shared.lock().stats
+    }
+}
+
+impl Drop for ConversionEventReceiver {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_closed = true;
+        state.events.clear();
+        drop(state);
+        self.shared.drained.notify_all();
+    }
+}
+
+/// Index of the newest waiting event of task `id` that `matches`, unless a
+/// lifecycle event of the task comes after it and must stay behind it.
+fn latest_waiting(
+    events: &VecDeque<ConversionEvent>,
+    id: &str,
+    matches: impl Fn(&ConversionEvent) -> bool,
+) -> Option<usize> {
+    for (index, event) in events.iter().enumerate().rev() {
+        if event.id() != id {
+            continue;
+        }
+        if matches(event) {
+            return Some(index);
+        }
+        if !matches!(
+            event,
+            ConversionEvent::Progress(_) | ConversionEvent::Log(_) | ConversionEvent::LogBatch(_)
+        ) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Appends `lines` to the batch waiting for task `id` in `phase`, trimming
+/// it to [`EVENT_QUEUE_MAX_BATCH_LINES`]. Returns the lines back, trimmed,
+/// when no batch is waiting.
+fn merge_log_lines(
+    state: &mut QueueState,
+    id: &str,
+    phase: Option<ConversionPhase>,
+    mut lines: Vec<String>,
+) -> Option<Vec<String>> {
+    let waiting = latest_waiting(
+        &state.events,
+        id,
+        |event| matches!(event, ConversionEvent::LogBatch(payload) if payload.phase == phase),
+    );
+    let Some(index) = waiting else {
+        state.stats.dropped_log_lines += trim_front(&mut lines);
+        return Some(lines);
+    };
+    let ConversionEvent::LogBatch(batch) = &mut state.events[index] else {
+        unreachable!("waiting logs are always queued as batches");
+    };
+    batch.lines.append(&mut lines);
+    let dropped = trim_front(&mut batch.lines);
+    state.stats.dropped_log_lines += dropped;
+    state.stats.coalesced_logs += 1;
+    None
+}
+
+/// Drops the oldest lines past [`EVENT_QUEUE_MAX_BATCH_LINES`], returning
+/// how many were dropped.
+fn trim_front(lines: &mut Vec<String>) -> u64 {
+    let excess = lines.len().saturating_sub(EVENT_QUEUE_MAX_BATCH_LINES);
+    lines.drain(..excess);
+    excess as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn a_flood_of_progress_and_logs_stays_bounded() {
+        let (sender, receiver) = conversion_event_queue(8);
+
+        for step in 0..100_000_u32 {
+            let id = if step % 2 == 0 { "av1" } else { "h264" };
+            sender.send(ConversionEvent::log(id, format!("frame={step}")));
+            sender.send(ConversionEvent::progress(id, f64::from(step) / 1000.0));
+        }
+        let stats = receiver.stats();
+        let events = receiver.drain();
+
+        assert_eq!(events.len(), 4);
+        assert!(stats.peak_len <= 4);
+        assert_eq!(stats.blocked_sends, 0);
+        assert_eq!(stats.dropped_progress, 99_998);
+        assert_eq!(stats.dropped_log_lines, 98_000);
+        let ConversionEvent::LogBatch(batch) = &events[0] else {
+            unreachable!("the first event is the merged av1 log");
+        };
+        assert_eq!(batch.lines.len(), EVENT_QUEUE_MAX_BATCH_LINES);
+        assert_eq!(batch.lines.last().map(String::as_str), Some("frame=99998"));
+        assert_eq!(events[1], ConversionEvent::progress("av1", 99.998));
+    }
+
+    #[test]
+    fn logs_and_progress_never_move_past_lifecycle_events() {
+        let (sender, receiver) = conversion_event_queue(8);
+
+        sender.send(ConversionEvent::progress("task", 90.0));
+        sender.send(ConversionEvent::log("task", "last frame"));
+        sender.send(ConversionEvent::completed("task", "/out.mp4"));
+        sender.send(ConversionEvent::progress("task", 0.0));
+        sender.send(ConversionEvent::log("task", "next run"));
+
+        assert_eq!(
+            receiver.drain(),
+            [
+                ConversionEvent::progress("task", 90.0),
+                ConversionEvent::log_batch("task", vec!["last frame".to_string()]),
+                ConversionEvent::completed("task", "/out.mp4"),
+                ConversionEvent::progress("task", 0.0),
+                ConversionEvent::log_batch("task", vec!["next run".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_full_queue_makes_lifecycle_senders_wait_for_the_ui() {
+        let (sender, receiver) = conversion_event_queue(2);
+        sender.send(ConversionEvent::started("a", "/a.mp4"));
+        sender.send(ConversionEvent::progress("a", 10.0));
+
+        let producer = thread::spawn(move || {
+            sender.send(ConversionEvent::started("b", "/b.mp4"));
+            sender.send(ConversionEvent::completed("a", "/a.mp4"));
+        });
+        while !receiver.is_disconnected() {
+            thread::sleep(Duration::from_millis(20));
+            if receiver.stats().blocked_sends > 0 {
+                break;
+            }
+        }
+        let first = receiver.drain();
+        producer.join().expect("producer should finish");
+        let second = receiver.drain();
+
+        assert_eq!(
+            first,
+            [
+                ConversionEvent::started("a", "/a.mp4"),
+                ConversionEvent::started("b", "/b.mp4"),
+            ]
+        );
+        assert_eq!(second, [ConversionEvent::completed("a", "/a.mp4")]);
+        assert!(receiver.is_disconnected());
+        let stats = receiver.stats();
+        assert_eq!((stats.dropped_progress, stats.blocked_sends), (1, 1));
+        assert!(stats.saw_backpressure());
+    }
+
+    #[test]
+    fn dropping_the_receiver_releases_a_waiting_sender() {
+        let (sender, receiver) = conversion_event_queue(1);
+        sender.send(ConversionEvent::started("a", "/a.mp4"));
+
+        let producer = thread::spawn(move || {
+            sender.send(ConversionEvent::completed("a", "/a.mp4"));
+            sender.send(ConversionEvent::started("b", "/b.mp4"));
+        });
+        thread::sleep(Duration::from_millis(20));
+        drop(receiver);
+
+        producer.join().expect("sender should not stay blocked");
+    }
+}
@@ -1,5 +1,10 @@
+use std::time::{Duration, Instant};
+
+use crate::error::{ConversionError, ConversionFailureKind};
 use crate::types::{
-    CancelledPayload, CompletedPayload, ErrorPayload, LogPayload, ProgressPayload, StartedPayload,
+    CancelledPayload, CompletedPayload, ConversionPhase, ErrorPayload, LogBatchPayload, LogPayload,
+    OutputSummary, PostActionOutcome, ProgressPayload, ProgressStats, StartedPayload,
+    WarningPayload,
 };
 
 pub const CONVERSION_STARTED_EVENT: &str = "conversion-started";
@@ -8,6 +13,15 @@ pub const CONVERSION_COMPLETED_EVENT: &str = "conversion-completed";
 pub const CONVERSION_ERROR_EVENT: &str = "conversion-error";
 pub const CONVERSION_LOG_EVENT: &str = "conversion-log";
 pub const CONVERSION_CANCELLED_EVENT: &str = "conversion-cancelled";
+pub const CONVERSION_LOG_BATCH_EVENT: &str = "conversion-log-batch";
+pub const CONVERSION_WARNING_EVENT: &str = "conversion-warning";
+
+/// Minimum spacing between forwarded progress events for one task.
+pub const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(200);
+/// Maximum age of a buffered log batch before it is flushed.
+pub const LOG_BATCH_INTERVAL: Duration = Duration::from_millis(250);
+/// Maximum number of log lines buffered before a batch is flushed.
+pub const LOG_BATCH_MAX_LINES: usize = 50;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ConversionEvent {
@@ -16,36 +30,119 @@ pub enum ConversionEvent {
     Completed(CompletedPayload),
     Error(ErrorPayload),
     Log(LogPayload),
+    LogBatch(LogBatchPayload),
     Cancelled(CancelledPayload),
+    Warning(WarningPayload),
 }
 
 impl ConversionEvent {
     #[must_use]
-    pub fn started(id: impl Into<String>) -> Self {
-        Self::Started(StartedPayload { id: id.into() })
+    pub fn started(id: impl Into<String>, output_path: impl Into<String>) -> Self {
+        Self::Started(StartedPayload {
+            id: id.into(),
+            output_path: output_path.into(),
+        })
     }
 
     #[must_use]
     pub fn progress(id: impl Into<String>, progress: f64) -> Self {
+        Self::progress_with_stats(id, progress, ProgressStats::default())
+    }
+
+    #[must_use]
+    pub fn progress_with_stats(id: impl Into<String>, progress: f64, stats: ProgressStats) -> Self {
         Self::Progress(ProgressPayload {
             id: id.into(),
             progress,
+            indeterminate: false,
+            phase: None,
+            stats,
+        })
+    }
+
+    /// Progress of a source whose length is unknown, such as a live stream,
+    /// reported by the time and bytes processed alone.
+    #[must_use]
+    pub fn indeterminate_progress(id: impl Into<String>, stats: ProgressStats) -> Self {
+        Self::Progress(ProgressPayload {
+            id: id.into(),
+            progress: 0.0,
+            indeterminate: true,
+            phase: None,
+            stats,
         })
     }
 
     #[must_use]
     pub fn completed(id: impl Into<String>, output_path: impl Into<String>) -> Self {
+        Self::completed_with_summary(id, output_path, None, OutputSummary::default())
+    }
+
+    #[must_use]
+    pub fn completed_with_summary(
+        id: impl Into<String>,
+        output_path: impl Into<String>,
+        post_action: Option<PostActionOutcome>,
+        summary: OutputSummary,
+    ) -> Self {
+        let output_path = output_path.into();
         Self::Completed(CompletedPayload {
             id: id.into(),
-            output_path: output_path.into(),
+            outputs: vec![output_path.clone()],
+            output_path,
+            post_action,
+            summary,
         })
     }
 
+    /// Lists the parts of a split output on a completed event; other events
+    /// are returned unchanged.
+    #[must_use]
+    pub fn with_outputs(self, outputs: Vec<String>) -> Self {
+        match self {
+            Self::Completed(payload) => Self::Completed(CompletedPayload { outputs, ..payload }),
+            event => event,
+        }
+    }
+
     #[must_use]
     pub fn error(id: impl Into<String>, error: impl Into<String>) -> Self {
+        Self::error_with_log_tail(id, error, Vec::new())
+    }
+
+    #[must_use]
+    pub fn error_with_log_tail(
+        id: impl Into<String>,
+        error: impl Into<String>,
+        log_tail: Vec<String>,
+    ) -> Self {
         Self::Error(ErrorPayload {
             id: id.into(),
             error: error.into(),
+            code: ConversionFailureKind::Unknown,
+            retryable: false,
+            detail: None,
+            log_tail,
+            log_path: None,
+        })
+    }
+
+    /// Reports a failed task with its classified error and captured log.
+    #[must_use]
+    pub fn failed(
+        id: impl Into<String>,
+        error: &ConversionError,
+        log_tail: Vec<String>,
+        log_path: Option<String>,
+    ) -> Self {
+        Self::Error(ErrorPayload {
+            id: id.into(),
+            error: error.to_string(),
+            code: error.failure_kind(),
+            retryable: error.failure_kind().is_retryable(),
+            detail: Some(error.detail()),
+            log_tail,
+            log_path,
         })
     }
 
@@ -54,9 +151,32 @@ impl ConversionEvent {
         Self::Log(LogPayload {
             id: id.into(),
             line: line.into(),
+            phase: None,
+        })
+    }
+
+    #[must_use]
+    pub fn log_batch(id: impl Into<String>, lines: Vec<String>) -> Self {
+        Self::LogBatch(LogBatchPayload {
+            id: id.into(),
+            lines,
+            phase: None,
         })
     }
 
+    /// Tags progress and log events with the task stage they came from;
+    /// other events are returned unchanged.
+    #[must_use]
+    pub fn with_phase(self, phase: ConversionPhase) -> Self {
+        let phase = Some(phase);
+        match self {
+            Self::Progress(payload) => Self::Progress(ProgressPayload { phase, ..payload }),
+            Self::Log(payload) => Self::Log(LogPayload { phase, ..payload }),
+            Self::LogBatch(payload) => Self::LogBatch(LogBatchPayload { phase, ..payload }),
+            event => event,
+        }
+    }
+
     #[must_use]
     pub fn cancelled(id: impl Into<String>) -> Self {
         Self::Cancelled(CancelledPayload { id: id.into() })
@@ -70,7 +190,9 @@ impl ConversionEvent {
             Self::Completed(_) => CONVERSION_COMPLETED_EVENT,
             Self::Error(_) => CONVERSION_ERROR_EVENT,
             Self::Log(_) => CONVERSION_LOG_EVENT,
+            Self::LogBatch(_) => CONVERSION_LOG_BATCH_EVENT,
             Self::Cancelled(_) => CONVERSION_CANCELLED_EVENT,
+            Self::Warning(_) => CONVERSION_WARNING_EVENT,
         }
     }
 
@@ -82,7 +204,96 @@ impl ConversionEvent {
             Self::Completed(payload) => &payload.id,
             Self::Error(payload) => &payload.id,
             Self::Log(payload) => &payload.id,
+            Self::LogBatch(payload) => &payload.id,
             Self::Cancelled(payload) => &payload.id,
+            Self::Warning(payload) => &payload.id,
+        }
+    }
+}
+
+/// Rate-limits progress events and batches log lines for a single task.
+///
+/// Lifecycle events flush everything buffered before them, and callers must
+/// call [`Self::flush`] once the process exits so trailing output is kept.
+#[derive(Debug, Default)]
+pub struct ConversionEventCoalescer {
+    last_progress_at: Option<Instant>,
+    pending_progress: Option<ProgressPayload>,
+    log_batch_id: Option<String>,
+    log_batch_phase: Option<ConversionPhase>,
+    log_lines: Vec<String>,
+    log_batch_started_at: Option<Instant>,
+}
+
+impl ConversionEventCoalescer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(
+        &mut self,
+        event: ConversionEvent,
+        now: Instant,
+        emit: &mut impl FnMut(ConversionEvent),
+    ) {
+        match event {
+            ConversionEvent::Progress(payload) => {
+                let is_due = self
+                    .last_progress_at
+                    .is_none_or(|last| now.duration_since(last) >= PROGRESS_EVENT_INTERVAL);
+                if is_due {
+                    self.pending_progress = None;
+                    self.last_progress_at = Some(now);
+                    emit(ConversionEvent::Progress(payload));
+                } else {
+                    self.pending_progress = Some(payload);
+                }
+            }
+            ConversionEvent::Log(payload) => {
+                if self
+                    .log_batch_id
+                    .as_ref()
+                    .is_some_and(|id| *id != payload.id || self.log_batch_phase != payload.phase)
+                {
+                    self.flush_logs(emit);
+                }
+                self.log_batch_id.get_or_insert(payload.id);
+                self.log_batch_phase = payload.phase;
+                self.log_lines.push(payload.line);
+                let started_at = *self.log_batch_started_at.get_or_insert(now);
+                if self.log_lines.len() >= LOG_BATCH_MAX_LINES
+                    || now.duration_since(started_at) >= LOG_BATCH_INTERVAL
+                {
+                    self.flush_logs(emit);
+                }
+            }
+            event => {
+                self.flush(emit);
+                emit(event);
+            }
+        }
+    }
+
+    /// Emits any buffered log lines and the latest held-back progress value.
+    pub fn flush(&mut self, emit: &mut impl FnMut(ConversionEvent)) {
+        self.flush_logs(emit);
+        if let Some(payload) = self.pending_progress.take() {
+            emit(ConversionEvent::Progress(payload));
+        }
+    }
+
+    fn flush_logs(&mut self, emit: &mut impl FnMut(ConversionEvent)) {
+        self.log_batch_started_at = None;
+        let Some(id) = self.log_batch_id.take() else {
+            return;
+        };
+        if !self.log_lines.is_empty() {
+            emit(ConversionEvent::LogBatch(LogBatchPayload {
+                id,
+                lines: std::mem::take(&mut self.log_lines),
+                phase: self.log_batch_phase,
+            }));
         }
     }
 }
@@ -142,10 +353,278 @@ mod tests {
             ConversionEvent::Error(ErrorPayload {
                 id: "task-3".to_string(),
                 error: "ffmpeg failed".to_string(),
+                code: ConversionFailureKind::Unknown,
+                retryable: false,
+                detail: None,
+                log_tail: Vec::new(),
+                log_path: None,
+            })
+        );
+    }
+
+    #[test]
+    fn failed_event_carries_the_classified_failure_code() {
+        let error = ConversionError::Failed(crate::error::ConversionFailure {
+            kind: ConversionFailureKind::DiskFull,
+            details: vec!["No space left on device".to_string()],
+        });
+
+        let ConversionEvent::Error(payload) = ConversionEvent::failed(
+            "task-4",
+            &error,
+            vec!["No space left on device".to_string()],
+            None,
+        ) else {
+            unreachable!("failed builds an error event");
+        };
+
+        assert_eq!(
+            serde_json::to_value(payload).expect("error should serialize"),
+            serde_json::json!({
+                "id": "task-4",
+                "error": "The output drive is full: No space left on device",
+                "code": "disk_full",
+                "detail": {
+                    "code": "worker.disk_full",
+                    "message": "The output drive is full: No space left on device",
+                    "context": {"details": ["No space left on device"]}
+                },
+                "log_tail": ["No space left on device"]
             })
         );
     }
 
+    #[test]
+    fn coalescer_rate_limits_progress_and_flushes_final_value() {
+        let mut coalescer = ConversionEventCoalescer::new();
+        let mut events = Vec::new();
+        let start = Instant::now();
+
+        coalescer.push(
+            ConversionEvent::progress("task-5", 1.0),
+            start,
+            &mut |event| {
+                events.push(event);
+            },
+        );
+        coalescer.push(
+            ConversionEvent::progress("task-5", 2.0),
+            start + PROGRESS_EVENT_INTERVAL / 2,
+            &mut |event| events.push(event),
+        );
+        coalescer.push(
+            ConversionEvent::progress("task-5", 3.0),
+            start + PROGRESS_EVENT_INTERVAL / 2,
+            &mut |event| events.push(event),
+        );
+        coalescer.flush(&mut |event| events.push(event));
+
+        assert_eq!(
+            events,
+            [
+                ConversionEvent::progress("task-5", 1.0),
+                ConversionEvent::progress("task-5", 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_payload_serializes_stats_only_when_known() {
+        let payload = |event| match event {
+            ConversionEvent::Progress(payload) => {
+                serde_json::to_value(payload).expect("progress should serialize")
+            }
+            _ => unreachable!("progress constructors build progress events"),
+        };
+        let detailed = ConversionEvent::progress_with_stats(
+            "task-6",
+            25.0,
+            ProgressStats {
+                speed_x: Some(2.5),
+                processed_seconds: Some(12.0),
+                ..ProgressStats::default()
+            },
+        );
+
+        assert_eq!(
+            payload(ConversionEvent::progress("task-6", 10.0)),
+            serde_json::json!({ "id": "task-6", "progress": 10.0 })
+        );
+        assert_eq!(
+            payload(detailed),
+            serde_json::json!({
+                "id": "task-6",
+                "progress": 25.0,
+                "speedX": 2.5,
+                "processedSeconds": 12.0
+            })
+        );
+        assert_eq!(
+            payload(ConversionEvent::indeterminate_progress(
+                "task-6",
+                ProgressStats {
+                    processed_seconds: Some(90.0),
+                    written_bytes: Some(1_048_576),
+                    ..ProgressStats::default()
+                },
+            )),
+            serde_json::json!({
+                "id": "task-6",
+                "progress": 0.0,
+                "indeterminate": true,
+                "processedSeconds": 90.0,
+                "writtenBytes": 1_048_576
+            })
+        );
+    }
+
+    #[test]
+    fn coalescer_batches_logs_by_line_count() {
+        let mut coalescer = ConversionEventCoalescer::new();
+        let mut events = Vec::new();
+        let now = Instant::now();
+
+        for index in 0..=LOG_BATCH_MAX_LINES {
+            coalescer.push(
+                ConversionEvent::log("task-6", format!("line {index}")),
+                now,
+                &mut |event| events.push(event),
+            );
+        }
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            ConversionEvent::LogBatch(payload) if payload.lines.len() == LOG_BATCH_MAX_LINES
+        ));
+    }
+
+    #[test]
+    fn coalescer_flushes_logs_after_batch_interval() {
+        let mut coalescer = ConversionEventCoalescer::new();
+        let mut events = Vec::new();
+        let start = Instant::now();
+
+        coalescer.push(
+            ConversionEvent::log("task-7", "first"),
+            start,
+            &mut |event| {
+                events.push(event);
+            },
+        );
+        coalescer.push(
+            ConversionEvent::log("task-7", "second"),
+            start + LOG_BATCH_INTERVAL,
+            &mut |event| events.push(event),
+        );
+
+        assert_eq!(
+            events,
+            [ConversionEvent::log_batch(
+                "task-7",
+                vec!["first".to_string(), "second".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn coalescer_starts_a_new_log_batch_when_the_phase_changes() {
+        let mut coalescer = ConversionEventCoalescer::new();
+        let mut events = Vec::new();
+        let now = Instant::now();
+
+        for (line, phase) in [
+            ("scan", ConversionPhase::Analyze),
+            ("frame=1", ConversionPhase::Encode),
+            ("frame=2", ConversionPhase::Encode),
+        ] {
+            coalescer.push(
+                ConversionEvent::log("task-9", line).with_phase(phase),
+                now,
+                &mut |event| events.push(event),
+            );
+        }
+        coalescer.flush(&mut |event| events.push(event));
+
+        assert_eq!(
+            events,
+            [
+                ConversionEvent::log_batch("task-9", vec!["scan".to_string()])
+                    .with_phase(ConversionPhase::Analyze),
+                ConversionEvent::log_batch(
+                    "task-9",
+                    vec!["frame=1".to_string(), "frame=2".to_string()]
+                )
+                .with_phase(ConversionPhase::Encode),
+            ]
+        );
+    }
+
+    #[test]
+    fn phase_is_serialized_only_when_tagged() {
+        let log = |event| match event {
+            ConversionEvent::Log(payload) => {
+                serde_json::to_value(payload).expect("log should serialize")
+            }
+            _ => unreachable!("log constructors build log events"),
+        };
+
+        assert_eq!(
+            log(ConversionEvent::log("task-10", "line")),
+            serde_json::json!({ "id": "task-10", "line": "line" })
+        );
+        assert_eq!(
+            log(ConversionEvent::log("task-10", "line").with_phase(ConversionPhase::Encode)),
+            serde_json::json!({ "id": "task-10", "line": "line", "phase": "encode" })
+        );
+        assert_eq!(
+            ConversionEvent::cancelled("task-10").with_phase(ConversionPhase::Encode),
+            ConversionEvent::cancelled("task-10")
+        );
+    }
+
+    #[test]
+    fn coalescer_flushes_buffered_output_before_terminal_events() {
+        let mut coalescer = ConversionEventCoalescer::new();
+        let mut events = Vec::new();
+        let start = Instant::now();
+
+        coalescer.push(
+            ConversionEvent::progress("task-8", 10.0),
+            start,
+            &mut |event| {
+                events.push(event);
+            },
+        );
+        coalescer.push(
+            ConversionEvent::progress("task-8", 99.0),
+            start,
+            &mut |event| {
+                events.push(event);
+            },
+        );
+        coalescer.push(
+            ConversionEvent::log("task-8", "last line"),
+            start,
+            &mut |event| {
+                events.push(event);
+            },
+        );
+        coalescer.push(ConversionEvent::cancelled("task-8"), start, &mut |event| {
+            events.push(event);
+        });
+
+        assert_eq!(
+            events,
+            [
+                ConversionEvent::progress("task-8", 10.0),
+                ConversionEvent::log_batch("task-8", vec!["last line".to_string()]),
+                ConversionEvent::progress("task-8", 99.0),
+                ConversionEvent::cancelled("task-8"),
+            ]
+        );
+    }
+
     #[test]
     fn conversion_event_sink_accepts_native_events() {
         let sink = CollectingSink::default();
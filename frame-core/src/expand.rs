@@ -0,0 +1,316 @@
+//! Expanding one source into several queue entries, one per chapter or per
+//! listed segment.
+//!
+//! Unlike a chapter split, which writes every part in one run with one set
+//! of settings, each entry is a separate task trimmed with a start and end
+//! time, so its settings can be changed before it is queued.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::ConversionError;
+use crate::segments::validate_trim_bounds;
+use crate::split::chapter_part_name;
+use crate::timecode::parse_time_at_frame_rate;
+use crate::types::{ConversionConfig, ProbeMetadata, TrimSegment};
+use crate::utils::format_time;
+
+/// What a source is expanded by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpandMode {
+    /// One entry per source chapter.
+    Chapters,
+    /// One entry per segment, in the order given.
+    Segments(Vec<TrimSegment>),
+}
+
+/// One suggested queue entry of an expanded source.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandedTask {
+    pub file_path: String,
+    /// The source settings trimmed to the entry's range.
+    pub config: ConversionConfig,
+    /// Output name without extension.
+    pub output_name: String,
+}
+
+/// Splits `file_path` into one trimmed task per chapter or segment.
+///
+/// Every entry starts from `config` with its other trims and splits cleared,
+/// and carries `hh:mm:ss.mmm` start and end times. Entries are named
+/// `{stem} - {index:02}` after the source, followed by the chapter title
+/// when there is one.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the source has no
+/// chapters, a chapter or segment is empty or unreadable, or a range lies
+/// beyond the source duration.
+pub fn expand_to_tasks(
+    file_path: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+    mode: &ExpandMode,
+) -> Result<Vec<ExpandedTask>, ConversionError> {
+    let ranges = match mode {
+        ExpandMode::Chapters => chapter_ranges(probe)?,
+        ExpandMode::Segments(segments) => segment_ranges(segments, probe)?,
+    };
+    let stem = Path::new(file_path)
+        .file_stem()
+        .map_or_else(|| "output".into(), |stem| stem.to_string_lossy());
+
+    ranges
+        .into_iter()
+        .zip(1_usize..)
+        .map(|((start, end, title), index)| {
+            let mut entry = config.clone();
+            entry.start_time = Some(format_time(start));
+            entry.end_time = Some(format_time(end));
+            entry.segments.clear();
+            entry.remove_segments.clear();
+            entry.split = None;
+            entry.split_by_chapters = false;
+            if let Some(duration) = probe.duration_seconds() {
+                validate_trim_bounds(&entry, duration)?;
+            }
+            Ok(ExpandedTask {
+                file_path: file_path.to_string(),
+                config: entry,
+                output_name: chapter_part_name(&stem, index, title),
+            })
+        })
+        .collect()
+}
+
+type Range<'a> = (f64, f64, Option<&'a str>);
+
+fn chapter_ranges(probe: &ProbeMetadata) -> Result<Vec<Range<'_>>, ConversionError> {
+    if probe.chapters.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "The source has no chapters to expand; list the segments instead".to_string(),
+        ));
+    }
+    probe
+        .chapters
+        .iter()
+        .map(|chapter| {
+            if chapter.end_seconds <= chapter.start_seconds {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Chapter {} ends before it starts",
+                    chapter.id
+                )));
+            }
+            Ok((
+                chapter.start_seconds,
+                chapter.end_seconds,
+                chapter.title.as_deref(),
+            ))
+        })
+        .collect()
+}
+
+fn segment_ranges<'a>(
+    segments: &[TrimSegment],
+    probe: &ProbeMetadata,
+) -> Result<Vec<Range<'a>>, ConversionError> {
+    if segments.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "No segments were given to expand".to_string(),
+        ));
+    }
+    segments
+        .iter()
+        .zip(1_usize..)
+        .map(|(segment, index)| {
+            let time = |value: &str, label: &str| {
+                parse_time_at_frame_rate(value, probe.frame_rate)
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or_else(|| {
+                        ConversionError::InvalidInput(format!(
+                            "Segment {index} {label} time '{value}' is not a valid time"
+                        ))
+                    })
+            };
+            let start = time(&segment.start, "start")?;
+            let end = time(&segment.end, "end")?;
+            if end <= start {
+                return Err(ConversionError::InvalidInput(format!(
+                    "Segment {index} ends before it starts"
+                )));
+            }
+            Ok((start, end, None))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Chapter, sample_config};
+
+    fn chapter(id: i64, start_seconds: f64, end_seconds: f64, title: Option<&str>) -> Chapter {
+        Chapter {
+            id,
+            start_seconds,
+            end_seconds,
+            title: title.map(str::to_string),
+        }
+    }
+
+    fn lecture_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("5400.0".to_string()),
+            frame_rate: Some(25.0),
+            chapters: vec![
+                chapter(0, 0.0, 1800.5, Some("Intro: Setup")),
+                chapter(1, 1800.5, 3600.0, None),
+                chapter(2, 3600.0, 5400.0, Some("Q&A")),
+            ],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    fn segment(start: &str, end: &str) -> TrimSegment {
+        TrimSegment {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn chapters_expand_into_trimmed_and_named_entries() {
+        let mut config = sample_config("mkv", "libx264");
+        config.split_by_chapters = true;
+        config.start_time = Some("10".to_string());
+
+        let entries = expand_to_tasks(
+            "/media/lecture.mkv",
+            &config,
+            &lecture_probe(),
+            &ExpandMode::Chapters,
+        )
+        .expect("chapters should expand");
+
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.config.start_time.as_deref(),
+                    entry.config.end_time.as_deref(),
+                    entry.output_name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    Some("00:00:00.000"),
+                    Some("00:30:00.500"),
+                    "lecture - 01 Intro_ Setup"
+                ),
+                (Some("00:30:00.500"), Some("01:00:00.000"), "lecture - 02"),
+                (
+                    Some("01:00:00.000"),
+                    Some("01:30:00.000"),
+                    "lecture - 03 Q&A"
+                ),
+            ]
+        );
+        assert!(entries.iter().all(|entry| {
+            entry.file_path == "/media/lecture.mkv" && !entry.config.split_by_chapters
+        }));
+    }
+
+    #[test]
+    fn segments_expand_in_order_with_frame_times_resolved() {
+        let mut config = sample_config("mkv", "libx264");
+        config.segments = vec![segment("0", "5")];
+
+        let entries = expand_to_tasks(
+            "/media/lecture.mkv",
+            &config,
+            &lecture_probe(),
+            &ExpandMode::Segments(vec![
+                segment("1:00", "1:30.25"),
+                segment("250f", "00:00:20:05"),
+            ]),
+        )
+        .expect("segments should expand");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].config.start_time.as_deref(),
+            Some("00:01:00.000")
+        );
+        assert_eq!(entries[0].config.end_time.as_deref(), Some("00:01:30.250"));
+        assert_eq!(
+            entries[1].config.start_time.as_deref(),
+            Some("00:00:10.000")
+        );
+        assert_eq!(entries[1].config.end_time.as_deref(), Some("00:00:20.200"));
+        assert_eq!(entries[1].output_name, "lecture - 02");
+        assert!(entries[0].config.segments.is_empty());
+    }
+
+    #[test]
+    fn expansion_rejects_sources_without_chapters_and_bad_segments() {
+        let mut no_chapters = lecture_probe();
+        no_chapters.chapters.clear();
+        let cases = [
+            (
+                expand_to_tasks(
+                    "a.mkv",
+                    &sample_config("mkv", "libx264"),
+                    &no_chapters,
+                    &ExpandMode::Chapters,
+                ),
+                "has no chapters to expand",
+            ),
+            (
+                expand_to_tasks(
+                    "a.mkv",
+                    &sample_config("mkv", "libx264"),
+                    &lecture_probe(),
+                    &ExpandMode::Segments(Vec::new()),
+                ),
+                "No segments were given",
+            ),
+            (
+                expand_to_tasks(
+                    "a.mkv",
+                    &sample_config("mkv", "libx264"),
+                    &lecture_probe(),
+                    &ExpandMode::Segments(vec![segment("abc", "10")]),
+                ),
+                "Segment 1 start time 'abc' is not a valid time",
+            ),
+            (
+                expand_to_tasks(
+                    "a.mkv",
+                    &sample_config("mkv", "libx264"),
+                    &lecture_probe(),
+                    &ExpandMode::Segments(vec![segment("0", "10"), segment("30", "20")]),
+                ),
+                "Segment 2 ends before it starts",
+            ),
+            (
+                expand_to_tasks(
+                    "a.mkv",
+                    &sample_config("mkv", "libx264"),
+                    &lecture_probe(),
+                    &ExpandMode::Segments(vec![segment("2:00:00", "2:10:00")]),
+                ),
+                "beyond the file duration",
+            ),
+        ];
+
+        for (result, expected) in cases {
+            let error = result.expect_err("expansion should fail").to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+}
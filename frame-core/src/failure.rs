@@ -0,0 +1,243 @@
+//! Classification of failed `FFmpeg` conversions from their exit and stderr.
+
+use crate::error::{ConversionFailure, ConversionFailureKind};
+
+/// Most stderr lines kept as details of a [`ConversionFailure`].
+pub const CONVERSION_FAILURE_DETAIL_LINES: usize = 3;
+
+/// Stderr fragments per failure kind, checked in order so that an explicit
+/// cause wins over the generic decode errors `FFmpeg` often prints alongside
+/// it.
+const CONVERSION_FAILURE_PATTERNS: &[(ConversionFailureKind, &[&str])] = &[
+    (
+        ConversionFailureKind::DiskFull,
+        &[
+            "no space left on device",
+            "not enough space on the disk",
+            "disk full",
+            "disk quota exceeded",
+        ],
+    ),
+    (
+        ConversionFailureKind::PermissionDenied,
+        &[
+            "permission denied",
+            "access is denied",
+            "operation not permitted",
+            "read-only file system",
+        ],
+    ),
+    (
+        ConversionFailureKind::EncoderMissing,
+        &[
+            "unknown encoder",
+            "encoder not found",
+            "not found for output stream",
+            "error selecting an encoder",
+            "automatic encoder selection failed",
+            "cannot load",
+        ],
+    ),
+    (
+        ConversionFailureKind::Killed,
+        &["received signal", "killed"],
+    ),
+    (
+        ConversionFailureKind::Network,
+        &[
+            "connection reset",
+            "connection refused",
+            "connection timed out",
+            "network is unreachable",
+            "no route to host",
+            "failed to resolve hostname",
+            "temporary failure in name resolution",
+            "stream ends prematurely",
+            "error in the pull function",
+            "server returned 5",
+        ],
+    ),
+    (
+        ConversionFailureKind::InvalidData,
+        &[
+            "invalid data found when processing input",
+            "error while decoding",
+            "moov atom not found",
+            "corrupt",
+            "truncat",
+        ],
+    ),
+];
+
+/// Categorizes a failed `FFmpeg` run from the trailing lines of its output,
+/// keeping the lines that identify the cause.
+///
+/// `killed` marks a process terminated by a signal, which takes precedence
+/// over its output. `exit_status` describes the process exit and is reported
+/// when no output was captured.
+#[must_use]
+pub fn classify_conversion_failure(
+    lines: &[String],
+    exit_status: &str,
+    killed: bool,
+) -> ConversionFailure {
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if killed {
+        return ConversionFailure {
+            kind: ConversionFailureKind::Killed,
+            details: vec![format!("ffmpeg exited with status {exit_status}")],
+        };
+    }
+
+    for (kind, patterns) in CONVERSION_FAILURE_PATTERNS {
+        let matching: Vec<String> = lines
+            .iter()
+            .filter(|line| {
+                let line = line.to_ascii_lowercase();
+                patterns.iter().any(|pattern| line.contains(pattern))
+            })
+            .take(CONVERSION_FAILURE_DETAIL_LINES)
+            .map(|line| (*line).to_string())
+            .collect();
+        if !matching.is_empty() {
+            return ConversionFailure {
+                kind: *kind,
+                details: matching,
+            };
+        }
+    }
+
+    let details = if lines.is_empty() {
+        vec![format!("ffmpeg exited with status {exit_status}")]
+    } else {
+        lines[lines.len().saturating_sub(CONVERSION_FAILURE_DETAIL_LINES)..]
+            .iter()
+            .map(|line| (*line).to_string())
+            .collect()
+    };
+    ConversionFailure {
+        kind: ConversionFailureKind::Unknown,
+        details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(output: &str) -> ConversionFailure {
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        classify_conversion_failure(&lines, "exit status: 1", false)
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_disk_full() {
+        let failure = classify(
+            "frame=  120 fps= 60 q=28.0 size=  2048kB time=00:00:04.00\n\
+             [mp4 @ 0x6000] Error writing trailer: No space left on device\n\
+             Error closing file out.mp4: No space left on device\n",
+        );
+
+        assert_eq!(failure.kind, ConversionFailureKind::DiskFull);
+        assert_eq!(
+            failure.details,
+            [
+                "[mp4 @ 0x6000] Error writing trailer: No space left on device",
+                "Error closing file out.mp4: No space left on device"
+            ]
+        );
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_permission_denied() {
+        let failure = classify("/Volumes/Locked/out.mp4: Permission denied\n");
+
+        assert_eq!(failure.kind, ConversionFailureKind::PermissionDenied);
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_missing_encoder() {
+        let unknown = classify("Unknown encoder 'libsvtav1'\n");
+        let unavailable = classify(
+            "[vost#0:0 @ 0x6000] Encoder (codec hevc) not found for output stream\n\
+             Error selecting an encoder\n",
+        );
+
+        assert_eq!(unknown.kind, ConversionFailureKind::EncoderMissing);
+        assert_eq!(unavailable.kind, ConversionFailureKind::EncoderMissing);
+        assert_eq!(unavailable.details.len(), 2);
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_invalid_data() {
+        let failure = classify(
+            "[h264 @ 0x6000] error while decoding MB 12 4, bytestream -7\n\
+             /tmp/broken.mp4: Invalid data found when processing input\n",
+        );
+
+        assert_eq!(failure.kind, ConversionFailureKind::InvalidData);
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_dropped_network_sources() {
+        let failure = classify(
+            "[https @ 0x6000] Stream ends prematurely at 5242880, should be 73400320\n\
+             [aac @ 0x6100] Input buffer exhausted before END element found\n\
+             Error while decoding stream #0:0: Invalid data found when processing input\n",
+        );
+        let reset = classify("[tcp @ 0x6000] Connection reset by peer\n");
+
+        assert_eq!(failure.kind, ConversionFailureKind::Network);
+        assert_eq!(
+            failure.details,
+            ["[https @ 0x6000] Stream ends prematurely at 5242880, should be 73400320"]
+        );
+        assert_eq!(reset.kind, ConversionFailureKind::Network);
+        assert!(reset.kind.is_retryable());
+        assert!(!ConversionFailureKind::InvalidData.is_retryable());
+    }
+
+    #[test]
+    fn classify_conversion_failure_detects_killed_processes() {
+        let by_signal = classify_conversion_failure(&[], "signal: 9 (SIGKILL)", true);
+        let by_output = classify("Exiting normally, received signal 15.\n");
+
+        assert_eq!(by_signal.kind, ConversionFailureKind::Killed);
+        assert_eq!(
+            by_signal.details,
+            ["ffmpeg exited with status signal: 9 (SIGKILL)"]
+        );
+        assert_eq!(by_output.kind, ConversionFailureKind::Killed);
+    }
+
+    #[test]
+    fn classify_conversion_failure_falls_back_to_trailing_lines() {
+        let failure = classify("one\ntwo\nthree\nConversion failed!\n");
+        let silent = classify_conversion_failure(&[], "exit status: 1", false);
+
+        assert_eq!(failure.kind, ConversionFailureKind::Unknown);
+        assert_eq!(failure.details, ["two", "three", "Conversion failed!"]);
+        assert_eq!(silent.details, ["ffmpeg exited with status exit status: 1"]);
+        assert_eq!(
+            failure.to_string(),
+            "FFmpeg could not convert this file: two; three; Conversion failed!"
+        );
+    }
+
+    #[test]
+    fn failure_code_serializes_in_snake_case() {
+        let failure = classify("Unknown encoder 'libsvtav1'\n");
+
+        assert_eq!(
+            serde_json::to_value(&failure).expect("failure should serialize"),
+            serde_json::json!({
+                "code": "encoder_missing",
+                "details": ["Unknown encoder 'libsvtav1'"]
+            })
+        );
+    }
+}
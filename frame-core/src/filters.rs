@@ -1,14 +1,21 @@
 use crate::{
+    concat::ConcatPlan,
     media_filters::{
         build_audio_effect_filters, build_video_post_scale_filters, build_video_pre_scale_filters,
     },
     media_rules::is_image_container,
+    segments::TrimRange,
     types::ConversionConfig,
 };
 
 pub const EVEN_DIMENSIONS_FILTER: &str = "pad=ceil(iw/2)*2:ceil(ih/2)*2:0:0";
+/// Converts Display P3 pixels, which phone cameras shoot, to sRGB so stills
+/// look the same in viewers that ignore color profiles.
+pub const DISPLAY_P3_TO_SRGB_FILTER: &str = "colorspace=all=bt709:trc=srgb";
 pub const PREVIEW_OUTPUT_LABEL: &str = "preview_v";
 pub const VIDEO_OUTPUT_LABEL: &str = "vout";
+const SOURCE_VIDEO_LABEL: &str = "0:V:0";
+const SEGMENT_VIDEO_LABEL: &str = "vcat";
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum VisualFilterBase {
@@ -171,6 +178,9 @@ fn build_resolution_scale_filter(config: &ConversionConfig) -> String {
         let height = config.custom_height.as_deref().unwrap_or("-1");
         return custom_resolution_scale_filter(width, height, algorithm);
     }
+    if config.resolution == "fit" {
+        return fit_resolution_scale_filter(config, algorithm);
+    }
 
     match config.resolution.as_str() {
         "1080p" => format!("scale=-2:1080{algorithm}"),
@@ -192,6 +202,25 @@ fn custom_resolution_scale_filter(width: &str, height: &str, algorithm: &str) ->
     }
 }
 
+/// Shrinks the frame into the custom width and height, either of which may
+/// be left out, keeping its aspect ratio. Smaller frames are left as they are.
+fn fit_resolution_scale_filter(config: &ConversionConfig, algorithm: &str) -> String {
+    let bound = |value: Option<&str>, source: &str| {
+        value
+            .map(str::trim)
+            .filter(|value| !value.is_empty() && *value != "-1")
+            .map_or_else(
+                || source.to_string(),
+                |value| format!("'min({value},{source})'"),
+            )
+    };
+    format!(
+        "scale={}:{}:force_original_aspect_ratio=decrease{algorithm}",
+        bound(config.custom_width.as_deref(), "iw"),
+        bound(config.custom_height.as_deref(), "ih")
+    )
+}
+
 #[must_use]
 pub fn build_encode_video_filters(config: &ConversionConfig, include_scale: bool) -> Vec<String> {
     let mut filters = build_video_filters(config, include_scale);
@@ -218,7 +247,7 @@ pub fn build_visual_filter_chain(
                 VisualFilterBase::Video => build_encode_video_filters(config, true),
                 VisualFilterBase::Image => build_video_filters(config, true),
             };
-            apply_preview_subtitle_timebase(&mut filters, source_time_seconds);
+            rebase_subtitle_timestamps(&mut filters, source_time_seconds);
             filters.extend(preview_low_res_filters(width, height, fps));
             filters
         }
@@ -234,6 +263,7 @@ pub fn build_visual_filter_complex(
         VisualFilterProfile::ExportVideo | VisualFilterProfile::ExportImage => {
             build_export_filter_complex(
                 config,
+                SOURCE_VIDEO_LABEL,
                 &build_visual_filter_chain(config, profile),
                 VIDEO_OUTPUT_LABEL,
             )
@@ -259,25 +289,62 @@ pub fn has_overlay(config: &ConversionConfig) -> bool {
 #[must_use]
 pub fn build_overlay_filter_complex(config: &ConversionConfig) -> String {
     let filters = build_video_filters(config, true);
-    build_overlay_filter_complex_with_filters(config, &filters, VIDEO_OUTPUT_LABEL)
+    build_overlay_filter_complex_with_filters(
+        config,
+        SOURCE_VIDEO_LABEL,
+        &filters,
+        VIDEO_OUTPUT_LABEL,
+    )
+}
+
+/// Encode filters for a source an input seek moved to `pre_seek_seconds`,
+/// with burned subtitles kept on source time.
+#[must_use]
+pub fn build_pre_seeked_encode_video_filters(
+    config: &ConversionConfig,
+    pre_seek_seconds: f64,
+) -> Vec<String> {
+    let mut filters = build_encode_video_filters(config, true);
+    rebase_subtitle_timestamps(&mut filters, pre_seek_seconds);
+    filters
+}
+
+/// Overlay graph counterpart of [`build_pre_seeked_encode_video_filters`].
+#[must_use]
+pub fn build_pre_seeked_encode_overlay_filter_complex(
+    config: &ConversionConfig,
+    pre_seek_seconds: f64,
+) -> String {
+    build_overlay_filter_complex_with_filters(
+        config,
+        SOURCE_VIDEO_LABEL,
+        &build_pre_seeked_encode_video_filters(config, pre_seek_seconds),
+        VIDEO_OUTPUT_LABEL,
+    )
 }
 
 #[must_use]
 pub fn build_encode_overlay_filter_complex(config: &ConversionConfig) -> String {
     let filters = build_encode_video_filters(config, true);
-    build_overlay_filter_complex_with_filters(config, &filters, VIDEO_OUTPUT_LABEL)
+    build_overlay_filter_complex_with_filters(
+        config,
+        SOURCE_VIDEO_LABEL,
+        &filters,
+        VIDEO_OUTPUT_LABEL,
+    )
 }
 
 fn build_overlay_filter_complex_with_filters(
     config: &ConversionConfig,
+    input_label: &str,
     filters: &[String],
     output_label: &str,
 ) -> String {
     let Some(overlay) = &config.overlay else {
-        return labeled_filter_chain(filters, output_label);
+        return labeled_filter_chain(input_label, filters, output_label);
     };
 
-    let base_chain = labeled_filter_chain(filters, "base");
+    let base_chain = labeled_filter_chain(input_label, filters, "base");
     let x = overlay.x.clamp(0.0, 1.0);
     let y = overlay.y.clamp(0.0, 1.0);
     let width = overlay.width.clamp(0.03, 0.8);
@@ -290,16 +357,166 @@ fn build_overlay_filter_complex_with_filters(
 
 fn build_export_filter_complex(
     config: &ConversionConfig,
+    input_label: &str,
     filters: &[String],
     output_label: &str,
 ) -> String {
     if has_overlay(config) {
-        build_overlay_filter_complex_with_filters(config, filters, output_label)
+        build_overlay_filter_complex_with_filters(config, input_label, filters, output_label)
     } else {
-        labeled_filter_chain(filters, output_label)
+        labeled_filter_chain(input_label, filters, output_label)
     }
 }
 
+/// Builds the encode graph of a task keeping several ranges.
+///
+/// Each range of the video and of the audio streams in `audio_streams` is trimmed and
+/// rebased to zero, the pieces are concatenated in order, and the joined
+/// streams run through the usual encode filters. Video leaves on
+/// [`VIDEO_OUTPUT_LABEL`] and audio stream `n` on `aout{n}`.
+#[must_use]
+pub fn build_segment_concat_filter_complex(
+    config: &ConversionConfig,
+    ranges: &[TrimRange],
+    include_video: bool,
+    audio_streams: &[u32],
+) -> String {
+    let mut graph = Vec::new();
+    let mut concat_inputs = Vec::new();
+    for (segment, range) in ranges.iter().enumerate() {
+        let bounds = range.end.map_or_else(
+            || format!("start={:.3}", range.start),
+            |end| format!("start={:.3}:end={end:.3}", range.start),
+        );
+        if include_video {
+            graph.push(format!(
+                "[{SOURCE_VIDEO_LABEL}]trim={bounds},setpts=PTS-STARTPTS[v{segment}]"
+            ));
+            concat_inputs.push(format!("[v{segment}]"));
+        }
+        for (track, stream) in audio_streams.iter().enumerate() {
+            graph.push(format!(
+                "[0:{stream}]atrim={bounds},asetpts=PTS-STARTPTS[a{segment}_{track}]"
+            ));
+            concat_inputs.push(format!("[a{segment}_{track}]"));
+        }
+    }
+
+    let mut concat_outputs = Vec::new();
+    if include_video {
+        concat_outputs.push(format!("[{SEGMENT_VIDEO_LABEL}]"));
+    }
+    concat_outputs.extend((0..audio_streams.len()).map(|track| format!("[acat{track}]")));
+    graph.push(format!(
+        "{}concat=n={}:v={}:a={}{}",
+        concat_inputs.concat(),
+        ranges.len(),
+        u8::from(include_video),
+        audio_streams.len(),
+        concat_outputs.concat()
+    ));
+
+    if include_video {
+        graph.push(build_export_filter_complex(
+            config,
+            SEGMENT_VIDEO_LABEL,
+            &build_encode_video_filters(config, true),
+            VIDEO_OUTPUT_LABEL,
+        ));
+    }
+    let audio_filters = build_audio_filters(config);
+    let audio_chain = if audio_filters.is_empty() {
+        "anull".to_string()
+    } else {
+        audio_filters.join(",")
+    };
+    for track in 0..audio_streams.len() {
+        graph.push(format!("[acat{track}]{audio_chain}[aout{track}]"));
+    }
+    graph.join(";")
+}
+
+/// Builds the encode graph joining several sources.
+///
+/// Each source is scaled and padded to the plan's size and frame rate and
+/// its audio resampled to the plan's format, with silence for sources
+/// without audio. The joined video runs through the usual encode filters
+/// and leaves on [`VIDEO_OUTPUT_LABEL`]; audio leaves on `aout0`.
+#[must_use]
+pub fn build_source_concat_filter_complex(config: &ConversionConfig, plan: &ConcatPlan) -> String {
+    let mut graph = Vec::new();
+    let mut concat_inputs = Vec::new();
+    for (input, source) in plan.sources.iter().enumerate() {
+        if let Some((width, height)) = plan.size {
+            let fps = plan
+                .frame_rate
+                .map(|frame_rate| format!(",fps={frame_rate}"))
+                .unwrap_or_default();
+            graph.push(format!(
+                "[{input}:V:0]scale={width}:{height}:force_original_aspect_ratio=decrease,\
+                 pad={width}:{height}:(ow-iw)/2:(oh-ih)/2,setsar=1{fps}[v{input}]"
+            ));
+            concat_inputs.push(format!("[v{input}]"));
+        }
+        if let Some(audio) = &plan.audio {
+            let rate = audio.sample_rate;
+            let layout = &audio.channel_layout;
+            graph.push(source.audio_stream.map_or_else(
+                || {
+                    format!(
+                        "anullsrc=channel_layout={layout}:sample_rate={rate},\
+                         atrim=duration={:.3}[a{input}]",
+                        source.duration.unwrap_or(0.0)
+                    )
+                },
+                |stream| {
+                    format!(
+                        "[{input}:{stream}]aresample={rate},\
+                         aformat=sample_rates={rate}:channel_layouts={layout}[a{input}]"
+                    )
+                },
+            ));
+            concat_inputs.push(format!("[a{input}]"));
+        }
+    }
+
+    let include_video = plan.size.is_some();
+    let include_audio = plan.audio.is_some();
+    let mut concat_outputs = Vec::new();
+    if include_video {
+        concat_outputs.push(format!("[{SEGMENT_VIDEO_LABEL}]"));
+    }
+    if include_audio {
+        concat_outputs.push("[acat0]".to_string());
+    }
+    graph.push(format!(
+        "{}concat=n={}:v={}:a={}{}",
+        concat_inputs.concat(),
+        plan.sources.len(),
+        u8::from(include_video),
+        u8::from(include_audio),
+        concat_outputs.concat()
+    ));
+
+    if include_video {
+        graph.push(labeled_filter_chain(
+            SEGMENT_VIDEO_LABEL,
+            &build_encode_video_filters(config, true),
+            VIDEO_OUTPUT_LABEL,
+        ));
+    }
+    if include_audio {
+        let audio_filters = build_audio_filters(config);
+        let audio_chain = if audio_filters.is_empty() {
+            "anull".to_string()
+        } else {
+            audio_filters.join(",")
+        };
+        graph.push(format!("[acat0]{audio_chain}[aout0]"));
+    }
+    graph.join(";")
+}
+
 fn build_preview_filter_complex(
     config: &ConversionConfig,
     base: VisualFilterBase,
@@ -312,22 +529,28 @@ fn build_preview_filter_complex(
         VisualFilterBase::Video => build_encode_video_filters(config, true),
         VisualFilterBase::Image => build_video_filters(config, true),
     };
-    apply_preview_subtitle_timebase(&mut base_filters, source_time_seconds);
+    rebase_subtitle_timestamps(&mut base_filters, source_time_seconds);
     let preview_filters = preview_low_res_filters(width, height, fps);
     let preview_chain = preview_filters.join(",");
 
     if has_overlay(config) {
-        let graph =
-            build_overlay_filter_complex_with_filters(config, &base_filters, "preview_export");
+        let graph = build_overlay_filter_complex_with_filters(
+            config,
+            SOURCE_VIDEO_LABEL,
+            &base_filters,
+            "preview_export",
+        );
         format!("{graph};[preview_export]{preview_chain}[{PREVIEW_OUTPUT_LABEL}]")
     } else {
         let mut filters = base_filters;
         filters.extend(preview_filters);
-        labeled_filter_chain(&filters, PREVIEW_OUTPUT_LABEL)
+        labeled_filter_chain(SOURCE_VIDEO_LABEL, &filters, PREVIEW_OUTPUT_LABEL)
     }
 }
 
-fn apply_preview_subtitle_timebase(filters: &mut Vec<String>, source_time_seconds: f64) {
+/// Shifts frames back onto source time around the `subtitles` filter, for
+/// video whose timestamps an input seek to `source_time_seconds` reset.
+fn rebase_subtitle_timestamps(filters: &mut Vec<String>, source_time_seconds: f64) {
     if !source_time_seconds.is_finite() || source_time_seconds <= 0.0 {
         return;
     }
@@ -344,11 +567,11 @@ fn apply_preview_subtitle_timebase(filters: &mut Vec<String>, source_time_second
     filters.insert(subtitle_index + 2, format!("setpts=PTS-{offset}/TB"));
 }
 
-fn labeled_filter_chain(filters: &[String], output_label: &str) -> String {
+fn labeled_filter_chain(input_label: &str, filters: &[String], output_label: &str) -> String {
     if filters.is_empty() {
-        format!("[0:v:0]null[{output_label}]")
+        format!("[{input_label}]null[{output_label}]")
     } else {
-        format!("[0:v:0]{}[{output_label}]", filters.join(","))
+        format!("[{input_label}]{}[{output_label}]", filters.join(","))
     }
 }
 
@@ -373,7 +596,7 @@ pub fn build_audio_filters(config: &ConversionConfig) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{CropConfig, MetadataConfig, OverlayConfig};
+    use crate::types::{CropConfig, MetadataConfig, OverlayConfig, OverwritePolicy, SeekMode};
 
     fn default_config() -> ConversionConfig {
         ConversionConfig {
@@ -404,11 +627,14 @@ mod tests {
             custom_height: None,
             scaling_algorithm: "lanczos".to_string(),
             fps: "original".to_string(),
+            input_framerate: None,
             crf: 23,
             quality: 50,
             preset: "medium".to_string(),
             start_time: None,
             end_time: None,
+            segments: Vec::new(),
+            remove_segments: Vec::new(),
             metadata: MetadataConfig::default(),
             rotation: "0".to_string(),
             flip_horizontal: false,
@@ -419,6 +645,7 @@ mod tests {
             nvenc_temporal_aq: false,
             videotoolbox_allow_sw: false,
             hw_decode: false,
+            hw_device: None,
             pixel_format: "auto".to_string(),
             image_jpeg_quality: 85,
             image_jpeg_huffman: "optimal".to_string(),
@@ -429,9 +656,21 @@ mod tests {
             image_png_compression: 9,
             image_png_prediction: "paeth".to_string(),
             image_tiff_compression: "packbits".to_string(),
+            image_avif_crf: 30,
+            image_target_max_kb: None,
             gif_colors: 256,
             gif_dither: "sierra2_4a".to_string(),
             gif_loop: 0,
+            overwrite_policy: OverwritePolicy::default(),
+            split: None,
+            split_by_chapters: false,
+            image_sequence_output: false,
+            smart_cut: false,
+            seek_mode: SeekMode::default(),
+            parallel_chunks: None,
+            verify_output: false,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
         }
     }
 
@@ -502,6 +741,29 @@ mod tests {
         assert_eq!(filters, vec!["crop=101:201:10:20", EVEN_DIMENSIONS_FILTER]);
     }
 
+    #[test]
+    fn fit_resolution_shrinks_into_the_box_without_enlarging() {
+        let mut config = default_config();
+        config.resolution = "fit".to_string();
+        config.scaling_algorithm = "lanczos".to_string();
+        config.custom_width = Some("1600".to_string());
+
+        let width_only = build_video_filters(&config, true);
+        config.custom_height = Some("900".to_string());
+        let both = build_video_filters(&config, true);
+
+        assert_eq!(
+            width_only,
+            ["scale='min(1600,iw)':ih:force_original_aspect_ratio=decrease:flags=lanczos"]
+        );
+        assert_eq!(
+            both,
+            [
+                "scale='min(1600,iw)':'min(900,ih)':force_original_aspect_ratio=decrease:flags=lanczos"
+            ]
+        );
+    }
+
     #[test]
     fn test_overlay_filter_complex() {
         let mut config = default_config();
@@ -518,7 +780,7 @@ mod tests {
 
         let filter = build_overlay_filter_complex(&config);
 
-        assert!(filter.contains("[0:v:0]scale=-2:720:flags=lanczos[base]"));
+        assert!(filter.contains("[0:V:0]scale=-2:720:flags=lanczos[base]"));
         assert!(filter.contains("[1:v:0]format=rgba,colorchannelmixer=aa=0.750"));
         assert!(filter.contains("[base]split[base_ref][base_out]"));
         assert!(filter.contains("[overlay_src][base_ref]scale=w='min(rw*0.200000,rh*iw/ih)':h=-1"));
@@ -530,6 +792,55 @@ mod tests {
         assert!(filter.ends_with("[vout]"));
     }
 
+    #[test]
+    fn segment_concat_filter_complex_trims_each_range_before_encode_filters() {
+        let mut config = default_config();
+        config.resolution = "720p".to_string();
+        config.audio_volume = 150.0;
+        let ranges = [
+            TrimRange {
+                start: 0.0,
+                end: Some(312.0),
+            },
+            TrimRange {
+                start: 1060.0,
+                end: Some(1325.0),
+            },
+        ];
+
+        let graph = build_segment_concat_filter_complex(&config, &ranges, true, &[1, 2]);
+
+        assert!(graph.starts_with(
+            "[0:V:0]trim=start=0.000:end=312.000,setpts=PTS-STARTPTS[v0];[0:1]atrim=start=0.000:end=312.000,asetpts=PTS-STARTPTS[a0_0];"
+        ));
+        assert!(graph.contains(
+            "[v0][a0_0][a0_1][v1][a1_0][a1_1]concat=n=2:v=1:a=2[vcat][acat0][acat1];[vcat]scale=-2:720"
+        ));
+        assert!(graph.contains("[acat1]volume=1.500[aout1]"));
+        assert!(graph.contains("[vout]"));
+    }
+
+    #[test]
+    fn segment_concat_filter_complex_skips_video_for_audio_outputs() {
+        let ranges = [
+            TrimRange {
+                start: 5.0,
+                end: Some(10.0),
+            },
+            TrimRange {
+                start: 20.0,
+                end: Some(30.0),
+            },
+        ];
+
+        let graph = build_segment_concat_filter_complex(&default_config(), &ranges, false, &[1]);
+
+        assert_eq!(
+            graph,
+            "[0:1]atrim=start=5.000:end=10.000,asetpts=PTS-STARTPTS[a0_0];[0:1]atrim=start=20.000:end=30.000,asetpts=PTS-STARTPTS[a1_0];[a0_0][a1_0]concat=n=2:v=0:a=1[acat0];[acat0]anull[aout0]"
+        );
+    }
+
     #[test]
     fn encode_overlay_filter_complex_pads_base_before_overlay() {
         let mut config = default_config();
@@ -545,7 +856,7 @@ mod tests {
 
         let filter = build_encode_overlay_filter_complex(&config);
 
-        assert!(filter.contains("[0:v:0]pad=ceil(iw/2)*2:ceil(ih/2)*2:0:0[base]"));
+        assert!(filter.contains("[0:V:0]pad=ceil(iw/2)*2:ceil(ih/2)*2:0:0[base]"));
     }
 
     #[test]
@@ -0,0 +1,365 @@
+//! Enumeration and selection of the GPUs hardware encoders can bind to.
+
+use std::{fs, io, path::Path};
+
+use regex::Regex;
+
+use crate::{error::ConversionError, types::ConversionConfig, utils::is_nvenc_codec};
+
+/// Most DXGI adapter indices probed by [`dxgi_adapter_probe_args`].
+pub const MAX_DXGI_ADAPTERS: u32 = 8;
+
+const VAAPI_RENDER_NODE_PREFIX: &str = "renderD";
+
+/// API through which an encoder family addresses a device.
+#[derive(serde::Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HwDeviceKind {
+    /// CUDA devices used by NVENC, selected by index.
+    Cuda,
+    /// Linux DRM render nodes, selected by path.
+    Vaapi,
+    /// Windows DXGI adapters, selected by index.
+    Dxgi,
+}
+
+/// One device an encoder family can be bound to.
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HwDevice {
+    pub kind: HwDeviceKind,
+    pub index: u32,
+    pub name: String,
+    /// Device node of a VAAPI device, e.g. `/dev/dri/renderD128`.
+    pub path: Option<String>,
+}
+
+impl HwDevice {
+    /// Value stored in [`ConversionConfig::hw_device`] to select this device.
+    #[must_use]
+    pub fn config_value(&self) -> String {
+        self.path.clone().unwrap_or_else(|| self.index.to_string())
+    }
+}
+
+/// Device API of the encoder family of `video_codec`, or `None` when the
+/// encoder does not take an explicit device.
+#[must_use]
+pub fn hw_device_kind(video_codec: &str) -> Option<HwDeviceKind> {
+    if is_nvenc_codec(video_codec) {
+        return Some(HwDeviceKind::Cuda);
+    }
+    match video_codec.rsplit_once('_').map(|(_, family)| family) {
+        Some("vaapi") => Some(HwDeviceKind::Vaapi),
+        Some("qsv" | "amf" | "mf") => Some(HwDeviceKind::Dxgi),
+        _ => None,
+    }
+}
+
+/// Arguments that make NVENC print the CUDA devices it can use.
+#[must_use]
+pub fn nvenc_gpu_list_args() -> Vec<String> {
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-f",
+        "lavfi",
+        "-i",
+        "nullsrc=s=256x256",
+        "-frames:v",
+        "1",
+        "-c:v",
+        "h264_nvenc",
+        "-gpu",
+        "list",
+        "-f",
+        "null",
+        "-",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// Parses the `[ GPU #0 - < name > has Compute SM 8.6 ]` lines NVENC prints
+/// for `-gpu list`.
+#[must_use]
+pub fn parse_nvenc_gpu_list(ffmpeg_stderr: impl AsRef<str>) -> Vec<HwDevice> {
+    let Ok(row) = Regex::new(r"GPU #(\d+) - < (.+?) >") else {
+        return Vec::new();
+    };
+    ffmpeg_stderr
+        .as_ref()
+        .lines()
+        .filter_map(|line| {
+            let captures = row.captures(line)?;
+            Some(HwDevice {
+                kind: HwDeviceKind::Cuda,
+                index: captures.get(1)?.as_str().parse().ok()?,
+                name: captures.get(2)?.as_str().trim().to_string(),
+                path: None,
+            })
+        })
+        .collect()
+}
+
+/// Lists the `renderD*` nodes in `dri_directory`, usually `/dev/dri`, in
+/// node order. A missing directory has no devices.
+///
+/// # Errors
+///
+/// Returns an error when the directory exists but cannot be read.
+pub fn list_vaapi_render_nodes(dri_directory: &Path) -> io::Result<Vec<HwDevice>> {
+    let entries = match fs::read_dir(dri_directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut nodes = Vec::new();
+    for entry in entries {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(number) = name
+            .strip_prefix(VAAPI_RENDER_NODE_PREFIX)
+            .and_then(|number| number.parse::<u32>().ok())
+        {
+            nodes.push((number, name));
+        }
+    }
+    nodes.sort_unstable();
+    Ok(nodes
+        .into_iter()
+        .zip(0..)
+        .map(|((_, name), index)| HwDevice {
+            kind: HwDeviceKind::Vaapi,
+            index,
+            path: Some(dri_directory.join(&name).to_string_lossy().into_owned()),
+            name,
+        })
+        .collect())
+}
+
+/// Arguments that open DXGI adapter `index` through D3D11VA and log its
+/// description.
+#[must_use]
+pub fn dxgi_adapter_probe_args(index: u32) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-v".to_string(),
+        "verbose".to_string(),
+        "-init_hw_device".to_string(),
+        format!("d3d11va=frame:{index}"),
+        "-f".to_string(),
+        "lavfi".to_string(),
+        "-i".to_string(),
+        "nullsrc=s=16x16".to_string(),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reads the `Using device 10de:2504 (name).` line of a
+/// [`dxgi_adapter_probe_args`] run; `None` when the adapter does not exist.
+#[must_use]
+pub fn parse_dxgi_adapter(ffmpeg_stderr: impl AsRef<str>, index: u32) -> Option<HwDevice> {
+    let row = Regex::new(r"Using device [0-9a-fA-F]{4}:[0-9a-fA-F]{4} \((.+)\)").ok()?;
+    let name = ffmpeg_stderr
+        .as_ref()
+        .lines()
+        .find_map(|line| row.captures(line)?.get(1))?;
+    Some(HwDevice {
+        kind: HwDeviceKind::Dxgi,
+        index,
+        name: name.as_str().trim().to_string(),
+        path: None,
+    })
+}
+
+/// Global options, placed before `-i`, that bind the selected device of the
+/// task's encoder family. NVENC takes its device as an encoder option
+/// instead; see [`nvenc_gpu_args`].
+#[must_use]
+pub fn hw_device_input_args(config: &ConversionConfig) -> Vec<String> {
+    let Some(device) = config.hw_device.as_deref() else {
+        return Vec::new();
+    };
+    match hw_device_kind(&config.video_codec) {
+        Some(HwDeviceKind::Cuda) if config.hw_decode => {
+            vec!["-hwaccel_device".to_string(), device.to_string()]
+        }
+        Some(HwDeviceKind::Vaapi) => vec!["-vaapi_device".to_string(), device.to_string()],
+        Some(HwDeviceKind::Dxgi) => vec![
+            "-init_hw_device".to_string(),
+            format!("d3d11va=frame:{device}"),
+            "-filter_hw_device".to_string(),
+            "frame".to_string(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// The `-gpu` encoder option selecting the configured CUDA device.
+#[must_use]
+pub fn nvenc_gpu_args(config: &ConversionConfig) -> Vec<String> {
+    match config.hw_device.as_deref() {
+        Some(device) if hw_device_kind(&config.video_codec) == Some(HwDeviceKind::Cuda) => {
+            vec!["-gpu".to_string(), device.to_string()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Checks that the configured device is one of `devices`, the enumeration
+/// of this machine.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the task's encoder family
+/// has no device with the configured index or path.
+pub fn validate_hw_device(
+    config: &ConversionConfig,
+    devices: &[HwDevice],
+) -> Result<(), ConversionError> {
+    let (Some(device), Some(kind)) = (
+        config.hw_device.as_deref(),
+        hw_device_kind(&config.video_codec),
+    ) else {
+        return Ok(());
+    };
+    if devices
+        .iter()
+        .any(|candidate| candidate.kind == kind && candidate.config_value() == device)
+    {
+        return Ok(());
+    }
+    Err(ConversionError::InvalidInput(format!(
+        "Hardware device {device} is not available for {}",
+        config.video_codec
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn device_config(video_codec: &str, hw_device: Option<&str>) -> ConversionConfig {
+        let mut config = sample_config("mp4", video_codec);
+        config.hw_device = hw_device.map(str::to_string);
+        config
+    }
+
+    #[test]
+    fn parse_nvenc_gpu_list_reads_cuda_devices() {
+        let stderr = "\
+[h264_nvenc @ 0x55d0] [ GPU #0 - < NVIDIA GeForce RTX 3060 > has Compute SM 8.6 ]
+[h264_nvenc @ 0x55d0] [ GPU #1 - < NVIDIA RTX A2000 12GB > has Compute SM 8.6 ]
+[vost#0:0/h264_nvenc @ 0x55d0] Error while opening encoder
+";
+
+        let devices = parse_nvenc_gpu_list(stderr);
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[1].kind, HwDeviceKind::Cuda);
+        assert_eq!(devices[1].index, 1);
+        assert_eq!(devices[1].name, "NVIDIA RTX A2000 12GB");
+        assert_eq!(devices[1].config_value(), "1");
+        assert!(parse_nvenc_gpu_list("Unknown encoder 'h264_nvenc'\n").is_empty());
+    }
+
+    #[test]
+    fn list_vaapi_render_nodes_orders_nodes_by_number() {
+        let directory =
+            std::env::temp_dir().join(format!("frame-dri-nodes-{}", std::process::id()));
+        fs::create_dir_all(&directory).expect("test directory should be created");
+        for name in ["renderD129", "card0", "renderD128"] {
+            fs::write(directory.join(name), b"").expect("test node should be written");
+        }
+
+        let devices = list_vaapi_render_nodes(&directory).expect("nodes should be listed");
+        let missing = list_vaapi_render_nodes(&directory.join("missing"));
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(
+            devices
+                .iter()
+                .map(|device| (device.index, device.name.as_str()))
+                .collect::<Vec<_>>(),
+            [(0, "renderD128"), (1, "renderD129")]
+        );
+        assert_eq!(
+            devices[0].config_value(),
+            directory.join("renderD128").to_string_lossy()
+        );
+        assert!(missing.is_ok_and(|devices| devices.is_empty()));
+    }
+
+    #[test]
+    fn parse_dxgi_adapter_reads_the_opened_device() {
+        let device = parse_dxgi_adapter(
+            "[AVHWDeviceContext @ 0000021c] Using device 10de:2504 (NVIDIA GeForce RTX 3060).\n",
+            1,
+        )
+        .expect("adapter line should parse");
+
+        assert_eq!(device.kind, HwDeviceKind::Dxgi);
+        assert_eq!(
+            (device.index, device.name.as_str()),
+            (1, "NVIDIA GeForce RTX 3060")
+        );
+        assert_eq!(
+            parse_dxgi_adapter("Device creation failed: -542398533.\n", 2),
+            None
+        );
+    }
+
+    #[test]
+    fn hw_device_args_follow_the_encoder_family() {
+        let mut nvenc = device_config("hevc_nvenc", Some("1"));
+        let vaapi = device_config("h264_vaapi", Some("/dev/dri/renderD129"));
+        let qsv = device_config("h264_qsv", Some("0"));
+        let software = device_config("libx264", Some("1"));
+
+        assert!(hw_device_input_args(&nvenc).is_empty());
+        assert_eq!(nvenc_gpu_args(&nvenc), ["-gpu", "1"]);
+        nvenc.hw_decode = true;
+        assert_eq!(hw_device_input_args(&nvenc), ["-hwaccel_device", "1"]);
+        assert_eq!(
+            hw_device_input_args(&vaapi),
+            ["-vaapi_device", "/dev/dri/renderD129"]
+        );
+        assert_eq!(
+            hw_device_input_args(&qsv),
+            [
+                "-init_hw_device",
+                "d3d11va=frame:0",
+                "-filter_hw_device",
+                "frame"
+            ]
+        );
+        assert!(hw_device_input_args(&software).is_empty());
+        assert!(nvenc_gpu_args(&software).is_empty());
+    }
+
+    #[test]
+    fn validate_hw_device_requires_an_enumerated_device() {
+        let devices = parse_nvenc_gpu_list("[ GPU #0 - < NVIDIA GeForce RTX 3060 > ]\n");
+        let present = device_config("h264_nvenc", Some("0"));
+        let missing = device_config("h264_nvenc", Some("2"));
+        let unselected = device_config("h264_nvenc", None);
+
+        let error = validate_hw_device(&missing, &devices)
+            .expect_err("an index outside the enumeration should be rejected");
+
+        assert!(validate_hw_device(&present, &devices).is_ok());
+        assert!(validate_hw_device(&unselected, &[]).is_ok());
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Hardware device 2 is not available for h264_nvenc"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Batch image export: resizing stills into a bounding box, re-encoding them
+//! and keeping each file under a size budget.
+//!
+//! Every image becomes an ordinary still-image task. `FFmpeg` turns decoded
+//! frames by the source's display matrix, which carries the EXIF
+//! orientation, so images are upright before their metadata is stripped.
+
+use crate::error::ConversionError;
+use crate::media_rules::{is_image_container, video_codecs_for_container};
+use crate::types::{ConversionConfig, MetadataMode};
+
+/// Lowest quality the size budget search may settle on.
+pub const IMAGE_QUALITY_FLOOR: u32 = 40;
+
+/// Encodes the size budget search may run, the first one included.
+pub const IMAGE_BUDGET_MAX_ATTEMPTS: usize = 6;
+
+/// Settings shared by every image of a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageBatchOptions {
+    /// Bounding box the images are shrunk into; images are never enlarged.
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    /// Output image container, e.g. `jpg` or `webp`.
+    pub format: String,
+    /// Encoder quality from 1 to 100.
+    pub quality: u32,
+    pub strip_metadata: bool,
+    /// Largest output size in kilobytes.
+    pub target_max_kb: Option<u64>,
+}
+
+/// Applies the batch settings to one image's task configuration.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the format is not an image
+/// container, or a dimension, the quality or the size budget is zero or out
+/// of range.
+pub fn apply_image_batch_options(
+    config: &mut ConversionConfig,
+    options: &ImageBatchOptions,
+) -> Result<(), ConversionError> {
+    let format = options.format.trim().to_ascii_lowercase();
+    let codec = video_codecs_for_container(&format)
+        .and_then(<[String]>::first)
+        .filter(|_| is_image_container(&format) && format != "gif")
+        .ok_or_else(|| {
+            ConversionError::InvalidInput(format!("'{format}' is not a still image format"))
+        })?;
+    if !(1..=100).contains(&options.quality) {
+        return Err(ConversionError::InvalidInput(
+            "Image quality must be between 1 and 100".to_string(),
+        ));
+    }
+    if options.max_width == Some(0) || options.max_height == Some(0) {
+        return Err(ConversionError::InvalidInput(
+            "Maximum image dimensions must be greater than zero".to_string(),
+        ));
+    }
+    if options.target_max_kb == Some(0) {
+        return Err(ConversionError::InvalidInput(
+            "Image size budget must be greater than zero".to_string(),
+        ));
+    }
+
+    config.processing_mode = "reencode".to_string();
+    config.video_codec.clone_from(codec);
+    config.container = format;
+    if options.max_width.is_some() || options.max_height.is_some() {
+        config.resolution = "fit".to_string();
+        config.custom_width = options.max_width.map(|width| width.to_string());
+        config.custom_height = options.max_height.map(|height| height.to_string());
+    } else {
+        config.resolution = "original".to_string();
+    }
+    config.image_sequence_output = false;
+    config.image_webp_lossless = false;
+    config.image_jpeg_quality = options.quality;
+    config.image_webp_quality = options.quality;
+    if options.strip_metadata {
+        config.metadata.mode = MetadataMode::Clean;
+    }
+    config.image_target_max_kb = options.target_max_kb;
+    Ok(())
+}
+
+/// Quality of a lossy image output on the 1 to 100 scale, or `None` for
+/// formats without one.
+#[must_use]
+pub fn image_quality(config: &ConversionConfig) -> Option<u32> {
+    match config.container.as_str() {
+        "jpg" => Some(config.image_jpeg_quality),
+        "webp" if !config.image_webp_lossless => Some(config.image_webp_quality),
+        _ => None,
+    }
+}
+
+/// Sets the quality [`image_quality`] reports.
+pub const fn set_image_quality(config: &mut ConversionConfig, quality: u32) {
+    config.image_jpeg_quality = quality;
+    config.image_webp_quality = quality;
+}
+
+/// Search for the highest quality whose encode fits a size budget, from the
+/// configured quality down to [`IMAGE_QUALITY_FLOOR`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QualitySearch {
+    low: u32,
+    high: u32,
+    best_fit: Option<u32>,
+    attempts: usize,
+}
+
+impl QualitySearch {
+    #[must_use]
+    pub fn new(quality: u32) -> Self {
+        Self {
+            low: IMAGE_QUALITY_FLOOR.min(quality),
+            high: quality,
+            best_fit: None,
+            attempts: 0,
+        }
+    }
+
+    /// Records whether the encode at `quality` fit the budget and returns
+    /// the quality to try next, or `None` once the search is over.
+    pub fn record(&mut self, quality: u32, fits: bool) -> Option<u32> {
+        self.attempts += 1;
+        if fits {
+            self.best_fit = self.best_fit.max(Some(quality));
+            self.low = quality + 1;
+        } else if quality <= self.low {
+            return None;
+        } else {
+            self.high = quality - 1;
+        }
+        if self.attempts >= IMAGE_BUDGET_MAX_ATTEMPTS || self.low > self.high {
+            return None;
+        }
+        Some(self.low.midpoint(self.high + 1))
+    }
+
+    /// Quality the output should finally be encoded at: the best fit, or
+    /// the floor when nothing fit.
+    #[must_use]
+    pub fn settled_quality(&self) -> u32 {
+        self.best_fit.unwrap_or(self.low)
+    }
+
+    /// Whether any encode fit the budget.
+    #[must_use]
+    pub const fn found_fit(&self) -> bool {
+        self.best_fit.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn web_export() -> ImageBatchOptions {
+        ImageBatchOptions {
+            max_width: Some(1600),
+            max_height: None,
+            format: "WebP".to_string(),
+            quality: 82,
+            strip_metadata: true,
+            target_max_kb: Some(300),
+        }
+    }
+
+    #[test]
+    fn batch_options_turn_a_task_into_a_bounded_still_export() {
+        let mut config = sample_config("mp4", "libx264");
+
+        apply_image_batch_options(&mut config, &web_export()).expect("options should apply");
+
+        assert_eq!(config.container, "webp");
+        assert_eq!(config.video_codec, "libwebp");
+        assert_eq!(config.resolution, "fit");
+        assert_eq!(config.custom_width.as_deref(), Some("1600"));
+        assert_eq!(config.custom_height, None);
+        assert_eq!(image_quality(&config), Some(82));
+        assert_eq!(config.metadata.mode, MetadataMode::Clean);
+        assert_eq!(config.image_target_max_kb, Some(300));
+    }
+
+    #[test]
+    fn batch_options_reject_animated_formats_and_empty_limits() {
+        let mut gif = web_export();
+        gif.format = "gif".to_string();
+        let mut no_budget = web_export();
+        no_budget.target_max_kb = Some(0);
+        let mut no_width = web_export();
+        no_width.max_width = Some(0);
+
+        for (options, expected) in [
+            (gif, "not a still image format"),
+            (no_budget, "size budget must be greater than zero"),
+            (no_width, "dimensions must be greater than zero"),
+        ] {
+            let error = apply_image_batch_options(&mut sample_config("mp4", "libx264"), &options)
+                .expect_err("options should be rejected")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+
+    #[test]
+    fn quality_search_settles_on_the_highest_fitting_quality() {
+        let mut search = QualitySearch::new(90);
+        let fits = |quality: u32| quality <= 71;
+
+        let mut tried = vec![90];
+        let mut next = search.record(90, fits(90));
+        while let Some(quality) = next {
+            tried.push(quality);
+            next = search.record(quality, fits(quality));
+        }
+
+        assert!(tried.len() <= IMAGE_BUDGET_MAX_ATTEMPTS);
+        assert!(search.found_fit());
+        assert_eq!(search.settled_quality(), 71);
+        assert_eq!(tried[1], 65);
+    }
+
+    #[test]
+    fn quality_search_stops_at_the_floor_when_nothing_fits() {
+        let mut search = QualitySearch::new(90);
+
+        let mut next = search.record(90, false);
+        while let Some(quality) = next {
+            next = search.record(quality, false);
+        }
+
+        assert!(!search.found_fit());
+        assert_eq!(search.settled_quality(), IMAGE_QUALITY_FLOOR);
+    }
+}
@@ -0,0 +1,811 @@
+//! Numbered still frames read as one video input.
+//!
+//! A task source that is a folder of frames, or a path whose file name holds
+//! a `%d`/`%04d` frame number, is resolved into the frames it covers.
+//! Frames numbered without gaps are read by the image2 demuxer from a
+//! printf pattern; sequences with gaps are listed for the concat demuxer,
+//! each frame lasting one frame interval.
+//!
+//! In the other direction, an image output can be written as a sequence:
+//! the output path without its extension becomes a folder of frames named
+//! `{folder}_{number:06}`.
+
+use std::fs;
+use std::path::Path;
+
+use crate::args::{build_ffmpeg_args, output_muxer};
+use crate::error::ConversionError;
+use crate::types::{ConversionConfig, ConversionTask, PostAction, ProbeMetadata};
+
+/// Rate frames are read at when the task sets no `input_framerate`; the
+/// image2 demuxer's own default.
+pub const DEFAULT_SEQUENCE_FRAME_RATE: f64 = 25.0;
+
+/// Extensions of the still formats a folder is scanned for.
+const SEQUENCE_EXTENSIONS: [&str; 10] = [
+    "png", "exr", "dpx", "tif", "tiff", "jpg", "jpeg", "bmp", "tga", "webp",
+];
+
+/// How the demuxer finds the frames of a sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceNumbering {
+    /// Consecutive numbers read through a printf pattern.
+    Contiguous { pattern: String, start_number: u64 },
+    /// Numbers with gaps or mixed padding, listed file by file.
+    Listed,
+}
+
+/// The frames of an image sequence, in frame order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageSequence {
+    pub files: Vec<String>,
+    pub numbering: SequenceNumbering,
+}
+
+impl ImageSequence {
+    /// Path of the first frame, which stands in for the sequence when
+    /// checking the input.
+    #[must_use]
+    pub fn first_file(&self) -> &str {
+        &self.files[0]
+    }
+
+    /// Number of frames in the sequence.
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.files.len() as u64
+    }
+}
+
+/// Reports whether the file name of `path` holds a printf frame number such
+/// as `%d` or `%04d`.
+#[must_use]
+pub fn is_sequence_pattern(path: &str) -> bool {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    split_pattern(file_name).is_some()
+}
+
+/// Resolves a folder of frames or a frame pattern into its sequence, or
+/// returns `None` when `path` is neither.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the folder cannot be read,
+/// holds no numbered frames, or mixes several sequences, and when a pattern
+/// matches no files.
+pub fn detect_image_sequence(path: &str) -> Result<Option<ImageSequence>, ConversionError> {
+    let source = Path::new(path);
+    if source.is_dir() {
+        let names = list_file_names(source)?
+            .into_iter()
+            .filter(|name| has_sequence_extension(name))
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            return Err(ConversionError::InvalidInput(format!(
+                "No numbered image frames were found in {path}"
+            )));
+        }
+        return sequence_from_names(source, &names).map(Some);
+    }
+
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let Some((prefix, width, suffix)) = split_pattern(&file_name) else {
+        return Ok(None);
+    };
+    let directory = source
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let names = list_file_names(directory)?
+        .into_iter()
+        .filter(|name| {
+            name.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(suffix))
+                .is_some_and(|digits| {
+                    digits.len() >= width.unwrap_or(1)
+                        && digits.bytes().all(|byte| byte.is_ascii_digit())
+                })
+        })
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return Err(ConversionError::InvalidInput(format!(
+            "No frames match the sequence pattern {path}"
+        )));
+    }
+    sequence_from_names(directory, &names).map(Some)
+}
+
+/// Orders `names`, all in `directory`, into one sequence.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when a name carries no frame
+/// number or the names belong to more than one sequence.
+pub fn sequence_from_names(
+    directory: &Path,
+    names: &[String],
+) -> Result<ImageSequence, ConversionError> {
+    let mut frames = names
+        .iter()
+        .map(|name| {
+            frame_name_parts(name).ok_or_else(|| {
+                ConversionError::InvalidInput(format!("Frame {name} has no frame number"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    frames.sort_by_key(|frame| frame.number);
+
+    let first = &frames[0];
+    if let Some(other) = frames
+        .iter()
+        .find(|frame| (frame.prefix, frame.extension) != (first.prefix, first.extension))
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "{} and {} belong to different sequences",
+            first.name, other.name
+        )));
+    }
+
+    let contiguous = frames
+        .windows(2)
+        .all(|pair| pair[1].number == pair[0].number + 1);
+    let numbering = match frame_number_spec(&frames) {
+        Some(spec) if contiguous => SequenceNumbering::Contiguous {
+            pattern: directory
+                .join(format!(
+                    "{}{spec}.{}",
+                    first.prefix.replace('%', "%%"),
+                    first.extension.replace('%', "%%")
+                ))
+                .to_string_lossy()
+                .into_owned(),
+            start_number: first.number,
+        },
+        _ => SequenceNumbering::Listed,
+    };
+
+    Ok(ImageSequence {
+        files: frames
+            .iter()
+            .map(|frame| directory.join(frame.name).to_string_lossy().into_owned())
+            .collect(),
+        numbering,
+    })
+}
+
+struct FrameName<'a> {
+    name: &'a str,
+    prefix: &'a str,
+    digits: &'a str,
+    number: u64,
+    extension: &'a str,
+}
+
+fn frame_name_parts(name: &str) -> Option<FrameName<'_>> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    let prefix = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let digits = &stem[prefix.len()..];
+    Some(FrameName {
+        name,
+        prefix,
+        digits,
+        number: digits.parse().ok()?,
+        extension,
+    })
+}
+
+/// Printf spec matching every frame number, or `None` when the padding is
+/// inconsistent.
+fn frame_number_spec(frames: &[FrameName<'_>]) -> Option<String> {
+    let width = frames[0].digits.len();
+    if frames.iter().all(|frame| frame.digits.len() == width) {
+        return Some(if width > 1 {
+            format!("%0{width}d")
+        } else {
+            "%d".to_string()
+        });
+    }
+    frames
+        .iter()
+        .all(|frame| !frame.digits.starts_with('0') || frame.digits == "0")
+        .then(|| "%d".to_string())
+}
+
+/// Splits a pattern file name around its `%d`/`%0Nd` token into the prefix,
+/// the padding width and the suffix.
+fn split_pattern(file_name: &str) -> Option<(&str, Option<usize>, &str)> {
+    let start = file_name.find('%')?;
+    let rest = &file_name[start + 1..];
+    let spec_end = rest.find('d')?;
+    let spec = &rest[..spec_end];
+    let width = match spec {
+        "" => None,
+        padded if padded.starts_with('0') && padded.len() > 1 => Some(padded[1..].parse().ok()?),
+        _ => return None,
+    };
+    Some((&file_name[..start], width, &rest[spec_end + 1..]))
+}
+
+fn has_sequence_extension(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        SEQUENCE_EXTENSIONS
+            .iter()
+            .any(|known| extension.eq_ignore_ascii_case(known))
+    })
+}
+
+fn list_file_names(directory: &Path) -> Result<Vec<String>, ConversionError> {
+    let entries = fs::read_dir(directory).map_err(|error| {
+        ConversionError::InvalidInput(format!(
+            "Cannot read the frame folder {}: {error}",
+            directory.display()
+        ))
+    })?;
+    Ok(entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect())
+}
+
+/// Checks the task settings an image sequence source cannot honor.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for stream copy, for joins with
+/// further sources, for parallel chunks, which seek a single source file,
+/// and for post actions, which act on a single source file.
+pub fn validate_sequence_task(task: &ConversionTask) -> Result<(), ConversionError> {
+    let unsupported = |feature: &str| {
+        Err(ConversionError::InvalidInput(format!(
+            "{feature} is not available for image sequence sources"
+        )))
+    };
+    if task.config.processing_mode == "copy" {
+        return unsupported("Stream copy");
+    }
+    if !task.concat_inputs.is_empty() {
+        return unsupported("Joining sources");
+    }
+    if task.config.parallel_chunks.is_some() {
+        return unsupported("Parallel chunked encoding");
+    }
+    if task.post_action != PostAction::Keep {
+        return unsupported("Trashing or deleting the source");
+    }
+    Ok(())
+}
+
+/// Reports whether `container` is a still format that can be written as a
+/// numbered frame sequence.
+#[must_use]
+pub fn supports_sequence_output(container: &str) -> bool {
+    output_muxer(container) == Some("image2")
+}
+
+/// Checks that an image sequence output writes a format the image2 muxer
+/// stores frame by frame.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the task asks for an image
+/// sequence output in another container.
+pub fn validate_sequence_output(config: &ConversionConfig) -> Result<(), ConversionError> {
+    if config.image_sequence_output && !supports_sequence_output(&config.container) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Image sequence output is not available for container '{}'",
+            config.container
+        )));
+    }
+    Ok(())
+}
+
+/// Folder the frames of an image sequence output are written to: the
+/// output path without its extension.
+#[must_use]
+pub fn sequence_output_directory(output_path: &str, container: &str) -> String {
+    let extension = format!(".{container}");
+    output_path
+        .len()
+        .checked_sub(extension.len())
+        .filter(|&stem_end| output_path[stem_end..].eq_ignore_ascii_case(&extension))
+        .map_or(output_path, |stem_end| &output_path[..stem_end])
+        .to_string()
+}
+
+/// Printf pattern of the frames written into `directory`, named after the
+/// folder `name`.
+#[must_use]
+pub fn sequence_output_pattern(directory: &str, name: &str, container: &str) -> String {
+    Path::new(directory)
+        .join(format!("{}_%06d.{container}", name.replace('%', "%%")))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Checks that the frames of an image sequence output can go to
+/// `directory`: it must be missing, so it can be created, or empty.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when `directory` is a file,
+/// cannot be read, or already holds files.
+pub fn validate_sequence_output_directory(directory: &str) -> Result<(), ConversionError> {
+    let path = Path::new(directory);
+    if !path.exists() {
+        return Ok(());
+    }
+    if !path.is_dir() {
+        return Err(ConversionError::InvalidInput(format!(
+            "Frame folder {directory} is a file"
+        )));
+    }
+    let has_entries = fs::read_dir(path)
+        .map_err(|error| {
+            ConversionError::InvalidInput(format!(
+                "Cannot read the frame folder {directory}: {error}"
+            ))
+        })?
+        .next()
+        .is_some();
+    if has_entries {
+        return Err(ConversionError::InvalidInput(format!(
+            "Frame folder {directory} is not empty"
+        )));
+    }
+    Ok(())
+}
+
+/// Number of frames in a written frame folder and their total size in bytes.
+///
+/// # Errors
+///
+/// Returns the I/O error raised while listing `directory`.
+pub fn written_sequence_totals(directory: &Path) -> std::io::Result<(u64, u64)> {
+    fs::read_dir(directory)?.try_fold((0, 0), |(frames, bytes), entry| {
+        let metadata = entry?.metadata()?;
+        Ok(if metadata.is_file() {
+            (frames + 1, bytes + metadata.len())
+        } else {
+            (frames, bytes)
+        })
+    })
+}
+
+/// Parses an input frame rate given as a number or as a fraction such as
+/// `24000/1001`.
+#[must_use]
+pub fn parse_input_frame_rate(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let rate = match value.split_once('/') {
+        Some((numerator, denominator)) => {
+            numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?
+        }
+        None => value.parse().ok()?,
+    };
+    (rate.is_finite() && rate > 0.0).then_some(rate)
+}
+
+/// Rate the frames of a sequence are read at.
+#[must_use]
+pub fn sequence_frame_rate(config: &ConversionConfig) -> f64 {
+    config
+        .input_framerate
+        .as_deref()
+        .and_then(parse_input_frame_rate)
+        .unwrap_or(DEFAULT_SEQUENCE_FRAME_RATE)
+}
+
+/// Demuxer options that go before `-i` when reading `sequence`.
+#[must_use]
+pub fn sequence_input_args(sequence: &ImageSequence, config: &ConversionConfig) -> Vec<String> {
+    match &sequence.numbering {
+        SequenceNumbering::Contiguous { start_number, .. } => vec![
+            "-f".to_string(),
+            "image2".to_string(),
+            "-framerate".to_string(),
+            config
+                .input_framerate
+                .as_deref()
+                .map(str::trim)
+                .filter(|rate| parse_input_frame_rate(rate).is_some())
+                .map_or_else(|| DEFAULT_SEQUENCE_FRAME_RATE.to_string(), str::to_string),
+            "-start_number".to_string(),
+            start_number.to_string(),
+        ],
+        SequenceNumbering::Listed => vec![
+            "-f".to_string(),
+            "concat".to_string(),
+            "-safe".to_string(),
+            "0".to_string(),
+        ],
+    }
+}
+
+/// Concat demuxer list showing each frame of a listed sequence for one
+/// frame interval, or `None` when the sequence is read through a pattern.
+/// The last frame is repeated so the demuxer keeps its duration.
+#[must_use]
+pub fn sequence_concat_list(sequence: &ImageSequence, frame_rate: f64) -> Option<String> {
+    if sequence.numbering != SequenceNumbering::Listed {
+        return None;
+    }
+    let duration = format!("duration {:.6}\n", 1.0 / frame_rate);
+    let mut list = "ffconcat version 1.0\n".to_string();
+    for file in &sequence.files {
+        list.push_str("file '");
+        list.push_str(&file.replace('\'', "'\\''"));
+        list.push_str("'\n");
+        list.push_str(&duration);
+    }
+    list.push_str("file '");
+    list.push_str(&sequence.files[sequence.files.len() - 1].replace('\'', "'\\''"));
+    list.push_str("'\n");
+    Some(list)
+}
+
+/// Builds the conversion of `sequence`, read from `input` (its pattern or
+/// concat list), with the regular arguments and the sequence demuxer
+/// options ahead of the input.
+///
+/// # Errors
+///
+/// Returns [`ConversionError`] under the same conditions as
+/// [`build_ffmpeg_args`].
+pub fn build_sequence_ffmpeg_args(
+    sequence: &ImageSequence,
+    input: &str,
+    output: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Result<Vec<String>, ConversionError> {
+    let mut args = build_ffmpeg_args(input, output, config, probe)?;
+    let input_position = args
+        .windows(2)
+        .position(|pair| pair[0] == "-i" && pair[1] == input)
+        .unwrap_or(0);
+    args.splice(
+        input_position..input_position,
+        sequence_input_args(sequence, config),
+    );
+    Ok(args)
+}
+
+/// `FFprobe` pass printing the size of every decoded frame of `sequence`.
+#[must_use]
+pub fn ffprobe_sequence_frame_size_args(
+    sequence: &ImageSequence,
+    input: &str,
+    config: &ConversionConfig,
+) -> Vec<String> {
+    let mut args = vec!["-v".to_string(), "error".to_string()];
+    args.extend(sequence_input_args(sequence, config));
+    args.extend([
+        "-select_streams".to_string(),
+        "v:0".to_string(),
+        "-show_entries".to_string(),
+        "frame=width,height".to_string(),
+        "-of".to_string(),
+        "csv=p=0".to_string(),
+        input.to_string(),
+    ]);
+    args
+}
+
+/// Checks that every frame of `sequence` has the size of the first, given
+/// the output of [`ffprobe_sequence_frame_size_args`], and returns it.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] naming the first frame whose
+/// size differs, and [`ConversionError::Probe`] when no frame was read.
+pub fn check_sequence_frame_sizes(
+    sequence: &ImageSequence,
+    stdout: &str,
+) -> Result<(u32, u32), ConversionError> {
+    let mut sizes = stdout.lines().filter_map(|line| {
+        let (width, height) = line.trim().split_once(',')?;
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+    let Some(first) = sizes.next() else {
+        return Err(ConversionError::Probe(
+            "ffprobe read no frames of the image sequence".to_string(),
+        ));
+    };
+    for (size, file) in sizes.zip(&sequence.files[1..]) {
+        if size != first {
+            return Err(ConversionError::InvalidInput(format!(
+                "Frame {file} is {}x{}, but the sequence starts at {}x{}",
+                size.0, size.1, first.0, first.1
+            )));
+        }
+    }
+    Ok(first)
+}
+
+/// Probe data for the whole sequence, from the probe of its first frame.
+#[must_use]
+pub fn sequence_probe_metadata(
+    sequence: &ImageSequence,
+    first_frame: ProbeMetadata,
+    frame_rate: f64,
+    (width, height): (u32, u32),
+) -> ProbeMetadata {
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "frame counts stay far below the range where f64 loses whole frames"
+    )]
+    let duration = sequence.frame_count() as f64 / frame_rate;
+    ProbeMetadata {
+        media_kind: "video".to_string(),
+        duration: Some(format!("{duration:.6}")),
+        frame_rate: Some(frame_rate),
+        frame_count: Some(sequence.frame_count()),
+        width: Some(width),
+        height: Some(height),
+        resolution: Some(format!("{width}x{height}")),
+        ..first_frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn sequence_config(input_framerate: Option<&str>) -> ConversionConfig {
+        let mut config = sample_config("mp4", "libx264");
+        config.input_framerate = input_framerate.map(str::to_string);
+        config
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    fn render_directory(label: &str, files: &[&str]) -> std::path::PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("frame-sequence-{label}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("render directory should be created");
+        for file in files {
+            fs::write(directory.join(file), b"frame").expect("frame should be written");
+        }
+        directory
+    }
+
+    #[test]
+    fn padded_consecutive_frames_are_read_through_a_pattern() {
+        let sequence = sequence_from_names(
+            Path::new("/renders/shot"),
+            &names(&["frame_0102.png", "frame_0100.png", "frame_0101.png"]),
+        )
+        .expect("sequence should resolve");
+
+        assert_eq!(
+            sequence.numbering,
+            SequenceNumbering::Contiguous {
+                pattern: "/renders/shot/frame_%04d.png".to_string(),
+                start_number: 100,
+            }
+        );
+        assert_eq!(sequence.first_file(), "/renders/shot/frame_0100.png");
+        assert_eq!(sequence.frame_count(), 3);
+    }
+
+    #[test]
+    fn unpadded_frames_use_a_plain_number_pattern() {
+        let sequence = sequence_from_names(
+            Path::new("/renders"),
+            &names(&["f9.exr", "f10.exr", "f11.exr"]),
+        )
+        .expect("sequence should resolve");
+
+        assert_eq!(
+            sequence.numbering,
+            SequenceNumbering::Contiguous {
+                pattern: "/renders/f%d.exr".to_string(),
+                start_number: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn gaps_and_mixed_padding_fall_back_to_a_frame_list() {
+        for frames in [
+            names(&["f_0001.png", "f_0002.png", "f_0004.png"]),
+            names(&["f_01.png", "f_002.png"]),
+        ] {
+            let sequence =
+                sequence_from_names(Path::new("/r"), &frames).expect("sequence should resolve");
+            assert_eq!(sequence.numbering, SequenceNumbering::Listed);
+        }
+
+        let sequence = sequence_from_names(
+            Path::new("/r"),
+            &names(&["f_0004.png", "f_0001.png", "it's_0002.png"]),
+        );
+        assert!(
+            sequence
+                .expect_err("mixed sequences should be rejected")
+                .to_string()
+                .contains("belong to different sequences")
+        );
+    }
+
+    #[test]
+    fn listed_sequences_hold_every_frame_for_one_interval() {
+        let sequence = ImageSequence {
+            files: names(&["/r/a_1.png", "/r/a_3.png"]),
+            numbering: SequenceNumbering::Listed,
+        };
+
+        assert_eq!(
+            sequence_concat_list(&sequence, 25.0).as_deref(),
+            Some(
+                "ffconcat version 1.0\nfile '/r/a_1.png'\nduration 0.040000\n\
+                 file '/r/a_3.png'\nduration 0.040000\nfile '/r/a_3.png'\n"
+            )
+        );
+        assert_eq!(
+            sequence_input_args(&sequence, &sequence_config(None)),
+            ["-f", "concat", "-safe", "0"]
+        );
+    }
+
+    #[test]
+    fn frame_rates_accept_numbers_and_fractions() {
+        assert_eq!(parse_input_frame_rate("24"), Some(24.0));
+        assert_eq!(
+            parse_input_frame_rate(" 30000/1001 "),
+            Some(30000.0 / 1001.0)
+        );
+        assert_eq!(parse_input_frame_rate("0"), None);
+        assert_eq!(parse_input_frame_rate("24/0"), None);
+        assert_eq!(parse_input_frame_rate("fast"), None);
+        assert_eq!(
+            sequence_frame_rate(&sequence_config(None)).to_bits(),
+            DEFAULT_SEQUENCE_FRAME_RATE.to_bits()
+        );
+        assert_eq!(
+            sequence_frame_rate(&sequence_config(Some("12"))).to_bits(),
+            12.0_f64.to_bits()
+        );
+    }
+
+    #[test]
+    fn sequence_args_put_the_demuxer_options_before_the_input() {
+        let sequence = ImageSequence {
+            files: names(&["/r/f_0001.png", "/r/f_0002.png"]),
+            numbering: SequenceNumbering::Contiguous {
+                pattern: "/r/f_%04d.png".to_string(),
+                start_number: 1,
+            },
+        };
+        let config = sequence_config(Some("24000/1001"));
+        let probe = sequence_probe_metadata(
+            &sequence,
+            ProbeMetadata {
+                video_codec: Some("png".to_string()),
+                ..ProbeMetadata::default()
+            },
+            sequence_frame_rate(&config),
+            (1920, 1080),
+        );
+
+        let args = build_sequence_ffmpeg_args(
+            &sequence,
+            "/r/f_%04d.png",
+            "/out/shot.mp4",
+            &config,
+            &probe,
+        )
+        .expect("arguments should build");
+
+        let input = args
+            .iter()
+            .position(|arg| arg == "/r/f_%04d.png")
+            .expect("input should be present");
+        assert_eq!(
+            args[input - 7..input],
+            [
+                "-f",
+                "image2",
+                "-framerate",
+                "24000/1001",
+                "-start_number",
+                "1",
+                "-i"
+            ]
+        );
+        assert_eq!(probe.media_kind, "video");
+        assert_eq!(probe.frame_count, Some(2));
+        assert_eq!(probe.duration.as_deref(), Some("0.083417"));
+    }
+
+    #[test]
+    fn a_frame_of_another_size_is_named() {
+        let sequence = ImageSequence {
+            files: names(&["/r/f_1.png", "/r/f_2.png", "/r/f_3.png"]),
+            numbering: SequenceNumbering::Listed,
+        };
+
+        assert_eq!(
+            check_sequence_frame_sizes(&sequence, "1920,1080\n1920,1080\n1920,1080\n")
+                .expect("sizes should match"),
+            (1920, 1080)
+        );
+        let error = check_sequence_frame_sizes(&sequence, "1920,1080\n1920,1080\n1280,720\n")
+            .expect_err("mixed sizes should be rejected");
+        assert_eq!(
+            error.to_string(),
+            ConversionError::InvalidInput(
+                "Frame /r/f_3.png is 1280x720, but the sequence starts at 1920x1080".to_string()
+            )
+            .to_string()
+        );
+        assert!(check_sequence_frame_sizes(&sequence, "").is_err());
+    }
+
+    #[test]
+    fn folders_and_patterns_resolve_from_disk() {
+        let directory = render_directory(
+            "detect",
+            &["shot_001.png", "shot_002.png", "shot_003.png", "notes.txt"],
+        );
+        let folder = directory.to_string_lossy().into_owned();
+
+        let from_folder = detect_image_sequence(&folder)
+            .expect("folder should resolve")
+            .expect("folder should be a sequence");
+        let from_pattern = detect_image_sequence(&format!("{folder}/shot_%03d.png"))
+            .expect("pattern should resolve")
+            .expect("pattern should be a sequence");
+
+        assert_eq!(from_folder, from_pattern);
+        assert_eq!(from_folder.frame_count(), 3);
+        assert!(is_sequence_pattern("/r/shot_%03d.png"));
+        assert!(!is_sequence_pattern("/r/100%.png"));
+        assert_eq!(
+            detect_image_sequence(&format!("{folder}/notes.txt")).expect("files are not sequences"),
+            None
+        );
+        assert!(detect_image_sequence(&format!("{folder}/take_%03d.png")).is_err());
+
+        let _ = fs::remove_dir_all(directory);
+    }
+
+    #[test]
+    fn sequence_outputs_go_to_a_folder_named_after_the_output() {
+        let directory = sequence_output_directory("/out/Trip 100%.PNG", "png");
+
+        assert_eq!(directory, "/out/Trip 100%");
+        assert_eq!(sequence_output_directory("/out/clip", "png"), "/out/clip");
+        assert_eq!(
+            sequence_output_pattern(&directory, "Trip 100%", "png"),
+            "/out/Trip 100%/Trip 100%%_%06d.png"
+        );
+    }
+
+    #[test]
+    fn frame_folders_must_be_missing_or_empty() {
+        let written = render_directory("written", &["clip_000001.png", "clip_000002.png"]);
+        let empty = render_directory("empty-output", &[]);
+        let missing = empty.join("missing");
+
+        let totals = written_sequence_totals(&written).expect("folder should be listed");
+        let error = validate_sequence_output_directory(&written.to_string_lossy())
+            .expect_err("a folder with frames should be rejected");
+
+        assert_eq!(totals, (2, 10));
+        assert!(error.to_string().contains("is not empty"));
+        assert!(validate_sequence_output_directory(&empty.to_string_lossy()).is_ok());
+        assert!(validate_sequence_output_directory(&missing.to_string_lossy()).is_ok());
+
+        let _ = fs::remove_dir_all(written);
+        let _ = fs::remove_dir_all(empty);
+    }
+}
@@ -0,0 +1,298 @@
+//! Dumping every keyframe of a source as a numbered still, for scrubbing
+//! previews and quality checks.
+//!
+//! Only keyframes are decoded, so the pass runs far faster than a full
+//! decode. Files are named by presentation time in milliseconds, which keeps
+//! them sorted and ties each one back to its place in the source.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::ConversionError;
+use crate::thumbnail::frame_encoder_args;
+use crate::types::ProbeMetadata;
+
+/// Keyframe spacing assumed before any keyframe has been seen; most
+/// camera and streaming encodes place one every one to two seconds.
+pub const TYPICAL_KEYFRAME_INTERVAL_SECONDS: f64 = 2.0;
+
+/// Encoder quality of JPEG and WebP keyframes.
+const KEYFRAME_QUALITY: u32 = 90;
+
+/// How keyframes are written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyframeExtractOptions {
+    /// `jpg`, `png` or `webp`.
+    pub format: String,
+    /// Most files to write; every Nth keyframe is taken when the source
+    /// likely holds more.
+    pub max_count: Option<u32>,
+}
+
+/// `FFmpeg` arguments for one keyframe dump.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyframeExtractPlan {
+    pub args: Vec<String>,
+    /// Files the pass is expected to write, before any refinement.
+    pub estimated_frames: u64,
+    /// Keyframes advanced per written file; 1 writes every keyframe.
+    pub sample_every: u64,
+}
+
+/// Outcome of a keyframe dump.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedKeyframes {
+    pub output_directory: String,
+    pub frames: u64,
+    pub bytes: u64,
+    /// False when the pass was cancelled; the frames written until then are
+    /// kept.
+    pub complete: bool,
+}
+
+/// Keyframes a source is expected to hold, from its duration and
+/// [`TYPICAL_KEYFRAME_INTERVAL_SECONDS`].
+#[must_use]
+pub fn estimated_keyframe_count(probe: &ProbeMetadata) -> Option<u64> {
+    let duration = probe.duration_seconds()?;
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the estimate is positive and far below u64::MAX"
+    )]
+    let count = (duration / TYPICAL_KEYFRAME_INTERVAL_SECONDS).ceil() as u64;
+    Some(count.max(1))
+}
+
+/// Files a pass will write in total, re-estimated from `written` files
+/// reaching `last_seconds` of a `duration` long source. Falls back to
+/// `estimated` until the pass has moved past the start.
+#[must_use]
+pub fn refined_keyframe_estimate(
+    estimated: u64,
+    written: u64,
+    last_seconds: f64,
+    duration: f64,
+    max_count: Option<u32>,
+) -> u64 {
+    let total = if last_seconds > 0.0 && duration > last_seconds {
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss,
+            reason = "frame counts stay far below 2^52 and the ratio is positive"
+        )]
+        let projected = (written as f64 * duration / last_seconds).ceil() as u64;
+        projected
+    } else if last_seconds > 0.0 {
+        written
+    } else {
+        estimated
+    };
+    let total = total.max(written);
+    max_count.map_or(total, |max_count| total.min(u64::from(max_count)))
+}
+
+/// Printf pattern of the keyframes written into `directory`: the source
+/// `stem` followed by the presentation time in milliseconds.
+#[must_use]
+pub fn keyframe_output_pattern(directory: &str, stem: &str, format: &str) -> String {
+    Path::new(directory)
+        .join(format!(
+            "{}_%010d.{}",
+            stem.replace('%', "%%"),
+            format.trim().to_ascii_lowercase()
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Builds the arguments that write the keyframes of `input` to the printf
+/// `pattern`.
+///
+/// Frames keep their own timestamps and are retimed to a millisecond time
+/// base, so the number in each file name is its presentation time. When
+/// `max_count` is below the expected keyframe count, every Nth keyframe is
+/// taken so the picks still span the whole source, and the count is capped
+/// in case the estimate was low.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the source has no video,
+/// the format is not JPEG, PNG or WebP, or `max_count` is zero.
+pub fn build_keyframe_extract_args(
+    input: &str,
+    pattern: &str,
+    options: &KeyframeExtractOptions,
+    probe: &ProbeMetadata,
+) -> Result<KeyframeExtractPlan, ConversionError> {
+    if probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "The source has no video stream to take keyframes from".to_string(),
+        ));
+    }
+    if options.max_count == Some(0) {
+        return Err(ConversionError::InvalidInput(
+            "The keyframe limit must be greater than zero".to_string(),
+        ));
+    }
+    let encoder_args = frame_encoder_args(&options.format, KEYFRAME_QUALITY)?;
+
+    let keyframes = estimated_keyframe_count(probe).unwrap_or(1);
+    let sample_every = options
+        .max_count
+        .map_or(1, |max_count| keyframes.div_ceil(u64::from(max_count)))
+        .max(1);
+    let estimated_frames = refined_keyframe_estimate(
+        keyframes.div_ceil(sample_every),
+        0,
+        0.0,
+        0.0,
+        options.max_count,
+    );
+
+    let mut filters = vec!["settb=1/1000".to_string()];
+    if sample_every > 1 {
+        filters.push(format!("select='not(mod(n\\,{sample_every}))'"));
+    }
+    filters.push("showinfo".to_string());
+
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-skip_frame".to_string(),
+        "nokey".to_string(),
+        "-i".to_string(),
+        input.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+        "-vf".to_string(),
+        filters.join(","),
+        "-vsync".to_string(),
+        "0".to_string(),
+    ];
+    args.extend(encoder_args);
+    if let Some(max_count) = options.max_count {
+        args.extend(["-frames:v".to_string(), max_count.to_string()]);
+    }
+    args.extend([
+        "-frame_pts".to_string(),
+        "1".to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-f".to_string(),
+        "image2".to_string(),
+        "-y".to_string(),
+        pattern.to_string(),
+    ]);
+
+    Ok(KeyframeExtractPlan {
+        args,
+        estimated_frames,
+        sample_every,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_probe(duration: &str) -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some(duration.to_string()),
+            video_codec: Some("h264".to_string()),
+            ..ProbeMetadata::default()
+        }
+    }
+
+    fn options(max_count: Option<u32>) -> KeyframeExtractOptions {
+        KeyframeExtractOptions {
+            format: "jpg".to_string(),
+            max_count,
+        }
+    }
+
+    #[test]
+    fn every_keyframe_is_written_under_its_presentation_time() {
+        let pattern = keyframe_output_pattern("/out/keys", "clip 100%", "JPG");
+        let plan = build_keyframe_extract_args(
+            "/in/clip.mp4",
+            &pattern,
+            &options(None),
+            &video_probe("120.0"),
+        )
+        .expect("keyframe args should build");
+
+        assert_eq!(pattern, "/out/keys/clip 100%%_%010d.jpg");
+        assert_eq!(
+            plan.args[2..6],
+            ["-skip_frame", "nokey", "-i", "/in/clip.mp4"]
+        );
+        assert!(
+            plan.args
+                .windows(2)
+                .any(|pair| pair == ["-vf", "settb=1/1000,showinfo"])
+        );
+        assert!(plan.args.windows(2).any(|pair| pair == ["-vsync", "0"]));
+        assert!(plan.args.windows(2).any(|pair| pair == ["-frame_pts", "1"]));
+        assert!(!plan.args.iter().any(|arg| arg == "-frames:v"));
+        assert_eq!(plan.args.last(), Some(&pattern));
+        assert_eq!((plan.estimated_frames, plan.sample_every), (60, 1));
+    }
+
+    #[test]
+    fn a_keyframe_limit_samples_every_nth_keyframe() {
+        let plan = build_keyframe_extract_args(
+            "/in/film.mkv",
+            "/out/film_%010d.jpg",
+            &options(Some(100)),
+            &video_probe("7200.0"),
+        )
+        .expect("capped keyframe args should build");
+
+        assert_eq!(plan.sample_every, 36);
+        assert_eq!(plan.estimated_frames, 100);
+        assert!(
+            plan.args
+                .contains(&"settb=1/1000,select='not(mod(n\\,36))',showinfo".to_string())
+        );
+        assert!(
+            plan.args
+                .windows(2)
+                .any(|pair| pair == ["-frames:v", "100"])
+        );
+    }
+
+    #[test]
+    fn keyframe_estimates_follow_the_frames_seen() {
+        assert_eq!(refined_keyframe_estimate(60, 0, 0.0, 120.0, None), 60);
+        assert_eq!(refined_keyframe_estimate(60, 10, 40.0, 120.0, None), 30);
+        assert_eq!(refined_keyframe_estimate(60, 10, 40.0, 120.0, Some(20)), 20);
+        assert_eq!(refined_keyframe_estimate(60, 31, 120.0, 120.0, None), 31);
+    }
+
+    #[test]
+    fn invalid_keyframe_requests_are_rejected() {
+        let mut gif = options(None);
+        gif.format = "gif".to_string();
+        let audio_only = ProbeMetadata {
+            video_codec: None,
+            ..video_probe("60.0")
+        };
+        let cases = [
+            (options(Some(0)), video_probe("60.0"), "greater than zero"),
+            (gif, video_probe("60.0"), "not 'gif'"),
+            (options(None), audio_only, "no video stream"),
+        ];
+
+        for (options, probe, expected) in cases {
+            let error = build_keyframe_extract_args("/in.mkv", "/out_%010d.jpg", &options, &probe)
+                .expect_err("request should be rejected")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+}
@@ -1,15 +1,47 @@
 //! Shared backend services for the Frame GPUI migration.
 
+pub mod analysis;
 pub mod args;
+pub mod bitrate;
 pub mod capabilities;
+pub mod chunked;
 pub mod codec;
+pub mod command_line;
+pub mod comparison_clip;
+pub mod concat;
+pub mod contact_sheet;
+pub mod container_config;
+pub mod dependencies;
 pub mod error;
+pub mod estimate;
+pub mod event_queue;
 pub mod events;
+pub mod expand;
+pub mod failure;
 pub mod filters;
 pub mod fonts;
+pub mod hw_devices;
+pub mod image_batch;
+pub mod image_sequence;
+pub mod keyframes;
 pub mod media_filters;
 pub mod media_rules;
+pub mod network_input;
+pub mod output_template;
 pub mod preview;
 pub mod probe;
+pub mod progress;
+pub mod quality;
+pub mod sample_preview;
+pub mod scratch;
+pub mod segments;
+pub mod smart_cut;
+pub mod split;
+pub mod task_log;
+pub mod task_log_file;
+pub mod thumbnail;
+pub mod timecode;
 pub mod types;
 pub mod utils;
+pub mod verify;
+pub mod warnings;
@@ -393,7 +393,7 @@ fn validate_u32(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{AudioFiltersConfig, VideoColorFiltersConfig};
+    use crate::types::{AudioFiltersConfig, OverwritePolicy, SeekMode, VideoColorFiltersConfig};
 
     #[test]
     fn default_video_filters_emit_empty_chains() {
@@ -520,11 +520,14 @@ mod tests {
             custom_height: None,
             scaling_algorithm: "lanczos".to_string(),
             fps: "original".to_string(),
+            input_framerate: None,
             crf: 23,
             quality: 50,
             preset: "medium".to_string(),
             start_time: None,
             end_time: None,
+            segments: Vec::new(),
+            remove_segments: Vec::new(),
             metadata: crate::types::MetadataConfig::default(),
             rotation: "0".to_string(),
             flip_horizontal: false,
@@ -535,6 +538,7 @@ mod tests {
             nvenc_temporal_aq: false,
             videotoolbox_allow_sw: false,
             hw_decode: false,
+            hw_device: None,
             pixel_format: "auto".to_string(),
             image_jpeg_quality: 85,
             image_jpeg_huffman: "optimal".to_string(),
@@ -545,9 +549,21 @@ mod tests {
             image_png_compression: 9,
             image_png_prediction: "paeth".to_string(),
             image_tiff_compression: "packbits".to_string(),
+            image_avif_crf: 30,
+            image_target_max_kb: None,
             gif_colors: 256,
             gif_dither: "sierra2_4a".to_string(),
             gif_loop: 0,
+            overwrite_policy: OverwritePolicy::default(),
+            split: None,
+            split_by_chapters: false,
+            image_sequence_output: false,
+            smart_cut: false,
+            seek_mode: SeekMode::default(),
+            parallel_chunks: None,
+            verify_output: false,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
         }
     }
 }
@@ -4,6 +4,8 @@ use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 
+use crate::utils::pixel_format_bit_depth;
+
 const ANY_CODEC_TOKEN: &str = "*";
 
 #[derive(Debug, Deserialize)]
@@ -207,6 +209,33 @@ pub fn is_video_pixel_format_allowed(container: &str, encoder: &str, pixel_forma
     allowed.contains(ANY_CODEC_TOKEN) || allowed.contains(&pixel_format)
 }
 
+/// Returns whether `encoder` can write pixel formats deeper than 8 bits into
+/// `container`. Combinations without pixel format rules are assumed to.
+#[must_use]
+pub fn video_encoder_supports_high_bit_depth(container: &str, encoder: &str) -> bool {
+    let container = normalize(container);
+    let encoder = normalize(encoder);
+
+    let Some(container_rules) = MEDIA_RULES
+        .container_encoder_pixel_format_compatibility
+        .get(&container)
+    else {
+        return true;
+    };
+
+    let Some(allowed) = container_rules
+        .get(&encoder)
+        .or_else(|| container_rules.get(ANY_CODEC_TOKEN))
+    else {
+        return true;
+    };
+
+    allowed.contains(ANY_CODEC_TOKEN)
+        || allowed
+            .iter()
+            .any(|pixel_format| pixel_format_bit_depth(pixel_format).is_some_and(|bits| bits > 8))
+}
+
 #[must_use]
 pub fn is_audio_codec_allowed(container: &str, codec: &str) -> bool {
     codec_allowed(
@@ -248,6 +277,18 @@ pub fn default_audio_codec_for_container(container: &str) -> &str {
         )
 }
 
+/// Preferred video encoder for `container`: the first encoder of the
+/// fallback order it accepts, else the first one it lists.
+#[must_use]
+pub fn default_video_codec_for_container(container: &str) -> &'static str {
+    MEDIA_RULES
+        .video_codec_fallback_order
+        .iter()
+        .find(|codec| is_video_codec_allowed(container, codec))
+        .or_else(|| video_codecs_for_container(container).and_then(<[String]>::first))
+        .map_or("libx264", String::as_str)
+}
+
 fn codec_allowed(
     container: &str,
     codec: &str,
@@ -324,6 +365,7 @@ mod tests {
                 "webp".to_string(),
                 "bmp".to_string(),
                 "tiff".to_string(),
+                "avif".to_string(),
                 "mp3".to_string(),
                 "m4a".to_string(),
                 "wav".to_string(),
@@ -342,6 +384,8 @@ mod tests {
     fn image_containers_do_not_support_audio_or_subtitles() {
         assert!(!container_supports_audio("png"));
         assert!(!container_supports_subtitles("png"));
+        assert!(!container_supports_audio("avif"));
+        assert!(!container_supports_subtitles("avif"));
     }
 
     #[test]
@@ -362,6 +406,14 @@ mod tests {
         assert!(is_audio_codec_allowed("mov", "flac"));
     }
 
+    #[test]
+    fn default_video_codecs_follow_the_fallback_order() {
+        assert_eq!(default_video_codec_for_container("mov"), "libx264");
+        assert_eq!(default_video_codec_for_container("WEBM"), "vp9");
+        assert_eq!(default_video_codec_for_container("gif"), "gif");
+        assert_eq!(default_video_codec_for_container("jpg"), "mjpeg");
+    }
+
     #[test]
     fn webm_default_audio_codec_matches_shared_rules() {
         assert_eq!(default_audio_codec_for_container("webm"), "libopus");
@@ -380,4 +432,11 @@ mod tests {
             "yuv444p"
         ));
     }
+
+    #[test]
+    fn high_bit_depth_support_follows_pixel_format_rules() {
+        assert!(video_encoder_supports_high_bit_depth("mp4", "libx265"));
+        assert!(!video_encoder_supports_high_bit_depth("mp4", "h264_nvenc"));
+        assert!(!video_encoder_supports_high_bit_depth("webm", "vp9"));
+    }
 }
@@ -0,0 +1,168 @@
+//! HTTP(S) sources, which `FFmpeg` and `FFprobe` read directly instead of
+//! from a local file.
+
+use std::time::Duration;
+
+use crate::error::ConversionError;
+
+/// Schemes accepted for network sources.
+const NETWORK_INPUT_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// How long a network read may stall before `FFmpeg` gives up on the source.
+pub const NETWORK_INPUT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Scheme of a `scheme://` source, or `None` for a file path. Single-letter
+/// schemes are left to paths, so Windows drive letters are not mistaken
+/// for one.
+#[must_use]
+pub fn input_url_scheme(file_path: &str) -> Option<&str> {
+    let (scheme, _) = file_path.trim_start().split_once("://")?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars
+            .next()
+            .is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|char| char.is_ascii_alphanumeric() || matches!(char, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Whether `file_path` is an HTTP(S) URL rather than a local file.
+#[must_use]
+pub fn is_network_input(file_path: &str) -> bool {
+    input_url_scheme(file_path).is_some_and(|scheme| {
+        NETWORK_INPUT_SCHEMES
+            .iter()
+            .any(|accepted| scheme.eq_ignore_ascii_case(accepted))
+    })
+}
+
+/// Checks that a URL source uses HTTP(S) and names a host.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for other schemes, URLs
+/// without a host, and URLs containing whitespace or control characters.
+pub fn validate_network_input(url: &str) -> Result<(), ConversionError> {
+    let Some(scheme) = input_url_scheme(url) else {
+        return Err(ConversionError::InvalidInput(format!(
+            "Input is not a URL: {url}"
+        )));
+    };
+    if !is_network_input(url) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Unsupported URL scheme: {scheme} (only http and https sources are accepted)"
+        )));
+    }
+    if url
+        .chars()
+        .any(|char| char.is_whitespace() || char.is_control())
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Input URL contains spaces or control characters: {url}"
+        )));
+    }
+    let host = url_host(url);
+    if host.is_empty() || host.starts_with(':') {
+        return Err(ConversionError::InvalidInput(format!(
+            "Input URL has no host: {url}"
+        )));
+    }
+    Ok(())
+}
+
+/// Protocol options placed before a network source's `-i`, or before the
+/// source in an `FFprobe` call, so a stalled connection fails instead of
+/// hanging. Local files get none.
+#[must_use]
+pub fn network_input_args(file_path: &str) -> Vec<String> {
+    if !is_network_input(file_path) {
+        return Vec::new();
+    }
+    let micros = NETWORK_INPUT_TIMEOUT.as_micros().to_string();
+    vec![
+        "-rw_timeout".to_string(),
+        micros.clone(),
+        "-timeout".to_string(),
+        micros,
+    ]
+}
+
+/// Last path segment of a URL without its query or fragment, falling back
+/// to the host for URLs that end in a slash.
+#[must_use]
+pub fn network_input_file_name(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    match rest.split_once('/') {
+        Some((host, path)) => path
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(host),
+        None => rest,
+    }
+}
+
+/// Host, with any port and credentials, of a `scheme://` URL.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_http_urls_are_network_inputs() {
+        assert!(is_network_input("https://cdn.example.com/episode.mp3"));
+        assert!(is_network_input("HTTP://example.com/live/index.m3u8"));
+        assert!(!is_network_input("ftp://example.com/clip.mp4"));
+        assert!(!is_network_input("/media/clip.mp4"));
+        assert!(!is_network_input(r"C:\Videos\clip.mp4"));
+        assert_eq!(input_url_scheme("rtmp://example.com/live"), Some("rtmp"));
+        assert_eq!(input_url_scheme("C://Videos/clip.mp4"), None);
+    }
+
+    #[test]
+    fn validation_names_the_problem_with_a_url() {
+        let message = |url: &str| {
+            validate_network_input(url)
+                .expect_err("url should be rejected")
+                .to_string()
+        };
+
+        assert!(validate_network_input("https://user@example.com:8443/a.mp4?t=1").is_ok());
+        assert!(message("ftp://example.com/a.mp4").contains("Unsupported URL scheme: ftp"));
+        assert!(message("https:///a.mp4").contains("no host"));
+        assert!(message("https://example.com/my clip.mp4").contains("spaces"));
+    }
+
+    #[test]
+    fn timeouts_are_added_for_urls_only() {
+        assert_eq!(
+            network_input_args("https://example.com/a.mp4"),
+            ["-rw_timeout", "15000000", "-timeout", "15000000"]
+        );
+        assert!(network_input_args("/media/a.mp4").is_empty());
+    }
+
+    #[test]
+    fn file_names_drop_queries_and_fall_back_to_the_host() {
+        assert_eq!(
+            network_input_file_name("https://cdn.example.com/feeds/ep-12.mp3?token=a.b#t=3"),
+            "ep-12.mp3"
+        );
+        assert_eq!(
+            network_input_file_name("https://example.com/"),
+            "example.com"
+        );
+        assert_eq!(
+            network_input_file_name("https://example.com"),
+            "example.com"
+        );
+    }
+}
@@ -0,0 +1,514 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::ConversionError;
+use crate::split::SPLIT_PART_PLACEHOLDER;
+use crate::types::{ConversionConfig, ProbeMetadata};
+use crate::utils::is_audio_only_container;
+
+/// Tokens accepted inside `{}` in output filename templates.
+pub const OUTPUT_TEMPLATE_TOKENS: [&str; 10] = [
+    "name",
+    "container",
+    "vcodec",
+    "acodec",
+    "width",
+    "height",
+    "fps",
+    "date",
+    "time",
+    "part",
+];
+
+const UNKNOWN_TOKEN_VALUE: &str = "unknown";
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Values available to an output filename template.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputTemplateContext<'a> {
+    /// Input file name without its final extension.
+    pub input_stem: &'a str,
+    pub config: &'a ConversionConfig,
+    pub probe: Option<&'a ProbeMetadata>,
+    /// Moment used for `{date}` and `{time}`, rendered in UTC.
+    pub timestamp: SystemTime,
+}
+
+/// Checks that `template` only references known tokens and has balanced braces.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for empty templates, unknown
+/// tokens, or unmatched braces.
+pub fn validate_output_template(template: &str) -> Result<(), ConversionError> {
+    if template.trim().is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Output filename template is empty".to_string(),
+        ));
+    }
+    template_segments(template).map(|_| ())
+}
+
+/// Expands `template` into an output file stem.
+///
+/// The container extension is not part of the template; it is appended when
+/// the output path is built. Characters that are invalid in file names are
+/// replaced with `_` after expansion, so token values cannot introduce path
+/// separators.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the template is invalid or
+/// expands to an empty name.
+pub fn expand_output_template(
+    template: &str,
+    context: &OutputTemplateContext<'_>,
+) -> Result<String, ConversionError> {
+    validate_output_template(template)?;
+
+    let mut expanded = String::with_capacity(template.len());
+    for segment in template_segments(template)? {
+        match segment {
+            TemplateSegment::Literal(text) => expanded.push_str(text),
+            TemplateSegment::Token(token) => expanded.push_str(&token_value(token, context)),
+        }
+    }
+
+    let sanitized = replace_invalid_file_name_characters(&expanded);
+    let trimmed = sanitized.trim().trim_end_matches('.');
+    if trimmed.is_empty() || trimmed.chars().all(|character| character == '.') {
+        return Err(ConversionError::InvalidInput(format!(
+            "Output filename template '{template}' expands to an empty name"
+        )));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Replaces path separators, characters reserved on Windows, and control
+/// characters with `_`.
+#[must_use]
+pub fn replace_invalid_file_name_characters(value: &str) -> String {
+    value
+        .chars()
+        .map(|character| {
+            if character.is_control()
+                || matches!(
+                    character,
+                    '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*'
+                )
+            {
+                '_'
+            } else {
+                character
+            }
+        })
+        .collect()
+}
+
+enum TemplateSegment<'a> {
+    Literal(&'a str),
+    Token(&'a str),
+}
+
+fn template_segments(template: &str) -> Result<Vec<TemplateSegment<'_>>, ConversionError> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err(unmatched_brace(template));
+        }
+        if open > 0 {
+            segments.push(TemplateSegment::Literal(&rest[..open]));
+        }
+        let after_open = &rest[open + 1..];
+        let close = after_open
+            .find('}')
+            .ok_or_else(|| unmatched_brace(template))?;
+        let token = &after_open[..close];
+        if token.contains('{') {
+            return Err(unmatched_brace(template));
+        }
+        if !OUTPUT_TEMPLATE_TOKENS.contains(&token) {
+            return Err(ConversionError::InvalidInput(format!(
+                "Unknown output filename token '{{{token}}}'; expected one of {}",
+                OUTPUT_TEMPLATE_TOKENS
+                    .iter()
+                    .map(|known| format!("{{{known}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        segments.push(TemplateSegment::Token(token));
+        rest = &after_open[close + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(TemplateSegment::Literal(rest));
+    }
+
+    Ok(segments)
+}
+
+fn unmatched_brace(template: &str) -> ConversionError {
+    ConversionError::InvalidInput(format!(
+        "Output filename template '{template}' has an unmatched brace"
+    ))
+}
+
+fn token_value(token: &str, context: &OutputTemplateContext<'_>) -> String {
+    let config = context.config;
+    match token {
+        "name" => context.input_stem.to_string(),
+        "container" => config.container.clone(),
+        "vcodec" => video_codec_value(context),
+        "acodec" => audio_codec_value(context),
+        "width" => {
+            output_dimensions(context).map_or_else(unknown_value, |(width, _)| width.to_string())
+        }
+        "height" => {
+            output_dimensions(context).map_or_else(unknown_value, |(_, height)| height.to_string())
+        }
+        "fps" => frame_rate_value(context),
+        "date" => format_utc_date(context.timestamp),
+        "time" => format_utc_time(context.timestamp),
+        // Left in place for split outputs, which number each part; plain
+        // outputs drop it.
+        "part" if config.split.is_some() => SPLIT_PART_PLACEHOLDER.to_string(),
+        "part" => String::new(),
+        _ => unknown_value(),
+    }
+}
+
+fn unknown_value() -> String {
+    UNKNOWN_TOKEN_VALUE.to_string()
+}
+
+fn is_copy_mode(config: &ConversionConfig) -> bool {
+    config.processing_mode == "copy"
+}
+
+fn video_codec_value(context: &OutputTemplateContext<'_>) -> String {
+    if is_audio_only_container(&context.config.container) {
+        return "none".to_string();
+    }
+    if is_copy_mode(context.config) {
+        return context
+            .probe
+            .and_then(|probe| probe.video_codec.clone())
+            .unwrap_or_else(unknown_value);
+    }
+    context.config.video_codec.clone()
+}
+
+fn audio_codec_value(context: &OutputTemplateContext<'_>) -> String {
+    if is_copy_mode(context.config) {
+        return context
+            .probe
+            .and_then(|probe| probe.audio_codec.clone())
+            .unwrap_or_else(unknown_value);
+    }
+    context.config.audio_codec.clone()
+}
+
+fn frame_rate_value(context: &OutputTemplateContext<'_>) -> String {
+    let configured = context.config.fps.trim();
+    if !is_copy_mode(context.config) && configured != "original" && !configured.is_empty() {
+        return configured.to_string();
+    }
+    context
+        .probe
+        .and_then(|probe| probe.frame_rate)
+        .map_or_else(unknown_value, format_frame_rate)
+}
+
+fn format_frame_rate(frame_rate: f64) -> String {
+    let rounded = format!("{frame_rate:.2}");
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn output_dimensions(context: &OutputTemplateContext<'_>) -> Option<(u32, u32)> {
+    predicted_output_dimensions(context.config, context.probe?)
+}
+
+/// Predicts the encoded frame size from the source dimensions and the
+/// crop, rotation, and resolution settings.
+pub(crate) fn predicted_output_dimensions(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Option<(u32, u32)> {
+    let mut width = f64::from(probe.width?);
+    let mut height = f64::from(probe.height?);
+    if is_copy_mode(config) {
+        return Some((probe.width?, probe.height?));
+    }
+
+    if let Some(crop) = config.crop.as_ref().filter(|crop| crop.enabled)
+        && crop.width > 0.0
+        && crop.height > 0.0
+    {
+        width = crop.width;
+        height = crop.height;
+    }
+    if matches!(config.rotation.as_str(), "90" | "270") {
+        std::mem::swap(&mut width, &mut height);
+    }
+
+    let (width, height) = match config.resolution.as_str() {
+        "1080p" => (even_width_for_height(width, height, 1080.0), 1080.0),
+        "720p" => (even_width_for_height(width, height, 720.0), 720.0),
+        "480p" => (even_width_for_height(width, height, 480.0), 480.0),
+        "custom" => custom_dimensions(config, width, height),
+        "fit" => fit_dimensions(config, width, height),
+        _ => (width, height),
+    };
+    Some((dimension_from_f64(width), dimension_from_f64(height)))
+}
+
+fn custom_dimensions(config: &ConversionConfig, width: f64, height: f64) -> (f64, f64) {
+    let parse = |value: Option<&String>| {
+        value
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| *value > 0.0)
+    };
+    match (
+        parse(config.custom_width.as_ref()),
+        parse(config.custom_height.as_ref()),
+    ) {
+        (Some(custom_width), Some(custom_height)) => (custom_width, custom_height),
+        (Some(custom_width), None) => (custom_width, height * custom_width / width),
+        (None, Some(custom_height)) => (width * custom_height / height, custom_height),
+        (None, None) => (width, height),
+    }
+}
+
+fn fit_dimensions(config: &ConversionConfig, width: f64, height: f64) -> (f64, f64) {
+    let scale = |value: Option<&String>, source: f64| {
+        value
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| *value > 0.0)
+            .map_or(1.0, |bound| (bound / source).min(1.0))
+    };
+    let scale = scale(config.custom_width.as_ref(), width)
+        .min(scale(config.custom_height.as_ref(), height));
+    ((width * scale).floor(), (height * scale).floor())
+}
+
+fn even_width_for_height(width: f64, height: f64, target_height: f64) -> f64 {
+    ((width * target_height / height) / 2.0).round() * 2.0
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "frame dimensions are clamped to non-negative values well below u32::MAX"
+)]
+const fn dimension_from_f64(value: f64) -> u32 {
+    value.max(0.0).round() as u32
+}
+
+fn format_utc_date(timestamp: SystemTime) -> String {
+    let days = unix_seconds(timestamp) / SECONDS_PER_DAY;
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn format_utc_time(timestamp: SystemTime) -> String {
+    let seconds_of_day = unix_seconds(timestamp) % SECONDS_PER_DAY;
+    format!(
+        "{:02}-{:02}-{:02}",
+        seconds_of_day / 3600,
+        (seconds_of_day / 60) % 60,
+        seconds_of_day % 60
+    )
+}
+
+fn unix_seconds(timestamp: SystemTime) -> u64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Converts days since the Unix epoch into a proleptic Gregorian date.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::types::{CropConfig, OutputSplit, sample_config};
+
+    fn sample_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            video_codec: Some("hevc".to_string()),
+            audio_codec: Some("opus".to_string()),
+            frame_rate: Some(29.97),
+            width: Some(3840),
+            height: Some(2160),
+            ..Default::default()
+        }
+    }
+
+    fn expand(template: &str, config: &ConversionConfig, probe: Option<&ProbeMetadata>) -> String {
+        expand_output_template(
+            template,
+            &OutputTemplateContext {
+                input_stem: "show.S01E01",
+                config,
+                probe,
+                // 2024-02-29T13:05:09Z
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_709_211_909),
+            },
+        )
+        .unwrap_or_else(|error| panic!("template {template} should expand: {error}"))
+    }
+
+    #[test]
+    fn expand_output_template_substitutes_tokens() {
+        let probe = sample_probe();
+        let mut scaled = sample_config("mp4", "libx264");
+        scaled.resolution = "720p".to_string();
+        scaled.fps = "24".to_string();
+        let mut copy = sample_config("mp4", "libx264");
+        copy.processing_mode = "copy".to_string();
+        let mut custom = sample_config("mp4", "libx264");
+        custom.resolution = "custom".to_string();
+        custom.custom_width = Some("1000".to_string());
+        let mut fit = custom.clone();
+        fit.resolution = "fit".to_string();
+        fit.custom_height = Some("9000".to_string());
+        let mut rotated = sample_config("mp4", "libx264");
+        rotated.rotation = "90".to_string();
+        rotated.crop = Some(CropConfig {
+            enabled: true,
+            width: 1920.0,
+            height: 800.0,
+            ..CropConfig::default()
+        });
+        let mut audio = sample_config("mp4", "libx264");
+        audio.container = "mp3".to_string();
+        audio.audio_codec = "libmp3lame".to_string();
+        let mut split = sample_config("mp4", "libx264");
+        split.split = Some(OutputSplit::Duration { seconds: 900.0 });
+
+        let cases = vec![
+            (
+                "{name}_converted",
+                sample_config("mp4", "libx264"),
+                "show.S01E01_converted",
+            ),
+            (
+                "{name}_{height}p_{vcodec}",
+                sample_config("mp4", "libx264"),
+                "show.S01E01_2160p_libx264",
+            ),
+            ("{width}x{height}@{fps}", scaled.clone(), "1280x720@24"),
+            (
+                "{width}x{height}@{fps}",
+                sample_config("mp4", "libx264"),
+                "3840x2160@29.97",
+            ),
+            ("{vcodec}-{acodec}.{container}", copy, "hevc-opus.mp4"),
+            ("{width}x{height}", custom, "1000x563"),
+            ("{width}x{height}", fit, "1000x562"),
+            ("{width}x{height}", rotated, "800x1920"),
+            ("{vcodec}_{acodec}", audio, "none_libmp3lame"),
+            ("{name} {part}", split, "show.S01E01 {part}"),
+            (
+                "{name}{part}",
+                sample_config("mp4", "libx264"),
+                "show.S01E01",
+            ),
+            (
+                "{name} {date} {time}",
+                scaled,
+                "show.S01E01 2024-02-29 13-05-09",
+            ),
+        ];
+
+        for (template, config, expected) in cases {
+            assert_eq!(
+                expand(template, &config, Some(&probe)),
+                expected,
+                "template {template}"
+            );
+        }
+    }
+
+    #[test]
+    fn expand_output_template_marks_missing_probe_values_unknown() {
+        assert_eq!(
+            expand(
+                "{name}_{height}p_{fps}",
+                &sample_config("mp4", "libx264"),
+                None
+            ),
+            "show.S01E01_unknownp_unknown"
+        );
+    }
+
+    #[test]
+    fn expand_output_template_replaces_invalid_characters() {
+        let mut config = sample_config("mp4", "libx264");
+        config.video_codec = "lib/x264".to_string();
+
+        assert_eq!(
+            expand("{vcodec}: <{name}>?", &config, None),
+            "lib_x264_ _show.S01E01__"
+        );
+        assert_eq!(expand("../{name}", &config, None), ".._show.S01E01");
+    }
+
+    #[test]
+    fn validate_output_template_rejects_unknown_tokens_and_braces() {
+        for template in ["", "   ", "{name", "name}", "{nmae}", "{{name}}", "{}"] {
+            assert!(
+                matches!(
+                    validate_output_template(template),
+                    Err(ConversionError::InvalidInput(_))
+                ),
+                "template {template:?} should be rejected"
+            );
+        }
+        validate_output_template("{name}_{date}").expect("known tokens should validate");
+    }
+
+    #[test]
+    fn expand_output_template_rejects_names_that_collapse_to_dots() {
+        let config = sample_config("mp4", "libx264");
+        let context = OutputTemplateContext {
+            input_stem: "",
+            config: &config,
+            probe: None,
+            timestamp: UNIX_EPOCH,
+        };
+
+        assert!(expand_output_template("..{name}", &context).is_err());
+        assert!(expand_output_template(" {name} ", &context).is_err());
+    }
+
+    #[test]
+    fn civil_from_days_handles_epoch_and_leap_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+}
@@ -412,6 +412,15 @@ fn apply_resolution_dimensions(config: &ConversionConfig, dimensions: Dimensions
         };
     }
 
+    if config.resolution == "fit" {
+        return fit_dimensions(
+            dimensions.width,
+            dimensions.height,
+            parse_dimension(config.custom_width.as_deref()).unwrap_or(dimensions.width),
+            parse_dimension(config.custom_height.as_deref()).unwrap_or(dimensions.height),
+        );
+    }
+
     let Some(target_height) = (match config.resolution.as_str() {
         "1080p" => Some(1080),
         "720p" => Some(720),
@@ -517,7 +526,7 @@ fn format_seconds(seconds: f64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{CropConfig, MetadataConfig, OverlayConfig};
+    use crate::types::{CropConfig, MetadataConfig, OverlayConfig, OverwritePolicy, SeekMode};
 
     fn default_config() -> ConversionConfig {
         ConversionConfig {
@@ -548,11 +557,14 @@ mod tests {
             custom_height: None,
             scaling_algorithm: "lanczos".to_string(),
             fps: "original".to_string(),
+            input_framerate: None,
             crf: 23,
             quality: 50,
             preset: "medium".to_string(),
             start_time: None,
             end_time: None,
+            segments: Vec::new(),
+            remove_segments: Vec::new(),
             metadata: MetadataConfig::default(),
             rotation: "0".to_string(),
             flip_horizontal: false,
@@ -563,6 +575,7 @@ mod tests {
             nvenc_temporal_aq: false,
             videotoolbox_allow_sw: false,
             hw_decode: false,
+            hw_device: None,
             pixel_format: "auto".to_string(),
             image_jpeg_quality: 85,
             image_jpeg_huffman: "optimal".to_string(),
@@ -573,9 +586,21 @@ mod tests {
             image_png_compression: 9,
             image_png_prediction: "paeth".to_string(),
             image_tiff_compression: "packbits".to_string(),
+            image_avif_crf: 30,
+            image_target_max_kb: None,
             gif_colors: 256,
             gif_dither: "sierra2_4a".to_string(),
             gif_loop: 0,
+            overwrite_policy: OverwritePolicy::default(),
+            split: None,
+            split_by_chapters: false,
+            image_sequence_output: false,
+            smart_cut: false,
+            seek_mode: SeekMode::default(),
+            parallel_chunks: None,
+            verify_output: false,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
         }
     }
 
@@ -1,22 +1,228 @@
 //! `FFprobe` argument construction and metadata parsing.
 
-use std::path::Path;
+use std::{fs, io, path::Path};
 
-use crate::error::ConversionError;
-use crate::types::{AudioTrack, FfprobeOutput, FfprobeStream, ProbeMetadata, SubtitleTrack};
-use crate::utils::{parse_frame_rate_string, parse_probe_bitrate};
+use crate::error::{ConversionError, ProbeFailure, ProbeFailureKind};
+use crate::network_input::{is_network_input, network_input_args};
+use crate::types::{
+    Attachment, AudioTrack, Chapter, ContentLightLevel, CoverArtInfo, DataStream, FfprobeChapter,
+    FfprobeOutput, FfprobeSideData, FfprobeStream, MasteringDisplayMetadata, PreprobedSource,
+    ProbeMetadata, SourceFingerprint, SubtitleFormat, SubtitleTrack,
+};
+use crate::utils::{
+    is_bitmap_subtitle_codec, is_text_subtitle_codec, parse_frame_rate_string, parse_probe_bitrate,
+    pixel_format_bit_depth,
+};
+
+/// Most stderr lines kept as details of a [`ProbeFailure`].
+pub const PROBE_FAILURE_DETAIL_LINES: usize = 3;
+
+/// Stderr fragments per failure kind, checked in order so that specific
+/// causes win over the generic "Invalid data" message `FFprobe` prints last.
+const PROBE_FAILURE_PATTERNS: &[(ProbeFailureKind, &[&str])] = &[
+    (
+        ProbeFailureKind::Encrypted,
+        &[
+            "encrypted",
+            "decryption key",
+            "drm protected",
+            "drm-protected",
+        ],
+    ),
+    (
+        ProbeFailureKind::FileNotFound,
+        &["no such file or directory", "cannot find the file"],
+    ),
+    (
+        ProbeFailureKind::PermissionDenied,
+        &[
+            "permission denied",
+            "access is denied",
+            "operation not permitted",
+        ],
+    ),
+    (
+        ProbeFailureKind::Corrupt,
+        &[
+            "moov atom not found",
+            "truncat",
+            "partial file",
+            "error reading header",
+            "invalid nal unit",
+            "corrupt",
+        ],
+    ),
+    (
+        ProbeFailureKind::UnrecognizedFormat,
+        &["invalid data found when processing input", "unknown format"],
+    ),
+];
+
+/// Extensions of HEIC/HEIF photos.
+const HEIF_EXTENSIONS: [&str; 3] = ["heic", "heif", "hif"];
+
+/// Extensions of camera RAW files, which `FFmpeg` can only read through the
+/// TIFF structure and embedded preview some of them carry.
+const CAMERA_RAW_EXTENSIONS: [&str; 10] = [
+    "dng", "cr2", "cr3", "nef", "arw", "raf", "orf", "rw2", "pef", "srw",
+];
+
+const MASTERING_DISPLAY_SIDE_DATA: &str = "Mastering display metadata";
+const CONTENT_LIGHT_LEVEL_SIDE_DATA: &str = "Content light level metadata";
 
 #[must_use]
 pub fn ffprobe_json_args(file_path: &str) -> Vec<String> {
-    vec![
+    let mut args = vec![
         "-v".to_string(),
         "quiet".to_string(),
         "-print_format".to_string(),
         "json".to_string(),
         "-show_format".to_string(),
         "-show_streams".to_string(),
-        file_path.to_string(),
-    ]
+        "-show_chapters".to_string(),
+    ];
+    args.extend(network_input_args(file_path));
+    args.push(file_path.to_string());
+    args
+}
+
+/// Longest source range that [`ffprobe_count_packets_args`] should scan;
+/// counting packets reads the whole range, so longer files fall back to
+/// time-based estimates.
+pub const FRAME_COUNT_MAX_SCAN_SECONDS: f64 = 1800.0;
+
+/// Builds an `ffprobe` pass that counts the packets of the first video stream.
+///
+/// Limited ranges are read from the keyframe preceding `start_seconds`, so
+/// trimmed counts are approximate.
+#[must_use]
+pub fn ffprobe_count_packets_args(
+    file_path: &str,
+    start_seconds: Option<f64>,
+    end_seconds: Option<f64>,
+) -> Vec<String> {
+    let mut args = vec![
+        "-v".to_string(),
+        "error".to_string(),
+        "-select_streams".to_string(),
+        "v:0".to_string(),
+        "-count_packets".to_string(),
+        "-show_entries".to_string(),
+        "stream=nb_read_packets".to_string(),
+        "-of".to_string(),
+        "csv=p=0".to_string(),
+    ];
+
+    if start_seconds.is_some() || end_seconds.is_some() {
+        let start = start_seconds.map_or_else(String::new, |start| format!("{start:.3}"));
+        let end = end_seconds.map_or_else(String::new, |end| format!("{end:.3}"));
+        args.push("-read_intervals".to_string());
+        args.push(format!("{start}%{end}"));
+    }
+
+    args.push(file_path.to_string());
+    args
+}
+
+/// Parses the `nb_read_packets` value printed by [`ffprobe_count_packets_args`].
+#[must_use]
+pub fn parse_packet_count(stdout: &str) -> Option<u64> {
+    stdout
+        .lines()
+        .map(|line| line.trim().trim_end_matches(','))
+        .find(|line| !line.is_empty())?
+        .parse()
+        .ok()
+}
+
+#[must_use]
+pub fn frame_count_scan_allowed(scan_seconds: f64) -> bool {
+    scan_seconds.is_finite() && scan_seconds > 0.0 && scan_seconds <= FRAME_COUNT_MAX_SCAN_SECONDS
+}
+
+/// Categorizes a failed `FFprobe` run from its stderr, keeping the lines that
+/// identify the cause.
+///
+/// `exit_status` describes the process exit and is reported when stderr is
+/// empty.
+#[must_use]
+pub fn classify_probe_failure(stderr: &str, exit_status: &str) -> ProbeFailure {
+    let lines: Vec<&str> = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    for (kind, patterns) in PROBE_FAILURE_PATTERNS {
+        let matching: Vec<String> = lines
+            .iter()
+            .filter(|line| {
+                let line = line.to_ascii_lowercase();
+                patterns.iter().any(|pattern| line.contains(pattern))
+            })
+            .take(PROBE_FAILURE_DETAIL_LINES)
+            .map(|line| (*line).to_string())
+            .collect();
+        if !matching.is_empty() {
+            return ProbeFailure {
+                kind: *kind,
+                details: matching,
+            };
+        }
+    }
+
+    let details = if lines.is_empty() {
+        vec![format!("ffprobe exited with status {exit_status}")]
+    } else {
+        lines[lines.len().saturating_sub(PROBE_FAILURE_DETAIL_LINES)..]
+            .iter()
+            .map(|line| (*line).to_string())
+            .collect()
+    };
+    ProbeFailure {
+        kind: ProbeFailureKind::Other,
+        details,
+    }
+}
+
+/// Names the format when `FFprobe` failed on a HEIC/HEIF or camera RAW still,
+/// which it otherwise reports as unrecognized or corrupt data although the
+/// build is usually what lacks support.
+///
+/// `heif_supported` tells whether the `FFmpeg` build decodes HEIF, when it
+/// is known. Other failures are returned unchanged.
+#[must_use]
+pub fn explain_unsupported_still(
+    failure: ProbeFailure,
+    file_path: &str,
+    heif_supported: Option<bool>,
+) -> ProbeFailure {
+    if !matches!(
+        failure.kind,
+        ProbeFailureKind::UnrecognizedFormat | ProbeFailureKind::Corrupt | ProbeFailureKind::Other
+    ) {
+        return failure;
+    }
+    let extension = file_extension(file_path);
+    let explanation = if HEIF_EXTENSIONS.contains(&extension.as_str()) {
+        if heif_supported == Some(false) {
+            "HEIC/HEIF photos need FFmpeg 7.1 or newer, and this build is older"
+        } else {
+            "This FFmpeg build was not compiled with HEIC/HEIF support"
+        }
+    } else if CAMERA_RAW_EXTENSIONS.contains(&extension.as_str()) {
+        "Camera RAW files are read through their embedded preview, which this FFmpeg build could not decode; export a JPEG or TIFF from a RAW developer instead"
+    } else {
+        return failure;
+    };
+
+    let mut details = vec![explanation.to_string()];
+    details.extend(failure.details);
+    details.truncate(PROBE_FAILURE_DETAIL_LINES);
+    ProbeFailure {
+        kind: ProbeFailureKind::UnsupportedImage,
+        details,
+    }
 }
 
 /// Parses `ffprobe` JSON output into Frame source metadata.
@@ -49,7 +255,29 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
         metadata.tags = Some(tags);
     }
 
-    if let Some(video_stream) = probe_data.streams.iter().find(|s| s.codec_type == "video") {
+    if let Some(cover_stream) = probe_data
+        .streams
+        .iter()
+        .find(|s| s.codec_type == "video" && is_attached_picture(s))
+        && let Some(codec) = recognized_codec_name(cover_stream.codec_name.as_deref())
+    {
+        metadata.cover_art = Some(CoverArtInfo {
+            index: cover_stream.index,
+            codec: codec.to_string(),
+            width: cover_stream
+                .width
+                .and_then(|width| u32::try_from(width).ok()),
+            height: cover_stream
+                .height
+                .and_then(|height| u32::try_from(height).ok()),
+        });
+    }
+
+    if let Some(video_stream) = probe_data
+        .streams
+        .iter()
+        .find(|s| s.codec_type == "video" && !is_attached_picture(s))
+    {
         metadata.video_codec.clone_from(&video_stream.codec_name);
         metadata.pixel_format.clone_from(&video_stream.pix_fmt);
         metadata.color_space.clone_from(&video_stream.color_space);
@@ -58,6 +286,34 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
             .color_primaries
             .clone_from(&video_stream.color_primaries);
         metadata.profile.clone_from(&video_stream.profile);
+        metadata.video_level = video_stream.level.filter(|level| *level > 0);
+        metadata.field_order = video_stream
+            .field_order
+            .clone()
+            .filter(|order| !order.eq_ignore_ascii_case("unknown"));
+        metadata
+            .color_transfer
+            .clone_from(&video_stream.color_transfer);
+        metadata.bit_depth = video_stream
+            .bits_per_raw_sample
+            .as_deref()
+            .and_then(|bits| bits.trim().parse().ok())
+            .filter(|bits| *bits > 0)
+            .or_else(|| {
+                video_stream
+                    .pix_fmt
+                    .as_deref()
+                    .and_then(pixel_format_bit_depth)
+            });
+        metadata.mastering_display = side_data_of_type(video_stream, MASTERING_DISPLAY_SIDE_DATA)
+            .map(mastering_display_from_side_data);
+        metadata.content_light_level =
+            side_data_of_type(video_stream, CONTENT_LIGHT_LEVEL_SIDE_DATA).and_then(|side_data| {
+                Some(ContentLightLevel {
+                    max_content: side_data.max_content?,
+                    max_average: side_data.max_average?,
+                })
+            });
 
         if let (Some(width), Some(height)) = (video_stream.width, video_stream.height)
             && width > 0
@@ -65,6 +321,12 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
         {
             let (display_width, display_height) =
                 display_oriented_dimensions(width, height, video_stream);
+            metadata.rotation = video_stream
+                .side_data_list
+                .iter()
+                .filter_map(|side_data| side_data.rotation)
+                .find(|rotation| rotation.is_finite())
+                .map(|rotation| rotation.rem_euclid(360.0));
             metadata.width = u32::try_from(display_width).ok();
             metadata.height = u32::try_from(display_height).ok();
             metadata.resolution = Some(format!("{display_width}x{display_height}"));
@@ -77,6 +339,12 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
         if metadata.video_bitrate_kbps.is_none() {
             metadata.video_bitrate_kbps = parse_probe_bitrate(video_stream.bit_rate.as_deref());
         }
+
+        metadata.frame_count = video_stream
+            .nb_frames
+            .as_deref()
+            .and_then(|frames| frames.trim().parse().ok())
+            .filter(|frames| *frames > 0);
     }
 
     for stream in probe_data
@@ -101,6 +369,10 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
             language,
             bitrate_kbps: track_bitrate,
             sample_rate: stream.sample_rate.clone(),
+            channel_layout: stream.channel_layout.clone(),
+            profile: stream.profile.clone(),
+            is_default: stream.disposition.default != 0,
+            is_forced: stream.disposition.forced != 0,
         });
     }
 
@@ -120,9 +392,42 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
             codec: codec.to_string(),
             language,
             label,
+            format: subtitle_format(codec),
+            is_default: stream.disposition.default != 0,
+            is_forced: stream.disposition.forced != 0,
         });
     }
 
+    for stream in &probe_data.streams {
+        let label = stream.tags.as_ref().and_then(|t| t.title.clone());
+        match stream.codec_type.as_str() {
+            "attachment" => metadata.attachments.push(Attachment {
+                index: stream.index,
+                filename: stream.tags.as_ref().and_then(|t| t.filename.clone()),
+                mimetype: stream.tags.as_ref().and_then(|t| t.mimetype.clone()),
+                size_bytes: stream.extradata_size,
+            }),
+            "data" => metadata.data_streams.push(DataStream {
+                index: stream.index,
+                codec: recognized_codec_name(stream.codec_name.as_deref()).map(str::to_string),
+                label,
+            }),
+            _ => {}
+        }
+    }
+
+    for chapter in &probe_data.chapters {
+        match chapter_from_ffprobe(chapter) {
+            Some(parsed) => metadata.chapters.push(parsed),
+            None => metadata.warnings.push(format!(
+                "Skipped chapter {} with malformed timestamps",
+                chapter
+                    .id
+                    .map_or_else(|| "without id".to_string(), |id| id.to_string())
+            )),
+        }
+    }
+
     if let Some(first_audio) = metadata.audio_tracks.first() {
         metadata.audio_codec = Some(first_audio.codec.clone());
     }
@@ -140,10 +445,18 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
         }
     }
 
+    metadata.animated = is_animated_image(&metadata, source_format_name.as_deref());
+    if metadata.animated
+        && let Some(frames) = metadata.frame_count
+    {
+        set_animated_frame_count(&mut metadata, frames);
+    }
+
     let has_audio = !metadata.audio_tracks.is_empty();
     let has_video = metadata.video_codec.is_some();
     metadata.media_kind = if has_video {
         if !has_audio
+            && !metadata.animated
             && (is_known_image_extension(file_path)
                 || format_name_indicates_image(source_format_name.as_deref()))
         {
@@ -165,6 +478,81 @@ fn metadata_from_ffprobe(file_path: &str, probe_data: FfprobeOutput) -> ProbeMet
     metadata
 }
 
+const fn is_attached_picture(stream: &FfprobeStream) -> bool {
+    stream.disposition.attached_pic != 0
+}
+
+fn chapter_from_ffprobe(chapter: &FfprobeChapter) -> Option<Chapter> {
+    let start_seconds = parse_frame_rate_string(chapter.start_time.as_deref())?;
+    let end_seconds = parse_frame_rate_string(chapter.end_time.as_deref())?;
+    if !start_seconds.is_finite() || start_seconds < 0.0 || end_seconds < start_seconds {
+        return None;
+    }
+
+    Some(Chapter {
+        id: chapter.id.unwrap_or_default(),
+        start_seconds,
+        end_seconds,
+        title: chapter.tags.as_ref().and_then(|tags| tags.title.clone()),
+    })
+}
+
+/// Names the HDR transfer function of a `color_transfer` value, if any.
+#[must_use]
+pub fn hdr_transfer_name(color_transfer: &str) -> Option<&'static str> {
+    match color_transfer.trim().to_ascii_lowercase().as_str() {
+        "smpte2084" => Some("PQ"),
+        "arib-std-b67" => Some("HLG"),
+        _ => None,
+    }
+}
+
+/// Returns whether a probed `field_order` describes interlaced fields.
+#[must_use]
+pub fn field_order_is_interlaced(field_order: &str) -> bool {
+    matches!(
+        field_order.trim().to_ascii_lowercase().as_str(),
+        "tt" | "bb" | "tb" | "bt"
+    )
+}
+
+fn subtitle_format(codec: &str) -> Option<SubtitleFormat> {
+    if is_text_subtitle_codec(codec) {
+        Some(SubtitleFormat::Text)
+    } else if is_bitmap_subtitle_codec(codec) {
+        Some(SubtitleFormat::Bitmap)
+    } else {
+        None
+    }
+}
+
+fn side_data_of_type<'a>(
+    stream: &'a FfprobeStream,
+    side_data_type: &str,
+) -> Option<&'a FfprobeSideData> {
+    stream.side_data_list.iter().find(|side_data| {
+        side_data
+            .side_data_type
+            .as_deref()
+            .is_some_and(|value| value.eq_ignore_ascii_case(side_data_type))
+    })
+}
+
+fn mastering_display_from_side_data(side_data: &FfprobeSideData) -> MasteringDisplayMetadata {
+    MasteringDisplayMetadata {
+        red_x: parse_frame_rate_string(side_data.red_x.as_deref()),
+        red_y: parse_frame_rate_string(side_data.red_y.as_deref()),
+        green_x: parse_frame_rate_string(side_data.green_x.as_deref()),
+        green_y: parse_frame_rate_string(side_data.green_y.as_deref()),
+        blue_x: parse_frame_rate_string(side_data.blue_x.as_deref()),
+        blue_y: parse_frame_rate_string(side_data.blue_y.as_deref()),
+        white_point_x: parse_frame_rate_string(side_data.white_point_x.as_deref()),
+        white_point_y: parse_frame_rate_string(side_data.white_point_y.as_deref()),
+        min_luminance: parse_frame_rate_string(side_data.min_luminance.as_deref()),
+        max_luminance: parse_frame_rate_string(side_data.max_luminance.as_deref()),
+    }
+}
+
 fn recognized_codec_name(codec_name: Option<&str>) -> Option<&str> {
     codec_name.map(str::trim).filter(|codec| {
         !codec.is_empty()
@@ -200,16 +588,99 @@ fn is_side_display_rotation(rotation: f64) -> bool {
     (normalized - 90.0).abs() < TOLERANCE_DEGREES || (normalized - 270.0).abs() < TOLERANCE_DEGREES
 }
 
-fn is_known_image_extension(file_path: &str) -> bool {
+/// Records the frame count of an animated image and derives its average
+/// frame rate.
+///
+/// The count comes from the header or a packet scan. GIF and APNG frames
+/// carry their own delays, so the stream's nominal rate is usually the
+/// timestamp tick rather than the playback rate.
+pub fn set_animated_frame_count(metadata: &mut ProbeMetadata, frames: u64) {
+    if frames == 0 {
+        return;
+    }
+    metadata.frame_count = Some(frames);
+    if let Some(duration) = metadata.duration_seconds() {
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "frame counts stay far below 2^52"
+        )]
+        let frame_rate = frames as f64 / duration;
+        metadata.frame_rate = Some(frame_rate);
+    }
+}
+
+/// Reads the size and modification time of a source. Network sources have
+/// neither, so their metadata is never reused.
+///
+/// # Errors
+///
+/// Returns the I/O error when the file's metadata cannot be read.
+pub fn source_fingerprint(file_path: &str) -> io::Result<SourceFingerprint> {
+    if is_network_input(file_path) {
+        return Ok(SourceFingerprint {
+            size_bytes: 0,
+            modified: None,
+        });
+    }
+    let metadata = fs::metadata(file_path)?;
+    Ok(SourceFingerprint {
+        size_bytes: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+/// Metadata probed ahead of a conversion, when the source still has the
+/// size and modification time it had then.
+///
+/// Files whose modification time cannot be read are always probed again,
+/// since a rewrite of the same size would go unnoticed.
+#[must_use]
+pub fn fresh_preprobed_metadata<'a>(
+    preprobed: Option<&'a PreprobedSource>,
+    file_path: &str,
+) -> Option<&'a ProbeMetadata> {
+    let preprobed = preprobed.filter(|preprobed| preprobed.fingerprint.modified.is_some())?;
+    let current = source_fingerprint(file_path).ok()?;
+    (current == preprobed.fingerprint).then_some(&preprobed.metadata)
+}
+
+/// GIF, APNG and WebP sources are animated when the header lists several
+/// frames or, lacking a count, when the animation demuxer reports a
+/// duration.
+fn is_animated_image(metadata: &ProbeMetadata, format_name: Option<&str>) -> bool {
+    let Some(codec) = metadata.video_codec.as_deref() else {
+        return false;
+    };
+    if !matches!(codec, "gif" | "apng" | "webp") {
+        return false;
+    }
+    metadata.frame_count.map_or_else(
+        || {
+            metadata.duration_seconds().is_some()
+                && format_name.is_some_and(|raw| {
+                    raw.split(',')
+                        .map(str::trim)
+                        .any(|name| matches!(name, "gif" | "apng" | "webp"))
+                })
+        },
+        |frames| frames > 1,
+    )
+}
+
+fn file_extension(file_path: &str) -> String {
     Path::new(file_path)
         .extension()
-        .and_then(|ext| ext.to_str())
-        .is_some_and(|ext| {
-            matches!(
-                ext.to_ascii_lowercase().as_str(),
-                "png" | "jpg" | "jpeg" | "webp" | "bmp" | "tif" | "tiff" | "avif" | "heif" | "heic"
-            )
-        })
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_known_image_extension(file_path: &str) -> bool {
+    let extension = file_extension(file_path);
+    matches!(
+        extension.as_str(),
+        "png" | "jpg" | "jpeg" | "webp" | "bmp" | "tif" | "tiff" | "avif"
+    ) || HEIF_EXTENSIONS.contains(&extension.as_str())
+        || CAMERA_RAW_EXTENSIONS.contains(&extension.as_str())
 }
 
 fn format_name_indicates_image(format_name: Option<&str>) -> bool {
@@ -246,9 +717,113 @@ mod tests {
                 "json",
                 "-show_format",
                 "-show_streams",
+                "-show_chapters",
                 "/tmp/input.mp4"
             ]
         );
+        assert_eq!(
+            ffprobe_json_args("https://example.com/a.mp4")[7..],
+            [
+                "-rw_timeout",
+                "15000000",
+                "-timeout",
+                "15000000",
+                "https://example.com/a.mp4"
+            ]
+        );
+    }
+
+    #[test]
+    fn network_sources_are_always_probed_again() {
+        let url = "https://example.com/a.mp4";
+        let fingerprint = source_fingerprint(url).expect("urls need no file metadata");
+        let preprobed = PreprobedSource {
+            metadata: ProbeMetadata::default(),
+            fingerprint,
+        };
+
+        assert_eq!(fingerprint.modified, None);
+        assert!(fresh_preprobed_metadata(Some(&preprobed), url).is_none());
+    }
+
+    #[test]
+    fn ffprobe_count_packets_args_limit_trimmed_ranges() {
+        let full = ffprobe_count_packets_args("/tmp/input.mp4", None, None);
+        let trimmed = ffprobe_count_packets_args("/tmp/input.mp4", Some(10.0), Some(25.5));
+        let open_ended = ffprobe_count_packets_args("/tmp/input.mp4", None, Some(4.0));
+
+        assert!(!full.iter().any(|arg| arg == "-read_intervals"));
+        assert!(
+            trimmed
+                .windows(2)
+                .any(|pair| pair == ["-read_intervals", "10.000%25.500"])
+        );
+        assert!(
+            open_ended
+                .windows(2)
+                .any(|pair| pair == ["-read_intervals", "%4.000"])
+        );
+        assert_eq!(trimmed.last().map(String::as_str), Some("/tmp/input.mp4"));
+    }
+
+    #[test]
+    fn parse_packet_count_reads_csv_output() {
+        assert_eq!(parse_packet_count("1438\n"), Some(1438));
+        assert_eq!(parse_packet_count("\n720,\n"), Some(720));
+        assert_eq!(parse_packet_count("N/A"), None);
+    }
+
+    #[test]
+    fn frame_count_scan_allowed_skips_long_or_unknown_ranges() {
+        assert!(frame_count_scan_allowed(600.0));
+        assert!(!frame_count_scan_allowed(
+            FRAME_COUNT_MAX_SCAN_SECONDS + 1.0
+        ));
+        assert!(!frame_count_scan_allowed(0.0));
+    }
+
+    #[test]
+    fn classify_probe_failure_prefers_specific_causes() {
+        let truncated = classify_probe_failure(
+            "[mov,mp4,m4a,3gp,3g2,mj2 @ 0x7f9] moov atom not found\n/tmp/clip.mp4: Invalid data found when processing input\n",
+            "exit status: 1",
+        );
+        let encrypted = classify_probe_failure(
+            "[mov,mp4,m4a,3gp,3g2,mj2 @ 0x7f9] stream 0, could not find decryption key\n",
+            "exit status: 1",
+        );
+        let missing = classify_probe_failure(
+            "/tmp/missing.mkv: No such file or directory\n",
+            "exit status: 1",
+        );
+        let unknown = classify_probe_failure(
+            "/tmp/notes.txt: Invalid data found when processing input\n",
+            "exit status: 1",
+        );
+
+        assert_eq!(truncated.kind, ProbeFailureKind::Corrupt);
+        assert_eq!(
+            truncated.details,
+            ["[mov,mp4,m4a,3gp,3g2,mj2 @ 0x7f9] moov atom not found"]
+        );
+        assert_eq!(encrypted.kind, ProbeFailureKind::Encrypted);
+        assert_eq!(missing.kind, ProbeFailureKind::FileNotFound);
+        assert_eq!(unknown.kind, ProbeFailureKind::UnrecognizedFormat);
+        assert!(
+            encrypted
+                .to_string()
+                .starts_with("This file appears to be DRM-protected: ")
+        );
+    }
+
+    #[test]
+    fn classify_probe_failure_falls_back_to_last_stderr_lines() {
+        let failure = classify_probe_failure("one\ntwo\nthree\nfour\n", "exit status: 1");
+        let silent = classify_probe_failure("  \n", "signal: 9");
+
+        assert_eq!(failure.kind, ProbeFailureKind::Other);
+        assert_eq!(failure.details, ["two", "three", "four"]);
+        assert_eq!(silent.details, ["ffprobe exited with status signal: 9"]);
     }
 
     #[test]
@@ -258,6 +833,30 @@ mod tests {
         assert!(is_known_image_extension("C:\\frames\\shot.avif"));
         assert!(!is_known_image_extension("/tmp/clip.mp4"));
         assert!(!is_known_image_extension("/tmp/animation.gif"));
+        assert!(is_known_image_extension("/photos/IMG_0420.HEIC"));
+        assert!(is_known_image_extension("/photos/DSC_0042.NEF"));
+    }
+
+    #[test]
+    fn explain_unsupported_still_names_heic_and_raw_sources() {
+        let invalid = || {
+            classify_probe_failure(
+                "/photos/IMG_0420.HEIC: Invalid data found when processing input\n",
+                "exit status: 1",
+            )
+        };
+
+        let old_build = explain_unsupported_still(invalid(), "/photos/IMG_0420.HEIC", Some(false));
+        let raw = explain_unsupported_still(invalid(), "/photos/DSC_0042.nef", None);
+        let video = explain_unsupported_still(invalid(), "/clips/broken.mp4", Some(false));
+
+        assert_eq!(old_build.kind, ProbeFailureKind::UnsupportedImage);
+        assert!(old_build.to_string().starts_with(
+            "This FFmpeg build cannot decode this image format: HEIC/HEIF photos need FFmpeg 7.1"
+        ));
+        assert_eq!(old_build.details.len(), 2);
+        assert!(raw.details[0].starts_with("Camera RAW files"));
+        assert_eq!(video, invalid());
     }
 
     #[test]
@@ -335,6 +934,259 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_ffprobe_stdout_extracts_track_details_and_dispositions() {
+        let metadata = parse_ffprobe_stdout(
+            "/tmp/movie.mkv",
+            r#"{
+                "streams": [
+                    {
+                        "index": 0,
+                        "codec_type": "video",
+                        "codec_name": "h264",
+                        "profile": "High",
+                        "level": 41,
+                        "field_order": "tt",
+                        "pix_fmt": "yuv420p",
+                        "bits_per_raw_sample": "8"
+                    },
+                    {
+                        "index": 1,
+                        "codec_type": "audio",
+                        "codec_name": "aac",
+                        "profile": "LC",
+                        "level": -99,
+                        "channels": 6,
+                        "channel_layout": "5.1",
+                        "sample_rate": "48000",
+                        "bit_rate": "384000",
+                        "disposition": { "default": 1, "forced": 0 },
+                        "tags": { "language": "eng", "title": "Surround" }
+                    },
+                    {
+                        "index": 2,
+                        "codec_type": "audio",
+                        "codec_name": "opus"
+                    },
+                    {
+                        "index": 3,
+                        "codec_type": "subtitle",
+                        "codec_name": "hdmv_pgs_subtitle",
+                        "disposition": { "default": 0, "forced": 1 },
+                        "tags": { "language": "eng" }
+                    },
+                    {
+                        "index": 4,
+                        "codec_type": "subtitle",
+                        "codec_name": "ass"
+                    }
+                ],
+                "format": {}
+            }"#,
+        )
+        .expect("probe metadata should parse");
+
+        assert_eq!(metadata.profile.as_deref(), Some("High"));
+        assert_eq!(metadata.video_level, Some(41));
+        assert_eq!(metadata.field_order.as_deref(), Some("tt"));
+        assert!(field_order_is_interlaced("tt"));
+        assert!(!field_order_is_interlaced("progressive"));
+        assert_eq!(metadata.bit_depth, Some(8));
+
+        let surround = &metadata.audio_tracks[0];
+        assert_eq!(surround.channels, "6");
+        assert_eq!(surround.channel_layout.as_deref(), Some("5.1"));
+        assert_eq!(surround.sample_rate.as_deref(), Some("48000"));
+        assert_eq!(surround.profile.as_deref(), Some("LC"));
+        assert!(surround.is_default && !surround.is_forced);
+
+        let sparse = &metadata.audio_tracks[1];
+        assert_eq!(sparse.channels, "?");
+        assert!(sparse.channel_layout.is_none() && sparse.sample_rate.is_none());
+        assert!(!sparse.is_default);
+
+        let pgs = &metadata.subtitle_tracks[0];
+        assert_eq!(pgs.format, Some(SubtitleFormat::Bitmap));
+        assert!(pgs.is_forced && !pgs.is_default);
+        assert_eq!(
+            metadata.subtitle_tracks[1].format,
+            Some(SubtitleFormat::Text)
+        );
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_extracts_chapters_attachments_and_data_streams() {
+        let metadata = parse_ffprobe_stdout(
+            "/tmp/anime.mkv",
+            r#"{
+                "streams": [
+                    { "index": 0, "codec_type": "video", "codec_name": "h264" },
+                    {
+                        "index": 1,
+                        "codec_type": "attachment",
+                        "codec_name": "ttf",
+                        "extradata_size": 163452,
+                        "tags": { "filename": "Signs.ttf", "mimetype": "font/ttf" }
+                    },
+                    {
+                        "index": 2,
+                        "codec_type": "data",
+                        "codec_tag_string": "tmcd",
+                        "tags": { "title": "Timecode" }
+                    }
+                ],
+                "chapters": [
+                    {
+                        "id": 0,
+                        "start_time": "0.000000",
+                        "end_time": "90.500000",
+                        "tags": { "title": "Opening" }
+                    },
+                    { "id": 1, "start_time": "N/A", "end_time": "120.000000" },
+                    { "id": 2, "start_time": "90.500000", "end_time": "1440.000000" }
+                ],
+                "format": {}
+            }"#,
+        )
+        .expect("probe metadata should parse");
+
+        assert_eq!(
+            metadata.attachments,
+            vec![Attachment {
+                index: 1,
+                filename: Some("Signs.ttf".to_string()),
+                mimetype: Some("font/ttf".to_string()),
+                size_bytes: Some(163_452),
+            }]
+        );
+        assert_eq!(metadata.data_streams[0].index, 2);
+        assert_eq!(metadata.data_streams[0].label.as_deref(), Some("Timecode"));
+        assert_eq!(
+            metadata
+                .chapters
+                .iter()
+                .map(|chapter| (chapter.id, chapter.title.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![(0, Some("Opening")), (2, None)]
+        );
+        assert_eq!(metadata.warnings.len(), 1);
+        assert!(metadata.warnings[0].contains("chapter 1"));
+    }
+
+    #[test]
+    fn probe_metadata_omits_empty_chapter_and_attachment_lists_when_serialized() {
+        let value = serde_json::to_value(ProbeMetadata::default())
+            .expect("probe metadata should serialize");
+
+        assert!(value.get("chapters").is_none());
+        assert!(value.get("attachments").is_none());
+        assert!(value.get("warnings").is_none());
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_classifies_attached_pictures_as_cover_art() {
+        let mp3 = parse_ffprobe_stdout(
+            "/tmp/track.mp3",
+            include_str!("../tests/fixtures/ffprobe_mp3_cover_art.json"),
+        )
+        .expect("mp3 fixture should parse");
+        let flac = parse_ffprobe_stdout(
+            "/tmp/track.flac",
+            include_str!("../tests/fixtures/ffprobe_flac_cover_art.json"),
+        )
+        .expect("flac fixture should parse");
+
+        assert_eq!(mp3.media_kind, "audio");
+        assert_eq!(mp3.video_codec, None);
+        assert_eq!(mp3.resolution, None);
+        assert_eq!(
+            mp3.cover_art,
+            Some(CoverArtInfo {
+                index: 1,
+                codec: "mjpeg".to_string(),
+                width: Some(600),
+                height: Some(600),
+            })
+        );
+        assert_eq!(flac.media_kind, "audio");
+        assert_eq!(flac.video_codec, None);
+        assert_eq!(
+            flac.cover_art.map(|cover| (cover.index, cover.codec)),
+            Some((1, "png".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_extracts_hdr10_color_metadata() {
+        let metadata = parse_ffprobe_stdout(
+            "/tmp/hdr10.mp4",
+            include_str!("../tests/fixtures/ffprobe_hdr10.json"),
+        )
+        .expect("HDR10 fixture should parse");
+
+        assert_eq!(metadata.pixel_format.as_deref(), Some("yuv420p10le"));
+        assert_eq!(metadata.color_primaries.as_deref(), Some("bt2020"));
+        assert_eq!(metadata.color_transfer.as_deref(), Some("smpte2084"));
+        assert_eq!(metadata.color_space.as_deref(), Some("bt2020nc"));
+        assert_eq!(metadata.bit_depth, Some(10));
+        assert_eq!(metadata.frame_count, Some(1438));
+        assert_eq!(
+            hdr_transfer_name(metadata.color_transfer.as_deref().unwrap_or_default()),
+            Some("PQ")
+        );
+
+        let mastering_display = metadata
+            .mastering_display
+            .expect("mastering display side data should parse");
+        assert!(
+            mastering_display
+                .red_x
+                .is_some_and(|value| (value - 0.68).abs() < 1e-9)
+        );
+        assert!(
+            mastering_display
+                .white_point_y
+                .is_some_and(|value| (value - 0.329).abs() < 1e-9)
+        );
+        assert!(
+            mastering_display
+                .min_luminance
+                .is_some_and(|value| (value - 0.005).abs() < 1e-9)
+        );
+        assert!(
+            mastering_display
+                .max_luminance
+                .is_some_and(|value| (value - 1000.0).abs() < 1e-9)
+        );
+        assert_eq!(
+            metadata.content_light_level,
+            Some(ContentLightLevel {
+                max_content: 1000,
+                max_average: 400,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_extracts_hlg_color_metadata() {
+        let metadata = parse_ffprobe_stdout(
+            "/tmp/hlg.mov",
+            include_str!("../tests/fixtures/ffprobe_hlg.json"),
+        )
+        .expect("HLG fixture should parse");
+
+        assert_eq!(metadata.color_transfer.as_deref(), Some("arib-std-b67"));
+        assert_eq!(metadata.color_primaries.as_deref(), Some("bt2020"));
+        assert_eq!(metadata.bit_depth, Some(10));
+        assert_eq!(metadata.resolution.as_deref(), Some("1080x1920"));
+        assert_eq!(
+            hdr_transfer_name(metadata.color_transfer.as_deref().unwrap_or_default()),
+            Some("HLG")
+        );
+        assert!(metadata.mastering_display.is_none());
+        assert!(metadata.content_light_level.is_none());
+    }
+
     #[test]
     fn parse_ffprobe_stdout_omits_streams_without_a_recognized_codec() {
         let metadata = parse_ffprobe_stdout(
@@ -408,6 +1260,51 @@ mod tests {
             ),
             (Some(2160), Some(3840), Some("2160x3840"))
         );
+        assert_eq!(metadata.rotation, Some(270.0));
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_keeps_timing_of_animated_gifs() {
+        let mut metadata = parse_ffprobe_stdout(
+            "/tmp/reaction.gif",
+            include_str!("../tests/fixtures/ffprobe_animated_gif.json"),
+        )
+        .unwrap();
+
+        assert!(metadata.animated);
+        assert_eq!(metadata.media_kind, "video");
+        assert_eq!(metadata.duration_seconds(), Some(2.4));
+        assert_eq!(metadata.frame_count, None);
+        assert_eq!(metadata.frame_rate, Some(100.0 / 7.0));
+
+        set_animated_frame_count(&mut metadata, 36);
+
+        assert_eq!(metadata.frame_count, Some(36));
+        assert_eq!(metadata.frame_rate, Some(15.0));
+    }
+
+    #[test]
+    fn parse_ffprobe_stdout_treats_single_frame_webp_as_a_still() {
+        let metadata = parse_ffprobe_stdout(
+            "/tmp/photo.webp",
+            r#"{
+                "streams": [
+                    {
+                        "index": 0,
+                        "codec_type": "video",
+                        "codec_name": "webp",
+                        "width": 800,
+                        "height": 600,
+                        "nb_frames": "1"
+                    }
+                ],
+                "format": { "format_name": "webp_pipe" }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(!metadata.animated);
+        assert_eq!(metadata.media_kind, "image");
     }
 
     #[test]
@@ -440,4 +1337,38 @@ mod tests {
         assert_eq!(metadata.frame_rate, None);
         assert_eq!(metadata.video_bitrate_kbps, None);
     }
+
+    #[test]
+    fn preprobed_metadata_is_used_only_while_the_source_is_unchanged() {
+        let path = std::env::temp_dir().join(format!("frame-preprobed-{}", std::process::id()));
+        fs::write(&path, b"first").expect("source should be written");
+        let file_path = path.to_string_lossy().into_owned();
+        let preprobed = PreprobedSource {
+            metadata: ProbeMetadata {
+                duration: Some("12.5".to_string()),
+                ..ProbeMetadata::default()
+            },
+            fingerprint: source_fingerprint(&file_path).expect("fingerprint should be read"),
+        };
+
+        let fresh = fresh_preprobed_metadata(Some(&preprobed), &file_path);
+        let unknown_time = PreprobedSource {
+            fingerprint: SourceFingerprint {
+                modified: None,
+                ..preprobed.fingerprint
+            },
+            ..preprobed.clone()
+        };
+        let without_time = fresh_preprobed_metadata(Some(&unknown_time), &file_path);
+        fs::write(&path, b"rewritten").expect("source should be rewritten");
+        let stale = fresh_preprobed_metadata(Some(&preprobed), &file_path);
+        let _ = fs::remove_file(&path);
+        let missing = fresh_preprobed_metadata(Some(&preprobed), &file_path);
+
+        assert_eq!(fresh, Some(&preprobed.metadata));
+        assert_eq!(without_time, None);
+        assert_eq!(stale, None);
+        assert_eq!(missing, None);
+        assert_eq!(fresh_preprobed_metadata(None, &file_path), None);
+    }
 }
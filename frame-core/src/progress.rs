@@ -0,0 +1,191 @@
+//! Parser for the `key=value` blocks `FFmpeg` writes with `-progress`.
+
+use crate::{types::ProgressStats, utils::parse_time};
+
+/// Global options that send structured progress to stdout and silence the
+/// interactive stats line on stderr.
+pub const FFMPEG_PROGRESS_ARGS: [&str; 3] = ["-progress", "pipe:1", "-nostats"];
+
+/// One progress block; fields `FFmpeg` reported as `N/A` stay `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FfmpegProgress {
+    pub frame: Option<u64>,
+    pub fps: Option<f64>,
+    pub bitrate_kbps: Option<f64>,
+    pub total_size: Option<u64>,
+    pub out_time_seconds: Option<f64>,
+    /// Encoding speed as a multiple of realtime.
+    pub speed: Option<f64>,
+    /// `FFmpeg` closed the stream with `progress=end`.
+    pub finished: bool,
+}
+
+impl FfmpegProgress {
+    /// Throughput fields carried on progress events.
+    #[must_use]
+    pub const fn stats(&self) -> ProgressStats {
+        ProgressStats {
+            fps: self.fps,
+            speed_x: self.speed,
+            bitrate_kbps: self.bitrate_kbps,
+            processed_seconds: self.out_time_seconds,
+            written_bytes: self.total_size,
+        }
+    }
+}
+
+/// Accumulates `-progress` lines into [`FfmpegProgress`] blocks.
+#[derive(Debug, Default)]
+pub struct FfmpegProgressParser {
+    current: FfmpegProgress,
+}
+
+impl FfmpegProgressParser {
+    /// Feeds one line, returning the completed block when `line` is the
+    /// `progress=` key that terminates it.
+    pub fn push_line(&mut self, line: &str) -> Option<FfmpegProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+        let current = &mut self.current;
+        match key.trim() {
+            "frame" => current.frame = value.parse().ok(),
+            "fps" => current.fps = parse_non_negative(value),
+            "bitrate" => {
+                current.bitrate_kbps = parse_non_negative(value.trim_end_matches("kbits/s"));
+            }
+            "total_size" => current.total_size = value.parse().ok(),
+            // `out_time_ms` is also in microseconds, a long-standing FFmpeg quirk.
+            "out_time_us" | "out_time_ms" => {
+                if let Some(micros) = parse_non_negative(value) {
+                    current.out_time_seconds = Some(micros / 1_000_000.0);
+                }
+            }
+            "out_time" if current.out_time_seconds.is_none() && !value.starts_with('-') => {
+                current.out_time_seconds = parse_time(value);
+            }
+            "speed" => current.speed = parse_non_negative(value.trim_end_matches('x')),
+            "progress" => {
+                current.finished = value == "end";
+                return Some(std::mem::take(current));
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+fn parse_non_negative(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIDEO_PROGRESS: &str = "frame=120
+fps=59.94
+stream_0_0_q=28.0
+bitrate=1534.2kbits/s
+total_size=786432
+out_time_us=4004000
+out_time_ms=4004000
+out_time=00:00:04.004000
+dup_frames=0
+drop_frames=0
+speed=2.01x
+progress=continue
+frame=240
+fps=60.00
+stream_0_0_q=-1.0
+bitrate=1498.7kbits/s
+total_size=1500000
+out_time_us=8008000
+out_time_ms=8008000
+out_time=00:00:08.008000
+dup_frames=0
+drop_frames=0
+speed=2.02x
+progress=end
+";
+
+    const AUDIO_START_PROGRESS: &str = "bitrate=N/A
+total_size=N/A
+out_time_us=N/A
+out_time_ms=N/A
+out_time=-577014:32:22.775808
+dup_frames=0
+drop_frames=0
+speed=N/A
+progress=continue
+bitrate= 128.0kbits/s
+total_size=262144
+out_time_us=16384000
+out_time_ms=16384000
+out_time=00:00:16.384000
+dup_frames=0
+drop_frames=0
+speed=41.2x
+progress=continue
+";
+
+    fn parse_blocks(output: &str) -> Vec<FfmpegProgress> {
+        let mut parser = FfmpegProgressParser::default();
+        output
+            .lines()
+            .filter_map(|line| parser.push_line(line))
+            .collect()
+    }
+
+    #[test]
+    fn parses_captured_video_progress_blocks() {
+        let blocks = parse_blocks(VIDEO_PROGRESS);
+
+        assert_eq!(
+            blocks[0],
+            FfmpegProgress {
+                frame: Some(120),
+                fps: Some(59.94),
+                bitrate_kbps: Some(1534.2),
+                total_size: Some(786_432),
+                out_time_seconds: Some(4.004),
+                speed: Some(2.01),
+                finished: false,
+            }
+        );
+        assert_eq!(blocks[1].frame, Some(240));
+        assert!(blocks[1].finished);
+    }
+
+    #[test]
+    fn parses_audio_progress_without_frame_keys() {
+        let blocks = parse_blocks(AUDIO_START_PROGRESS);
+
+        assert_eq!(blocks[0], FfmpegProgress::default());
+        assert_eq!(
+            blocks[1],
+            FfmpegProgress {
+                bitrate_kbps: Some(128.0),
+                total_size: Some(262_144),
+                out_time_seconds: Some(16.384),
+                speed: Some(41.2),
+                ..FfmpegProgress::default()
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_lines_outside_the_progress_format() {
+        let mut parser = FfmpegProgressParser::default();
+
+        assert_eq!(parser.push_line("Press [q] to stop"), None);
+        assert_eq!(parser.push_line(""), None);
+        assert_eq!(
+            parser.push_line("progress=continue"),
+            Some(FfmpegProgress::default())
+        );
+    }
+}
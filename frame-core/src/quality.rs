@@ -0,0 +1,359 @@
+//! Objective quality scores of a converted file against its source, from
+//! `FFmpeg`'s `libvmaf`, `ssim` and `psnr` filters.
+//!
+//! Both files are decoded in full. The converted file is scaled and cropped
+//! to the geometry of the source and, when its frame rate differs, retimed
+//! to the source rate, so the filters compare matching frames.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConversionError;
+use crate::types::ProbeMetadata;
+
+/// Duration difference, in seconds, below which two files count as equally
+/// long.
+pub const QUALITY_DURATION_TOLERANCE_SECONDS: f64 = 0.5;
+
+static SSIM_FRAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"lavfi\.ssim\.All=(\S+)").unwrap());
+
+static PSNR_FRAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"lavfi\.psnr\.psnr_avg=(\S+)").unwrap());
+
+static SSIM_SUMMARY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"SSIM Y:.*\bAll:(\S+)").unwrap());
+
+static PSNR_SUMMARY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"PSNR y:.*\baverage:(\S+)").unwrap());
+
+/// Score computed by a quality comparison.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityMetric {
+    /// Netflix VMAF, from 0 to 100; needs an `FFmpeg` built with `libvmaf`.
+    Vmaf,
+    /// Structural similarity, from 0 to 1.
+    Ssim,
+    /// Peak signal-to-noise ratio in dB; identical frames score infinity.
+    Psnr,
+}
+
+impl QualityMetric {
+    /// Name of the `FFmpeg` filter computing the metric.
+    #[must_use]
+    pub const fn filter_name(self) -> &'static str {
+        match self {
+            Self::Vmaf => "libvmaf",
+            Self::Ssim => "ssim",
+            Self::Psnr => "psnr",
+        }
+    }
+}
+
+/// Arguments of one comparison pass and what it will compare.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityComparisonPlan {
+    pub args: Vec<String>,
+    /// Seconds of each file that are compared, when both durations are known.
+    pub compared_seconds: Option<f64>,
+    /// Adjustments made so the files could be compared.
+    pub notes: Vec<String>,
+}
+
+/// Scores of a finished comparison.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityComparison {
+    pub metric: QualityMetric,
+    pub mean: f64,
+    pub min: f64,
+    /// Harmonic mean of the frame scores, reported for VMAF only.
+    pub harmonic_mean: Option<f64>,
+    pub frames: u64,
+    pub compared_seconds: Option<f64>,
+    pub notes: Vec<String>,
+}
+
+/// Builds a pass comparing `distorted` against `reference` with `metric`.
+///
+/// VMAF writes its frame and pooled scores as JSON to `vmaf_log_path`, which
+/// [`parse_vmaf_log`] reads; SSIM and PSNR log them to stderr for
+/// [`parse_frame_scores`]. When the files differ in length by more than
+/// [`QUALITY_DURATION_TOLERANCE_SECONDS`], only the start they share is
+/// compared.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when either file has no video
+/// stream or the reference size is unknown.
+pub fn build_quality_comparison_args(
+    reference: &str,
+    distorted: &str,
+    metric: QualityMetric,
+    reference_probe: &ProbeMetadata,
+    distorted_probe: &ProbeMetadata,
+    vmaf_log_path: &str,
+) -> Result<QualityComparisonPlan, ConversionError> {
+    if reference_probe.video_codec.is_none() || distorted_probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "Quality can only be compared between two files with video".to_string(),
+        ));
+    }
+    let (Some(width), Some(height)) = (reference_probe.width, reference_probe.height) else {
+        return Err(ConversionError::InvalidInput(
+            "The reference video size is unknown".to_string(),
+        ));
+    };
+
+    let mut notes = Vec::new();
+    let mut trim_to = None;
+    let compared_seconds = match (
+        reference_probe.duration_seconds(),
+        distorted_probe.duration_seconds(),
+    ) {
+        (Some(reference_seconds), Some(distorted_seconds)) => {
+            let overlap = reference_seconds.min(distorted_seconds);
+            if (reference_seconds - distorted_seconds).abs() > QUALITY_DURATION_TOLERANCE_SECONDS {
+                notes.push(format!(
+                    "Only the first {overlap:.2} s were compared: the reference lasts \
+                     {reference_seconds:.2} s and the compared file {distorted_seconds:.2} s"
+                ));
+                trim_to = Some(overlap);
+            }
+            Some(overlap)
+        }
+        _ => None,
+    };
+    if distorted_probe.width != Some(width) || distorted_probe.height != Some(height) {
+        notes.push(format!(
+            "The compared file was scaled to the reference size {width}x{height}"
+        ));
+    }
+    let retime = match (reference_probe.frame_rate, distorted_probe.frame_rate) {
+        (Some(reference_rate), Some(distorted_rate))
+            if (reference_rate - distorted_rate).abs() > 0.01 =>
+        {
+            notes.push(format!(
+                "The compared file was retimed from {distorted_rate:.3} to {reference_rate:.3} fps"
+            ));
+            format!("fps={reference_rate:.6},")
+        }
+        _ => String::new(),
+    };
+
+    let metric_filter = match metric {
+        QualityMetric::Vmaf => format!(
+            "libvmaf=log_fmt=json:log_path='{}'",
+            escape_filter_path(vmaf_log_path)
+        ),
+        QualityMetric::Ssim => "ssim,metadata=mode=print:key=lavfi.ssim.All".to_string(),
+        QualityMetric::Psnr => "psnr,metadata=mode=print:key=lavfi.psnr.psnr_avg".to_string(),
+    };
+    let filter_complex = format!(
+        "[0:v:0]scale={width}:{height}:force_original_aspect_ratio=increase:flags=bicubic,\
+         crop={width}:{height},{retime}setsar=1,format=yuv420p,setpts=PTS-STARTPTS[distorted];\
+         [1:v:0]setsar=1,format=yuv420p,setpts=PTS-STARTPTS[reference];\
+         [distorted][reference]{metric_filter}"
+    );
+
+    let mut args = vec!["-hide_banner".to_string(), "-nostdin".to_string()];
+    for input in [distorted, reference] {
+        if let Some(seconds) = trim_to {
+            args.extend(["-t".to_string(), format!("{seconds:.3}")]);
+        }
+        args.extend(["-i".to_string(), input.to_string()]);
+    }
+    args.extend([
+        "-filter_complex".to_string(),
+        filter_complex,
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]);
+
+    Ok(QualityComparisonPlan {
+        args,
+        compared_seconds,
+        notes,
+    })
+}
+
+/// Reads the SSIM or PSNR scores from the stderr of a comparison pass, as
+/// `(mean, min, frames)`.
+///
+/// The mean is `FFmpeg`'s own summary, which for PSNR is computed from the
+/// total error rather than by averaging frames. Returns `None` for VMAF,
+/// whose scores are in its JSON log.
+#[must_use]
+pub fn parse_frame_scores(metric: QualityMetric, stderr: &str) -> Option<(f64, f64, u64)> {
+    let (frame_regex, summary_regex) = match metric {
+        QualityMetric::Vmaf => return None,
+        QualityMetric::Ssim => (&SSIM_FRAME_REGEX, &SSIM_SUMMARY_REGEX),
+        QualityMetric::Psnr => (&PSNR_FRAME_REGEX, &PSNR_SUMMARY_REGEX),
+    };
+    let scores: Vec<f64> = frame_regex
+        .captures_iter(stderr)
+        .filter_map(|captures| captures[1].parse().ok())
+        .collect();
+    let mean = summary_regex.captures_iter(stderr).last()?[1]
+        .parse()
+        .ok()?;
+    let min = scores.iter().copied().reduce(f64::min)?;
+    Some((mean, min, scores.len() as u64))
+}
+
+/// Reads `(mean, min, harmonic mean, frames)` from the JSON log written by
+/// `libvmaf`.
+#[must_use]
+pub fn parse_vmaf_log(json: &str) -> Option<(f64, f64, f64, u64)> {
+    let log: serde_json::Value = serde_json::from_str(json).ok()?;
+    let pooled = log.get("pooled_metrics")?.get("vmaf")?;
+    let value = |key: &str| pooled.get(key).and_then(serde_json::Value::as_f64);
+    let frames = log
+        .get("frames")
+        .and_then(serde_json::Value::as_array)
+        .map_or(0, Vec::len);
+    Some((
+        value("mean")?,
+        value("min")?,
+        value("harmonic_mean")?,
+        frames as u64,
+    ))
+}
+
+/// Escapes `path` for a quoted filter option value.
+fn escape_filter_path(path: &str) -> String {
+    path.replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_probe(width: u32, height: u32, duration: &str, frame_rate: f64) -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some(duration.to_string()),
+            video_codec: Some("h264".to_string()),
+            width: Some(width),
+            height: Some(height),
+            frame_rate: Some(frame_rate),
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn matching_files_are_compared_in_full() {
+        let probe = video_probe(1920, 1080, "60.0", 25.0);
+
+        let plan = build_quality_comparison_args(
+            "/in/source.mkv",
+            "/out/encoded.mp4",
+            QualityMetric::Ssim,
+            &probe,
+            &probe,
+            "/tmp/vmaf.json",
+        )
+        .expect("comparison should build");
+
+        assert!(plan.notes.is_empty());
+        assert_eq!(plan.compared_seconds, Some(60.0));
+        assert_eq!(
+            plan.args[2..6],
+            ["-i", "/out/encoded.mp4", "-i", "/in/source.mkv"]
+        );
+        let filter = &plan.args[7];
+        assert!(filter.starts_with("[0:v:0]scale=1920:1080:force_original_aspect_ratio=increase"));
+        assert!(!filter.contains("fps="));
+        assert!(
+            filter.ends_with("[distorted][reference]ssim,metadata=mode=print:key=lavfi.ssim.All")
+        );
+    }
+
+    #[test]
+    fn trimmed_rescaled_and_retimed_outputs_are_aligned_with_notes() {
+        let reference = video_probe(1920, 1080, "60.0", 25.0);
+        let distorted = video_probe(1280, 720, "30.0", 30.0);
+
+        let plan = build_quality_comparison_args(
+            "/in/source.mkv",
+            "/out/encoded.mp4",
+            QualityMetric::Vmaf,
+            &reference,
+            &distorted,
+            "C:\\Temp\\vmaf.json",
+        )
+        .expect("comparison should build");
+
+        assert_eq!(plan.compared_seconds, Some(30.0));
+        assert_eq!(plan.notes.len(), 3);
+        assert!(plan.notes[0].contains("Only the first 30.00 s"));
+        assert_eq!(
+            plan.args[2..8],
+            ["-t", "30.000", "-i", "/out/encoded.mp4", "-t", "30.000"]
+        );
+        let filter = &plan.args[11];
+        assert!(filter.contains("crop=1920:1080,fps=25.000000,setsar=1"));
+        assert!(filter.ends_with("libvmaf=log_fmt=json:log_path='C\\:/Temp/vmaf.json'"));
+    }
+
+    #[test]
+    fn files_without_video_cannot_be_compared() {
+        let audio = ProbeMetadata::default();
+        let error = build_quality_comparison_args(
+            "/in.wav",
+            "/out.mp3",
+            QualityMetric::Psnr,
+            &audio,
+            &audio,
+            "/tmp/vmaf.json",
+        )
+        .expect_err("audio files should be rejected")
+        .to_string();
+
+        assert!(error.contains("two files with video"), "{error}");
+    }
+
+    #[test]
+    fn ssim_and_psnr_scores_are_read_from_stderr() {
+        let ssim = "\
+[Parsed_metadata_7 @ 0x1] frame:0    pts:0       pts_time:0
+[Parsed_metadata_7 @ 0x1] lavfi.ssim.All=0.982000
+[Parsed_metadata_7 @ 0x1] frame:1    pts:1       pts_time:0.04
+[Parsed_metadata_7 @ 0x1] lavfi.ssim.All=0.950500
+[Parsed_ssim_6 @ 0x2] SSIM Y:0.960000 (13.9) U:0.97 (15.2) V:0.97 (15.2) All:0.966250 (14.7)";
+        let psnr = "\
+[Parsed_metadata_7 @ 0x1] lavfi.psnr.psnr_avg=inf
+[Parsed_metadata_7 @ 0x1] lavfi.psnr.psnr_avg=41.25
+[Parsed_psnr_6 @ 0x2] PSNR y:40.1 u:44.0 v:44.2 average:44.02 min:41.25 max:inf";
+
+        assert_eq!(
+            parse_frame_scores(QualityMetric::Ssim, ssim),
+            Some((0.96625, 0.9505, 2))
+        );
+        assert_eq!(
+            parse_frame_scores(QualityMetric::Psnr, psnr),
+            Some((44.02, 41.25, 2))
+        );
+        assert_eq!(parse_frame_scores(QualityMetric::Vmaf, ssim), None);
+        assert_eq!(parse_frame_scores(QualityMetric::Ssim, "no scores"), None);
+    }
+
+    #[test]
+    fn vmaf_pooled_scores_are_read_from_the_json_log() {
+        let log = r#"{
+            "version": "2.3.1",
+            "frames": [{"frameNum": 0, "metrics": {"vmaf": 97.1}}, {"frameNum": 1, "metrics": {"vmaf": 88.4}}],
+            "pooled_metrics": {"vmaf": {"min": 88.4, "max": 97.1, "mean": 92.75, "harmonic_mean": 92.54}}
+        }"#;
+
+        assert_eq!(parse_vmaf_log(log), Some((92.75, 88.4, 92.54, 2)));
+        assert_eq!(parse_vmaf_log("{}"), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Short preview encodes that try conversion settings on a few seconds of
+//! the source before the whole file is converted.
+
+use serde::Serialize;
+
+use crate::chunked::parallel_chunk_count;
+use crate::error::ConversionError;
+use crate::estimate::OutputSizeEstimate;
+use crate::media_rules::is_image_container;
+use crate::types::{ConversionConfig, OverwritePolicy};
+
+/// Length of a preview when none is requested, in seconds.
+pub const SAMPLE_PREVIEW_SECONDS: f64 = 10.0;
+
+/// Where a preview starts when no timestamp is requested, as a fraction of
+/// the source duration.
+pub const SAMPLE_PREVIEW_POSITION: f64 = 0.25;
+
+/// A finished preview encode.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplePreview {
+    pub path: String,
+    /// Source time the preview starts at, in seconds.
+    pub start_seconds: f64,
+    pub duration_seconds: f64,
+    pub bytes: u64,
+    /// Size of the full output extrapolated from the preview, when the
+    /// output duration is known.
+    pub estimated_full_size: Option<OutputSizeEstimate>,
+}
+
+/// Picks the `(start, length)` of a preview, in seconds.
+///
+/// Without `at_seconds` the preview starts [`SAMPLE_PREVIEW_POSITION`] into
+/// the source, and without `length_seconds` it lasts
+/// [`SAMPLE_PREVIEW_SECONDS`]. When the source duration is known, a preview
+/// that would run past the end is moved back to end with the source.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the timestamp is negative
+/// or the length is not positive.
+pub fn sample_preview_range(
+    source_seconds: Option<f64>,
+    at_seconds: Option<f64>,
+    length_seconds: Option<f64>,
+) -> Result<(f64, f64), ConversionError> {
+    let length = length_seconds.unwrap_or(SAMPLE_PREVIEW_SECONDS);
+    if !length.is_finite() || length <= 0.0 {
+        return Err(ConversionError::InvalidInput(
+            "The preview length must be greater than zero".to_string(),
+        ));
+    }
+    if at_seconds.is_some_and(|at| !at.is_finite() || at < 0.0) {
+        return Err(ConversionError::InvalidInput(
+            "The preview timestamp cannot be negative".to_string(),
+        ));
+    }
+
+    let source_seconds = source_seconds.filter(|seconds| *seconds > 0.0);
+    let start = at_seconds
+        .unwrap_or_else(|| source_seconds.map_or(0.0, |seconds| seconds * SAMPLE_PREVIEW_POSITION));
+    Ok(source_seconds.map_or((start, length), |source_seconds| {
+        let length = length.min(source_seconds);
+        (start.min(source_seconds - length), length)
+    }))
+}
+
+/// Conversion settings that encode only `length` seconds of the source
+/// from `start`, into a single file.
+///
+/// Trims, cut segments and output splitting are replaced by the preview
+/// range, and an existing preview file is overwritten.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for image outputs, which have
+/// no duration to sample, and for parallel chunked encodes, which the
+/// preview cannot reproduce in a single pass.
+pub fn sample_preview_config(
+    config: &ConversionConfig,
+    start: f64,
+    length: f64,
+) -> Result<ConversionConfig, ConversionError> {
+    if is_image_container(&config.container) || config.image_sequence_output {
+        return Err(ConversionError::InvalidInput(
+            "Previews are not available for image outputs".to_string(),
+        ));
+    }
+    if parallel_chunk_count(config).is_some() {
+        return Err(ConversionError::InvalidInput(
+            "Previews cannot be encoded in parallel chunks; turn parallel chunked encoding off \
+             to preview these settings"
+                .to_string(),
+        ));
+    }
+
+    let mut preview = config.clone();
+    preview.start_time = Some(format!("{start:.3}"));
+    preview.end_time = Some(format!("{:.3}", start + length));
+    preview.segments.clear();
+    preview.remove_segments.clear();
+    preview.split = None;
+    preview.split_by_chapters = false;
+    preview.overwrite_policy = OverwritePolicy::Overwrite;
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TrimSegment, sample_config};
+
+    #[test]
+    fn previews_default_to_ten_seconds_a_quarter_into_the_source() {
+        assert_eq!(
+            sample_preview_range(Some(400.0), None, None).expect("range should resolve"),
+            (100.0, SAMPLE_PREVIEW_SECONDS)
+        );
+        assert_eq!(
+            sample_preview_range(None, None, Some(4.0)).expect("range should resolve"),
+            (0.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn previews_near_the_end_are_moved_back_inside_the_source() {
+        assert_eq!(
+            sample_preview_range(Some(60.0), Some(58.0), None).expect("range should resolve"),
+            (50.0, 10.0)
+        );
+        assert_eq!(
+            sample_preview_range(Some(6.0), Some(2.0), Some(10.0)).expect("range should resolve"),
+            (0.0, 6.0)
+        );
+    }
+
+    #[test]
+    fn invalid_preview_ranges_are_rejected() {
+        for (at, length) in [
+            (Some(-1.0), None),
+            (None, Some(0.0)),
+            (None, Some(f64::NAN)),
+        ] {
+            assert!(sample_preview_range(Some(60.0), at, length).is_err());
+        }
+    }
+
+    #[test]
+    fn preview_config_replaces_trims_and_splits_with_the_preview_range() {
+        let mut source = sample_config("mp4", "libx264");
+        source.start_time = Some("5".to_string());
+        source.segments = vec![TrimSegment {
+            start: "10".to_string(),
+            end: "20".to_string(),
+        }];
+        source.split_by_chapters = true;
+
+        let preview = sample_preview_config(&source, 100.0, 10.0).expect("preview should build");
+
+        assert_eq!(preview.start_time.as_deref(), Some("100.000"));
+        assert_eq!(preview.end_time.as_deref(), Some("110.000"));
+        assert!(preview.segments.is_empty());
+        assert!(!preview.split_by_chapters);
+        assert_eq!(preview.overwrite_policy, OverwritePolicy::Overwrite);
+        assert_eq!(preview.crf, source.crf);
+    }
+
+    #[test]
+    fn image_and_chunked_configs_cannot_be_previewed() {
+        let mut chunked = sample_config("mp4", "libx264");
+        chunked.parallel_chunks = Some(4);
+
+        for (config, expected) in [
+            (sample_config("png", "libx264"), "image outputs"),
+            (chunked, "parallel"),
+        ] {
+            let error = sample_preview_config(&config, 0.0, 10.0)
+                .expect_err("preview should be rejected")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Scratch directory for large intermediate files such as frame sequences,
+//! encoder pass logs and segment chunks.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::ConversionError;
+
+/// Directory intermediates are written to: `configured` when set, otherwise
+/// the OS temporary directory.
+#[must_use]
+pub fn scratch_directory(configured: Option<&Path>) -> PathBuf {
+    configured.map_or_else(std::env::temp_dir, Path::to_path_buf)
+}
+
+/// Checks that `directory` exists, accepts new files and lies outside
+/// `install_directory`, returning its canonical form.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when any check fails.
+pub fn validate_scratch_directory(
+    directory: &Path,
+    install_directory: Option<&Path>,
+) -> Result<PathBuf, ConversionError> {
+    let display = directory.display();
+    let canonical = fs::canonicalize(directory).map_err(|error| {
+        ConversionError::InvalidInput(format!(
+            "Scratch directory is unavailable: {display}: {error}"
+        ))
+    })?;
+    if !canonical.is_dir() {
+        return Err(ConversionError::InvalidInput(format!(
+            "Scratch path is not a directory: {display}"
+        )));
+    }
+    if let Some(install_directory) =
+        install_directory.and_then(|install_directory| fs::canonicalize(install_directory).ok())
+        && canonical.starts_with(&install_directory)
+    {
+        return Err(ConversionError::InvalidInput(format!(
+            "Scratch directory cannot be inside the application: {display}"
+        )));
+    }
+
+    let probe_path = canonical.join(format!(".frame-scratch-check-{}", std::process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe_path)
+        .map_err(|error| {
+            ConversionError::InvalidInput(format!(
+                "Scratch directory is not writable: {display}: {error}"
+            ))
+        })?;
+    let _ = fs::remove_file(&probe_path);
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scratch_directory_defaults_to_os_temp_directory() {
+        assert_eq!(scratch_directory(None), std::env::temp_dir());
+        assert_eq!(
+            scratch_directory(Some(Path::new("/mnt/scratch"))),
+            PathBuf::from("/mnt/scratch")
+        );
+    }
+
+    #[test]
+    fn validate_scratch_directory_rejects_unusable_locations() {
+        let root = std::env::temp_dir().join(format!("frame-scratch-{}", std::process::id()));
+        let install = root.join("Frame.app");
+        let inside_install = install.join("Contents");
+        fs::create_dir_all(&inside_install).expect("test directories should be created");
+        let file = root.join("scratch.txt");
+        fs::write(&file, b"").expect("test file should be written");
+
+        let accepted = validate_scratch_directory(&root, Some(&install));
+        let missing = validate_scratch_directory(&root.join("missing"), None);
+        let not_directory = validate_scratch_directory(&file, None);
+        let bundled = validate_scratch_directory(&inside_install, Some(&install));
+
+        let _ = fs::remove_dir_all(&root);
+        assert!(accepted.is_ok());
+        assert!(missing.is_err_and(|error| error.to_string().contains("unavailable")));
+        assert!(not_directory.is_err_and(|error| error.to_string().contains("not a directory")));
+        assert!(bundled.is_err_and(|error| error.to_string().contains("inside the application")));
+    }
+}
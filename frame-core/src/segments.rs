@@ -0,0 +1,632 @@
+//! Keep-segments trimming: ranges of the source glued into one output.
+//!
+//! The legacy `start_time`/`end_time` pair is read as a single segment, so
+//! argument building, validation and progress share one code path. Removed
+//! ranges are turned into the complementary keep list once the source
+//! duration is known.
+
+use crate::error::ConversionError;
+use crate::media_rules::{is_gif_container, is_image_container};
+use crate::timecode::{TimeInput, parse_time_input};
+use crate::types::{ConversionConfig, TrimSegment};
+use crate::utils::{format_time, parse_time};
+
+/// Slack allowed past the probed duration, which containers round. Kept
+/// ranges shorter than this between two removals are dropped.
+const SEGMENT_DURATION_TOLERANCE_SECONDS: f64 = 0.05;
+
+/// One kept range of the source, in seconds. `end` is `None` when the
+/// range runs to the end of the source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrimRange {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+impl TrimRange {
+    /// Length of the range, resolving an open end against the source.
+    #[must_use]
+    pub fn duration(&self, source_duration: Option<f64>) -> Option<f64> {
+        Some(self.end.or(source_duration)? - self.start).filter(|duration| *duration > 0.0)
+    }
+}
+
+/// Reads a segment time as seconds. Frame counts and timecodes read as
+/// `None` until [`crate::timecode::resolve_frame_times`] converts them.
+fn segment_seconds(
+    value: &str,
+    invalid: impl FnOnce() -> String,
+) -> Result<Option<f64>, ConversionError> {
+    match parse_time_input(value) {
+        Some(TimeInput::Seconds(seconds)) => Ok(Some(seconds)),
+        Some(TimeInput::Frames(_) | TimeInput::Timecode { .. }) => Ok(None),
+        None => Err(ConversionError::InvalidInput(invalid())),
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Ranges of the source kept in the output, in order. Empty when the task
+/// keeps the whole source.
+#[must_use]
+pub fn trim_ranges(config: &ConversionConfig) -> Vec<TrimRange> {
+    if !config.segments.is_empty() {
+        return config
+            .segments
+            .iter()
+            .filter_map(|segment| {
+                Some(TrimRange {
+                    start: parse_time(segment.start.trim())?,
+                    end: Some(parse_time(segment.end.trim())?),
+                })
+            })
+            .collect();
+    }
+
+    let start = non_empty(config.start_time.as_deref()).and_then(parse_time);
+    let end = non_empty(config.end_time.as_deref()).and_then(parse_time);
+    if start.is_none() && end.is_none() {
+        return Vec::new();
+    }
+    vec![TrimRange {
+        start: start.unwrap_or(0.0),
+        end,
+    }]
+}
+
+/// Reports whether the task joins several ranges, which needs the trim and
+/// concat filter graph instead of input seeking.
+#[must_use]
+pub const fn uses_segment_concat(config: &ConversionConfig) -> bool {
+    config.segments.len() > 1
+}
+
+/// Output duration after trimming: the summed length of the kept ranges.
+#[must_use]
+pub fn kept_duration_seconds(
+    config: &ConversionConfig,
+    source_duration: Option<f64>,
+) -> Option<f64> {
+    let ranges = trim_ranges(config);
+    if ranges.is_empty() {
+        return source_duration.filter(|duration| *duration > 0.0);
+    }
+    ranges
+        .iter()
+        .map(|range| range.duration(source_duration))
+        .sum()
+}
+
+/// Validates the keep-segments of a task: every time parses, each segment
+/// ends after it starts, segments are ordered without overlapping and, when
+/// the source duration is known, end within it.
+///
+/// Segments written in frames are only checked for syntax until they are
+/// resolved.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] describing the first invalid
+/// segment, or when several segments are combined with stream copy, an
+/// output that cannot be concatenated, or selected subtitle tracks.
+pub fn validate_trim_segments(
+    config: &ConversionConfig,
+    source_duration: Option<f64>,
+) -> Result<(), ConversionError> {
+    let mut previous_end = None;
+    for (position, segment) in config.segments.iter().enumerate() {
+        let number = position + 1;
+        let start = segment_seconds(&segment.start, || {
+            format!("Invalid start time in segment {number}: {}", segment.start)
+        })?;
+        let end = segment_seconds(&segment.end, || {
+            format!("Invalid end time in segment {number}: {}", segment.end)
+        })?;
+        let (Some(start), Some(end)) = (start, end) else {
+            previous_end = None;
+            continue;
+        };
+        if end <= start {
+            return Err(ConversionError::InvalidInput(format!(
+                "Segment {number} must end after it starts"
+            )));
+        }
+        if previous_end.is_some_and(|previous_end| start < previous_end) {
+            return Err(ConversionError::InvalidInput(format!(
+                "Segment {number} overlaps or precedes the segment before it"
+            )));
+        }
+        if let Some(duration) = source_duration
+            && end > duration + SEGMENT_DURATION_TOLERANCE_SECONDS
+        {
+            return Err(ConversionError::InvalidInput(format!(
+                "Segment {number} ends after the end of the source ({duration:.3}s)"
+            )));
+        }
+        previous_end = Some(end);
+    }
+
+    if !uses_segment_concat(config) {
+        return Ok(());
+    }
+    if config.processing_mode == "copy" {
+        return Err(ConversionError::InvalidInput(
+            "Multiple trim segments require re-encoding".to_string(),
+        ));
+    }
+    if is_gif_container(&config.container) || is_image_container(&config.container) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Multiple trim segments are not available for container '{}'",
+            config.container
+        )));
+    }
+    if !config.selected_subtitle_tracks.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Subtitle tracks cannot be kept when joining multiple trim segments".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reports whether the task trims the source with a start or end time or
+/// with kept or removed segments.
+#[must_use]
+pub fn has_trim_times(config: &ConversionConfig) -> bool {
+    non_empty(config.start_time.as_deref()).is_some()
+        || non_empty(config.end_time.as_deref()).is_some()
+        || !config.segments.is_empty()
+        || !config.remove_segments.is_empty()
+}
+
+/// Checks every trim time of a task against the probed source duration.
+///
+/// Starts must leave something of the source to keep and ends may run past
+/// the duration only by the rounding slack containers introduce.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] naming the first time that lies
+/// beyond the source duration.
+pub fn validate_trim_bounds(
+    config: &ConversionConfig,
+    source_duration: f64,
+) -> Result<(), ConversionError> {
+    let beyond = |label: String, value: &str| {
+        ConversionError::InvalidInput(format!(
+            "{label} {} is beyond the file duration {}",
+            value.trim(),
+            format_time(source_duration)
+        ))
+    };
+    let check_start = |label: String, value: &str| match parse_time(value.trim()) {
+        Some(start) if start + SEGMENT_DURATION_TOLERANCE_SECONDS >= source_duration => {
+            Err(beyond(label, value))
+        }
+        _ => Ok(()),
+    };
+    let check_end = |label: String, value: &str| match parse_time(value.trim()) {
+        Some(end) if end > source_duration + SEGMENT_DURATION_TOLERANCE_SECONDS => {
+            Err(beyond(label, value))
+        }
+        _ => Ok(()),
+    };
+
+    if let Some(start) = non_empty(config.start_time.as_deref()) {
+        check_start("Start time".to_string(), start)?;
+    }
+    if let Some(end) = non_empty(config.end_time.as_deref()) {
+        check_end("End time".to_string(), end)?;
+    }
+    for (position, segment) in config.segments.iter().enumerate() {
+        let number = position + 1;
+        check_start(format!("Segment {number} start time"), &segment.start)?;
+        check_end(format!("Segment {number} end time"), &segment.end)?;
+    }
+    for (position, segment) in config.remove_segments.iter().enumerate() {
+        let number = position + 1;
+        check_start(
+            format!("Removed segment {number} start time"),
+            &segment.start,
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses the removed ranges of a task, sorted by start.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when a removed range has an
+/// invalid time or does not end after it starts, or when removals are
+/// combined with keep-segments.
+pub fn validate_remove_segments(config: &ConversionConfig) -> Result<(), ConversionError> {
+    parse_remove_ranges(config).map(|_| ())
+}
+
+fn parse_remove_ranges(config: &ConversionConfig) -> Result<Vec<(f64, f64)>, ConversionError> {
+    if config.remove_segments.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !config.segments.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Kept and removed segments cannot be combined".to_string(),
+        ));
+    }
+
+    let mut ranges = Vec::new();
+    for (position, segment) in config.remove_segments.iter().enumerate() {
+        let number = position + 1;
+        let start = segment_seconds(&segment.start, || {
+            format!(
+                "Invalid start time in removed segment {number}: {}",
+                segment.start
+            )
+        })?;
+        let end = segment_seconds(&segment.end, || {
+            format!(
+                "Invalid end time in removed segment {number}: {}",
+                segment.end
+            )
+        })?;
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+        if end <= start {
+            return Err(ConversionError::InvalidInput(format!(
+                "Removed segment {number} must end after it starts"
+            )));
+        }
+        ranges.push((start, end));
+    }
+    ranges.sort_by(|left, right| left.0.total_cmp(&right.0));
+    Ok(ranges)
+}
+
+/// Replaces the removed ranges of a task with the ranges kept around them.
+///
+/// Removals apply within the legacy trim when one is set. Overlapping or
+/// touching removals are merged; a single kept range degenerates to a plain
+/// trim.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when a removed range is
+/// invalid, the source duration is unknown, or the removals delete the
+/// whole source.
+pub fn resolve_remove_segments(
+    config: &mut ConversionConfig,
+    source_duration: Option<f64>,
+) -> Result<(), ConversionError> {
+    let removals = parse_remove_ranges(config)?;
+    if removals.is_empty() {
+        return Ok(());
+    }
+    let source_duration = source_duration.filter(|duration| *duration > 0.0);
+    let bounds = trim_ranges(config).first().copied().unwrap_or(TrimRange {
+        start: 0.0,
+        end: None,
+    });
+    let Some(bounds_end) = bounds.end.or(source_duration) else {
+        return Err(ConversionError::InvalidInput(
+            "Removing segments requires a known source duration".to_string(),
+        ));
+    };
+
+    let mut kept = Vec::new();
+    let mut cursor = bounds.start;
+    for (start, end) in removals {
+        if start - cursor > SEGMENT_DURATION_TOLERANCE_SECONDS {
+            kept.push((cursor, start.min(bounds_end)));
+        }
+        cursor = cursor.max(end);
+        if cursor >= bounds_end {
+            break;
+        }
+    }
+    if bounds_end - cursor > SEGMENT_DURATION_TOLERANCE_SECONDS {
+        kept.push((cursor, bounds_end));
+    }
+    kept.retain(|(start, end)| end - start > SEGMENT_DURATION_TOLERANCE_SECONDS);
+    if kept.is_empty() {
+        return Err(ConversionError::InvalidInput(
+            "Removed segments cover the whole source".to_string(),
+        ));
+    }
+
+    config.segments = kept
+        .into_iter()
+        .map(|(start, end)| TrimSegment {
+            start: format!("{start:.3}"),
+            end: format!("{end:.3}"),
+        })
+        .collect();
+    config.remove_segments.clear();
+    config.start_time = None;
+    config.end_time = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn segment(start: &str, end: &str) -> TrimSegment {
+        TrimSegment {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn config_with_segments(segments: Vec<TrimSegment>) -> ConversionConfig {
+        let mut config = sample_config("mp4", "libx264");
+        config.segments = segments;
+        config
+    }
+
+    #[test]
+    fn legacy_start_and_end_read_as_a_single_range() {
+        let mut config = config_with_segments(Vec::new());
+        assert!(trim_ranges(&config).is_empty());
+
+        config.end_time = Some("00:00:30".to_string());
+        assert_eq!(
+            trim_ranges(&config),
+            [TrimRange {
+                start: 0.0,
+                end: Some(30.0)
+            }]
+        );
+
+        config.start_time = Some("00:00:05".to_string());
+        config.end_time = Some(String::new());
+        assert_eq!(
+            trim_ranges(&config),
+            [TrimRange {
+                start: 5.0,
+                end: None
+            }]
+        );
+        assert_eq!(kept_duration_seconds(&config, Some(65.0)), Some(60.0));
+    }
+
+    #[test]
+    fn segments_take_precedence_and_sum_their_durations() {
+        let mut config =
+            config_with_segments(vec![segment("0:00", "5:12"), segment("17:40", "22:05")]);
+        config.start_time = Some("1:00".to_string());
+
+        assert_eq!(trim_ranges(&config).len(), 2);
+        assert!(uses_segment_concat(&config));
+        assert_eq!(kept_duration_seconds(&config, None), Some(312.0 + 265.0));
+    }
+
+    #[test]
+    fn validate_trim_segments_accepts_ordered_segments_within_the_source() {
+        let config = config_with_segments(vec![segment("0:00", "5:12"), segment("5:12", "22:05")]);
+
+        assert!(validate_trim_segments(&config, Some(1400.0)).is_ok());
+    }
+
+    #[test]
+    fn validate_trim_segments_rejects_invalid_segments() {
+        let cases = [
+            (
+                vec![segment("0:00", "later")],
+                "Invalid end time in segment 1: later",
+            ),
+            (
+                vec![segment("0:10", "0:20"), segment("0:40", "0:30")],
+                "Segment 2 must end after it starts",
+            ),
+            (
+                vec![segment("0:10", "0:20"), segment("0:15", "0:30")],
+                "Segment 2 overlaps or precedes the segment before it",
+            ),
+            (
+                vec![segment("0:10", "0:20"), segment("0:30", "2:00")],
+                "Segment 2 ends after the end of the source (90.000s)",
+            ),
+        ];
+
+        for (segments, expected) in cases {
+            let config = config_with_segments(segments);
+            let error = validate_trim_segments(&config, Some(90.0))
+                .expect_err("invalid segments should be rejected");
+            assert_eq!(error.to_string(), format!("Invalid input: {expected}"));
+        }
+    }
+
+    #[test]
+    fn frame_based_segments_are_checked_once_resolved() {
+        let mut config = config_with_segments(vec![
+            segment("0:10", "0:20"),
+            segment("00:00:15:00", "480f"),
+            segment("0:25", "0:30"),
+        ]);
+        assert!(validate_trim_segments(&config, Some(90.0)).is_ok());
+
+        config.segments[1].end = "48x".to_string();
+        let error = validate_trim_segments(&config, Some(90.0))
+            .expect_err("malformed frame counts should be rejected");
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Invalid end time in segment 2: 48x"
+        );
+
+        config.segments[1].end = "480f".to_string();
+        crate::timecode::resolve_frame_times(&mut config, Some(24.0))
+            .expect("frames should resolve");
+        let error =
+            validate_trim_segments(&config, Some(90.0)).expect_err("resolved segments overlap");
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Segment 2 overlaps or precedes the segment before it"
+        );
+    }
+
+    #[test]
+    fn validate_trim_bounds_rejects_times_beyond_the_source() {
+        let duration = 2832.48;
+        let mut config = config_with_segments(Vec::new());
+        config.start_time = Some("00:10:00".to_string());
+        config.end_time = Some("00:47:12.5".to_string());
+        assert!(validate_trim_bounds(&config, duration).is_ok());
+
+        let cases = [
+            (
+                Some("01:30:00"),
+                None,
+                Vec::new(),
+                Vec::new(),
+                "Start time 01:30:00 is beyond the file duration 00:47:12.480",
+            ),
+            (
+                Some("00:47:12.480"),
+                None,
+                Vec::new(),
+                Vec::new(),
+                "Start time 00:47:12.480 is beyond the file duration 00:47:12.480",
+            ),
+            (
+                None,
+                Some("00:50:00"),
+                Vec::new(),
+                Vec::new(),
+                "End time 00:50:00 is beyond the file duration 00:47:12.480",
+            ),
+            (
+                None,
+                None,
+                vec![segment("0:10", "0:20"), segment("48:00", "49:00")],
+                Vec::new(),
+                "Segment 2 start time 48:00 is beyond the file duration 00:47:12.480",
+            ),
+            (
+                None,
+                None,
+                vec![segment("0:10", "47:30")],
+                Vec::new(),
+                "Segment 1 end time 47:30 is beyond the file duration 00:47:12.480",
+            ),
+            (
+                None,
+                None,
+                Vec::new(),
+                vec![segment("1:00:00", "1:10:00")],
+                "Removed segment 1 start time 1:00:00 is beyond the file duration 00:47:12.480",
+            ),
+        ];
+
+        for (start, end, segments, removals, expected) in cases {
+            let mut config = config_with_segments(segments);
+            config.start_time = start.map(str::to_string);
+            config.end_time = end.map(str::to_string);
+            config.remove_segments = removals;
+            let error = validate_trim_bounds(&config, duration)
+                .expect_err("times beyond the source should be rejected");
+            assert_eq!(error.to_string(), format!("Invalid input: {expected}"));
+        }
+    }
+
+    #[test]
+    fn removed_segments_may_run_past_the_end_of_the_source() {
+        let mut config = config_with_segments(Vec::new());
+        config.remove_segments = vec![segment("40:00", "59:59")];
+
+        assert!(has_trim_times(&config));
+        assert!(validate_trim_bounds(&config, 2832.48).is_ok());
+        assert!(!has_trim_times(&config_with_segments(Vec::new())));
+    }
+
+    fn resolved_segments(removals: Vec<TrimSegment>) -> Result<Vec<(String, String)>, String> {
+        let mut config = config_with_segments(Vec::new());
+        config.remove_segments = removals;
+        resolve_remove_segments(&mut config, Some(600.0)).map_err(|error| error.to_string())?;
+        assert!(config.remove_segments.is_empty());
+        Ok(config
+            .segments
+            .into_iter()
+            .map(|segment| (segment.start, segment.end))
+            .collect())
+    }
+
+    fn owned(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(start, end)| ((*start).to_string(), (*end).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolve_remove_segments_keeps_the_ranges_around_removals() {
+        assert_eq!(
+            resolved_segments(vec![segment("1:00", "2:00"), segment("5:00", "5:30")]),
+            Ok(owned(&[
+                ("0.000", "60.000"),
+                ("120.000", "300.000"),
+                ("330.000", "600.000")
+            ]))
+        );
+    }
+
+    #[test]
+    fn resolve_remove_segments_merges_overlaps_and_trims_at_the_edges() {
+        assert_eq!(
+            resolved_segments(vec![
+                segment("9:00", "10:00"),
+                segment("0:00", "0:30"),
+                segment("0:20", "1:00"),
+                segment("1:00", "1:10"),
+            ]),
+            Ok(owned(&[("70.000", "540.000")]))
+        );
+    }
+
+    #[test]
+    fn resolve_remove_segments_rejects_removing_everything() {
+        assert_eq!(
+            resolved_segments(vec![segment("0:00", "6:00"), segment("5:00", "10:00")]),
+            Err("Invalid input: Removed segments cover the whole source".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_remove_segments_stays_within_the_legacy_trim() {
+        let mut config = config_with_segments(Vec::new());
+        config.start_time = Some("0:30".to_string());
+        config.end_time = Some("2:00".to_string());
+        config.remove_segments = vec![segment("1:00", "1:30")];
+
+        resolve_remove_segments(&mut config, None).expect("removals should resolve");
+
+        assert_eq!(config.start_time, None);
+        assert_eq!(kept_duration_seconds(&config, None), Some(60.0));
+        assert!(uses_segment_concat(&config));
+    }
+
+    #[test]
+    fn resolve_remove_segments_requires_a_known_duration() {
+        let mut config = config_with_segments(Vec::new());
+        config.remove_segments = vec![segment("1:00", "1:30")];
+
+        assert!(resolve_remove_segments(&mut config, None).is_err());
+    }
+
+    #[test]
+    fn validate_trim_segments_requires_reencoding_a_concatenable_output() {
+        let mut config =
+            config_with_segments(vec![segment("0:00", "0:10"), segment("0:20", "0:30")]);
+        config.processing_mode = "copy".to_string();
+        assert!(validate_trim_segments(&config, None).is_err());
+
+        config.processing_mode = "reencode".to_string();
+        config.container = "gif".to_string();
+        assert!(validate_trim_segments(&config, None).is_err());
+
+        config.segments.truncate(1);
+        assert!(validate_trim_segments(&config, None).is_ok());
+    }
+}
@@ -0,0 +1,476 @@
+//! Smart cut: frame-accurate stream-copy trims.
+//!
+//! Only the partial GOPs at the cut points are re-encoded, with settings
+//! matched to the source; the span between the first and last keyframe
+//! inside the trim is copied untouched. The pieces are joined with the
+//! concat demuxer.
+
+use crate::segments::{TrimRange, trim_ranges, uses_segment_concat};
+use crate::split::splits_output;
+use crate::types::{ConversionConfig, ProbeMetadata};
+use crate::utils::is_audio_only_container;
+
+/// Span searched for keyframes after the trim start and before its end.
+pub const SMART_CUT_KEYFRAME_WINDOW_SECONDS: f64 = 30.0;
+
+/// Cut points closer than this to a keyframe are treated as on it.
+const KEYFRAME_TOLERANCE_SECONDS: f64 = 0.001;
+
+/// Source codecs a smart cut can match: `(source codec, encoder, piece
+/// muxer, quality options)`. Annex B codecs are cut into MPEG-TS pieces so
+/// each piece carries its own parameter sets.
+const SMART_CUT_ENCODERS: [(&str, &str, &str, &[&str]); 5] = [
+    (
+        "h264",
+        "libx264",
+        "mpegts",
+        &["-crf", "16", "-preset", "medium"],
+    ),
+    (
+        "hevc",
+        "libx265",
+        "mpegts",
+        &["-crf", "16", "-preset", "medium"],
+    ),
+    ("mpeg4", "mpeg4", "mpegts", &["-q:v", "2"]),
+    (
+        "vp9",
+        "libvpx-vp9",
+        "matroska",
+        &["-crf", "18", "-b:v", "0"],
+    ),
+    ("av1", "libsvtav1", "matroska", &["-crf", "20"]),
+];
+
+/// One piece of a smart cut, in source seconds. `end` is `None` when the
+/// piece runs to the end of the source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmartCutPiece {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub reencode: bool,
+}
+
+/// Encoder settings that reproduce the source's video stream closely enough
+/// to be joined with copied packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmartCutEncoder {
+    pub encoder: &'static str,
+    /// Muxer the pieces are written with.
+    pub muxer: &'static str,
+    args: Vec<String>,
+}
+
+impl SmartCutEncoder {
+    /// File extension of the pieces.
+    #[must_use]
+    pub fn extension(&self) -> &'static str {
+        if self.muxer == "mpegts" { "ts" } else { "mkv" }
+    }
+}
+
+/// The trimmed range a smart cut applies to, or `None` when the task is not
+/// a single stream-copy trim with smart cut enabled.
+#[must_use]
+pub fn smart_cut_range(config: &ConversionConfig) -> Option<TrimRange> {
+    if !config.smart_cut
+        || config.processing_mode != "copy"
+        || is_audio_only_container(&config.container)
+        || uses_segment_concat(config)
+        || splits_output(config)
+    {
+        return None;
+    }
+    let [range] = trim_ranges(config)[..] else {
+        return None;
+    };
+    Some(range)
+}
+
+/// Picks the encoder that matches the source's video stream.
+///
+/// # Errors
+///
+/// Returns the reason the source cannot be matched, such as an unsupported
+/// codec, an unknown pixel format or interlaced video.
+pub fn smart_cut_encoder(probe: &ProbeMetadata) -> Result<SmartCutEncoder, String> {
+    let codec = probe
+        .video_codec
+        .as_deref()
+        .ok_or_else(|| "the source has no video stream".to_string())?;
+    let (_, encoder, muxer, quality) = SMART_CUT_ENCODERS
+        .iter()
+        .find(|(source, ..)| source.eq_ignore_ascii_case(codec))
+        .ok_or_else(|| format!("no encoder can match the source codec '{codec}'"))?;
+    let pixel_format = probe
+        .pixel_format
+        .as_deref()
+        .ok_or_else(|| "the source pixel format is unknown".to_string())?;
+    if probe
+        .field_order
+        .as_deref()
+        .is_some_and(|order| order != "progressive" && order != "unknown")
+    {
+        return Err("interlaced sources cannot be matched".to_string());
+    }
+
+    let mut args = vec!["-c:v".to_string(), (*encoder).to_string()];
+    args.extend(quality.iter().map(|arg| (*arg).to_string()));
+    args.extend(["-pix_fmt".to_string(), pixel_format.to_string()]);
+    if let Some(profile) = probe
+        .profile
+        .as_deref()
+        .filter(|_| matches!(*encoder, "libx264" | "libx265"))
+    {
+        args.push("-profile:v".to_string());
+        args.push(profile.to_ascii_lowercase().replace(' ', ""));
+    }
+    for (option, value) in [
+        ("-colorspace", probe.color_space.as_deref()),
+        ("-color_primaries", probe.color_primaries.as_deref()),
+        ("-color_trc", probe.color_transfer.as_deref()),
+        ("-color_range", probe.color_range.as_deref()),
+    ] {
+        if let Some(value) = value.filter(|value| *value != "unknown") {
+            args.push(option.to_string());
+            args.push(value.to_string());
+        }
+    }
+    Ok(SmartCutEncoder {
+        encoder,
+        muxer,
+        args,
+    })
+}
+
+/// `FFprobe` arguments listing the keyframe timestamps of the first video
+/// stream between `from_seconds` and `to_seconds`. Only keyframes are
+/// decoded.
+#[must_use]
+pub fn keyframe_probe_args(file_path: &str, from_seconds: f64, to_seconds: f64) -> Vec<String> {
+    vec![
+        "-v".to_string(),
+        "error".to_string(),
+        "-select_streams".to_string(),
+        "v:0".to_string(),
+        "-skip_frame".to_string(),
+        "nokey".to_string(),
+        "-show_frames".to_string(),
+        "-show_entries".to_string(),
+        "frame=pts_time".to_string(),
+        "-of".to_string(),
+        "csv=p=0".to_string(),
+        "-read_intervals".to_string(),
+        format!("{:.3}%{to_seconds:.3}", from_seconds.max(0.0)),
+        file_path.to_string(),
+    ]
+}
+
+/// Reads the timestamps printed by [`keyframe_probe_args`], sorted and
+/// without duplicates.
+#[must_use]
+pub fn parse_keyframe_times(stdout: &str) -> Vec<f64> {
+    let mut times: Vec<f64> = stdout
+        .lines()
+        .filter_map(|line| line.trim().trim_end_matches(',').parse().ok())
+        .filter(|time: &f64| time.is_finite())
+        .collect();
+    times.sort_by(f64::total_cmp);
+    times.dedup_by(|later, earlier| (*later - *earlier).abs() < KEYFRAME_TOLERANCE_SECONDS);
+    times
+}
+
+/// Splits `range` into re-encoded edges and a copied middle, given the
+/// keyframes found within [`SMART_CUT_KEYFRAME_WINDOW_SECONDS`] of its
+/// start and end.
+///
+/// Returns `None` when no keyframe follows the start and the range is too
+/// long to re-encode whole.
+#[must_use]
+pub fn plan_smart_cut(range: TrimRange, keyframes: &[f64]) -> Option<Vec<SmartCutPiece>> {
+    let reencode = |start, end| SmartCutPiece {
+        start,
+        end: Some(end),
+        reencode: true,
+    };
+    let first_keyframe = keyframes
+        .iter()
+        .copied()
+        .find(|keyframe| *keyframe >= range.start - KEYFRAME_TOLERANCE_SECONDS);
+    let Some(first_keyframe) = first_keyframe.filter(|keyframe| {
+        range
+            .end
+            .is_none_or(|end| *keyframe < end - KEYFRAME_TOLERANCE_SECONDS)
+    }) else {
+        return range
+            .end
+            .filter(|end| end - range.start <= SMART_CUT_KEYFRAME_WINDOW_SECONDS)
+            .map(|end| vec![reencode(range.start, end)]);
+    };
+
+    let mut pieces = Vec::with_capacity(3);
+    if first_keyframe - range.start > KEYFRAME_TOLERANCE_SECONDS {
+        pieces.push(reencode(range.start, first_keyframe));
+    }
+    let Some(end) = range.end else {
+        pieces.push(SmartCutPiece {
+            start: first_keyframe,
+            end: None,
+            reencode: false,
+        });
+        return Some(pieces);
+    };
+    let last_keyframe = keyframes
+        .iter()
+        .copied()
+        .rev()
+        .find(|keyframe| *keyframe <= end + KEYFRAME_TOLERANCE_SECONDS)
+        .filter(|keyframe| *keyframe > first_keyframe)
+        .unwrap_or(first_keyframe);
+    if last_keyframe > first_keyframe {
+        pieces.push(SmartCutPiece {
+            start: first_keyframe,
+            end: Some(last_keyframe),
+            reencode: false,
+        });
+    }
+    if end - last_keyframe > KEYFRAME_TOLERANCE_SECONDS {
+        pieces.push(reencode(last_keyframe, end));
+    } else if let Some(copied) = pieces.last_mut().filter(|piece| !piece.reencode) {
+        copied.end = Some(end);
+    }
+    Some(pieces)
+}
+
+/// Arguments that write one smart cut piece of `input` to `output`. Video
+/// is re-encoded with `encoder` or copied; audio is always copied and
+/// subtitles are dropped.
+#[must_use]
+pub fn smart_cut_piece_args(
+    input: &str,
+    output: &str,
+    config: &ConversionConfig,
+    piece: &SmartCutPiece,
+    encoder: &SmartCutEncoder,
+) -> Vec<String> {
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-y".to_string(),
+        "-ss".to_string(),
+        format!("{:.3}", piece.start),
+        "-i".to_string(),
+        input.to_string(),
+    ];
+    if let Some(end) = piece.end {
+        args.push("-t".to_string());
+        args.push(format!("{:.3}", end - piece.start));
+    }
+    args.extend(["-map".to_string(), "0:V:0".to_string()]);
+    if config.selected_audio_tracks.is_empty() {
+        args.extend(["-map".to_string(), "0:a?".to_string()]);
+    } else {
+        for index in &config.selected_audio_tracks {
+            args.extend(["-map".to_string(), format!("0:{index}")]);
+        }
+    }
+    if piece.reencode {
+        args.extend(encoder.args.iter().cloned());
+        args.extend(["-fps_mode".to_string(), "passthrough".to_string()]);
+        args.extend(["-c:a".to_string(), "copy".to_string()]);
+    } else {
+        args.extend(["-c".to_string(), "copy".to_string()]);
+    }
+    args.extend(["-sn".to_string(), "-dn".to_string()]);
+    args.extend(["-avoid_negative_ts".to_string(), "make_zero".to_string()]);
+    if encoder.muxer == "mpegts" {
+        args.extend([
+            "-muxdelay".to_string(),
+            "0".to_string(),
+            "-muxpreload".to_string(),
+            "0".to_string(),
+        ]);
+    }
+    args.extend([
+        "-f".to_string(),
+        encoder.muxer.to_string(),
+        output.to_string(),
+    ]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn smart_cut_config() -> ConversionConfig {
+        let mut config = sample_config("mp4", "libx264");
+        config.processing_mode = "copy".to_string();
+        config.start_time = Some("00:00:10.5".to_string());
+        config.end_time = Some("00:01:00".to_string());
+        config.smart_cut = true;
+        config
+    }
+
+    fn h264_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            video_codec: Some("h264".to_string()),
+            pixel_format: Some("yuv420p".to_string()),
+            profile: Some("High".to_string()),
+            color_space: Some("bt709".to_string()),
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn smart_cut_range_needs_a_single_copy_trim() {
+        let mut config = smart_cut_config();
+        assert_eq!(
+            smart_cut_range(&config),
+            Some(TrimRange {
+                start: 10.5,
+                end: Some(60.0)
+            })
+        );
+
+        config.processing_mode = "reencode".to_string();
+        assert_eq!(smart_cut_range(&config), None);
+        config.processing_mode = "copy".to_string();
+        config.smart_cut = false;
+        assert_eq!(smart_cut_range(&config), None);
+    }
+
+    #[test]
+    fn plan_smart_cut_reencodes_only_the_partial_gops() {
+        let range = TrimRange {
+            start: 10.5,
+            end: Some(60.0),
+        };
+
+        let pieces = plan_smart_cut(range, &[8.0, 12.0, 16.0, 52.0, 56.0])
+            .expect("keyframes should plan a cut");
+
+        assert_eq!(
+            pieces,
+            [
+                SmartCutPiece {
+                    start: 10.5,
+                    end: Some(12.0),
+                    reencode: true
+                },
+                SmartCutPiece {
+                    start: 12.0,
+                    end: Some(56.0),
+                    reencode: false
+                },
+                SmartCutPiece {
+                    start: 56.0,
+                    end: Some(60.0),
+                    reencode: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_smart_cut_copies_edges_that_fall_on_keyframes() {
+        let on_keyframes = TrimRange {
+            start: 12.0,
+            end: Some(56.0),
+        };
+        let open_end = TrimRange {
+            start: 12.0,
+            end: None,
+        };
+
+        assert_eq!(
+            plan_smart_cut(on_keyframes, &[12.0, 56.0]),
+            Some(vec![SmartCutPiece {
+                start: 12.0,
+                end: Some(56.0),
+                reencode: false
+            }])
+        );
+        assert_eq!(
+            plan_smart_cut(open_end, &[12.0]),
+            Some(vec![SmartCutPiece {
+                start: 12.0,
+                end: None,
+                reencode: false
+            }])
+        );
+    }
+
+    #[test]
+    fn plan_smart_cut_reencodes_short_ranges_without_keyframes() {
+        let short = TrimRange {
+            start: 10.0,
+            end: Some(14.0),
+        };
+        let long = TrimRange {
+            start: 10.0,
+            end: None,
+        };
+
+        assert_eq!(
+            plan_smart_cut(short, &[8.0]),
+            Some(vec![SmartCutPiece {
+                start: 10.0,
+                end: Some(14.0),
+                reencode: true
+            }])
+        );
+        assert_eq!(plan_smart_cut(long, &[8.0]), None);
+    }
+
+    #[test]
+    fn smart_cut_encoder_matches_the_source_stream() {
+        let encoder = smart_cut_encoder(&h264_probe()).expect("h264 should be matched");
+        let mut interlaced = h264_probe();
+        interlaced.field_order = Some("tt".to_string());
+        let mut prores = h264_probe();
+        prores.video_codec = Some("prores".to_string());
+
+        assert_eq!(encoder.encoder, "libx264");
+        assert_eq!(encoder.extension(), "ts");
+        assert!(
+            encoder
+                .args
+                .windows(2)
+                .any(|pair| pair == ["-profile:v", "high"])
+        );
+        assert!(smart_cut_encoder(&interlaced).is_err());
+        assert!(smart_cut_encoder(&prores).is_err_and(|reason| reason.contains("prores")));
+    }
+
+    #[test]
+    fn smart_cut_piece_args_copy_audio_around_reencoded_video() {
+        let config = smart_cut_config();
+        let encoder = smart_cut_encoder(&h264_probe()).expect("h264 should be matched");
+        let piece = SmartCutPiece {
+            start: 10.5,
+            end: Some(12.0),
+            reencode: true,
+        };
+
+        let args = smart_cut_piece_args("/in.mp4", "/tmp/piece0.ts", &config, &piece, &encoder);
+
+        assert_eq!(
+            args[3..9],
+            ["-ss", "10.500", "-i", "/in.mp4", "-t", "1.500"]
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-c:v", "libx264"]));
+        assert!(args.windows(2).any(|pair| pair == ["-c:a", "copy"]));
+        assert!(args.windows(2).any(|pair| pair == ["-muxdelay", "0"]));
+        assert_eq!(args[args.len() - 3..], ["-f", "mpegts", "/tmp/piece0.ts"]);
+    }
+
+    #[test]
+    fn parse_keyframe_times_sorts_and_skips_unknown_times() {
+        assert_eq!(
+            parse_keyframe_times("12.000000\n8.000000,\nN/A\n12.0000001\n"),
+            [8.0, 12.0]
+        );
+    }
+}
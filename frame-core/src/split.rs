@@ -0,0 +1,487 @@
+//! Splitting one task's output into numbered parts with `FFmpeg`'s segment
+//! muxer.
+//!
+//! Parts are named from the task's output path: a `{part}` placeholder left
+//! by the filename template is replaced with the part number, otherwise
+//! `-partNNN` is added before the extension. Chapter splits name each part
+//! after its chapter instead.
+
+use crate::args::{build_output_path, output_muxer};
+use crate::error::ConversionError;
+use crate::estimate::{
+    SizeEstimateMethod, estimate_bitrate_size, estimate_stream_copy_size, size_estimate_method,
+};
+use crate::media_rules::{is_gif_container, is_image_container};
+use crate::output_template::replace_invalid_file_name_characters;
+use crate::segments::trim_ranges;
+use crate::types::{ConversionConfig, OutputSplit, ProbeMetadata};
+use crate::utils::is_audio_only_container;
+
+/// Placeholder the `{part}` filename token leaves in split output names.
+pub const SPLIT_PART_PLACEHOLDER: &str = "{part}";
+
+/// Shortest part the splitter produces, in seconds.
+const MIN_SPLIT_PART_SECONDS: f64 = 1.0;
+/// Span the bitrate is sampled over when sizing parts, in seconds.
+const SIZE_SPLIT_SAMPLE_SECONDS: f64 = 60.0;
+
+/// Where the segment muxer starts new parts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplitCuts {
+    /// A new part every this many seconds.
+    Every(f64),
+    /// New parts at these output timestamps, in seconds, in order.
+    At(Vec<f64>),
+}
+
+/// Reports whether the task writes several parts instead of one file.
+#[must_use]
+pub const fn splits_output(config: &ConversionConfig) -> bool {
+    config.split.is_some() || config.split_by_chapters
+}
+
+/// Validates the split settings of a task, independent of the source.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] for non-positive part sizes,
+/// chapter splits combined with other splits or trimming, or outputs that
+/// cannot be split, such as still images and GIFs.
+pub fn validate_output_split(config: &ConversionConfig) -> Result<(), ConversionError> {
+    if !splits_output(config) {
+        return Ok(());
+    }
+    if is_image_container(&config.container) || is_gif_container(&config.container) {
+        return Err(ConversionError::InvalidInput(format!(
+            "Output splitting is not available for container '{}'",
+            config.container
+        )));
+    }
+    if config.split_by_chapters {
+        if config.split.is_some() {
+            return Err(ConversionError::InvalidInput(
+                "Splitting by chapters cannot be combined with splitting by duration or size"
+                    .to_string(),
+            ));
+        }
+        if !trim_ranges(config).is_empty() || !config.remove_segments.is_empty() {
+            return Err(ConversionError::InvalidInput(
+                "Splitting by chapters cannot be combined with trimming".to_string(),
+            ));
+        }
+        return Ok(());
+    }
+    let Some(split) = config.split else {
+        return Ok(());
+    };
+    match split {
+        OutputSplit::Duration { seconds }
+            if !seconds.is_finite() || seconds < MIN_SPLIT_PART_SECONDS =>
+        {
+            Err(ConversionError::InvalidInput(format!(
+                "Split parts must be at least {MIN_SPLIT_PART_SECONDS:.0} second long"
+            )))
+        }
+        OutputSplit::Size { bytes: 0 } => Err(ConversionError::InvalidInput(
+            "Split part size must be positive".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Where the output is cut into parts, or `None` when the task is not split.
+///
+/// Size-based parts divide the target size by the bitrate the output is
+/// expected to reach at the top of the estimate range. Chapter splits cut at
+/// the start of every chapter after the first.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the split settings are
+/// invalid, when a chapter split source has fewer than two chapters, or
+/// when parts are sized by bytes but the output bitrate cannot be known in
+/// advance, as with CRF encodes.
+pub fn split_cuts(
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Result<Option<SplitCuts>, ConversionError> {
+    validate_output_split(config)?;
+    if config.split_by_chapters {
+        return chapter_cuts(probe).map(Some);
+    }
+    let bytes = match config.split {
+        None => return Ok(None),
+        Some(OutputSplit::Duration { seconds }) => return Ok(Some(SplitCuts::Every(seconds))),
+        Some(OutputSplit::Size { bytes }) => bytes,
+    };
+
+    let sample = match size_estimate_method(config, probe)? {
+        SizeEstimateMethod::StreamCopy => {
+            estimate_stream_copy_size(config, probe, SIZE_SPLIT_SAMPLE_SECONDS)
+        }
+        SizeEstimateMethod::Bitrate => {
+            estimate_bitrate_size(config, probe, SIZE_SPLIT_SAMPLE_SECONDS)
+        }
+        SizeEstimateMethod::SampleEncode => None,
+    }
+    .filter(|estimate| estimate.high_bytes > 0)
+    .ok_or_else(|| {
+        ConversionError::InvalidInput(
+            "Splitting by size needs a target bitrate or a source bitrate to copy".to_string(),
+        )
+    })?;
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "part sizes stay far below the range where f64 loses whole bytes"
+    )]
+    let seconds = bytes as f64 / sample.high_bytes as f64 * SIZE_SPLIT_SAMPLE_SECONDS;
+    Ok(Some(SplitCuts::Every(
+        seconds.floor().max(MIN_SPLIT_PART_SECONDS),
+    )))
+}
+
+fn chapter_cuts(probe: &ProbeMetadata) -> Result<SplitCuts, ConversionError> {
+    if probe.chapters.len() < 2 {
+        return Err(ConversionError::InvalidInput(
+            "Splitting by chapters needs a source with at least two chapters".to_string(),
+        ));
+    }
+    let mut cuts: Vec<f64> = Vec::with_capacity(probe.chapters.len() - 1);
+    for chapter in &probe.chapters[1..] {
+        if cuts
+            .last()
+            .is_some_and(|last| chapter.start_seconds <= *last)
+            || chapter.start_seconds <= 0.0
+        {
+            return Err(ConversionError::InvalidInput(format!(
+                "Chapter {} starts before the chapter it follows",
+                chapter.id
+            )));
+        }
+        cuts.push(chapter.start_seconds);
+    }
+    Ok(SplitCuts::At(cuts))
+}
+
+/// Warns that stream copy can only cut parts at source keyframes.
+#[must_use]
+pub fn split_keyframe_warning(config: &ConversionConfig) -> Option<String> {
+    (splits_output(config) && config.processing_mode == "copy").then(|| {
+        "Copy mode cuts parts at the nearest source keyframe, so part bounds may be off by \
+         up to one keyframe interval"
+            .to_string()
+    })
+}
+
+/// Output options that make the segment muxer cut at `cuts`. Re-encodes
+/// force a keyframe at every cut so parts have exact bounds; stream copy
+/// cuts at the next source keyframe.
+#[must_use]
+pub fn split_output_args(config: &ConversionConfig, cuts: &SplitCuts) -> Vec<String> {
+    let (keyframes, cut_option, cut_value) = match cuts {
+        SplitCuts::Every(seconds) => {
+            let seconds = format!("{seconds:.3}");
+            (
+                format!("expr:gte(t,n_forced*{seconds})"),
+                "-segment_time",
+                seconds,
+            )
+        }
+        SplitCuts::At(times) => {
+            let times = times
+                .iter()
+                .map(|time| format!("{time:.3}"))
+                .collect::<Vec<_>>()
+                .join(",");
+            (times.clone(), "-segment_times", times)
+        }
+    };
+    let mut args = Vec::new();
+    if config.processing_mode != "copy" && !is_audio_only_container(&config.container) {
+        args.push("-force_key_frames".to_string());
+        args.push(keyframes);
+    }
+    args.push("-f".to_string());
+    args.push("segment".to_string());
+    if let Some(muxer) = output_muxer(&config.container) {
+        args.push("-segment_format".to_string());
+        args.push(muxer.to_string());
+    }
+    args.extend([
+        cut_option.to_string(),
+        cut_value,
+        "-segment_start_number".to_string(),
+        "1".to_string(),
+        "-reset_timestamps".to_string(),
+        "1".to_string(),
+    ]);
+    args
+}
+
+/// Final paths of the parts of a chapter split, in chapter order.
+///
+/// Parts are named `{name} - {index:02} {title}` after the output stem and
+/// the chapter; chapters without a title are only numbered. Empty unless the
+/// task splits by chapters.
+#[must_use]
+pub fn chapter_part_paths(
+    output_path: &str,
+    config: &ConversionConfig,
+    probe: &ProbeMetadata,
+) -> Vec<String> {
+    if !config.split_by_chapters {
+        return Vec::new();
+    }
+    let (directory, file_name) = output_path
+        .rsplit_once(['/', '\\'])
+        .unwrap_or((".", output_path));
+    let extension = format!(".{}", config.container);
+    let stem = file_name.strip_suffix(&extension).unwrap_or(file_name);
+    probe
+        .chapters
+        .iter()
+        .zip(1_usize..)
+        .map(|(chapter, index)| {
+            let name = chapter_part_name(stem, index, chapter.title.as_deref());
+            build_output_path(directory, &config.container, Some(&name))
+        })
+        .collect()
+}
+
+/// Output name of chapter `index` (counted from 1): `{stem} - {index:02}`,
+/// followed by the chapter title with characters invalid in file names
+/// replaced.
+pub(crate) fn chapter_part_name(stem: &str, index: usize, title: Option<&str>) -> String {
+    title
+        .map(|title| replace_invalid_file_name_characters(title.trim()))
+        .filter(|title| !title.is_empty())
+        .map_or_else(
+            || format!("{stem} - {index:02}"),
+            |title| format!("{stem} - {index:02} {title}"),
+        )
+}
+
+fn split_path_around_part(output_path: &str) -> (String, String) {
+    if let Some((head, tail)) = output_path.rsplit_once(SPLIT_PART_PLACEHOLDER) {
+        return (head.to_string(), tail.to_string());
+    }
+    let file_start = output_path.rfind(['/', '\\']).map_or(0, |index| index + 1);
+    match output_path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let (stem, extension) = output_path.split_at(file_start + dot);
+            (format!("{stem}-part"), extension.to_string())
+        }
+        _ => (format!("{output_path}-part"), String::new()),
+    }
+}
+
+/// Path of part `part` (counted from 1) of a split output.
+#[must_use]
+pub fn split_part_path(output_path: &str, part: u32) -> String {
+    let (head, tail) = split_path_around_part(output_path);
+    format!("{head}{part:03}{tail}")
+}
+
+/// Pattern handed to the segment muxer for a split output; `%` in the path
+/// is escaped so only the part number is substituted.
+#[must_use]
+pub fn split_part_pattern(output_path: &str) -> String {
+    let (head, tail) = split_path_around_part(output_path);
+    format!("{}%03d{}", head.replace('%', "%%"), tail.replace('%', "%%"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Chapter, sample_config};
+
+    fn split_config(split: Option<OutputSplit>) -> ConversionConfig {
+        let mut config = sample_config("mp4", "libx264");
+        config.video_bitrate_mode = "bitrate".to_string();
+        config.video_bitrate = "7872".to_string();
+        config.split = split;
+        config
+    }
+
+    fn chapter(id: i64, start_seconds: f64, title: Option<&str>) -> Chapter {
+        Chapter {
+            id,
+            start_seconds,
+            end_seconds: start_seconds + 600.0,
+            title: title.map(str::to_string),
+        }
+    }
+
+    fn sample_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("3600.0".to_string()),
+            video_codec: Some("h264".to_string()),
+            audio_tracks: vec![crate::types::AudioTrack {
+                index: 1,
+                codec: "aac".to_string(),
+                ..crate::types::AudioTrack::default()
+            }],
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn split_part_paths_number_parts_before_the_extension() {
+        assert_eq!(split_part_path("/out/talk.mp4", 2), "/out/talk-part002.mp4");
+        assert_eq!(
+            split_part_pattern("/out/100%/talk.mp4"),
+            "/out/100%%/talk-part%03d.mp4"
+        );
+        assert_eq!(split_part_path("/out.d/talk", 1), "/out.d/talk-part001");
+    }
+
+    #[test]
+    fn split_part_paths_fill_the_template_placeholder() {
+        assert_eq!(
+            split_part_path("/out/talk ({part}).mkv", 12),
+            "/out/talk (012).mkv"
+        );
+        assert_eq!(
+            split_part_pattern("/out/{part}_talk.mkv"),
+            "/out/%03d_talk.mkv"
+        );
+    }
+
+    #[test]
+    fn split_cuts_uses_the_configured_duration() {
+        let config = split_config(Some(OutputSplit::Duration { seconds: 900.0 }));
+
+        assert_eq!(
+            split_cuts(&config, &sample_probe()).ok(),
+            Some(Some(SplitCuts::Every(900.0)))
+        );
+        assert_eq!(
+            split_cuts(&split_config(None), &sample_probe()).ok(),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn split_cuts_sizes_parts_from_the_target_bitrate() {
+        let config = split_config(Some(OutputSplit::Size {
+            bytes: 2_000_000_000,
+        }));
+
+        let Some(SplitCuts::Every(seconds)) =
+            split_cuts(&config, &sample_probe()).expect("bitrate encodes can be split by size")
+        else {
+            panic!("size splits should cut at a fixed interval");
+        };
+
+        // 8000 kbps is 1 MB/s; the estimate margin keeps parts under 2 GB.
+        assert!(seconds < 2000.0);
+        assert!(seconds > 1500.0);
+    }
+
+    #[test]
+    fn split_cuts_rejects_size_splits_without_a_known_bitrate() {
+        let mut config = split_config(Some(OutputSplit::Size { bytes: 1_000_000 }));
+        config.video_bitrate_mode = "crf".to_string();
+
+        assert!(split_cuts(&config, &sample_probe()).is_err());
+    }
+
+    #[test]
+    fn validate_output_split_rejects_short_parts_and_still_images() {
+        let short = split_config(Some(OutputSplit::Duration { seconds: 0.5 }));
+        assert!(validate_output_split(&short).is_err());
+
+        let mut image = split_config(Some(OutputSplit::Duration { seconds: 60.0 }));
+        image.container = "png".to_string();
+        assert!(validate_output_split(&image).is_err());
+    }
+
+    #[test]
+    fn split_output_args_force_keyframes_only_when_reencoding() {
+        let mut config = split_config(Some(OutputSplit::Duration { seconds: 900.0 }));
+
+        let reencode = split_output_args(&config, &SplitCuts::Every(900.0));
+        assert_eq!(
+            reencode[..4],
+            [
+                "-force_key_frames",
+                "expr:gte(t,n_forced*900.000)",
+                "-f",
+                "segment"
+            ]
+        );
+        assert!(
+            reencode
+                .windows(2)
+                .any(|pair| pair == ["-segment_format", "mp4"])
+        );
+
+        config.processing_mode = "copy".to_string();
+        let copy = split_output_args(&config, &SplitCuts::Every(900.0));
+        assert_eq!(copy[..2], ["-f", "segment"]);
+        assert!(
+            copy.windows(2)
+                .any(|pair| pair == ["-reset_timestamps", "1"])
+        );
+    }
+
+    #[test]
+    fn chapter_splits_cut_at_every_later_chapter_start() {
+        let mut config = split_config(None);
+        config.split_by_chapters = true;
+        let mut probe = sample_probe();
+        probe.chapters = vec![
+            chapter(0, 0.0, Some("Intro")),
+            chapter(1, 600.0, None),
+            chapter(2, 1200.5, Some("Encore")),
+        ];
+
+        let cuts = split_cuts(&config, &probe)
+            .expect("chapters should cut")
+            .expect("chapter split should be enabled");
+        let args = split_output_args(&config, &cuts);
+
+        assert_eq!(cuts, SplitCuts::At(vec![600.0, 1200.5]));
+        assert_eq!(args[..2], ["-force_key_frames", "600.000,1200.500"]);
+        assert!(
+            args.windows(2)
+                .any(|pair| pair == ["-segment_times", "600.000,1200.500"])
+        );
+    }
+
+    #[test]
+    fn chapter_splits_require_chapters_and_no_trimming() {
+        let mut config = split_config(None);
+        config.split_by_chapters = true;
+        let error = split_cuts(&config, &sample_probe())
+            .expect_err("a source without chapters cannot be split by chapter");
+
+        config.start_time = Some("00:00:10".to_string());
+
+        assert!(error.to_string().contains("at least two chapters"));
+        assert!(validate_output_split(&config).is_err());
+        assert!(split_keyframe_warning(&config).is_none());
+        config.processing_mode = "copy".to_string();
+        assert!(split_keyframe_warning(&config).is_some());
+    }
+
+    #[test]
+    fn chapter_part_paths_name_parts_after_chapter_titles() {
+        let mut config = split_config(None);
+        config.container = "m4a".to_string();
+        let mut probe = sample_probe();
+        probe.chapters = vec![
+            chapter(0, 0.0, Some("Act I: Arrival")),
+            chapter(1, 600.0, Some("  ")),
+        ];
+
+        assert!(chapter_part_paths("/out/show.m4a", &config, &probe).is_empty());
+        config.split_by_chapters = true;
+
+        assert_eq!(
+            chapter_part_paths("/out/show.m4a", &config, &probe),
+            ["/out/show - 01 Act I_ Arrival.m4a", "/out/show - 02.m4a"]
+        );
+    }
+}
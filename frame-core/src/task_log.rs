@@ -0,0 +1,147 @@
+//! Bounded per-task `FFmpeg` log retention.
+
+use std::collections::VecDeque;
+
+/// Maximum number of lines kept for a single task.
+pub const TASK_LOG_MAX_LINES: usize = 2000;
+/// Number of trailing log lines attached to failure events.
+pub const TASK_LOG_FAILURE_TAIL_LINES: usize = 30;
+/// Maximum number of finished tasks whose logs stay in memory.
+pub const TASK_LOG_MAX_RETAINED_TASKS: usize = 50;
+
+/// Ring buffer holding the most recent log lines of one task.
+#[derive(Clone, Debug)]
+pub struct TaskLogBuffer {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for TaskLogBuffer {
+    fn default() -> Self {
+        Self::with_capacity(TASK_LOG_MAX_LINES)
+    }
+}
+
+impl TaskLogBuffer {
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            lines: VecDeque::with_capacity(capacity.min(TASK_LOG_MAX_LINES)),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, line: impl Into<String>) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.into());
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    #[must_use]
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines.into()
+    }
+}
+
+/// Logs of recently finished tasks, evicting the oldest task beyond the cap.
+#[derive(Clone, Debug)]
+pub struct TaskLogStore {
+    logs: VecDeque<(String, Vec<String>)>,
+    max_tasks: usize,
+}
+
+impl Default for TaskLogStore {
+    fn default() -> Self {
+        Self::with_max_tasks(TASK_LOG_MAX_RETAINED_TASKS)
+    }
+}
+
+impl TaskLogStore {
+    #[must_use]
+    pub fn with_max_tasks(max_tasks: usize) -> Self {
+        Self {
+            logs: VecDeque::new(),
+            max_tasks: max_tasks.max(1),
+        }
+    }
+
+    pub fn insert(&mut self, id: impl Into<String>, lines: Vec<String>) {
+        let id = id.into();
+        self.logs.retain(|(existing, _)| *existing != id);
+        if self.logs.len() == self.max_tasks {
+            self.logs.pop_front();
+        }
+        self.logs.push_back((id, lines));
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&[String]> {
+        self.logs
+            .iter()
+            .find(|(existing, _)| existing == id)
+            .map(|(_, lines)| lines.as_slice())
+    }
+
+    /// Returns up to `count` trailing lines of a stored task log.
+    #[must_use]
+    pub fn tail(&self, id: &str, count: usize) -> Vec<String> {
+        self.get(id).map_or_else(Vec::new, |lines| {
+            lines[lines.len().saturating_sub(count)..].to_vec()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_log_buffer_keeps_most_recent_lines() {
+        let mut buffer = TaskLogBuffer::with_capacity(3);
+
+        for index in 0..5 {
+            buffer.push(format!("line {index}"));
+        }
+
+        assert_eq!(buffer.into_lines(), ["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn task_log_store_evicts_oldest_task() {
+        let mut store = TaskLogStore::with_max_tasks(2);
+
+        store.insert("a", vec!["a".to_string()]);
+        store.insert("b", vec!["b".to_string()]);
+        store.insert("c", vec!["c".to_string()]);
+
+        assert_eq!(store.get("a"), None);
+        assert_eq!(store.get("c"), Some(["c".to_string()].as_slice()));
+    }
+
+    #[test]
+    fn task_log_store_tail_returns_trailing_lines() {
+        let mut store = TaskLogStore::default();
+        store.insert(
+            "task-1",
+            (0..40).map(|index| format!("line {index}")).collect(),
+        );
+
+        let tail = store.tail("task-1", TASK_LOG_FAILURE_TAIL_LINES);
+
+        assert_eq!(tail.len(), TASK_LOG_FAILURE_TAIL_LINES);
+        assert_eq!(tail.first().map(String::as_str), Some("line 10"));
+        assert!(store.tail("missing", 5).is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+//! Per-task conversion logs written to disk so they survive the session.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+pub const TASK_LOG_FILE_EXTENSION: &str = "log";
+/// Longest time a written line may stay buffered before reaching disk.
+pub const TASK_LOG_FILE_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// Age after which [`prune_task_logs`] removes a log by default.
+pub const TASK_LOG_FILE_MAX_AGE: Duration = Duration::from_hours(30 * 24);
+/// Number of most recent logs [`prune_task_logs`] keeps by default.
+pub const TASK_LOG_FILE_MAX_FILES: usize = 200;
+
+const EXIT_LINE_PREFIX: &str = "[EXIT] ";
+/// Bytes read from the end of a log when looking for its exit line.
+const STATUS_TAIL_BYTES: u64 = 4096;
+
+/// How the process recorded in a log file ended.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskLogStatus {
+    Completed,
+    Failed,
+    Cancelled,
+    /// No exit line was written: the task is still running or Frame quit
+    /// before it finished.
+    Incomplete,
+}
+
+impl TaskLogStatus {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Incomplete => "incomplete",
+        }
+    }
+
+    fn from_exit_line(line: &str) -> Self {
+        let status = line
+            .strip_prefix(EXIT_LINE_PREFIX)
+            .and_then(|rest| rest.split_whitespace().next());
+        match status {
+            Some("completed") => Self::Completed,
+            Some("failed") => Self::Failed,
+            Some("cancelled") => Self::Cancelled,
+            _ => Self::Incomplete,
+        }
+    }
+}
+
+/// A log file found by [`list_task_logs`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskLogEntry {
+    pub id: String,
+    /// Last write time, in seconds since the Unix epoch.
+    pub modified_unix_seconds: u64,
+    pub status: TaskLogStatus,
+    pub path: PathBuf,
+}
+
+/// Limits applied by [`prune_task_logs`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TaskLogRetention {
+    pub max_age: Duration,
+    pub max_files: usize,
+}
+
+impl Default for TaskLogRetention {
+    fn default() -> Self {
+        Self {
+            max_age: TASK_LOG_FILE_MAX_AGE,
+            max_files: TASK_LOG_FILE_MAX_FILES,
+        }
+    }
+}
+
+/// Path of the log for `task_id` inside `directory`; characters that are not
+/// safe in file names are replaced with `_`.
+#[must_use]
+pub fn task_log_path(directory: &Path, task_id: &str) -> PathBuf {
+    let stem = task_id
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    directory.join(format!("{stem}.{TASK_LOG_FILE_EXTENSION}"))
+}
+
+/// Streams one task's output to its log file, flushing at least every
+/// [`TASK_LOG_FILE_FLUSH_INTERVAL`].
+#[derive(Debug)]
+pub struct TaskLogFile {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    last_flush: Instant,
+}
+
+impl TaskLogFile {
+    /// Creates or replaces the log of `task_id` in `directory` and writes the
+    /// exact argument vector of the process it records, preceded by the
+    /// `FFmpeg` version when it is known.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the directory or file cannot be created or
+    /// written.
+    pub fn create(
+        directory: &Path,
+        task_id: &str,
+        program: &str,
+        args: &[String],
+        ffmpeg_version: Option<&str>,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let path = task_log_path(directory, task_id);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let command_line = std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        if let Some(version) = ffmpeg_version {
+            writeln!(writer, "[FFMPEG] {version}")?;
+        }
+        writeln!(writer, "[TASK] {task_id}")?;
+        writeln!(
+            writer,
+            "[ARGS] {}",
+            serde_json::to_string(&command_line).map_err(io::Error::other)?
+        )?;
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            path,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Reopens the finished log of `task_id` in `directory` for a pass that
+    /// runs after its process exited, such as output verification. The exit
+    /// line written by [`Self::finish`] then supersedes the earlier one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the log does not exist or cannot be opened.
+    pub fn reopen(directory: &Path, task_id: &str) -> io::Result<Self> {
+        let path = task_log_path(directory, task_id);
+        let file = fs::OpenOptions::new().append(true).open(&path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+            last_flush: Instant::now(),
+        })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends one line of process output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the line cannot be written or flushed.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{line}")?;
+        if self.last_flush.elapsed() >= TASK_LOG_FILE_FLUSH_INTERVAL {
+            self.writer.flush()?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Writes the exit line, e.g. `[EXIT] failed (exit status: 1)`, and
+    /// flushes the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the line cannot be written or flushed.
+    pub fn finish(mut self, status: TaskLogStatus, detail: &str) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{EXIT_LINE_PREFIX}{} ({detail})",
+            status.as_str()
+        )?;
+        self.writer.flush()
+    }
+}
+
+/// Lists the logs in `directory`, newest first. A missing directory has no
+/// logs.
+///
+/// # Errors
+///
+/// Returns an error when the directory or a log's metadata cannot be read.
+pub fn list_task_logs(directory: &Path) -> io::Result<Vec<TaskLogEntry>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut logs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str())
+            != Some(TASK_LOG_FILE_EXTENSION)
+        {
+            continue;
+        }
+        let metadata = fs::metadata(&path)?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        logs.push(TaskLogEntry {
+            id: id.to_string(),
+            modified_unix_seconds: unix_seconds(metadata.modified()?),
+            status: read_task_log_status(&path)?,
+            path,
+        });
+    }
+    logs.sort_by(|left, right| {
+        right
+            .modified_unix_seconds
+            .cmp(&left.modified_unix_seconds)
+            .then_with(|| left.id.cmp(&right.id))
+    });
+    Ok(logs)
+}
+
+/// Removes logs older than `retention.max_age` at `now`, then all but the
+/// `retention.max_files` newest, returning how many were removed.
+///
+/// # Errors
+///
+/// Returns an error when the logs cannot be listed or removed.
+pub fn prune_task_logs(
+    directory: &Path,
+    retention: TaskLogRetention,
+    now: SystemTime,
+) -> io::Result<usize> {
+    let now = unix_seconds(now);
+    let mut removed = 0;
+    for (index, log) in list_task_logs(directory)?.into_iter().enumerate() {
+        let expired = now.saturating_sub(log.modified_unix_seconds) > retention.max_age.as_secs();
+        if expired || index >= retention.max_files {
+            fs::remove_file(&log.path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn read_task_log_status(path: &Path) -> io::Result<TaskLogStatus> {
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(STATUS_TAIL_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok(String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map_or(TaskLogStatus::Incomplete, TaskLogStatus::from_exit_line))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_test_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("frame-task-logs-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn task_log_file_records_arguments_output_and_exit_status() {
+        let directory = unique_test_dir("write");
+        let mut log = TaskLogFile::create(
+            &directory,
+            "task/1",
+            "ffmpeg",
+            &["-i".to_string(), "in put.mov".to_string()],
+            Some("7.1"),
+        )
+        .expect("log file should be created");
+        let path = log.path().to_path_buf();
+        log.write_line("frame=1").expect("line should be written");
+        log.finish(TaskLogStatus::Failed, "exit status: 1")
+            .expect("log should be finished");
+
+        let contents = fs::read_to_string(&path).expect("log should be readable");
+        let logs = list_task_logs(&directory).expect("logs should be listed");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(path, directory.join("task_1.log"));
+        assert_eq!(
+            contents,
+            "[FFMPEG] 7.1\n[TASK] task/1\n[ARGS] [\"ffmpeg\",\"-i\",\"in put.mov\"]\nframe=1\n\
+             [EXIT] failed (exit status: 1)\n"
+        );
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].id, "task_1");
+        assert_eq!(logs[0].status, TaskLogStatus::Failed);
+    }
+
+    #[test]
+    fn reopened_logs_report_their_latest_exit_status() {
+        let directory = unique_test_dir("reopen");
+        TaskLogFile::create(&directory, "verified", "ffmpeg", &[], None)
+            .and_then(|log| log.finish(TaskLogStatus::Completed, "exit status: 0"))
+            .expect("log should be written");
+        let mut log = TaskLogFile::reopen(&directory, "verified").expect("log should be reopened");
+        log.write_line("[WARN] Output failed verification: 2 decode errors")
+            .expect("line should be written");
+        log.finish(TaskLogStatus::Failed, "output failed verification")
+            .expect("log should be finished");
+
+        let logs = list_task_logs(&directory).expect("logs should be listed");
+        let missing = TaskLogFile::reopen(&directory, "missing");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(logs[0].status, TaskLogStatus::Failed);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn list_task_logs_reports_unfinished_logs_and_missing_directories() {
+        let directory = unique_test_dir("list");
+        let log = TaskLogFile::create(&directory, "running", "ffmpeg", &[], None)
+            .expect("log file should be created");
+        drop(log);
+        fs::write(directory.join("notes.txt"), b"not a log").expect("file should be written");
+
+        let logs = list_task_logs(&directory).expect("logs should be listed");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].status, TaskLogStatus::Incomplete);
+        assert!(
+            list_task_logs(&directory.join("missing"))
+                .expect("missing directory should list")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn prune_task_logs_applies_age_and_count_limits() {
+        let directory = unique_test_dir("prune");
+        let now = SystemTime::now();
+        for (id, age_days) in [("new", 0), ("recent", 1), ("old", 40)] {
+            TaskLogFile::create(&directory, id, "ffmpeg", &[], None)
+                .and_then(|log| log.finish(TaskLogStatus::Completed, "exit status: 0"))
+                .expect("log should be written");
+            File::options()
+                .write(true)
+                .open(task_log_path(&directory, id))
+                .and_then(|file| file.set_modified(now - Duration::from_hours(age_days * 24)))
+                .expect("modification time should be set");
+        }
+
+        let removed_by_age =
+            prune_task_logs(&directory, TaskLogRetention::default(), now).expect("prune");
+        let removed_by_count = prune_task_logs(
+            &directory,
+            TaskLogRetention {
+                max_files: 1,
+                ..TaskLogRetention::default()
+            },
+            now,
+        )
+        .expect("prune");
+        let remaining = list_task_logs(&directory).expect("logs should be listed");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(removed_by_age, 1);
+        assert_eq!(removed_by_count, 1);
+        assert_eq!(
+            remaining
+                .iter()
+                .map(|log| log.id.as_str())
+                .collect::<Vec<_>>(),
+            ["new"]
+        );
+    }
+}
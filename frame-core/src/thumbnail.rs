@@ -0,0 +1,297 @@
+//! Grabbing one frame of a source as a still image, such as a poster or a
+//! thumbnail.
+//!
+//! The input is seeked before it is opened, so only the frames from the
+//! preceding keyframe are decoded and repeated grabs stay quick.
+
+use serde::Serialize;
+
+use crate::codec::jpeg_quality_to_qscale;
+use crate::error::ConversionError;
+use crate::probe::hdr_transfer_name;
+use crate::timecode::parse_time_at_frame_rate;
+use crate::types::ProbeMetadata;
+use crate::utils::format_time;
+
+/// Maps PQ and HLG sources to BT.709 SDR with the Hable curve, so frames of
+/// HDR video keep their contrast.
+pub const FRAME_TONEMAP_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,\
+     tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,format=rgb24";
+
+/// How a frame is grabbed and encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameExtractOptions {
+    /// Source time in any trim time form, e.g. `00:12:34` or `250f`.
+    pub timestamp: String,
+    /// `jpg`, `png` or `webp`.
+    pub format: String,
+    /// Encoder quality from 1 to 100; PNG is lossless and ignores it.
+    pub quality: u32,
+    /// Output width; the height follows the source aspect ratio.
+    pub width: Option<u32>,
+    /// Whether the `FFmpeg` build has the `zscale` and `tonemap` filters.
+    pub tonemapping: bool,
+}
+
+/// `FFmpeg` arguments for one frame grab.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameExtractPlan {
+    pub args: Vec<String>,
+    /// Source second the frame is taken from, after clamping.
+    pub seconds: f64,
+    /// Adjustments the caller should be told about.
+    pub notes: Vec<String>,
+}
+
+/// A written frame.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedFrame {
+    pub output_path: String,
+    pub seconds: f64,
+    pub notes: Vec<String>,
+}
+
+/// Builds the arguments that write the frame of `input` at
+/// `options.timestamp` to `output`.
+///
+/// Timestamps at or past the end of the source are moved to its last second,
+/// and HDR sources are tone mapped when the build allows it; both are
+/// reported in the plan's notes.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when the source has no video,
+/// or the timestamp, format, quality or width is invalid.
+pub fn build_frame_extract_args(
+    input: &str,
+    output: &str,
+    options: &FrameExtractOptions,
+    probe: &ProbeMetadata,
+) -> Result<FrameExtractPlan, ConversionError> {
+    if probe.video_codec.is_none() {
+        return Err(ConversionError::InvalidInput(
+            "The source has no video stream to take a frame from".to_string(),
+        ));
+    }
+    let encoder_args = frame_encoder_args(&options.format, options.quality)?;
+    let timestamp = options.timestamp.trim();
+    let requested = parse_time_at_frame_rate(timestamp, probe.frame_rate)
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .ok_or_else(|| {
+            ConversionError::InvalidInput(format!("Invalid frame timestamp: {timestamp}"))
+        })?;
+
+    let mut notes = Vec::new();
+    let seconds = match probe.duration_seconds() {
+        Some(duration) if requested >= duration => {
+            let last_second = (duration - 1.0).max(0.0);
+            notes.push(format!(
+                "{timestamp} is past the end of the source; the frame at {} was used instead",
+                format_time(last_second)
+            ));
+            last_second
+        }
+        _ => requested,
+    };
+
+    let mut filters = Vec::new();
+    match options.width {
+        Some(0) => {
+            return Err(ConversionError::InvalidInput(
+                "Frame width must be greater than zero".to_string(),
+            ));
+        }
+        Some(width) => filters.push(format!("scale={width}:-2")),
+        None => {}
+    }
+    if let Some(transfer) = hdr_transfer_name(probe.color_transfer.as_deref().unwrap_or_default()) {
+        if options.tonemapping {
+            filters.push(FRAME_TONEMAP_FILTER.to_string());
+        } else {
+            notes.push(format!(
+                "Source uses {transfer} HDR, but this FFmpeg build cannot tone map it; the frame may look washed out"
+            ));
+        }
+    }
+
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-loglevel".to_string(),
+        "error".to_string(),
+        "-ss".to_string(),
+        format!("{seconds:.3}"),
+        "-i".to_string(),
+        input.to_string(),
+        "-map".to_string(),
+        "0:V:0".to_string(),
+    ];
+    if !filters.is_empty() {
+        args.push("-vf".to_string());
+        args.push(filters.join(","));
+    }
+    args.extend(encoder_args);
+    args.extend([
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-update".to_string(),
+        "1".to_string(),
+        "-an".to_string(),
+        "-sn".to_string(),
+        "-dn".to_string(),
+        "-f".to_string(),
+        "image2".to_string(),
+        "-y".to_string(),
+        output.to_string(),
+    ]);
+
+    Ok(FrameExtractPlan {
+        args,
+        seconds,
+        notes,
+    })
+}
+
+pub(crate) fn frame_encoder_args(
+    format: &str,
+    quality: u32,
+) -> Result<Vec<String>, ConversionError> {
+    let format = format.trim().to_ascii_lowercase();
+    if format != "png" && !(1..=100).contains(&quality) {
+        return Err(ConversionError::InvalidInput(
+            "Frame quality must be between 1 and 100".to_string(),
+        ));
+    }
+    let args = match format.as_str() {
+        "jpg" | "jpeg" => vec![
+            "-c:v".to_string(),
+            "mjpeg".to_string(),
+            "-q:v".to_string(),
+            jpeg_quality_to_qscale(quality).to_string(),
+        ],
+        "png" => vec!["-c:v".to_string(), "png".to_string()],
+        "webp" => vec![
+            "-c:v".to_string(),
+            "libwebp".to_string(),
+            "-quality".to_string(),
+            quality.to_string(),
+        ],
+        _ => {
+            return Err(ConversionError::InvalidInput(format!(
+                "Frames can be saved as jpg, png or webp, not '{format}'"
+            )));
+        }
+    };
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(timestamp: &str, format: &str) -> FrameExtractOptions {
+        FrameExtractOptions {
+            timestamp: timestamp.to_string(),
+            format: format.to_string(),
+            quality: 90,
+            width: Some(640),
+            tonemapping: true,
+        }
+    }
+
+    fn video_probe() -> ProbeMetadata {
+        ProbeMetadata {
+            duration: Some("900.0".to_string()),
+            video_codec: Some("h264".to_string()),
+            frame_rate: Some(25.0),
+            ..ProbeMetadata::default()
+        }
+    }
+
+    #[test]
+    fn frames_are_seeked_on_input_scaled_and_encoded_once() {
+        let plan = build_frame_extract_args(
+            "/in/film.mkv",
+            "/out/poster.jpg",
+            &options("00:12:34", "jpg"),
+            &video_probe(),
+        )
+        .expect("frame args should build");
+
+        assert_eq!(plan.args[4..8], ["-ss", "754.000", "-i", "/in/film.mkv"]);
+        assert!(
+            plan.args
+                .windows(2)
+                .any(|pair| pair == ["-vf", "scale=640:-2"])
+        );
+        assert!(plan.args.windows(2).any(|pair| pair == ["-c:v", "mjpeg"]));
+        assert!(plan.args.windows(2).any(|pair| pair == ["-frames:v", "1"]));
+        assert_eq!(
+            plan.args.last().map(String::as_str),
+            Some("/out/poster.jpg")
+        );
+        assert!(plan.notes.is_empty());
+    }
+
+    #[test]
+    fn timestamps_past_the_end_use_the_last_second() {
+        let plan = build_frame_extract_args(
+            "/in/film.mkv",
+            "/out/poster.png",
+            &options("2:00:00", "png"),
+            &video_probe(),
+        )
+        .expect("late timestamps should be clamped");
+
+        assert_eq!(plan.seconds.to_bits(), 899.0_f64.to_bits());
+        assert_eq!(
+            plan.notes,
+            ["2:00:00 is past the end of the source; the frame at 00:14:59.000 was used instead"]
+        );
+    }
+
+    #[test]
+    fn hdr_frames_are_tone_mapped_when_the_build_can() {
+        let mut probe = video_probe();
+        probe.color_transfer = Some("smpte2084".to_string());
+        let mut without_zimg = options("10", "webp");
+        without_zimg.tonemapping = false;
+
+        let mapped =
+            build_frame_extract_args("/in/hdr.mkv", "/out/a.webp", &options("10", "webp"), &probe)
+                .expect("HDR frame args should build");
+        let unmapped =
+            build_frame_extract_args("/in/hdr.mkv", "/out/a.webp", &without_zimg, &probe)
+                .expect("HDR frame args should build");
+
+        assert!(
+            mapped
+                .args
+                .contains(&format!("scale=640:-2,{FRAME_TONEMAP_FILTER}"))
+        );
+        assert!(!unmapped.args.iter().any(|arg| arg.contains("tonemap")));
+        assert!(unmapped.notes[0].contains("cannot tone map"));
+    }
+
+    #[test]
+    fn invalid_frame_requests_are_rejected() {
+        let mut zero_width = options("10", "jpg");
+        zero_width.width = Some(0);
+        let mut low_quality = options("10", "webp");
+        low_quality.quality = 0;
+        let cases = [
+            (options("soon", "jpg"), "Invalid frame timestamp: soon"),
+            (options("10", "gif"), "not 'gif'"),
+            (zero_width, "width must be greater than zero"),
+            (low_quality, "quality must be between 1 and 100"),
+        ];
+
+        for (options, expected) in cases {
+            let error = build_frame_extract_args("/in.mkv", "/out.jpg", &options, &video_probe())
+                .expect_err("request should be rejected")
+                .to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Frame-based trim times: frame counts such as `1234f` and SMPTE
+//! `hh:mm:ss:ff` timecodes, next to the clock times [`parse_time`] reads.
+//!
+//! Frame-based times only become seconds once the source frame rate is
+//! probed, so validation before probing checks their syntax and the worker
+//! rewrites them into seconds with [`resolve_frame_times`].
+//!
+//! Timecodes are read as non-drop-frame: the frame field counts frames at
+//! the nominal rate (24 for 23.976, 30 for 29.97) and the frame number is
+//! divided by the real rate, the way editors label NDF timelines.
+//! Drop-frame timecodes written with `;` are rejected rather than guessed.
+
+use crate::error::ConversionError;
+use crate::types::ConversionConfig;
+use crate::utils::parse_time;
+
+/// One parsed trim time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeInput {
+    /// Clock time in seconds: `ss[.ms]`, `mm:ss[.ms]` or `hh:mm:ss[.ms]`.
+    Seconds(f64),
+    /// Frame count written with an `f` suffix.
+    Frames(u64),
+    /// SMPTE `hh:mm:ss:ff` timecode, stored as whole seconds plus frames.
+    Timecode { seconds: u64, frames: u64 },
+}
+
+impl TimeInput {
+    /// Reports whether the time can only be converted with a frame rate.
+    #[must_use]
+    pub const fn needs_frame_rate(self) -> bool {
+        !matches!(self, Self::Seconds(_))
+    }
+
+    /// Converts the time into seconds of source time. Returns `None` when a
+    /// frame rate is needed but missing, or when a timecode frame field
+    /// reaches the nominal frame rate.
+    #[must_use]
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "frame numbers stay far below the f64 mantissa"
+    )]
+    pub fn to_seconds(self, frame_rate: Option<f64>) -> Option<f64> {
+        match self {
+            Self::Seconds(seconds) => Some(seconds),
+            Self::Frames(frames) => Some(frames as f64 / usable_frame_rate(frame_rate)?),
+            Self::Timecode { seconds, frames } => {
+                let frame_rate = usable_frame_rate(frame_rate)?;
+                let nominal = frame_rate.round().max(1.0);
+                if frames as f64 >= nominal {
+                    return None;
+                }
+                Some((seconds as f64).mul_add(nominal, frames as f64) / frame_rate)
+            }
+        }
+    }
+}
+
+fn usable_frame_rate(frame_rate: Option<f64>) -> Option<f64> {
+    frame_rate.filter(|rate| rate.is_finite() && *rate > 0.0)
+}
+
+/// Parses a trim time in any supported form without converting frames.
+#[must_use]
+pub fn parse_time_input(value: &str) -> Option<TimeInput> {
+    let value = value.trim();
+    if let Some(frames) = value.strip_suffix(['f', 'F']) {
+        return parse_digits(frames).map(TimeInput::Frames);
+    }
+
+    let parts: Vec<&str> = value.split(':').collect();
+    if let [hours, minutes, seconds, frames] = parts[..] {
+        let minutes = parse_digits(minutes).filter(|minutes| *minutes < 60)?;
+        let seconds = parse_digits(seconds).filter(|seconds| *seconds < 60)?;
+        return Some(TimeInput::Timecode {
+            seconds: parse_digits(hours)? * 3600 + minutes * 60 + seconds,
+            frames: parse_digits(frames)?,
+        });
+    }
+
+    parse_time(value)
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(TimeInput::Seconds)
+}
+
+fn parse_digits(value: &str) -> Option<u64> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Parses a trim time in any supported form into seconds, converting frame
+/// counts and timecodes with `frame_rate`.
+#[must_use]
+pub fn parse_time_at_frame_rate(value: &str, frame_rate: Option<f64>) -> Option<f64> {
+    parse_time_input(value)?.to_seconds(frame_rate)
+}
+
+/// Reports whether a time reads as seconds only once the frame rate is
+/// known, so checks comparing it have to wait for the probe.
+#[must_use]
+pub fn is_frame_time(value: &str) -> bool {
+    parse_time_input(value).is_some_and(TimeInput::needs_frame_rate)
+}
+
+/// Rewrites every frame-based trim time of a task into seconds using the
+/// probed frame rate, and returns whether anything changed.
+///
+/// Converted times are rounded down to the millisecond so a cut never
+/// starts after the frame it names. Unparseable times are left for
+/// validation to report.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::InvalidInput`] when a frame-based time is used
+/// without a known frame rate, or when a timecode frame field reaches the
+/// frame rate.
+pub fn resolve_frame_times(
+    config: &mut ConversionConfig,
+    frame_rate: Option<f64>,
+) -> Result<bool, ConversionError> {
+    let mut changed = false;
+    if let Some(start) = config.start_time.as_mut() {
+        changed |= resolve_frame_time(start, "Start time", frame_rate)?;
+    }
+    if let Some(end) = config.end_time.as_mut() {
+        changed |= resolve_frame_time(end, "End time", frame_rate)?;
+    }
+    for (position, segment) in config.segments.iter_mut().enumerate() {
+        let number = position + 1;
+        changed |= resolve_frame_time(
+            &mut segment.start,
+            &format!("Start time in segment {number}"),
+            frame_rate,
+        )?;
+        changed |= resolve_frame_time(
+            &mut segment.end,
+            &format!("End time in segment {number}"),
+            frame_rate,
+        )?;
+    }
+    for (position, segment) in config.remove_segments.iter_mut().enumerate() {
+        let number = position + 1;
+        changed |= resolve_frame_time(
+            &mut segment.start,
+            &format!("Start time in removed segment {number}"),
+            frame_rate,
+        )?;
+        changed |= resolve_frame_time(
+            &mut segment.end,
+            &format!("End time in removed segment {number}"),
+            frame_rate,
+        )?;
+    }
+    Ok(changed)
+}
+
+fn resolve_frame_time(
+    value: &mut String,
+    label: &str,
+    frame_rate: Option<f64>,
+) -> Result<bool, ConversionError> {
+    let Some(input) = parse_time_input(value).filter(|input| input.needs_frame_rate()) else {
+        return Ok(false);
+    };
+    let Some(frame_rate) = usable_frame_rate(frame_rate) else {
+        return Err(ConversionError::InvalidInput(format!(
+            "{label} {} needs a known source frame rate",
+            value.trim()
+        )));
+    };
+    let seconds = input.to_seconds(Some(frame_rate)).ok_or_else(|| {
+        ConversionError::InvalidInput(format!(
+            "{label} {} has a frame field at or above the frame rate ({frame_rate:.3} fps)",
+            value.trim()
+        ))
+    })?;
+    let milliseconds = seconds.mul_add(1000.0, 1e-6).floor();
+    *value = format!("{:.3}", milliseconds / 1000.0);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TrimSegment, sample_config};
+
+    const NTSC_FILM: f64 = 24_000.0 / 1001.0;
+    const NTSC_VIDEO: f64 = 30_000.0 / 1001.0;
+
+    fn assert_seconds(value: &str, frame_rate: Option<f64>, expected: Option<f64>) {
+        let actual = parse_time_at_frame_rate(value, frame_rate);
+        match (actual, expected) {
+            (Some(actual), Some(expected)) => assert!(
+                (actual - expected).abs() < 1e-9,
+                "{value} at {frame_rate:?}: expected {expected}, got {actual}"
+            ),
+            _ => assert_eq!(actual, expected, "{value} at {frame_rate:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_time_input_reads_every_form() {
+        let cases = [
+            ("90", Some(TimeInput::Seconds(90.0))),
+            ("1:30.5", Some(TimeInput::Seconds(90.5))),
+            ("00:01:30.250", Some(TimeInput::Seconds(90.25))),
+            ("1234f", Some(TimeInput::Frames(1234))),
+            (" 48F ", Some(TimeInput::Frames(48))),
+            ("0f", Some(TimeInput::Frames(0))),
+            (
+                "00:00:41:16",
+                Some(TimeInput::Timecode {
+                    seconds: 41,
+                    frames: 16,
+                }),
+            ),
+            (
+                "01:02:03:04",
+                Some(TimeInput::Timecode {
+                    seconds: 3723,
+                    frames: 4,
+                }),
+            ),
+            ("f", None),
+            ("12.5f", None),
+            ("-3f", None),
+            ("00:00:41;16", None),
+            ("00:61:00:00", None),
+            ("00:00:75:00", None),
+            ("00:00:41:1.5", None),
+            ("00:00:00:00:00", None),
+            ("-5", None),
+            ("soon", None),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(parse_time_input(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn frame_counts_convert_with_the_frame_rate() {
+        let cases = [
+            ("0f", Some(25.0), Some(0.0)),
+            ("50f", Some(25.0), Some(2.0)),
+            ("1234f", Some(24.0), Some(1234.0 / 24.0)),
+            ("24f", Some(NTSC_FILM), Some(1.001)),
+            ("30f", Some(NTSC_VIDEO), Some(1.001)),
+            ("1234f", None, None),
+            ("1234f", Some(0.0), None),
+            ("1234f", Some(f64::NAN), None),
+        ];
+
+        for (value, frame_rate, expected) in cases {
+            assert_seconds(value, frame_rate, expected);
+        }
+    }
+
+    #[test]
+    fn timecodes_read_as_non_drop_frame() {
+        let cases = [
+            ("00:00:41:16", Some(24.0), Some(41.0 + 16.0 / 24.0)),
+            ("00:00:41:16", Some(25.0), Some(41.64)),
+            ("00:00:01:00", Some(NTSC_FILM), Some(1.001)),
+            ("00:00:00:23", Some(NTSC_FILM), Some(23.0 * 1.001 / 24.0)),
+            ("00:01:00:00", Some(NTSC_VIDEO), Some(60.06)),
+            ("00:00:00:29", Some(NTSC_VIDEO), Some(29.0 * 1.001 / 30.0)),
+            ("00:00:00:59", Some(60.0), Some(59.0 / 60.0)),
+            ("00:00:00:00", None, None),
+        ];
+
+        for (value, frame_rate, expected) in cases {
+            assert_seconds(value, frame_rate, expected);
+        }
+    }
+
+    #[test]
+    fn timecode_frame_fields_at_or_above_the_rate_are_rejected() {
+        let cases = [
+            ("00:00:41:24", Some(24.0)),
+            ("00:00:41:24", Some(NTSC_FILM)),
+            ("00:00:41:25", Some(25.0)),
+            ("00:00:41:30", Some(NTSC_VIDEO)),
+            ("00:00:41:99", Some(60.0)),
+        ];
+
+        for (value, frame_rate) in cases {
+            assert_seconds(value, frame_rate, None);
+        }
+    }
+
+    #[test]
+    fn clock_times_ignore_the_frame_rate() {
+        let cases = [
+            ("12.5", None, Some(12.5)),
+            ("12.5", Some(24.0), Some(12.5)),
+            ("00:00:41.666", Some(NTSC_FILM), Some(41.666)),
+        ];
+
+        for (value, frame_rate, expected) in cases {
+            assert_seconds(value, frame_rate, expected);
+        }
+        assert!(!is_frame_time("00:00:41.666"));
+        assert!(is_frame_time("00:00:41:16"));
+        assert!(is_frame_time("1234f"));
+        assert!(!is_frame_time("1234x"));
+    }
+
+    #[test]
+    fn resolve_frame_times_rewrites_every_trim_field_into_seconds() {
+        let mut config = ConversionConfig {
+            start_time: Some("48f".to_string()),
+            end_time: Some("00:00:10:12".to_string()),
+            segments: vec![TrimSegment {
+                start: "00:00:01.500".to_string(),
+                end: "1234f".to_string(),
+            }],
+            remove_segments: vec![TrimSegment {
+                start: "00:00:02:00".to_string(),
+                end: "00:00:03:00".to_string(),
+            }],
+            ..sample_config("mp4", "libx264")
+        };
+
+        assert!(resolve_frame_times(&mut config, Some(24.0)).expect("frames should resolve"));
+
+        assert_eq!(config.start_time.as_deref(), Some("2.000"));
+        assert_eq!(config.end_time.as_deref(), Some("10.500"));
+        assert_eq!(config.segments[0].start, "00:00:01.500");
+        assert_eq!(config.segments[0].end, "51.416");
+        assert_eq!(config.remove_segments[0].start, "2.000");
+        assert_eq!(config.remove_segments[0].end, "3.000");
+        assert!(!resolve_frame_times(&mut config, Some(24.0)).expect("seconds stay as they are"));
+    }
+
+    #[test]
+    fn resolve_frame_times_rounds_down_to_keep_the_named_frame() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("1000f".to_string());
+
+        resolve_frame_times(&mut config, Some(NTSC_FILM)).expect("frames should resolve");
+
+        let start: f64 = config.start_time.as_deref().unwrap().parse().unwrap();
+        assert!(start <= 1000.0 / NTSC_FILM);
+        assert!(1000.0 / NTSC_FILM - start < 0.001);
+    }
+
+    #[test]
+    fn resolve_frame_times_reports_missing_rates_and_bad_frame_fields() {
+        let mut config = sample_config("mp4", "libx264");
+        config.start_time = Some("1234f".to_string());
+        let error = resolve_frame_times(&mut config.clone(), None)
+            .expect_err("frames need a frame rate")
+            .to_string();
+        assert!(error.contains("Start time 1234f needs a known source frame rate"));
+
+        config.start_time = None;
+        config.end_time = Some("00:00:41:30".to_string());
+        let error = resolve_frame_times(&mut config, Some(NTSC_VIDEO))
+            .expect_err("frame field 30 is out of range at 29.97 fps")
+            .to_string();
+        assert!(error.contains("End time 00:00:41:30 has a frame field at or above"));
+    }
+}
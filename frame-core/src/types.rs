@@ -1,7 +1,12 @@
 //! Shared conversion, probing, and event payload types.
 
+use std::time::SystemTime;
+
 use serde::{Deserialize, Serialize};
 
+use crate::error::{ConversionFailureKind, ErrorDetail};
+use crate::verify::OutputVerification;
+
 pub const DEFAULT_MAX_CONCURRENCY: usize = 2;
 pub const VOLUME_EPSILON: f64 = 0.01;
 
@@ -218,7 +223,7 @@ impl Default for AudioFiltersConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AudioTrack {
     pub index: u32,
@@ -229,18 +234,40 @@ pub struct AudioTrack {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bitrate_kbps: Option<f64>,
     pub sample_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_layout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub is_forced: bool,
+}
+
+/// Whether a subtitle codec carries text cues or rendered bitmaps.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SubtitleFormat {
+    Text,
+    Bitmap,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SubtitleTrack {
     pub index: u32,
     pub codec: String,
     pub language: Option<String>,
     pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<SubtitleFormat>,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub is_forced: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ProbeMetadata {
     #[serde(default = "default_media_kind")]
@@ -256,8 +283,19 @@ pub struct ProbeMetadata {
     pub width: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
+    /// Display rotation of the video in degrees, 0..360, from its display
+    /// matrix. `width` and `height` are already reported rotated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub video_bitrate_kbps: Option<f64>,
+    /// Frame count stored in the container header, when it provides one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_count: Option<u64>,
+    /// Set for GIF, APNG and WebP sources with more than one frame, which
+    /// are converted as video rather than as a still.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub animated: bool,
     pub audio_tracks: Vec<AudioTrack>,
     pub subtitle_tracks: Vec<SubtitleTrack>,
     #[serde(default)]
@@ -267,6 +305,114 @@ pub struct ProbeMetadata {
     pub color_range: Option<String>,
     pub color_primaries: Option<String>,
     pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_level: Option<i32>,
+    /// Field order reported by the container, such as `progressive` or `tt`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_transfer: Option<String>,
+    /// Bits per color component of the primary video stream.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit_depth: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mastering_display: Option<MasteringDisplayMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_light_level: Option<ContentLightLevel>,
+    /// Embedded cover image, kept apart from `video_codec` so audio files with
+    /// artwork are still classified as audio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_art: Option<CoverArtInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_streams: Vec<DataStream>,
+    /// Non-fatal problems found while parsing, such as skipped chapters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl ProbeMetadata {
+    /// Container duration in seconds, when `FFprobe` reported a positive one.
+    #[must_use]
+    pub fn duration_seconds(&self) -> Option<f64> {
+        self.duration
+            .as_deref()
+            .and_then(|duration| duration.trim().parse::<f64>().ok())
+            .filter(|duration| *duration > 0.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverArtInfo {
+    pub index: u32,
+    pub codec: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    pub id: i64,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub title: Option<String>,
+}
+
+/// A file embedded in the container, such as a font attached to an MKV.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub index: u32,
+    pub filename: Option<String>,
+    pub mimetype: Option<String>,
+    pub size_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DataStream {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub label: Option<String>,
+}
+
+/// SMPTE ST 2086 mastering display color volume. Chromaticities are CIE 1931
+/// xy coordinates and luminance values are in cd/m².
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MasteringDisplayMetadata {
+    pub red_x: Option<f64>,
+    pub red_y: Option<f64>,
+    pub green_x: Option<f64>,
+    pub green_y: Option<f64>,
+    pub blue_x: Option<f64>,
+    pub blue_y: Option<f64>,
+    pub white_point_x: Option<f64>,
+    pub white_point_y: Option<f64>,
+    pub min_luminance: Option<f64>,
+    pub max_luminance: Option<f64>,
+}
+
+/// CTA-861.3 content light level (`MaxCLL` / `MaxFALL`) in cd/m².
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentLightLevel {
+    pub max_content: u32,
+    pub max_average: u32,
+}
+
+/// One range of the source kept in the output. Times use the formats
+/// accepted for `start_time` and `end_time`.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimSegment {
+    pub start: String,
+    pub end: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -315,12 +461,24 @@ pub struct ConversionConfig {
     pub custom_height: Option<String>,
     pub scaling_algorithm: String,
     pub fps: String,
+    /// Rate image sequence frames are read at, as a number or a fraction
+    /// such as `24000/1001`. Other inputs ignore it.
+    #[serde(default)]
+    pub input_framerate: Option<String>,
     pub crf: u8,
     #[serde(default = "default_quality")]
     pub quality: u32,
     pub preset: String,
     pub start_time: Option<String>,
     pub end_time: Option<String>,
+    /// Ranges kept and joined in order. When set they replace `start_time`
+    /// and `end_time`; more than one is cut with a trim and concat graph.
+    #[serde(default)]
+    pub segments: Vec<TrimSegment>,
+    /// Ranges cut out of the source; the worker turns them into `segments`
+    /// once the source duration is probed.
+    #[serde(default)]
+    pub remove_segments: Vec<TrimSegment>,
     #[serde(default)]
     pub metadata: MetadataConfig,
     #[serde(default = "default_rotation")]
@@ -341,6 +499,10 @@ pub struct ConversionConfig {
     pub videotoolbox_allow_sw: bool,
     #[serde(default = "default_hw_decode")]
     pub hw_decode: bool,
+    /// Device the hardware encoder binds to: a CUDA or DXGI index, or a
+    /// VAAPI render node path. `None` lets `FFmpeg` pick.
+    #[serde(default)]
+    pub hw_device: Option<String>,
     #[serde(default = "default_pixel_format")]
     pub pixel_format: String,
     #[serde(default = "default_image_jpeg_quality")]
@@ -361,12 +523,51 @@ pub struct ConversionConfig {
     pub image_png_prediction: String,
     #[serde(default = "default_image_tiff_compression")]
     pub image_tiff_compression: String,
+    /// CRF of AVIF stills, from 0 (lossless) to 63.
+    #[serde(default = "default_image_avif_crf")]
+    pub image_avif_crf: u32,
+    /// Largest size in kilobytes of a JPEG or lossy WebP output. The quality
+    /// is lowered until the output fits or reaches the quality floor.
+    #[serde(default)]
+    pub image_target_max_kb: Option<u64>,
     #[serde(default = "default_gif_colors")]
     pub gif_colors: u16,
     #[serde(default = "default_gif_dither")]
     pub gif_dither: String,
     #[serde(default = "default_gif_loop")]
     pub gif_loop: u16,
+    #[serde(default)]
+    pub overwrite_policy: OverwritePolicy,
+    /// Cuts the output into numbered parts instead of one file.
+    #[serde(default)]
+    pub split: Option<OutputSplit>,
+    /// Writes one file per source chapter, named after the chapter.
+    #[serde(default)]
+    pub split_by_chapters: bool,
+    /// Writes every frame of an image output as a numbered file in a
+    /// folder named after the output, instead of a single still.
+    #[serde(default)]
+    pub image_sequence_output: bool,
+    /// Makes stream-copy trims frame accurate by re-encoding the partial
+    /// GOPs at the cut points.
+    #[serde(default)]
+    pub smart_cut: bool,
+    #[serde(default)]
+    pub seek_mode: SeekMode,
+    /// Splits the video into this many chunks at source keyframes and
+    /// encodes them concurrently; audio is encoded once in its own pass.
+    #[serde(default)]
+    pub parallel_chunks: Option<u32>,
+    /// Decodes the finished output in full and checks its duration before
+    /// the task counts as completed.
+    #[serde(default)]
+    pub verify_output: bool,
+    /// Arguments added by hand just before the source `-i`.
+    #[serde(default)]
+    pub extra_input_args: Vec<String>,
+    /// Arguments added by hand just before the output path.
+    #[serde(default)]
+    pub extra_output_args: Vec<String>,
 }
 
 fn default_rotation() -> String {
@@ -425,6 +626,10 @@ fn default_image_webp_preset() -> String {
     "default".to_string()
 }
 
+const fn default_image_avif_crf() -> u32 {
+    30
+}
+
 const fn default_image_png_compression() -> u32 {
     9
 }
@@ -489,6 +694,67 @@ pub struct MetadataConfig {
     pub comment: Option<String>,
 }
 
+/// Decides what happens when a task's output path already exists at the moment
+/// its worker starts.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OverwritePolicy {
+    /// Replace the existing file.
+    Overwrite,
+    /// Pick the next free numbered output name.
+    #[default]
+    Rename,
+    /// Refuse to start and report [`crate::error::ConversionError::OutputExists`].
+    Fail,
+}
+
+/// Where the start of a single trim range is sought, relative to `-i`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SeekMode {
+    /// Seek the input before decoding: fast, but burned subtitles lose
+    /// their timing.
+    #[default]
+    Fast,
+    /// Decode from the start and drop frames up to the target.
+    Accurate,
+    /// Seek the input a few seconds short of the target, then decode the
+    /// rest of the way.
+    Hybrid,
+}
+
+/// How a task's output is cut into numbered parts.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum OutputSplit {
+    /// Parts of at most `seconds` of output each.
+    Duration { seconds: f64 },
+    /// Parts expected to stay under `bytes`, sized from the target bitrate.
+    Size { bytes: u64 },
+}
+
+/// What happens to a task's source file once its output has been verified.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PostAction {
+    /// Leave the source in place.
+    #[default]
+    Keep,
+    /// Move the source to the platform's trash or recycle bin.
+    Trash,
+    /// Remove the source permanently.
+    Delete,
+}
+
+/// Result of a task's [`PostAction`], reported with its completion.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PostActionOutcome {
+    pub action: PostAction,
+    /// Why the action was skipped or failed; `None` when it succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum MetadataMode {
@@ -498,15 +764,57 @@ pub enum MetadataMode {
     Replace,
 }
 
+/// Stage of a task that a progress or log event belongs to.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConversionPhase {
+    /// A decode-only pass that inspects the source, such as loudness or
+    /// scene detection.
+    Analyze,
+    /// The `FFmpeg` run that writes the output.
+    Encode,
+    /// A full decode of the finished output that checks it for errors.
+    Verify,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct ProgressPayload {
     pub id: String,
     pub progress: f64,
+    /// The source's length is unknown, so `progress` stays at 0 and only
+    /// the time and bytes processed are reported.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub indeterminate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<ConversionPhase>,
+    #[serde(flatten)]
+    pub stats: ProgressStats,
+}
+
+/// Encoder throughput reported with progress; unknown values are omitted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    /// Encoding speed as a multiple of realtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_x: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate_kbps: Option<f64>,
+    /// Output timestamp reached so far.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processed_seconds: Option<f64>,
+    /// Bytes of output written so far.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written_bytes: Option<u64>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct StartedPayload {
     pub id: String,
+    /// Output path chosen once the overwrite policy was applied.
+    pub output_path: String,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -514,28 +822,151 @@ pub struct CancelledPayload {
     pub id: String,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct CompletedPayload {
     pub id: String,
     pub output_path: String,
+    /// Every file the task produced, in order; more than `output_path` only
+    /// when the output was split into parts.
+    pub outputs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_action: Option<PostActionOutcome>,
+    #[serde(flatten)]
+    pub summary: OutputSummary,
+}
+
+/// What a finished task produced; fields that could not be determined are
+/// omitted.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_size_bytes: Option<u64>,
+    /// Output duration in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub audio_codecs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Wall-clock time the task took, probing included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_seconds: Option<f64>,
+    /// Frames written by an image sequence output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_count: Option<u64>,
+    /// Quality a JPEG or lossy WebP output was encoded at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_quality: Option<u32>,
+    /// Result of the output check, when the task asked for one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<OutputVerification>,
+    /// Distinct warnings reported while converting.
+    pub warning_count: usize,
+}
+
+impl OutputSummary {
+    /// Copies the media facts of a probe of the output.
+    pub fn apply_probe(&mut self, probe: &ProbeMetadata) {
+        self.duration = probe
+            .duration
+            .as_deref()
+            .and_then(|duration| duration.trim().parse::<f64>().ok())
+            .filter(|duration| duration.is_finite() && *duration >= 0.0);
+        self.video_codec.clone_from(&probe.video_codec);
+        self.audio_codecs = probe
+            .audio_tracks
+            .iter()
+            .map(|track| track.codec.clone())
+            .collect();
+        self.width = probe.width;
+        self.height = probe.height;
+    }
+
+    /// Output size minus input size, in bytes.
+    #[must_use]
+    pub fn size_delta_bytes(&self) -> Option<i128> {
+        Some(i128::from(self.output_size_bytes?) - i128::from(self.input_size_bytes?))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct ErrorPayload {
     pub id: String,
     pub error: String,
+    pub code: ConversionFailureKind,
+    /// Whether retrying the task may succeed; see
+    /// [`ConversionFailureKind::is_retryable`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub retryable: bool,
+    /// Structured form of `error`, for branching on the error kind.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ErrorDetail>,
+    /// Trailing `FFmpeg` output captured before the failure.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub log_tail: Vec<String>,
+    /// On-disk log of the task, when one was written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<String>,
+}
+
+/// How serious a classified `FFmpeg` warning is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConversionWarningSeverity {
+    /// Timestamp or layout fix-ups that usually leave the output usable.
+    Warning,
+    /// Damaged input the decoder had to skip or conceal.
+    Error,
+}
+
+/// A notable `FFmpeg` warning; repeats of the same warning are reported again
+/// with a higher `count` instead of as new warnings.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct WarningPayload {
+    pub id: String,
+    pub severity: ConversionWarningSeverity,
+    /// First line that raised the warning.
+    pub message: String,
+    pub count: u32,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct LogPayload {
     pub id: String,
     pub line: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<ConversionPhase>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct LogBatchPayload {
+    pub id: String,
+    pub lines: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<ConversionPhase>,
 }
 
 #[derive(Deserialize)]
 pub struct FfprobeOutput {
     pub streams: Vec<FfprobeStream>,
     pub format: FfprobeFormat,
+    #[serde(default)]
+    pub chapters: Vec<FfprobeChapter>,
+}
+
+#[derive(Deserialize)]
+pub struct FfprobeChapter {
+    pub id: Option<i64>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub tags: Option<FfprobeTags>,
 }
 
 #[derive(Deserialize)]
@@ -548,22 +979,52 @@ pub struct FfprobeStream {
     pub channels: Option<i32>,
     pub bit_rate: Option<String>,
     pub avg_frame_rate: Option<String>,
-    #[allow(dead_code)]
     pub channel_layout: Option<String>,
     pub tags: Option<FfprobeTags>,
     pub pix_fmt: Option<String>,
     pub color_space: Option<String>,
     pub color_range: Option<String>,
     pub color_primaries: Option<String>,
+    pub color_transfer: Option<String>,
+    pub bits_per_raw_sample: Option<String>,
     pub profile: Option<String>,
+    pub level: Option<i32>,
+    pub field_order: Option<String>,
+    pub nb_frames: Option<String>,
+    pub extradata_size: Option<u64>,
     pub sample_rate: Option<String>,
     #[serde(default)]
+    pub disposition: FfprobeDisposition,
+    #[serde(default)]
     pub side_data_list: Vec<FfprobeSideData>,
 }
 
+#[derive(Deserialize, Default)]
+pub struct FfprobeDisposition {
+    #[serde(default)]
+    pub default: u8,
+    #[serde(default)]
+    pub forced: u8,
+    #[serde(default)]
+    pub attached_pic: u8,
+}
+
 #[derive(Deserialize)]
 pub struct FfprobeSideData {
+    pub side_data_type: Option<String>,
     pub rotation: Option<f64>,
+    pub red_x: Option<String>,
+    pub red_y: Option<String>,
+    pub green_x: Option<String>,
+    pub green_y: Option<String>,
+    pub blue_x: Option<String>,
+    pub blue_y: Option<String>,
+    pub white_point_x: Option<String>,
+    pub white_point_y: Option<String>,
+    pub min_luminance: Option<String>,
+    pub max_luminance: Option<String>,
+    pub max_content: Option<u32>,
+    pub max_average: Option<u32>,
 }
 
 #[derive(Deserialize)]
@@ -574,7 +1035,7 @@ pub struct FfprobeFormat {
     pub tags: Option<FfprobeTags>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
 pub struct FfprobeTags {
     #[serde(alias = "TITLE")]
     pub title: Option<String>,
@@ -593,6 +1054,10 @@ pub struct FfprobeTags {
     pub comment: Option<String>,
     #[serde(rename = "DESCRIPTION")]
     pub description_upper: Option<String>,
+    #[serde(alias = "FILENAME", skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(alias = "MIMETYPE", skip_serializing_if = "Option::is_none")]
+    pub mimetype: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -601,7 +1066,70 @@ pub struct ConversionTask {
     pub file_path: String,
     pub output_directory: String,
     pub output_name: Option<String>,
+    /// Creates `output_directory` when it is missing instead of failing.
+    pub create_output_directory: bool,
+    /// Source folder relative to a scanned root, recreated under
+    /// `output_directory`.
+    pub relative_subpath: Option<String>,
+    /// Directory for large intermediate files; `None` uses the OS temporary
+    /// directory. See [`crate::scratch::scratch_directory`].
+    pub scratch_directory: Option<String>,
+    /// Filename template expanded once the source is probed; replaces
+    /// `output_name` when set.
+    pub output_template: Option<String>,
+    /// Copies the source's timestamps, and on Unix its permission bits, to
+    /// the finished output.
+    pub preserve_file_times: bool,
+    /// Applied to the source after the output is verified.
+    pub post_action: PostAction,
     pub config: ConversionConfig,
+    /// Further sources joined after `file_path`, in order, into the one
+    /// output. See [`crate::concat`].
+    pub concat_inputs: Vec<String>,
+    /// Task that must complete successfully before this one starts.
+    pub depends_on: Option<String>,
+    /// Source metadata probed before the task was queued; used instead of
+    /// probing again while the source keeps its fingerprint.
+    pub preprobed: Option<PreprobedSource>,
+}
+
+/// Size and modification time of a source file, which tell whether metadata
+/// probed earlier still describes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceFingerprint {
+    pub size_bytes: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Metadata of a source probed ahead of its conversion, with the
+/// fingerprint the file had when it was probed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreprobedSource {
+    pub metadata: ProbeMetadata,
+    pub fingerprint: SourceFingerprint,
+}
+
+/// Settings as saved by the app, with every field the tests do not set left
+/// to its default. Shared by the test modules of the crate.
+#[cfg(test)]
+pub(crate) fn sample_config(container: &str, video_codec: &str) -> ConversionConfig {
+    serde_json::from_value(serde_json::json!({
+        "container": container,
+        "videoCodec": video_codec,
+        "videoBitrateMode": "crf",
+        "videoBitrate": "5000",
+        "audioCodec": "aac",
+        "audioBitrate": "128",
+        "audioChannels": "original",
+        "selectedAudioTracks": [],
+        "selectedSubtitleTracks": [],
+        "resolution": "original",
+        "scalingAlgorithm": "bicubic",
+        "fps": "original",
+        "crf": 23,
+        "preset": "medium"
+    }))
+    .expect("config fixture should deserialize")
 }
 
 #[cfg(test)]
@@ -658,6 +1186,7 @@ mod tests {
         assert_eq!(config.gif_dither, "sierra2_4a");
         assert_eq!(config.gif_loop, 0);
         assert_eq!(config.metadata.mode, MetadataMode::Preserve);
+        assert_eq!(config.overwrite_policy, OverwritePolicy::Rename);
     }
 
     #[test]
@@ -671,6 +1200,7 @@ mod tests {
         assert_eq!(serialized["imageWebpPreset"], "default");
         assert_eq!(serialized["imagePngPrediction"], "paeth");
         assert_eq!(serialized["metadata"]["mode"], "preserve");
+        assert_eq!(serialized["overwritePolicy"], "rename");
         assert!(serialized.get("processing_mode").is_none());
     }
 
@@ -698,4 +1228,42 @@ mod tests {
         assert_eq!(tags.artist.as_deref(), Some("Frame"));
         assert_eq!(tags.description_upper.as_deref(), Some("Demo"));
     }
+
+    #[test]
+    fn output_summary_reads_probe_and_serializes_known_fields() {
+        let probe = ProbeMetadata {
+            duration: Some("12.5".to_string()),
+            video_codec: Some("hevc".to_string()),
+            width: Some(1920),
+            height: Some(1080),
+            audio_tracks: vec![AudioTrack {
+                codec: "aac".to_string(),
+                ..AudioTrack::default()
+            }],
+            ..ProbeMetadata::default()
+        };
+        let mut summary = OutputSummary {
+            output_size_bytes: Some(400),
+            input_size_bytes: Some(1000),
+            ..OutputSummary::default()
+        };
+
+        summary.apply_probe(&probe);
+
+        assert_eq!(summary.size_delta_bytes(), Some(-600));
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            json!({
+                "outputSizeBytes": 400,
+                "inputSizeBytes": 1000,
+                "duration": 12.5,
+                "videoCodec": "hevc",
+                "audioCodecs": ["aac"],
+                "width": 1920,
+                "height": 1080,
+                "warningCount": 0
+            })
+        );
+        assert_eq!(OutputSummary::default().size_delta_bytes(), None);
+    }
 }
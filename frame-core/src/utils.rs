@@ -1,5 +1,6 @@
 use crate::media_rules;
 use regex::Regex;
+use std::borrow::Cow;
 use std::path::Path;
 use std::sync::LazyLock;
 
@@ -92,6 +93,64 @@ pub fn map_svt_av1_preset(preset: &str) -> String {
     }
 }
 
+/// Infers bits per component from an `FFmpeg` pixel format name such as
+/// `yuv420p10le` or `p010le`; formats without a depth suffix are 8-bit.
+#[must_use]
+pub fn pixel_format_bit_depth(pixel_format: &str) -> Option<u32> {
+    let name = pixel_format.trim().to_ascii_lowercase();
+    if name.is_empty() || name == "auto" {
+        return None;
+    }
+
+    let name = name
+        .strip_suffix("le")
+        .or_else(|| name.strip_suffix("be"))
+        .unwrap_or(&name);
+    let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let digits = &name[prefix.len()..];
+    if !digits.is_empty() && (prefix.ends_with('p') || prefix.ends_with("gray")) {
+        return digits.parse().ok();
+    }
+
+    Some(8)
+}
+
+#[must_use]
+pub fn is_text_subtitle_codec(codec: &str) -> bool {
+    matches!(
+        codec.trim().to_ascii_lowercase().as_str(),
+        "text"
+            | "ssa"
+            | "mov_text"
+            | "srt"
+            | "microdvd"
+            | "eia_608"
+            | "jacosub"
+            | "sami"
+            | "realtext"
+            | "stl"
+            | "subviewer1"
+            | "subviewer"
+            | "subrip"
+            | "webvtt"
+            | "mpl2"
+            | "vplayer"
+            | "pjs"
+            | "ass"
+            | "hdmv_text_subtitle"
+            | "ttml"
+    )
+}
+
+/// Matches subtitle codecs stored as rendered images rather than text cues.
+#[must_use]
+pub fn is_bitmap_subtitle_codec(codec: &str) -> bool {
+    matches!(
+        codec.trim().to_ascii_lowercase().as_str(),
+        "hdmv_pgs_subtitle" | "dvd_subtitle" | "dvb_subtitle" | "xsub"
+    )
+}
+
 #[must_use]
 pub fn parse_time(time_str: &str) -> Option<f64> {
     let parts: Vec<&str> = time_str.split(':').collect();
@@ -112,19 +171,49 @@ pub fn parse_time(time_str: &str) -> Option<f64> {
     }
 }
 
+/// Formats seconds as `hh:mm:ss.mmm`, the canonical form [`parse_time`]
+/// reads back.
 #[must_use]
-pub fn get_hwaccel_args(video_codec: &str) -> Vec<String> {
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "times are clamped to non-negative values well below u64::MAX"
+)]
+pub fn format_time(seconds: f64) -> String {
+    let milliseconds = (seconds.max(0.0) * 1000.0).round() as u64;
+    let hours = milliseconds / 3_600_000;
+    let minutes = milliseconds / 60_000 % 60;
+    let seconds = milliseconds / 1000 % 60;
+    format!(
+        "{hours:02}:{minutes:02}:{seconds:02}.{:03}",
+        milliseconds % 1000
+    )
+}
+
+/// `-hwaccel` method hardware decoding uses for outputs encoded with
+/// `video_codec`, or `None` when the encoder has no paired decoder.
+#[must_use]
+pub fn hwaccel_method(video_codec: &str) -> Option<&'static str> {
     if is_nvenc_codec(video_codec) {
-        vec![
+        Some("cuda")
+    } else if is_videotoolbox_codec(video_codec) {
+        Some("videotoolbox")
+    } else {
+        None
+    }
+}
+
+#[must_use]
+pub fn get_hwaccel_args(video_codec: &str) -> Vec<String> {
+    match hwaccel_method(video_codec) {
+        Some("cuda") => vec![
             "-hwaccel".to_string(),
             "cuda".to_string(),
             "-hwaccel_output_format".to_string(),
             "cuda".to_string(),
-        ]
-    } else if is_videotoolbox_codec(video_codec) {
-        vec!["-hwaccel".to_string(), "videotoolbox".to_string()]
-    } else {
-        vec![]
+        ],
+        Some(method) => vec!["-hwaccel".to_string(), method.to_string()],
+        None => vec![],
     }
 }
 
@@ -147,6 +236,39 @@ pub fn sanitize_external_tool_path(path: &Path) -> String {
     }
 }
 
+/// Length, in UTF-16 units including the terminator, of Windows' `MAX_PATH`.
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Converts an absolute Windows path that exceeds [`WINDOWS_MAX_PATH`] to its
+/// extended-length `\\?\` form so external tools can still open it.
+///
+/// Short paths, relative paths, and paths with `.` or `..` segments, which
+/// the extended form does not resolve, are returned unchanged.
+#[must_use]
+pub fn extended_length_path(path: &str) -> Cow<'_, str> {
+    if path.encode_utf16().count() < WINDOWS_MAX_PATH || path.starts_with(r"\\?\") {
+        return Cow::Borrowed(path);
+    }
+
+    let normalized = path.replace('/', r"\");
+    if normalized
+        .split('\\')
+        .skip(1)
+        .any(|segment| segment == "." || segment == "..")
+    {
+        return Cow::Borrowed(path);
+    }
+    if let Some(share) = normalized.strip_prefix(r"\\") {
+        return Cow::Owned(format!(r"\\?\UNC\{share}"));
+    }
+    let bytes = normalized.as_bytes();
+    if bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return Cow::Owned(format!(r"\\?\{normalized}"));
+    }
+
+    Cow::Borrowed(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,4 +289,73 @@ mod tests {
     fn map_svt_av1_preset_falls_back_to_medium_speed() {
         assert_eq!(map_svt_av1_preset("unknown"), "8");
     }
+
+    #[test]
+    fn format_time_round_trips_through_parse_time() {
+        let cases = [
+            (0.0, "00:00:00.000"),
+            (2.5, "00:00:02.500"),
+            (90.0, "00:01:30.000"),
+            (2832.48, "00:47:12.480"),
+            (5400.0, "01:30:00.000"),
+            (41.6666, "00:00:41.667"),
+            (-1.0, "00:00:00.000"),
+        ];
+
+        for (seconds, expected) in cases {
+            assert_eq!(format_time(seconds), expected);
+            let parsed = parse_time(expected).expect("formatted times should parse");
+            assert!((parsed - seconds.max(0.0)).abs() < 0.0005);
+        }
+    }
+
+    #[test]
+    fn pixel_format_bit_depth_reads_depth_suffixes() {
+        assert_eq!(pixel_format_bit_depth("yuv420p"), Some(8));
+        assert_eq!(pixel_format_bit_depth("nv12"), Some(8));
+        assert_eq!(pixel_format_bit_depth("yuv420p10le"), Some(10));
+        assert_eq!(pixel_format_bit_depth("p010le"), Some(10));
+        assert_eq!(pixel_format_bit_depth("gray12be"), Some(12));
+        assert_eq!(pixel_format_bit_depth("auto"), None);
+    }
+
+    #[test]
+    fn extended_length_path_prefixes_only_long_absolute_paths() {
+        let deep = "nested folder\\".repeat(20);
+        let long_drive = format!(r"C:\Users\frame\{deep}clip.mp4");
+        let long_unc = format!(r"\\nas\media\{deep}clip.mp4");
+        let long_forward = long_drive.replace('\\', "/");
+        let long_relative = format!(r"{deep}clip.mp4");
+        let long_dotted = format!(r"C:\Users\..\{deep}clip.mp4");
+        let already_extended = format!(r"\\?\{long_drive}");
+
+        assert_eq!(
+            extended_length_path(r"C:\Videos\clip.mp4"),
+            r"C:\Videos\clip.mp4"
+        );
+        assert_eq!(
+            extended_length_path(&long_drive),
+            format!(r"\\?\{long_drive}")
+        );
+        assert_eq!(
+            extended_length_path(&long_unc),
+            format!(r"\\?\UNC\nas\media\{deep}clip.mp4")
+        );
+        assert_eq!(
+            extended_length_path(&long_forward),
+            format!(r"\\?\{long_drive}")
+        );
+        assert_eq!(extended_length_path(&long_relative), long_relative);
+        assert_eq!(extended_length_path(&long_dotted), long_dotted);
+        assert_eq!(extended_length_path(&already_extended), already_extended);
+    }
+
+    #[test]
+    fn extended_length_path_measures_utf16_units() {
+        let name = "\u{1F3AC}".repeat(64);
+        let path = format!(r"C:\{name}\{name}.mp4");
+
+        assert!(path.chars().count() < WINDOWS_MAX_PATH);
+        assert!(matches!(extended_length_path(&path), Cow::Owned(_)));
+    }
 }
@@ -0,0 +1,264 @@
+//! Checks a finished output by decoding it in full and comparing its length
+//! with the length the task was expected to produce.
+//!
+//! The decode runs at the `error` log level, so every line it prints other
+//! than the `-progress` block is a decode error.
+
+use serde::Serialize;
+
+use crate::error::{ConversionFailure, ConversionFailureKind};
+use crate::media_rules::{is_gif_container, is_image_container};
+use crate::segments::kept_duration_seconds;
+use crate::types::ConversionConfig;
+
+/// Suffix added to an output that failed verification; the file is kept
+/// under that name for inspection.
+pub const SUSPECT_OUTPUT_SUFFIX: &str = ".suspect";
+/// Smallest duration difference, in seconds, that fails verification.
+pub const VERIFY_DURATION_TOLERANCE_SECONDS: f64 = 1.0;
+/// Duration difference, as a share of the expected duration, that fails
+/// verification when it exceeds [`VERIFY_DURATION_TOLERANCE_SECONDS`].
+pub const VERIFY_DURATION_TOLERANCE_RATIO: f64 = 0.01;
+/// Decode errors kept verbatim in a verification result.
+pub const VERIFY_ERROR_SAMPLE_LINES: usize = 5;
+
+/// Builds a full decode of `output_path` that prints only errors, with
+/// `-progress` blocks on stderr for tracking.
+#[must_use]
+pub fn output_verification_args(output_path: &str) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-nostdin".to_string(),
+        "-v".to_string(),
+        "error".to_string(),
+        "-progress".to_string(),
+        "pipe:2".to_string(),
+        "-nostats".to_string(),
+        "-i".to_string(),
+        output_path.to_string(),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Reports whether a line of a verification pass is a decode error rather
+/// than a `key=value` progress line.
+#[must_use]
+pub fn is_decode_error_line(line: &str) -> bool {
+    let line = line.trim();
+    if line.is_empty() {
+        return false;
+    }
+    !line.split_once('=').is_some_and(|(key, _)| {
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || character == '_')
+    })
+}
+
+/// Length the output of `config` should have: the kept part of the source
+/// for video, audio and GIF outputs, and none for stills.
+#[must_use]
+pub fn expected_output_duration(
+    config: &ConversionConfig,
+    source_duration: Option<f64>,
+) -> Option<f64> {
+    if is_image_container(&config.container) && !is_gif_container(&config.container) {
+        return None;
+    }
+    kept_duration_seconds(config, source_duration)
+}
+
+/// Largest difference between the output and expected durations that still
+/// passes verification.
+#[must_use]
+pub fn duration_tolerance_seconds(expected_seconds: f64) -> f64 {
+    (expected_seconds * VERIFY_DURATION_TOLERANCE_RATIO).max(VERIFY_DURATION_TOLERANCE_SECONDS)
+}
+
+/// Path an output that failed verification is kept at.
+#[must_use]
+pub fn suspect_output_path(output_path: &str) -> String {
+    format!("{output_path}{SUSPECT_OUTPUT_SUFFIX}")
+}
+
+/// Result of verifying a task's output, reported with its completion.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputVerification {
+    pub decode_errors: u64,
+    /// The first decode errors printed, up to [`VERIFY_ERROR_SAMPLE_LINES`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub error_samples: Vec<String>,
+    /// Output length the task was expected to produce, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_duration: Option<f64>,
+    /// Probed output length, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_duration: Option<f64>,
+    /// Output minus expected duration, in seconds, when both are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_delta: Option<f64>,
+}
+
+impl OutputVerification {
+    /// Counts one decode error, keeping its text while samples are short.
+    pub fn record_error(&mut self, line: &str) {
+        self.decode_errors += 1;
+        if self.error_samples.len() < VERIFY_ERROR_SAMPLE_LINES {
+            self.error_samples.push(line.trim().to_string());
+        }
+    }
+
+    /// Records the durations compared; the delta is left unset unless both
+    /// are known.
+    pub fn set_durations(&mut self, expected: Option<f64>, output: Option<f64>) {
+        self.expected_duration = expected.filter(|duration| *duration > 0.0);
+        self.output_duration = output;
+        self.duration_delta = self
+            .expected_duration
+            .zip(output)
+            .map(|(expected, output)| output - expected);
+    }
+
+    /// Whether the output length is further from the expected length than
+    /// [`duration_tolerance_seconds`] allows.
+    #[must_use]
+    pub fn duration_mismatch(&self) -> bool {
+        self.expected_duration
+            .zip(self.duration_delta)
+            .is_some_and(|(expected, delta)| delta.abs() > duration_tolerance_seconds(expected))
+    }
+
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.decode_errors == 0 && !self.duration_mismatch()
+    }
+
+    /// One-line account of the check for the task log.
+    #[must_use]
+    pub fn summary_line(&self) -> String {
+        let errors = match self.decode_errors {
+            0 => "no decode errors".to_string(),
+            1 => "1 decode error".to_string(),
+            count => format!("{count} decode errors"),
+        };
+        let duration = match (self.output_duration, self.expected_duration) {
+            (Some(output), Some(expected)) => {
+                format!("duration {output:.2}s, expected {expected:.2}s")
+            }
+            (Some(output), None) => format!("duration {output:.2}s, none expected"),
+            (None, _) => "duration unknown".to_string(),
+        };
+        format!("{errors}, {duration}")
+    }
+
+    /// Failure reported for an output that did not pass, naming the paths
+    /// it was kept at.
+    #[must_use]
+    pub fn failure(&self, suspect_paths: &[String]) -> ConversionFailure {
+        let mut details = vec![self.summary_line()];
+        details.extend(self.error_samples.iter().cloned());
+        details.extend(
+            suspect_paths
+                .iter()
+                .map(|path| format!("the output was kept as {path}")),
+        );
+        ConversionFailure {
+            kind: ConversionFailureKind::VerificationFailed,
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::sample_config;
+
+    fn trimmed_config(container: &str) -> ConversionConfig {
+        let mut config = sample_config(container, "libx264");
+        config.start_time = Some("10".to_string());
+        config.end_time = Some("25".to_string());
+        config
+    }
+
+    #[test]
+    fn verification_decodes_the_output_at_the_error_log_level() {
+        let args = output_verification_args("/out/clip.mp4");
+
+        assert_eq!(
+            args.join(" "),
+            "-hide_banner -nostdin -v error -progress pipe:2 -nostats -i /out/clip.mp4 -f null -"
+        );
+    }
+
+    #[test]
+    fn stills_have_no_expected_duration() {
+        let duration = |container| expected_output_duration(&trimmed_config(container), Some(60.0));
+
+        assert_eq!(duration("mp4"), Some(15.0));
+        assert_eq!(duration("gif"), Some(15.0));
+        assert_eq!(duration("png"), None);
+    }
+
+    #[test]
+    fn progress_lines_are_not_counted_as_decode_errors() {
+        let mut verification = OutputVerification::default();
+        for line in [
+            "frame=240",
+            "out_time_us=10000000",
+            "progress=continue",
+            "[h264 @ 0x55d4] error while decoding MB 12 30, bytestream -7",
+            "[aac @ 0x55d5] Input buffer exhausted before END element found",
+            "",
+        ] {
+            if is_decode_error_line(line) {
+                verification.record_error(line);
+            }
+        }
+
+        assert_eq!(verification.decode_errors, 2);
+        assert!(verification.error_samples[0].starts_with("[h264 @ 0x55d4]"));
+        assert!(!verification.passed());
+    }
+
+    #[test]
+    fn duration_tolerance_grows_with_long_outputs() {
+        let mut short = OutputVerification::default();
+        short.set_durations(Some(30.0), Some(29.2));
+        let mut truncated = OutputVerification::default();
+        truncated.set_durations(Some(600.0), Some(570.0));
+        let mut long = OutputVerification::default();
+        long.set_durations(Some(3600.0), Some(3590.0));
+        let mut unknown = OutputVerification::default();
+        unknown.set_durations(None, Some(12.0));
+
+        assert!(short.passed());
+        assert!(truncated.duration_mismatch());
+        assert!(long.passed());
+        assert!(unknown.passed());
+        assert_eq!(unknown.duration_delta, None);
+    }
+
+    #[test]
+    fn failures_list_the_check_errors_and_suspect_paths() {
+        let mut verification = OutputVerification::default();
+        verification.record_error("[mov,mp4 @ 0x1] moov atom not found");
+        verification.set_durations(Some(20.0), Some(4.5));
+
+        let failure = verification.failure(&[suspect_output_path("/out/clip.mp4")]);
+
+        assert_eq!(failure.kind, ConversionFailureKind::VerificationFailed);
+        assert_eq!(
+            failure.details,
+            [
+                "1 decode error, duration 4.50s, expected 20.00s",
+                "[mov,mp4 @ 0x1] moov atom not found",
+                "the output was kept as /out/clip.mp4.suspect",
+            ]
+        );
+    }
+}
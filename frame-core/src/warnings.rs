@@ -0,0 +1,249 @@
+//! Classification of notable `FFmpeg` warnings among its routine output.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    events::ConversionEvent,
+    types::{ConversionWarningSeverity, WarningPayload},
+};
+
+/// Minimum spacing between repeated reports of one warning.
+pub const WARNING_REPEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lowercase stderr fragments worth surfacing, checked in order. Each
+/// fragment is one warning: lines matching the same fragment are counted as
+/// repeats.
+const CONVERSION_WARNING_PATTERNS: &[(ConversionWarningSeverity, &str)] = &[
+    (ConversionWarningSeverity::Error, "error while decoding"),
+    (ConversionWarningSeverity::Error, "invalid nal unit"),
+    (
+        ConversionWarningSeverity::Error,
+        "missing reference picture",
+    ),
+    (
+        ConversionWarningSeverity::Error,
+        "decode_slice_header error",
+    ),
+    (ConversionWarningSeverity::Error, "concealing"),
+    (ConversionWarningSeverity::Error, "corrupt"),
+    (ConversionWarningSeverity::Error, "invalid data found"),
+    (ConversionWarningSeverity::Warning, "non-monotonous dts"),
+    (ConversionWarningSeverity::Warning, "non-monotonic dts"),
+    (ConversionWarningSeverity::Warning, "invalid dts"),
+    (ConversionWarningSeverity::Warning, "timestamps are unset"),
+    (
+        ConversionWarningSeverity::Warning,
+        "queue input is backward in time",
+    ),
+    (ConversionWarningSeverity::Warning, "past duration"),
+    (ConversionWarningSeverity::Warning, "frames duplicated"),
+    (ConversionWarningSeverity::Warning, "guessed channel layout"),
+    (
+        ConversionWarningSeverity::Warning,
+        "deprecated pixel format",
+    ),
+    (
+        ConversionWarningSeverity::Warning,
+        "estimating duration from bitrate",
+    ),
+    (
+        ConversionWarningSeverity::Warning,
+        "too many packets buffered",
+    ),
+];
+
+/// Matches one stderr line against the warning table, returning its
+/// severity and the fragment that identifies the warning.
+#[must_use]
+pub fn classify_ffmpeg_warning(line: &str) -> Option<(ConversionWarningSeverity, &'static str)> {
+    let line = line.to_ascii_lowercase();
+    CONVERSION_WARNING_PATTERNS
+        .iter()
+        .find(|(_, pattern)| line.contains(pattern))
+        .copied()
+}
+
+#[derive(Debug)]
+struct TrackedWarning {
+    pattern: &'static str,
+    payload: WarningPayload,
+    reported_count: u32,
+    reported_at: Instant,
+}
+
+impl TrackedWarning {
+    fn report(&mut self, now: Instant) -> ConversionEvent {
+        self.reported_count = self.payload.count;
+        self.reported_at = now;
+        ConversionEvent::Warning(self.payload.clone())
+    }
+}
+
+/// De-duplicates the warnings of one task.
+///
+/// The first occurrence is reported at once and repeats at most every
+/// [`WARNING_REPEAT_INTERVAL`] with their running count. Callers must call
+/// [`Self::flush`] once the process exits so final counts are reported.
+#[derive(Debug, Default)]
+pub struct ConversionWarningTracker {
+    warnings: Vec<TrackedWarning>,
+}
+
+impl ConversionWarningTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Classifies one stderr line of task `id`, returning the warning event
+    /// to emit, if any.
+    pub fn push(&mut self, id: &str, line: &str, now: Instant) -> Option<ConversionEvent> {
+        let (severity, pattern) = classify_ffmpeg_warning(line)?;
+        if let Some(warning) = self
+            .warnings
+            .iter_mut()
+            .find(|warning| warning.pattern == pattern)
+        {
+            warning.payload.count = warning.payload.count.saturating_add(1);
+            return (now.duration_since(warning.reported_at) >= WARNING_REPEAT_INTERVAL)
+                .then(|| warning.report(now));
+        }
+
+        let mut warning = TrackedWarning {
+            pattern,
+            payload: WarningPayload {
+                id: id.to_string(),
+                severity,
+                message: line.trim().to_string(),
+                count: 1,
+            },
+            reported_count: 0,
+            reported_at: now,
+        };
+        let event = warning.report(now);
+        self.warnings.push(warning);
+        Some(event)
+    }
+
+    /// Reports warnings whose latest count has not been emitted yet.
+    pub fn flush(&mut self, now: Instant, emit: &mut impl FnMut(ConversionEvent)) {
+        for warning in &mut self.warnings {
+            if warning.payload.count > warning.reported_count {
+                emit(warning.report(now));
+            }
+        }
+    }
+
+    /// Number of distinct warnings seen so far.
+    #[must_use]
+    pub const fn warning_count(&self) -> usize {
+        self.warnings.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(event: Option<ConversionEvent>) -> WarningPayload {
+        match event {
+            Some(ConversionEvent::Warning(payload)) => payload,
+            event => panic!("expected a warning event, got {event:?}"),
+        }
+    }
+
+    #[test]
+    fn classify_ffmpeg_warning_recognizes_common_warnings() {
+        for (line, severity) in [
+            (
+                "[mp4 @ 0x6000] Non-monotonous DTS in output stream 0:1; previous: 1024, \
+                 current: 1000; changing to 1025. This may result in incorrect timestamps.",
+                ConversionWarningSeverity::Warning,
+            ),
+            (
+                "[vist#0:0/h264 @ 0x6000] Past duration 0.999 too large",
+                ConversionWarningSeverity::Warning,
+            ),
+            (
+                "[aist#0:1/pcm_s16le @ 0x6000] Guessed Channel Layout: stereo",
+                ConversionWarningSeverity::Warning,
+            ),
+            (
+                "[h264 @ 0x6000] concealing 120 DC, 120 AC, 120 MV errors in P frame",
+                ConversionWarningSeverity::Error,
+            ),
+        ] {
+            assert_eq!(
+                classify_ffmpeg_warning(line).map(|(severity, _)| severity),
+                Some(severity),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn classify_ffmpeg_warning_ignores_routine_output() {
+        for line in [
+            "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'source.mov':",
+            "Stream #0:0 -> #0:0 (h264 (native) -> hevc (libx265))",
+            "frame=  120 fps= 60 q=28.0 size=  2048kB time=00:00:04.00",
+            "[libx265 @ 0x6000] frame I:      1, Avg QP:22.90  kb/s: 8042.16",
+        ] {
+            assert_eq!(classify_ffmpeg_warning(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn tracker_reports_repeats_once_per_interval_with_their_count() {
+        let mut tracker = ConversionWarningTracker::new();
+        let start = Instant::now();
+        let line = |previous: u32| {
+            format!("Non-monotonous DTS in output stream 0:1; previous: {previous}, current: 0")
+        };
+
+        let first = warning(tracker.push("task-1", &line(1), start));
+        let suppressed = tracker.push("task-1", &line(2), start + WARNING_REPEAT_INTERVAL / 2);
+        let repeated = warning(tracker.push("task-1", &line(3), start + WARNING_REPEAT_INTERVAL));
+        let _ = tracker.push("task-1", &line(4), start + WARNING_REPEAT_INTERVAL);
+        let ignored = tracker.push("task-1", "frame=  120 fps= 60", start);
+        let mut flushed = Vec::new();
+        tracker.flush(start + WARNING_REPEAT_INTERVAL, &mut |event| {
+            flushed.push(event);
+        });
+
+        assert_eq!(first.count, 1);
+        assert_eq!(first.message, line(1));
+        assert_eq!(suppressed, None);
+        assert_eq!(repeated.count, 3);
+        assert_eq!(repeated.message, line(1));
+        assert_eq!(ignored, None);
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(warning(flushed.pop()).count, 4);
+        assert_eq!(tracker.warning_count(), 1);
+    }
+
+    #[test]
+    fn tracker_counts_distinct_warnings_separately() {
+        let mut tracker = ConversionWarningTracker::new();
+        let now = Instant::now();
+
+        let dts = warning(tracker.push("task-1", "Non-monotonous DTS in output", now));
+        let layout = warning(tracker.push("task-1", "Guessed Channel Layout: 5.1", now));
+        let mut flushed = Vec::new();
+        tracker.flush(now, &mut |event| flushed.push(event));
+
+        assert_eq!(dts.count, 1);
+        assert_eq!(layout.count, 1);
+        assert!(flushed.is_empty());
+        assert_eq!(tracker.warning_count(), 2);
+        assert_eq!(
+            serde_json::to_value(layout).expect("warning should serialize"),
+            serde_json::json!({
+                "id": "task-1",
+                "severity": "warning",
+                "message": "Guessed Channel Layout: 5.1",
+                "count": 1
+            })
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Snapshot tests of the `FFmpeg` arguments built for representative
+//! conversions.
+//!
+//! Each case is stored under `tests/snapshots/ffmpeg_args/` with one
+//! argument per line, so any change to the generated command shows up as a
+//! reviewable diff. Run with `UPDATE_ARGS_SNAPSHOTS=1` to rewrite the files
+//! after an intended change.
+
+use std::{env, fs, path::PathBuf};
+
+use frame_core::args::build_ffmpeg_args;
+use frame_core::types::{
+    AudioTrack, ConversionConfig, MetadataMode, OverwritePolicy, ProbeMetadata, SeekMode,
+    SubtitleFormat, SubtitleTrack, TrimSegment,
+};
+
+fn base_config(container: &str, video_codec: &str, audio_codec: &str) -> ConversionConfig {
+    serde_json::from_value(serde_json::json!({
+        "container": container,
+        "videoCodec": video_codec,
+        "videoBitrateMode": "crf",
+        "videoBitrate": "5000",
+        "audioCodec": audio_codec,
+        "audioBitrate": "128",
+        "audioChannels": "original",
+        "selectedAudioTracks": [],
+        "selectedSubtitleTracks": [],
+        "resolution": "original",
+        "scalingAlgorithm": "bicubic",
+        "fps": "original",
+        "crf": 23,
+        "preset": "medium"
+    }))
+    .expect("config fixture should deserialize")
+}
+
+fn movie_probe() -> ProbeMetadata {
+    ProbeMetadata {
+        media_kind: "video".to_string(),
+        duration: Some("120.0".to_string()),
+        video_codec: Some("h264".to_string()),
+        width: Some(1920),
+        height: Some(1080),
+        pixel_format: Some("yuv420p".to_string()),
+        audio_tracks: vec![
+            AudioTrack {
+                index: 1,
+                codec: "aac".to_string(),
+                channels: "2".to_string(),
+                ..AudioTrack::default()
+            },
+            AudioTrack {
+                index: 2,
+                codec: "ac3".to_string(),
+                channels: "6".to_string(),
+                ..AudioTrack::default()
+            },
+        ],
+        subtitle_tracks: vec![SubtitleTrack {
+            index: 3,
+            codec: "subrip".to_string(),
+            format: Some(SubtitleFormat::Text),
+            ..SubtitleTrack::default()
+        }],
+        ..ProbeMetadata::default()
+    }
+}
+
+fn segment(start: &str, end: &str) -> TrimSegment {
+    TrimSegment {
+        start: start.to_string(),
+        end: end.to_string(),
+    }
+}
+
+/// Name, output path, config and probe of every snapshot case.
+fn cases() -> Vec<(&'static str, &'static str, ConversionConfig, ProbeMetadata)> {
+    let mut copy = base_config("mkv", "libx264", "aac");
+    copy.processing_mode = "copy".to_string();
+
+    let mut audio_only = base_config("mp3", "libx264", "libmp3lame");
+    audio_only.start_time = Some("10".to_string());
+    audio_only.end_time = Some("40".to_string());
+    audio_only.audio_volume = 80.0;
+
+    let mut gif = base_config("gif", "gif", "aac");
+    gif.fps = "12".to_string();
+    gif.gif_colors = 128;
+    gif.gif_dither = "bayer".to_string();
+    gif.gif_loop = 1;
+
+    let mut sequence = base_config("png", "png", "aac");
+    sequence.image_sequence_output = true;
+    sequence.fps = "5".to_string();
+
+    let mut segments = base_config("webm", "libvpx-vp9", "libopus");
+    segments.segments = vec![segment("0:05", "0:15"), segment("1:00", "1:20")];
+
+    let mut clean = base_config("mp4", "libx265", "aac");
+    clean.metadata.mode = MetadataMode::Clean;
+    clean.seek_mode = SeekMode::Hybrid;
+    clean.start_time = Some("30".to_string());
+    clean.end_time = Some("45".to_string());
+    clean.overwrite_policy = OverwritePolicy::Overwrite;
+    clean.selected_audio_tracks = vec![2];
+
+    let mut replace = base_config("mov", "libx264", "aac");
+    replace.metadata.mode = MetadataMode::Replace;
+    replace.metadata.title = Some("Holiday".to_string());
+    replace.metadata.artist = Some("Frame".to_string());
+    replace.video_bitrate_mode = "bitrate".to_string();
+    replace.video_bitrate = "8000".to_string();
+    replace.resolution = "720p".to_string();
+
+    vec![
+        (
+            "mp4_h264_crf",
+            "/out/movie.mp4",
+            base_config("mp4", "libx264", "aac"),
+            movie_probe(),
+        ),
+        ("mkv_stream_copy", "/out/movie.mkv", copy, movie_probe()),
+        (
+            "mp3_audio_only_trimmed",
+            "/out/movie.mp3",
+            audio_only,
+            movie_probe(),
+        ),
+        ("gif_palette", "/out/movie.gif", gif, movie_probe()),
+        (
+            "png_still",
+            "/out/movie.png",
+            base_config("png", "png", "aac"),
+            movie_probe(),
+        ),
+        (
+            "png_sequence",
+            "/out/movie_%05d.png",
+            sequence,
+            movie_probe(),
+        ),
+        (
+            "webm_vp9_segments",
+            "/out/movie.webm",
+            segments,
+            movie_probe(),
+        ),
+        (
+            "mp4_hevc_clean_hybrid_seek",
+            "/out/movie.mp4.part",
+            clean,
+            movie_probe(),
+        ),
+        (
+            "mov_replaced_metadata_bitrate",
+            "/out/movie.mov",
+            replace,
+            movie_probe(),
+        ),
+    ]
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots/ffmpeg_args")
+        .join(format!("{name}.args"))
+}
+
+#[test]
+fn ffmpeg_args_match_their_snapshots() {
+    let update = env::var_os("UPDATE_ARGS_SNAPSHOTS").is_some();
+    let mut mismatches = Vec::new();
+
+    for (name, output, config, probe) in cases() {
+        let args = build_ffmpeg_args("/in/movie.mkv", output, &config, &probe)
+            .unwrap_or_else(|error| panic!("{name}: arguments should build: {error}"));
+        let actual = format!("{}\n", args.join("\n"));
+        let path = snapshot_path(name);
+        if update {
+            fs::create_dir_all(path.parent().expect("snapshots have a directory"))
+                .expect("snapshot directory should be created");
+            fs::write(&path, &actual).expect("snapshot should be written");
+            continue;
+        }
+        match fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => mismatches.push(format!(
+                "{name}:\n--- expected\n{expected}+++ actual\n{actual}"
+            )),
+            Err(error) => mismatches.push(format!("{name}: {} ({error})", path.display())),
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "FFmpeg arguments changed; review them and rerun with UPDATE_ARGS_SNAPSHOTS=1 if \
+         intended:\n{}",
+        mismatches.join("\n")
+    );
+}
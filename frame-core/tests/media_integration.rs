@@ -13,7 +13,8 @@ use frame_core::{
     preview::{PreviewFfmpegOptions, build_ffmpeg_preview_args},
     probe::{ffprobe_json_args, parse_ffprobe_stdout},
     types::{
-        ConversionConfig, CropConfig, MetadataConfig, MetadataMode, OverlayConfig, ProbeMetadata,
+        ConversionConfig, CropConfig, MetadataConfig, MetadataMode, OverlayConfig, OverwritePolicy,
+        ProbeMetadata, SeekMode,
     },
 };
 
@@ -335,6 +336,41 @@ fn gif_output_should_write_palette_gif_video() -> TestResult {
     Ok(())
 }
 
+#[test]
+#[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
+fn animated_gif_round_trips_should_preserve_timing() -> TestResult {
+    let tools = Toolchain::discover()?;
+    let sandbox = Sandbox::new("animated_gif_round_trips")?;
+    let input = sandbox.path("source.gif");
+    let mp4 = sandbox.path("output.mp4");
+    let webm = sandbox.path("output.webm");
+
+    generate_animated_gif_source(&tools, &input)?;
+    let source = probe_media(&tools, &input)?;
+    assert!(source.animated);
+    let source_duration = duration_seconds(&source)?;
+    convert(&tools, &input, &mp4, &video_config("mp4", "libx264", "aac"))?;
+    convert(
+        &tools,
+        &input,
+        &webm,
+        &video_config("webm", "vp9", "libopus"),
+    )?;
+
+    let mp4_metadata = probe_media(&tools, &mp4)?;
+    assert_eq!(mp4_metadata.pixel_format.as_deref(), Some("yuv420p"));
+    assert_eq!(mp4_metadata.width, Some(76));
+    assert_eq!(mp4_metadata.height, Some(50));
+    for metadata in [mp4_metadata, probe_media(&tools, &webm)?] {
+        let duration = duration_seconds(&metadata)?;
+        assert!(
+            (duration - source_duration).abs() < 0.15,
+            "output lasts {duration}s, source {source_duration}s"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 #[ignore = "requires FFmpeg/FFprobe; run with --ignored"]
 fn odd_yuv420p_reencode_should_pad_to_even_dimensions() -> TestResult {
@@ -909,11 +945,14 @@ fn base_config(container: &str, video_codec: &str) -> ConversionConfig {
         custom_height: None,
         scaling_algorithm: "bicubic".to_string(),
         fps: "original".to_string(),
+        input_framerate: None,
         crf: 28,
         quality: 60,
         preset: "ultrafast".to_string(),
         start_time: None,
         end_time: None,
+        segments: Vec::new(),
+        remove_segments: Vec::new(),
         metadata: MetadataConfig::default(),
         rotation: "0".to_string(),
         flip_horizontal: false,
@@ -924,6 +963,7 @@ fn base_config(container: &str, video_codec: &str) -> ConversionConfig {
         nvenc_temporal_aq: false,
         videotoolbox_allow_sw: false,
         hw_decode: false,
+        hw_device: None,
         pixel_format: "auto".to_string(),
         image_jpeg_quality: 85,
         image_jpeg_huffman: "optimal".to_string(),
@@ -934,9 +974,21 @@ fn base_config(container: &str, video_codec: &str) -> ConversionConfig {
         image_png_compression: 9,
         image_png_prediction: "paeth".to_string(),
         image_tiff_compression: "packbits".to_string(),
+        image_avif_crf: 30,
+        image_target_max_kb: None,
         gif_colors: 256,
         gif_dither: "sierra2_4a".to_string(),
         gif_loop: 0,
+        overwrite_policy: OverwritePolicy::default(),
+        split: None,
+        split_by_chapters: false,
+        image_sequence_output: false,
+        smart_cut: false,
+        seek_mode: SeekMode::default(),
+        parallel_chunks: None,
+        verify_output: false,
+        extra_input_args: Vec::new(),
+        extra_output_args: Vec::new(),
     }
 }
 
@@ -1126,6 +1178,25 @@ fn generate_odd_h264_source(tools: &Toolchain, output: &Path) -> TestResult {
     )
 }
 
+fn generate_animated_gif_source(tools: &Toolchain, output: &Path) -> TestResult {
+    run_tool(
+        &tools.ffmpeg,
+        &args(&[
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-f",
+            "lavfi",
+            "-i",
+            "testsrc=size=75x49:rate=10:duration=2",
+            "-loop",
+            "0",
+            "-y",
+            &path_arg(output),
+        ]),
+    )
+}
+
 fn generate_audio_source(tools: &Toolchain, output: &Path) -> TestResult {
     run_tool(
         &tools.ffmpeg,